@@ -0,0 +1,154 @@
+//! Benchmarks of the webhook hot path: signature verification, payload
+//! parsing, scoring, and turning lockfiles into graph dependencies.
+//!
+//! Run with `cargo bench -p rsr-engine`, then `rsr bench` to compare against
+//! the stored baseline (see `rsr_engine::db::benchmarks`). Graph ingestion
+//! into the in-process graph needs `--features graphs-memory`.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rsr_engine::adapters::github::GitHubAdapter;
+use rsr_engine::adapters::signature::{sign_hmac, verify_hmac, HmacAlgorithm};
+use rsr_engine::adapters::{AdapterConfig, Headers, PlatformAdapter};
+use rsr_engine::{score, CertificationTier, CheckResult, ScoringPolicy};
+use std::hint::black_box;
+
+const SECRET: &[u8] = b"benchmark webhook secret";
+
+/// A GitHub push of `commits` commits, each touching a handful of files
+fn push_payload(commits: usize) -> Vec<u8> {
+    let commits: Vec<serde_json::Value> = (0..commits)
+        .map(|i| {
+            serde_json::json!({
+                "id": format!("{:040x}", i),
+                "message": format!("Change {}\n\nLonger description of the change", i),
+                "timestamp": "2026-01-01T00:00:00Z",
+                "author": { "name": "Dev", "email": "dev@example.com", "username": "dev" },
+                "added": [format!("src/new_{}.rs", i)],
+                "modified": ["src/lib.rs", "README.adoc"],
+                "removed": [],
+            })
+        })
+        .collect();
+
+    serde_json::to_vec(&serde_json::json!({
+        "ref": "refs/heads/main",
+        "before": "0".repeat(40),
+        "after": "f".repeat(40),
+        "commits": commits,
+        "pusher": { "name": "dev", "email": "dev@example.com" },
+        "repository": {
+            "name": "engine",
+            "owner": { "id": 1, "login": "hyperpolymath", "avatar_url": null },
+        },
+    }))
+    .expect("payload serializes")
+}
+
+fn bench_webhook(c: &mut Criterion) {
+    let payload = push_payload(20);
+    let signature = sign_hmac(HmacAlgorithm::Sha256, SECRET, &payload);
+    let adapter = GitHubAdapter::new(AdapterConfig {
+        webhook_secret: Some(String::from_utf8_lossy(SECRET).into_owned()),
+        ..Default::default()
+    });
+    let headers: Headers = [
+        ("x-github-event".to_string(), "push".to_string()),
+        ("x-hub-signature-256".to_string(), signature.clone()),
+    ]
+    .into_iter()
+    .collect();
+
+    let mut group = c.benchmark_group("webhook");
+    group.throughput(Throughput::Bytes(payload.len() as u64));
+    group.bench_function("hmac_sha256", |b| {
+        b.iter(|| verify_hmac(HmacAlgorithm::Sha256, SECRET, black_box(&payload), black_box(&signature)))
+    });
+    group.bench_function("verify_github", |b| {
+        b.iter(|| adapter.verify_webhook(black_box(&payload), &headers).expect("verifies"))
+    });
+    group.bench_function("parse_github_push", |b| {
+        b.iter(|| adapter.parse_webhook(black_box(&payload), &headers).expect("parses"))
+    });
+    group.finish();
+}
+
+fn bench_scoring(c: &mut Criterion) {
+    let tiers = [
+        CertificationTier::Bronze,
+        CertificationTier::Silver,
+        CertificationTier::Gold,
+        CertificationTier::Rhodium,
+    ];
+    let results: Vec<CheckResult> = (0..40)
+        .map(|i| CheckResult {
+            id: format!("check_{}", i),
+            name: format!("Check {}", i),
+            tier: tiers[i % tiers.len()],
+            passed: i % 7 != 0,
+            message: String::new(),
+            details: None,
+            findings: Vec::new(),
+        })
+        .collect();
+    let policy = ScoringPolicy::default();
+
+    c.bench_function("scoring/forty_checks", |b| b.iter(|| score(black_box(&results), &policy)));
+}
+
+/// A `Cargo.lock` of the workspace and `packages` registry crates it depends on
+fn cargo_lock(packages: usize) -> String {
+    let mut lock = String::from("version = 4\n\n[[package]]\nname = \"app\"\nversion = \"0.1.0\"\ndependencies = [\n");
+    for i in 0..packages {
+        lock.push_str(&format!(" \"crate-{}\",\n", i));
+    }
+    lock.push_str("]\n");
+    for i in 0..packages {
+        lock.push_str(&format!(
+            "\n[[package]]\nname = \"crate-{}\"\nversion = \"1.{}.0\"\n\
+             source = \"registry+https://github.com/rust-lang/crates.io-index\"\n\
+             checksum = \"{:064x}\"\n",
+            i, i, i
+        ));
+    }
+    lock
+}
+
+fn bench_ingestion(c: &mut Criterion) {
+    let lock = cargo_lock(300);
+
+    let mut group = c.benchmark_group("ingestion");
+    group.throughput(Throughput::Elements(300));
+    group.bench_function("parse_cargo_lock", |b| {
+        b.iter(|| rsr_engine::deps::parse(|name| (name == "Cargo.lock").then(|| lock.clone())))
+    });
+
+    #[cfg(feature = "graphs-memory")]
+    {
+        use criterion::BatchSize;
+        use rsr_engine::db::graphs::GraphStore;
+        use rsr_engine::db::graphs_memory::MemoryGraph;
+
+        let runtime = tokio::runtime::Runtime::new().expect("runtime starts");
+        let dependencies = rsr_engine::deps::parse(|name| (name == "Cargo.lock").then(|| lock.clone()));
+        group.bench_function("memory_graph_snapshot", |b| {
+            b.to_async(&runtime).iter_batched(
+                || (MemoryGraph::new(), dependencies.clone()),
+                |(graph, dependencies)| async move {
+                    let repo_key = graph
+                        .register_repository("github", "hyperpolymath", "engine")
+                        .await
+                        .expect("registers");
+                    graph
+                        .upsert_dependency_snapshot(&repo_key, dependencies)
+                        .await
+                        .expect("ingests")
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_webhook, bench_scoring, bench_ingestion);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+//! Lockfiles and manifests, one file at a time
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use rsr_engine::deps::{parse_file, LOCKFILES, MANIFESTS};
+
+#[derive(Debug, Arbitrary)]
+struct File<'a> {
+    name: u8,
+    contents: &'a str,
+}
+
+fuzz_target!(|file: File| {
+    let names: Vec<&str> = LOCKFILES.iter().chain(MANIFESTS.iter()).copied().collect();
+    let name = names[file.name as usize % names.len()];
+
+    let _ = parse_file(name, file.contents);
+});
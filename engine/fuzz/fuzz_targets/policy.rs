@@ -0,0 +1,10 @@
+//! Engine configuration files: policies, rulepacks, hierarchies and the rest
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rsr_engine::config::EngineConfig;
+
+fuzz_target!(|content: &str| {
+    let _ = EngineConfig::parse(content);
+});
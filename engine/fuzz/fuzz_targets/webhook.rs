@@ -0,0 +1,34 @@
+//! Webhook payloads and headers for every built-in platform
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use rsr_engine::adapters::{parse_webhook_bytes, AdapterFactory, Headers};
+
+#[derive(Debug, Arbitrary)]
+struct Delivery<'a> {
+    platform: u8,
+    /// Event type header, e.g. `push`
+    event: &'a str,
+    payload: &'a [u8],
+}
+
+fuzz_target!(|delivery: Delivery| {
+    let platforms = AdapterFactory::supported_platforms();
+    let platform = platforms[delivery.platform as usize % platforms.len()];
+
+    // Each adapter reads its own event header; set them all
+    let headers: Headers = [
+        "x-github-event",
+        "x-gitlab-event",
+        "x-event-key",
+        "x-gitea-event",
+        "x-amz-sns-message-type",
+    ]
+    .into_iter()
+    .map(|name| (name.to_string(), delivery.event.to_string()))
+    .collect();
+
+    let _ = parse_webhook_bytes(platform, delivery.payload, &headers);
+});
@@ -0,0 +1,134 @@
+-- Documents schema for the Postgres store, equivalent to SurrealDB migrations 1-9
+
+CREATE TABLE IF NOT EXISTS repository (
+    platform TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    adapter TEXT,
+    active BOOLEAN NOT NULL DEFAULT true,
+    registered_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_scanned_at TIMESTAMPTZ,
+    deactivated_at TIMESTAMPTZ,
+    deleted_at TIMESTAMPTZ,
+    PRIMARY KEY (platform, owner, name)
+);
+
+-- Content-addressed, hash-chained reports
+CREATE TABLE IF NOT EXISTS compliance_report (
+    digest TEXT PRIMARY KEY,
+    previous_digest TEXT UNIQUE,
+    platform TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    repo TEXT NOT NULL,
+    tier TEXT NOT NULL,
+    score REAL NOT NULL,
+    checks JSONB NOT NULL,
+    standard JSONB NOT NULL DEFAULT '[]',
+    canonical JSONB,
+    evidence JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL,
+    deleted_at TIMESTAMPTZ,
+    badge_issued_at TIMESTAMPTZ
+);
+CREATE INDEX IF NOT EXISTS report_time_idx ON compliance_report (platform, owner, repo, created_at);
+CREATE INDEX IF NOT EXISTS report_owner_time_idx ON compliance_report (platform, owner, created_at);
+
+-- Reports can't be removed or have their content changed, and a pruned
+-- report stays pruned. Transfers still move them to the repository's new
+-- owner/name.
+CREATE OR REPLACE FUNCTION report_immutable() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        RAISE EXCEPTION 'chained compliance reports are immutable';
+    END IF;
+    IF (OLD.tier, OLD.score, OLD.checks, OLD.standard, OLD.canonical, OLD.evidence, OLD.created_at, OLD.digest, OLD.previous_digest)
+        IS DISTINCT FROM
+        (NEW.tier, NEW.score, NEW.checks, NEW.standard, NEW.canonical, NEW.evidence, NEW.created_at, NEW.digest, NEW.previous_digest)
+    THEN
+        RAISE EXCEPTION 'chained compliance reports are immutable';
+    END IF;
+    IF OLD.deleted_at IS NOT NULL AND NEW.deleted_at IS DISTINCT FROM OLD.deleted_at THEN
+        RAISE EXCEPTION 'pruned compliance reports stay pruned';
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS report_immutable ON compliance_report;
+CREATE TRIGGER report_immutable BEFORE UPDATE OR DELETE ON compliance_report
+    FOR EACH ROW EXECUTE FUNCTION report_immutable();
+
+-- Redirects left behind by repository transfers and renames
+CREATE TABLE IF NOT EXISTS repo_redirect (
+    platform TEXT NOT NULL,
+    from_owner TEXT NOT NULL,
+    from_repo TEXT NOT NULL,
+    to_owner TEXT NOT NULL,
+    to_repo TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (platform, from_owner, from_repo)
+);
+
+-- Raw deliveries kept for replay
+CREATE TABLE IF NOT EXISTS webhook_event (
+    id BIGSERIAL PRIMARY KEY,
+    platform TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    delivery_id TEXT,
+    headers JSONB NOT NULL,
+    payload TEXT NOT NULL,
+    verification TEXT NOT NULL,
+    processed BOOLEAN NOT NULL DEFAULT false,
+    error TEXT,
+    received_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS webhook_status_idx ON webhook_event (processed, received_at);
+
+-- Annotations on reports, kept apart from the immutable reports themselves
+CREATE TABLE IF NOT EXISTS report_annotation (
+    id BIGSERIAL PRIMARY KEY,
+    platform TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    repo TEXT NOT NULL,
+    report_at TIMESTAMPTZ NOT NULL,
+    kind TEXT NOT NULL,
+    check_id TEXT,
+    message TEXT NOT NULL,
+    reference TEXT,
+    author TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS annotation_report_idx ON report_annotation (platform, owner, repo, report_at);
+
+-- Append-only audit trail of status posts, configuration changes, badge issuance and manual overrides
+CREATE TABLE IF NOT EXISTS audit_event (
+    id BIGSERIAL PRIMARY KEY,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    target TEXT NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS audit_time_idx ON audit_event (created_at);
+CREATE INDEX IF NOT EXISTS audit_target_idx ON audit_event (target, created_at);
+CREATE INDEX IF NOT EXISTS audit_actor_idx ON audit_event (actor, created_at);
+
+CREATE OR REPLACE FUNCTION audit_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit events are append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_append_only ON audit_event;
+CREATE TRIGGER audit_append_only BEFORE UPDATE OR DELETE ON audit_event
+    FOR EACH ROW EXECUTE FUNCTION audit_append_only();
+
+-- Cached owner-level compliance summaries
+CREATE TABLE IF NOT EXISTS org_summary (
+    platform TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    summary JSONB NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (platform, owner)
+);
@@ -0,0 +1,5 @@
+-- Quarantine for verified webhooks whose payload failed to parse
+
+ALTER TABLE webhook_event ADD COLUMN IF NOT EXISTS quarantined_by TEXT;
+CREATE INDEX IF NOT EXISTS webhook_quarantine_idx ON webhook_event (quarantined_by, received_at)
+    WHERE quarantined_by IS NOT NULL;
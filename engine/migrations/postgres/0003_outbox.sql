@@ -0,0 +1,11 @@
+-- Jobs written alongside the records they process, until relayed onto their queue
+
+CREATE TABLE IF NOT EXISTS outbox (
+    job_id TEXT PRIMARY KEY,
+    queue TEXT NOT NULL,
+    job TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS outbox_created_idx ON outbox (created_at);
@@ -0,0 +1,3 @@
+-- Warnings from strict parsing of a webhook's payload
+
+ALTER TABLE webhook_event ADD COLUMN IF NOT EXISTS parse_warnings JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,135 @@
+-- Documents schema for the embedded SQLite store, equivalent to SurrealDB
+-- migrations 1-10. Timestamps are RFC 3339 text in UTC with nanoseconds, so
+-- they sort as text.
+
+CREATE TABLE IF NOT EXISTS repository (
+    platform TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    name TEXT NOT NULL,
+    adapter TEXT,
+    active INTEGER NOT NULL DEFAULT 1,
+    registered_at TEXT NOT NULL,
+    last_scanned_at TEXT,
+    deactivated_at TEXT,
+    deleted_at TEXT,
+    PRIMARY KEY (platform, owner, name)
+);
+
+-- Content-addressed, hash-chained reports
+CREATE TABLE IF NOT EXISTS compliance_report (
+    digest TEXT PRIMARY KEY,
+    previous_digest TEXT UNIQUE,
+    platform TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    repo TEXT NOT NULL,
+    tier TEXT NOT NULL,
+    score REAL NOT NULL,
+    checks TEXT NOT NULL,
+    standard TEXT NOT NULL DEFAULT '[]',
+    canonical TEXT,
+    evidence TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL,
+    deleted_at TEXT,
+    badge_issued_at TEXT
+);
+CREATE INDEX IF NOT EXISTS report_time_idx ON compliance_report (platform, owner, repo, created_at);
+CREATE INDEX IF NOT EXISTS report_owner_time_idx ON compliance_report (platform, owner, created_at);
+
+-- Reports can't be removed or have their content changed, and a pruned
+-- report stays pruned. Transfers still move them to the repository's new
+-- owner/name.
+CREATE TRIGGER IF NOT EXISTS report_immutable_delete BEFORE DELETE ON compliance_report
+BEGIN
+    SELECT RAISE(ABORT, 'chained compliance reports are immutable');
+END;
+
+CREATE TRIGGER IF NOT EXISTS report_immutable_update BEFORE UPDATE ON compliance_report
+WHEN OLD.tier IS NOT NEW.tier OR OLD.score IS NOT NEW.score OR OLD.checks IS NOT NEW.checks
+    OR OLD.standard IS NOT NEW.standard OR OLD.canonical IS NOT NEW.canonical OR OLD.evidence IS NOT NEW.evidence
+    OR OLD.created_at IS NOT NEW.created_at OR OLD.digest IS NOT NEW.digest
+    OR OLD.previous_digest IS NOT NEW.previous_digest
+BEGIN
+    SELECT RAISE(ABORT, 'chained compliance reports are immutable');
+END;
+
+CREATE TRIGGER IF NOT EXISTS report_tombstone BEFORE UPDATE ON compliance_report
+WHEN OLD.deleted_at IS NOT NULL AND NEW.deleted_at IS NOT OLD.deleted_at
+BEGIN
+    SELECT RAISE(ABORT, 'pruned compliance reports stay pruned');
+END;
+
+-- Redirects left behind by repository transfers and renames
+CREATE TABLE IF NOT EXISTS repo_redirect (
+    platform TEXT NOT NULL,
+    from_owner TEXT NOT NULL,
+    from_repo TEXT NOT NULL,
+    to_owner TEXT NOT NULL,
+    to_repo TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (platform, from_owner, from_repo)
+);
+
+-- Raw deliveries kept for replay, and quarantined when they fail to parse
+CREATE TABLE IF NOT EXISTS webhook_event (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    platform TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    delivery_id TEXT,
+    headers TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    verification TEXT NOT NULL,
+    processed INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    quarantined_by TEXT,
+    received_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS webhook_status_idx ON webhook_event (processed, received_at);
+CREATE INDEX IF NOT EXISTS webhook_quarantine_idx ON webhook_event (quarantined_by, received_at);
+
+-- Annotations on reports, kept apart from the immutable reports themselves
+CREATE TABLE IF NOT EXISTS report_annotation (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    platform TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    repo TEXT NOT NULL,
+    report_at TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    check_id TEXT,
+    message TEXT NOT NULL,
+    reference TEXT,
+    author TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS annotation_report_idx ON report_annotation (platform, owner, repo, report_at);
+
+-- Append-only audit trail of status posts, configuration changes, badge issuance and manual overrides
+CREATE TABLE IF NOT EXISTS audit_event (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    target TEXT NOT NULL,
+    details TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS audit_time_idx ON audit_event (created_at);
+CREATE INDEX IF NOT EXISTS audit_target_idx ON audit_event (target, created_at);
+CREATE INDEX IF NOT EXISTS audit_actor_idx ON audit_event (actor, created_at);
+
+CREATE TRIGGER IF NOT EXISTS audit_no_update BEFORE UPDATE ON audit_event
+BEGIN
+    SELECT RAISE(ABORT, 'audit events are append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_no_delete BEFORE DELETE ON audit_event
+BEGIN
+    SELECT RAISE(ABORT, 'audit events are append-only');
+END;
+
+-- Cached owner-level compliance summaries
+CREATE TABLE IF NOT EXISTS org_summary (
+    platform TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    computed_at TEXT NOT NULL,
+    PRIMARY KEY (platform, owner)
+);
@@ -0,0 +1,3 @@
+-- Warnings from strict parsing of a webhook's payload
+
+ALTER TABLE webhook_event ADD COLUMN parse_warnings TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,13 @@
+-- Latest completed CI run of each workflow on each branch, from webhooks
+
+CREATE TABLE IF NOT EXISTS ci_run (
+    platform TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    repo TEXT NOT NULL,
+    branch TEXT NOT NULL,
+    workflow TEXT NOT NULL,
+    commit_sha TEXT NOT NULL,
+    conclusion TEXT NOT NULL,
+    completed_at TEXT NOT NULL,
+    PRIMARY KEY (platform, owner, repo, branch, workflow)
+);
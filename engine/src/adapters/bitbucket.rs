@@ -2,6 +2,8 @@
 //!
 //! Supports Bitbucket Cloud (bitbucket.org).
 
+use super::http::{HttpLayer, SendVia};
+use super::signature::{verify_hmac, HmacAlgorithm};
 use super::{AdapterConfig, Headers, PlatformAdapter, RepoMetadata};
 use crate::events::RepoEvent;
 use crate::{ComplianceStatus, RepoRef, Result, RsrError};
@@ -12,6 +14,7 @@
 pub struct BitbucketAdapter {
     config: AdapterConfig,
     client: reqwest::Client,
+    http: HttpLayer,
     api_url: String,
 }
 
@@ -19,9 +22,12 @@ impl BitbucketAdapter {
     pub fn new(config: AdapterConfig) -> Self {
         let api_url = config.api_url.clone().unwrap_or_else(|| DEFAULT_API_URL.to_string());
 
+        let http = HttpLayer::new("bitbucket", config.retry.clone()).with_etag_cache(config.etag_cache.clone());
+
         Self {
             config,
             client: reqwest::Client::new(),
+            http,
             api_url,
         }
     }
@@ -33,16 +39,24 @@ fn platform_id(&self) -> &'static str {
         "bitbucket"
     }
 
-    fn verify_webhook(&self, _payload: &[u8], headers: &Headers) -> Result<bool> {
-        // Bitbucket Cloud uses IP allowlisting or webhook signatures
-        // For webhook signatures, check X-Hub-Signature header (HMAC-SHA256)
-        if let Some(ref _secret) = self.config.webhook_secret {
-            // TODO: Implement proper signature verification
-            if headers.contains_key("x-hub-signature") {
-                tracing::warn!("Bitbucket webhook signature verification not fully implemented");
-            }
+    fn verify_webhook(&self, payload: &[u8], headers: &Headers) -> Result<bool> {
+        let Some(ref secret) = self.config.webhook_secret else {
+            return self.config.unsigned_webhook(self.platform_id());
+        };
+
+        // Bitbucket signs with the webhook secret: X-Hub-Signature: sha256=<hex>
+        let Some(signature) = headers.get("x-hub-signature") else {
+            return Err(RsrError::WebhookVerification);
+        };
+        if !signature.starts_with(HmacAlgorithm::Sha256.prefix()) {
+            return Err(RsrError::WebhookVerification);
         }
-        Ok(true)
+
+        Ok(verify_hmac(HmacAlgorithm::Sha256, secret.as_bytes(), payload, signature))
+    }
+
+    fn delivery_id(&self, headers: &Headers) -> Option<String> {
+        headers.get("x-request-uuid").cloned()
     }
 
     fn parse_webhook(&self, payload: &[u8], headers: &Headers) -> Result<RepoEvent> {
@@ -87,7 +101,7 @@ async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &Complianc
             .post(&url)
             .header("Authorization", format!("Bearer {}", token))
             .json(&body)
-            .send()
+            .send_via(&self.http)
             .await?;
 
         if !response.status().is_success() {
@@ -112,17 +126,21 @@ async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
         let response = self.client
             .get(&url)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
+            .fetch_via(&self.http)
             .await?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
+        if response.status == reqwest::StatusCode::NOT_FOUND {
             return Err(RsrError::RepoNotFound {
                 owner: repo.owner.clone(),
                 repo: repo.repo.clone(),
             });
         }
 
-        Ok(response.bytes().await?.to_vec())
+        if !response.status.is_success() {
+            return Err(RsrError::Platform(format!("Failed to fetch {} ({}): {}", path, response.status, response.text())));
+        }
+
+        Ok(response.body)
     }
 
     async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<String>> {
@@ -140,10 +158,10 @@ async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<Str
         let response = self.client
             .get(&url)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
+            .fetch_via(&self.http)
             .await?;
 
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = response.json()?;
 
         let files: Vec<String> = json["values"]
             .as_array()
@@ -170,10 +188,10 @@ async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
         let response = self.client
             .get(&url)
             .header("Authorization", format!("Bearer {}", token))
-            .send()
+            .fetch_via(&self.http)
             .await?;
 
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = response.json()?;
 
         Ok(RepoMetadata {
             default_branch: json["mainbranch"]["name"].as_str().unwrap_or("main").to_string(),
@@ -187,6 +205,8 @@ async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
             open_issues_count: 0, // Would need separate API call
             stargazers_count: 0, // Bitbucket doesn't show stars
             forks_count: json["forks_count"].as_u64().unwrap_or(0) as u32,
+            upstream: json["parent"]["full_name"].as_str().map(String::from),
+            archived: false,
             license: None,
             topics: Vec::new(), // Bitbucket uses "project" instead of topics
             last_push: json["updated_on"]
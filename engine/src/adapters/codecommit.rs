@@ -0,0 +1,682 @@
+//! AWS CodeCommit platform adapter
+//!
+//! CodeCommit has no webhooks. Repository and pull request state changes are
+//! routed through EventBridge, delivered either by an API destination or an
+//! SNS HTTPS subscription. Both can only prove authenticity with a shared
+//! secret: set an `X-Rsr-Webhook-Token` header on the API destination
+//! connection, or embed basic-auth credentials (`https://rsr:<secret>@host/...`)
+//! in the SNS subscription URL.
+//!
+//! API calls use SigV4 with credentials from the standard `AWS_ACCESS_KEY_ID`,
+//! `AWS_SECRET_ACCESS_KEY`, `AWS_SESSION_TOKEN` and `AWS_REGION` variables.
+//! Repositories are addressed as `<account-id>/<repository-name>`.
+
+use super::http::{HttpLayer, SendVia};
+use super::signature::constant_time_eq;
+use super::{decode_payload, AdapterConfig, Headers, PlatformAdapter, RepoMetadata};
+use crate::events::*;
+use crate::{CertificationTier, ComplianceStatus, RepoRef, Result, RsrError};
+use async_trait::async_trait;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// JSON protocol version of the CodeCommit API
+const TARGET_PREFIX: &str = "CodeCommit_20150413";
+
+const CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+
+/// Static AWS credentials
+struct AwsCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    fn from_env() -> Option<Self> {
+        Some(Self {
+            access_key_id: std::env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret_access_key: std::env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+}
+
+pub struct CodeCommitAdapter {
+    config: AdapterConfig,
+    client: reqwest::Client,
+    http: HttpLayer,
+    credentials: Option<AwsCredentials>,
+    region: String,
+    api_url: String,
+}
+
+impl CodeCommitAdapter {
+    pub fn new(config: AdapterConfig) -> Self {
+        let region = std::env::var("AWS_REGION")
+            .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+            .unwrap_or_else(|_| DEFAULT_REGION.to_string());
+
+        let api_url = config
+            .api_url
+            .clone()
+            .unwrap_or_else(|| format!("https://codecommit.{}.amazonaws.com", region));
+
+        let http = HttpLayer::new("codecommit", config.retry.clone());
+
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            http,
+            credentials: AwsCredentials::from_env(),
+            region,
+            api_url,
+        }
+    }
+
+    /// Call a CodeCommit API operation, e.g. `GetFile`
+    async fn call(&self, operation: &str, body: serde_json::Value) -> Result<serde_json::Value> {
+        let Some(ref credentials) = self.credentials else {
+            return Err(RsrError::Config("AWS credentials required".to_string()));
+        };
+
+        let url = reqwest::Url::parse(&self.api_url)
+            .map_err(|e| RsrError::Config(format!("Invalid CodeCommit endpoint: {}", e)))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| RsrError::Config("CodeCommit endpoint has no host".to_string()))?;
+
+        let target = format!("{}.{}", TARGET_PREFIX, operation);
+        let payload = serde_json::to_vec(&body)?;
+        let now = chrono::Utc::now();
+        let authorization = sign_v4(credentials, &self.region, host, &target, &payload, now);
+
+        let mut request = self.client
+            .post(url.clone())
+            .header("Content-Type", CONTENT_TYPE)
+            .header("X-Amz-Date", now.format("%Y%m%dT%H%M%SZ").to_string())
+            .header("X-Amz-Target", &target)
+            .header("Authorization", authorization)
+            .body(payload);
+        if let Some(ref token) = credentials.session_token {
+            request = request.header("X-Amz-Security-Token", token);
+        }
+
+        let response = request.send_via(&self.http).await?;
+        let status = response.status();
+        let json: serde_json::Value = response.json().await.unwrap_or_default();
+
+        if status.is_success() {
+            return Ok(json);
+        }
+
+        // Errors carry the exception name in `__type`, e.g. `...#FileDoesNotExistException`
+        let kind = json["__type"].as_str().unwrap_or_default();
+        let kind = kind.rsplit('#').next().unwrap_or(kind);
+        match kind {
+            "RepositoryDoesNotExistException"
+            | "FileDoesNotExistException"
+            | "FolderDoesNotExistException"
+            | "CommitDoesNotExistException" => Err(RsrError::RepoNotFound {
+                owner: String::new(),
+                repo: body["repositoryName"].as_str().unwrap_or_default().to_string(),
+            }),
+            "ThrottlingException" => Err(RsrError::RateLimited),
+            _ => Err(RsrError::Platform(format!(
+                "CodeCommit {} failed ({}): {}",
+                operation,
+                status,
+                json["message"].as_str().or(json["Message"].as_str()).unwrap_or(kind)
+            ))),
+        }
+    }
+
+    /// Comment on a pull request and approve it (or revoke approval) with the result.
+    ///
+    /// The comment and approval apply to the pull request's current revision,
+    /// which is looked up first.
+    pub async fn post_pull_request_feedback(
+        &self,
+        repo: &RepoRef,
+        pull_request_id: &str,
+        status: &ComplianceStatus,
+    ) -> Result<()> {
+        let pull_request = self
+            .call("GetPullRequest", serde_json::json!({ "pullRequestId": pull_request_id }))
+            .await?;
+        let pull_request = &pull_request["pullRequest"];
+
+        let target = pull_request["pullRequestTargets"]
+            .as_array()
+            .and_then(|targets| {
+                targets
+                    .iter()
+                    .find(|t| t["repositoryName"].as_str() == Some(repo.repo.as_str()))
+            })
+            .ok_or_else(|| RsrError::Platform(format!("Pull request {} does not target {}", pull_request_id, repo)))?;
+
+        self.call(
+            "PostCommentForPullRequest",
+            serde_json::json!({
+                "pullRequestId": pull_request_id,
+                "repositoryName": repo.repo,
+                "beforeCommitId": target["destinationCommit"],
+                "afterCommitId": target["sourceCommit"],
+                "content": feedback_comment(status),
+            }),
+        )
+        .await?;
+
+        let approval = if status.tier >= CertificationTier::Bronze {
+            "APPROVE"
+        } else {
+            "REVOKE"
+        };
+
+        self.call(
+            "UpdatePullRequestApprovalState",
+            serde_json::json!({
+                "pullRequestId": pull_request_id,
+                "revisionId": pull_request["revisionId"],
+                "approvalState": approval,
+            }),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Confirm an SNS subscription in the background. Only AWS-hosted
+    /// confirmation URLs are followed.
+    fn confirm_subscription(&self, subscribe_url: Option<&str>) {
+        let Some(url) = subscribe_url.and_then(|u| reqwest::Url::parse(u).ok()) else {
+            return;
+        };
+
+        let trusted = url.scheme() == "https"
+            && url.host_str().is_some_and(|host| host.ends_with(".amazonaws.com"));
+        if !trusted {
+            tracing::warn!("Ignoring SNS subscription confirmation for untrusted URL {}", url);
+            return;
+        }
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!("Confirm the SNS subscription manually: {}", url);
+            return;
+        };
+
+        let client = self.client.clone();
+        runtime.spawn(async move {
+            match client.get(url.clone()).send().await {
+                Ok(response) if response.status().is_success() => {
+                    tracing::info!("Confirmed SNS subscription");
+                }
+                Ok(response) => tracing::warn!("SNS subscription confirmation returned {}", response.status()),
+                Err(e) => tracing::warn!("SNS subscription confirmation failed: {}", e),
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl PlatformAdapter for CodeCommitAdapter {
+    fn platform_id(&self) -> &'static str {
+        "codecommit"
+    }
+
+    fn verify_webhook(&self, _payload: &[u8], headers: &Headers) -> Result<bool> {
+        let Some(ref secret) = self.config.webhook_secret else {
+            return self.config.unsigned_webhook(self.platform_id());
+        };
+
+        // API destination header, or basic auth from the SNS subscription URL
+        let presented = headers.get("x-rsr-webhook-token").cloned().or_else(|| {
+            let encoded = headers.get("authorization")?.strip_prefix("Basic ")?;
+            let decoded = base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok()?;
+            let credentials = String::from_utf8(decoded).ok()?;
+            credentials.split_once(':').map(|(_, password)| password.to_string())
+        });
+
+        let Some(presented) = presented else {
+            return Err(RsrError::WebhookVerification);
+        };
+
+        Ok(constant_time_eq(presented.as_bytes(), secret.as_bytes()))
+    }
+
+    fn delivery_id(&self, headers: &Headers) -> Option<String> {
+        headers.get("x-amz-sns-message-id").cloned()
+    }
+
+    fn parse_webhook(&self, payload: &[u8], headers: &Headers) -> Result<RepoEvent> {
+        // SNS wraps the EventBridge event as a JSON string in `Message`
+        let message;
+        let payload = match headers.get("x-amz-sns-message-type").map(String::as_str) {
+            Some(message_type) => {
+                let envelope: SnsEnvelope = decode_payload("SNS notification", payload)?;
+                if message_type == "SubscriptionConfirmation" {
+                    self.confirm_subscription(envelope.subscribe_url.as_deref());
+                    return Err(RsrError::Platform("SNS subscription confirmation carries no event".to_string()));
+                }
+                message = envelope.message;
+                message.as_bytes()
+            }
+            None => payload,
+        };
+
+        let kind: EventKind = decode_payload("EventBridge event", payload)?;
+
+        match kind.detail_type.as_str() {
+            "CodeCommit Repository State Change" => parse_reference_event(decode_payload(&kind.detail_type, payload)?),
+            "CodeCommit Pull Request State Change" => {
+                parse_pull_request_event(decode_payload(&kind.detail_type, payload)?)
+            }
+            other => Err(RsrError::Platform(format!("Unsupported CodeCommit event: {}", other))),
+        }
+    }
+
+    /// Comment on the commit. Pull requests also get their approval state
+    /// set, through `post_pull_request_status`.
+    async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &ComplianceStatus) -> Result<()> {
+        self.call(
+            "PostCommentForComparedCommit",
+            serde_json::json!({
+                "repositoryName": repo.repo,
+                "afterCommitId": commit_sha,
+                "content": feedback_comment(status),
+            }),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Comment on the pull request and approve it (or revoke approval), so
+    /// approval rules can require a compliant result
+    async fn post_pull_request_status(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        _head_sha: &str,
+        status: &ComplianceStatus,
+    ) -> Result<()> {
+        self.post_pull_request_feedback(repo, &number.to_string(), status).await
+    }
+
+    async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
+        let mut body = serde_json::json!({
+            "repositoryName": repo.repo,
+            "filePath": path,
+        });
+        if let Some(ref branch) = repo.branch {
+            body["commitSpecifier"] = branch.clone().into();
+        }
+
+        let response = self.call("GetFile", body).await?;
+
+        base64::engine::general_purpose::STANDARD
+            .decode(response["fileContent"].as_str().unwrap_or_default())
+            .map_err(|e| RsrError::Platform(format!("Invalid file encoding: {}", e)))
+    }
+
+    async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<String>> {
+        let mut files = Vec::new();
+        let mut pending = vec![format!("/{}", path.unwrap_or("").trim_matches('/'))];
+
+        while let Some(folder) = pending.pop() {
+            let mut body = serde_json::json!({
+                "repositoryName": repo.repo,
+                "folderPath": folder,
+            });
+            if let Some(ref branch) = repo.branch {
+                body["commitSpecifier"] = branch.clone().into();
+            }
+
+            let response = self.call("GetFolder", body).await?;
+
+            let paths = |key: &str| -> Vec<String> {
+                response[key]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|entry| entry["absolutePath"].as_str())
+                    .map(|p| p.trim_start_matches('/').to_string())
+                    .collect()
+            };
+
+            files.extend(paths("files"));
+            pending.extend(paths("subFolders").into_iter().map(|p| format!("/{}", p)));
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
+        let response = self
+            .call("GetRepository", serde_json::json!({ "repositoryName": repo.repo }))
+            .await?;
+        let metadata = &response["repositoryMetadata"];
+
+        Ok(RepoMetadata {
+            default_branch: metadata["defaultBranch"].as_str().unwrap_or("main").to_string(),
+            description: metadata["repositoryDescription"].as_str().map(String::from),
+            has_issues: false,
+            has_wiki: false,
+            has_pages: false,
+            has_ci: false,
+            // Approval rule templates are the closest equivalent; not inspected yet
+            has_branch_protection: false,
+            has_security_policy: false,
+            open_issues_count: 0,
+            stargazers_count: 0,
+            forks_count: 0,
+            upstream: None,
+            archived: false,
+            license: None,
+            topics: Vec::new(),
+            last_push: metadata["lastModifiedDate"]
+                .as_f64()
+                .and_then(|secs| chrono::DateTime::from_timestamp(secs as i64, 0)),
+        })
+    }
+}
+
+// EventBridge parsing helpers
+
+fn parse_reference_event(event: EventBridgeEvent<ReferenceDetail>) -> Result<RepoEvent> {
+    let detail = event.detail;
+    if detail.reference_type != "branch" {
+        return Err(RsrError::Platform("Only branch updates are processed".to_string()));
+    }
+
+    if detail.event == "referenceDeleted" {
+        return Err(RsrError::Platform("Branch deletions are not processed".to_string()));
+    }
+
+    Ok(RepoEvent::Push(PushEvent {
+        repo_owner: event.account,
+        repo_name: detail.repository_name,
+        branch: detail.reference_name,
+        // Absent when the branch was just created
+        before: detail.old_commit_id.unwrap_or_default(),
+        after: detail.commit_id,
+        // EventBridge reports the ref update only
+        commits: Vec::new(),
+        pusher: arn_user(&detail.caller_user_arn),
+    }))
+}
+
+fn parse_pull_request_event(event: EventBridgeEvent<PullRequestDetail>) -> Result<RepoEvent> {
+    let detail = event.detail;
+    let action = match detail.event.as_str() {
+        "pullRequestCreated" => PullRequestAction::Opened,
+        "pullRequestSourceBranchUpdated" => PullRequestAction::Synchronize,
+        "pullRequestMergeStatusUpdated" => PullRequestAction::Merged,
+        "pullRequestStatusChanged" => match detail.pull_request_status.as_deref() {
+            Some("Open") => PullRequestAction::Reopened,
+            _ if detail.is_merged.as_deref() == Some("True") => PullRequestAction::Merged,
+            _ => PullRequestAction::Closed,
+        },
+        other => {
+            return Err(RsrError::Platform(format!("Unsupported pull request event: {}", other)));
+        }
+    };
+
+    let number = detail
+        .pull_request_id
+        .parse()
+        .map_err(|_| RsrError::Platform(format!("Invalid pull request ID: {}", detail.pull_request_id)))?;
+
+    Ok(RepoEvent::PullRequest(PullRequestEvent {
+        repo_owner: event.account,
+        repo_name: detail
+            .repository_names
+            .into_iter()
+            .next()
+            .ok_or_else(|| RsrError::Platform("Pull request event names no repository".to_string()))?,
+        action,
+        number,
+        title: detail.title,
+        body: detail.description,
+        source_branch: branch_name(&detail.source_reference),
+        target_branch: branch_name(&detail.destination_reference),
+        head_sha: detail.source_commit.unwrap_or_default(),
+        author: arn_user(&detail.author),
+        draft: false,
+    }))
+}
+
+fn branch_name(reference: &str) -> String {
+    reference.trim_start_matches("refs/heads/").to_string()
+}
+
+/// A user from an IAM ARN - the last path segment is the user or session name
+fn arn_user(arn: &str) -> User {
+    User {
+        id: arn.to_string(),
+        username: arn.rsplit('/').next().unwrap_or(arn).to_string(),
+        email: None,
+        avatar_url: None,
+    }
+}
+
+fn feedback_comment(status: &ComplianceStatus) -> String {
+    let mut comment = format!(
+        "**RSR Compliance: {} ({:.0}%)**\n",
+        status.tier.code(),
+        status.score * 100.0
+    );
+
+    let failed: Vec<_> = status.checks.iter().filter(|c| !c.passed).collect();
+    if failed.is_empty() {
+        comment.push_str("\nAll checks passed.\n");
+    } else {
+        comment.push_str("\nFailed checks:\n");
+        for check in failed {
+            comment.push_str(&format!("- [{}] {} - {}\n", check.tier.code(), check.name, check.message));
+        }
+    }
+
+    comment
+}
+
+// Notification payloads
+
+#[derive(Debug, Deserialize)]
+struct SnsEnvelope {
+    #[serde(rename = "Message")]
+    message: String,
+    #[serde(rename = "SubscribeURL")]
+    subscribe_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventKind {
+    #[serde(rename = "detail-type")]
+    detail_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventBridgeEvent<T> {
+    account: String,
+    detail: T,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReferenceDetail {
+    event: String,
+    reference_type: String,
+    reference_name: String,
+    repository_name: String,
+    commit_id: String,
+    old_commit_id: Option<String>,
+    caller_user_arn: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequestDetail {
+    event: String,
+    pull_request_id: String,
+    title: String,
+    description: Option<String>,
+    source_reference: String,
+    destination_reference: String,
+    source_commit: Option<String>,
+    repository_names: Vec<String>,
+    author: String,
+    pull_request_status: Option<String>,
+    /// "True" or "False"
+    is_merged: Option<String>,
+}
+
+// SigV4 signing helpers
+
+/// `Authorization` header value for a CodeCommit JSON API request
+fn sign_v4(
+    credentials: &AwsCredentials,
+    region: &str,
+    host: &str,
+    target: &str,
+    payload: &[u8],
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let mut canonical_headers = format!("content-type:{}\nhost:{}\nx-amz-date:{}\n", CONTENT_TYPE, host, amz_date);
+    let mut signed_headers = String::from("content-type;host;x-amz-date");
+    if let Some(ref token) = credentials.session_token {
+        canonical_headers.push_str(&format!("x-amz-security-token:{}\n", token));
+        signed_headers.push_str(";x-amz-security-token");
+    }
+    canonical_headers.push_str(&format!("x-amz-target:{}\n", target));
+    signed_headers.push_str(";x-amz-target");
+
+    let canonical_request = format!(
+        "POST\n/\n\n{}\n{}\n{}",
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(payload))
+    );
+
+    let scope = format!("{}/{}/codecommit/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = [region, "codecommit", "aws4_request"]
+        .iter()
+        .fold(hmac_sha256(format!("AWS4{}", credentials.secret_access_key).as_bytes(), date.as_bytes()), |key, part| {
+            hmac_sha256(&key, part.as_bytes())
+        });
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn adapter(server: &MockServer) -> CodeCommitAdapter {
+        let mut adapter = CodeCommitAdapter::new(AdapterConfig {
+            api_url: Some(server.uri()),
+            ..Default::default()
+        });
+        adapter.credentials = Some(AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: None,
+        });
+        adapter
+    }
+
+    /// Answer `operation` with `response`, expecting it `times` times
+    async fn expect(server: &MockServer, operation: &str, body: serde_json::Value, response: serde_json::Value, times: u64) {
+        Mock::given(method("POST"))
+            .and(header("x-amz-target", format!("{}.{}", TARGET_PREFIX, operation).as_str()))
+            .and(body_partial_json(body))
+            .respond_with(ResponseTemplate::new(200).set_body_json(response))
+            .expect(times)
+            .mount(server)
+            .await;
+    }
+
+    #[test]
+    fn pull_request_event_records_the_source_commit() {
+        let payload = serde_json::json!({
+            "detail-type": "CodeCommit Pull Request State Change",
+            "account": "123456789012",
+            "detail": {
+                "event": "pullRequestSourceBranchUpdated",
+                "pullRequestId": "7",
+                "title": "Add a license",
+                "sourceReference": "refs/heads/license",
+                "destinationReference": "refs/heads/main",
+                "sourceCommit": "abc123",
+                "repositoryNames": ["app"],
+                "author": "arn:aws:iam::123456789012:user/dev",
+            },
+        });
+        let Ok(RepoEvent::PullRequest(pr)) = parse_pull_request_event(serde_json::from_value(payload).unwrap()) else {
+            panic!("not parsed as a pull request event");
+        };
+        assert_eq!(pr.head_sha, "abc123");
+    }
+
+    #[tokio::test]
+    async fn pull_request_status_sets_the_approval_state() {
+        let server = MockServer::start().await;
+        let pull_request = serde_json::json!({
+            "pullRequest": {
+                "revisionId": "rev-1",
+                "pullRequestTargets": [{
+                    "repositoryName": "app",
+                    "sourceCommit": "abc123",
+                    "destinationCommit": "def456",
+                }],
+            },
+        });
+        expect(&server, "GetPullRequest", serde_json::json!({ "pullRequestId": "7" }), pull_request, 1).await;
+        let comment = serde_json::json!({ "pullRequestId": "7", "afterCommitId": "abc123" });
+        expect(&server, "PostCommentForPullRequest", comment, serde_json::json!({}), 1).await;
+        let approval = serde_json::json!({ "pullRequestId": "7", "revisionId": "rev-1", "approvalState": "REVOKE" });
+        expect(&server, "UpdatePullRequestApprovalState", approval, serde_json::json!({}), 1).await;
+
+        let repo = RepoRef::new("codecommit", "123456789012", "app");
+        let license = crate::CheckResult {
+            id: "bronze.license".to_string(),
+            name: "License".to_string(),
+            tier: CertificationTier::Bronze,
+            passed: false,
+            message: "No license".to_string(),
+            details: None,
+            findings: Vec::new(),
+        };
+        let status = ComplianceStatus::builder().repo(repo.clone()).check(license).build().unwrap();
+        adapter(&server).post_pull_request_status(&repo, 7, "abc123", &status).await.unwrap();
+    }
+}
@@ -0,0 +1,577 @@
+//! Gerrit Code Review platform adapter
+//!
+//! Events arrive from the webhooks plugin, or from a relay forwarding
+//! `gerrit stream-events` lines; both use the same JSON shape. Neither signs
+//! its requests, so authenticity rests on a shared secret presented in an
+//! `X-Rsr-Webhook-Token` header (set by the relay or a fronting proxy) or as
+//! the basic-auth password.
+//!
+//! Results are posted as a review on the change: a vote on the configured
+//! label (`Verified` by default) plus the compliance summary as the message.
+//! The API token is `username:http-password`. Listing files needs the gitiles
+//! plugin. Projects map to `owner/repo` at their last path segment, so
+//! `platform/build/soong` is owner `platform/build`, repo `soong`.
+
+use super::http::{HttpLayer, SendVia};
+use super::signature::constant_time_eq;
+use super::{decode_payload, AdapterCapabilities, AdapterConfig, Headers, PlatformAdapter, RepoMetadata};
+use crate::events::*;
+use crate::{CertificationTier, ComplianceStatus, RepoRef, Result, RsrError};
+use async_trait::async_trait;
+use base64::Engine;
+use serde::Deserialize;
+
+/// Label voted on when none is configured
+const DEFAULT_LABEL: &str = "Verified";
+
+/// Review tag, so Gerrit can hide our votes behind "Only comments"
+const REVIEW_TAG: &str = "autogenerated:rsr";
+
+/// Prefix Gerrit puts before JSON responses to defeat XSSI
+const XSSI_PREFIX: &str = ")]}'";
+
+pub struct GerritAdapter {
+    config: AdapterConfig,
+    client: reqwest::Client,
+    http: HttpLayer,
+    api_url: String,
+}
+
+impl GerritAdapter {
+    pub fn new(config: AdapterConfig) -> Self {
+        // Gerrit has no public default instance
+        let api_url = config
+            .api_url
+            .clone()
+            .unwrap_or_else(|| {
+                tracing::warn!("No API URL configured for Gerrit - using placeholder");
+                "https://gerrit.example.com".to_string()
+            })
+            .trim_end_matches('/')
+            .to_string();
+
+        let http = HttpLayer::new("gerrit", config.retry.clone()).with_etag_cache(config.etag_cache.clone());
+
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            http,
+            api_url,
+        }
+    }
+
+    /// REST URL for `path`; authenticated requests go through `/a/`
+    fn rest_url(&self, path: &str) -> String {
+        if self.config.api_token.is_some() {
+            format!("{}/a/{}", self.api_url, path)
+        } else {
+            format!("{}/{}", self.api_url, path)
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        match self.config.api_token.as_deref().map(|token| token.split_once(':')) {
+            Some(Some((username, password))) => request.basic_auth(username, Some(password)),
+            Some(None) => request.bearer_auth(self.config.api_token.as_deref().unwrap_or_default()),
+            None => request,
+        }
+    }
+
+    /// Label voted on for results
+    fn label(&self) -> &str {
+        self.config.review_label.as_deref().unwrap_or(DEFAULT_LABEL)
+    }
+
+    /// Vote on the configured label of a change's patch set, +1 if `status`
+    /// reaches Bronze and -1 otherwise
+    async fn vote(&self, repo: &RepoRef, number: u64, revision: &str, status: &ComplianceStatus) -> Result<()> {
+        let vote = if status.tier >= CertificationTier::Bronze { 1 } else { -1 };
+        let body = serde_json::json!({
+            "message": review_message(status),
+            "labels": { self.label(): vote },
+            "tag": REVIEW_TAG,
+        });
+
+        let path = format!(
+            "changes/{}~{}/revisions/{}/review",
+            urlencoding::encode(&project_name(repo)),
+            number,
+            revision
+        );
+        let response = self
+            .request(reqwest::Method::POST, &self.rest_url(&path))
+            .json(&body)
+            .send_via(&self.http)
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RsrError::Platform(format!("Failed to post review: {}", error_text)));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PlatformAdapter for GerritAdapter {
+    fn platform_id(&self) -> &'static str {
+        "gerrit"
+    }
+
+    fn capabilities(&self) -> AdapterCapabilities {
+        AdapterCapabilities {
+            reviews: true,
+            ..Default::default()
+        }
+    }
+
+    fn verify_webhook(&self, _payload: &[u8], headers: &Headers) -> Result<bool> {
+        let Some(ref secret) = self.config.webhook_secret else {
+            return self.config.unsigned_webhook(self.platform_id());
+        };
+
+        let presented = headers.get("x-rsr-webhook-token").cloned().or_else(|| {
+            let encoded = headers.get("authorization")?.strip_prefix("Basic ")?;
+            let decoded = base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok()?;
+            let credentials = String::from_utf8(decoded).ok()?;
+            credentials.split_once(':').map(|(_, password)| password.to_string())
+        });
+
+        let Some(presented) = presented else {
+            return Err(RsrError::WebhookVerification);
+        };
+
+        Ok(constant_time_eq(presented.as_bytes(), secret.as_bytes()))
+    }
+
+    fn parse_webhook(&self, payload: &[u8], _headers: &Headers) -> Result<RepoEvent> {
+        let kind: EventKind = decode_payload("Gerrit event", payload)?;
+
+        match kind.event_type.as_str() {
+            "patchset-created" | "change-merged" | "change-abandoned" | "change-restored" => {
+                parse_change_event(decode_payload(&kind.event_type, payload)?)
+            }
+            other => Err(RsrError::Platform(format!("Unsupported Gerrit event: {}", other))),
+        }
+    }
+
+    /// Vote on the change the commit is a patch set of
+    async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &ComplianceStatus) -> Result<()> {
+        if self.config.api_token.is_none() {
+            return Err(RsrError::Config("API token required for posting status".to_string()));
+        }
+
+        let project = project_name(repo);
+        let query = format!(
+            "changes/?q={}&n=1",
+            urlencoding::encode(&format!("commit:{} project:{}", commit_sha, project))
+        );
+        let response = self
+            .request(reqwest::Method::GET, &self.rest_url(&query))
+            .send_via(&self.http)
+            .await?;
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RsrError::Platform(format!("Change lookup failed: {}", error_text)));
+        }
+        let changes = gerrit_json(&response.bytes().await?)?;
+
+        let Some(number) = changes[0]["_number"].as_u64() else {
+            // Pushed straight to a branch, bypassing review
+            tracing::info!("No Gerrit change for {} in {} - nothing to vote on", commit_sha, project);
+            return Ok(());
+        };
+
+        self.vote(repo, number, commit_sha, status).await
+    }
+
+    /// Vote on the patch set directly; the change is already known
+    async fn post_pull_request_status(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        head_sha: &str,
+        status: &ComplianceStatus,
+    ) -> Result<()> {
+        if self.config.api_token.is_none() {
+            return Err(RsrError::Config("API token required for posting status".to_string()));
+        }
+        self.vote(repo, number, head_sha, status).await
+    }
+
+    /// Vote -1 on Code-Review for the change's current patch set
+    async fn request_changes(&self, repo: &RepoRef, number: u64, body: &str) -> Result<()> {
+        if self.config.api_token.is_none() {
+            return Err(RsrError::Config("API token required for submitting reviews".to_string()));
+        }
+
+        let path = format!(
+            "changes/{}~{}/revisions/current/review",
+            urlencoding::encode(&project_name(repo)),
+            number
+        );
+        let response = self
+            .request(reqwest::Method::POST, &self.rest_url(&path))
+            .json(&serde_json::json!({
+                "message": body,
+                "labels": { "Code-Review": -1 },
+                "tag": REVIEW_TAG,
+            }))
+            .send_via(&self.http)
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RsrError::Platform(format!("Failed to post review: {}", error_text)));
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
+        let url = self.rest_url(&format!(
+            "projects/{}/branches/{}/files/{}/content",
+            urlencoding::encode(&project_name(repo)),
+            urlencoding::encode(repo.branch.as_deref().unwrap_or("HEAD")),
+            urlencoding::encode(path)
+        ));
+
+        let response = self.request(reqwest::Method::GET, &url).fetch_via(&self.http).await?;
+
+        if response.status == reqwest::StatusCode::NOT_FOUND {
+            return Err(RsrError::RepoNotFound {
+                owner: repo.owner.clone(),
+                repo: repo.repo.clone(),
+            });
+        }
+
+        if !response.status.is_success() {
+            return Err(RsrError::Platform(format!("Failed to fetch {} ({}): {}", path, response.status, response.text())));
+        }
+
+        // File content comes back base64 encoded, without the XSSI prefix
+        base64::engine::general_purpose::STANDARD
+            .decode(response.text().trim())
+            .map_err(|e| RsrError::Platform(format!("Invalid file encoding: {}", e)))
+    }
+
+    async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<String>> {
+        let prefix = path.unwrap_or("").trim_matches('/');
+        let url = format!(
+            "{}/plugins/gitiles/{}/+/{}/{}?format=JSON&recursive=1",
+            self.api_url,
+            project_name(repo),
+            repo.branch.as_deref().unwrap_or("HEAD"),
+            prefix
+        );
+
+        let response = self.request(reqwest::Method::GET, &url).fetch_via(&self.http).await?;
+
+        if response.status == reqwest::StatusCode::NOT_FOUND {
+            return Err(RsrError::RepoNotFound {
+                owner: repo.owner.clone(),
+                repo: repo.repo.clone(),
+            });
+        }
+
+        let json = gerrit_json(&response.body)?;
+
+        // Entry names are relative to the listed directory
+        let files = json["entries"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry["type"].as_str() == Some("blob"))
+            .filter_map(|entry| entry["name"].as_str())
+            .map(|name| {
+                if prefix.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}/{}", prefix, name)
+                }
+            })
+            .collect();
+
+        Ok(files)
+    }
+
+    async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
+        let project = urlencoding::encode(&project_name(repo)).into_owned();
+
+        let response = self
+            .request(reqwest::Method::GET, &self.rest_url(&format!("projects/{}", project)))
+            .fetch_via(&self.http)
+            .await?;
+
+        if response.status == reqwest::StatusCode::NOT_FOUND {
+            return Err(RsrError::RepoNotFound {
+                owner: repo.owner.clone(),
+                repo: repo.repo.clone(),
+            });
+        }
+
+        let json = gerrit_json(&response.body)?;
+
+        // HEAD is a JSON string like "refs/heads/main"
+        let head = self
+            .request(reqwest::Method::GET, &self.rest_url(&format!("projects/{}/HEAD", project)))
+            .fetch_via(&self.http)
+            .await?;
+        let default_branch = gerrit_json(&head.body)
+            .ok()
+            .and_then(|head| head.as_str().map(|r| r.trim_start_matches("refs/heads/").to_string()))
+            .unwrap_or_else(|| "master".to_string());
+
+        Ok(RepoMetadata {
+            default_branch,
+            description: json["description"].as_str().map(String::from),
+            has_issues: false,
+            has_wiki: false,
+            has_pages: false,
+            has_ci: false,
+            // Every change goes through review and label submit rules
+            has_branch_protection: true,
+            has_security_policy: false,
+            open_issues_count: 0,
+            stargazers_count: 0,
+            forks_count: 0,
+            upstream: None,
+            archived: false,
+            license: None,
+            topics: Vec::new(),
+            last_push: None,
+        })
+    }
+}
+
+// Event parsing helpers
+
+fn parse_change_event(event: ChangeEvent) -> Result<RepoEvent> {
+    let action = match event.event_type.as_str() {
+        "patchset-created" if event.patch_set.number == 1 => PullRequestAction::Opened,
+        "patchset-created" => PullRequestAction::Synchronize,
+        "change-merged" => PullRequestAction::Merged,
+        "change-abandoned" => PullRequestAction::Closed,
+        _ => PullRequestAction::Reopened,
+    };
+
+    let change = event.change;
+    let (repo_owner, repo_name) = split_project(&change.project);
+    let owner = change.owner;
+
+    Ok(RepoEvent::PullRequest(PullRequestEvent {
+        repo_owner,
+        repo_name,
+        action,
+        number: change.number,
+        title: change.subject,
+        body: change.commit_message,
+        // Patch sets have no source branch; their ref is fetchable instead
+        source_branch: event.patch_set.git_ref,
+        target_branch: change.branch,
+        head_sha: event.patch_set.revision,
+        author: User {
+            id: owner.username.clone().or(owner.email.clone()).unwrap_or_default(),
+            username: owner.username.or(owner.name).unwrap_or_default(),
+            email: owner.email,
+            avatar_url: None,
+        },
+        draft: change.wip || change.private,
+    }))
+}
+
+/// Split a project name at its last path segment
+fn split_project(project: &str) -> (String, String) {
+    match project.rsplit_once('/') {
+        Some((owner, repo)) => (owner.to_string(), repo.to_string()),
+        None => (String::new(), project.to_string()),
+    }
+}
+
+fn project_name(repo: &RepoRef) -> String {
+    if repo.owner.is_empty() {
+        repo.repo.clone()
+    } else {
+        format!("{}/{}", repo.owner, repo.repo)
+    }
+}
+
+/// Parse a JSON response, dropping the XSSI prefix
+fn gerrit_json(body: &[u8]) -> Result<serde_json::Value> {
+    let text = String::from_utf8_lossy(body);
+    let text = text.trim_start().strip_prefix(XSSI_PREFIX).unwrap_or(&text);
+    Ok(serde_json::from_str(text)?)
+}
+
+fn review_message(status: &ComplianceStatus) -> String {
+    let mut message = format!(
+        "RSR Compliance: {} ({:.0}%)\n",
+        status.tier.code(),
+        status.score * 100.0
+    );
+
+    let failed: Vec<_> = status.checks.iter().filter(|c| !c.passed).collect();
+    if failed.is_empty() {
+        message.push_str("\nAll checks passed.\n");
+    } else {
+        message.push_str("\nFailed checks:\n");
+        for check in failed {
+            message.push_str(&format!("* [{}] {} - {}\n", check.tier.code(), check.name, check.message));
+        }
+    }
+
+    message
+}
+
+// Event payloads
+
+#[derive(Debug, Deserialize)]
+struct EventKind {
+    #[serde(rename = "type")]
+    event_type: String,
+}
+
+/// `patchset-created`, `change-merged`, `change-abandoned` or `change-restored`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChangeEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    change: Change,
+    patch_set: PatchSet,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Change {
+    project: String,
+    branch: String,
+    #[serde(deserialize_with = "number")]
+    number: u64,
+    subject: String,
+    commit_message: Option<String>,
+    owner: Account,
+    #[serde(default)]
+    wip: bool,
+    #[serde(default)]
+    private: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct PatchSet {
+    #[serde(deserialize_with = "number")]
+    number: u64,
+    #[serde(rename = "ref")]
+    git_ref: String,
+    /// Commit of the patch set
+    revision: String,
+}
+
+/// Accounts carry whichever of these the user has set
+#[derive(Debug, Deserialize)]
+struct Account {
+    name: Option<String>,
+    email: Option<String>,
+    username: Option<String>,
+}
+
+/// Older Gerrit versions send numbers as strings
+fn number<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Int(u64),
+        Text(String),
+    }
+
+    match Number::deserialize(deserializer)? {
+        Number::Int(n) => Ok(n),
+        Number::Text(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn adapter(server: &MockServer) -> GerritAdapter {
+        GerritAdapter::new(AdapterConfig {
+            api_url: Some(server.uri()),
+            api_token: Some("rsr:password".to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// A status failing a Bronze check, so below every tier
+    fn failing_status(repo: &RepoRef) -> ComplianceStatus {
+        let license = crate::CheckResult {
+            id: "bronze.license".to_string(),
+            name: "License".to_string(),
+            tier: CertificationTier::Bronze,
+            passed: false,
+            message: "No license".to_string(),
+            details: None,
+            findings: Vec::new(),
+        };
+        ComplianceStatus::builder().repo(repo.clone()).check(license).build().unwrap()
+    }
+
+    async fn expect_vote(server: &MockServer, label: &str) {
+        Mock::given(method("POST"))
+            .and(path("/a/changes/acme%2Fapp~7/revisions/abc123/review"))
+            .and(body_partial_json(serde_json::json!({ "labels": { label: -1 }, "tag": REVIEW_TAG })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(")]}'\n{}"))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[test]
+    fn patch_set_event_records_its_revision() {
+        let payload = serde_json::json!({
+            "type": "patchset-created",
+            "change": {
+                "project": "acme/app",
+                "branch": "main",
+                "number": 7,
+                "subject": "Add a license",
+                "owner": { "username": "dev" },
+            },
+            "patchSet": { "number": 2, "ref": "refs/changes/07/7/2", "revision": "abc123" },
+        });
+        let Ok(RepoEvent::PullRequest(pr)) = parse_change_event(serde_json::from_value(payload).unwrap()) else {
+            panic!("not parsed as a pull request event");
+        };
+        assert_eq!(pr.head_sha, "abc123");
+    }
+
+    #[tokio::test]
+    async fn pull_request_status_votes_on_the_patch_set() {
+        let server = MockServer::start().await;
+        expect_vote(&server, DEFAULT_LABEL).await;
+
+        let repo = RepoRef::new("gerrit", "acme", "app");
+        adapter(&server).post_pull_request_status(&repo, 7, "abc123", &failing_status(&repo)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn commit_status_votes_on_the_change_it_belongs_to() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/a/changes/"))
+            .and(query_param("q", "commit:abc123 project:acme/app"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(")]}'\n[{\"_number\": 7}]"))
+            .mount(&server)
+            .await;
+        expect_vote(&server, "RSR-Compliant").await;
+
+        let mut adapter = adapter(&server);
+        adapter.config.review_label = Some("RSR-Compliant".to_string());
+        let repo = RepoRef::new("gerrit", "acme", "app");
+        adapter.post_status(&repo, "abc123", &failing_status(&repo)).await.unwrap();
+    }
+}
@@ -2,18 +2,17 @@
 //!
 //! Supports Gitea and Forgejo instances (API compatible).
 
-use super::{AdapterConfig, Headers, PlatformAdapter, RepoMetadata};
+use super::http::{HttpLayer, SendVia};
+use super::signature::{verify_hmac, HmacAlgorithm};
+use super::{AdapterConfig, BranchProtection, Headers, PlatformAdapter, RepoMetadata, STATUS_CONTEXT};
 use crate::events::RepoEvent;
 use crate::{ComplianceStatus, RepoRef, Result, RsrError};
 use async_trait::async_trait;
-use hmac::{Hmac, Mac};
-use sha2::Sha256;
-
-type HmacSha256 = Hmac<Sha256>;
 
 pub struct GiteaAdapter {
     config: AdapterConfig,
     client: reqwest::Client,
+    http: HttpLayer,
     api_url: String,
 }
 
@@ -25,9 +24,12 @@ pub fn new(config: AdapterConfig) -> Self {
             "https://gitea.example.com/api/v1".to_string()
         });
 
+        let http = HttpLayer::new("gitea", config.retry.clone()).with_etag_cache(config.etag_cache.clone());
+
         Self {
             config,
             client: reqwest::Client::new(),
+            http,
             api_url,
         }
     }
@@ -41,38 +43,22 @@ fn platform_id(&self) -> &'static str {
 
     fn verify_webhook(&self, payload: &[u8], headers: &Headers) -> Result<bool> {
         let Some(ref secret) = self.config.webhook_secret else {
-            tracing::warn!("Webhook secret not configured - skipping verification");
-            return Ok(true);
+            return self.config.unsigned_webhook(self.platform_id());
         };
 
-        // Gitea uses X-Gitea-Signature (HMAC-SHA256)
-        let Some(signature) = headers.get("x-gitea-signature") else {
-            // Also check for X-Hub-Signature-256 (GitHub-compatible format)
-            if let Some(sig) = headers.get("x-hub-signature-256") {
-                let expected_prefix = "sha256=";
-                if !sig.starts_with(expected_prefix) {
-                    return Err(RsrError::WebhookVerification);
-                }
-                let signature_hex = &sig[expected_prefix.len()..];
-
-                let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
-                    .map_err(|_| RsrError::WebhookVerification)?;
-                mac.update(payload);
-                let result = mac.finalize();
-                let computed = hex::encode(result.into_bytes());
-
-                return Ok(constant_time_eq(signature_hex.as_bytes(), computed.as_bytes()));
-            }
-            return Err(RsrError::WebhookVerification);
+        // Gitea uses X-Gitea-Signature (bare HMAC-SHA256 hex), and also sends
+        // the GitHub-compatible X-Hub-Signature-256
+        let signature = match (headers.get("x-gitea-signature"), headers.get("x-hub-signature-256")) {
+            (Some(signature), _) => signature,
+            (None, Some(signature)) if signature.starts_with(HmacAlgorithm::Sha256.prefix()) => signature,
+            _ => return Err(RsrError::WebhookVerification),
         };
 
-        let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
-            .map_err(|_| RsrError::WebhookVerification)?;
-        mac.update(payload);
-        let result = mac.finalize();
-        let computed = hex::encode(result.into_bytes());
+        Ok(verify_hmac(HmacAlgorithm::Sha256, secret.as_bytes(), payload, signature))
+    }
 
-        Ok(constant_time_eq(signature.as_bytes(), computed.as_bytes()))
+    fn delivery_id(&self, headers: &Headers) -> Option<String> {
+        headers.get("x-gitea-delivery").cloned()
     }
 
     fn parse_webhook(&self, payload: &[u8], headers: &Headers) -> Result<RepoEvent> {
@@ -110,14 +96,14 @@ async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &Complianc
             "state": state,
             "target_url": format!("https://rsr-certified.dev/report/{}/{}", repo.owner, repo.repo),
             "description": format!("RSR Compliance: {} ({:.0}%)", status.tier.code(), status.score * 100.0),
-            "context": "RSR / Compliance Check"
+            "context": STATUS_CONTEXT
         });
 
         let response = self.client
             .post(&url)
             .header("Authorization", format!("token {}", token))
             .json(&body)
-            .send()
+            .send_via(&self.http)
             .await?;
 
         if !response.status().is_success() {
@@ -142,17 +128,21 @@ async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
         let response = self.client
             .get(&url)
             .header("Authorization", format!("token {}", token))
-            .send()
+            .fetch_via(&self.http)
             .await?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
+        if response.status == reqwest::StatusCode::NOT_FOUND {
             return Err(RsrError::RepoNotFound {
                 owner: repo.owner.clone(),
                 repo: repo.repo.clone(),
             });
         }
 
-        Ok(response.bytes().await?.to_vec())
+        if !response.status.is_success() {
+            return Err(RsrError::Platform(format!("Failed to fetch {} ({}): {}", path, response.status, response.text())));
+        }
+
+        Ok(response.body)
     }
 
     async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<String>> {
@@ -170,10 +160,10 @@ async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<Str
         let response = self.client
             .get(&url)
             .header("Authorization", format!("token {}", token))
-            .send()
+            .fetch_via(&self.http)
             .await?;
 
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = response.json()?;
 
         let files: Vec<String> = json
             .as_array()
@@ -200,10 +190,10 @@ async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
         let response = self.client
             .get(&url)
             .header("Authorization", format!("token {}", token))
-            .send()
+            .fetch_via(&self.http)
             .await?;
 
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = response.json()?;
 
         Ok(RepoMetadata {
             default_branch: json["default_branch"].as_str().unwrap_or("main").to_string(),
@@ -217,6 +207,8 @@ async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
             open_issues_count: json["open_issues_count"].as_u64().unwrap_or(0) as u32,
             stargazers_count: json["stars_count"].as_u64().unwrap_or(0) as u32,
             forks_count: json["forks_count"].as_u64().unwrap_or(0) as u32,
+            upstream: json["parent"]["full_name"].as_str().map(String::from),
+            archived: json["archived"].as_bool().unwrap_or(false),
             license: None,
             topics: json["topics"]
                 .as_array()
@@ -228,11 +220,53 @@ async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
                 .map(|dt| dt.with_timezone(&chrono::Utc)),
         })
     }
-}
 
-fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
-    if a.len() != b.len() {
-        return false;
+    async fn get_branch_protection(&self, repo: &RepoRef, branch: &str) -> Result<Option<BranchProtection>> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required".to_string()));
+        };
+
+        // Rules are looked up by name, which is the branch for rules that
+        // protect a single branch rather than a pattern
+        let url = format!(
+            "{}/repos/{}/{}/branch_protections/{}",
+            self.api_url, repo.owner, repo.repo, branch
+        );
+        let response = self.client
+            .get(&url)
+            .header("Authorization", format!("token {}", token))
+            .fetch_via(&self.http)
+            .await?;
+
+        if response.status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status.is_success() {
+            return Err(RsrError::Platform(format!(
+                "Failed to read protection of {} ({}): {}",
+                branch,
+                response.status,
+                response.text()
+            )));
+        }
+
+        let json: serde_json::Value = response.json()?;
+        let contexts = match json["enable_status_check"].as_bool() {
+            Some(true) => json["status_check_contexts"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|context| context.as_str().map(String::from))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Ok(Some(BranchProtection {
+            required_approvals: json["required_approvals"].as_u64().unwrap_or(0) as u32,
+            required_status_checks: contexts,
+            // Versions without the setting refuse force pushes to protected branches
+            allows_force_pushes: json["enable_force_push"].as_bool().unwrap_or(false),
+            requires_signed_commits: json["require_signed_commits"].as_bool().unwrap_or(false),
+        }))
     }
-    a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
 }
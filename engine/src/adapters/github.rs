@@ -2,20 +2,68 @@
 //!
 //! Supports both GitHub.com and GitHub Enterprise Server.
 
-use super::{AdapterConfig, Headers, PlatformAdapter, RepoMetadata};
+mod payloads;
+
+use super::pagination::fetch_all_pages;
+use super::http::{HttpLayer, SendVia};
+use super::signature::{verify_hmac, HmacAlgorithm};
+use super::{
+    decode_payload, strict, AdapterCapabilities, AdapterConfig, BranchProtection, Headers, PlatformAdapter, Release, RepoMetadata,
+    Signature, SignatureFormat, SignedObject, WorkflowRun,
+};
+use payloads::*;
 use crate::events::*;
 use crate::{ComplianceStatus, RepoRef, Result, RsrError};
 use async_trait::async_trait;
-use hmac::{Hmac, Mac};
-use sha2::Sha256;
-
-type HmacSha256 = Hmac<Sha256>;
+use std::collections::HashMap;
 
 const DEFAULT_API_URL: &str = "https://api.github.com";
 
+/// Name shared by the commit status context and the check run
+const CHECK_NAME: &str = super::STATUS_CONTEXT;
+
+/// GitHub accepts at most 50 annotations per check run request
+const MAX_ANNOTATIONS_PER_REQUEST: usize = 50;
+
+/// RSR checks are repository-level, so annotations are anchored to the config file
+const ANNOTATION_PATH: &str = ".rsr.toml";
+
+/// Hidden marker identifying the RSR summary comment on a pull request
+const SUMMARY_COMMENT_MARKER: &str = "<!-- rsr-compliance-summary -->";
+
+/// Blobs requested per GraphQL query, keeping each well under the node limit
+const GRAPHQL_BLOBS_PER_QUERY: usize = 50;
+
+/// Advisories requested per page of `securityAdvisories`
+const GRAPHQL_ADVISORIES_PER_PAGE: usize = 100;
+
+/// Reviewed advisories with the package versions they affect
+const SECURITY_ADVISORIES_QUERY: &str = r#"
+query($first: Int!, $after: String, $updatedSince: DateTime) {
+  securityAdvisories(first: $first, after: $after, updatedSince: $updatedSince, orderBy: {field: UPDATED_AT, direction: ASC}) {
+    pageInfo { hasNextPage endCursor }
+    nodes {
+      ghsaId
+      identifiers { type value }
+      severity
+      updatedAt
+      withdrawnAt
+      vulnerabilities(first: 100) {
+        nodes {
+          package { ecosystem name }
+          vulnerableVersionRange
+          firstPatchedVersion { identifier }
+        }
+      }
+    }
+  }
+}
+"#;
+
 pub struct GitHubAdapter {
     config: AdapterConfig,
     client: reqwest::Client,
+    http: HttpLayer,
     api_url: String,
 }
 
@@ -23,16 +71,380 @@ impl GitHubAdapter {
     pub fn new(config: AdapterConfig) -> Self {
         let api_url = config.api_url.clone().unwrap_or_else(|| DEFAULT_API_URL.to_string());
 
+        let http = HttpLayer::new("github", config.retry.clone()).with_etag_cache(config.etag_cache.clone());
+
         Self {
             config,
             client: reqwest::Client::new(),
+            http,
             api_url,
         }
     }
 
+    /// GraphQL endpoint - `/graphql` on GitHub.com, `/api/graphql` on Enterprise Server
+    fn graphql_url(&self) -> String {
+        let base = self.api_url.trim_end_matches('/');
+        match base.strip_suffix("/v3") {
+            Some(enterprise) => format!("{}/graphql", enterprise),
+            None => format!("{}/graphql", base),
+        }
+    }
+
+    /// First page, of at most `limit` entries, of a repository listing such as
+    /// `tags` or `releases`
+    async fn list_json(&self, repo: &RepoRef, listing: &str, limit: usize) -> Result<serde_json::Value> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required".to_string()));
+        };
+
+        let url = format!(
+            "{}/repos/{}/{}/{}?per_page={}",
+            self.api_url,
+            repo.owner,
+            repo.repo,
+            listing,
+            limit.clamp(1, 100)
+        );
+        let response = self.client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "RSR-Certified/0.1")
+            .fetch_via(&self.http)
+            .await?;
+
+        if !response.status.is_success() {
+            return Err(RsrError::Platform(format!("Failed to list {} ({}): {}", listing, response.status, response.text())));
+        }
+        response.json()
+    }
+
+    /// A repository resource such as `git/tags/{sha}`, or `None` if it
+    /// doesn't exist
+    async fn get_json(&self, repo: &RepoRef, resource: &str) -> Result<Option<serde_json::Value>> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required".to_string()));
+        };
+
+        let url = format!("{}/repos/{}/{}/{}", self.api_url, repo.owner, repo.repo, resource);
+        let response = self.client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "RSR-Certified/0.1")
+            .fetch_via(&self.http)
+            .await?;
+
+        if response.status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status.is_success() {
+            return Err(RsrError::Platform(format!("Failed to read {} ({}): {}", resource, response.status, response.text())));
+        }
+        response.json().map(Some)
+    }
+
+    /// One page of reviewed security advisories, least recently updated
+    /// first, optionally only those updated since `updated_since`. Returns
+    /// the advisory nodes and the cursor of the next page, if any.
+    pub async fn security_advisories(
+        &self,
+        updated_since: Option<chrono::DateTime<chrono::Utc>>,
+        after: Option<&str>,
+    ) -> Result<(Vec<serde_json::Value>, Option<String>)> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required".to_string()));
+        };
+
+        let variables = serde_json::json!({
+            "first": GRAPHQL_ADVISORIES_PER_PAGE,
+            "after": after,
+            "updatedSince": updated_since,
+        });
+        let response = self.client
+            .post(self.graphql_url())
+            .header("Authorization", format!("Bearer {}", token))
+            .header("User-Agent", "RSR-Certified/0.1")
+            .json(&serde_json::json!({ "query": SECURITY_ADVISORIES_QUERY, "variables": variables }))
+            .send_via(&self.http)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RsrError::Platform(format!("GraphQL request failed ({}): {}", status, error_text)));
+        }
+
+        let mut json: serde_json::Value = response.json().await?;
+        let advisories = json["data"]["securityAdvisories"].take();
+        if advisories.is_null() {
+            return Err(RsrError::Platform(format!("GraphQL query failed: {}", json["errors"])));
+        }
+        let next = match advisories["pageInfo"]["hasNextPage"].as_bool() {
+            Some(true) => advisories["pageInfo"]["endCursor"].as_str().map(String::from),
+            _ => None,
+        };
+        let nodes = advisories["nodes"].as_array().cloned().unwrap_or_default();
+        Ok((nodes, next))
+    }
+
     fn get_event_type(headers: &Headers) -> Option<&str> {
         headers.get("x-github-event").map(|s| s.as_str())
     }
+
+    /// Create or update the RSR check run for a commit.
+    ///
+    /// An existing run with the same name on the commit is updated in place so
+    /// re-scans don't stack up duplicate runs. Failed checks become annotations.
+    pub async fn post_check_run(&self, repo: &RepoRef, commit_sha: &str, status: &ComplianceStatus) -> Result<()> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required for posting check runs".to_string()));
+        };
+
+        let annotations: Vec<serde_json::Value> = status
+            .checks
+            .iter()
+            .filter(|check| !check.passed)
+            .map(check_annotation)
+            .collect();
+        let mut batches = annotations.chunks(MAX_ANNOTATIONS_PER_REQUEST);
+
+        let failed = annotations.len();
+        let output = |batch: &[serde_json::Value]| {
+            serde_json::json!({
+                "title": format!("{} ({:.0}%)", status.tier.code(), status.score * 100.0),
+                "summary": format!("{} of {} checks passed", status.checks.len() - failed, status.checks.len()),
+                "text": check_run_summary(status),
+                "annotations": batch,
+            })
+        };
+
+        let body = serde_json::json!({
+            "name": CHECK_NAME,
+            "head_sha": commit_sha,
+            "status": "completed",
+            "conclusion": check_run_conclusion(status),
+            "completed_at": status.timestamp.to_rfc3339(),
+            "details_url": format!("https://rsr-certified.dev/report/{}/{}", repo.owner, repo.repo),
+            "output": output(batches.next().unwrap_or_default()),
+        });
+
+        let check_run_id = match self.find_check_run(token, repo, commit_sha).await? {
+            Some(id) => {
+                let url = format!("{}/repos/{}/{}/check-runs/{}", self.api_url, repo.owner, repo.repo, id);
+                self.send_check_run(self.client.patch(&url), token, &body).await?;
+                id
+            }
+            None => {
+                let url = format!("{}/repos/{}/{}/check-runs", self.api_url, repo.owner, repo.repo);
+                let created = self.send_check_run(self.client.post(&url), token, &body).await?;
+                created["id"]
+                    .as_u64()
+                    .ok_or_else(|| RsrError::Platform("Check run response missing id".to_string()))?
+            }
+        };
+
+        // Further annotations are appended by updating the run
+        let url = format!("{}/repos/{}/{}/check-runs/{}", self.api_url, repo.owner, repo.repo, check_run_id);
+        for batch in batches {
+            let body = serde_json::json!({ "output": output(batch) });
+            self.send_check_run(self.client.patch(&url), token, &body).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Post the compliance summary as a pull request comment.
+    ///
+    /// A summary posted earlier is edited in place, so each pull request keeps
+    /// a single, current RSR comment.
+    pub async fn post_summary_comment(&self, repo: &RepoRef, number: u64, status: &ComplianceStatus) -> Result<()> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required for posting comments".to_string()));
+        };
+
+        let body = format!("{}\n{}", SUMMARY_COMMENT_MARKER, check_run_summary(status));
+
+        match self.find_summary_comment(token, repo, number).await? {
+            Some(id) => self.update_comment(repo, &id, &body).await,
+            None => self.post_comment(repo, number, &body).await.map(|_| ()),
+        }
+    }
+
+    /// Id of an earlier RSR summary comment on the issue or pull request, if any
+    async fn find_summary_comment(&self, token: &str, repo: &RepoRef, number: u64) -> Result<Option<String>> {
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}/comments?per_page=100",
+            self.api_url, repo.owner, repo.repo, number
+        );
+
+        let comments = fetch_all_pages(
+            &self.http,
+            &url,
+            |page| {
+                self.client
+                    .get(page)
+                    .header("Authorization", format!("Bearer {}", token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("X-GitHub-Api-Version", "2022-11-28")
+                    .header("User-Agent", "RSR-Certified/0.1")
+            },
+            |page| match page {
+                serde_json::Value::Array(comments) => comments,
+                _ => Vec::new(),
+            },
+        )
+        .await?;
+
+        Ok(comments
+            .iter()
+            .find(|comment| comment["body"].as_str().is_some_and(|b| b.starts_with(SUMMARY_COMMENT_MARKER)))
+            .and_then(|comment| comment["id"].as_u64())
+            .map(|id| id.to_string()))
+    }
+
+    async fn send_comment(&self, request: reqwest::RequestBuilder, token: &str, body: &str) -> Result<serde_json::Value> {
+        let response = request
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "RSR-Certified/0.1")
+            .json(&serde_json::json!({ "body": body }))
+            .send_via(&self.http)
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RsrError::Platform(format!("Failed to post comment: {}", error_text)));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Id of an existing RSR check run on the commit, if any
+    async fn find_check_run(&self, token: &str, repo: &RepoRef, commit_sha: &str) -> Result<Option<u64>> {
+        let url = format!(
+            "{}/repos/{}/{}/commits/{}/check-runs?check_name={}&per_page=100",
+            self.api_url,
+            repo.owner,
+            repo.repo,
+            commit_sha,
+            urlencoding::encode(CHECK_NAME)
+        );
+
+        let runs = fetch_all_pages(
+            &self.http,
+            &url,
+            |page| {
+                self.client
+                    .get(page)
+                    .header("Authorization", format!("Bearer {}", token))
+                    .header("Accept", "application/vnd.github+json")
+                    .header("X-GitHub-Api-Version", "2022-11-28")
+                    .header("User-Agent", "RSR-Certified/0.1")
+            },
+            |mut page| match page["check_runs"].take() {
+                serde_json::Value::Array(runs) => runs,
+                _ => Vec::new(),
+            },
+        )
+        .await?;
+
+        Ok(runs.first().and_then(|run| run["id"].as_u64()))
+    }
+
+    /// Fetch a tree object via the Git Trees API
+    async fn fetch_tree(&self, token: &str, repo: &RepoRef, tree_ish: &str, recursive: bool) -> Result<serde_json::Value> {
+        let mut url = format!(
+            "{}/repos/{}/{}/git/trees/{}",
+            self.api_url, repo.owner, repo.repo, tree_ish
+        );
+        if recursive {
+            url.push_str("?recursive=1");
+        }
+
+        let response = self.client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "RSR-Certified/0.1")
+            .fetch_via(&self.http)
+            .await?;
+
+        if response.status == reqwest::StatusCode::NOT_FOUND {
+            return Err(RsrError::RepoNotFound {
+                owner: repo.owner.clone(),
+                repo: repo.repo.clone(),
+            });
+        }
+
+        response.json()
+    }
+
+    /// List files by fetching each subtree non-recursively, skipping
+    /// directories outside `prefix`
+    async fn walk_tree(
+        &self,
+        token: &str,
+        repo: &RepoRef,
+        root: String,
+        prefix: Option<&str>,
+        files: &mut Vec<String>,
+    ) -> Result<()> {
+        let prefix = prefix.map(|p| p.trim_matches('/')).unwrap_or("");
+        let mut pending = vec![(root, String::new())];
+
+        while let Some((sha, dir)) = pending.pop() {
+            let tree = self.fetch_tree(token, repo, &sha, false).await?;
+            files.extend(tree_blobs(&tree, &dir));
+
+            for entry in tree["tree"].as_array().into_iter().flatten() {
+                if entry["type"].as_str() != Some("tree") {
+                    continue;
+                }
+                let (Some(name), Some(sha)) = (entry["path"].as_str(), entry["sha"].as_str()) else {
+                    continue;
+                };
+
+                let path = join_tree_path(&dir, name);
+                let overlaps = prefix.is_empty()
+                    || path == prefix
+                    || path.starts_with(&format!("{}/", prefix))
+                    || prefix.starts_with(&format!("{}/", path));
+                if overlaps {
+                    pending.push((sha.to_string(), path));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn send_check_run(
+        &self,
+        request: reqwest::RequestBuilder,
+        token: &str,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let response = request
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "RSR-Certified/0.1")
+            .json(body)
+            .send_via(&self.http)
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RsrError::Platform(format!("Failed to post check run: {}", error_text)));
+        }
+
+        Ok(response.json().await?)
+    }
 }
 
 #[async_trait]
@@ -41,53 +453,65 @@ fn platform_id(&self) -> &'static str {
         "github"
     }
 
+    fn capabilities(&self) -> AdapterCapabilities {
+        AdapterCapabilities {
+            check_runs: true,
+            comments: true,
+            reviews: true,
+            ..Default::default()
+        }
+    }
+
     fn verify_webhook(&self, payload: &[u8], headers: &Headers) -> Result<bool> {
         let Some(ref secret) = self.config.webhook_secret else {
-            // No secret configured - skip verification (not recommended for production)
-            tracing::warn!("Webhook secret not configured - skipping signature verification");
-            return Ok(true);
+            return self.config.unsigned_webhook(self.platform_id());
         };
 
-        let Some(signature) = headers.get("x-hub-signature-256") else {
-            return Err(RsrError::WebhookVerification);
+        // GitHub signature format: sha256=<hex>. Older GHES only sends sha1=<hex>.
+        let (algorithm, signature) = match (headers.get("x-hub-signature-256"), headers.get("x-hub-signature")) {
+            (Some(signature), _) => (HmacAlgorithm::Sha256, signature),
+            (None, Some(signature)) if self.config.allow_legacy_signatures => (HmacAlgorithm::Sha1, signature),
+            _ => return Err(RsrError::WebhookVerification),
         };
-
-        // GitHub signature format: sha256=<hex>
-        let expected_prefix = "sha256=";
-        if !signature.starts_with(expected_prefix) {
+        if !signature.starts_with(algorithm.prefix()) {
             return Err(RsrError::WebhookVerification);
         }
-        let signature_hex = &signature[expected_prefix.len()..];
 
-        let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
-            .map_err(|_| RsrError::WebhookVerification)?;
-        mac.update(payload);
-        let result = mac.finalize();
-        let computed = hex::encode(result.into_bytes());
+        Ok(verify_hmac(algorithm, secret.as_bytes(), payload, signature))
+    }
 
-        // Constant-time comparison
-        Ok(constant_time_eq(signature_hex.as_bytes(), computed.as_bytes()))
+    fn delivery_id(&self, headers: &Headers) -> Option<String> {
+        headers.get("x-github-delivery").cloned()
     }
 
     fn parse_webhook(&self, payload: &[u8], headers: &Headers) -> Result<RepoEvent> {
         let event_type = Self::get_event_type(headers)
             .ok_or_else(|| RsrError::Platform("Missing X-GitHub-Event header".to_string()))?;
 
-        let json: serde_json::Value = serde_json::from_slice(payload)?;
-
         match event_type {
-            "push" => parse_push_event(&json),
-            "pull_request" => parse_pull_request_event(&json),
-            "issues" => parse_issue_event(&json),
-            "release" => parse_release_event(&json),
-            "security_advisory" | "dependabot_alert" => parse_security_event(&json),
-            "workflow_run" => parse_workflow_event(&json),
-            "issue_comment" | "pull_request_review_comment" => parse_comment_event(&json, event_type),
+            "push" => parse_push_event(payload),
+            "pull_request" => parse_pull_request_event(payload),
+            "issues" => parse_issue_event(payload),
+            "release" => parse_release_event(payload),
+            "security_advisory" | "dependabot_alert" => parse_security_event(payload, event_type),
+            "workflow_run" => parse_workflow_event(payload),
+            "issue_comment" | "pull_request_review_comment" => parse_comment_event(payload, event_type),
+            "repository" => parse_repository_event(payload),
+            "fork" => parse_fork_event(payload),
+            "check_suite" => parse_check_suite_event(payload),
+            "deployment" | "deployment_status" => parse_deployment_event(payload, event_type),
+            "branch_protection_rule" => parse_branch_protection_event(payload),
+            "member" => parse_member_event(payload),
+            "team" => parse_team_event(payload),
             _ => Err(RsrError::Platform(format!("Unsupported event type: {}", event_type))),
         }
     }
 
     async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &ComplianceStatus) -> Result<()> {
+        if self.config.use_check_runs {
+            return self.post_check_run(repo, commit_sha, status).await;
+        }
+
         let Some(ref token) = self.config.api_token else {
             return Err(RsrError::Config("API token required for posting status".to_string()));
         };
@@ -107,7 +531,7 @@ async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &Complianc
             "state": state,
             "target_url": format!("https://rsr-certified.dev/report/{}/{}", repo.owner, repo.repo),
             "description": format!("RSR Compliance: {} ({:.0}%)", status.tier.code(), status.score * 100.0),
-            "context": "RSR / Compliance Check"
+            "context": CHECK_NAME
         });
 
         let response = self.client
@@ -117,7 +541,7 @@ async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &Complianc
             .header("X-GitHub-Api-Version", "2022-11-28")
             .header("User-Agent", "RSR-Certified/0.1")
             .json(&body)
-            .send()
+            .send_via(&self.http)
             .await?;
 
         if !response.status().is_success() {
@@ -128,6 +552,141 @@ async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &Complianc
         Ok(())
     }
 
+    /// Pull requests share the issue comment API
+    async fn post_comment(&self, repo: &RepoRef, number: u64, body: &str) -> Result<String> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required for posting comments".to_string()));
+        };
+
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}/comments",
+            self.api_url, repo.owner, repo.repo, number
+        );
+        let created = self.send_comment(self.client.post(&url), token, body).await?;
+
+        created["id"]
+            .as_u64()
+            .map(|id| id.to_string())
+            .ok_or_else(|| RsrError::Platform("Comment response missing id".to_string()))
+    }
+
+    async fn update_comment(&self, repo: &RepoRef, comment_id: &str, body: &str) -> Result<()> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required for posting comments".to_string()));
+        };
+
+        let url = format!(
+            "{}/repos/{}/{}/issues/comments/{}",
+            self.api_url, repo.owner, repo.repo, comment_id
+        );
+        self.send_comment(self.client.patch(&url), token, body).await?;
+
+        Ok(())
+    }
+
+    async fn request_changes(&self, repo: &RepoRef, number: u64, body: &str) -> Result<()> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required for submitting reviews".to_string()));
+        };
+
+        let url = format!(
+            "{}/repos/{}/{}/pulls/{}/reviews",
+            self.api_url, repo.owner, repo.repo, number
+        );
+
+        let response = self.client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "RSR-Certified/0.1")
+            .json(&serde_json::json!({
+                "event": "REQUEST_CHANGES",
+                "body": body,
+            }))
+            .send_via(&self.http)
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RsrError::Platform(format!("Failed to submit review: {}", error_text)));
+        }
+
+        Ok(())
+    }
+
+    /// Fetch blobs through GraphQL, many per query. Binary or truncated blobs,
+    /// which GraphQL can't return as text, fall back to REST.
+    async fn fetch_files(&self, repo: &RepoRef, paths: &[&str]) -> Result<HashMap<String, Vec<u8>>> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required".to_string()));
+        };
+
+        let branch = repo.branch.as_deref().unwrap_or("HEAD");
+        let mut files = HashMap::new();
+        let mut fallback = Vec::new();
+
+        for chunk in paths.chunks(GRAPHQL_BLOBS_PER_QUERY) {
+            let (query, variables) = blobs_query(repo, branch, chunk);
+
+            let response = self.client
+                .post(self.graphql_url())
+                .header("Authorization", format!("Bearer {}", token))
+                .header("User-Agent", "RSR-Certified/0.1")
+                .json(&serde_json::json!({ "query": query, "variables": variables }))
+                .send_via(&self.http)
+                .await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(RsrError::Platform(format!("GraphQL request failed ({}): {}", status, error_text)));
+            }
+
+            let json: serde_json::Value = response.json().await?;
+            let repository = &json["data"]["repository"];
+            if repository.is_null() {
+                if let Some(errors) = json["errors"].as_array().filter(|e| !e.is_empty()) {
+                    if errors.iter().all(|e| e["type"] != "NOT_FOUND") {
+                        return Err(RsrError::Platform(format!("GraphQL query failed: {}", json["errors"])));
+                    }
+                }
+                return Err(RsrError::RepoNotFound {
+                    owner: repo.owner.clone(),
+                    repo: repo.repo.clone(),
+                });
+            }
+
+            for (i, path) in chunk.iter().enumerate() {
+                let blob = &repository[format!("f{}", i)];
+                if blob.is_null() {
+                    continue;
+                }
+                match blob["text"].as_str() {
+                    Some(text) if blob["isTruncated"] != true => {
+                        files.insert(path.to_string(), text.as_bytes().to_vec());
+                    }
+                    _ => fallback.push(*path),
+                }
+            }
+        }
+
+        if !fallback.is_empty() {
+            tracing::debug!("Fetching {} binary or large blobs over REST", fallback.len());
+            for path in fallback {
+                match self.fetch_file(repo, path).await {
+                    Ok(content) => {
+                        files.insert(path.to_string(), content);
+                    }
+                    Err(RsrError::RepoNotFound { .. }) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
     async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
         let Some(ref token) = self.config.api_token else {
             return Err(RsrError::Config("API token required".to_string()));
@@ -145,21 +704,21 @@ async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
             .header("Accept", "application/vnd.github.raw+json")
             .header("X-GitHub-Api-Version", "2022-11-28")
             .header("User-Agent", "RSR-Certified/0.1")
-            .send()
+            .fetch_via(&self.http)
             .await?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
+        if response.status == reqwest::StatusCode::NOT_FOUND {
             return Err(RsrError::RepoNotFound {
                 owner: repo.owner.clone(),
                 repo: repo.repo.clone(),
             });
         }
 
-        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
-            return Err(RsrError::RateLimited);
+        if !response.status.is_success() {
+            return Err(RsrError::Platform(format!("Failed to fetch {} ({}): {}", path, response.status, response.text())));
         }
 
-        Ok(response.bytes().await?.to_vec())
+        Ok(response.body)
     }
 
     async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<String>> {
@@ -168,37 +727,19 @@ async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<Str
         };
 
         let branch = repo.branch.as_deref().unwrap_or("HEAD");
-        let url = match path {
-            Some(p) => format!(
-                "{}/repos/{}/{}/contents/{}?ref={}",
-                self.api_url, repo.owner, repo.repo, p, branch
-            ),
-            None => format!(
-                "{}/repos/{}/{}/contents?ref={}",
-                self.api_url, repo.owner, repo.repo, branch
-            ),
-        };
-
-        let response = self.client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("Accept", "application/vnd.github+json")
-            .header("X-GitHub-Api-Version", "2022-11-28")
-            .header("User-Agent", "RSR-Certified/0.1")
-            .send()
-            .await?;
-
-        let json: serde_json::Value = response.json().await?;
-
-        let files: Vec<String> = json
-            .as_array()
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|item| item["path"].as_str().map(String::from))
-                    .collect()
-            })
-            .unwrap_or_default();
+        let tree = self.fetch_tree(token, repo, branch, true).await?;
+
+        let mut files = Vec::new();
+        if tree["truncated"].as_bool().unwrap_or(false) {
+            // Recursive listing is capped by GitHub; walk the tree level by level
+            tracing::debug!("Tree for {} truncated, walking subtrees", repo);
+            let root = tree["sha"].as_str().unwrap_or(branch).to_string();
+            self.walk_tree(token, repo, root, path, &mut files).await?;
+        } else {
+            files.extend(tree_blobs(&tree, ""));
+        }
 
+        files.retain(|file| super::matches_prefix(file, path));
         Ok(files)
     }
 
@@ -218,10 +759,10 @@ async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
             .header("Accept", "application/vnd.github+json")
             .header("X-GitHub-Api-Version", "2022-11-28")
             .header("User-Agent", "RSR-Certified/0.1")
-            .send()
+            .fetch_via(&self.http)
             .await?;
 
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = response.json()?;
 
         Ok(RepoMetadata {
             default_branch: json["default_branch"].as_str().unwrap_or("main").to_string(),
@@ -235,6 +776,8 @@ async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
             open_issues_count: json["open_issues_count"].as_u64().unwrap_or(0) as u32,
             stargazers_count: json["stargazers_count"].as_u64().unwrap_or(0) as u32,
             forks_count: json["forks_count"].as_u64().unwrap_or(0) as u32,
+            upstream: json["parent"]["full_name"].as_str().map(String::from),
+            archived: json["archived"].as_bool().unwrap_or(false),
             license: json["license"]["spdx_id"].as_str().map(String::from),
             topics: json["topics"]
                 .as_array()
@@ -246,67 +789,395 @@ async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
                 .map(|dt| dt.with_timezone(&chrono::Utc)),
         })
     }
+
+    async fn list_commits(&self, repo: &RepoRef, limit: usize) -> Result<Vec<Commit>> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required".to_string()));
+        };
+
+        let branch = repo.branch.as_deref().unwrap_or("HEAD");
+        let url = format!(
+            "{}/repos/{}/{}/commits?sha={}&per_page={}",
+            self.api_url,
+            repo.owner,
+            repo.repo,
+            branch,
+            limit.clamp(1, 100)
+        );
+
+        let response = self.client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "RSR-Certified/0.1")
+            .fetch_via(&self.http)
+            .await?;
+
+        if !response.status.is_success() {
+            return Err(RsrError::Platform(format!("Failed to list commits ({}): {}", response.status, response.text())));
+        }
+
+        let json: serde_json::Value = response.json()?;
+        Ok(json
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(limit)
+            .map(|c| {
+                let git_author = &c["commit"]["author"];
+                // `author` is the GitHub account the commit email is linked to, if any
+                let login = c["author"]["login"].as_str();
+                Commit {
+                    sha: c["sha"].as_str().unwrap_or_default().to_string(),
+                    message: c["commit"]["message"].as_str().unwrap_or_default().to_string(),
+                    author: User {
+                        id: login.unwrap_or_default().to_string(),
+                        username: login.or(git_author["name"].as_str()).unwrap_or_default().to_string(),
+                        email: git_author["email"].as_str().map(String::from),
+                        avatar_url: c["author"]["avatar_url"].as_str().map(String::from),
+                    },
+                    timestamp: git_author["date"].as_str().unwrap_or_default().to_string(),
+                    added: Vec::new(),
+                    modified: Vec::new(),
+                    removed: Vec::new(),
+                }
+            })
+            .collect())
+    }
+
+    async fn is_member(&self, owner: &str, username: &str) -> Result<Option<bool>> {
+        if owner.eq_ignore_ascii_case(username) {
+            return Ok(Some(true));
+        }
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required".to_string()));
+        };
+
+        // Answers 204 for members and 404 otherwise, including when `owner`
+        // is a user rather than an organization
+        let url = format!("{}/orgs/{}/members/{}", self.api_url, owner, username);
+        let response = self.client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "RSR-Certified/0.1")
+            .send_via(&self.http)
+            .await?;
+
+        match response.status() {
+            reqwest::StatusCode::NO_CONTENT => Ok(Some(true)),
+            reqwest::StatusCode::NOT_FOUND => Ok(Some(false)),
+            status => Err(RsrError::Platform(format!("Failed to check membership of {} in {} ({})", username, owner, status))),
+        }
+    }
+
+    async fn list_tags(&self, repo: &RepoRef, limit: usize) -> Result<Vec<String>> {
+        let json = self.list_json(repo, "tags", limit).await?;
+        Ok(json
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(limit)
+            .filter_map(|tag| tag["name"].as_str().map(String::from))
+            .collect())
+    }
+
+    async fn list_releases(&self, repo: &RepoRef, limit: usize) -> Result<Vec<Release>> {
+        let json = self.list_json(repo, "releases", limit).await?;
+        Ok(json
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(limit)
+            .map(|release| Release {
+                tag: release["tag_name"].as_str().unwrap_or_default().to_string(),
+                name: release["name"].as_str().map(String::from),
+                notes: release["body"].as_str().map(String::from),
+                draft: release["draft"].as_bool().unwrap_or(false),
+                prerelease: release["prerelease"].as_bool().unwrap_or(false),
+                assets: release["assets"].as_array().map_or(0, Vec::len),
+                published_at: release["published_at"]
+                    .as_str()
+                    .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+                    .map(|at| at.with_timezone(&chrono::Utc)),
+            })
+            .collect())
+    }
+
+    async fn list_workflow_runs(&self, repo: &RepoRef, limit: usize) -> Result<Vec<WorkflowRun>> {
+        let json = self.list_json(repo, "actions/runs", limit).await?;
+        Ok(json["workflow_runs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(limit)
+            .map(|run| WorkflowRun {
+                workflow: run["name"].as_str().unwrap_or_default().to_string(),
+                head_sha: run["head_sha"].as_str().unwrap_or_default().to_string(),
+                attempt: run["run_attempt"].as_u64().map_or(1, |attempt| attempt as u32),
+                conclusion: run["conclusion"].as_str().map(|conclusion| parse_conclusion("workflow_run", conclusion)),
+                created_at: run["created_at"]
+                    .as_str()
+                    .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+                    .map(|at| at.with_timezone(&chrono::Utc)),
+            })
+            .collect())
+    }
+
+    async fn commit_signatures(&self, repo: &RepoRef, limit: usize) -> Result<Vec<SignedObject>> {
+        let mut listing = format!("commits?per_page={}", limit.clamp(1, 100));
+        if let Some(ref branch) = repo.branch {
+            listing.push_str(&format!("&sha={}", branch));
+        }
+        let json = self.get_json(repo, &listing).await?.unwrap_or_default();
+        Ok(json
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(limit)
+            .map(|c| SignedObject {
+                id: c["sha"].as_str().unwrap_or_default().to_string(),
+                signature: signature(&c["commit"]["verification"]),
+            })
+            .collect())
+    }
+
+    async fn tag_signatures(&self, repo: &RepoRef, limit: usize) -> Result<Vec<SignedObject>> {
+        let mut signed = Vec::new();
+        for tag in self.list_tags(repo, limit).await? {
+            // Only annotated tags are objects that can carry a signature
+            let signature = match self.get_json(repo, &format!("git/ref/tags/{}", tag)).await? {
+                Some(reference) if reference["object"]["type"].as_str() == Some("tag") => {
+                    let sha = reference["object"]["sha"].as_str().unwrap_or_default();
+                    self.get_json(repo, &format!("git/tags/{}", sha))
+                        .await?
+                        .and_then(|object| signature(&object["verification"]))
+                }
+                _ => None,
+            };
+            signed.push(SignedObject { id: tag, signature });
+        }
+        Ok(signed)
+    }
+
+    async fn get_branch_protection(&self, repo: &RepoRef, branch: &str) -> Result<Option<BranchProtection>> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required".to_string()));
+        };
+
+        let url = format!(
+            "{}/repos/{}/{}/branches/{}/protection",
+            self.api_url, repo.owner, repo.repo, branch
+        );
+        let response = self.client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "RSR-Certified/0.1")
+            .fetch_via(&self.http)
+            .await?;
+
+        // "Branch not protected"
+        if response.status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status.is_success() {
+            return Err(RsrError::Platform(format!(
+                "Failed to read protection of {} ({}): {}",
+                branch,
+                response.status,
+                response.text()
+            )));
+        }
+
+        let json: serde_json::Value = response.json()?;
+        let checks = &json["required_status_checks"];
+        // Rules list each check both as a bare context and with its app
+        let mut contexts: Vec<String> = checks["contexts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .chain(checks["checks"].as_array().into_iter().flatten().map(|check| &check["context"]))
+            .filter_map(|context| context.as_str().map(String::from))
+            .collect();
+        contexts.sort();
+        contexts.dedup();
+
+        Ok(Some(BranchProtection {
+            required_approvals: json["required_pull_request_reviews"]["required_approving_review_count"]
+                .as_u64()
+                .unwrap_or(0) as u32,
+            required_status_checks: contexts,
+            allows_force_pushes: json["allow_force_pushes"]["enabled"].as_bool().unwrap_or(false),
+            requires_signed_commits: json["required_signatures"]["enabled"].as_bool().unwrap_or(false),
+        }))
+    }
 }
 
-// Helper function for constant-time comparison
-fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
-    if a.len() != b.len() {
-        return false;
+/// Signature described by a commit or tag's `verification` object, or
+/// `None` if the object is unsigned
+fn signature(verification: &serde_json::Value) -> Option<Signature> {
+    let reason = verification["reason"].as_str().unwrap_or("unsigned");
+    if reason == "unsigned" {
+        return None;
     }
-    a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+    let verified = verification["verified"].as_bool().unwrap_or(false);
+    Some(Signature {
+        format: verification["signature"].as_str().and_then(SignatureFormat::detect),
+        verified,
+        reason: (!verified).then(|| reason.to_string()),
+    })
 }
 
-// Event parsing helpers
+// Tree helpers
 
-fn parse_push_event(json: &serde_json::Value) -> Result<RepoEvent> {
-    let commits: Vec<Commit> = json["commits"]
+/// Paths of the blobs in a trees API response, relative to the repository root
+fn tree_blobs(tree: &serde_json::Value, dir: &str) -> Vec<String> {
+    tree["tree"]
         .as_array()
-        .map(|arr| {
-            arr.iter()
-                .map(|c| Commit {
-                    sha: c["id"].as_str().unwrap_or_default().to_string(),
-                    message: c["message"].as_str().unwrap_or_default().to_string(),
-                    author: User {
-                        id: c["author"]["username"].as_str().unwrap_or_default().to_string(),
-                        username: c["author"]["username"].as_str().unwrap_or_default().to_string(),
-                        email: c["author"]["email"].as_str().map(String::from),
-                        avatar_url: None,
-                    },
-                    timestamp: c["timestamp"].as_str().unwrap_or_default().to_string(),
-                    added: c["added"].as_array().map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect()).unwrap_or_default(),
-                    modified: c["modified"].as_array().map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect()).unwrap_or_default(),
-                    removed: c["removed"].as_array().map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect()).unwrap_or_default(),
-                })
-                .collect()
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry["type"].as_str() == Some("blob"))
+        .filter_map(|entry| entry["path"].as_str())
+        .map(|path| join_tree_path(dir, path))
+        .collect()
+}
+
+/// Query fetching each path as an aliased `object(expression:)` field (`f0`, `f1`, ...).
+/// Expressions are passed as variables so paths never need escaping.
+fn blobs_query(repo: &RepoRef, branch: &str, paths: &[&str]) -> (String, serde_json::Map<String, serde_json::Value>) {
+    let mut variables = serde_json::Map::new();
+    variables.insert("owner".to_string(), repo.owner.clone().into());
+    variables.insert("name".to_string(), repo.repo.clone().into());
+
+    let mut params = String::from("$owner: String!, $name: String!");
+    let mut fields = String::new();
+    for (i, path) in paths.iter().enumerate() {
+        params.push_str(&format!(", $e{}: String!", i));
+        fields.push_str(&format!(
+            " f{0}: object(expression: $e{0}) {{ ... on Blob {{ text isTruncated }} }}",
+            i
+        ));
+        variables.insert(format!("e{}", i), format!("{}:{}", branch, path).into());
+    }
+
+    let query = format!(
+        "query({}) {{ repository(owner: $owner, name: $name) {{{} }} }}",
+        params, fields
+    );
+    (query, variables)
+}
+
+fn join_tree_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+// Check run helpers
+
+/// Success when every check passes, neutral when a tier was still reached,
+/// failure when the repository doesn't reach Bronze
+fn check_run_conclusion(status: &ComplianceStatus) -> &'static str {
+    if status.checks.iter().all(|check| check.passed) {
+        "success"
+    } else if status.tier >= crate::CertificationTier::Bronze {
+        "neutral"
+    } else {
+        "failure"
+    }
+}
+
+fn check_annotation(check: &crate::CheckResult) -> serde_json::Value {
+    // Only Bronze failures block certification; higher-tier gaps are advisory
+    let level = if check.tier <= crate::CertificationTier::Bronze {
+        "failure"
+    } else {
+        "warning"
+    };
+
+    let mut message = check.message.clone();
+    if let Some(ref details) = check.details {
+        message.push_str("\n\n");
+        message.push_str(details);
+    }
+
+    serde_json::json!({
+        "path": ANNOTATION_PATH,
+        "start_line": 1,
+        "end_line": 1,
+        "annotation_level": level,
+        "title": format!("[{}] {}", check.tier.code(), check.name),
+        "message": message,
+        "raw_details": check.id,
+    })
+}
+
+/// Markdown rendering of the compliance report, for check runs and PR comments
+fn check_run_summary(status: &ComplianceStatus) -> String {
+    let mut summary = format!(
+        "## RSR Compliance: {} {}\n\n**Score:** {:.1}%\n\n| | Tier | Check | Result |\n|---|---|---|---|\n",
+        status.tier.symbol(),
+        status.tier.code(),
+        status.score * 100.0
+    );
+
+    for check in &status.checks {
+        summary.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            if check.passed { "✅" } else { "❌" },
+            check.tier.code(),
+            check.name,
+            check.message.replace('|', "\\|")
+        ));
+    }
+
+    summary
+}
+
+// Event parsing helpers
+
+fn parse_push_event(payload: &[u8]) -> Result<RepoEvent> {
+    let payload: PushPayload = decode_payload("push", payload)?;
+
+    let commits = payload
+        .commits
+        .into_iter()
+        .map(|c| Commit {
+            sha: c.id,
+            message: c.message,
+            author: c.author.into_commit_author(),
+            timestamp: c.timestamp,
+            added: c.added,
+            modified: c.modified,
+            removed: c.removed,
         })
-        .unwrap_or_default();
+        .collect();
 
     Ok(RepoEvent::Push(PushEvent {
-        repo_owner: json["repository"]["owner"]["login"].as_str().unwrap_or_default().to_string(),
-        repo_name: json["repository"]["name"].as_str().unwrap_or_default().to_string(),
-        branch: json["ref"].as_str().unwrap_or_default().replace("refs/heads/", ""),
-        before: json["before"].as_str().unwrap_or_default().to_string(),
-        after: json["after"].as_str().unwrap_or_default().to_string(),
+        repo_owner: payload.repository.owner.login,
+        repo_name: payload.repository.name,
+        branch: payload.git_ref.replace("refs/heads/", ""),
+        before: payload.before,
+        after: payload.after,
         commits,
-        pusher: User {
-            id: json["pusher"]["name"].as_str().unwrap_or_default().to_string(),
-            username: json["pusher"]["name"].as_str().unwrap_or_default().to_string(),
-            email: json["pusher"]["email"].as_str().map(String::from),
-            avatar_url: None,
-        },
+        pusher: payload.pusher.into(),
     }))
 }
 
-fn parse_pull_request_event(json: &serde_json::Value) -> Result<RepoEvent> {
-    let action = match json["action"].as_str().unwrap_or_default() {
+fn parse_pull_request_event(payload: &[u8]) -> Result<RepoEvent> {
+    let payload: PullRequestPayload = decode_payload("pull_request", payload)?;
+    let pr = payload.pull_request;
+
+    let action = match payload.action.as_str() {
         "opened" => PullRequestAction::Opened,
-        "closed" => {
-            if json["pull_request"]["merged"].as_bool().unwrap_or(false) {
-                PullRequestAction::Merged
-            } else {
-                PullRequestAction::Closed
-            }
-        }
+        "closed" if pr.merged => PullRequestAction::Merged,
+        "closed" => PullRequestAction::Closed,
         "reopened" => PullRequestAction::Reopened,
         "edited" => PullRequestAction::Edited,
         "synchronize" => PullRequestAction::Synchronize,
@@ -316,32 +1187,31 @@ fn parse_pull_request_event(json: &serde_json::Value) -> Result<RepoEvent> {
         "unlabeled" => PullRequestAction::Unlabeled,
         "ready_for_review" => PullRequestAction::ReadyForReview,
         "converted_to_draft" => PullRequestAction::ConvertedToDraft,
-        _ => PullRequestAction::Edited,
+        other => {
+            strict::unrecognized("pull_request", "action", other);
+            PullRequestAction::Edited
+        }
     };
 
-    let pr = &json["pull_request"];
-
     Ok(RepoEvent::PullRequest(PullRequestEvent {
-        repo_owner: json["repository"]["owner"]["login"].as_str().unwrap_or_default().to_string(),
-        repo_name: json["repository"]["name"].as_str().unwrap_or_default().to_string(),
+        repo_owner: payload.repository.owner.login,
+        repo_name: payload.repository.name,
         action,
-        number: pr["number"].as_u64().unwrap_or(0),
-        title: pr["title"].as_str().unwrap_or_default().to_string(),
-        body: pr["body"].as_str().map(String::from),
-        source_branch: pr["head"]["ref"].as_str().unwrap_or_default().to_string(),
-        target_branch: pr["base"]["ref"].as_str().unwrap_or_default().to_string(),
-        author: User {
-            id: pr["user"]["id"].as_u64().map(|n| n.to_string()).unwrap_or_default(),
-            username: pr["user"]["login"].as_str().unwrap_or_default().to_string(),
-            email: None,
-            avatar_url: pr["user"]["avatar_url"].as_str().map(String::from),
-        },
-        draft: pr["draft"].as_bool().unwrap_or(false),
+        number: pr.number,
+        title: pr.title,
+        body: pr.body,
+        source_branch: pr.head.git_ref,
+        target_branch: pr.base.git_ref,
+        head_sha: pr.head.sha,
+        author: pr.user.into(),
+        draft: pr.draft,
     }))
 }
 
-fn parse_issue_event(json: &serde_json::Value) -> Result<RepoEvent> {
-    let action = match json["action"].as_str().unwrap_or_default() {
+fn parse_issue_event(payload: &[u8]) -> Result<RepoEvent> {
+    let payload: IssuePayload = decode_payload("issues", payload)?;
+
+    let action = match payload.action.as_str() {
         "opened" => IssueAction::Opened,
         "closed" => IssueAction::Closed,
         "reopened" => IssueAction::Reopened,
@@ -350,169 +1220,470 @@ fn parse_issue_event(json: &serde_json::Value) -> Result<RepoEvent> {
         "unlabeled" => IssueAction::Unlabeled,
         "assigned" => IssueAction::Assigned,
         "unassigned" => IssueAction::Unassigned,
-        _ => IssueAction::Edited,
+        other => {
+            strict::unrecognized("issues", "action", other);
+            IssueAction::Edited
+        }
     };
 
-    let issue = &json["issue"];
+    let issue = payload.issue;
 
     Ok(RepoEvent::Issue(IssueEvent {
-        repo_owner: json["repository"]["owner"]["login"].as_str().unwrap_or_default().to_string(),
-        repo_name: json["repository"]["name"].as_str().unwrap_or_default().to_string(),
+        repo_owner: payload.repository.owner.login,
+        repo_name: payload.repository.name,
         action,
-        number: issue["number"].as_u64().unwrap_or(0),
-        title: issue["title"].as_str().unwrap_or_default().to_string(),
-        body: issue["body"].as_str().map(String::from),
-        author: User {
-            id: issue["user"]["id"].as_u64().map(|n| n.to_string()).unwrap_or_default(),
-            username: issue["user"]["login"].as_str().unwrap_or_default().to_string(),
-            email: None,
-            avatar_url: issue["user"]["avatar_url"].as_str().map(String::from),
-        },
-        labels: issue["labels"]
-            .as_array()
-            .map(|arr| arr.iter().filter_map(|l| l["name"].as_str().map(String::from)).collect())
-            .unwrap_or_default(),
+        number: issue.number,
+        title: issue.title,
+        body: issue.body,
+        author: issue.user.into(),
+        labels: issue.labels.into_iter().map(|l| l.name).collect(),
     }))
 }
 
-fn parse_release_event(json: &serde_json::Value) -> Result<RepoEvent> {
-    let action = match json["action"].as_str().unwrap_or_default() {
+fn parse_release_event(payload: &[u8]) -> Result<RepoEvent> {
+    let payload: ReleasePayload = decode_payload("release", payload)?;
+
+    let action = match payload.action.as_str() {
         "published" => ReleaseAction::Published,
         "created" => ReleaseAction::Created,
         "edited" => ReleaseAction::Edited,
         "deleted" => ReleaseAction::Deleted,
         "prereleased" => ReleaseAction::Prereleased,
         "released" => ReleaseAction::Released,
-        _ => ReleaseAction::Created,
+        other => {
+            strict::unrecognized("release", "action", other);
+            ReleaseAction::Created
+        }
     };
 
-    let release = &json["release"];
+    let release = payload.release;
 
     Ok(RepoEvent::Release(ReleaseEvent {
-        repo_owner: json["repository"]["owner"]["login"].as_str().unwrap_or_default().to_string(),
-        repo_name: json["repository"]["name"].as_str().unwrap_or_default().to_string(),
+        repo_owner: payload.repository.owner.login,
+        repo_name: payload.repository.name,
         action,
-        tag_name: release["tag_name"].as_str().unwrap_or_default().to_string(),
-        name: release["name"].as_str().map(String::from),
-        body: release["body"].as_str().map(String::from),
-        draft: release["draft"].as_bool().unwrap_or(false),
-        prerelease: release["prerelease"].as_bool().unwrap_or(false),
-        author: User {
-            id: release["author"]["id"].as_u64().map(|n| n.to_string()).unwrap_or_default(),
-            username: release["author"]["login"].as_str().unwrap_or_default().to_string(),
-            email: None,
-            avatar_url: release["author"]["avatar_url"].as_str().map(String::from),
-        },
+        tag_name: release.tag_name,
+        name: release.name,
+        body: release.body,
+        draft: release.draft,
+        prerelease: release.prerelease,
+        author: release.author.into(),
     }))
 }
 
-fn parse_security_event(json: &serde_json::Value) -> Result<RepoEvent> {
-    let action = match json["action"].as_str().unwrap_or_default() {
+fn parse_security_event(payload: &[u8], event_type: &str) -> Result<RepoEvent> {
+    let payload: SecurityPayload = decode_payload(event_type, payload)?;
+
+    let action = match payload.action.as_str() {
         "created" => SecurityAlertAction::Created,
         "dismissed" => SecurityAlertAction::Dismissed,
         "fixed" => SecurityAlertAction::Fixed,
         "reopened" => SecurityAlertAction::Reopened,
-        _ => SecurityAlertAction::Created,
+        other => {
+            strict::unrecognized(event_type, "action", other);
+            SecurityAlertAction::Created
+        }
     };
 
-    let alert = json.get("alert").or(json.get("security_advisory"));
-    let severity = alert
-        .and_then(|a| a["severity"].as_str())
-        .map(|s| match s.to_lowercase().as_str() {
-            "critical" => Severity::Critical,
-            "high" => Severity::High,
-            "medium" | "moderate" => Severity::Medium,
-            "low" => Severity::Low,
-            _ => Severity::Unknown,
-        })
-        .unwrap_or(Severity::Unknown);
+    let alert = payload.alert.or(payload.security_advisory).unwrap_or_default();
+    let severity = alert.severity.as_deref().map_or(Severity::Unknown, Severity::from_label);
 
     Ok(RepoEvent::SecurityAlert(SecurityAlertEvent {
-        repo_owner: json["repository"]["owner"]["login"].as_str().unwrap_or_default().to_string(),
-        repo_name: json["repository"]["name"].as_str().unwrap_or_default().to_string(),
+        repo_owner: payload.repository.owner.login,
+        repo_name: payload.repository.name,
         action,
         severity,
-        package_name: alert.and_then(|a| a["package"]["name"].as_str().map(String::from)),
-        vulnerable_version: alert.and_then(|a| a["vulnerable_version_range"].as_str().map(String::from)),
-        patched_version: alert.and_then(|a| a["patched_versions"].as_str().map(String::from)),
-        cve_id: alert.and_then(|a| a["cve_id"].as_str().map(String::from)),
+        package_name: alert.package.map(|p| p.name),
+        vulnerable_version: alert.vulnerable_version_range,
+        patched_version: alert.patched_versions,
+        cve_id: alert.cve_id,
     }))
 }
 
-fn parse_workflow_event(json: &serde_json::Value) -> Result<RepoEvent> {
-    let workflow = &json["workflow_run"];
+fn parse_workflow_event(payload: &[u8]) -> Result<RepoEvent> {
+    let payload: WorkflowRunPayload = decode_payload("workflow_run", payload)?;
+    let workflow = payload.workflow_run;
 
-    let action = match json["action"].as_str().unwrap_or_default() {
+    let action = match payload.action.as_str() {
         "requested" => WorkflowAction::Requested,
         "completed" => WorkflowAction::Completed,
         "in_progress" => WorkflowAction::InProgress,
-        _ => WorkflowAction::Requested,
+        other => {
+            strict::unrecognized("workflow_run", "action", other);
+            WorkflowAction::Requested
+        }
     };
 
-    let status = match workflow["status"].as_str().unwrap_or_default() {
-        "queued" => WorkflowStatus::Queued,
-        "in_progress" => WorkflowStatus::InProgress,
-        "completed" => WorkflowStatus::Completed,
-        _ => WorkflowStatus::Queued,
-    };
+    Ok(RepoEvent::WorkflowRun(WorkflowEvent {
+        repo_owner: payload.repository.owner.login,
+        repo_name: payload.repository.name,
+        workflow_name: workflow.name,
+        action,
+        status: parse_run_status("workflow_run", workflow.status.as_deref()),
+        conclusion: workflow.conclusion.as_deref().map(|conclusion| parse_conclusion("workflow_run", conclusion)),
+        branch: workflow.head_branch.unwrap_or_default(),
+        commit_sha: workflow.head_sha,
+    }))
+}
 
-    let conclusion = workflow["conclusion"].as_str().map(|s| match s {
+fn parse_run_status(event: &str, status: Option<&str>) -> WorkflowStatus {
+    match status {
+        Some("in_progress") => WorkflowStatus::InProgress,
+        Some("completed") => WorkflowStatus::Completed,
+        Some("queued" | "requested" | "waiting" | "pending") | None => WorkflowStatus::Queued,
+        Some(other) => {
+            strict::unrecognized(event, "status", other);
+            WorkflowStatus::Queued
+        }
+    }
+}
+
+fn parse_conclusion(event: &str, conclusion: &str) -> WorkflowConclusion {
+    match conclusion {
         "success" => WorkflowConclusion::Success,
         "failure" => WorkflowConclusion::Failure,
-        "cancelled" => WorkflowConclusion::Cancelled,
-        "skipped" => WorkflowConclusion::Skipped,
+        "cancelled" | "stale" => WorkflowConclusion::Cancelled,
+        "skipped" | "neutral" => WorkflowConclusion::Skipped,
         "timed_out" => WorkflowConclusion::TimedOut,
         "action_required" => WorkflowConclusion::ActionRequired,
-        _ => WorkflowConclusion::Failure,
-    });
+        other => {
+            strict::unrecognized(event, "conclusion", other);
+            WorkflowConclusion::Failure
+        }
+    }
+}
 
-    Ok(RepoEvent::WorkflowRun(WorkflowEvent {
-        repo_owner: json["repository"]["owner"]["login"].as_str().unwrap_or_default().to_string(),
-        repo_name: json["repository"]["name"].as_str().unwrap_or_default().to_string(),
-        workflow_name: workflow["name"].as_str().unwrap_or_default().to_string(),
+fn parse_check_suite_event(payload: &[u8]) -> Result<RepoEvent> {
+    let payload: CheckSuitePayload = decode_payload("check_suite", payload)?;
+    let suite = payload.check_suite;
+
+    let action = match payload.action.as_str() {
+        "requested" => CheckSuiteAction::Requested,
+        "rerequested" => CheckSuiteAction::Rerequested,
+        "completed" => CheckSuiteAction::Completed,
+        other => {
+            return Err(RsrError::Platform(format!("Unsupported check_suite action: {}", other)));
+        }
+    };
+
+    Ok(RepoEvent::CheckSuite(CheckSuiteEvent {
+        repo_owner: payload.repository.owner.login,
+        repo_name: payload.repository.name,
         action,
-        status,
-        conclusion,
-        branch: workflow["head_branch"].as_str().unwrap_or_default().to_string(),
-        commit_sha: workflow["head_sha"].as_str().unwrap_or_default().to_string(),
+        app: suite.app.map(|app| app.slug),
+        status: parse_run_status("check_suite", suite.status.as_deref()),
+        conclusion: suite.conclusion.as_deref().map(|conclusion| parse_conclusion("check_suite", conclusion)),
+        branch: suite.head_branch,
+        commit_sha: suite.head_sha,
     }))
 }
 
-fn parse_comment_event(json: &serde_json::Value, event_type: &str) -> Result<RepoEvent> {
-    let action = match json["action"].as_str().unwrap_or_default() {
+fn parse_deployment_event(payload: &[u8], event_type: &str) -> Result<RepoEvent> {
+    let payload: DeploymentPayload = decode_payload(event_type, payload)?;
+    let deployment = payload.deployment;
+
+    let (state, description) = match (event_type, payload.deployment_status) {
+        ("deployment", _) => (DeploymentState::Created, deployment.description),
+        (_, Some(status)) => {
+            let state = match status.state.as_str() {
+                "pending" => DeploymentState::Pending,
+                "queued" => DeploymentState::Queued,
+                "in_progress" => DeploymentState::InProgress,
+                "success" => DeploymentState::Success,
+                "failure" => DeploymentState::Failure,
+                "inactive" => DeploymentState::Inactive,
+                "error" => DeploymentState::Error,
+                other => {
+                    strict::unrecognized(event_type, "deployment_status.state", other);
+                    DeploymentState::Error
+                }
+            };
+            (state, status.description)
+        }
+        (_, None) => {
+            return Err(RsrError::Platform(
+                "Invalid deployment_status payload: missing field `deployment_status`".to_string(),
+            ));
+        }
+    };
+
+    Ok(RepoEvent::Deployment(DeploymentEvent {
+        repo_owner: payload.repository.owner.login,
+        repo_name: payload.repository.name,
+        deployment_id: deployment.id,
+        environment: deployment.environment,
+        state,
+        branch: deployment.git_ref,
+        commit_sha: deployment.sha,
+        description,
+    }))
+}
+
+fn parse_branch_protection_event(payload: &[u8]) -> Result<RepoEvent> {
+    let payload: BranchProtectionPayload = decode_payload("branch_protection_rule", payload)?;
+    let rule = payload.rule;
+
+    let action = match payload.action.as_str() {
+        "created" => BranchProtectionAction::Created,
+        "edited" => BranchProtectionAction::Edited,
+        "deleted" => BranchProtectionAction::Deleted,
+        other => {
+            return Err(RsrError::Platform(format!("Unsupported branch_protection_rule action: {}", other)));
+        }
+    };
+
+    Ok(RepoEvent::BranchProtection(BranchProtectionEvent {
+        repo_owner: payload.repository.owner.login,
+        repo_name: payload.repository.name,
+        action,
+        pattern: rule.name,
+        required_approvals: rule.required_approving_review_count,
+        required_status_checks: rule.required_status_checks,
+        enforce_admins: rule.admin_enforced,
+        allow_force_pushes: rule
+            .allow_force_pushes_enforcement_level
+            .is_some_and(|level| level != "off"),
+    }))
+}
+
+fn parse_member_event(payload: &[u8]) -> Result<RepoEvent> {
+    let payload: MemberPayload = decode_payload("member", payload)?;
+
+    let action = match payload.action.as_str() {
+        "added" => OrganizationAction::MemberAdded,
+        "removed" => OrganizationAction::MemberRemoved,
+        "edited" => OrganizationAction::MemberUpdated,
+        other => {
+            return Err(RsrError::Platform(format!("Unsupported member action: {}", other)));
+        }
+    };
+
+    let access_level = payload
+        .changes
+        .permission
+        .and_then(|c| c.to)
+        .or_else(|| payload.changes.role_name.and_then(|c| c.to));
+
+    Ok(RepoEvent::Organization(OrganizationEvent {
+        org: payload.repository.owner.login,
+        action,
+        repo_name: Some(payload.repository.name),
+        member: Some(payload.member.into()),
+        team: None,
+        access_level,
+        previous_path: None,
+    }))
+}
+
+/// Team access to a repository was granted, revoked or changed
+fn parse_team_event(payload: &[u8]) -> Result<RepoEvent> {
+    let payload: TeamPayload = decode_payload("team", payload)?;
+
+    let permissions_changed = payload
+        .changes
+        .repository
+        .is_some_and(|changes| changes.permissions.is_some());
+
+    let action = match payload.action.as_str() {
+        "added_to_repository" => OrganizationAction::MemberAdded,
+        "removed_from_repository" => OrganizationAction::MemberRemoved,
+        "edited" if permissions_changed => OrganizationAction::MemberUpdated,
+        other => {
+            return Err(RsrError::Platform(format!("Unsupported team action: {}", other)));
+        }
+    };
+
+    // The strongest permission the team now holds on the repository
+    let access_level = payload
+        .repository
+        .as_ref()
+        .and_then(|repo| repo.permissions.as_ref())
+        .and_then(|permissions| permissions.strongest())
+        .map(String::from);
+
+    Ok(RepoEvent::Organization(OrganizationEvent {
+        org: payload.organization.login,
+        action,
+        repo_name: payload.repository.map(|repo| repo.name),
+        member: None,
+        team: Some(payload.team.slug),
+        access_level,
+        previous_path: None,
+    }))
+}
+
+fn parse_comment_event(payload: &[u8], event_type: &str) -> Result<RepoEvent> {
+    let payload: CommentPayload = decode_payload(event_type, payload)?;
+
+    let action = match payload.action.as_str() {
         "created" => CommentAction::Created,
         "edited" => CommentAction::Edited,
         "deleted" => CommentAction::Deleted,
-        _ => CommentAction::Created,
+        other => {
+            strict::unrecognized(event_type, "action", other);
+            CommentAction::Created
+        }
     };
 
     let comment_type = match event_type {
-        "issue_comment" => {
-            if json["issue"]["pull_request"].is_object() {
-                CommentType::PullRequest
-            } else {
-                CommentType::Issue
-            }
+        "issue_comment" if payload.issue.as_ref().is_some_and(|i| i.pull_request.is_some()) => {
+            CommentType::PullRequest
         }
         "pull_request_review_comment" => CommentType::Review,
         "commit_comment" => CommentType::Commit,
         _ => CommentType::Issue,
     };
 
-    let comment = &json["comment"];
-
     Ok(RepoEvent::Comment(CommentEvent {
-        repo_owner: json["repository"]["owner"]["login"].as_str().unwrap_or_default().to_string(),
-        repo_name: json["repository"]["name"].as_str().unwrap_or_default().to_string(),
+        repo_owner: payload.repository.owner.login,
+        repo_name: payload.repository.name,
         action,
         comment_type,
-        body: comment["body"].as_str().unwrap_or_default().to_string(),
-        author: User {
-            id: comment["user"]["id"].as_u64().map(|n| n.to_string()).unwrap_or_default(),
-            username: comment["user"]["login"].as_str().unwrap_or_default().to_string(),
-            email: None,
-            avatar_url: comment["user"]["avatar_url"].as_str().map(String::from),
-        },
-        parent_id: json["issue"]["number"].as_u64().or(json["pull_request"]["number"].as_u64()),
+        body: payload.comment.body,
+        author: payload.comment.user.into(),
+        parent_id: payload
+            .issue
+            .map(|i| i.number)
+            .or(payload.pull_request.map(|pr| pr.number)),
     }))
 }
+
+fn parse_repository_event(payload: &[u8]) -> Result<RepoEvent> {
+    let payload: RepositoryPayload = decode_payload("repository", payload)?;
+
+    let action = match payload.action.as_str() {
+        "created" => RepositoryAction::Created,
+        "transferred" => RepositoryAction::Transferred,
+        "renamed" => RepositoryAction::Renamed,
+        "archived" => RepositoryAction::Archived,
+        "unarchived" => RepositoryAction::Unarchived,
+        "deleted" => RepositoryAction::Deleted,
+        other => {
+            return Err(RsrError::Platform(format!("Unsupported repository action: {}", other)));
+        }
+    };
+
+    let previous_owner = payload
+        .changes
+        .owner
+        .and_then(|change| change.from.user.or(change.from.organization))
+        .map(|account| account.login);
+
+    Ok(RepoEvent::Repository(RepositoryEvent {
+        repo_owner: payload.repository.owner.login,
+        repo_name: payload.repository.name,
+        action,
+        previous_owner,
+        previous_name: payload
+            .changes
+            .repository
+            .and_then(|change| change.name)
+            .and_then(|name| name.from),
+        upstream: None,
+    }))
+}
+
+/// A fork event is delivered to the upstream; the event describes the new
+/// fork
+fn parse_fork_event(payload: &[u8]) -> Result<RepoEvent> {
+    let payload: ForkPayload = decode_payload("fork", payload)?;
+
+    Ok(RepoEvent::Repository(RepositoryEvent {
+        repo_owner: payload.forkee.owner.login,
+        repo_name: payload.forkee.name,
+        action: RepositoryAction::Forked,
+        previous_owner: None,
+        previous_name: None,
+        upstream: Some(format!("{}/{}", payload.repository.owner.login, payload.repository.name)),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::STATUS_CONTEXT;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn adapter(server: &MockServer, use_check_runs: bool) -> GitHubAdapter {
+        GitHubAdapter::new(AdapterConfig {
+            api_url: Some(server.uri()),
+            api_token: Some("token".to_string()),
+            use_check_runs,
+            ..Default::default()
+        })
+    }
+
+    /// A status failing a Bronze check, so below every tier
+    fn failing_status() -> ComplianceStatus {
+        let check = crate::CheckResult {
+            id: "bronze.license".to_string(),
+            name: "License".to_string(),
+            tier: crate::CertificationTier::Bronze,
+            passed: false,
+            message: "No license".to_string(),
+            details: None,
+            findings: Vec::new(),
+        };
+        ComplianceStatus::builder().repo(RepoRef::new("github", "acme", "app")).check(check).build().unwrap()
+    }
+
+    #[test]
+    fn pull_request_event_records_the_head_commit() {
+        let payload = serde_json::json!({
+            "action": "synchronize",
+            "pull_request": {
+                "number": 7,
+                "title": "Add a license",
+                "body": null,
+                "head": { "ref": "license", "sha": "abc123" },
+                "base": { "ref": "main", "sha": "def456" },
+                "user": { "id": 1, "login": "octocat" },
+            },
+            "repository": { "name": "app", "owner": { "id": 2, "login": "acme" } },
+        });
+        let Ok(RepoEvent::PullRequest(pr)) = parse_pull_request_event(payload.to_string().as_bytes()) else {
+            panic!("not parsed as a pull request event");
+        };
+        assert_eq!(pr.head_sha, "abc123");
+    }
+
+    #[tokio::test]
+    async fn pull_request_status_is_posted_on_the_head_commit() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/repos/acme/app/statuses/abc123"))
+            .and(body_partial_json(serde_json::json!({ "state": "failure", "context": STATUS_CONTEXT })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let repo = RepoRef::new("github", "acme", "app");
+        adapter(&server, false).post_pull_request_status(&repo, 7, "abc123", &failing_status()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn check_run_is_created_when_configured() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/acme/app/commits/abc123/check-runs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "check_runs": [] })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/acme/app/check-runs"))
+            .and(body_partial_json(serde_json::json!({
+                "name": STATUS_CONTEXT,
+                "head_sha": "abc123",
+                "conclusion": "failure",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({ "id": 42 })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let repo = RepoRef::new("github", "acme", "app");
+        adapter(&server, true).post_pull_request_status(&repo, 7, "abc123", &failing_status()).await.unwrap();
+    }
+}
@@ -0,0 +1,434 @@
+//! Typed GitHub webhook payloads
+//!
+//! Only the fields the parsers read are modelled. Fields GitHub always sends
+//! are required, so a malformed delivery fails with the missing field's name
+//! instead of producing an event with empty strings.
+
+use crate::events::User;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+
+/// A user, bot or organization account
+#[derive(Debug, Deserialize)]
+pub(super) struct Account {
+    pub id: u64,
+    pub login: String,
+    pub avatar_url: Option<String>,
+}
+
+impl From<Account> for User {
+    fn from(account: Account) -> Self {
+        User {
+            id: account.id.to_string(),
+            username: account.login,
+            email: None,
+            avatar_url: account.avatar_url,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct Repository {
+    pub name: String,
+    pub owner: Account,
+    /// Present on `team` events: the team's access to the repository
+    pub permissions: Option<Permissions>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(super) struct Permissions {
+    pub admin: bool,
+    pub maintain: bool,
+    pub push: bool,
+    pub triage: bool,
+    pub pull: bool,
+}
+
+impl Permissions {
+    /// Name of the strongest permission held
+    pub fn strongest(&self) -> Option<&'static str> {
+        [
+            ("admin", self.admin),
+            ("maintain", self.maintain),
+            ("push", self.push),
+            ("triage", self.triage),
+            ("pull", self.pull),
+        ]
+        .into_iter()
+        .find(|(_, held)| *held)
+        .map(|(level, _)| level)
+    }
+}
+
+/// An edited value in a `changes` object
+#[derive(Debug, Deserialize)]
+pub(super) struct Change {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Commit author or pusher, identified by git name and email
+#[derive(Debug, Deserialize)]
+pub(super) struct GitActor {
+    pub name: String,
+    pub email: Option<String>,
+    pub username: Option<String>,
+}
+
+impl GitActor {
+    /// A commit author. Only authors whose email is linked to a GitHub
+    /// account carry a login; the rest keep their git name and get no `id`.
+    pub fn into_commit_author(self) -> User {
+        User {
+            id: self.username.clone().unwrap_or_default(),
+            username: self.username.unwrap_or(self.name),
+            email: self.email,
+            avatar_url: None,
+        }
+    }
+}
+
+impl From<GitActor> for User {
+    fn from(actor: GitActor) -> Self {
+        // Pushers carry only a git name; commit authors may add a GitHub login
+        let username = actor.username.unwrap_or(actor.name);
+        User {
+            id: username.clone(),
+            username,
+            email: actor.email,
+            avatar_url: None,
+        }
+    }
+}
+
+// push
+
+#[derive(Debug, Deserialize)]
+pub(super) struct PushPayload {
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    pub before: String,
+    pub after: String,
+    #[serde(default)]
+    pub commits: Vec<PushCommit>,
+    pub pusher: GitActor,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct PushCommit {
+    pub id: String,
+    pub message: String,
+    pub timestamp: String,
+    pub author: GitActor,
+    #[serde(default)]
+    pub added: Vec<String>,
+    #[serde(default)]
+    pub modified: Vec<String>,
+    #[serde(default)]
+    pub removed: Vec<String>,
+}
+
+// pull_request
+
+#[derive(Debug, Deserialize)]
+pub(super) struct PullRequestPayload {
+    pub action: String,
+    pub pull_request: PullRequest,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub body: Option<String>,
+    pub head: BranchRef,
+    pub base: BranchRef,
+    pub user: Account,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub merged: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct BranchRef {
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    pub sha: String,
+}
+
+// issues
+
+#[derive(Debug, Deserialize)]
+pub(super) struct IssuePayload {
+    pub action: String,
+    pub issue: Issue,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub body: Option<String>,
+    pub user: Account,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct Label {
+    pub name: String,
+}
+
+// release
+
+#[derive(Debug, Deserialize)]
+pub(super) struct ReleasePayload {
+    pub action: String,
+    pub release: Release,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct Release {
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub body: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    pub author: Account,
+}
+
+// security_advisory, dependabot_alert
+
+#[derive(Debug, Deserialize)]
+pub(super) struct SecurityPayload {
+    pub action: String,
+    pub alert: Option<Advisory>,
+    pub security_advisory: Option<Advisory>,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct Advisory {
+    pub severity: Option<String>,
+    pub package: Option<Package>,
+    pub vulnerable_version_range: Option<String>,
+    pub patched_versions: Option<String>,
+    pub cve_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct Package {
+    pub name: String,
+}
+
+// workflow_run
+
+#[derive(Debug, Deserialize)]
+pub(super) struct WorkflowRunPayload {
+    pub action: String,
+    pub workflow_run: WorkflowRun,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct WorkflowRun {
+    pub name: String,
+    pub status: Option<String>,
+    pub conclusion: Option<String>,
+    /// Null for runs not triggered from a branch
+    pub head_branch: Option<String>,
+    pub head_sha: String,
+}
+
+// check_suite
+
+#[derive(Debug, Deserialize)]
+pub(super) struct CheckSuitePayload {
+    pub action: String,
+    pub check_suite: CheckSuite,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct CheckSuite {
+    pub status: Option<String>,
+    pub conclusion: Option<String>,
+    pub head_branch: Option<String>,
+    pub head_sha: String,
+    pub app: Option<App>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct App {
+    pub slug: String,
+}
+
+// deployment, deployment_status
+
+#[derive(Debug, Deserialize)]
+pub(super) struct DeploymentPayload {
+    pub deployment: Deployment,
+    /// Only on `deployment_status` events
+    pub deployment_status: Option<DeploymentStatus>,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct Deployment {
+    pub id: u64,
+    pub environment: String,
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    pub sha: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct DeploymentStatus {
+    pub state: String,
+    pub description: Option<String>,
+}
+
+// branch_protection_rule
+
+#[derive(Debug, Deserialize)]
+pub(super) struct BranchProtectionPayload {
+    pub action: String,
+    pub rule: BranchProtectionRule,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct BranchProtectionRule {
+    pub name: String,
+    #[serde(default)]
+    pub required_approving_review_count: u32,
+    #[serde(default)]
+    pub required_status_checks: Vec<String>,
+    #[serde(default)]
+    pub admin_enforced: bool,
+    /// "off", "non_admins" or "everyone"
+    pub allow_force_pushes_enforcement_level: Option<String>,
+}
+
+// member
+
+#[derive(Debug, Deserialize)]
+pub(super) struct MemberPayload {
+    pub action: String,
+    pub member: Account,
+    #[serde(default)]
+    pub changes: MemberChanges,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct MemberChanges {
+    pub permission: Option<Change>,
+    pub role_name: Option<Change>,
+}
+
+// team
+
+#[derive(Debug, Deserialize)]
+pub(super) struct TeamPayload {
+    pub action: String,
+    pub team: Team,
+    pub organization: Account,
+    /// Absent for changes to the team itself
+    pub repository: Option<Repository>,
+    #[serde(default)]
+    pub changes: TeamChanges,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct Team {
+    pub slug: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct TeamChanges {
+    pub repository: Option<TeamRepositoryChanges>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct TeamRepositoryChanges {
+    pub permissions: Option<IgnoredAny>,
+}
+
+// issue_comment, pull_request_review_comment
+
+#[derive(Debug, Deserialize)]
+pub(super) struct CommentPayload {
+    pub action: String,
+    pub comment: Comment,
+    pub issue: Option<CommentedIssue>,
+    pub pull_request: Option<CommentedPullRequest>,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct Comment {
+    pub body: String,
+    pub user: Account,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct CommentedIssue {
+    pub number: u64,
+    /// Present when the issue is a pull request
+    pub pull_request: Option<IgnoredAny>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct CommentedPullRequest {
+    pub number: u64,
+}
+
+// repository
+
+#[derive(Debug, Deserialize)]
+pub(super) struct ForkPayload {
+    /// The new fork
+    pub forkee: Repository,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct RepositoryPayload {
+    pub action: String,
+    #[serde(default)]
+    pub changes: RepositoryChanges,
+    pub repository: Repository,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct RepositoryChanges {
+    pub owner: Option<OwnerChange>,
+    pub repository: Option<RepositoryNameChange>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct OwnerChange {
+    pub from: PreviousOwner,
+}
+
+/// Transfers report the previous owner as either a user or an organization
+#[derive(Debug, Deserialize)]
+pub(super) struct PreviousOwner {
+    pub user: Option<Account>,
+    pub organization: Option<Account>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct RepositoryNameChange {
+    pub name: Option<Change>,
+}
@@ -2,16 +2,21 @@
 //!
 //! Supports both GitLab.com and self-hosted GitLab instances.
 
-use super::{AdapterConfig, Headers, PlatformAdapter, RepoMetadata};
-use crate::events::RepoEvent;
+use super::pagination::fetch_all_pages;
+use super::http::{HttpLayer, SendVia};
+use super::signature::constant_time_eq;
+use super::{decode_payload, AdapterConfig, Headers, PlatformAdapter, RepoMetadata};
+use crate::events::*;
 use crate::{ComplianceStatus, RepoRef, Result, RsrError};
 use async_trait::async_trait;
+use serde::Deserialize;
 
 const DEFAULT_API_URL: &str = "https://gitlab.com/api/v4";
 
 pub struct GitLabAdapter {
     config: AdapterConfig,
     client: reqwest::Client,
+    http: HttpLayer,
     api_url: String,
 }
 
@@ -19,9 +24,12 @@ impl GitLabAdapter {
     pub fn new(config: AdapterConfig) -> Self {
         let api_url = config.api_url.clone().unwrap_or_else(|| DEFAULT_API_URL.to_string());
 
+        let http = HttpLayer::new("gitlab", config.retry.clone()).with_etag_cache(config.etag_cache.clone());
+
         Self {
             config,
             client: reqwest::Client::new(),
+            http,
             api_url,
         }
     }
@@ -35,8 +43,7 @@ fn platform_id(&self) -> &'static str {
 
     fn verify_webhook(&self, _payload: &[u8], headers: &Headers) -> Result<bool> {
         let Some(ref secret) = self.config.webhook_secret else {
-            tracing::warn!("Webhook secret not configured - skipping verification");
-            return Ok(true);
+            return self.config.unsigned_webhook(self.platform_id());
         };
 
         // GitLab uses X-Gitlab-Token header for webhook verification
@@ -44,22 +51,27 @@ fn verify_webhook(&self, _payload: &[u8], headers: &Headers) -> Result<bool> {
             return Err(RsrError::WebhookVerification);
         };
 
-        Ok(token == secret)
+        Ok(constant_time_eq(token.as_bytes(), secret.as_bytes()))
+    }
+
+    fn delivery_id(&self, headers: &Headers) -> Option<String> {
+        headers.get("x-gitlab-event-uuid").cloned()
     }
 
-    fn parse_webhook(&self, _payload: &[u8], headers: &Headers) -> Result<RepoEvent> {
+    fn parse_webhook(&self, payload: &[u8], headers: &Headers) -> Result<RepoEvent> {
         let event_type = headers
             .get("x-gitlab-event")
             .ok_or_else(|| RsrError::Platform("Missing X-Gitlab-Event header".to_string()))?;
 
-        let _json: serde_json::Value = serde_json::from_slice(payload)?;
-
-        // TODO: Implement full GitLab webhook parsing
-        // For now, return a placeholder error
-        Err(RsrError::Platform(format!(
-            "GitLab adapter not fully implemented yet. Event type: {}",
-            event_type
-        )))
+        match event_type.as_str() {
+            // Instance-wide system hooks, and the group webhook equivalents
+            "System Hook" | "Member Hook" | "Subgroup Hook" | "Project Hook" => parse_system_event(payload),
+            // TODO: Implement project webhook parsing (push, merge request, ...)
+            _ => Err(RsrError::Platform(format!(
+                "GitLab adapter not fully implemented yet. Event type: {}",
+                event_type
+            ))),
+        }
     }
 
     async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &ComplianceStatus) -> Result<()> {
@@ -93,7 +105,7 @@ async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &Complianc
             .post(&url)
             .header("PRIVATE-TOKEN", token)
             .json(&body)
-            .send()
+            .send_via(&self.http)
             .await?;
 
         if !response.status().is_success() {
@@ -122,17 +134,21 @@ async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
         let response = self.client
             .get(&url)
             .header("PRIVATE-TOKEN", token)
-            .send()
+            .fetch_via(&self.http)
             .await?;
 
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
+        if response.status == reqwest::StatusCode::NOT_FOUND {
             return Err(RsrError::RepoNotFound {
                 owner: repo.owner.clone(),
                 repo: repo.repo.clone(),
             });
         }
 
-        Ok(response.bytes().await?.to_vec())
+        if !response.status.is_success() {
+            return Err(RsrError::Platform(format!("Failed to fetch {} ({}): {}", path, response.status, response.text())));
+        }
+
+        Ok(response.body)
     }
 
     async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<String>> {
@@ -145,7 +161,7 @@ async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<Str
         let branch = repo.branch.as_deref().unwrap_or("HEAD");
 
         let mut url = format!(
-            "{}/projects/{}/repository/tree?ref={}",
+            "{}/projects/{}/repository/tree?ref={}&recursive=true&per_page=100&pagination=keyset",
             self.api_url, encoded_project, branch
         );
 
@@ -153,22 +169,22 @@ async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<Str
             url.push_str(&format!("&path={}", urlencoding::encode(p)));
         }
 
-        let response = self.client
-            .get(&url)
-            .header("PRIVATE-TOKEN", token)
-            .send()
-            .await?;
-
-        let json: serde_json::Value = response.json().await?;
-
-        let files: Vec<String> = json
-            .as_array()
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|item| item["path"].as_str().map(String::from))
-                    .collect()
-            })
-            .unwrap_or_default();
+        let entries = fetch_all_pages(
+            &self.http,
+            &url,
+            |page| self.client.get(page).header("PRIVATE-TOKEN", token),
+            |page| match page {
+                serde_json::Value::Array(entries) => entries,
+                _ => Vec::new(),
+            },
+        )
+        .await?;
+
+        let files: Vec<String> = entries
+            .iter()
+            .filter(|item| item["type"].as_str() == Some("blob"))
+            .filter_map(|item| item["path"].as_str().map(String::from))
+            .collect();
 
         Ok(files)
     }
@@ -186,10 +202,10 @@ async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
         let response = self.client
             .get(&url)
             .header("PRIVATE-TOKEN", token)
-            .send()
+            .fetch_via(&self.http)
             .await?;
 
-        let json: serde_json::Value = response.json().await?;
+        let json: serde_json::Value = response.json()?;
 
         Ok(RepoMetadata {
             default_branch: json["default_branch"].as_str().unwrap_or("main").to_string(),
@@ -203,6 +219,8 @@ async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
             open_issues_count: json["open_issues_count"].as_u64().unwrap_or(0) as u32,
             stargazers_count: json["star_count"].as_u64().unwrap_or(0) as u32,
             forks_count: json["forks_count"].as_u64().unwrap_or(0) as u32,
+            upstream: json["forked_from_project"]["path_with_namespace"].as_str().map(String::from),
+            archived: json["archived"].as_bool().unwrap_or(false),
             license: None, // Would need separate API call
             topics: json["topics"]
                 .as_array()
@@ -215,3 +233,157 @@ async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
         })
     }
 }
+
+// System hook and group webhook parsing helpers
+
+/// Parse a system hook or group webhook by its `event_name`.
+///
+/// Project lifecycle events become repository events (driving registration
+/// and transfers); group and membership events become organization events.
+fn parse_system_event(payload: &[u8]) -> Result<RepoEvent> {
+    let kind: SystemHookKind = decode_payload("system hook", payload)?;
+    let event_name = kind.event_name.as_str();
+
+    match event_name {
+        "project_create" | "project_destroy" | "project_rename" | "project_transfer" => {
+            let hook: ProjectHook = decode_payload(event_name, payload)?;
+            let action = match event_name {
+                "project_create" => RepositoryAction::Created,
+                "project_destroy" => RepositoryAction::Deleted,
+                "project_rename" => RepositoryAction::Renamed,
+                _ => RepositoryAction::Transferred,
+            };
+
+            let (repo_owner, repo_name) = split_project_path(&hook.path_with_namespace);
+            let previous = hook.old_path_with_namespace.as_deref().map(split_project_path);
+
+            Ok(RepoEvent::Repository(RepositoryEvent {
+                repo_owner: repo_owner.to_string(),
+                repo_name: repo_name.to_string(),
+                action,
+                previous_owner: previous
+                    .filter(|(owner, _)| *owner != repo_owner)
+                    .map(|(owner, _)| owner.to_string()),
+                previous_name: previous
+                    .filter(|(_, name)| *name != repo_name)
+                    .map(|(_, name)| name.to_string()),
+                upstream: None,
+            }))
+        }
+        "user_add_to_team" | "user_remove_from_team" | "user_update_for_team" => {
+            let hook: ProjectMemberHook = decode_payload(event_name, payload)?;
+            let (org, repo_name) = split_project_path(&hook.project_path_with_namespace);
+
+            Ok(RepoEvent::Organization(OrganizationEvent {
+                org: org.to_string(),
+                action: member_action(event_name),
+                repo_name: Some(repo_name.to_string()),
+                member: Some(hook.member.into()),
+                team: None,
+                access_level: hook.access_level,
+                previous_path: None,
+            }))
+        }
+        "user_add_to_group" | "user_remove_from_group" | "user_update_for_group" => {
+            let hook: GroupMemberHook = decode_payload(event_name, payload)?;
+
+            Ok(RepoEvent::Organization(OrganizationEvent {
+                org: hook.group_path,
+                action: member_action(event_name),
+                repo_name: None,
+                member: Some(hook.member.into()),
+                team: None,
+                access_level: hook.group_access,
+                previous_path: None,
+            }))
+        }
+        "group_create" | "group_destroy" | "group_rename" | "subgroup_create" | "subgroup_destroy" => {
+            let hook: GroupHook = decode_payload(event_name, payload)?;
+            let action = match event_name {
+                "group_create" | "subgroup_create" => OrganizationAction::Created,
+                "group_rename" => OrganizationAction::Renamed,
+                _ => OrganizationAction::Deleted,
+            };
+
+            Ok(RepoEvent::Organization(OrganizationEvent {
+                org: hook.full_path,
+                action,
+                repo_name: None,
+                member: None,
+                team: None,
+                access_level: None,
+                previous_path: hook.old_full_path,
+            }))
+        }
+        other => Err(RsrError::Platform(format!("Unsupported GitLab system event: {}", other))),
+    }
+}
+
+/// Split `group/subgroup/project` into namespace and project path
+fn split_project_path(path: &str) -> (&str, &str) {
+    path.rsplit_once('/').unwrap_or(("", path))
+}
+
+fn member_action(event_name: &str) -> OrganizationAction {
+    if event_name.starts_with("user_add") {
+        OrganizationAction::MemberAdded
+    } else if event_name.starts_with("user_remove") {
+        OrganizationAction::MemberRemoved
+    } else {
+        OrganizationAction::MemberUpdated
+    }
+}
+
+// System hook payloads
+
+#[derive(Debug, Deserialize)]
+struct SystemHookKind {
+    event_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectHook {
+    path_with_namespace: String,
+    old_path_with_namespace: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectMemberHook {
+    project_path_with_namespace: String,
+    access_level: Option<String>,
+    #[serde(flatten)]
+    member: HookMember,
+}
+
+#[derive(Debug, Deserialize)]
+struct GroupMemberHook {
+    group_path: String,
+    group_access: Option<String>,
+    #[serde(flatten)]
+    member: HookMember,
+}
+
+#[derive(Debug, Deserialize)]
+struct GroupHook {
+    full_path: String,
+    old_full_path: Option<String>,
+}
+
+/// The user a membership hook is about
+#[derive(Debug, Deserialize)]
+struct HookMember {
+    user_id: u64,
+    user_username: String,
+    user_email: Option<String>,
+}
+
+impl From<HookMember> for User {
+    fn from(member: HookMember) -> Self {
+        User {
+            id: member.user_id.to_string(),
+            username: member.user_username,
+            email: member.user_email,
+            avatar_url: None,
+        }
+    }
+}
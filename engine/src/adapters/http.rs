@@ -0,0 +1,380 @@
+//! Shared HTTP layer for platform adapters
+//!
+//! Wraps request sending with rate-limit awareness and retries:
+//! - reads `X-RateLimit-*` / `RateLimit-*` and `Retry-After` headers
+//! - waits out short rate-limit windows, defers long ones as `RsrError::RateLimited`
+//! - retries transient 5xx and connection errors with exponential backoff and jitter
+//! - records the remaining quota per platform for the metrics endpoint
+//! - revalidates cached GETs with `If-None-Match` so unchanged resources cost a 304
+
+use crate::{Result, RsrError};
+use once_cell::sync::Lazy;
+use reqwest::header::HeaderMap;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Last seen quota per platform
+static QUOTAS: Lazy<RwLock<HashMap<&'static str, QuotaSnapshot>>> = Lazy::new(Default::default);
+
+/// Retry and rate-limit behaviour
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Backoff before the first retry; doubled on each further attempt
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Longest rate-limit window we are willing to sleep through.
+    /// Anything longer is returned as `RateLimited` so the caller can defer.
+    pub max_rate_limit_wait: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            max_rate_limit_wait: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Remaining API quota as last reported by a platform
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct QuotaSnapshot {
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    /// Unix time the quota resets
+    pub reset_at: Option<i64>,
+}
+
+/// How long a cached response is kept for revalidation
+const ETAG_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// A response body stored alongside the ETag it was served with
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub etag: String,
+    pub body: Vec<u8>,
+    /// `rel="next"` URL of a paginated listing
+    pub next_page: Option<String>,
+}
+
+/// Storage for conditional-request validators
+#[async_trait::async_trait]
+pub trait EtagCache: Send + Sync {
+    async fn get_etag(&self, key: &str) -> Result<Option<CachedResponse>>;
+    async fn put_etag(&self, key: &str, entry: &CachedResponse, ttl_secs: u64) -> Result<()>;
+}
+
+impl std::fmt::Debug for dyn EtagCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EtagCache")
+    }
+}
+
+/// Result of a GET that may have been answered from the ETag cache
+#[derive(Debug, Clone)]
+pub struct Fetched {
+    pub status: StatusCode,
+    pub body: Vec<u8>,
+    pub next_page: Option<String>,
+    /// The platform answered 304 Not Modified
+    pub not_modified: bool,
+}
+
+impl Fetched {
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// Per-adapter sender applying the retry policy
+#[derive(Debug, Clone)]
+pub struct HttpLayer {
+    platform: &'static str,
+    policy: RetryPolicy,
+    etags: Option<Arc<dyn EtagCache>>,
+}
+
+impl HttpLayer {
+    pub fn new(platform: &'static str, policy: RetryPolicy) -> Self {
+        Self {
+            platform,
+            policy,
+            etags: None,
+        }
+    }
+
+    /// Revalidate GETs made through `get` against this cache
+    pub fn with_etag_cache(mut self, cache: Option<Arc<dyn EtagCache>>) -> Self {
+        self.etags = cache;
+        self
+    }
+
+    /// Send a GET, revalidating a previously cached response with `If-None-Match`.
+    ///
+    /// A 304 is answered from the cache and doesn't count against most
+    /// platforms' quotas. Cache failures are logged and never fail the request.
+    pub async fn get(&self, request: RequestBuilder) -> Result<Fetched> {
+        let Some(ref etags) = self.etags else {
+            return fetched(self.send(request).await?).await;
+        };
+
+        let Some(key) = self.etag_key(&request) else {
+            return fetched(self.send(request).await?).await;
+        };
+
+        let cached = etags.get_etag(&key).await.unwrap_or_else(|e| {
+            tracing::warn!("ETag lookup failed for {}: {}", self.platform, e);
+            None
+        });
+
+        let request = match cached {
+            Some(ref entry) => request.header(reqwest::header::IF_NONE_MATCH, &entry.etag),
+            None => request,
+        };
+
+        let response = self.send(request).await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(entry) = cached {
+                tracing::debug!("{} resource not modified, served from cache", self.platform);
+                return Ok(Fetched {
+                    status: StatusCode::OK,
+                    body: entry.body,
+                    next_page: entry.next_page,
+                    not_modified: true,
+                });
+            }
+        }
+
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let result = fetched(response).await?;
+
+        if let Some(etag) = etag.filter(|_| result.status.is_success()) {
+            let entry = CachedResponse {
+                etag,
+                body: result.body.clone(),
+                next_page: result.next_page.clone(),
+            };
+            if let Err(e) = etags.put_etag(&key, &entry, ETAG_TTL_SECS).await {
+                tracing::warn!("ETag store failed for {}: {}", self.platform, e);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// `{platform}:{digest}` over the URL, `Accept` and credentials, so
+    /// different representations and tokens never share an entry
+    fn etag_key(&self, request: &RequestBuilder) -> Option<String> {
+        let built = request.try_clone()?.build().ok()?;
+        let mut hasher = Sha256::new();
+        hasher.update(built.url().as_str());
+        for name in [reqwest::header::ACCEPT, reqwest::header::AUTHORIZATION] {
+            hasher.update([0]);
+            if let Some(value) = built.headers().get(&name) {
+                hasher.update(value.as_bytes());
+            }
+        }
+        hasher.update([0]);
+        if let Some(value) = built.headers().get("private-token") {
+            hasher.update(value.as_bytes());
+        }
+        Some(format!("{}:{}", self.platform, hex::encode(hasher.finalize())))
+    }
+
+    /// Send a request, waiting out rate limits and retrying transient failures
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        // Don't spend a request we know will be rejected
+        if let Some(wait) = self.exhausted_for() {
+            if wait > self.policy.max_rate_limit_wait {
+                return Err(RsrError::RateLimited);
+            }
+            tracing::info!("{} quota exhausted, waiting {:?}", self.platform, wait);
+            tokio::time::sleep(wait).await;
+        }
+
+        let mut attempt = 0;
+        let mut request = Some(request);
+        loop {
+            let pending = request.take().expect("request is only consumed by the final attempt");
+
+            // Requests with streaming bodies can't be cloned and get a single attempt
+            let (current, retry) = match pending.try_clone() {
+                Some(clone) if attempt < self.policy.max_retries => {
+                    request = Some(pending);
+                    (clone, true)
+                }
+                _ => (pending, false),
+            };
+
+            match current.send().await {
+                Ok(response) => {
+                    let quota = self.record_quota(response.headers());
+
+                    if is_rate_limited(&response, &quota) {
+                        let wait = rate_limit_wait(response.headers(), &quota);
+                        if !retry || wait > self.policy.max_rate_limit_wait {
+                            tracing::warn!("{} rate limited for {:?}, deferring", self.platform, wait);
+                            return Err(RsrError::RateLimited);
+                        }
+                        tracing::info!("{} rate limited, retrying in {:?}", self.platform, wait);
+                        tokio::time::sleep(wait).await;
+                    } else if response.status().is_server_error() && retry {
+                        let delay = self.backoff(attempt);
+                        tracing::debug!("{} returned {}, retrying in {:?}", self.platform, response.status(), delay);
+                        tokio::time::sleep(delay).await;
+                    } else {
+                        return Ok(response);
+                    }
+                }
+                Err(e) if retry && (e.is_timeout() || e.is_connect()) => {
+                    let delay = self.backoff(attempt);
+                    tracing::debug!("{} request failed ({}), retrying in {:?}", self.platform, e, delay);
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+
+            attempt += 1;
+        }
+    }
+
+    /// Exponential backoff with full jitter
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .policy
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.policy.max_delay);
+        let millis = ceiling.as_millis() as u64;
+        if millis == 0 {
+            return ceiling;
+        }
+        Duration::from_millis(jitter() % (millis + 1))
+    }
+
+    /// How long until the recorded quota resets, if it is used up
+    fn exhausted_for(&self) -> Option<Duration> {
+        let quotas = QUOTAS.read().expect("quota lock poisoned");
+        let quota = quotas.get(self.platform)?;
+        if quota.remaining != Some(0) {
+            return None;
+        }
+        let seconds = quota.reset_at? - chrono::Utc::now().timestamp();
+        (seconds > 0).then(|| Duration::from_secs(seconds as u64))
+    }
+
+    fn record_quota(&self, headers: &HeaderMap) -> QuotaSnapshot {
+        let quota = QuotaSnapshot {
+            limit: header_u64(headers, &["x-ratelimit-limit", "ratelimit-limit"]),
+            remaining: header_u64(headers, &["x-ratelimit-remaining", "ratelimit-remaining"]),
+            reset_at: header_u64(headers, &["x-ratelimit-reset", "ratelimit-reset"]).map(|v| v as i64),
+        };
+
+        if quota.remaining.is_some() {
+            QUOTAS
+                .write()
+                .expect("quota lock poisoned")
+                .insert(self.platform, quota.clone());
+        }
+
+        quota
+    }
+}
+
+/// Lets adapters write `request.send_via(&self.http)` in place of `send()`,
+/// or `request.fetch_via(&self.http)` for GETs worth revalidating
+#[async_trait::async_trait]
+pub trait SendVia {
+    async fn send_via(self, layer: &HttpLayer) -> Result<Response>;
+    async fn fetch_via(self, layer: &HttpLayer) -> Result<Fetched>;
+}
+
+#[async_trait::async_trait]
+impl SendVia for RequestBuilder {
+    async fn send_via(self, layer: &HttpLayer) -> Result<Response> {
+        layer.send(self).await
+    }
+
+    async fn fetch_via(self, layer: &HttpLayer) -> Result<Fetched> {
+        layer.get(self).await
+    }
+}
+
+async fn fetched(response: Response) -> Result<Fetched> {
+    let status = response.status();
+    let next_page = super::pagination::next_page_url(response.headers());
+    let body = response.bytes().await?.to_vec();
+    Ok(Fetched {
+        status,
+        body,
+        next_page,
+        not_modified: false,
+    })
+}
+
+/// Last recorded quota for every platform that has reported one
+pub fn quota_snapshots() -> Vec<(&'static str, QuotaSnapshot)> {
+    let mut quotas: Vec<_> = QUOTAS
+        .read()
+        .expect("quota lock poisoned")
+        .iter()
+        .map(|(platform, quota)| (*platform, quota.clone()))
+        .collect();
+    quotas.sort_by_key(|(platform, _)| *platform);
+    quotas
+}
+
+/// 429, or a 403 with the quota used up (GitHub's primary rate limit)
+fn is_rate_limited(response: &Response, quota: &QuotaSnapshot) -> bool {
+    response.status() == StatusCode::TOO_MANY_REQUESTS
+        || (response.status() == StatusCode::FORBIDDEN
+            && (quota.remaining == Some(0) || response.headers().contains_key("retry-after")))
+}
+
+/// Time to wait before retrying a rate-limited request
+fn rate_limit_wait(headers: &HeaderMap, quota: &QuotaSnapshot) -> Duration {
+    if let Some(seconds) = header_u64(headers, &["retry-after"]) {
+        return Duration::from_secs(seconds);
+    }
+
+    // Some platforms send a reset delay rather than an epoch timestamp
+    let now = chrono::Utc::now().timestamp();
+    match quota.reset_at {
+        Some(reset) if reset > now => Duration::from_secs((reset - now) as u64),
+        Some(reset) if reset < 1_000_000_000 => Duration::from_secs(reset.max(0) as u64),
+        _ => Duration::from_secs(60),
+    }
+}
+
+fn header_u64(headers: &HeaderMap, names: &[&str]) -> Option<u64> {
+    names
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok()?.trim().parse().ok())
+}
+
+/// Random value for jitter, without pulling in a RNG crate
+fn jitter() -> u64 {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default());
+    hasher.finish()
+}
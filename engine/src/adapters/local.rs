@@ -0,0 +1,504 @@
+//! Local repository adapter
+//!
+//! Reads a local working tree or bare repository directly from disk via `gix`,
+//! so CI jobs can run RSR checks without any platform API token.
+
+use super::{
+    matches_prefix, AdapterCapabilities, AdapterConfig, Headers, PlatformAdapter, RepoMetadata, Signature, SignatureFormat,
+    SignedObject,
+};
+use crate::events::RepoEvent;
+use crate::{ComplianceStatus, RepoRef, Result, RsrError};
+use async_trait::async_trait;
+use std::path::{Component, Path, PathBuf};
+
+/// Files whose presence indicates CI is configured
+const CI_INDICATORS: &[&str] = &[
+    ".github/workflows/",
+    ".gitlab-ci.yml",
+    ".woodpecker.yml",
+    ".woodpecker/",
+    "Jenkinsfile",
+    ".travis.yml",
+    ".circleci/",
+    "azure-pipelines.yml",
+    ".drone.yml",
+    "bitbucket-pipelines.yml",
+    ".builds/",
+];
+
+#[derive(Clone)]
+pub struct LocalRepoAdapter {
+    path: PathBuf,
+}
+
+impl LocalRepoAdapter {
+    /// Create an adapter from config - `repo_path` must point at the repository
+    pub fn new(config: AdapterConfig) -> Result<Self> {
+        let path = config
+            .repo_path
+            .ok_or_else(|| RsrError::Config("Repository path required for local adapter".to_string()))?;
+
+        Self::open(path)
+    }
+
+    /// Open a working tree or bare repository at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let adapter = Self {
+            path: path.as_ref().to_path_buf(),
+        };
+
+        // Fail early if the path isn't a repository
+        adapter.repository()?;
+
+        Ok(adapter)
+    }
+
+    /// Path the adapter was opened with
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Open the repository for a single operation.
+    ///
+    /// `gix::Repository` is not `Sync` without the `parallel` feature, so the
+    /// adapter holds only the path and reopens per call.
+    fn repository(&self) -> Result<gix::Repository> {
+        gix::open(&self.path).map_err(|e| {
+            RsrError::Platform(format!("Failed to open repository {}: {}", self.path.display(), e))
+        })
+    }
+
+    /// Working tree to read from, if the repository has one and no branch was requested.
+    ///
+    /// An explicit branch always reads committed content, so results match what
+    /// a platform adapter would see for that ref.
+    fn workdir(&self, repo: &RepoRef) -> Option<PathBuf> {
+        if repo.branch.is_some() {
+            return None;
+        }
+        self.repository().ok()?.workdir().map(Path::to_path_buf)
+    }
+
+    /// Run blocking repository access (`gix`, `std::fs`) off the async runtime
+    async fn blocking<T, F>(&self, read: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&LocalRepoAdapter) -> Result<T> + Send + 'static,
+    {
+        let adapter = self.clone();
+        tokio::task::spawn_blocking(move || read(&adapter))
+            .await
+            .map_err(|e| RsrError::Platform(format!("Repository read failed: {}", e)))?
+    }
+
+    /// Resolve the tree for the requested branch, or HEAD if none was given
+    fn tree<'r>(&self, local: &'r gix::Repository, repo: &RepoRef) -> Result<gix::Tree<'r>> {
+        let commit = match repo.branch.as_deref() {
+            Some(branch) => local
+                .find_reference(format!("refs/heads/{}", branch).as_str())
+                .map_err(|e| RsrError::Platform(format!("Branch {} not found: {}", branch, e)))?
+                .peel_to_commit()
+                .map_err(|e| RsrError::Platform(format!("Failed to resolve {}: {}", branch, e)))?,
+            None => local
+                .head_commit()
+                .map_err(|e| RsrError::Platform(format!("Failed to resolve HEAD: {}", e)))?,
+        };
+
+        commit
+            .tree()
+            .map_err(|e| RsrError::Platform(format!("Failed to read tree: {}", e)))
+    }
+
+    fn list_tree(&self, repo: &RepoRef, prefix: Option<&str>) -> Result<Vec<String>> {
+        let local = self.repository()?;
+        let tree = self.tree(&local, repo)?;
+
+        let entries = tree
+            .traverse()
+            .breadthfirst
+            .files()
+            .map_err(|e| RsrError::Platform(format!("Failed to traverse tree: {}", e)))?;
+
+        Ok(entries
+            .into_iter()
+            .filter(|entry| !entry.mode.is_tree())
+            .map(|entry| entry.filepath.to_string())
+            .filter(|path| matches_prefix(path, prefix))
+            .collect())
+    }
+
+    /// Read a file from the working tree, or from the branch's tree. `path`
+    /// must stay inside the repository, including through symlinks.
+    fn read_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
+        if !is_repo_relative(path) {
+            return Err(RsrError::Platform(format!("{} is not a path inside the repository", path)));
+        }
+        let Some(workdir) = self.workdir(repo) else {
+            return self.read_tree_file(repo, path);
+        };
+
+        let not_found = || RsrError::RepoNotFound {
+            owner: repo.owner.clone(),
+            repo: repo.repo.clone(),
+        };
+        let resolved = match workdir.join(path).canonicalize() {
+            Ok(resolved) => resolved,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(not_found()),
+            Err(e) => return Err(e.into()),
+        };
+        if !resolved.starts_with(workdir.canonicalize()?) {
+            return Err(RsrError::Platform(format!("{} resolves outside the repository", path)));
+        }
+
+        match std::fs::read(resolved) {
+            Ok(content) => Ok(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(not_found()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Files of the working tree, or of the branch's tree, under `prefix`
+    fn list(&self, repo: &RepoRef, prefix: Option<&str>) -> Result<Vec<String>> {
+        let Some(workdir) = self.workdir(repo) else {
+            return self.list_tree(repo, prefix);
+        };
+
+        let mut files = Vec::new();
+        walk_workdir(&workdir, &workdir, &mut files)?;
+        files.retain(|file| matches_prefix(file, prefix));
+        files.sort();
+
+        Ok(files)
+    }
+
+    fn read_tree_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
+        let local = self.repository()?;
+        let tree = self.tree(&local, repo)?;
+
+        let entry = tree
+            .lookup_entry_by_path(path)
+            .map_err(|e| RsrError::Platform(format!("Failed to look up {}: {}", path, e)))?
+            .ok_or_else(|| RsrError::RepoNotFound {
+                owner: repo.owner.clone(),
+                repo: repo.repo.clone(),
+            })?;
+
+        let object = entry
+            .object()
+            .map_err(|e| RsrError::Platform(format!("Failed to read {}: {}", path, e)))?;
+
+        Ok(object.data.clone())
+    }
+
+    fn last_commit_time(&self, repo: &RepoRef) -> Option<chrono::DateTime<chrono::Utc>> {
+        let local = self.repository().ok()?;
+        let commit = match repo.branch.as_deref() {
+            Some(branch) => local
+                .find_reference(format!("refs/heads/{}", branch).as_str())
+                .ok()?
+                .peel_to_commit()
+                .ok()?,
+            None => local.head_commit().ok()?,
+        };
+
+        let time = commit.time().ok()?;
+        chrono::DateTime::from_timestamp(time.seconds, 0)
+    }
+
+    fn default_branch(&self) -> String {
+        self.repository()
+            .ok()
+            .and_then(|local| local.head_name().ok())
+            .flatten()
+            .map(|name| name.shorten().to_string())
+            .unwrap_or_else(|| "main".to_string())
+    }
+
+    /// Run git in the repository and return what it printed. Signature
+    /// verification needs the user's keyrings and allowed signers, which only
+    /// the git CLI knows how to find.
+    async fn git(&self, args: &[&str]) -> Result<String> {
+        let output = tokio::process::Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| RsrError::Platform(format!("Failed to run git: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(RsrError::Platform(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn description(&self) -> Option<String> {
+        let local = self.repository().ok()?;
+        let content = std::fs::read_to_string(local.git_dir().join("description")).ok()?;
+        let content = content.trim();
+
+        // git init writes a placeholder description
+        if content.is_empty() || content.starts_with("Unnamed repository") {
+            None
+        } else {
+            Some(content.to_string())
+        }
+    }
+}
+
+#[async_trait]
+impl PlatformAdapter for LocalRepoAdapter {
+    fn platform_id(&self) -> &'static str {
+        "local"
+    }
+
+    fn capabilities(&self) -> AdapterCapabilities {
+        AdapterCapabilities {
+            webhooks: false,
+            commit_status: false,
+            ..Default::default()
+        }
+    }
+
+    fn verify_webhook(&self, _payload: &[u8], _headers: &Headers) -> Result<bool> {
+        Err(RsrError::Platform(
+            "Local repositories do not receive webhooks".to_string(),
+        ))
+    }
+
+    fn parse_webhook(&self, _payload: &[u8], _headers: &Headers) -> Result<RepoEvent> {
+        Err(RsrError::Platform(
+            "Local repositories do not receive webhooks".to_string(),
+        ))
+    }
+
+    async fn post_status(&self, _repo: &RepoRef, _commit_sha: &str, _status: &ComplianceStatus) -> Result<()> {
+        Err(RsrError::Platform(
+            "Local repositories have no status API - use the `rsr check` report instead".to_string(),
+        ))
+    }
+
+    async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
+        let (repo, path) = (repo.clone(), path.to_string());
+        self.blocking(move |adapter| adapter.read_file(&repo, &path)).await
+    }
+
+    async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<String>> {
+        let (repo, prefix) = (repo.clone(), path.map(str::to_string));
+        self.blocking(move |adapter| adapter.list(&repo, prefix.as_deref())).await
+    }
+
+    async fn commit_signatures(&self, repo: &RepoRef, limit: usize) -> Result<Vec<SignedObject>> {
+        let revision = repo.branch.as_deref().unwrap_or("HEAD");
+        let count = format!("-n{}", limit);
+
+        // %G? is git's verdict; the raw headers say how each commit was signed
+        let verdicts = self.git(&["log", &count, "--format=%H%x1f%G?", revision, "--"]).await?;
+        let raw = self.git(&["log", &count, "--format=raw", revision, "--"]).await?;
+        let formats = signature_headers(&raw);
+
+        Ok(verdicts
+            .lines()
+            .filter_map(|line| line.split_once('\x1f'))
+            .map(|(sha, verdict)| SignedObject {
+                id: sha.to_string(),
+                signature: commit_signature(verdict, formats.get(sha).copied().flatten()),
+            })
+            .collect())
+    }
+
+    async fn tag_signatures(&self, _repo: &RepoRef, limit: usize) -> Result<Vec<SignedObject>> {
+        let count = format!("--count={}", limit);
+        let refs = self
+            .git(&[
+                "for-each-ref",
+                "--sort=-creatordate",
+                &count,
+                "--format=%(refname:short)%1f%(objecttype)%1f%(contents:signature)%1e",
+                "refs/tags",
+            ])
+            .await?;
+
+        let mut signed = Vec::new();
+        for entry in refs.split('\x1e').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let mut fields = entry.split('\x1f');
+            let name = fields.next().unwrap_or_default().to_string();
+            let kind = fields.next().unwrap_or_default();
+            let armor = fields.next().unwrap_or_default();
+
+            // Lightweight tags and unsigned annotated tags carry no signature
+            let signature = if kind == "tag" && !armor.trim().is_empty() {
+                let verified = self.git(&["verify-tag", &name]).await.is_ok();
+                Some(Signature {
+                    format: SignatureFormat::detect(armor),
+                    verified,
+                    reason: (!verified).then(|| "git could not verify the signature".to_string()),
+                })
+            } else {
+                None
+            };
+            signed.push(SignedObject { id: name, signature });
+        }
+        Ok(signed)
+    }
+
+    async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
+        let files = self.list_files(repo, None).await?;
+
+        let has_ci = files
+            .iter()
+            .any(|f| CI_INDICATORS.iter().any(|ci| f == ci || f.starts_with(ci)));
+        let has_security_policy = files
+            .iter()
+            .any(|f| f == "SECURITY.md" || f == ".github/SECURITY.md");
+        let head = repo.clone();
+        let (default_branch, description, last_push) = self
+            .blocking(move |adapter| {
+                Ok((adapter.default_branch(), adapter.description(), adapter.last_commit_time(&head)))
+            })
+            .await?;
+
+        Ok(RepoMetadata {
+            default_branch,
+            description,
+            has_issues: false, // Not applicable to local repositories
+            has_wiki: false,
+            has_pages: false,
+            has_ci,
+            has_branch_protection: false,
+            has_security_policy,
+            open_issues_count: 0,
+            stargazers_count: 0,
+            forks_count: 0,
+            upstream: None,
+            archived: false,
+            license: None,
+            topics: Vec::new(),
+            last_push,
+        })
+    }
+}
+
+/// Signature format of each commit in `git log --format=raw` output; `None`
+/// for commits with no signature header
+fn signature_headers(raw: &str) -> std::collections::HashMap<String, Option<SignatureFormat>> {
+    let mut formats = std::collections::HashMap::new();
+    let mut current = None;
+    for line in raw.lines() {
+        if let Some(sha) = line.strip_prefix("commit ") {
+            let sha = sha.split_whitespace().next().unwrap_or_default().to_string();
+            formats.insert(sha.clone(), None);
+            current = Some(sha);
+        } else if let Some(armor) = line.strip_prefix("gpgsig ").or_else(|| line.strip_prefix("gpgsig-sha256 ")) {
+            if let Some(ref sha) = current {
+                formats.insert(sha.clone(), SignatureFormat::detect(armor));
+            }
+        }
+    }
+    formats
+}
+
+/// Signature from a `%G?` verdict, or `None` for an unsigned commit
+fn commit_signature(verdict: &str, format: Option<SignatureFormat>) -> Option<Signature> {
+    let reason = match verdict.trim() {
+        "N" | "" => return None,
+        "G" => None,
+        "B" => Some("bad signature"),
+        "U" => Some("good signature of unknown validity"),
+        "X" => Some("expired signature"),
+        "Y" => Some("signed by an expired key"),
+        "R" => Some("signed by a revoked key"),
+        _ => Some("signature cannot be checked"),
+    };
+    Some(Signature {
+        format,
+        verified: reason.is_none(),
+        reason: reason.map(str::to_string),
+    })
+}
+
+/// Whether `path` names something inside the repository: relative, with no
+/// `.`, `..`, root or drive components
+fn is_repo_relative(path: &str) -> bool {
+    !path.is_empty() && Path::new(path).components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Recursively collect files in a working tree, skipping the `.git` directory
+pub(crate) fn walk_workdir(root: &Path, dir: &Path, files: &mut Vec<String>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if entry.file_name() == ".git" {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            walk_workdir(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A working tree with a README, next to a file outside it
+    fn workdir() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("repo");
+        std::fs::create_dir(&root).unwrap();
+        let status = std::process::Command::new("git").arg("init").arg("-q").arg(&root).status().unwrap();
+        assert!(status.success());
+        std::fs::write(root.join("README.md"), "# app\n").unwrap();
+        std::fs::write(dir.path().join("secret"), "hunter2").unwrap();
+        (dir, root)
+    }
+
+    #[tokio::test]
+    async fn reads_files_inside_the_working_tree() {
+        let (_dir, root) = workdir();
+        let adapter = LocalRepoAdapter::open(&root).unwrap();
+        let repo = RepoRef::new("local", "acme", "app");
+
+        assert_eq!(adapter.fetch_file(&repo, "README.md").await.unwrap(), b"# app\n");
+        assert!(matches!(
+            adapter.fetch_file(&repo, "LICENSE").await,
+            Err(RsrError::RepoNotFound { .. })
+        ));
+        assert_eq!(adapter.list_files(&repo, None).await.unwrap(), ["README.md"]);
+    }
+
+    #[tokio::test]
+    async fn paths_outside_the_working_tree_are_rejected() {
+        let (dir, root) = workdir();
+        let adapter = LocalRepoAdapter::open(&root).unwrap();
+        let repo = RepoRef::new("local", "acme", "app");
+        let absolute = dir.path().join("secret").display().to_string();
+
+        for path in ["../secret", "docs/../../secret", absolute.as_str(), "./README.md", ""] {
+            let read = adapter.fetch_file(&repo, path).await;
+            assert!(matches!(read, Err(RsrError::Platform(_))), "{}: {:?}", path, read);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn symlinks_out_of_the_working_tree_are_rejected() {
+        let (dir, root) = workdir();
+        std::os::unix::fs::symlink(dir.path().join("secret"), root.join("linked")).unwrap();
+        let adapter = LocalRepoAdapter::open(&root).unwrap();
+
+        let read = adapter.fetch_file(&RepoRef::new("local", "acme", "app"), "linked").await;
+        assert!(matches!(read, Err(RsrError::Platform(_))), "{:?}", read);
+    }
+}
@@ -2,43 +2,215 @@
 //!
 //! Each adapter implements the `PlatformAdapter` trait, providing a unified
 //! interface for webhook parsing, status posting, and content fetching.
+//! Adapters for niche forges can live in their own crates and be registered
+//! at runtime with `AdapterFactory::register`.
 
 pub mod github;
 pub mod gitlab;
 pub mod bitbucket;
+pub mod codecommit;
+pub mod gerrit;
 pub mod gitea;
+pub mod http;
+pub mod local;
+pub mod pagination;
+pub mod phabricator;
+pub mod signature;
+pub mod sourcehut;
+pub mod strict;
 
-use crate::events::RepoEvent;
+use crate::events::{Commit, RepoEvent, WorkflowConclusion};
 use crate::{ComplianceStatus, RepoRef, Result, RsrError};
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
 
 /// HTTP headers abstraction
 pub type Headers = HashMap<String, String>;
 
+/// Context compliance statuses are posted under, which branch protection
+/// rules name to require them. Never renamed, so those rules keep working.
+pub const STATUS_CONTEXT: &str = "RSR / Compliance Check";
+
+/// Whether `path` lies under the optional directory `prefix`
+pub(crate) fn matches_prefix(path: &str, prefix: Option<&str>) -> bool {
+    match prefix.map(|p| p.trim_matches('/')) {
+        None | Some("") => true,
+        Some(p) => path == p || path.starts_with(&format!("{}/", p)),
+    }
+}
+
+/// Deserialize a webhook payload into its typed model. The error names the
+/// event and the missing or mistyped field. In a [`strict::parse`], fields
+/// the model defaulted are reported as warnings.
+pub(crate) fn decode_payload<T: serde::de::DeserializeOwned>(event: &str, payload: &[u8]) -> Result<T> {
+    let decoded = if strict::collecting() {
+        strict::decode(event, payload)
+    } else {
+        serde_json::from_slice(payload)
+    };
+    decoded.map_err(|e| RsrError::Platform(format!("Invalid {} payload: {}", event, e)))
+}
+
+/// Operations an adapter supports, so callers can skip what a platform lacks
+/// instead of failing on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdapterCapabilities {
+    /// Verifies and parses webhooks
+    pub webhooks: bool,
+    /// Posts compliance status on commits (as a status, check or vote)
+    pub commit_status: bool,
+    /// Reports results as check runs with annotations
+    pub check_runs: bool,
+    /// Posts and edits pull request comments
+    pub comments: bool,
+    /// Submits reviews requesting changes
+    pub reviews: bool,
+    /// Lists repository files
+    pub file_listing: bool,
+}
+
+impl Default for AdapterCapabilities {
+    /// What every full adapter provides: webhooks, status posting and file listing
+    fn default() -> Self {
+        Self {
+            webhooks: true,
+            commit_status: true,
+            check_runs: false,
+            comments: false,
+            reviews: false,
+            file_listing: true,
+        }
+    }
+}
+
 /// Platform adapter trait - implement for each git host
 #[async_trait]
 pub trait PlatformAdapter: Send + Sync {
-    /// Platform identifier (github, gitlab, bitbucket, gitea)
+    /// Platform identifier (github, gitlab, bitbucket, gitea, sourcehut, codecommit, gerrit, phabricator, local)
     fn platform_id(&self) -> &'static str;
 
+    /// Operations this adapter supports
+    fn capabilities(&self) -> AdapterCapabilities {
+        AdapterCapabilities::default()
+    }
+
     /// Verify webhook signature
     fn verify_webhook(&self, payload: &[u8], headers: &Headers) -> Result<bool>;
 
+    /// Unique ID of a webhook delivery, used to reject replays
+    fn delivery_id(&self, _headers: &Headers) -> Option<String> {
+        None
+    }
+
     /// Parse platform-specific webhook into universal event
     fn parse_webhook(&self, payload: &[u8], headers: &Headers) -> Result<RepoEvent>;
 
     /// Post compliance status back to platform (e.g., commit status, check run)
     async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &ComplianceStatus) -> Result<()>;
 
+    /// Post a pull request's compliance status. Defaults to a status on its
+    /// head commit; platforms that gate pull requests some other way (such
+    /// as approvals) override it.
+    async fn post_pull_request_status(
+        &self,
+        repo: &RepoRef,
+        _number: u64,
+        head_sha: &str,
+        status: &ComplianceStatus,
+    ) -> Result<()> {
+        self.post_status(repo, head_sha, status).await
+    }
+
+    /// Comment on an issue or pull request, returning the new comment's ID
+    async fn post_comment(&self, _repo: &RepoRef, _number: u64, _body: &str) -> Result<String> {
+        Err(RsrError::Platform(format!("Posting comments is not supported on {}", self.platform_id())))
+    }
+
+    /// Replace the body of a comment created by `post_comment`
+    async fn update_comment(&self, _repo: &RepoRef, _comment_id: &str, _body: &str) -> Result<()> {
+        Err(RsrError::Platform(format!("Updating comments is not supported on {}", self.platform_id())))
+    }
+
+    /// Submit a review asking for changes on a pull request, blocking it
+    /// where the platform enforces reviews
+    async fn request_changes(&self, _repo: &RepoRef, _number: u64, _body: &str) -> Result<()> {
+        Err(RsrError::Platform(format!("Requesting changes is not supported on {}", self.platform_id())))
+    }
+
     /// Fetch repository file contents
     async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>>;
 
-    /// List files in repository
+    /// Fetch several files at once, keyed by path. Missing files are left out.
+    ///
+    /// The default fetches one file at a time; adapters with a batch API
+    /// should override it.
+    async fn fetch_files(&self, repo: &RepoRef, paths: &[&str]) -> Result<HashMap<String, Vec<u8>>> {
+        let mut files = HashMap::new();
+        for path in paths {
+            match self.fetch_file(repo, path).await {
+                Ok(content) => {
+                    files.insert(path.to_string(), content);
+                }
+                Err(RsrError::RepoNotFound { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(files)
+    }
+
+    /// List files in repository, recursively (optionally only under `path`)
     async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<String>>;
 
     /// Get repository metadata
     async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata>;
+
+    /// Latest commits on `repo.branch` (or the default branch), newest first.
+    /// Authors whose email isn't linked to an account on the platform have
+    /// an empty `id`.
+    async fn list_commits(&self, _repo: &RepoRef, _limit: usize) -> Result<Vec<Commit>> {
+        Err(RsrError::Platform(format!("Listing commits is not supported on {}", self.platform_id())))
+    }
+
+    /// Whether `username` belongs to the organization `owner`, or `None` if
+    /// the platform can't tell
+    async fn is_member(&self, _owner: &str, _username: &str) -> Result<Option<bool>> {
+        Ok(None)
+    }
+
+    /// Up to `limit` of the repository's tag names, newest first where the
+    /// platform orders them
+    async fn list_tags(&self, _repo: &RepoRef, _limit: usize) -> Result<Vec<String>> {
+        Err(RsrError::Platform(format!("Listing tags is not supported on {}", self.platform_id())))
+    }
+
+    /// Up to `limit` of the repository's releases, drafts included, newest first
+    async fn list_releases(&self, _repo: &RepoRef, _limit: usize) -> Result<Vec<Release>> {
+        Err(RsrError::Platform(format!("Listing releases is not supported on {}", self.platform_id())))
+    }
+
+    /// Up to `limit` of the repository's latest CI workflow runs, newest first
+    async fn list_workflow_runs(&self, _repo: &RepoRef, _limit: usize) -> Result<Vec<WorkflowRun>> {
+        Err(RsrError::Platform(format!("Listing workflow runs is not supported on {}", self.platform_id())))
+    }
+
+    /// Signatures of the latest commits on `repo.branch` (or the default
+    /// branch), newest first
+    async fn commit_signatures(&self, _repo: &RepoRef, _limit: usize) -> Result<Vec<SignedObject>> {
+        Err(RsrError::Platform(format!("Reading commit signatures is not supported on {}", self.platform_id())))
+    }
+
+    /// Signatures of up to `limit` of the repository's tags, newest first
+    /// where the platform orders them. Lightweight tags are unsigned.
+    async fn tag_signatures(&self, _repo: &RepoRef, _limit: usize) -> Result<Vec<SignedObject>> {
+        Err(RsrError::Platform(format!("Reading tag signatures is not supported on {}", self.platform_id())))
+    }
+
+    /// Protection rule of `branch`, or `None` if it isn't protected.
+    /// Platforms usually require admin access to read it.
+    async fn get_branch_protection(&self, _repo: &RepoRef, _branch: &str) -> Result<Option<BranchProtection>> {
+        Err(RsrError::Platform(format!("Reading branch protection is not supported on {}", self.platform_id())))
+    }
 }
 
 /// Repository metadata from platform API
@@ -55,11 +227,120 @@ pub struct RepoMetadata {
     pub open_issues_count: u32,
     pub stargazers_count: u32,
     pub forks_count: u32,
+    /// `owner/repo` on the same platform this repository was forked from
+    pub upstream: Option<String>,
+    /// Read-only and no longer maintained
+    pub archived: bool,
     pub license: Option<String>,
     pub topics: Vec<String>,
     pub last_push: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+/// A release published on the platform for a tag
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Release {
+    pub tag: String,
+    pub name: Option<String>,
+    /// Release notes, if any were written
+    pub notes: Option<String>,
+    pub draft: bool,
+    pub prerelease: bool,
+    /// Files attached to the release, not counting generated source archives
+    pub assets: usize,
+    /// When the release was published; drafts have none
+    pub published_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// What a branch protection rule enforces
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BranchProtection {
+    /// Approving reviews a pull request needs before it can merge
+    pub required_approvals: u32,
+    /// Status check contexts that must pass before a pull request can merge
+    pub required_status_checks: Vec<String>,
+    pub allows_force_pushes: bool,
+    pub requires_signed_commits: bool,
+}
+
+/// A commit or tag and its signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedObject {
+    /// Commit SHA or tag name
+    pub id: String,
+    /// `None` if the object isn't signed
+    pub signature: Option<Signature>,
+}
+
+/// A signature on a commit or annotated tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    /// `None` if the signature is in a format the engine doesn't know
+    pub format: Option<SignatureFormat>,
+    /// Whether the platform (or git, for local repositories) verified it
+    /// against a key it trusts for the signer
+    pub verified: bool,
+    /// Why it didn't verify, e.g. `unknown_key` or `bad signature`
+    pub reason: Option<String>,
+}
+
+/// How an object was signed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SignatureFormat {
+    Gpg,
+    Ssh,
+    /// X.509 (S/MIME), which Sigstore's gitsign produces
+    X509,
+}
+
+impl SignatureFormat {
+    /// Format of an ASCII-armored signature
+    pub fn detect(signature: &str) -> Option<Self> {
+        match signature.trim_start().lines().next()?.trim() {
+            "-----BEGIN PGP SIGNATURE-----" => Some(Self::Gpg),
+            "-----BEGIN SSH SIGNATURE-----" => Some(Self::Ssh),
+            "-----BEGIN SIGNED MESSAGE-----" => Some(Self::X509),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Gpg => "GPG",
+            Self::Ssh => "SSH",
+            Self::X509 => "X.509/Sigstore",
+        }
+    }
+}
+
+/// A CI workflow run, as of its latest attempt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkflowRun {
+    pub workflow: String,
+    /// Commit the run tested
+    pub head_sha: String,
+    /// Attempts so far; more than one when the run was re-run
+    pub attempt: u32,
+    /// How the latest attempt ended; `None` while it is still running
+    pub conclusion: Option<WorkflowConclusion>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Parse a webhook delivery to `platform` without verifying its signature,
+/// e.g. to replay an archived delivery or to fuzz the parsers. Malformed
+/// payloads are errors; no input may panic.
+pub fn parse_webhook_bytes(platform: &str, payload: &[u8], headers: &Headers) -> Result<RepoEvent> {
+    AdapterFactory::create(platform, AdapterConfig::default())?.parse_webhook(payload, headers)
+}
+
+/// Builds a registered adapter from its config
+pub type AdapterConstructor = Arc<dyn Fn(AdapterConfig) -> Result<Box<dyn PlatformAdapter>> + Send + Sync>;
+
+/// Adapters registered at runtime, keyed by lowercase platform id
+fn registry() -> &'static RwLock<HashMap<String, AdapterConstructor>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, AdapterConstructor>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
 /// Factory for creating platform adapters
 pub struct AdapterFactory;
 
@@ -71,18 +352,77 @@ pub fn create(platform: &str, config: AdapterConfig) -> Result<Box<dyn PlatformA
             "gitlab" => Ok(Box::new(gitlab::GitLabAdapter::new(config))),
             "bitbucket" => Ok(Box::new(bitbucket::BitbucketAdapter::new(config))),
             "gitea" | "forgejo" => Ok(Box::new(gitea::GiteaAdapter::new(config))),
-            _ => Err(RsrError::Platform(format!("Unknown platform: {}", platform))),
+            "sourcehut" | "srht" => Ok(Box::new(sourcehut::SourceHutAdapter::new(config))),
+            "codecommit" => Ok(Box::new(codecommit::CodeCommitAdapter::new(config))),
+            "gerrit" => Ok(Box::new(gerrit::GerritAdapter::new(config))),
+            "phabricator" | "phorge" => Ok(Box::new(phabricator::PhabricatorAdapter::new(config))),
+            "local" => Ok(Box::new(local::LocalRepoAdapter::new(config)?)),
+            other => {
+                let constructor = registry()
+                    .read()
+                    .expect("adapter registry lock poisoned")
+                    .get(other)
+                    .cloned();
+                match constructor {
+                    Some(constructor) => constructor(config),
+                    None => Err(RsrError::Platform(format!("Unknown platform: {}", platform))),
+                }
+            }
         }
     }
 
+    /// Register an out-of-tree adapter under `platform`, making it available
+    /// to `create`, config validation and the webhook server.
+    ///
+    /// Built-in platforms can't be replaced and each id can be registered once.
+    pub fn register<F>(platform: &str, constructor: F) -> Result<()>
+    where
+        F: Fn(AdapterConfig) -> Result<Box<dyn PlatformAdapter>> + Send + Sync + 'static,
+    {
+        let platform = platform.to_lowercase();
+        if platform.is_empty() || !platform.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(RsrError::Config(format!("Invalid platform id: {:?}", platform)));
+        }
+        if Self::supported_platforms().contains(&platform.as_str()) || matches!(platform.as_str(), "srht" | "local") {
+            return Err(RsrError::Config(format!("{} is a built-in platform", platform)));
+        }
+
+        let mut registry = registry().write().expect("adapter registry lock poisoned");
+        if registry.contains_key(&platform) {
+            return Err(RsrError::Config(format!("An adapter is already registered for {}", platform)));
+        }
+        registry.insert(platform, Arc::new(constructor));
+
+        Ok(())
+    }
+
     /// Get list of supported platforms
     pub fn supported_platforms() -> &'static [&'static str] {
-        &["github", "gitlab", "bitbucket", "gitea", "forgejo"]
+        &["github", "gitlab", "bitbucket", "gitea", "forgejo", "sourcehut", "codecommit", "gerrit", "phabricator", "phorge"]
+    }
+
+    /// Platforms added with `register`, sorted
+    pub fn registered_platforms() -> Vec<String> {
+        let mut platforms: Vec<String> = registry()
+            .read()
+            .expect("adapter registry lock poisoned")
+            .keys()
+            .cloned()
+            .collect();
+        platforms.sort();
+        platforms
+    }
+
+    /// Whether `platform` names a built-in or registered adapter
+    pub fn is_supported(platform: &str) -> bool {
+        let platform = platform.to_lowercase();
+        Self::supported_platforms().contains(&platform.as_str())
+            || registry().read().expect("adapter registry lock poisoned").contains_key(&platform)
     }
 }
 
 /// Configuration for platform adapters
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct AdapterConfig {
     /// API base URL (for self-hosted instances)
     pub api_url: Option<String>,
@@ -94,6 +434,41 @@ pub struct AdapterConfig {
     pub app_id: Option<String>,
     /// Private key (for GitHub Apps)
     pub private_key: Option<String>,
+    /// Repository path on disk (for the local adapter)
+    pub repo_path: Option<std::path::PathBuf>,
+    /// Report results as Check Runs instead of commit statuses (GitHub)
+    pub use_check_runs: bool,
+    /// Accept sha1 `X-Hub-Signature` when no sha256 signature is sent (older GHES)
+    pub allow_legacy_signatures: bool,
+    /// Reject webhooks when no secret is configured instead of accepting them
+    /// unverified. On by default in release builds.
+    pub require_signature: bool,
+    /// Review label to vote on (Gerrit, defaults to `Verified`)
+    pub review_label: Option<String>,
+    /// Retry and rate-limit handling for API requests
+    pub retry: http::RetryPolicy,
+    /// Cache for ETag revalidation of file, listing and metadata requests
+    pub etag_cache: Option<std::sync::Arc<dyn http::EtagCache>>,
+}
+
+impl Default for AdapterConfig {
+    fn default() -> Self {
+        Self {
+            api_url: None,
+            api_token: None,
+            webhook_secret: None,
+            app_id: None,
+            private_key: None,
+            repo_path: None,
+            use_check_runs: false,
+            allow_legacy_signatures: false,
+            // Fail closed in production, stay convenient for local development
+            require_signature: !cfg!(debug_assertions),
+            review_label: None,
+            retry: http::RetryPolicy::default(),
+            etag_cache: None,
+        }
+    }
 }
 
 impl AdapterConfig {
@@ -115,4 +490,53 @@ pub fn with_api_url(mut self, url: impl Into<String>) -> Self {
         self.api_url = Some(url.into());
         self
     }
+
+    pub fn with_repo_path(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.repo_path = Some(path.into());
+        self
+    }
+
+    pub fn with_check_runs(mut self, enabled: bool) -> Self {
+        self.use_check_runs = enabled;
+        self
+    }
+
+    pub fn with_legacy_signatures(mut self, enabled: bool) -> Self {
+        self.allow_legacy_signatures = enabled;
+        self
+    }
+
+    pub fn with_require_signature(mut self, required: bool) -> Self {
+        self.require_signature = required;
+        self
+    }
+
+    pub fn with_review_label(mut self, label: impl Into<String>) -> Self {
+        self.review_label = Some(label.into());
+        self
+    }
+
+    /// Outcome of verifying a webhook when no secret is configured:
+    /// accepted with a warning, or refused when signatures are required
+    pub(crate) fn unsigned_webhook(&self, platform: &str) -> Result<bool> {
+        if self.require_signature {
+            return Err(RsrError::InsecureConfiguration(format!(
+                "no webhook secret configured for {} and signatures are required",
+                platform
+            )));
+        }
+
+        tracing::warn!("Webhook secret not configured for {} - skipping verification", platform);
+        Ok(true)
+    }
+
+    pub fn with_retry_policy(mut self, policy: http::RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    pub fn with_etag_cache(mut self, cache: std::sync::Arc<dyn http::EtagCache>) -> Self {
+        self.etag_cache = Some(cache);
+        self
+    }
 }
@@ -0,0 +1,61 @@
+//! Link-header pagination shared by the REST adapters
+//!
+//! GitHub, GitLab and Gitea all advertise further pages with an RFC 8288
+//! `Link: <url>; rel="next"` header.
+
+use super::http::HttpLayer;
+use crate::{Result, RsrError};
+
+/// Hard stop so a misbehaving server can't keep us paging forever
+const MAX_PAGES: usize = 100;
+
+/// URL of the next page from a `Link` header, if there is one
+pub fn next_page_url(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    let link = headers.get(reqwest::header::LINK)?.to_str().ok()?;
+
+    link.split(',').find_map(|part| {
+        let mut sections = part.split(';');
+        let url = sections.next()?.trim().strip_prefix('<')?.strip_suffix('>')?;
+        sections
+            .any(|param| matches!(param.trim(), r#"rel="next""# | "rel=next"))
+            .then(|| url.to_string())
+    })
+}
+
+/// Follow `rel="next"` links from `first_url`, collecting the items of every page.
+///
+/// `request` builds an authenticated GET for a URL and `items` pulls the
+/// entries out of one page's JSON body. Each page goes through `http`, so
+/// rate limits and transient failures are handled per page, and unchanged
+/// pages are revalidated against the ETag cache.
+pub async fn fetch_all_pages<R, I>(
+    http: &HttpLayer,
+    first_url: &str,
+    request: R,
+    items: I,
+) -> Result<Vec<serde_json::Value>>
+where
+    R: Fn(&str) -> reqwest::RequestBuilder,
+    I: Fn(serde_json::Value) -> Vec<serde_json::Value>,
+{
+    let mut collected = Vec::new();
+    let mut url = Some(first_url.to_string());
+
+    for _ in 0..MAX_PAGES {
+        let Some(page_url) = url.take() else {
+            return Ok(collected);
+        };
+
+        let page = http.get(request(&page_url)).await?;
+
+        if !page.status.is_success() {
+            return Err(RsrError::Platform(format!("Request failed ({}): {}", page.status, page.text())));
+        }
+
+        url = page.next_page.clone();
+        collected.extend(items(page.json()?));
+    }
+
+    tracing::warn!("Stopped paginating {} after {} pages", first_url, MAX_PAGES);
+    Ok(collected)
+}
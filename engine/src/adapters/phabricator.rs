@@ -0,0 +1,241 @@
+//! Phabricator/Phorge platform adapter (read-only)
+//!
+//! Covers installs that are still mid-migration: files, listings and metadata
+//! come from the Conduit API so reports can be produced, but webhooks and
+//! status posting are not supported.
+//!
+//! Repositories are identified by short name or callsign in `RepoRef::repo`;
+//! Phabricator has no owners, so `RepoRef::owner` is ignored. The API token is
+//! a Conduit token (`api-...`).
+
+use super::http::{HttpLayer, SendVia};
+use super::{AdapterCapabilities, AdapterConfig, Headers, PlatformAdapter, RepoMetadata};
+use crate::events::RepoEvent;
+use crate::{ComplianceStatus, RepoRef, Result, RsrError};
+use async_trait::async_trait;
+use base64::Engine;
+
+pub struct PhabricatorAdapter {
+    config: AdapterConfig,
+    client: reqwest::Client,
+    http: HttpLayer,
+    api_url: String,
+}
+
+impl PhabricatorAdapter {
+    pub fn new(config: AdapterConfig) -> Self {
+        // Phabricator has no public default instance
+        let api_url = config
+            .api_url
+            .clone()
+            .unwrap_or_else(|| {
+                tracing::warn!("No API URL configured for Phabricator - using placeholder");
+                "https://phabricator.example.com".to_string()
+            })
+            .trim_end_matches('/')
+            .to_string();
+
+        let http = HttpLayer::new("phabricator", config.retry.clone());
+
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            http,
+            api_url,
+        }
+    }
+
+    /// Call a Conduit method, e.g. `diffusion.querypaths`, returning its `result`
+    async fn call(&self, method: &str, mut params: serde_json::Value) -> Result<serde_json::Value> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("Conduit API token required".to_string()));
+        };
+
+        params["__conduit__"] = serde_json::json!({ "token": token });
+        let form = [
+            ("params", params.to_string()),
+            ("output", "json".to_string()),
+            ("__conduit__", "1".to_string()),
+        ];
+
+        let response = self.client
+            .post(format!("{}/api/{}", self.api_url, method))
+            .form(&form)
+            .send_via(&self.http)
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RsrError::Platform(format!("Conduit {} failed: {}", method, error_text)));
+        }
+
+        let mut json: serde_json::Value = response.json().await?;
+
+        // Conduit reports errors in the body with a 200 status
+        if let Some(code) = json["error_code"].as_str() {
+            let info = json["error_info"].as_str().unwrap_or_default();
+            return Err(RsrError::Platform(format!("Conduit {} failed ({}): {}", method, code, info)));
+        }
+
+        Ok(json["result"].take())
+    }
+
+    /// Look up a repository by short name, then by callsign
+    async fn find_repository(&self, repo: &RepoRef) -> Result<serde_json::Value> {
+        for constraint in ["shortNames", "callsigns"] {
+            let mut result = self
+                .call(
+                    "diffusion.repository.search",
+                    serde_json::json!({ "constraints": { constraint: [repo.repo] } }),
+                )
+                .await?;
+
+            if let Some(found) = result["data"].as_array_mut().and_then(|data| data.pop()) {
+                return Ok(found);
+            }
+        }
+
+        Err(RsrError::RepoNotFound {
+            owner: repo.owner.clone(),
+            repo: repo.repo.clone(),
+        })
+    }
+
+    /// Branch to read from: the requested one, or the repository default
+    async fn commit(&self, repo: &RepoRef) -> Result<String> {
+        match repo.branch {
+            Some(ref branch) => Ok(branch.clone()),
+            None => Ok(default_branch(&self.find_repository(repo).await?)),
+        }
+    }
+}
+
+#[async_trait]
+impl PlatformAdapter for PhabricatorAdapter {
+    fn platform_id(&self) -> &'static str {
+        "phabricator"
+    }
+
+    fn capabilities(&self) -> AdapterCapabilities {
+        AdapterCapabilities {
+            webhooks: false,
+            commit_status: false,
+            ..Default::default()
+        }
+    }
+
+    fn verify_webhook(&self, _payload: &[u8], _headers: &Headers) -> Result<bool> {
+        Err(RsrError::Platform("The Phabricator adapter is read-only and does not accept webhooks".to_string()))
+    }
+
+    fn parse_webhook(&self, _payload: &[u8], _headers: &Headers) -> Result<RepoEvent> {
+        Err(RsrError::Platform("The Phabricator adapter is read-only and does not accept webhooks".to_string()))
+    }
+
+    async fn post_status(&self, _repo: &RepoRef, _commit_sha: &str, _status: &ComplianceStatus) -> Result<()> {
+        Err(RsrError::Platform("The Phabricator adapter is read-only and cannot post status".to_string()))
+    }
+
+    async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
+        let commit = self.commit(repo).await?;
+
+        let content = self
+            .call(
+                "diffusion.filecontentquery",
+                serde_json::json!({
+                    "repository": repo.repo,
+                    "path": path,
+                    "commit": commit,
+                }),
+            )
+            .await
+            .map_err(|e| match e {
+                // Missing paths surface as a core error rather than a distinct code
+                RsrError::Platform(ref message) if message.contains("does not exist") => RsrError::RepoNotFound {
+                    owner: repo.owner.clone(),
+                    repo: repo.repo.clone(),
+                },
+                e => e,
+            })?;
+
+        if content["tooHuge"].as_bool() == Some(true) || content["tooSlow"].as_bool() == Some(true) {
+            return Err(RsrError::Platform(format!("{} is too large to fetch through Conduit", path)));
+        }
+
+        let Some(file_phid) = content["filePHID"].as_str() else {
+            return Err(RsrError::RepoNotFound {
+                owner: repo.owner.clone(),
+                repo: repo.repo.clone(),
+            });
+        };
+
+        let data = self
+            .call("file.download", serde_json::json!({ "phid": file_phid }))
+            .await?;
+
+        base64::engine::general_purpose::STANDARD
+            .decode(data.as_str().unwrap_or_default())
+            .map_err(|e| RsrError::Platform(format!("Invalid file encoding: {}", e)))
+    }
+
+    async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<String>> {
+        let commit = self.commit(repo).await?;
+
+        // `diffusion.querypaths` lists files recursively under `path`
+        let paths = self
+            .call(
+                "diffusion.querypaths",
+                serde_json::json!({
+                    "repository": repo.repo,
+                    "path": path.unwrap_or("").trim_matches('/'),
+                    "commit": commit,
+                }),
+            )
+            .await?;
+
+        let mut files: Vec<String> = paths
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p.as_str().map(String::from))
+            .collect();
+        files.sort();
+
+        Ok(files)
+    }
+
+    async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
+        let found = self.find_repository(repo).await?;
+        let fields = &found["fields"];
+
+        Ok(RepoMetadata {
+            default_branch: default_branch(&found),
+            description: fields["description"]["raw"]
+                .as_str()
+                .filter(|d| !d.is_empty())
+                .map(String::from),
+            // Maniphest and Phriction are install-wide, not per repository
+            has_issues: false,
+            has_wiki: false,
+            has_pages: false,
+            has_ci: false,
+            has_branch_protection: false,
+            has_security_policy: false,
+            open_issues_count: 0,
+            stargazers_count: 0,
+            forks_count: 0,
+            upstream: None,
+            archived: false,
+            license: None,
+            topics: Vec::new(),
+            last_push: None,
+        })
+    }
+}
+
+fn default_branch(repository: &serde_json::Value) -> String {
+    repository["fields"]["defaultBranch"]
+        .as_str()
+        .unwrap_or("master")
+        .to_string()
+}
@@ -0,0 +1,148 @@
+//! Webhook signature verification shared by the adapters
+//!
+//! Comparisons go through `subtle`, so their timing doesn't reveal how much of
+//! a forged signature or token matched. New adapters should use these helpers
+//! rather than comparing secrets themselves.
+
+use hmac::{Hmac, Mac};
+use subtle::ConstantTimeEq;
+
+/// Hash function behind an HMAC webhook signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HmacAlgorithm {
+    /// Legacy signatures (e.g. GitHub's `X-Hub-Signature`)
+    Sha1,
+    Sha256,
+}
+
+impl HmacAlgorithm {
+    /// Prefix some platforms put before the hex digest, e.g. `sha256=`
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::Sha1 => "sha1=",
+            Self::Sha256 => "sha256=",
+        }
+    }
+
+    fn mac(self, secret: &[u8], payload: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha1 => {
+                let mut mac = Hmac::<sha1::Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+                mac.update(payload);
+                mac.finalize().into_bytes().to_vec()
+            }
+            Self::Sha256 => {
+                let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+                mac.update(payload);
+                mac.finalize().into_bytes().to_vec()
+            }
+        }
+    }
+}
+
+/// Compare two values without leaking where they differ. Only the lengths
+/// are compared in variable time.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// Check a hex-encoded HMAC of `payload`. The algorithm prefix (`sha256=`) is
+/// optional and hex digits may be either case.
+pub fn verify_hmac(algorithm: HmacAlgorithm, secret: &[u8], payload: &[u8], signature: &str) -> bool {
+    let signature = signature.trim();
+    let signature = signature.strip_prefix(algorithm.prefix()).unwrap_or(signature);
+
+    let Ok(presented) = hex::decode(signature) else {
+        return false;
+    };
+
+    constant_time_eq(&presented, &algorithm.mac(secret, payload))
+}
+
+/// Sign `payload` the way GitHub-style platforms do: `sha256=<hex digest>`
+pub fn sign_hmac(algorithm: HmacAlgorithm, secret: &[u8], payload: &[u8]) -> String {
+    format!("{}{}", algorithm.prefix(), hex::encode(algorithm.mac(secret, payload)))
+}
+
+/// A known-good signature, for checking an adapter's verification
+#[derive(Debug, Clone, Copy)]
+pub struct TestVector {
+    pub algorithm: HmacAlgorithm,
+    pub secret: &'static str,
+    pub payload: &'static [u8],
+    /// Hex digest, without prefix
+    pub signature: &'static str,
+}
+
+/// Published HMAC test vectors: GitHub's webhook documentation example and
+/// RFC 2202 / RFC 4231 test case 2
+pub const TEST_VECTORS: &[TestVector] = &[
+    TestVector {
+        algorithm: HmacAlgorithm::Sha256,
+        secret: "It's a Secret to Everybody",
+        payload: b"Hello, World!",
+        signature: "757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17",
+    },
+    TestVector {
+        algorithm: HmacAlgorithm::Sha1,
+        secret: "Jefe",
+        payload: b"what do ya want for nothing?",
+        signature: "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+    },
+    TestVector {
+        algorithm: HmacAlgorithm::Sha256,
+        secret: "Jefe",
+        payload: b"what do ya want for nothing?",
+        signature: "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn published_vectors_verify_and_sign() {
+        for vector in TEST_VECTORS {
+            let secret = vector.secret.as_bytes();
+            assert!(
+                verify_hmac(vector.algorithm, secret, vector.payload, vector.signature),
+                "{:?} vector for {:?}",
+                vector.algorithm,
+                vector.secret
+            );
+            assert_eq!(
+                sign_hmac(vector.algorithm, secret, vector.payload),
+                format!("{}{}", vector.algorithm.prefix(), vector.signature)
+            );
+        }
+    }
+
+    #[test]
+    fn prefixed_and_upper_case_signatures_verify() {
+        let vector = &TEST_VECTORS[0];
+        let secret = vector.secret.as_bytes();
+
+        let prefixed = format!("sha256={}", vector.signature);
+        assert!(verify_hmac(HmacAlgorithm::Sha256, secret, vector.payload, &prefixed));
+        assert!(verify_hmac(HmacAlgorithm::Sha256, secret, vector.payload, &vector.signature.to_uppercase()));
+        // Another algorithm's prefix isn't stripped
+        let misprefixed = format!("sha1={}", vector.signature);
+        assert!(!verify_hmac(HmacAlgorithm::Sha256, secret, vector.payload, &misprefixed));
+    }
+
+    #[test]
+    fn one_flipped_bit_fails() {
+        for vector in TEST_VECTORS {
+            let secret = vector.secret.as_bytes();
+
+            let mut signature = hex::decode(vector.signature).unwrap();
+            signature[0] ^= 1;
+            assert!(!verify_hmac(vector.algorithm, secret, vector.payload, &hex::encode(signature)));
+
+            let mut payload = vector.payload.to_vec();
+            payload[0] ^= 1;
+            assert!(!verify_hmac(vector.algorithm, secret, &payload, vector.signature));
+        }
+    }
+}
@@ -0,0 +1,524 @@
+//! SourceHut platform adapter
+//!
+//! Talks to the git.sr.ht and builds.sr.ht GraphQL APIs. SourceHut has no
+//! commit status API, so results are reported by submitting a small
+//! builds.sr.ht job that passes or fails with the compliance outcome and is
+//! tagged with the repository name.
+//!
+//! Webhooks are GraphQL webhooks: the delivery body is the result of the query
+//! registered with the hook, so it must select at least
+//!
+//! ```graphql
+//! query {
+//!   webhook {
+//!     uuid
+//!     event
+//!     ... on RepositoryEvent { repository { name owner { canonicalName } } }
+//!     ... on GitEvent {
+//!       repository { name owner { canonicalName } }
+//!       pusher { canonicalName }
+//!       updates { ref { name } old { id } new { id } }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Deliveries are signed with the instance's Ed25519 key; configure its
+//! base64 public key as the webhook secret.
+
+use super::http::{HttpLayer, SendVia};
+use super::{decode_payload, AdapterConfig, Headers, PlatformAdapter, RepoMetadata};
+use crate::events::*;
+use crate::{CertificationTier, ComplianceStatus, RepoRef, Result, RsrError};
+use async_trait::async_trait;
+use base64::Engine;
+use serde::Deserialize;
+
+const DEFAULT_API_URL: &str = "https://git.sr.ht";
+
+/// Selection for one page of tree entries
+const TREE_ENTRIES: &str = "entries(cursor: $cursor) { results { name object { type } } cursor }";
+
+pub struct SourceHutAdapter {
+    config: AdapterConfig,
+    client: reqwest::Client,
+    http: HttpLayer,
+    api_url: String,
+    builds_url: String,
+}
+
+impl SourceHutAdapter {
+    pub fn new(config: AdapterConfig) -> Self {
+        let api_url = config
+            .api_url
+            .clone()
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string())
+            .trim_end_matches('/')
+            .to_string();
+
+        // builds.sr.ht lives next to git.sr.ht on every instance
+        let builds_url = api_url.replacen("://git.", "://builds.", 1);
+
+        let http = HttpLayer::new("sourcehut", config.retry.clone());
+
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            http,
+            api_url,
+            builds_url,
+        }
+    }
+
+    /// Run a GraphQL query against `{base}/query` and return its `data`
+    async fn query(&self, base: &str, query: &str, variables: serde_json::Value) -> Result<serde_json::Value> {
+        let Some(ref token) = self.config.api_token else {
+            return Err(RsrError::Config("API token required".to_string()));
+        };
+
+        let response = self.client
+            .post(format!("{}/query", base))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&serde_json::json!({ "query": query, "variables": variables }))
+            .send_via(&self.http)
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(RsrError::Platform(format!("GraphQL request failed ({}): {}", status, error_text)));
+        }
+
+        let mut json: serde_json::Value = response.json().await?;
+        if let Some(errors) = json["errors"].as_array().filter(|e| !e.is_empty()) {
+            return Err(RsrError::Platform(format!("GraphQL query failed: {}", serde_json::Value::from(errors.clone()))));
+        }
+
+        Ok(json["data"].take())
+    }
+
+    /// The `repository` object for a repo, or `RepoNotFound`.
+    ///
+    /// `params` declares any variables `fields` uses beyond `$owner`/`$name`;
+    /// `$rev`, when declared, is filled in from the ref's branch.
+    async fn repository(
+        &self,
+        repo: &RepoRef,
+        params: &str,
+        fields: &str,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let query = format!(
+            "query($owner: String!, $name: String!{}) {{ \
+             user(username: $owner) {{ repository(name: $name) {{ {} }} }} }}",
+            params, fields
+        );
+
+        let mut vars = serde_json::json!({
+            "owner": repo.owner.trim_start_matches('~'),
+            "name": repo.repo,
+        });
+        if params.contains("$rev") {
+            vars["rev"] = repo.branch.as_deref().unwrap_or("HEAD").into();
+        }
+        if let (Some(vars), Some(extra)) = (vars.as_object_mut(), variables.as_object()) {
+            vars.extend(extra.clone());
+        }
+
+        let mut data = self.query(&self.api_url, &query, vars).await?;
+        let repository = data["user"]["repository"].take();
+        if repository.is_null() {
+            return Err(RsrError::RepoNotFound {
+                owner: repo.owner.clone(),
+                repo: repo.repo.clone(),
+            });
+        }
+
+        Ok(repository)
+    }
+
+    /// Files under `dir`, following tree pagination and descending into subtrees
+    async fn walk_tree(&self, repo: &RepoRef, dir: &str, files: &mut Vec<String>) -> Result<()> {
+        let mut pending = vec![dir.to_string()];
+
+        while let Some(dir) = pending.pop() {
+            let mut cursor: Option<String> = None;
+            loop {
+                // The root tree has no path entry of its own
+                let repository = if dir.is_empty() {
+                    self.repository(
+                        repo,
+                        ", $rev: String!, $cursor: Cursor",
+                        &format!("revparse_single(revspec: $rev) {{ ... on Commit {{ tree {{ {} }} }} }}", TREE_ENTRIES),
+                        serde_json::json!({ "cursor": cursor }),
+                    )
+                    .await?
+                } else {
+                    self.repository(
+                        repo,
+                        ", $rev: String!, $path: String!, $cursor: Cursor",
+                        &format!("path(revspec: $rev, path: $path) {{ object {{ ... on Tree {{ {} }} }} }}", TREE_ENTRIES),
+                        serde_json::json!({ "path": dir, "cursor": cursor }),
+                    )
+                    .await?
+                };
+
+                let entries = if dir.is_empty() {
+                    &repository["revparse_single"]["tree"]["entries"]
+                } else {
+                    &repository["path"]["object"]["entries"]
+                };
+
+                for entry in entries["results"].as_array().into_iter().flatten() {
+                    let Some(name) = entry["name"].as_str() else {
+                        continue;
+                    };
+                    let path = if dir.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{}/{}", dir, name)
+                    };
+                    match entry["object"]["type"].as_str() {
+                        Some("TREE") => pending.push(path),
+                        Some("BLOB") => files.push(path),
+                        _ => {}
+                    }
+                }
+
+                cursor = entries["cursor"].as_str().map(String::from);
+                if cursor.is_none() {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PlatformAdapter for SourceHutAdapter {
+    fn platform_id(&self) -> &'static str {
+        "sourcehut"
+    }
+
+    fn verify_webhook(&self, payload: &[u8], headers: &Headers) -> Result<bool> {
+        let Some(ref public_key) = self.config.webhook_secret else {
+            return self.config.unsigned_webhook(self.platform_id());
+        };
+
+        // Signature covers the body followed by the nonce
+        let (Some(signature), Some(nonce)) = (headers.get("x-payload-signature"), headers.get("x-payload-nonce")) else {
+            return Err(RsrError::WebhookVerification);
+        };
+
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let key: [u8; 32] = b64
+            .decode(public_key.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| RsrError::Config("SourceHut webhook key must be a base64 Ed25519 public key".to_string()))?;
+        let signature: [u8; 64] = b64
+            .decode(signature.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(RsrError::WebhookVerification)?;
+
+        let key = ed25519_dalek::VerifyingKey::from_bytes(&key)
+            .map_err(|e| RsrError::Config(format!("Invalid SourceHut webhook key: {}", e)))?;
+
+        let mut message = payload.to_vec();
+        message.extend_from_slice(nonce.as_bytes());
+
+        Ok(key
+            .verify_strict(&message, &ed25519_dalek::Signature::from_bytes(&signature))
+            .is_ok())
+    }
+
+    fn delivery_id(&self, headers: &Headers) -> Option<String> {
+        headers.get("x-webhook-delivery").cloned()
+    }
+
+    fn parse_webhook(&self, payload: &[u8], _headers: &Headers) -> Result<RepoEvent> {
+        let delivery: Delivery = decode_payload("SourceHut webhook", payload)?;
+        let webhook = delivery.data.webhook;
+
+        let repo_owner = webhook.repository.owner.canonical_name.trim_start_matches('~').to_string();
+        let repo_name = webhook.repository.name.clone();
+
+        match webhook.event.as_str() {
+            "GIT_POST_RECEIVE" => parse_push(webhook, repo_owner, repo_name),
+            "REPO_CREATED" => Ok(RepoEvent::Repository(RepositoryEvent {
+                repo_owner,
+                repo_name,
+                action: RepositoryAction::Created,
+                previous_owner: None,
+                previous_name: None,
+                upstream: None,
+            })),
+            "REPO_DELETED" => Ok(RepoEvent::Repository(RepositoryEvent {
+                repo_owner,
+                repo_name,
+                action: RepositoryAction::Deleted,
+                previous_owner: None,
+                previous_name: None,
+                upstream: None,
+            })),
+            other => Err(RsrError::Platform(format!("Unsupported SourceHut event: {}", other))),
+        }
+    }
+
+    /// Submit a builds.sr.ht job that succeeds or fails with the result.
+    /// The job is tagged `<repo>/rsr` so it groups with the repository's builds.
+    async fn post_status(&self, repo: &RepoRef, commit_sha: &str, status: &ComplianceStatus) -> Result<()> {
+        if self.config.api_token.is_none() {
+            return Err(RsrError::Config("API token required for posting status".to_string()));
+        }
+
+        let summary = format!("RSR Compliance: {} ({:.0}%)", status.tier.code(), status.score * 100.0);
+        let exit = if status.tier >= CertificationTier::Bronze { 0 } else { 1 };
+
+        let mut report = String::new();
+        for check in &status.checks {
+            report.push_str(&format!(
+                "      echo '{} [{}] {} - {}'\n",
+                if check.passed { "PASS" } else { "FAIL" },
+                check.tier.code(),
+                shell_quote(&check.name),
+                shell_quote(&check.message)
+            ));
+        }
+
+        let manifest = format!(
+            "image: alpine/latest\ntasks:\n  - rsr: |\n      echo '{}'\n{}      exit {}\n",
+            shell_quote(&summary),
+            report,
+            exit
+        );
+
+        let note = format!(
+            "{} for [{}](https://git.sr.ht/~{}/{}/commit/{})",
+            summary, &commit_sha[..commit_sha.len().min(12)], repo.owner.trim_start_matches('~'), repo.repo, commit_sha
+        );
+
+        let data = self
+            .query(
+                &self.builds_url,
+                "mutation($manifest: String!, $tags: [String!], $note: String) { \
+                 submit(manifest: $manifest, tags: $tags, note: $note) { id } }",
+                serde_json::json!({
+                    "manifest": manifest,
+                    "tags": [repo.repo, "rsr"],
+                    "note": note,
+                }),
+            )
+            .await?;
+
+        tracing::debug!("Submitted builds.sr.ht job {} for {}", data["submit"]["id"], repo);
+        Ok(())
+    }
+
+    async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
+        let repository = self
+            .repository(
+                repo,
+                ", $rev: String!, $path: String!",
+                "path(revspec: $rev, path: $path) { object { \
+                 ... on TextBlob { text } ... on BinaryBlob { base64 } } }",
+                serde_json::json!({ "path": path }),
+            )
+            .await?;
+
+        let object = &repository["path"]["object"];
+        if let Some(text) = object["text"].as_str() {
+            return Ok(text.as_bytes().to_vec());
+        }
+        if let Some(encoded) = object["base64"].as_str() {
+            return base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(|e| RsrError::Platform(format!("Invalid blob encoding: {}", e)));
+        }
+
+        Err(RsrError::RepoNotFound {
+            owner: repo.owner.clone(),
+            repo: repo.repo.clone(),
+        })
+    }
+
+    async fn list_files(&self, repo: &RepoRef, path: Option<&str>) -> Result<Vec<String>> {
+        let mut files = Vec::new();
+        self.walk_tree(repo, path.unwrap_or("").trim_matches('/'), &mut files)
+            .await?;
+        files.sort();
+        Ok(files)
+    }
+
+    async fn get_metadata(&self, repo: &RepoRef) -> Result<RepoMetadata> {
+        let repository = self
+            .repository(repo, "", "description updated HEAD { name }", serde_json::json!({}))
+            .await?;
+
+        Ok(RepoMetadata {
+            default_branch: repository["HEAD"]["name"]
+                .as_str()
+                .map(|name| name.trim_start_matches("refs/heads/"))
+                .unwrap_or("master")
+                .to_string(),
+            description: repository["description"].as_str().map(String::from),
+            // Trackers, lists and wikis are separate sr.ht services
+            has_issues: false,
+            has_wiki: false,
+            has_pages: false,
+            has_ci: false,
+            has_branch_protection: false,
+            has_security_policy: false,
+            open_issues_count: 0,
+            stargazers_count: 0,
+            forks_count: 0,
+            upstream: None,
+            archived: false,
+            license: None,
+            topics: Vec::new(),
+            last_push: repository["updated"]
+                .as_str()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                .map(|dt| dt.with_timezone(&chrono::Utc)),
+        })
+    }
+}
+
+/// A push event for the first branch updated by a `GIT_POST_RECEIVE` delivery
+fn parse_push(webhook: Webhook, repo_owner: String, repo_name: String) -> Result<RepoEvent> {
+    let pusher = webhook
+        .pusher
+        .ok_or_else(|| RsrError::Platform("Invalid GIT_POST_RECEIVE payload: missing field `pusher`".to_string()))?
+        .canonical_name;
+
+    let update = webhook
+        .updates
+        .into_iter()
+        .find(|u| u.git_ref.name.starts_with("refs/heads/"))
+        .ok_or_else(|| RsrError::Platform("Push did not update any branch".to_string()))?;
+
+    Ok(RepoEvent::Push(PushEvent {
+        repo_owner,
+        repo_name,
+        branch: update.git_ref.name.trim_start_matches("refs/heads/").to_string(),
+        // No previous object when the branch was just created
+        before: update.old.map(|o| o.id).unwrap_or_default(),
+        after: update.new.map(|o| o.id).unwrap_or_default(),
+        // Deliveries list ref updates, not individual commits
+        commits: Vec::new(),
+        pusher: User {
+            id: pusher.clone(),
+            username: pusher.trim_start_matches('~').to_string(),
+            email: None,
+            avatar_url: None,
+        },
+    }))
+}
+
+// Webhook payloads, shaped by the query documented above
+
+#[derive(Debug, Deserialize)]
+struct Delivery {
+    data: DeliveryData,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeliveryData {
+    webhook: Webhook,
+}
+
+#[derive(Debug, Deserialize)]
+struct Webhook {
+    event: String,
+    repository: Repository,
+    /// Only on git events
+    pusher: Option<Entity>,
+    #[serde(default)]
+    updates: Vec<RefUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Repository {
+    name: String,
+    owner: Entity,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entity {
+    canonical_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RefUpdate {
+    #[serde(rename = "ref")]
+    git_ref: Ref,
+    old: Option<GitObject>,
+    new: Option<GitObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Ref {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitObject {
+    id: String,
+}
+
+/// Escape text for a single-quoted shell string
+fn shell_quote(text: &str) -> String {
+    text.replace('\'', r"'\''")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn builds_are_submitted_next_to_the_git_service() {
+        let adapter = SourceHutAdapter::new(AdapterConfig {
+            api_url: Some("https://git.example.org/".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(adapter.builds_url, "https://builds.example.org");
+    }
+
+    #[tokio::test]
+    async fn status_submits_a_build_failing_with_the_result() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/query"))
+            .and(body_string_contains("submit(manifest"))
+            .and(body_string_contains("exit 1"))
+            .and(body_partial_json(serde_json::json!({ "variables": { "tags": ["app", "rsr"] } })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "data": { "submit": { "id": 42 } } })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let adapter = SourceHutAdapter::new(AdapterConfig {
+            api_url: Some(server.uri()),
+            api_token: Some("token".to_string()),
+            ..Default::default()
+        });
+        let repo = RepoRef::new("sourcehut", "~acme", "app");
+        let license = crate::CheckResult {
+            id: "bronze.license".to_string(),
+            name: "License".to_string(),
+            tier: CertificationTier::Bronze,
+            passed: false,
+            message: "No license".to_string(),
+            details: None,
+            findings: Vec::new(),
+        };
+        let status = ComplianceStatus::builder().repo(repo.clone()).check(license).build().unwrap();
+        adapter.post_status(&repo, "abc123", &status).await.unwrap();
+    }
+}
@@ -0,0 +1,342 @@
+//! Strict webhook parsing
+//!
+//! Payload models default the fields platforms don't always send, so a
+//! platform renaming or dropping a field shows up as an empty string or a
+//! fallback action rather than as an error. Parsing in strict mode still
+//! accepts the event, but collects a [`ParseWarning`] for:
+//!
+//! - a field the model reads that the payload doesn't have, where the model
+//!   filled in a default. Optional fields, which a model can tell apart from
+//!   a missing value, aren't reported.
+//! - a value (an action, a status) the adapter doesn't recognise and maps to
+//!   a fallback
+//!
+//! Fields the payload has and the model doesn't read aren't reported:
+//! platforms send far more than the engine reads.
+//!
+//! Warnings are collected per thread while [`parse`] runs the adapter's
+//! synchronous parser, so adapters report them from wherever they decode
+//! without passing a collector through.
+
+use super::{Headers, PlatformAdapter};
+use crate::events::RepoEvent;
+use crate::Result;
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{DeserializeOwned, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+
+thread_local! {
+    /// Warnings of the strict parse running on this thread, if any
+    static WARNINGS: RefCell<Option<Vec<ParseWarning>>> = const { RefCell::new(None) };
+}
+
+/// Warnings by platform and event, since the process started
+static TOTALS: Mutex<BTreeMap<(String, String), u64>> = Mutex::new(BTreeMap::new());
+
+/// Whether to parse webhooks strictly, from `RSR_WEBHOOK_STRICT_PARSING`
+/// (default off)
+pub fn enabled() -> bool {
+    std::env::var("RSR_WEBHOOK_STRICT_PARSING").is_ok_and(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+}
+
+/// Something in a webhook payload the engine didn't expect
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseWarning {
+    /// Event the payload was parsed as
+    pub event: String,
+    /// Path of the field, e.g. `pull_request.head.ref` or `commits[].id`
+    pub field: String,
+    pub kind: ParseWarningKind,
+    /// The unrecognised value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseWarningKind {
+    /// The field was absent and given a default
+    Missing,
+    /// The value isn't one the adapter knows, and was mapped to a fallback
+    Unrecognized,
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.kind, &self.value) {
+            (ParseWarningKind::Unrecognized, Some(value)) => {
+                write!(f, "{}: unrecognized {} {:?}", self.event, self.field, value)
+            }
+            _ => write!(f, "{}: missing {}", self.event, self.field),
+        }
+    }
+}
+
+/// Parse a webhook with `adapter`, collecting warnings about the payload.
+/// Warnings are collected whether or not parsing succeeds.
+pub fn parse(adapter: &dyn PlatformAdapter, payload: &[u8], headers: &Headers) -> (Result<RepoEvent>, Vec<ParseWarning>) {
+    /// Stops collecting even if the parser panics
+    struct Collecting;
+
+    impl Drop for Collecting {
+        fn drop(&mut self) {
+            WARNINGS.with(|warnings| warnings.borrow_mut().take());
+        }
+    }
+
+    WARNINGS.with(|warnings| *warnings.borrow_mut() = Some(Vec::new()));
+    let collecting = Collecting;
+    let result = adapter.parse_webhook(payload, headers);
+    let warnings = WARNINGS.with(|warnings| warnings.borrow_mut().take()).unwrap_or_default();
+    drop(collecting);
+
+    if !warnings.is_empty() {
+        let mut totals = TOTALS.lock().expect("parse warning totals lock poisoned");
+        for warning in &warnings {
+            *totals.entry((adapter.platform_id().to_string(), warning.event.clone())).or_default() += 1;
+        }
+    }
+    (result, warnings)
+}
+
+/// Warnings by `(platform, event)`, since the process started
+pub fn warning_totals() -> BTreeMap<(String, String), u64> {
+    TOTALS.lock().expect("parse warning totals lock poisoned").clone()
+}
+
+/// Whether a strict parse is running on this thread
+pub(crate) fn collecting() -> bool {
+    WARNINGS.with(|warnings| warnings.borrow().is_some())
+}
+
+fn push(warning: ParseWarning) {
+    WARNINGS.with(|warnings| {
+        if let Some(warnings) = warnings.borrow_mut().as_mut() {
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+    });
+}
+
+/// Report a value `field` of `event` had that the adapter mapped to a
+/// fallback. Does nothing outside a strict parse.
+pub(crate) fn unrecognized(event: &str, field: &str, value: &str) {
+    push(ParseWarning {
+        event: event.to_string(),
+        field: field.to_string(),
+        kind: ParseWarningKind::Unrecognized,
+        value: Some(value.to_string()),
+    });
+}
+
+/// Deserialize a payload, reporting the fields its model defaulted
+pub(crate) fn decode<T: DeserializeOwned>(event: &str, payload: &[u8]) -> serde_json::Result<T> {
+    let value: Value = serde_json::from_slice(payload)?;
+    let absent = RefCell::new(Vec::new());
+    let decoded = T::deserialize(Tracked {
+        value: &value,
+        pointer: String::new(),
+        path: String::new(),
+        absent: &absent,
+    })?;
+
+    let mut seen = HashSet::new();
+    for Absent { pointer, path } in absent.into_inner() {
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        let Some((parent, field)) = pointer.rsplit_once('/') else {
+            continue;
+        };
+        // An optional field accepts a null where a defaulted one doesn't
+        let mut probe = value.clone();
+        if let Some(Value::Object(object)) = probe.pointer_mut(parent) {
+            object.insert(unescape(field), Value::Null);
+        }
+        if serde_json::from_value::<T>(probe).is_err() {
+            push(ParseWarning {
+                event: event.to_string(),
+                field: path,
+                kind: ParseWarningKind::Missing,
+                value: None,
+            });
+        }
+    }
+
+    Ok(decoded)
+}
+
+/// A field a model expected that the payload didn't have
+struct Absent {
+    /// JSON pointer to where the field would be
+    pointer: String,
+    /// The field's path, with array indices elided
+    path: String,
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(key: &str) -> String {
+    key.replace("~1", "/").replace("~0", "~")
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Deserializes a JSON value, recording struct fields absent from it
+struct Tracked<'a, 'de> {
+    value: &'de Value,
+    pointer: String,
+    path: String,
+    absent: &'a RefCell<Vec<Absent>>,
+}
+
+impl<'a, 'de> Deserializer<'de> for Tracked<'a, 'de> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        match self.value {
+            Value::Object(object) => visitor.visit_map(Entries {
+                entries: object.iter(),
+                pending: None,
+                pointer: self.pointer,
+                path: self.path,
+                absent: self.absent,
+            }),
+            Value::Array(items) => visitor.visit_seq(Items {
+                items: items.iter().enumerate(),
+                pointer: self.pointer,
+                path: self.path,
+                absent: self.absent,
+            }),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> serde_json::Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        let Value::Object(object) = self.value else {
+            return self.value.deserialize_struct(name, fields, visitor);
+        };
+        self.absent.borrow_mut().extend(
+            fields
+                .iter()
+                .filter(|field| !object.contains_key(**field))
+                .map(|field| Absent {
+                    pointer: format!("{}/{}", self.pointer, escape(field)),
+                    path: join(&self.path, field),
+                }),
+        );
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier
+    }
+}
+
+struct Entries<'a, 'de> {
+    entries: serde_json::map::Iter<'de>,
+    pending: Option<(&'de String, &'de Value)>,
+    pointer: String,
+    path: String,
+    absent: &'a RefCell<Vec<Absent>>,
+}
+
+impl<'a, 'de> MapAccess<'de> for Entries<'a, 'de> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> serde_json::Result<Option<K::Value>> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.pending = Some((key, value));
+        seed.deserialize(BorrowedStrDeserializer::new(key)).map(Some)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> serde_json::Result<S::Value> {
+        let (key, value) = self
+            .pending
+            .take()
+            .ok_or_else(|| serde::de::Error::custom("value requested before its key"))?;
+        seed.deserialize(Tracked {
+            value,
+            pointer: format!("{}/{}", self.pointer, escape(key)),
+            path: join(&self.path, key),
+            absent: self.absent,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct Items<'a, 'de> {
+    items: std::iter::Enumerate<std::slice::Iter<'de, Value>>,
+    pointer: String,
+    path: String,
+    absent: &'a RefCell<Vec<Absent>>,
+}
+
+impl<'a, 'de> SeqAccess<'de> for Items<'a, 'de> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> serde_json::Result<Option<S::Value>> {
+        let Some((index, value)) = self.items.next() else {
+            return Ok(None);
+        };
+        seed.deserialize(Tracked {
+            value,
+            pointer: format!("{}/{}", self.pointer, index),
+            path: format!("{}[]", self.path),
+            absent: self.absent,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
@@ -0,0 +1,189 @@
+//! Security advisory ingestion
+//!
+//! Reviewed GitHub security advisories (GHSA) are read through the GraphQL
+//! `securityAdvisories` API and stored as vulnerability vertices linked to
+//! the packages they affect, so `get_affected_repos` reaches every
+//! repository depending on them. An advisory is keyed by its GHSA id with its
+//! CVE ids as aliases; one matching a vulnerability already stored under any
+//! of those ids, from another source, updates that vertex instead of adding a
+//! duplicate.
+//!
+//! Severities use the same [`Severity`] labels as security-alert webhooks,
+//! so an alert and the advisory behind it agree. Ingestion resumes from the
+//! most recently updated GHSA advisory already stored. Withdrawn advisories
+//! are skipped; what was stored before the withdrawal stays.
+
+use crate::adapters::github::GitHubAdapter;
+use crate::db::graphs::{GraphStore, Vulnerability};
+use crate::deps;
+use crate::events::Severity;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Source name advisories from GitHub are stored under
+pub const GHSA_SOURCE: &str = "ghsa";
+
+/// Registry a GHSA ecosystem maps to, for the ecosystems lockfiles are
+/// parsed for
+fn registry(ecosystem: &str) -> Option<&'static str> {
+    match ecosystem {
+        "RUST" => Some(deps::CRATES),
+        "NPM" => Some(deps::NPM),
+        "GO" => Some(deps::GO),
+        "PIP" => Some(deps::PYPI),
+        "RUBYGEMS" => Some(deps::RUBYGEMS),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GhsaAdvisory {
+    ghsa_id: String,
+    #[serde(default)]
+    identifiers: Vec<GhsaIdentifier>,
+    severity: String,
+    updated_at: DateTime<Utc>,
+    withdrawn_at: Option<DateTime<Utc>>,
+    vulnerabilities: GhsaNodes<GhsaVulnerability>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GhsaIdentifier {
+    #[serde(rename = "type")]
+    kind: String,
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GhsaNodes<T> {
+    #[serde(default = "Vec::new")]
+    nodes: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GhsaVulnerability {
+    package: GhsaPackage,
+    vulnerable_version_range: String,
+    first_patched_version: Option<GhsaVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GhsaPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GhsaVersion {
+    identifier: String,
+}
+
+impl GhsaAdvisory {
+    /// The vulnerability and the packages it affects, as (registry, name),
+    /// or `None` if it affects no package in a supported registry
+    fn into_vulnerability(self) -> Option<(Vulnerability, Vec<(String, String)>)> {
+        let mut packages = Vec::new();
+        let mut affected_versions = Vec::new();
+        let mut patched_versions = Vec::new();
+        for vulnerability in self.vulnerabilities.nodes {
+            let Some(registry) = registry(&vulnerability.package.ecosystem) else {
+                continue;
+            };
+            let package = (registry.to_string(), vulnerability.package.name);
+            if !packages.contains(&package) {
+                packages.push(package);
+            }
+            affected_versions.push(vulnerability.vulnerable_version_range);
+            patched_versions.extend(vulnerability.first_patched_version.map(|version| version.identifier));
+        }
+        if packages.is_empty() {
+            return None;
+        }
+
+        let aliases = self
+            .identifiers
+            .into_iter()
+            .filter(|identifier| identifier.kind == "CVE" && identifier.value != self.ghsa_id)
+            .map(|identifier| identifier.value)
+            .collect();
+        let vulnerability = Vulnerability {
+            id: self.ghsa_id,
+            aliases,
+            severity: Severity::from_label(&self.severity).as_str().to_string(),
+            affected_versions,
+            patched_versions,
+        };
+        Some((vulnerability, packages))
+    }
+}
+
+/// What an ingestion run stored
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IngestReport {
+    /// Advisories stored
+    pub stored: usize,
+    /// Of those, advisories merged into a vulnerability known by another id
+    pub merged: usize,
+    /// Advisories withdrawn or affecting no supported registry
+    pub skipped: usize,
+    /// Update time of the newest advisory seen
+    pub latest: Option<DateTime<Utc>>,
+    /// Whether more pages remain for the next run
+    pub truncated: bool,
+}
+
+/// Ingest GHSA advisories updated since the newest one stored, reading at
+/// most `max_pages` pages
+pub async fn ingest_ghsa(adapter: &GitHubAdapter, graphs: &dyn GraphStore, max_pages: usize) -> Result<IngestReport> {
+    let since = graphs.latest_advisory_update(GHSA_SOURCE).await?;
+    let mut report = IngestReport::default();
+    let mut cursor: Option<String> = None;
+
+    for page in 0..max_pages {
+        let (nodes, next) = adapter.security_advisories(since, cursor.as_deref()).await?;
+        for node in nodes {
+            let advisory: GhsaAdvisory = match serde_json::from_value(node) {
+                Ok(advisory) => advisory,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable advisory: {}", e);
+                    report.skipped += 1;
+                    continue;
+                }
+            };
+            report.latest = report.latest.max(Some(advisory.updated_at));
+            if advisory.withdrawn_at.is_some() {
+                report.skipped += 1;
+                continue;
+            }
+
+            let updated_at = advisory.updated_at;
+            let Some((vulnerability, packages)) = advisory.into_vulnerability() else {
+                report.skipped += 1;
+                continue;
+            };
+            let key = graphs.upsert_advisory(&vulnerability, GHSA_SOURCE, updated_at, &packages).await?;
+            report.stored += 1;
+            if key != vulnerability.id {
+                report.merged += 1;
+            }
+        }
+
+        match next {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+        report.truncated = page + 1 == max_pages;
+    }
+
+    tracing::info!(
+        "Ingested {} GHSA advisories ({} merged, {} skipped){}",
+        report.stored,
+        report.merged,
+        report.skipped,
+        if report.truncated { ", more to come" } else { "" }
+    );
+    Ok(report)
+}
@@ -0,0 +1,289 @@
+//! Compliance badge rendering
+//!
+//! Badges show a repository's tier, percentage score or letter grade. The
+//! percentage is formatted for the embedding site's locale. Colors come from
+//! the tier metals by default. A color-blind-safe palette and a high-contrast
+//! palette are available. Text color follows the background so light fills
+//! stay readable.
+
+use crate::branding::{Branding, TierLabels};
+use crate::{CertificationTier, RsrError};
+use serde::Deserialize;
+
+/// Longest custom badge label accepted
+pub const MAX_LABEL_CHARS: usize = 32;
+
+/// What the right-hand side of the badge shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BadgeValue {
+    /// Tier code, e.g. `RSR-Ag`, or the deployment's own
+    #[default]
+    Tier,
+    /// Compliance score, e.g. `75.0%`
+    Percent,
+    /// Letter grade derived from the score
+    Grade,
+}
+
+/// Visual style, following the shields.io names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BadgeStyle {
+    #[default]
+    Flat,
+    FlatSquare,
+    Plastic,
+    ForTheBadge,
+}
+
+impl std::str::FromStr for BadgeStyle {
+    type Err = RsrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(Self::Flat),
+            "flat-square" => Ok(Self::FlatSquare),
+            "plastic" => Ok(Self::Plastic),
+            "for-the-badge" => Ok(Self::ForTheBadge),
+            _ => Err(RsrError::Config(format!(
+                "Unknown badge style: {}. Use: flat, flat-square, plastic, for-the-badge",
+                s
+            ))),
+        }
+    }
+}
+
+/// Fill colors for each tier
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    /// The tier metals (`CertificationTier::color`)
+    #[default]
+    Metal,
+    /// Okabe-Ito colors, distinguishable with the common color-vision deficiencies
+    OkabeIto,
+    /// Dark fills that keep white text above WCAG AA contrast
+    HighContrast,
+}
+
+impl Palette {
+    pub fn color(self, tier: CertificationTier) -> &'static str {
+        match self {
+            Self::Metal => tier.color(),
+            Self::OkabeIto => match tier {
+                CertificationTier::None => "#999999",
+                CertificationTier::Bronze => "#D55E00",
+                CertificationTier::Silver => "#56B4E9",
+                CertificationTier::Gold => "#E69F00",
+                CertificationTier::Rhodium => "#0072B2",
+            },
+            Self::HighContrast => match tier {
+                CertificationTier::None => "#595959",
+                CertificationTier::Bronze => "#8B4513",
+                CertificationTier::Silver => "#4A5568",
+                CertificationTier::Gold => "#7A5C00",
+                CertificationTier::Rhodium => "#1A3A6B",
+            },
+        }
+    }
+}
+
+/// How the badge is rendered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadgeOptions {
+    pub value: BadgeValue,
+    pub style: BadgeStyle,
+    pub palette: Palette,
+    /// BCP 47 language tag used to format the percentage, e.g. `de-CH`
+    pub locale: String,
+    /// Left-hand text
+    pub label: String,
+    /// Codes shown for [`BadgeValue::Tier`]
+    pub tiers: TierLabels,
+}
+
+impl Default for BadgeOptions {
+    fn default() -> Self {
+        Self {
+            value: BadgeValue::default(),
+            style: BadgeStyle::default(),
+            palette: Palette::default(),
+            locale: "en".to_string(),
+            label: "RSR".to_string(),
+            tiers: TierLabels::default(),
+        }
+    }
+}
+
+impl BadgeOptions {
+    /// Default options with the deployment's badge label and tier codes
+    pub fn branded(branding: &Branding) -> Self {
+        Self {
+            label: branding.badge_label().to_string(),
+            tiers: branding.tiers.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Letter grade for a score between 0 and 1
+pub fn grade(score: f32) -> &'static str {
+    match score {
+        s if s >= 0.95 => "A+",
+        s if s >= 0.9 => "A",
+        s if s >= 0.8 => "B",
+        s if s >= 0.7 => "C",
+        s if s >= 0.6 => "D",
+        _ => "F",
+    }
+}
+
+/// Format a score between 0 and 1 as a percentage for `locale`.
+/// Unknown locales use English formatting.
+pub fn format_percent(score: f32, locale: &str) -> String {
+    let language = locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let mut number = format!("{:.1}", (score * 100.0).clamp(0.0, 100.0));
+    let decimal_comma = matches!(
+        language.as_str(),
+        "cs" | "da" | "de" | "es" | "fi" | "fr" | "id" | "it" | "nb" | "nl" | "no" | "pl" | "pt" | "ru" | "sv" | "tr" | "uk"
+    );
+    if decimal_comma {
+        number = number.replace('.', ",");
+    }
+
+    // CLDR percent patterns: a no-break space before the sign in some
+    // languages, the sign first in Turkish
+    match language.as_str() {
+        "tr" => format!("%{}", number),
+        "cs" | "da" | "de" | "es" | "fi" | "fr" | "nb" | "no" | "ru" | "sv" | "uk" => format!("{}\u{a0}%", number),
+        _ => format!("{}%", number),
+    }
+}
+
+/// Render a badge as SVG
+pub fn render(tier: CertificationTier, score: f32, options: &BadgeOptions) -> String {
+    let value = match options.value {
+        BadgeValue::Tier => options.tiers.code(tier).to_string(),
+        BadgeValue::Percent => format_percent(score, &options.locale),
+        BadgeValue::Grade => grade(score).to_string(),
+    };
+    let fill = options.palette.color(tier);
+    let title = format!("{}: {}", options.label, value);
+
+    let (label, value) = match options.style {
+        BadgeStyle::ForTheBadge => (options.label.to_uppercase(), value.to_uppercase()),
+        _ => (options.label.clone(), value),
+    };
+    let (char_width, padding, height) = match options.style {
+        BadgeStyle::ForTheBadge => (8.0, 24.0, 28.0),
+        BadgeStyle::Plastic => (6.5, 12.0, 18.0),
+        _ => (6.5, 12.0, 20.0),
+    };
+    let label_width = (label.chars().count() as f32 * char_width + padding).round();
+    let value_width = (value.chars().count() as f32 * char_width + padding).round();
+    let width = label_width + value_width;
+
+    let (radius, gradient) = match options.style {
+        BadgeStyle::Flat => (3, Some(r##"<stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/>"##)),
+        BadgeStyle::Plastic => (
+            4,
+            Some(
+                r##"<stop offset="0" stop-color="#fff" stop-opacity=".7"/><stop offset=".1" stop-color="#aaa" stop-opacity=".1"/><stop offset=".9" stop-opacity=".3"/><stop offset="1" stop-opacity=".5"/>"##,
+            ),
+        ),
+        BadgeStyle::FlatSquare | BadgeStyle::ForTheBadge => (0, None),
+    };
+
+    let text = |x: f32, content: &str, color: &str, shadow: bool| {
+        let baseline = height / 2.0 + 4.0;
+        let shadow = if shadow {
+            format!(
+                r##"<text x="{}" y="{}" fill="#010101" fill-opacity=".3">{}</text>"##,
+                x,
+                baseline + 1.0,
+                escape(content)
+            )
+        } else {
+            String::new()
+        };
+        format!(r#"{}<text x="{}" y="{}" fill="{}">{}</text>"#, shadow, x, baseline, color, escape(content))
+    };
+    let shadow = options.style != BadgeStyle::ForTheBadge;
+    let value_color = text_color(fill);
+    let value_shadow = shadow && value_color == "#fff";
+
+    let font = match options.style {
+        BadgeStyle::ForTheBadge => r#"font-size="10" font-weight="bold" letter-spacing="1""#,
+        _ => r#"font-size="11""#,
+    };
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" role="img" aria-label="{t}">
+<title>{t}</title>
+"#,
+        w = width,
+        h = height,
+        t = escape(&title)
+    );
+    if let Some(stops) = gradient {
+        svg.push_str(&format!("<linearGradient id=\"b\" x2=\"0\" y2=\"100%\">{}</linearGradient>\n", stops));
+    }
+    svg.push_str(&format!(
+        r##"<clipPath id="a"><rect width="{w}" height="{h}" rx="{r}" fill="#fff"/></clipPath>
+<g clip-path="url(#a)">
+<path fill="#555" d="M0 0h{lw}v{h}H0z"/>
+<path fill="{fill}" d="M{lw} 0h{vw}v{h}H{lw}z"/>
+"##,
+        w = width,
+        h = height,
+        r = radius,
+        lw = label_width,
+        vw = value_width,
+        fill = fill
+    ));
+    if gradient.is_some() {
+        svg.push_str(&format!("<path fill=\"url(#b)\" d=\"M0 0h{}v{}H0z\"/>\n", width, height));
+    }
+    svg.push_str(&format!(
+        "</g>\n<g text-anchor=\"middle\" font-family=\"Verdana,DejaVu Sans,sans-serif\" {}>\n{}\n{}\n</g>\n</svg>",
+        font,
+        text(label_width / 2.0, &label, "#fff", shadow),
+        text(label_width + value_width / 2.0, &value, value_color, value_shadow)
+    ));
+
+    svg
+}
+
+/// Dark text on light fills, white text otherwise
+fn text_color(fill: &str) -> &'static str {
+    let channel = |i: usize| {
+        let c = u8::from_str_radix(fill.get(i..i + 2).unwrap_or("00"), 16).unwrap_or(0) as f32 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let luminance = 0.2126 * channel(1) + 0.7152 * channel(3) + 0.0722 * channel(5);
+
+    // Above this, #333 contrasts better with the fill than white does
+    if luminance > 0.18 {
+        "#333"
+    } else {
+        "#fff"
+    }
+}
+
+/// Escape text for SVG or HTML content and double-quoted attributes
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
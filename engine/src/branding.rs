@@ -0,0 +1,191 @@
+//! Deployment branding
+//!
+//! Companies run internal certification programs on the engine under their
+//! own names. The `[branding]` section of the engine configuration renames
+//! the program and its tiers, sets the badge label and adds links (a policy
+//! page, a help channel) to reports and pull request reviews:
+//!
+//! ```toml
+//! [branding]
+//! program = "Acme Ready"
+//! badge_label = "acme"
+//!
+//! [branding.tiers.gold]
+//! name = "Platinum"
+//! code = "ACME-3"
+//!
+//! [[branding.links]]
+//! label = "Certification handbook"
+//! url = "https://wiki.example.com/acme-ready"
+//! ```
+//!
+//! Only wording changes. Tier ids in the API and configuration, check ids
+//! and the status check context platforms report under stay the same, so
+//! branch protection rules and integrations keep working.
+
+use crate::badge::MAX_LABEL_CHARS;
+use crate::CertificationTier;
+use serde::{Deserialize, Serialize};
+
+/// User-facing names for the program and its tiers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Branding {
+    /// Program name in report titles and review headings
+    #[serde(default = "default_program")]
+    pub program: String,
+    /// Left-hand badge text (defaults to the program name)
+    #[serde(default)]
+    pub badge_label: Option<String>,
+    #[serde(default)]
+    pub tiers: TierLabels,
+    /// Links shown at the end of reports and reviews
+    #[serde(default)]
+    pub links: Vec<BrandLink>,
+}
+
+fn default_program() -> String {
+    "RSR".to_string()
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Self {
+            program: default_program(),
+            badge_label: None,
+            tiers: TierLabels::default(),
+            links: Vec::new(),
+        }
+    }
+}
+
+impl Branding {
+    /// Full tier name, e.g. "Silver"
+    pub fn tier_name(&self, tier: CertificationTier) -> &str {
+        self.tiers.name(tier)
+    }
+
+    /// Tier designation code, e.g. "RSR-Ag"
+    pub fn tier_code(&self, tier: CertificationTier) -> &str {
+        self.tiers.code(tier)
+    }
+
+    pub fn badge_label(&self) -> &str {
+        self.badge_label.as_deref().unwrap_or(&self.program)
+    }
+
+    /// Problems with the branding, as configuration paths and messages
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.program.trim().is_empty() {
+            problems.push("branding.program: must not be empty".to_string());
+        }
+        if let Some(ref label) = self.badge_label {
+            if label.trim().is_empty() || label.chars().count() > MAX_LABEL_CHARS {
+                problems.push(format!("branding.badge_label: must be 1-{} characters", MAX_LABEL_CHARS));
+            }
+        }
+        for (key, label) in self.tiers.overrides() {
+            for (field, value) in [("name", &label.name), ("code", &label.code)] {
+                if value.as_deref().is_some_and(|value| value.trim().is_empty()) {
+                    problems.push(format!("branding.tiers.{}.{}: must not be empty", key, field));
+                }
+            }
+        }
+        for link in &self.links {
+            if link.label.trim().is_empty() {
+                problems.push(format!("branding.links: link to {} has no label", link.url));
+            }
+            match reqwest::Url::parse(&link.url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https" | "mailto") => {}
+                Ok(url) => problems.push(format!("branding.links.{}: unsupported scheme {}", link.label, url.scheme())),
+                Err(e) => problems.push(format!("branding.links.{}.url: {}", link.label, e)),
+            }
+        }
+
+        problems
+    }
+}
+
+/// Renamed tiers; tiers without an entry keep their standard names
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TierLabels {
+    #[serde(default)]
+    pub none: Option<TierLabel>,
+    #[serde(default)]
+    pub bronze: Option<TierLabel>,
+    #[serde(default)]
+    pub silver: Option<TierLabel>,
+    #[serde(default)]
+    pub gold: Option<TierLabel>,
+    #[serde(default)]
+    pub rhodium: Option<TierLabel>,
+}
+
+impl TierLabels {
+    fn get(&self, tier: CertificationTier) -> Option<&TierLabel> {
+        match tier {
+            CertificationTier::None => self.none.as_ref(),
+            CertificationTier::Bronze => self.bronze.as_ref(),
+            CertificationTier::Silver => self.silver.as_ref(),
+            CertificationTier::Gold => self.gold.as_ref(),
+            CertificationTier::Rhodium => self.rhodium.as_ref(),
+        }
+    }
+
+    /// Tier name, renamed or standard
+    pub fn name(&self, tier: CertificationTier) -> &str {
+        self.get(tier)
+            .and_then(|label| label.name.as_deref())
+            .unwrap_or_else(|| standard_name(tier))
+    }
+
+    /// Tier code, renamed or standard
+    pub fn code(&self, tier: CertificationTier) -> &str {
+        self.get(tier)
+            .and_then(|label| label.code.as_deref())
+            .unwrap_or_else(|| tier.code())
+    }
+
+    /// Entries by configuration key
+    fn overrides(&self) -> impl Iterator<Item = (&'static str, &TierLabel)> {
+        [
+            ("none", &self.none),
+            ("bronze", &self.bronze),
+            ("silver", &self.silver),
+            ("gold", &self.gold),
+            ("rhodium", &self.rhodium),
+        ]
+        .into_iter()
+        .filter_map(|(key, label)| Some((key, label.as_ref()?)))
+    }
+}
+
+/// What a tier is called instead
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TierLabel {
+    /// Full name, e.g. "Platinum"
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Designation code, e.g. "ACME-3"
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+/// A link added to reports and reviews
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrandLink {
+    pub label: String,
+    pub url: String,
+}
+
+/// The standard full tier name
+pub fn standard_name(tier: CertificationTier) -> &'static str {
+    match tier {
+        CertificationTier::None => "None",
+        CertificationTier::Bronze => "Bronze",
+        CertificationTier::Silver => "Silver",
+        CertificationTier::Gold => "Gold",
+        CertificationTier::Rhodium => "Rhodium",
+    }
+}
@@ -0,0 +1,231 @@
+//! Commit author policy
+//!
+//! Verifies that the commits on the default branch come from accounts the
+//! tenant accepts. Unknown committers - authors whose email isn't linked to
+//! an account on the platform - always fail the check; tenants can further
+//! require organization membership or deny and allow particular authors.
+//!
+//! Authorship is collected while scanning: commits from the push being
+//! processed come first, backfilled with the branch's latest commits from the
+//! platform API. The check doesn't apply to local scans; platform scans
+//! without commit data fail it as not evaluated.
+
+use super::{ComplianceCheck, RepoContents};
+use crate::adapters::PlatformAdapter;
+use crate::events::Commit;
+use crate::{CertificationTier, CheckResult, RepoRef, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Most distinct authors whose membership is looked up per scan
+const MAX_MEMBERSHIP_LOOKUPS: usize = 50;
+
+fn default_lookback_commits() -> usize {
+    50
+}
+
+/// Which commit authors a tenant accepts on the default branch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorPolicy {
+    /// Authors must belong to the organization that owns the repository
+    #[serde(default)]
+    pub require_org_members: bool,
+    /// Usernames or emails always accepted, e.g. bots. `*@example.com`
+    /// matches a whole email domain.
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Usernames or emails never accepted, even when allowed otherwise
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Latest commits on the branch to verify
+    #[serde(default = "default_lookback_commits")]
+    pub lookback_commits: usize,
+}
+
+impl Default for AuthorPolicy {
+    fn default() -> Self {
+        Self {
+            require_org_members: false,
+            allow: Vec::new(),
+            deny: Vec::new(),
+            lookback_commits: default_lookback_commits(),
+        }
+    }
+}
+
+impl AuthorPolicy {
+    fn lists(patterns: &[String], author: &CommitAuthor) -> bool {
+        patterns.iter().any(|pattern| {
+            let pattern = pattern.to_lowercase();
+            let matches = |value: &str| match pattern.strip_prefix('*') {
+                Some(suffix) => value.to_lowercase().ends_with(suffix),
+                None => value.eq_ignore_ascii_case(&pattern),
+            };
+            [&author.username, &author.email]
+                .into_iter()
+                .flatten()
+                .any(|value| matches(value))
+        })
+    }
+
+    /// Why `author` isn't accepted, if they aren't
+    pub fn violation(&self, author: &CommitAuthor) -> Option<&'static str> {
+        if Self::lists(&self.deny, author) {
+            return Some("denied");
+        }
+        if Self::lists(&self.allow, author) {
+            return None;
+        }
+        if author.username.is_none() {
+            return Some("unknown committer");
+        }
+        if self.require_org_members && author.member == Some(false) {
+            return Some("not an organization member");
+        }
+        None
+    }
+}
+
+/// Author of one commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommitAuthor {
+    pub sha: String,
+    /// Platform account the commit is attributed to; `None` when the commit
+    /// email isn't linked to any account
+    pub username: Option<String>,
+    pub email: Option<String>,
+    /// Whether the account belongs to the owning organization, if looked up
+    pub member: Option<bool>,
+}
+
+impl From<&Commit> for CommitAuthor {
+    fn from(commit: &Commit) -> Self {
+        Self {
+            sha: commit.sha.clone(),
+            username: (!commit.author.id.is_empty()).then(|| commit.author.username.clone()),
+            email: commit.author.email.clone(),
+            member: None,
+        }
+    }
+}
+
+/// Commit authors of a branch, with the policy they are held to
+#[derive(Debug, Clone)]
+pub struct Authorship {
+    pub policy: AuthorPolicy,
+    /// Newest first, each commit once
+    pub commits: Vec<CommitAuthor>,
+}
+
+impl Authorship {
+    /// Collect the authors of `pushed` and, up to the policy's lookback, of
+    /// the branch's latest commits. Membership is only looked up when the
+    /// policy requires it. Lookups that fail leave what they would have
+    /// added out, rather than failing the scan.
+    pub async fn collect(adapter: &dyn PlatformAdapter, repo: &RepoRef, policy: &AuthorPolicy, pushed: &[Commit]) -> Self {
+        let mut seen = HashSet::new();
+        let mut commits: Vec<CommitAuthor> = pushed
+            .iter()
+            .rev()
+            .map(CommitAuthor::from)
+            .filter(|author| seen.insert(author.sha.clone()))
+            .collect();
+
+        if commits.len() < policy.lookback_commits {
+            match adapter.list_commits(repo, policy.lookback_commits).await {
+                Ok(latest) => commits.extend(
+                    latest
+                        .iter()
+                        .map(CommitAuthor::from)
+                        .filter(|author| seen.insert(author.sha.clone())),
+                ),
+                Err(e) => tracing::debug!("Couldn't backfill commits of {}: {}", repo, e),
+            }
+        }
+        commits.truncate(policy.lookback_commits.max(pushed.len()));
+
+        if policy.require_org_members {
+            let mut membership: HashMap<String, Option<bool>> = HashMap::new();
+            for author in &mut commits {
+                let Some(ref username) = author.username else {
+                    continue;
+                };
+                if !membership.contains_key(username) {
+                    if membership.len() >= MAX_MEMBERSHIP_LOOKUPS {
+                        continue;
+                    }
+                    let member = adapter.is_member(&repo.owner, username).await.unwrap_or_else(|e| {
+                        tracing::debug!("Couldn't check whether {} belongs to {}: {}", username, repo.owner, e);
+                        None
+                    });
+                    membership.insert(username.clone(), member);
+                }
+                author.member = membership[username];
+            }
+        }
+
+        Self {
+            policy: policy.clone(),
+            commits,
+        }
+    }
+}
+
+/// Check that commits on the default branch come from accepted authors
+pub struct CommitAuthorsCheck;
+
+#[async_trait::async_trait]
+impl ComplianceCheck for CommitAuthorsCheck {
+    fn id(&self) -> &str {
+        "gold.commit_authors"
+    }
+
+    fn name(&self) -> &str {
+        "Accepted Commit Authors"
+    }
+
+    fn tier(&self) -> CertificationTier {
+        CertificationTier::Gold
+    }
+
+    async fn check_local(&self, _path: &Path) -> Result<CheckResult> {
+        Ok(CheckResult::not_applicable(self, "commit authors are only verified by platform scans"))
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        let Some(ref authorship) = contents.authorship else {
+            return Ok(CheckResult::not_evaluated(self, "commit authorship wasn't collected"));
+        };
+        if authorship.commits.is_empty() {
+            return Ok(CheckResult::not_evaluated(self, "no commits to verify"));
+        }
+
+        let violations: Vec<String> = authorship
+            .commits
+            .iter()
+            .filter_map(|author| {
+                let reason = authorship.policy.violation(author)?;
+                let who = author
+                    .username
+                    .as_deref()
+                    .or(author.email.as_deref())
+                    .unwrap_or("unknown");
+                let sha = author.sha.get(..12).unwrap_or(&author.sha);
+                Some(format!("{} by {}: {}", sha, who, reason))
+            })
+            .collect();
+
+        let total = authorship.commits.len();
+        if violations.is_empty() {
+            Ok(CheckResult::with_details(self, true, format!("{} latest commits come from accepted authors", total), None))
+        } else {
+            Ok(CheckResult::with_details(
+                self,
+                false,
+                format!("{} of {} latest commits come from authors the policy doesn't accept", violations.len(), total),
+                Some(violations.join("\n")),
+            ))
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Baseline documents: license, README, security policy, contributing guide
+//! and code of conduct
+//!
+//! Each document is looked for at the repository root and in `.github/` and
+//! `docs/`, under its usual names with any extension (`LICENSE-MIT`,
+//! `README.adoc`, `.github/SECURITY.md`). A document passes if one is found
+//! with more than a stub's worth of content. Findings say which file was
+//! used and what it is missing; gaps in content are warnings and don't fail
+//! the check.
+//!
+//! Licenses are identified by an `SPDX-License-Identifier` line or by the
+//! wording of common license texts, so reports can name the license.
+
+use super::{ComplianceCheck, RepoContents};
+use crate::{CertificationTier, CheckResult, Finding, Result, Severity};
+use std::path::Path;
+
+/// Directories documents are looked for in, besides the root
+const LOCATIONS: [&str; 2] = [".github/", "docs/"];
+
+/// Get the baseline document checks
+pub fn get_checks() -> Vec<Box<dyn ComplianceCheck>> {
+    DOCUMENTS
+        .iter()
+        .map(|document| Box::new(DocumentCheck(document)) as Box<dyn ComplianceCheck>)
+        .collect()
+}
+
+/// A document every certified repository carries
+struct Document {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    tier: CertificationTier,
+    /// File names, uppercase and without extension. A name followed by `-`,
+    /// `_` or `.` and more also matches, e.g. `LICENSE-MIT`.
+    names: &'static [&'static str],
+    /// Characters below which the document is a stub
+    min_chars: usize,
+    /// Topics a complete document covers, each as lowercase phrases any of
+    /// which counts
+    topics: &'static [(&'static str, &'static [&'static str])],
+    remediation: &'static str,
+}
+
+const DOCUMENTS: [Document; 5] = [
+    Document {
+        id: "bronze.license",
+        name: "License File",
+        description: "A license file, identified by SPDX identifier or text",
+        tier: CertificationTier::Bronze,
+        names: &["LICENSE", "LICENCE", "COPYING", "UNLICENSE"],
+        min_chars: 50,
+        topics: &[],
+        remediation: "Add a LICENSE file with the full text of an OSI-approved license, \
+                      e.g. from https://spdx.org/licenses/",
+    },
+    Document {
+        id: "bronze.readme",
+        name: "README File",
+        description: "A README describing the project",
+        tier: CertificationTier::Bronze,
+        names: &["README"],
+        min_chars: 50,
+        topics: &[
+            ("installation or usage", &["install", "usage", "getting started", "quick start", "build"]),
+        ],
+        remediation: "Add a README.md saying what the project is, how to install it and how to use it",
+    },
+    Document {
+        id: "silver.security_policy",
+        name: "Security Policy",
+        description: "A security policy explaining how to report vulnerabilities",
+        tier: CertificationTier::Silver,
+        names: &["SECURITY"],
+        min_chars: 50,
+        topics: &[
+            ("how to report a vulnerability", &["report", "disclos", "contact", "email", "@"]),
+            ("which versions are supported", &["support", "version"]),
+        ],
+        remediation: "Add SECURITY.md saying how to report a vulnerability privately \
+                      and which versions receive security fixes",
+    },
+    Document {
+        id: "silver.contributing",
+        name: "Contributing Guide",
+        description: "A guide to contributing changes",
+        tier: CertificationTier::Silver,
+        names: &["CONTRIBUTING"],
+        min_chars: 100,
+        topics: &[
+            ("how to submit changes", &["pull request", "merge request", "patch"]),
+            ("how to report issues", &["issue", "bug"]),
+        ],
+        remediation: "Add CONTRIBUTING.md explaining how to report issues and submit changes",
+    },
+    Document {
+        id: "silver.code_of_conduct",
+        name: "Code of Conduct",
+        description: "A code of conduct with a way to report violations",
+        tier: CertificationTier::Silver,
+        names: &["CODE_OF_CONDUCT", "CODE-OF-CONDUCT"],
+        min_chars: 100,
+        topics: &[("how to report violations", &["report", "contact", "email", "enforcement", "@"])],
+        remediation: "Add CODE_OF_CONDUCT.md (the Contributor Covenant is a common choice) \
+                      with a contact for reporting violations",
+    },
+];
+
+/// Checks that a baseline document is present and has real content
+struct DocumentCheck(&'static Document);
+
+impl DocumentCheck {
+    /// Whether `path` (relative to the repository root) is one of the
+    /// document's files
+    fn matches(&self, path: &str) -> bool {
+        let file_name = match path.rsplit_once('/') {
+            Some((dir, file_name)) if LOCATIONS.contains(&format!("{}/", dir).as_str()) => file_name,
+            Some(_) => return false,
+            None => path,
+        };
+        let upper = file_name.to_uppercase();
+        self.0.names.iter().any(|name| match upper.strip_prefix(name) {
+            Some(rest) => rest.is_empty() || rest.starts_with(['.', '-', '_']),
+            None => false,
+        })
+    }
+
+    /// Evaluate the document's candidate files, as `(path, content)` with
+    /// `None` for files that aren't text
+    fn evaluate(&self, mut candidates: Vec<(String, Option<String>)>, platform_policy: bool) -> CheckResult {
+        let document = self.0;
+        // Root files first, then `.github/`, then `docs/`
+        candidates.sort_by_key(|(path, _)| (path.matches('/').count(), !path.starts_with(".github/"), path.clone()));
+
+        let mut findings = Vec::new();
+        let usable: Vec<(&str, &str)> = candidates
+            .iter()
+            .filter_map(|(path, content)| {
+                let text = content.as_deref().map(str::trim).unwrap_or_default();
+                if text.chars().count() >= document.min_chars {
+                    return Some((path.as_str(), text));
+                }
+                findings.push(Finding {
+                    code: format!("{}.stub", self.code()),
+                    severity: Severity::Warning,
+                    path: Some(path.clone()),
+                    message: match content {
+                        Some(_) => format!("{} has under {} characters of content", path, document.min_chars),
+                        None => format!("{} is not a text file", path),
+                    },
+                    remediation: Some(document.remediation.to_string()),
+                });
+                None
+            })
+            .collect();
+
+        let Some(&(path, _)) = usable.first() else {
+            if platform_policy {
+                return CheckResult::from_findings(self, true, "Security policy is configured on the platform".to_string(), findings);
+            }
+            let message = match candidates.first() {
+                Some((path, _)) => format!("{} has no real content", path),
+                None => format!("No {} found", document.name.to_lowercase()),
+            };
+            findings.push(Finding {
+                code: format!("{}.missing", self.code()),
+                severity: Severity::Error,
+                path: None,
+                message: message.clone(),
+                remediation: Some(document.remediation.to_string()),
+            });
+            return CheckResult::from_findings(self, false, message, findings);
+        };
+
+        // Warnings for stubs shadowed by a usable document are noise
+        findings.clear();
+
+        let mut licenses = Vec::new();
+        if document.names.contains(&"LICENSE") {
+            for &(path, text) in &usable {
+                match license(text) {
+                    Some(id) => {
+                        findings.push(Finding {
+                            code: "license.identified".to_string(),
+                            severity: Severity::Info,
+                            path: Some(path.to_string()),
+                            message: format!("{} is {}", path, id),
+                            remediation: None,
+                        });
+                        licenses.push(id);
+                    }
+                    None => findings.push(Finding {
+                        code: "license.unidentified".to_string(),
+                        severity: Severity::Warning,
+                        path: Some(path.to_string()),
+                        message: format!("{} doesn't match a known license", path),
+                        remediation: Some(
+                            "Use an unmodified license text, or add an SPDX-License-Identifier line naming the license"
+                                .to_string(),
+                        ),
+                    }),
+                }
+            }
+        }
+
+        let text = usable
+            .iter()
+            .map(|(_, text)| text.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("\n");
+        for (topic, phrases) in document.topics {
+            if !phrases.iter().any(|phrase| text.contains(phrase)) {
+                findings.push(Finding {
+                    code: format!("{}.incomplete", self.code()),
+                    severity: Severity::Warning,
+                    path: Some(path.to_string()),
+                    message: format!("{} doesn't cover {}", path, topic),
+                    remediation: Some(document.remediation.to_string()),
+                });
+            }
+        }
+
+        let paths: Vec<&str> = usable.iter().map(|(path, _)| *path).collect();
+        let mut message = format!("Found {}", paths.join(", "));
+        if !licenses.is_empty() {
+            message.push_str(&format!(" ({})", licenses.join(", ")));
+        }
+        CheckResult::from_findings(self, true, message, findings)
+    }
+
+    /// Finding codes are prefixed with the check id's last segment
+    fn code(&self) -> &'static str {
+        self.0.id.rsplit('.').next().unwrap_or(self.0.id)
+    }
+}
+
+#[async_trait::async_trait]
+impl ComplianceCheck for DocumentCheck {
+    fn id(&self) -> &str {
+        self.0.id
+    }
+
+    fn name(&self) -> &str {
+        self.0.name
+    }
+
+    fn description(&self) -> &str {
+        self.0.description
+    }
+
+    fn tier(&self) -> CertificationTier {
+        self.0.tier
+    }
+
+    fn reads(&self, path: &str) -> bool {
+        self.matches(path)
+    }
+
+    async fn check_local(&self, path: &Path) -> Result<CheckResult> {
+        let mut candidates = Vec::new();
+        for dir in std::iter::once("").chain(LOCATIONS) {
+            let Ok(entries) = std::fs::read_dir(path.join(dir)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let relative = format!("{}{}", dir, entry.file_name().to_string_lossy());
+                if entry.file_type().is_ok_and(|kind| kind.is_file()) && self.matches(&relative) {
+                    candidates.push((relative, std::fs::read_to_string(entry.path()).ok()));
+                }
+            }
+        }
+        Ok(self.evaluate(candidates, false))
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        let candidates = contents
+            .files
+            .iter()
+            .filter(|file| self.matches(&file.path))
+            .map(|file| (file.path.clone(), file.content.clone()))
+            .collect();
+        let platform_policy = self.0.id == "silver.security_policy" && contents.metadata.has_security_policy;
+        Ok(self.evaluate(candidates, platform_policy))
+    }
+}
+
+/// License texts recognized by their wording, as SPDX identifiers and
+/// lowercase phrases that all appear in the text. More specific licenses
+/// come before those whose wording they contain.
+const LICENSE_TEXTS: [(&str, &[&str]); 13] = [
+    ("AGPL-3.0", &["gnu affero general public license", "version 3"]),
+    ("LGPL-3.0", &["gnu lesser general public license", "version 3"]),
+    ("LGPL-2.1", &["gnu lesser general public license", "version 2.1"]),
+    ("GPL-3.0", &["gnu general public license", "version 3"]),
+    ("GPL-2.0", &["gnu general public license", "version 2"]),
+    ("Apache-2.0", &["apache license", "version 2.0"]),
+    ("MPL-2.0", &["mozilla public license", "2.0"]),
+    ("EPL-2.0", &["eclipse public license", "2.0"]),
+    ("BSD-3-Clause", &["redistribution and use in source and binary forms", "neither the name"]),
+    ("BSD-2-Clause", &["redistribution and use in source and binary forms"]),
+    ("MIT", &["permission is hereby granted, free of charge"]),
+    ("ISC", &["permission to use, copy, modify, and/or distribute this software"]),
+    ("Unlicense", &["this is free and unencumbered software released into the public domain"]),
+];
+
+/// SPDX identifier of the license a file holds, from an
+/// `SPDX-License-Identifier` line or its wording
+fn license(text: &str) -> Option<String> {
+    let declared = text.lines().find_map(|line| {
+        let (_, id) = line.split_once("SPDX-License-Identifier:")?;
+        let id = id.trim().trim_end_matches("*/").trim();
+        (!id.is_empty()).then(|| id.to_string())
+    });
+    declared.or_else(|| {
+        let lower = text.to_lowercase();
+        LICENSE_TEXTS
+            .iter()
+            .find(|(_, phrases)| phrases.iter().all(|phrase| lower.contains(phrase)))
+            .map(|(id, _)| id.to_string())
+    })
+}
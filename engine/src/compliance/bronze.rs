@@ -7,185 +7,21 @@
 /// Get all Bronze tier checks
 pub fn get_checks() -> Vec<Box<dyn ComplianceCheck>> {
     vec![
-        Box::new(LicenseCheck),
-        Box::new(ReadmeCheck),
         Box::new(GitignoreCheck),
         Box::new(NoSecretsCheck),
     ]
 }
 
-/// Check for valid LICENSE file
-pub struct LicenseCheck;
-
-#[async_trait::async_trait]
-impl ComplianceCheck for LicenseCheck {
-    fn id(&self) -> &'static str {
-        "bronze.license"
-    }
-
-    fn name(&self) -> &'static str {
-        "License File"
-    }
-
-    fn tier(&self) -> CertificationTier {
-        CertificationTier::Bronze
-    }
-
-    async fn check_local(&self, path: &Path) -> Result<CheckResult> {
-        let license_files = ["LICENSE", "LICENSE.md", "LICENSE.txt", "LICENCE", "COPYING"];
-
-        for name in license_files {
-            let license_path = path.join(name);
-            if license_path.exists() {
-                let content = std::fs::read_to_string(&license_path).unwrap_or_default();
-                if content.len() > 50 {
-                    return Ok(CheckResult {
-                        id: self.id().to_string(),
-                        name: self.name().to_string(),
-                        tier: self.tier(),
-                        passed: true,
-                        message: format!("Found valid license file: {}", name),
-                        details: detect_license_type(&content),
-                    });
-                }
-            }
-        }
-
-        Ok(CheckResult {
-            id: self.id().to_string(),
-            name: self.name().to_string(),
-            tier: self.tier(),
-            passed: false,
-            message: "No valid LICENSE file found".to_string(),
-            details: Some("Add a LICENSE, LICENSE.md, or COPYING file".to_string()),
-        })
-    }
-
-    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
-        let license_files = ["LICENSE", "LICENSE.md", "LICENSE.txt", "LICENCE", "COPYING"];
-
-        for file in &contents.files {
-            let filename = file.path.split('/').last().unwrap_or(&file.path);
-            if license_files.contains(&filename) {
-                if let Some(ref content) = file.content {
-                    if content.len() > 50 {
-                        return Ok(CheckResult {
-                            id: self.id().to_string(),
-                            name: self.name().to_string(),
-                            tier: self.tier(),
-                            passed: true,
-                            message: format!("Found valid license file: {}", file.path),
-                            details: detect_license_type(content),
-                        });
-                    }
-                }
-            }
-        }
-
-        Ok(CheckResult {
-            id: self.id().to_string(),
-            name: self.name().to_string(),
-            tier: self.tier(),
-            passed: false,
-            message: "No valid LICENSE file found".to_string(),
-            details: None,
-        })
-    }
-}
-
-/// Check for README file
-pub struct ReadmeCheck;
-
-#[async_trait::async_trait]
-impl ComplianceCheck for ReadmeCheck {
-    fn id(&self) -> &'static str {
-        "bronze.readme"
-    }
-
-    fn name(&self) -> &'static str {
-        "README File"
-    }
-
-    fn tier(&self) -> CertificationTier {
-        CertificationTier::Bronze
-    }
-
-    async fn check_local(&self, path: &Path) -> Result<CheckResult> {
-        let readme_files = [
-            "README.md",
-            "README.adoc",
-            "README.rst",
-            "README.txt",
-            "README",
-        ];
-
-        for name in readme_files {
-            let readme_path = path.join(name);
-            if readme_path.exists() {
-                let content = std::fs::read_to_string(&readme_path).unwrap_or_default();
-                if content.len() > 50 {
-                    return Ok(CheckResult {
-                        id: self.id().to_string(),
-                        name: self.name().to_string(),
-                        tier: self.tier(),
-                        passed: true,
-                        message: format!("Found README: {} ({} chars)", name, content.len()),
-                        details: None,
-                    });
-                }
-            }
-        }
-
-        Ok(CheckResult {
-            id: self.id().to_string(),
-            name: self.name().to_string(),
-            tier: self.tier(),
-            passed: false,
-            message: "No valid README file found".to_string(),
-            details: Some("Add a README.md with project description".to_string()),
-        })
-    }
-
-    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
-        let readme_files = ["README.md", "README.adoc", "README.rst", "README.txt", "README"];
-
-        for file in &contents.files {
-            let filename = file.path.split('/').last().unwrap_or(&file.path);
-            if readme_files.contains(&filename) {
-                if file.size > 50 {
-                    return Ok(CheckResult {
-                        id: self.id().to_string(),
-                        name: self.name().to_string(),
-                        tier: self.tier(),
-                        passed: true,
-                        message: format!("Found README: {}", file.path),
-                        details: None,
-                    });
-                }
-            }
-        }
-
-        Ok(CheckResult {
-            id: self.id().to_string(),
-            name: self.name().to_string(),
-            tier: self.tier(),
-            passed: false,
-            message: "No valid README file found".to_string(),
-            details: None,
-        })
-    }
-}
-
 /// Check for .gitignore file
 pub struct GitignoreCheck;
 
 #[async_trait::async_trait]
 impl ComplianceCheck for GitignoreCheck {
-    fn id(&self) -> &'static str {
+    fn id(&self) -> &str {
         "bronze.gitignore"
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         ".gitignore File"
     }
 
@@ -193,6 +29,10 @@ fn tier(&self) -> CertificationTier {
         CertificationTier::Bronze
     }
 
+    fn reads(&self, path: &str) -> bool {
+        path == ".gitignore" || path.ends_with("/.gitignore")
+    }
+
     async fn check_local(&self, path: &Path) -> Result<CheckResult> {
         let gitignore_path = path.join(".gitignore");
 
@@ -208,6 +48,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                     passed: true,
                     message: format!(".gitignore found with {} patterns", non_empty_lines),
                     details: None,
+                    findings: Vec::new(),
                 });
             }
         }
@@ -219,22 +60,22 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
             passed: false,
             message: "No .gitignore file found".to_string(),
             details: Some("Add a .gitignore appropriate for your project type".to_string()),
+            findings: Vec::new(),
         })
     }
 
     async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
         for file in &contents.files {
-            if file.path == ".gitignore" || file.path.ends_with("/.gitignore") {
-                if file.size > 0 {
-                    return Ok(CheckResult {
-                        id: self.id().to_string(),
-                        name: self.name().to_string(),
-                        tier: self.tier(),
-                        passed: true,
-                        message: ".gitignore found".to_string(),
-                        details: None,
-                    });
-                }
+            if (file.path == ".gitignore" || file.path.ends_with("/.gitignore")) && file.size > 0 {
+                return Ok(CheckResult {
+                    id: self.id().to_string(),
+                    name: self.name().to_string(),
+                    tier: self.tier(),
+                    passed: true,
+                    message: ".gitignore found".to_string(),
+                    details: None,
+                    findings: Vec::new(),
+                });
             }
         }
 
@@ -245,20 +86,36 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
             passed: false,
             message: "No .gitignore file found".to_string(),
             details: None,
+            findings: Vec::new(),
         })
     }
 }
 
-/// Check for hardcoded secrets
+/// Check for hardcoded secrets. Remote scans read only the files secrets
+/// are usually committed in (see [`may_hold_secrets`]); a local scan also
+/// walks the source files.
 pub struct NoSecretsCheck;
 
+/// Whether `path` is a configuration, environment or key file: the files
+/// a remote scan reads to look for secrets
+fn may_hold_secrets(path: &str) -> bool {
+    const EXTENSIONS: [&str; 11] = ["env", "json", "yml", "yaml", "toml", "ini", "cfg", "conf", "properties", "pem", "key"];
+    const NAMES: [&str; 4] = [".npmrc", ".pypirc", ".netrc", ".dockercfg"];
+
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let extension = name.rsplit_once('.').map(|(_, extension)| extension.to_ascii_lowercase());
+    name.starts_with(".env")
+        || NAMES.contains(&name)
+        || extension.is_some_and(|extension| EXTENSIONS.contains(&extension.as_str()))
+}
+
 #[async_trait::async_trait]
 impl ComplianceCheck for NoSecretsCheck {
-    fn id(&self) -> &'static str {
+    fn id(&self) -> &str {
         "bronze.no_secrets"
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "No Hardcoded Secrets"
     }
 
@@ -266,6 +123,10 @@ fn tier(&self) -> CertificationTier {
         CertificationTier::Bronze
     }
 
+    fn reads(&self, path: &str) -> bool {
+        may_hold_secrets(path)
+    }
+
     async fn check_local(&self, path: &Path) -> Result<CheckResult> {
         let mut secrets_found = Vec::new();
 
@@ -299,6 +160,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                 passed: true,
                 message: "No obvious secrets detected".to_string(),
                 details: None,
+                findings: Vec::new(),
             })
         } else {
             Ok(CheckResult {
@@ -308,6 +170,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                 passed: false,
                 message: format!("Found {} potential secret(s)", secrets_found.len()),
                 details: Some(secrets_found.join("\n")),
+                findings: Vec::new(),
             })
         }
     }
@@ -331,6 +194,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
                 passed: true,
                 message: "No obvious secrets detected".to_string(),
                 details: None,
+                findings: Vec::new(),
             })
         } else {
             Ok(CheckResult {
@@ -340,6 +204,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
                 passed: false,
                 message: format!("Found {} potential secret(s)", secrets_found.len()),
                 details: Some(secrets_found.join("\n")),
+                findings: Vec::new(),
             })
         }
     }
@@ -347,22 +212,6 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
 
 // Helper functions
 
-fn detect_license_type(content: &str) -> Option<String> {
-    let content_lower = content.to_lowercase();
-
-    if content_lower.contains("mit license") || content_lower.contains("permission is hereby granted, free of charge") {
-        Some("Detected: MIT License".to_string())
-    } else if content_lower.contains("apache license") && content_lower.contains("version 2.0") {
-        Some("Detected: Apache License 2.0".to_string())
-    } else if content_lower.contains("gnu general public license") {
-        Some("Detected: GPL License".to_string())
-    } else if content_lower.contains("bsd") {
-        Some("Detected: BSD License".to_string())
-    } else {
-        None
-    }
-}
-
 fn is_gitignored(repo_path: &Path, file: &str) -> bool {
     let gitignore_path = repo_path.join(".gitignore");
     if let Ok(content) = std::fs::read_to_string(gitignore_path) {
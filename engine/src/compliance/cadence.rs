@@ -0,0 +1,294 @@
+//! Release cadence
+//!
+//! Part of the sustainability pillar: a project that keeps shipping is one
+//! users can rely on. Cadence is measured from the repository's published
+//! releases - the same history its release events describe, including
+//! releases from before the engine was installed - and its latest commit:
+//! - how often it releases (median interval, releases in the last year)
+//! - how long ago it last released
+//! - how long its latest commit has been waiting for a release
+//!
+//! What counts as healthy depends on the project's maturity, declared in
+//! `.rsr.toml` as `compliance.maturity`. Tenants set the expectations for
+//! each level.
+
+use super::releases::ReleaseHistory;
+use super::{ComplianceCheck, RepoContents};
+use crate::adapters::Release;
+use crate::{CertificationTier, CheckResult, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Where projects declare their maturity
+const PROJECT_CONFIG: &str = ".rsr.toml";
+
+/// How far along a project is, as it declares itself
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Maturity {
+    /// Early work; no cadence is expected
+    Experimental,
+    /// Under active development
+    #[default]
+    Active,
+    /// Feature complete, releasing fixes
+    Stable,
+    /// Kept working, rarely changed
+    Maintenance,
+}
+
+impl Maturity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Experimental => "experimental",
+            Self::Active => "active",
+            Self::Stable => "stable",
+            Self::Maintenance => "maintenance",
+        }
+    }
+
+    /// Maturity declared in the project's `.rsr.toml`, or the default when
+    /// none (or an unknown one) is declared
+    pub fn declared(contents: &RepoContents) -> Self {
+        contents
+            .files
+            .iter()
+            .find(|file| file.path == PROJECT_CONFIG)
+            .and_then(|file| file.content.as_deref())
+            .and_then(|config| config.parse::<toml::Table>().ok())
+            .and_then(|config| config.get("compliance")?.get("maturity")?.clone().try_into().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Cadence a project is held to; unset limits aren't enforced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CadenceExpectation {
+    /// Longest typical gap between releases, and since the latest one
+    #[serde(default)]
+    pub max_days_between_releases: Option<u32>,
+    /// Longest the latest commit may go unreleased
+    #[serde(default)]
+    pub max_unreleased_days: Option<u32>,
+}
+
+impl CadenceExpectation {
+    const fn new(between: u32, unreleased: u32) -> Self {
+        Self {
+            max_days_between_releases: Some(between),
+            max_unreleased_days: Some(unreleased),
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_days_between_releases.is_none() && self.max_unreleased_days.is_none()
+    }
+}
+
+fn default_active() -> CadenceExpectation {
+    CadenceExpectation::new(180, 90)
+}
+
+fn default_stable() -> CadenceExpectation {
+    CadenceExpectation::new(365, 180)
+}
+
+fn default_maintenance() -> CadenceExpectation {
+    CadenceExpectation::new(730, 365)
+}
+
+/// Cadence expected at each maturity level
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CadencePolicy {
+    #[serde(default)]
+    pub experimental: CadenceExpectation,
+    #[serde(default = "default_active")]
+    pub active: CadenceExpectation,
+    #[serde(default = "default_stable")]
+    pub stable: CadenceExpectation,
+    #[serde(default = "default_maintenance")]
+    pub maintenance: CadenceExpectation,
+}
+
+impl Default for CadencePolicy {
+    fn default() -> Self {
+        Self {
+            experimental: CadenceExpectation::default(),
+            active: default_active(),
+            stable: default_stable(),
+            maintenance: default_maintenance(),
+        }
+    }
+}
+
+impl CadencePolicy {
+    pub fn expectation(&self, maturity: Maturity) -> &CadenceExpectation {
+        match maturity {
+            Maturity::Experimental => &self.experimental,
+            Maturity::Active => &self.active,
+            Maturity::Stable => &self.stable,
+            Maturity::Maintenance => &self.maintenance,
+        }
+    }
+
+    /// Each level's expectations
+    pub fn levels(&self) -> [(Maturity, &CadenceExpectation); 4] {
+        [Maturity::Experimental, Maturity::Active, Maturity::Stable, Maturity::Maintenance]
+            .map(|maturity| (maturity, self.expectation(maturity)))
+    }
+}
+
+/// Release cadence and latency of a repository
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReleaseCadence {
+    /// Published, non-prerelease releases measured
+    pub releases: usize,
+    pub releases_last_year: usize,
+    /// Median days between consecutive releases, with at least two of them
+    pub median_days_between: Option<f64>,
+    pub days_since_release: Option<f64>,
+    /// Days the latest commit has gone without a release including it; zero
+    /// when the latest release is newer
+    pub unreleased_days: Option<f64>,
+}
+
+impl ReleaseCadence {
+    /// Measure `releases` as of `now`, given when the branch last changed
+    pub fn measure(releases: &[Release], last_commit: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Self {
+        let mut published: Vec<DateTime<Utc>> = releases
+            .iter()
+            .filter(|release| !release.draft && !release.prerelease)
+            .filter_map(|release| release.published_at)
+            .collect();
+        published.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut gaps: Vec<f64> = published.windows(2).map(|pair| days(pair[0] - pair[1])).collect();
+        gaps.sort_by(f64::total_cmp);
+        let median_days_between = match gaps.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(gaps[n / 2]),
+            n => Some((gaps[n / 2 - 1] + gaps[n / 2]) / 2.0),
+        };
+
+        let latest = published.first().copied();
+        let unreleased_days = match (last_commit, latest) {
+            (Some(commit), Some(release)) if commit > release => Some(days(now - commit).max(0.0)),
+            (Some(_), Some(_)) => Some(0.0),
+            (Some(commit), None) => Some(days(now - commit).max(0.0)),
+            (None, _) => None,
+        };
+
+        Self {
+            releases: published.len(),
+            releases_last_year: published.iter().filter(|at| now - **at <= Duration::days(365)).count(),
+            median_days_between,
+            days_since_release: latest.map(|at| days(now - at).max(0.0)),
+            unreleased_days,
+        }
+    }
+
+    /// Why the cadence falls short of `expected`, one finding per line
+    pub fn shortfalls(&self, expected: &CadenceExpectation) -> Vec<String> {
+        let mut findings = Vec::new();
+        if let Some(max) = expected.max_days_between_releases.map(f64::from) {
+            if self.releases == 0 {
+                findings.push("no releases published".to_string());
+            }
+            if let Some(median) = self.median_days_between.filter(|median| *median > max) {
+                findings.push(format!("releases come {:.0} days apart, expected at most {}", median, max));
+            }
+            if let Some(since) = self.days_since_release.filter(|since| *since > max) {
+                findings.push(format!("last release was {:.0} days ago, expected at most {}", since, max));
+            }
+        }
+        if let Some(max) = expected.max_unreleased_days.map(f64::from) {
+            if let Some(waiting) = self.unreleased_days.filter(|waiting| *waiting > max) {
+                findings.push(format!("latest commit unreleased for {:.0} days, expected at most {}", waiting, max));
+            }
+        }
+        findings
+    }
+
+    fn summary(&self) -> String {
+        let mut lines = vec![format!(
+            "{} releases measured, {} in the last year",
+            self.releases, self.releases_last_year
+        )];
+        if let Some(median) = self.median_days_between {
+            lines.push(format!("median {:.0} days between releases", median));
+        }
+        if let Some(since) = self.days_since_release {
+            lines.push(format!("last release {:.0} days ago", since));
+        }
+        if let Some(waiting) = self.unreleased_days {
+            lines.push(format!("latest commit unreleased for {:.0} days", waiting));
+        }
+        lines.join("\n")
+    }
+}
+
+fn days(duration: Duration) -> f64 {
+    duration.num_seconds() as f64 / 86_400.0
+}
+
+/// Check that a project releases as often as its maturity calls for
+pub struct ReleaseCadenceCheck;
+
+#[async_trait::async_trait]
+impl ComplianceCheck for ReleaseCadenceCheck {
+    fn id(&self) -> &str {
+        "rhodium.release_cadence"
+    }
+
+    fn name(&self) -> &str {
+        "Release Cadence"
+    }
+
+    fn tier(&self) -> CertificationTier {
+        CertificationTier::Rhodium
+    }
+
+    fn reads(&self, path: &str) -> bool {
+        path == PROJECT_CONFIG
+    }
+
+    async fn check_local(&self, _path: &Path) -> Result<CheckResult> {
+        Ok(CheckResult::not_applicable(self, "release cadence is only measured by platform scans"))
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        let Some(ReleaseHistory { ref policy, ref releases, .. }) = contents.releases else {
+            return Ok(CheckResult::not_evaluated(self, "the release history couldn't be read"));
+        };
+        let maturity = Maturity::declared(contents);
+        let expected = policy.cadence.expectation(maturity);
+        let cadence = ReleaseCadence::measure(releases, contents.metadata.last_commit_date, Utc::now());
+        let summary = cadence.summary();
+
+        if expected.is_unlimited() {
+            return Ok(CheckResult::with_details(
+                self,
+                true,
+                format!("No release cadence expected of {} projects", maturity.as_str()),
+                Some(summary),
+            ));
+        }
+        let shortfalls = cadence.shortfalls(expected);
+        if shortfalls.is_empty() {
+            Ok(CheckResult::with_details(
+                self,
+                true,
+                format!("Releases keep pace for {} projects", maturity.as_str()),
+                Some(summary),
+            ))
+        } else {
+            Ok(CheckResult::with_details(
+                self,
+                false,
+                format!("Releases fall behind for {} projects: {}", maturity.as_str(), shortfalls.join("; ")),
+                Some(summary),
+            ))
+        }
+    }
+}
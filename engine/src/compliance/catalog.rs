@@ -0,0 +1,143 @@
+//! Engine catalog
+//!
+//! Lists exactly which rule implementations the engine runs: its built-in
+//! checks, the rulepacks loaded on top of them with the digest and signature
+//! each was verified against, and the adapters registered as plugins. Every
+//! status records the standard versions it was checked against; the catalog
+//! maps those back to checks, so admins and auditors can tell what produced a
+//! certification.
+
+use super::rulepack::{PackProvenance, StandardEngines};
+use super::{builtin_standard, ComplianceEngine};
+use crate::adapters::AdapterFactory;
+use crate::CertificationTier;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// A check the engine runs
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckEntry {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub tier: CertificationTier,
+    /// Standard version (`name@version`) providing the check
+    pub source: String,
+}
+
+/// A loaded rulepack
+#[derive(Debug, Clone, Serialize)]
+pub struct RulepackEntry {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    /// Ids of the checks it defines
+    pub checks: Vec<String>,
+    /// Ids of the checks it removes
+    pub retires: Vec<String>,
+    pub provenance: Option<PackProvenance>,
+}
+
+/// A repository held on an older standard
+#[derive(Debug, Clone, Serialize)]
+pub struct PinnedRepository {
+    /// `platform:owner/repo`
+    pub repo: String,
+    pub until: DateTime<Utc>,
+}
+
+/// One standard the engine checks against, as recorded on statuses
+#[derive(Debug, Clone, Serialize)]
+pub struct StandardCatalog {
+    /// Standard versions, matching `ComplianceStatus::standard`
+    pub standard: Vec<String>,
+    /// Repositories pinned to this standard; empty for the current one
+    pub pinned: Vec<PinnedRepository>,
+    pub checks: Vec<CheckEntry>,
+    pub rulepacks: Vec<RulepackEntry>,
+}
+
+/// An out-of-tree extension
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginEntry {
+    pub kind: &'static str,
+    pub id: String,
+}
+
+/// Everything that decides how repositories are certified
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineCatalog {
+    pub engine_version: String,
+    /// The current standard first, then each pinned one
+    pub standards: Vec<StandardCatalog>,
+    pub plugins: Vec<PluginEntry>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl EngineCatalog {
+    pub fn new(engines: &StandardEngines) -> Self {
+        let plugins = AdapterFactory::registered_platforms()
+            .into_iter()
+            .map(|id| PluginEntry { kind: "adapter", id })
+            .collect();
+
+        Self {
+            engine_version: env!("CARGO_PKG_VERSION").to_string(),
+            standards: engines.catalog(),
+            plugins,
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// The standard a status recording `standard` was checked against
+    pub fn find_standard(&self, standard: &[String]) -> Option<&StandardCatalog> {
+        self.standards.iter().find(|catalog| catalog.standard == standard)
+    }
+}
+
+impl ComplianceEngine {
+    /// This engine's checks and rulepacks
+    pub fn catalog(&self) -> StandardCatalog {
+        let checks = self
+            .checks
+            .iter()
+            .map(|check| {
+                // A later pack's definition replaces an earlier one in place
+                let source = self
+                    .packs
+                    .iter()
+                    .rev()
+                    .find(|pack| pack.checks.iter().any(|definition| definition.id == check.id()))
+                    .map(|pack| format!("{}@{}", pack.name, pack.version))
+                    .unwrap_or_else(builtin_standard);
+                CheckEntry {
+                    id: check.id().to_string(),
+                    name: check.name().to_string(),
+                    description: check.description().to_string(),
+                    tier: check.tier(),
+                    source,
+                }
+            })
+            .collect();
+
+        let rulepacks = self
+            .packs
+            .iter()
+            .map(|pack| RulepackEntry {
+                name: pack.name.clone(),
+                version: pack.version.clone(),
+                description: pack.description.clone(),
+                checks: pack.checks.iter().map(|definition| definition.id.clone()).collect(),
+                retires: pack.policy.retire.clone(),
+                provenance: pack.provenance.clone(),
+            })
+            .collect();
+
+        StandardCatalog {
+            standard: self.standard.clone(),
+            pinned: Vec::new(),
+            checks,
+            rulepacks,
+        }
+    }
+}
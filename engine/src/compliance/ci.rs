@@ -0,0 +1,414 @@
+//! CI configuration and build status
+//!
+//! `silver.ci_config` looks through the repository's files for a CI
+//! system's configuration, and checks that at least one pipeline runs on
+//! pushes or pull requests: a workflow that only runs on a schedule or by
+//! hand doesn't gate changes. GitHub, Forgejo and Gitea Actions workflows
+//! name their triggers under `on:`; the other systems run on every push
+//! unless configured otherwise, so their configuration counts as it is.
+//!
+//! `silver.ci_passing` checks that the latest completed run of each workflow
+//! on the default branch succeeded, going by the runs webhooks have reported
+//! (see [`crate::db::ci_runs`]). Cancelled and skipped runs say nothing about
+//! the build and are ignored. Local scans have no recorded runs, so the
+//! check doesn't apply to them. Platform scans without recorded runs -
+//! platforms that don't send run webhooks, repositories whose CI hasn't run
+//! since they were onboarded - fail as not evaluated, since nothing shows
+//! the build works.
+
+use super::{ComplianceCheck, RepoContents};
+use crate::db::ci_runs::CiRun;
+use crate::db::documents::DocumentStore;
+use crate::events::WorkflowConclusion;
+use crate::{CertificationTier, CheckResult, Finding, RepoRef, Result, Severity};
+use std::path::Path;
+
+/// Triggers that run a workflow on a change before or as it lands
+const CHANGE_TRIGGERS: [&str; 4] = ["push", "pull_request", "pull_request_target", "merge_group"];
+
+/// A CI system and where its configuration lives
+struct CiSystem {
+    name: &'static str,
+    /// Files, and directories (ending in `/`) whose YAML files are each a
+    /// pipeline
+    paths: &'static [&'static str],
+    /// Pipelines name their triggers under `on:`, Actions style
+    triggers: bool,
+}
+
+const SYSTEMS: [CiSystem; 14] = [
+    CiSystem {
+        name: "GitHub Actions",
+        paths: &[".github/workflows/"],
+        triggers: true,
+    },
+    CiSystem {
+        name: "Forgejo Actions",
+        paths: &[".forgejo/workflows/"],
+        triggers: true,
+    },
+    CiSystem {
+        name: "Gitea Actions",
+        paths: &[".gitea/workflows/"],
+        triggers: true,
+    },
+    CiSystem {
+        name: "GitLab CI",
+        paths: &[".gitlab-ci.yml"],
+        triggers: false,
+    },
+    CiSystem {
+        name: "Woodpecker CI",
+        paths: &[".woodpecker.yml", ".woodpecker.yaml", ".woodpecker/"],
+        triggers: false,
+    },
+    CiSystem {
+        name: "SourceHut builds",
+        paths: &[".build.yml", ".builds/"],
+        triggers: false,
+    },
+    CiSystem {
+        name: "Jenkins",
+        paths: &["Jenkinsfile"],
+        triggers: false,
+    },
+    CiSystem {
+        name: "Travis CI",
+        paths: &[".travis.yml"],
+        triggers: false,
+    },
+    CiSystem {
+        name: "CircleCI",
+        paths: &[".circleci/config.yml"],
+        triggers: false,
+    },
+    CiSystem {
+        name: "Azure Pipelines",
+        paths: &["azure-pipelines.yml"],
+        triggers: false,
+    },
+    CiSystem {
+        name: "Drone CI",
+        paths: &[".drone.yml"],
+        triggers: false,
+    },
+    CiSystem {
+        name: "Bitbucket Pipelines",
+        paths: &["bitbucket-pipelines.yml"],
+        triggers: false,
+    },
+    CiSystem {
+        name: "Buildkite",
+        paths: &[".buildkite/"],
+        triggers: false,
+    },
+    CiSystem {
+        name: "AppVeyor",
+        paths: &["appveyor.yml", ".appveyor.yml"],
+        triggers: false,
+    },
+];
+
+impl CiSystem {
+    /// Whether `path` (relative to the repository root) is one of the
+    /// system's pipelines
+    fn matches(&self, path: &str) -> bool {
+        self.paths.iter().any(|pattern| match pattern.strip_suffix('/') {
+            Some(_) => path
+                .strip_prefix(pattern)
+                .is_some_and(|file| !file.contains('/') && (file.ends_with(".yml") || file.ends_with(".yaml"))),
+            None => path == *pattern,
+        })
+    }
+}
+
+/// Whether `path` (relative to the repository root) is a pipeline of any
+/// CI system
+pub(super) fn is_pipeline(path: &str) -> bool {
+    SYSTEMS.iter().any(|system| system.matches(path))
+}
+
+/// Triggers a workflow names under its top-level `on:` key, from a plain
+/// reading of the YAML: a single event, a flow list or mapping, or a block
+/// list or mapping
+fn triggers(workflow: &str) -> Vec<String> {
+    let unquote = |key: &str| key.trim().trim_matches(['"', '\'']).to_string();
+    let content = |line: &str| line.split(" #").next().unwrap_or_default().trim_end().to_string();
+
+    let mut lines = workflow.lines().map(content);
+    let Some(value) = lines.by_ref().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (!line.starts_with([' ', '\t']) && unquote(key) == "on").then(|| value.trim().to_string())
+    }) else {
+        return Vec::new();
+    };
+
+    if let Some(flow) = value.strip_prefix(['[', '{']) {
+        return flow
+            .trim_end_matches([']', '}'])
+            .split(',')
+            .filter_map(|item| {
+                let event = unquote(item.split(':').next().unwrap_or_default());
+                (!event.is_empty()).then_some(event)
+            })
+            .collect();
+    }
+    if !value.is_empty() {
+        return vec![unquote(&value)];
+    }
+
+    // A block: the entries indented least, up to the next top-level key
+    let block: Vec<String> = lines
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .take_while(|line| line.starts_with([' ', '\t']))
+        .collect();
+    let indent = block.iter().map(|line| line.len() - line.trim_start().len()).min().unwrap_or(0);
+    block
+        .iter()
+        .filter(|line| line.len() - line.trim_start().len() == indent)
+        .filter_map(|line| {
+            let entry = line.trim_start();
+            let event = match entry.strip_prefix('-') {
+                Some(item) => unquote(item),
+                None => unquote(entry.split(':').next().unwrap_or_default()),
+            };
+            (!event.is_empty()).then_some(event)
+        })
+        .collect()
+}
+
+/// Check that CI is configured and runs on changes
+pub struct CiConfigCheck;
+
+impl CiConfigCheck {
+    /// Evaluate the repository's pipelines, as `(path, content)` with `None`
+    /// for files that aren't text
+    fn evaluate(&self, pipelines: Vec<(String, Option<String>)>) -> CheckResult {
+        let mut findings = Vec::new();
+        let mut systems = Vec::new();
+        let mut gated = false;
+
+        for system in &SYSTEMS {
+            let files: Vec<&(String, Option<String>)> =
+                pipelines.iter().filter(|(path, _)| system.matches(path)).collect();
+            if files.is_empty() {
+                continue;
+            }
+            systems.push(system.name);
+            findings.push(Finding {
+                code: "ci.detected".to_string(),
+                severity: Severity::Info,
+                path: files.first().map(|(path, _)| path.clone()),
+                message: format!(
+                    "{} configured in {}",
+                    system.name,
+                    files.iter().map(|(path, _)| path.as_str()).collect::<Vec<_>>().join(", ")
+                ),
+                remediation: None,
+            });
+
+            if !system.triggers {
+                gated = true;
+                continue;
+            }
+            for (path, content) in files {
+                let events = content.as_deref().map(triggers).unwrap_or_default();
+                if events.iter().any(|event| CHANGE_TRIGGERS.contains(&event.as_str())) {
+                    gated = true;
+                    continue;
+                }
+                findings.push(Finding {
+                    code: "ci.untriggered".to_string(),
+                    severity: Severity::Warning,
+                    path: Some(path.clone()),
+                    message: if events.is_empty() {
+                        format!("{} names no triggers", path)
+                    } else {
+                        format!("{} only runs on {}", path, events.join(", "))
+                    },
+                    remediation: Some("Run the workflow `on: [push, pull_request]`".to_string()),
+                });
+            }
+        }
+
+        if systems.is_empty() {
+            findings.push(Finding {
+                code: "ci.missing".to_string(),
+                severity: Severity::Error,
+                path: None,
+                message: "No CI/CD configuration found".to_string(),
+                remediation: Some("Add CI configuration (GitHub Actions, GitLab CI, etc.)".to_string()),
+            });
+            return CheckResult::from_findings(self, false, "No CI/CD configuration found".to_string(), findings);
+        }
+        if !gated {
+            findings.push(Finding {
+                code: "ci.no_change_trigger".to_string(),
+                severity: Severity::Error,
+                path: None,
+                message: "No workflow runs on pushes or pull requests".to_string(),
+                remediation: Some("Trigger at least one workflow on push and pull_request".to_string()),
+            });
+            return CheckResult::from_findings(self, false, "CI doesn't run on pushes or pull requests".to_string(), findings);
+        }
+        CheckResult::from_findings(self, true, format!("Found CI configuration: {}", systems.join(", ")), findings)
+    }
+}
+
+#[async_trait::async_trait]
+impl ComplianceCheck for CiConfigCheck {
+    fn id(&self) -> &str {
+        "silver.ci_config"
+    }
+
+    fn name(&self) -> &str {
+        "CI/CD Configuration"
+    }
+
+    fn description(&self) -> &str {
+        "CI configuration with a pipeline that runs on pushes or pull requests"
+    }
+
+    fn tier(&self) -> CertificationTier {
+        CertificationTier::Silver
+    }
+
+    fn reads(&self, path: &str) -> bool {
+        is_pipeline(path)
+    }
+
+    async fn check_local(&self, path: &Path) -> Result<CheckResult> {
+        let mut files = Vec::new();
+        crate::adapters::local::walk_workdir(path, path, &mut files)?;
+        let pipelines = files
+            .into_iter()
+            .filter(|file| SYSTEMS.iter().any(|system| system.matches(file)))
+            .map(|file| {
+                let content = std::fs::read_to_string(path.join(&file)).ok();
+                (file, content)
+            })
+            .collect();
+        Ok(self.evaluate(pipelines))
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        let pipelines = contents
+            .files
+            .iter()
+            .filter(|file| SYSTEMS.iter().any(|system| system.matches(&file.path)))
+            .map(|file| (file.path.clone(), file.content.clone()))
+            .collect();
+        Ok(self.evaluate(pipelines))
+    }
+}
+
+/// The latest recorded run of each workflow on a repository's default branch
+#[derive(Debug, Clone)]
+pub struct BranchBuilds {
+    pub branch: String,
+    /// By workflow name
+    pub runs: Vec<CiRun>,
+}
+
+impl BranchBuilds {
+    /// Read the runs recorded for `branch`, or `None` if the store can't be
+    /// read
+    pub async fn collect(docs: &dyn DocumentStore, repo: &RepoRef, branch: &str) -> Option<Self> {
+        match docs.latest_ci_runs(repo, branch).await {
+            Ok(runs) => Some(Self {
+                branch: branch.to_string(),
+                runs,
+            }),
+            Err(e) => {
+                tracing::debug!("Couldn't read the CI runs of {}: {}", repo, e);
+                None
+            }
+        }
+    }
+}
+
+/// Check that the default branch's latest builds passed
+pub struct CiPassingCheck;
+
+impl CiPassingCheck {
+    fn evaluate(&self, builds: &BranchBuilds) -> CheckResult {
+        let judged: Vec<&CiRun> = builds
+            .runs
+            .iter()
+            .filter(|run| !matches!(run.conclusion, WorkflowConclusion::Cancelled | WorkflowConclusion::Skipped))
+            .collect();
+        if judged.is_empty() {
+            return CheckResult::not_evaluated(self, &format!("no CI runs recorded on {}", builds.branch));
+        }
+
+        let findings: Vec<Finding> = judged
+            .iter()
+            .filter(|run| run.conclusion != WorkflowConclusion::Success)
+            .map(|run| Finding {
+                code: "ci.failing".to_string(),
+                severity: Severity::Error,
+                path: None,
+                message: format!(
+                    "{} {} on {} at {}",
+                    run.workflow,
+                    conclusion_label(run.conclusion),
+                    run.branch,
+                    run.commit_sha.get(..7).unwrap_or(&run.commit_sha)
+                ),
+                remediation: Some(format!("Fix the {} build on {}", run.workflow, run.branch)),
+            })
+            .collect();
+
+        if findings.is_empty() {
+            return CheckResult::from_findings(
+                self,
+                true,
+                format!("Latest runs of {} workflow(s) on {} passed", judged.len(), builds.branch),
+                findings,
+            );
+        }
+        let message = format!("{} of {} workflow(s) failing on {}", findings.len(), judged.len(), builds.branch);
+        CheckResult::from_findings(self, false, message, findings)
+    }
+}
+
+fn conclusion_label(conclusion: WorkflowConclusion) -> &'static str {
+    match conclusion {
+        WorkflowConclusion::Success => "passed",
+        WorkflowConclusion::Failure => "failed",
+        WorkflowConclusion::Cancelled => "was cancelled",
+        WorkflowConclusion::Skipped => "was skipped",
+        WorkflowConclusion::TimedOut => "timed out",
+        WorkflowConclusion::ActionRequired => "is waiting for approval",
+    }
+}
+
+#[async_trait::async_trait]
+impl ComplianceCheck for CiPassingCheck {
+    fn id(&self) -> &str {
+        "silver.ci_passing"
+    }
+
+    fn name(&self) -> &str {
+        "Passing CI"
+    }
+
+    fn description(&self) -> &str {
+        "The latest run of each workflow on the default branch succeeded"
+    }
+
+    fn tier(&self) -> CertificationTier {
+        CertificationTier::Silver
+    }
+
+    async fn check_local(&self, _path: &Path) -> Result<CheckResult> {
+        Ok(CheckResult::not_applicable(self, "CI runs are only recorded for platform scans"))
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        match contents.builds {
+            Some(ref builds) => Ok(self.evaluate(builds)),
+            None => Ok(CheckResult::not_evaluated(self, "the recorded CI runs couldn't be read")),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Side-by-side comparison of repositories
+//!
+//! Lines up several repositories' reports check by check, with each tier's
+//! pass rate, e.g. to choose between candidate dependencies or to benchmark
+//! teams against each other.
+
+use crate::{CertificationTier, ComplianceStatus, RepoRef};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// One repository's column in a comparison
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparedRepo {
+    pub repo: RepoRef,
+    pub tier: CertificationTier,
+    pub score: f32,
+    /// Share of each tier's checks that passed; tiers without checks are left out
+    pub tier_scores: BTreeMap<CertificationTier, f32>,
+    pub checked_at: chrono::DateTime<chrono::Utc>,
+    pub standard: Vec<String>,
+}
+
+/// One check's row in a comparison
+#[derive(Debug, Clone, Serialize)]
+pub struct ComparedCheck {
+    pub id: String,
+    pub name: String,
+    pub tier: CertificationTier,
+    /// Result per repository, in column order; `None` where the check wasn't run
+    pub passed: Vec<Option<bool>>,
+    /// The repositories that ran the check disagree on it
+    pub differs: bool,
+}
+
+/// Reports lined up check by check
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub repos: Vec<ComparedRepo>,
+    /// Every check any repository ran, lowest tier first
+    pub checks: Vec<ComparedCheck>,
+}
+
+/// Compare reports, one column per report in the order given
+pub fn compare(reports: &[ComplianceStatus]) -> Comparison {
+    let mut checks: Vec<ComparedCheck> = Vec::new();
+    for (column, report) in reports.iter().enumerate() {
+        for result in &report.checks {
+            let row = match checks.iter().position(|check| check.id == result.id) {
+                Some(row) => row,
+                None => {
+                    checks.push(ComparedCheck {
+                        id: result.id.clone(),
+                        name: result.name.clone(),
+                        tier: result.tier,
+                        passed: vec![None; reports.len()],
+                        differs: false,
+                    });
+                    checks.len() - 1
+                }
+            };
+            checks[row].passed[column] = Some(result.passed);
+        }
+    }
+    for check in &mut checks {
+        let mut results = check.passed.iter().flatten();
+        check.differs = results.next().is_some_and(|first| results.any(|result| result != first));
+    }
+    checks.sort_by(|a, b| a.tier.cmp(&b.tier).then_with(|| a.id.cmp(&b.id)));
+
+    let repos = reports
+        .iter()
+        .map(|report| ComparedRepo {
+            repo: report.repo.clone(),
+            tier: report.tier,
+            score: report.score,
+            tier_scores: tier_scores(report),
+            checked_at: report.timestamp,
+            standard: report.standard.clone(),
+        })
+        .collect();
+
+    Comparison { repos, checks }
+}
+
+/// Pass rate per tier
+fn tier_scores(report: &ComplianceStatus) -> BTreeMap<CertificationTier, f32> {
+    let mut counts: BTreeMap<CertificationTier, (usize, usize)> = BTreeMap::new();
+    for check in &report.checks {
+        let (passed, total) = counts.entry(check.tier).or_default();
+        *total += 1;
+        if check.passed {
+            *passed += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .map(|(tier, (passed, total))| (tier, passed as f32 / total as f32))
+        .collect()
+}
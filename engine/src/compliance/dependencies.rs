@@ -0,0 +1,310 @@
+//! Abandoned dependency detection
+//!
+//! Flags direct dependencies that are yanked (or deprecated, npm's
+//! equivalent), whose upstream repository is archived, or that haven't had a
+//! release in years. Direct dependencies are read from the root `Cargo.toml`
+//! and `package.json`; the versions in use come from the lockfiles when they
+//! are committed.
+//!
+//! A package whose registry or platform can't be reached, or that is beyond
+//! the lookup limit, is reported as unchecked rather than flagged or counted
+//! as maintained. If no package could be checked, the check fails as not
+//! evaluated.
+
+use super::{ComplianceCheck, RepoContents};
+use crate::adapters::{AdapterConfig, AdapterFactory};
+use crate::discovery::{PackageHealth, PackageRegistry, RegistryClient};
+use crate::{CertificationTier, CheckResult, Result};
+use futures::StreamExt;
+use std::path::Path;
+
+/// Years without a release after which a package counts as unmaintained,
+/// unless `RSR_UNMAINTAINED_AFTER_YEARS` says otherwise
+const DEFAULT_UNMAINTAINED_AFTER_YEARS: i64 = 2;
+
+/// Most dependencies looked up per scan
+const MAX_LOOKUPS: usize = 200;
+
+/// Registry lookups in flight at once
+const CONCURRENT_LOOKUPS: usize = 8;
+
+/// A dependency declared directly in a root manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct DirectDependency {
+    pub registry: PackageRegistry,
+    pub name: String,
+    /// Version in use, if a lockfile pins exactly one
+    pub version: Option<String>,
+}
+
+/// Check that direct dependencies are still maintained
+pub struct AbandonedDependenciesCheck {
+    registries: RegistryClient,
+    unmaintained_after: chrono::Duration,
+}
+
+impl Default for AbandonedDependenciesCheck {
+    fn default() -> Self {
+        let years = std::env::var("RSR_UNMAINTAINED_AFTER_YEARS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_UNMAINTAINED_AFTER_YEARS);
+
+        Self {
+            registries: RegistryClient::new(),
+            unmaintained_after: chrono::Duration::days(365 * years),
+        }
+    }
+}
+
+impl AbandonedDependenciesCheck {
+    /// Why `dependency` counts as abandoned, if it does. `None` in the outer
+    /// option means it couldn't be checked.
+    async fn assess(&self, dependency: &DirectDependency) -> Option<Option<String>> {
+        let health = match self
+            .registries
+            .package_health(dependency.registry, &dependency.name, dependency.version.as_deref())
+            .await
+        {
+            Ok(health) => health,
+            Err(e) => {
+                tracing::debug!("Couldn't check {} on {}: {}", dependency.name, dependency.registry, e);
+                return None;
+            }
+        };
+
+        Some(self.abandonment(dependency, &health).await)
+    }
+
+    async fn abandonment(&self, dependency: &DirectDependency, health: &PackageHealth) -> Option<String> {
+        if health.yanked {
+            return Some(match dependency.version {
+                Some(ref version) => format!("{} is yanked", version),
+                None => "every version is yanked".to_string(),
+            });
+        }
+        if let Some(ref message) = health.deprecated {
+            return Some(format!("deprecated: {}", message));
+        }
+        if let Some(latest) = health.latest_release {
+            if chrono::Utc::now() - latest > self.unmaintained_after {
+                return Some(format!("no release since {}", latest.format("%Y-%m-%d")));
+            }
+        }
+
+        // Platform lookups are unauthenticated; a failure leaves the package unflagged
+        let repo = health.repository.as_ref()?;
+        let adapter = AdapterFactory::create(&repo.platform, AdapterConfig::default()).ok()?;
+        match adapter.get_metadata(repo).await {
+            Ok(metadata) if metadata.archived => Some(format!("{} is archived", repo)),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::debug!("Couldn't check whether {} is archived: {}", repo, e);
+                None
+            }
+        }
+    }
+
+    async fn evaluate(&self, dependencies: Vec<DirectDependency>) -> CheckResult {
+        if dependencies.is_empty() {
+            return CheckResult::with_details(self, true, "No registry dependencies to check".to_string(), None);
+        }
+
+        let total = dependencies.len();
+        if total > MAX_LOOKUPS {
+            tracing::info!("Checking the first {} of {} direct dependencies", MAX_LOOKUPS, total);
+        }
+
+        let assessed: Vec<_> = futures::stream::iter(dependencies.into_iter().take(MAX_LOOKUPS))
+            .map(|dependency| async move {
+                let assessment = self.assess(&dependency).await;
+                (dependency, assessment)
+            })
+            .buffer_unordered(CONCURRENT_LOOKUPS)
+            .collect()
+            .await;
+
+        let mut flagged = Vec::new();
+        let mut checked = 0;
+        for (dependency, assessment) in assessed {
+            match assessment {
+                Some(Some(reason)) => flagged.push(format!("{} ({}): {}", dependency.name, dependency.registry, reason)),
+                Some(None) => {}
+                None => continue,
+            }
+            checked += 1;
+        }
+        flagged.sort();
+
+        if checked == 0 {
+            return CheckResult::not_evaluated(
+                self,
+                &format!("none of the {} direct dependencies could be looked up", total),
+            );
+        }
+        let unchecked_note = match total - checked {
+            0 => String::new(),
+            n => format!("; {} of {} could not be checked", n, total),
+        };
+
+        if flagged.is_empty() {
+            CheckResult::with_details(
+                self,
+                true,
+                format!("{} direct dependencies checked are maintained{}", checked, unchecked_note),
+                None,
+            )
+        } else {
+            CheckResult::with_details(
+                self,
+                false,
+                format!(
+                    "{} of {} direct dependencies checked are yanked, archived or unmaintained{}",
+                    flagged.len(),
+                    checked,
+                    unchecked_note
+                ),
+                Some(flagged.join("\n")),
+            )
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ComplianceCheck for AbandonedDependenciesCheck {
+    fn id(&self) -> &str {
+        "gold.abandoned_dependencies"
+    }
+
+    fn name(&self) -> &str {
+        "Maintained Dependencies"
+    }
+
+    fn tier(&self) -> CertificationTier {
+        CertificationTier::Gold
+    }
+
+    fn reads(&self, path: &str) -> bool {
+        matches!(path, "Cargo.toml" | "Cargo.lock" | "package.json" | "package-lock.json")
+    }
+
+    async fn check_local(&self, path: &Path) -> Result<CheckResult> {
+        let dependencies = direct_dependencies(|name| std::fs::read_to_string(path.join(name)).ok());
+        Ok(self.evaluate(dependencies).await)
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        let dependencies = direct_dependencies(|name| {
+            contents
+                .files
+                .iter()
+                .find(|file| file.path == name)
+                .and_then(|file| file.content.clone())
+        });
+        Ok(self.evaluate(dependencies).await)
+    }
+}
+
+/// Registry dependencies declared in the root manifests, read through `read`
+fn direct_dependencies(read: impl Fn(&str) -> Option<String>) -> Vec<DirectDependency> {
+    let mut dependencies = Vec::new();
+    if let Some(manifest) = read("Cargo.toml") {
+        dependencies.extend(cargo_dependencies(&manifest, read("Cargo.lock").as_deref()));
+    }
+    if let Some(manifest) = read("package.json") {
+        dependencies.extend(npm_dependencies(&manifest, read("package-lock.json").as_deref()));
+    }
+    dependencies
+}
+
+/// crates.io dependencies from `[dependencies]`, `[build-dependencies]` and
+/// `[workspace.dependencies]`; path, git and alternate-registry ones are skipped
+pub(super) fn cargo_dependencies(manifest: &str, lockfile: Option<&str>) -> Vec<DirectDependency> {
+    let Ok(manifest) = manifest.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+    let locked = lockfile.and_then(|lock| lock.parse::<toml::Table>().ok());
+
+    let tables = [
+        manifest.get("dependencies"),
+        manifest.get("build-dependencies"),
+        manifest.get("workspace").and_then(|workspace| workspace.get("dependencies")),
+    ];
+
+    let mut dependencies: Vec<DirectDependency> = Vec::new();
+    for (key, spec) in tables.into_iter().flatten().filter_map(toml::Value::as_table).flatten() {
+        if let Some(spec) = spec.as_table() {
+            // `workspace = true` entries are declared (and collected) at the root
+            if ["path", "git", "registry", "workspace"].iter().any(|field| spec.contains_key(*field)) {
+                continue;
+            }
+        }
+        let name = spec
+            .get("package")
+            .and_then(toml::Value::as_str)
+            .unwrap_or(key)
+            .to_string();
+        if dependencies.iter().any(|dependency| dependency.name == name) {
+            continue;
+        }
+
+        let version = locked.as_ref().and_then(|lock| cargo_locked_version(lock, &name));
+        dependencies.push(DirectDependency {
+            registry: PackageRegistry::Crates,
+            name,
+            version,
+        });
+    }
+    dependencies
+}
+
+/// The locked version of a crate, if exactly one is locked
+fn cargo_locked_version(lock: &toml::Table, name: &str) -> Option<String> {
+    let mut versions = lock
+        .get("package")?
+        .as_array()?
+        .iter()
+        .filter(|package| package.get("name").and_then(toml::Value::as_str) == Some(name))
+        .filter_map(|package| package.get("version").and_then(toml::Value::as_str));
+
+    let version = versions.next()?;
+    versions.next().is_none().then(|| version.to_string())
+}
+
+/// npm dependencies from `dependencies`; local, git, URL and aliased ones are
+/// skipped
+fn npm_dependencies(manifest: &str, lockfile: Option<&str>) -> Vec<DirectDependency> {
+    let Ok(manifest) = serde_json::from_str::<serde_json::Value>(manifest) else {
+        return Vec::new();
+    };
+    let locked = lockfile.and_then(|lock| serde_json::from_str::<serde_json::Value>(lock).ok());
+
+    let Some(declared) = manifest["dependencies"].as_object() else {
+        return Vec::new();
+    };
+
+    declared
+        .iter()
+        .filter(|(_, range)| {
+            let range = range.as_str().unwrap_or_default();
+            !["file:", "link:", "git", "http:", "https:", "workspace:", "npm:"]
+                .iter()
+                .any(|prefix| range.starts_with(prefix))
+                && !range.contains('/')
+        })
+        .map(|(name, _)| {
+            // lockfileVersion 2+ keys by install path, version 1 by name
+            let version = locked.as_ref().and_then(|lock| {
+                lock["packages"][format!("node_modules/{}", name)]["version"]
+                    .as_str()
+                    .or_else(|| lock["dependencies"][name]["version"].as_str())
+                    .map(str::to_string)
+            });
+            DirectDependency {
+                registry: PackageRegistry::Npm,
+                name: name.clone(),
+                version,
+            }
+        })
+        .collect()
+}
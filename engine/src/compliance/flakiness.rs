@@ -0,0 +1,227 @@
+//! CI flakiness
+//!
+//! Green CI only means something if it is green on the first try. A commit
+//! whose workflow failed and then passed on a rerun - or on a fresh run of
+//! the same commit - got lucky; counting it as healthy hides tests that fail
+//! at random. Each workflow and commit pair that eventually passed is
+//! counted once, and is flaky if it failed first:
+//! - another run of the workflow on the same commit failed or timed out, or
+//! - the passing run took more than one attempt (reruns follow failures)
+//!
+//! Reading runs costs an API call per scan, so tenants opt in with
+//! `ci.analyze_runs`. Only run conclusions and attempt counts are read, not
+//! the logs. Scans without runs (local scans, platforms that can't list
+//! them, tenants that haven't opted in) mark the check not applicable, so it
+//! doesn't count towards the tier.
+
+use super::{ComplianceCheck, RepoContents};
+use crate::adapters::{PlatformAdapter, WorkflowRun};
+use crate::events::WorkflowConclusion;
+use crate::{CertificationTier, CheckResult, RepoRef, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+fn default_lookback_runs() -> usize {
+    100
+}
+
+fn default_max_flaky_percent() -> u32 {
+    10
+}
+
+fn default_min_passes() -> usize {
+    10
+}
+
+/// What a tenant expects of its CI runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CiPolicy {
+    /// Read the latest workflow runs while scanning
+    #[serde(default)]
+    pub analyze_runs: bool,
+    /// Latest workflow runs to read
+    #[serde(default = "default_lookback_runs")]
+    pub lookback_runs: usize,
+    /// Highest share of passes, in percent, that may have failed first
+    #[serde(default = "default_max_flaky_percent")]
+    pub max_flaky_percent: u32,
+    /// Fewest passes before flakiness is judged
+    #[serde(default = "default_min_passes")]
+    pub min_passes: usize,
+}
+
+impl Default for CiPolicy {
+    fn default() -> Self {
+        Self {
+            analyze_runs: false,
+            lookback_runs: default_lookback_runs(),
+            max_flaky_percent: default_max_flaky_percent(),
+            min_passes: default_min_passes(),
+        }
+    }
+}
+
+/// Latest workflow runs of a repository, with the policy they are held to
+#[derive(Debug, Clone)]
+pub struct CiHistory {
+    pub policy: CiPolicy,
+    /// Newest first
+    pub runs: Vec<WorkflowRun>,
+}
+
+impl CiHistory {
+    /// List the repository's latest workflow runs, or `None` if the tenant
+    /// hasn't opted in or the platform can't list them
+    pub async fn collect(adapter: &dyn PlatformAdapter, repo: &RepoRef, policy: &CiPolicy) -> Option<Self> {
+        if !policy.analyze_runs {
+            return None;
+        }
+        match adapter.list_workflow_runs(repo, policy.lookback_runs).await {
+            Ok(runs) => Some(Self {
+                policy: policy.clone(),
+                runs,
+            }),
+            Err(e) => {
+                tracing::debug!("Couldn't list the workflow runs of {}: {}", repo, e);
+                None
+            }
+        }
+    }
+}
+
+/// Passes and flaky passes of one workflow
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WorkflowFlakiness {
+    pub workflow: String,
+    pub passed: usize,
+    pub flaky: usize,
+}
+
+/// How often CI passed only after failing
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CiFlakiness {
+    /// Workflow and commit pairs that eventually passed
+    pub passed: usize,
+    /// Of those, pairs that failed first
+    pub flaky: usize,
+    /// Most flaky first
+    pub workflows: Vec<WorkflowFlakiness>,
+}
+
+impl CiFlakiness {
+    pub fn measure(runs: &[WorkflowRun]) -> Self {
+        #[derive(Default)]
+        struct Outcome {
+            passed: bool,
+            failed: bool,
+            rerun: bool,
+        }
+
+        let mut outcomes: BTreeMap<(&str, &str), Outcome> = BTreeMap::new();
+        for run in runs {
+            let outcome = outcomes.entry((run.workflow.as_str(), run.head_sha.as_str())).or_default();
+            match run.conclusion {
+                Some(WorkflowConclusion::Success) => {
+                    outcome.passed = true;
+                    outcome.rerun |= run.attempt > 1;
+                }
+                Some(WorkflowConclusion::Failure | WorkflowConclusion::TimedOut) => outcome.failed = true,
+                _ => {}
+            }
+        }
+
+        let mut workflows: BTreeMap<&str, WorkflowFlakiness> = BTreeMap::new();
+        for ((workflow, _), outcome) in outcomes.into_iter().filter(|(_, outcome)| outcome.passed) {
+            let entry = workflows.entry(workflow).or_insert_with(|| WorkflowFlakiness {
+                workflow: workflow.to_string(),
+                ..Default::default()
+            });
+            entry.passed += 1;
+            if outcome.failed || outcome.rerun {
+                entry.flaky += 1;
+            }
+        }
+
+        let mut workflows: Vec<WorkflowFlakiness> = workflows.into_values().collect();
+        workflows.sort_by(|a, b| (b.flaky * a.passed).cmp(&(a.flaky * b.passed)).then(b.flaky.cmp(&a.flaky)));
+        Self {
+            passed: workflows.iter().map(|workflow| workflow.passed).sum(),
+            flaky: workflows.iter().map(|workflow| workflow.flaky).sum(),
+            workflows,
+        }
+    }
+
+    /// Share of passes that failed first, in percent
+    pub fn percent(&self) -> Option<f64> {
+        (self.passed > 0).then(|| self.flaky as f64 * 100.0 / self.passed as f64)
+    }
+
+    fn summary(&self) -> String {
+        self.workflows
+            .iter()
+            .map(|workflow| format!("{}: {} of {} passes failed first", workflow.workflow, workflow.flaky, workflow.passed))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Check that CI passes without relying on reruns
+pub struct CiFlakinessCheck;
+
+#[async_trait::async_trait]
+impl ComplianceCheck for CiFlakinessCheck {
+    fn id(&self) -> &str {
+        "gold.ci_flakiness"
+    }
+
+    fn name(&self) -> &str {
+        "Reliable CI"
+    }
+
+    fn tier(&self) -> CertificationTier {
+        CertificationTier::Gold
+    }
+
+    async fn check_local(&self, _path: &Path) -> Result<CheckResult> {
+        Ok(CheckResult::not_applicable(self, "CI runs are only analyzed by platform scans"))
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        let Some(CiHistory { ref policy, ref runs }) = contents.ci else {
+            return Ok(CheckResult::not_applicable(
+                self,
+                "CI runs weren't read (enable `ci.analyze_runs` on a platform that lists them)",
+            ));
+        };
+        let flakiness = CiFlakiness::measure(runs);
+        let summary = (!flakiness.workflows.is_empty()).then(|| flakiness.summary());
+
+        let Some(percent) = flakiness.percent().filter(|_| flakiness.passed >= policy.min_passes) else {
+            return Ok(CheckResult::with_details(
+                self,
+                true,
+                format!("Too few passing CI runs to judge ({} of {})", flakiness.passed, policy.min_passes),
+                summary,
+            ));
+        };
+        if percent <= f64::from(policy.max_flaky_percent) {
+            Ok(CheckResult::with_details(
+                self,
+                true,
+                format!("{:.0}% of CI passes needed a rerun", percent),
+                summary,
+            ))
+        } else {
+            Ok(CheckResult::with_details(
+                self,
+                false,
+                format!(
+                    "CI is flaky: {:.0}% of passes ({} of {}) failed first, expected at most {}%",
+                    percent, flakiness.flaky, flakiness.passed, policy.max_flaky_percent
+                ),
+                summary,
+            ))
+        }
+    }
+}
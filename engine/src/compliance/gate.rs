@@ -0,0 +1,127 @@
+//! Pull request review gate
+//!
+//! Compares a pull request's head against its target branch. When the change
+//! lowers compliance and leaves the repository below the tenant's target
+//! tier, the gate produces a review listing the checks the change broke.
+
+use crate::branding::Branding;
+use crate::db::annotations::Annotation;
+use crate::{CertificationTier, CheckResult, ComplianceStatus};
+
+/// A compliance regression introduced by a pull request
+#[derive(Debug, Clone)]
+pub struct Regression {
+    pub target: CertificationTier,
+    pub base_tier: CertificationTier,
+    pub head_tier: CertificationTier,
+    pub base_score: f32,
+    pub head_score: f32,
+    /// Checks passing on the target branch but failing on the head
+    pub newly_failing: Vec<CheckResult>,
+    /// Checks failing on the target branch but passing on the head
+    pub newly_passing: Vec<CheckResult>,
+    /// Annotations from the repository's last stored report that bear on
+    /// this regression
+    pub annotations: Vec<Annotation>,
+}
+
+/// Compare head against base; `None` unless the head is worse than the base
+/// and below `target`
+pub fn evaluate(base: &ComplianceStatus, head: &ComplianceStatus, target: CertificationTier) -> Option<Regression> {
+    if head.tier >= target || (head.tier >= base.tier && head.score >= base.score) {
+        return None;
+    }
+
+    let passed_on_base = |id: &str| base.checks.iter().any(|c| c.id == id && c.passed);
+    let failed_on_base = |id: &str| base.checks.iter().any(|c| c.id == id && !c.passed);
+
+    Some(Regression {
+        target,
+        base_tier: base.tier,
+        head_tier: head.tier,
+        base_score: base.score,
+        head_score: head.score,
+        newly_failing: head
+            .checks
+            .iter()
+            .filter(|c| !c.passed && passed_on_base(&c.id))
+            .cloned()
+            .collect(),
+        newly_passing: head
+            .checks
+            .iter()
+            .filter(|c| c.passed && failed_on_base(&c.id))
+            .cloned()
+            .collect(),
+        annotations: Vec::new(),
+    })
+}
+
+impl Regression {
+    /// Keep the annotations that are about the whole report or a newly
+    /// failing check
+    pub fn with_annotations(mut self, annotations: Vec<Annotation>) -> Self {
+        self.annotations = annotations
+            .into_iter()
+            .filter(|a| match a.check_id {
+                Some(ref id) => self.newly_failing.iter().any(|c| &c.id == id),
+                None => true,
+            })
+            .collect();
+        self
+    }
+
+    /// Markdown review body with the diff of failing checks, in the
+    /// deployment's terms
+    pub fn review_body(&self, branding: &Branding) -> String {
+        let mut body = format!(
+            "## {} Compliance regression\n\nThis change drops the repository from {} {} ({:.1}%) to {} {} ({:.1}%), below the target tier {}.\n",
+            branding.program,
+            self.base_tier.symbol(),
+            branding.tier_code(self.base_tier),
+            self.base_score * 100.0,
+            self.head_tier.symbol(),
+            branding.tier_code(self.head_tier),
+            self.head_score * 100.0,
+            branding.tier_code(self.target)
+        );
+
+        if !self.newly_failing.is_empty() || !self.newly_passing.is_empty() {
+            body.push_str("\n```diff\n");
+            for check in &self.newly_failing {
+                body.push_str(&format!("- [{}] {}: {}\n", branding.tier_code(check.tier), check.name, check.message));
+            }
+            for check in &self.newly_passing {
+                body.push_str(&format!("+ [{}] {}: {}\n", branding.tier_code(check.tier), check.name, check.message));
+            }
+            body.push_str("```\n");
+        }
+
+        if !self.annotations.is_empty() {
+            body.push_str("\n### Annotations\n\n");
+            for annotation in &self.annotations {
+                let subject = annotation.check_id.as_deref().map(|id| format!(" `{}`", id)).unwrap_or_default();
+                let reference = annotation.reference.as_deref().map(|r| format!(" ({})", r)).unwrap_or_default();
+                body.push_str(&format!(
+                    "- **{}**{}: {}{} ({})\n",
+                    annotation.kind.label(),
+                    subject,
+                    annotation.message,
+                    reference,
+                    annotation.author
+                ));
+            }
+        }
+
+        if !branding.links.is_empty() {
+            let links: Vec<String> = branding
+                .links
+                .iter()
+                .map(|link| format!("[{}]({})", link.label, link.url))
+                .collect();
+            body.push_str(&format!("\n---\n{}\n", links.join(" · ")));
+        }
+
+        body
+    }
+}
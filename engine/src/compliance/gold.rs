@@ -10,7 +10,11 @@ pub fn get_checks() -> Vec<Box<dyn ComplianceCheck>> {
         Box::new(DocumentationCheck),
         Box::new(TestCoverageCheck),
         Box::new(DependencyScanningCheck),
+        Box::new(super::dependencies::AbandonedDependenciesCheck::default()),
         Box::new(IssueTemplatesCheck),
+        Box::new(super::authorship::CommitAuthorsCheck),
+        Box::new(super::flakiness::CiFlakinessCheck),
+        Box::new(super::signing::SignedCommitsCheck),
     ]
 }
 
@@ -19,11 +23,11 @@ pub fn get_checks() -> Vec<Box<dyn ComplianceCheck>> {
 
 #[async_trait::async_trait]
 impl ComplianceCheck for DocumentationCheck {
-    fn id(&self) -> &'static str {
+    fn id(&self) -> &str {
         "gold.documentation"
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "Comprehensive Documentation"
     }
 
@@ -73,6 +77,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                 passed: true,
                 message: format!("Found documentation: {}", found_docs.join(", ")),
                 details: None,
+                findings: Vec::new(),
             })
         } else {
             Ok(CheckResult {
@@ -82,6 +87,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                 passed: false,
                 message: "No comprehensive documentation found".to_string(),
                 details: Some("Add docs/ directory or API documentation".to_string()),
+                findings: Vec::new(),
             })
         }
     }
@@ -99,6 +105,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
                     passed: true,
                     message: format!("Found documentation: {}", file.path),
                     details: None,
+                    findings: Vec::new(),
                 });
             }
         }
@@ -110,6 +117,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
             passed: false,
             message: "No comprehensive documentation found".to_string(),
             details: None,
+            findings: Vec::new(),
         })
     }
 }
@@ -119,11 +127,11 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
 
 #[async_trait::async_trait]
 impl ComplianceCheck for TestCoverageCheck {
-    fn id(&self) -> &'static str {
+    fn id(&self) -> &str {
         "gold.test_coverage"
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "Test Coverage"
     }
 
@@ -202,6 +210,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                 passed: true,
                 message: "Tests and coverage configuration found".to_string(),
                 details: None,
+                findings: Vec::new(),
             })
         } else if has_tests {
             Ok(CheckResult {
@@ -211,6 +220,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                 passed: false,
                 message: "Tests found but no coverage configuration".to_string(),
                 details: Some("Add coverage reporting (codecov, coveralls, etc.)".to_string()),
+                findings: Vec::new(),
             })
         } else {
             Ok(CheckResult {
@@ -220,6 +230,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                 passed: false,
                 message: "No test suite found".to_string(),
                 details: Some("Add tests/ directory and coverage configuration".to_string()),
+                findings: Vec::new(),
             })
         }
     }
@@ -246,6 +257,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
                 passed: true,
                 message: "Tests and coverage found".to_string(),
                 details: None,
+                findings: Vec::new(),
             })
         } else {
             Ok(CheckResult {
@@ -255,6 +267,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
                 passed: false,
                 message: "Test coverage requirements not met".to_string(),
                 details: None,
+                findings: Vec::new(),
             })
         }
     }
@@ -265,11 +278,11 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
 
 #[async_trait::async_trait]
 impl ComplianceCheck for DependencyScanningCheck {
-    fn id(&self) -> &'static str {
+    fn id(&self) -> &str {
         "gold.dependency_scanning"
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "Dependency Scanning"
     }
 
@@ -297,6 +310,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                     passed: true,
                     message: format!("Found dependency scanning config: {}", config),
                     details: None,
+                    findings: Vec::new(),
                 });
             }
         }
@@ -315,6 +329,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                                 passed: true,
                                 message: "Found security scanning in CI".to_string(),
                                 details: None,
+                                findings: Vec::new(),
                             });
                         }
                     }
@@ -329,6 +344,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
             passed: false,
             message: "No dependency scanning configured".to_string(),
             details: Some("Add Dependabot, Renovate, or Snyk configuration".to_string()),
+            findings: Vec::new(),
         })
     }
 
@@ -345,6 +361,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
                     passed: true,
                     message: format!("Found dependency scanning: {}", file.path),
                     details: None,
+                    findings: Vec::new(),
                 });
             }
         }
@@ -356,6 +373,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
             passed: false,
             message: "No dependency scanning configured".to_string(),
             details: None,
+            findings: Vec::new(),
         })
     }
 }
@@ -365,11 +383,11 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
 
 #[async_trait::async_trait]
 impl ComplianceCheck for IssueTemplatesCheck {
-    fn id(&self) -> &'static str {
+    fn id(&self) -> &str {
         "gold.issue_templates"
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "Issue/PR Templates"
     }
 
@@ -403,6 +421,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                 passed: true,
                 message: format!("Found templates: {}", found.join(", ")),
                 details: None,
+                findings: Vec::new(),
             })
         } else {
             Ok(CheckResult {
@@ -412,6 +431,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                 passed: false,
                 message: "No issue/PR templates found".to_string(),
                 details: Some("Add .github/ISSUE_TEMPLATE/ and PR templates".to_string()),
+                findings: Vec::new(),
             })
         }
     }
@@ -429,6 +449,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
                     passed: true,
                     message: format!("Found template: {}", file.path),
                     details: None,
+                    findings: Vec::new(),
                 });
             }
         }
@@ -440,6 +461,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
             passed: false,
             message: "No issue/PR templates found".to_string(),
             details: None,
+            findings: Vec::new(),
         })
     }
 }
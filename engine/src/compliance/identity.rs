@@ -0,0 +1,106 @@
+//! Linked repository identities across platforms
+//!
+//! A project mirrored on several platforms (e.g. a GitHub mirror of a GitLab
+//! canonical repository) often keeps its CI, releases and security tooling on
+//! only one of them. Linking the identities lets evidence found on one
+//! satisfy checks for the others. Only the checks a link names can be
+//! satisfied this way, since most checks are about the repository's own
+//! content. Reports name the canonical identity and which checks were
+//! satisfied by which mirror.
+
+use crate::{RepoRef, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Checks whose evidence usually lives with the platform rather than the
+/// repository's content
+const DEFAULT_EVIDENCE_CHECKS: [&str; 5] = [
+    "silver.ci_config",
+    "gold.dependency_scanning",
+    "gold.issue_templates",
+    "rhodium.sbom",
+    "rhodium.slsa",
+];
+
+/// Identities of one project on several platforms
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdentityLink {
+    /// Canonical repository, as `platform:owner/repo`
+    pub canonical: String,
+    /// Mirrors of the canonical repository, as `platform:owner/repo`
+    pub mirrors: Vec<String>,
+    /// Check ids a linked identity may satisfy
+    #[serde(default = "default_evidence_checks")]
+    pub evidence_checks: Vec<String>,
+}
+
+fn default_evidence_checks() -> Vec<String> {
+    DEFAULT_EVIDENCE_CHECKS.iter().map(|id| id.to_string()).collect()
+}
+
+impl IdentityLink {
+    pub fn canonical(&self) -> Result<RepoRef> {
+        self.canonical.parse()
+    }
+
+    /// Every identity in the link, canonical first
+    pub fn identities(&self) -> Result<Vec<RepoRef>> {
+        std::iter::once(&self.canonical)
+            .chain(&self.mirrors)
+            .map(|key| key.parse())
+            .collect()
+    }
+
+    /// Whether `repo` (ignoring its branch) is one of the linked identities
+    pub fn contains(&self, repo: &RepoRef) -> bool {
+        let key = identity_key(repo);
+        self.canonical == key || self.mirrors.contains(&key)
+    }
+
+    /// Identities other than `repo` whose evidence may count for it
+    pub fn others(&self, repo: &RepoRef) -> Result<Vec<RepoRef>> {
+        let key = identity_key(repo);
+        Ok(self
+            .identities()?
+            .into_iter()
+            .filter(|identity| identity_key(identity) != key)
+            .collect())
+    }
+}
+
+/// The link `repo` belongs to, if any
+pub fn link_for<'a>(links: &'a [IdentityLink], repo: &RepoRef) -> Option<&'a IdentityLink> {
+    links.iter().find(|link| link.contains(repo))
+}
+
+/// Problems with a set of links: unparsable identities and identities
+/// claimed by more than one link
+pub fn validate_links(links: &[IdentityLink]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+
+    for link in links {
+        for key in std::iter::once(&link.canonical).chain(&link.mirrors) {
+            match key.parse::<RepoRef>() {
+                Ok(repo) if repo.branch.is_some() => {
+                    problems.push(format!("links.{}: identities must not name a branch", key));
+                }
+                Ok(_) => {}
+                Err(e) => problems.push(format!("links: {}", e)),
+            }
+            if !seen.insert(key.as_str()) {
+                problems.push(format!("links.{}: linked more than once", key));
+            }
+        }
+        if link.mirrors.is_empty() {
+            problems.push(format!("links.{}: no mirrors", link.canonical));
+        }
+    }
+
+    problems
+}
+
+/// `platform:owner/repo`, without the branch
+fn identity_key(repo: &RepoRef) -> String {
+    format!("{}:{}/{}", repo.platform, repo.owner, repo.repo)
+}
@@ -1,21 +1,53 @@
 //! Compliance checking logic for RSR certification tiers
 
+pub mod authorship;
+mod baseline;
 mod bronze;
+pub mod cadence;
+pub mod catalog;
+pub mod ci;
+pub mod compare;
+mod dependencies;
+pub mod flakiness;
+pub mod gate;
 mod gold;
+pub mod identity;
+pub mod protection;
+pub mod releases;
 mod rhodium;
+pub mod rulepack;
+pub mod sandbox;
+pub mod scoring;
+pub mod selfcheck;
+pub mod signing;
 mod silver;
+pub mod vetting;
 
-use crate::{CertificationTier, CheckResult, ComplianceStatus, RepoRef, Result};
+pub use rulepack::Rulepack;
+pub use sandbox::{Sandbox, SandboxLimits};
+pub use scoring::{score, ScoringPolicy};
+
+use crate::adapters::PlatformAdapter;
+use crate::{CertificationTier, CheckResult, ComplianceStatus, Finding, RepoRef, Result, Severity};
+use futures::StreamExt;
 use std::path::Path;
 
+/// Checks a scan runs at once. Results keep the checks' evaluation order.
+const CHECK_CONCURRENCY: usize = 8;
+
+/// Version of the standard built into this engine, e.g. `rsr@0.1.0`
+pub fn builtin_standard() -> String {
+    format!("rsr@{}", env!("CARGO_PKG_VERSION"))
+}
+
 /// Compliance check trait - implemented by each tier's check module
 #[async_trait::async_trait]
 pub trait ComplianceCheck: Send + Sync {
     /// Unique identifier for this check
-    fn id(&self) -> &'static str;
+    fn id(&self) -> &str;
 
     /// Human-readable name
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
 
     /// Which tier this check belongs to
     fn tier(&self) -> CertificationTier;
@@ -25,6 +57,136 @@ pub trait ComplianceCheck: Send + Sync {
 
     /// Run the check against remote repository contents
     async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult>;
+
+    /// Whether the check judges the content of `path`, rather than only
+    /// whether it exists. Remote scans fetch only the files some check reads.
+    fn reads(&self, _path: &str) -> bool {
+        false
+    }
+
+    /// What the check looks for; defaults to its name
+    fn description(&self) -> &str {
+        self.name()
+    }
+
+    /// Lowest tier a repository must pass this check to reach; the tier
+    /// the check belongs to
+    fn tier_requirement(&self) -> CertificationTier {
+        self.tier()
+    }
+
+    /// Run the check against whichever form of the repository `ctx` holds
+    async fn run(&self, ctx: RepoContext<'_>) -> Result<CheckResult> {
+        match ctx {
+            RepoContext::Local(path) => self.check_local(path).await,
+            RepoContext::Remote(contents) => self.check_remote(contents).await,
+        }
+    }
+
+    /// Whether the check comes from outside the engine and runs under the
+    /// engine's [`SandboxLimits`]
+    fn sandboxed(&self) -> bool {
+        false
+    }
+}
+
+impl CheckResult {
+    /// Result of `check` with its findings; the details list the warnings
+    /// and errors among them, each with its remediation
+    pub fn from_findings(check: &dyn ComplianceCheck, passed: bool, message: String, findings: Vec<Finding>) -> Self {
+        let details = findings
+            .iter()
+            .filter(|finding| finding.severity >= Severity::Warning)
+            .map(|finding| match finding.remediation {
+                Some(ref remediation) => format!("{}: {}", finding.message, remediation),
+                None => finding.message.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            id: check.id().to_string(),
+            name: check.name().to_string(),
+            tier: check.tier(),
+            passed,
+            message,
+            details: (!details.is_empty()).then(|| details.join("\n")),
+            findings,
+        }
+    }
+
+    /// Result of `check` with free-form details and no findings
+    pub fn with_details(check: &dyn ComplianceCheck, passed: bool, message: String, details: Option<String>) -> Self {
+        Self {
+            id: check.id().to_string(),
+            name: check.name().to_string(),
+            tier: check.tier(),
+            passed,
+            message,
+            details,
+            findings: Vec::new(),
+        }
+    }
+
+    /// Result of `check` when the evidence it judges couldn't be gathered.
+    /// It fails: a check that saw nothing can't vouch for the repository.
+    pub fn not_evaluated(check: &dyn ComplianceCheck, reason: &str) -> Self {
+        Self {
+            id: check.id().to_string(),
+            name: check.name().to_string(),
+            tier: check.tier(),
+            passed: false,
+            message: format!("Not evaluated: {}", reason),
+            details: None,
+            findings: vec![Finding {
+                code: NOT_EVALUATED.to_string(),
+                severity: Severity::Error,
+                path: None,
+                message: reason.to_string(),
+                remediation: None,
+            }],
+        }
+    }
+
+    /// Result of `check` when it doesn't apply to this scan, such as a
+    /// platform-only check on a local checkout or an opt-in check the tenant
+    /// hasn't enabled. Scoring leaves it out of both the tier and the score.
+    pub fn not_applicable(check: &dyn ComplianceCheck, reason: &str) -> Self {
+        Self {
+            id: check.id().to_string(),
+            name: check.name().to_string(),
+            tier: check.tier(),
+            passed: true,
+            message: format!("Not applicable: {}", reason),
+            details: None,
+            findings: vec![Finding {
+                code: NOT_APPLICABLE.to_string(),
+                severity: Severity::Info,
+                path: None,
+                message: reason.to_string(),
+                remediation: None,
+            }],
+        }
+    }
+
+    /// Whether the check didn't apply to the scan; see [`Self::not_applicable`]
+    pub fn is_not_applicable(&self) -> bool {
+        self.findings.iter().any(|finding| finding.code == NOT_APPLICABLE)
+    }
+}
+
+/// Finding code of a check that couldn't gather its evidence
+pub const NOT_EVALUATED: &str = "check.not_evaluated";
+
+/// Finding code of a check that doesn't apply to the scan
+pub const NOT_APPLICABLE: &str = "check.not_applicable";
+
+/// The repository a scan runs its checks against
+#[derive(Debug, Clone, Copy)]
+pub enum RepoContext<'a> {
+    /// A local checkout
+    Local(&'a Path),
+    /// Contents fetched through a platform adapter
+    Remote(&'a RepoContents),
 }
 
 /// Repository contents abstraction for remote checking
@@ -32,8 +194,112 @@ pub trait ComplianceCheck: Send + Sync {
 pub struct RepoContents {
     pub files: Vec<FileEntry>,
     pub metadata: RepoMetadata,
+    /// Authors of the branch's latest commits, when collected
+    pub authorship: Option<authorship::Authorship>,
+    /// Tags and releases, when collected
+    pub releases: Option<releases::ReleaseHistory>,
+    /// Latest CI workflow runs, when collected
+    pub ci: Option<flakiness::CiHistory>,
+    /// Latest recorded build of each workflow on the default branch, when collected
+    pub builds: Option<ci::BranchBuilds>,
+    /// The default branch's protection rule, when collected
+    pub protection: Option<protection::DefaultBranchProtection>,
+    /// Signatures of the latest commits and tags, when collected
+    pub signing: Option<signing::SignedHistory>,
+    /// The tenant's shared dependency audits, when collected
+    pub vetting: Option<vetting::AuditSets>,
 }
 
+impl RepoContents {
+    /// Load a directory as if it had been fetched from a platform.
+    /// Non-UTF-8 files are listed without content. The evidence fields are
+    /// left unset; the checks judging them report as not evaluated.
+    pub fn from_dir(path: &Path) -> Result<Self> {
+        let mut paths = Vec::new();
+        crate::adapters::local::walk_workdir(path, path, &mut paths)?;
+        paths.sort();
+
+        let files = paths
+            .into_iter()
+            .map(|relative| {
+                let bytes = std::fs::read(path.join(&relative))?;
+                Ok(FileEntry {
+                    size: bytes.len() as u64,
+                    content: String::from_utf8(bytes).ok(),
+                    path: relative,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            files,
+            metadata: RepoMetadata::default(),
+            authorship: None,
+            releases: None,
+            ci: None,
+            builds: None,
+            protection: None,
+            signing: None,
+            vetting: None,
+        })
+    }
+
+    /// Fetch a repository (at `repo.branch`, or its default branch) through
+    /// its platform adapter. Every file is listed, but only those one of
+    /// `engine`'s checks reads are downloaded; the rest, and non-UTF-8
+    /// files, are listed without content. As with [`Self::from_dir`], the
+    /// caller collects the evidence fields.
+    pub async fn fetch(adapter: &dyn PlatformAdapter, repo: &RepoRef, engine: &ComplianceEngine) -> Result<Self> {
+        let paths = adapter.list_files(repo, None).await?;
+        let read: Vec<&str> = paths.iter().map(String::as_str).filter(|path| engine.reads(path)).collect();
+        let mut fetched = if read.is_empty() {
+            Default::default()
+        } else {
+            adapter.fetch_files(repo, &read).await?
+        };
+
+        let files = paths
+            .iter()
+            .map(|path| match fetched.remove(path) {
+                Some(bytes) => FileEntry {
+                    size: bytes.len() as u64,
+                    content: String::from_utf8(bytes).ok(),
+                    path: path.clone(),
+                },
+                None => FileEntry {
+                    path: path.clone(),
+                    content: None,
+                    size: 0,
+                },
+            })
+            .collect();
+
+        let metadata = adapter.get_metadata(repo).await?;
+
+        Ok(Self {
+            files,
+            metadata: RepoMetadata {
+                has_ci: metadata.has_ci,
+                has_branch_protection: metadata.has_branch_protection,
+                has_security_policy: metadata.has_security_policy,
+                default_branch: metadata.default_branch,
+                open_issues: metadata.open_issues_count,
+                stars: metadata.stargazers_count,
+                last_commit_date: metadata.last_push,
+            },
+            authorship: None,
+            releases: None,
+            ci: None,
+            builds: None,
+            protection: None,
+            signing: None,
+            vetting: None,
+        })
+    }
+}
+
+/// A file of the repository. Remote scans fill `content` and `size` only
+/// for the files a check reads.
 #[derive(Debug, Clone)]
 pub struct FileEntry {
     pub path: String,
@@ -55,6 +321,12 @@ pub struct RepoMetadata {
 /// Main compliance engine
 pub struct ComplianceEngine {
     checks: Vec<Box<dyn ComplianceCheck>>,
+    scoring: ScoringPolicy,
+    standard: Vec<String>,
+    /// Rulepacks loaded, in load order
+    packs: Vec<Rulepack>,
+    /// Limits sandboxed checks run under
+    sandbox: Sandbox,
 }
 
 impl Default for ComplianceEngine {
@@ -67,9 +339,16 @@ impl ComplianceEngine {
     pub fn new() -> Self {
         let mut checks: Vec<Box<dyn ComplianceCheck>> = Vec::new();
 
+        // Add the baseline documents: license, README, security policy,
+        // contributing guide and code of conduct
+        checks.extend(baseline::get_checks());
+
         // Add Bronze tier checks
         checks.extend(bronze::get_checks());
 
+        // Add the default branch's protection rule, from Silver to Rhodium
+        checks.extend(protection::get_checks());
+
         // Add Silver tier checks
         checks.extend(silver::get_checks());
 
@@ -79,103 +358,307 @@ pub fn new() -> Self {
         // Add Rhodium tier checks
         checks.extend(rhodium::get_checks());
 
-        Self { checks }
+        Self {
+            checks,
+            scoring: ScoringPolicy::default(),
+            standard: vec![builtin_standard()],
+            packs: Vec::new(),
+            sandbox: Sandbox::default(),
+        }
+    }
+
+    /// Run sandboxed checks under `sandbox`
+    pub fn with_sandbox(mut self, sandbox: Sandbox) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    /// Add a rulepack's checks and apply its policy fragment. Checks the pack
+    /// retires are removed and checks with an existing id replace it in place.
+    pub fn with_rulepack(mut self, pack: &Rulepack) -> Self {
+        self.checks
+            .retain(|check| !pack.policy.retire.iter().any(|id| id == check.id()));
+
+        for check in pack.checks() {
+            match self.checks.iter().position(|existing| existing.id() == check.id()) {
+                Some(index) => self.checks[index] = check,
+                None => self.checks.push(check),
+            }
+        }
+
+        self.scoring
+            .tier_weights
+            .extend(pack.policy.tier_weights.iter().map(|(&tier, &weight)| (tier, weight)));
+
+        self.standard.push(format!("{}@{}", pack.name, pack.version));
+        self.packs.push(pack.clone());
+        tracing::debug!("Loaded rulepack {}@{}", pack.name, pack.version);
+        self
+    }
+
+    /// Engine with every configured rulepack verified and loaded
+    pub fn with_rulepacks(config: &rulepack::RulepackConfig) -> Result<Self> {
+        Self::with_pack_specs(config, &config.packs)
+    }
+
+    /// Engine with the given `name@version` packs from `config`'s directory,
+    /// their checks sandboxed under `config.sandbox`
+    pub fn with_pack_specs(config: &rulepack::RulepackConfig, specs: &[String]) -> Result<Self> {
+        let engine = Self::new().with_sandbox(Sandbox::new(config.sandbox.clone()));
+        Ok(config
+            .load(specs)?
+            .iter()
+            .fold(engine, |engine, pack| engine.with_rulepack(pack)))
+    }
+
+    /// Standard versions this engine checks against
+    pub fn standard(&self) -> &[String] {
+        &self.standard
+    }
+
+    /// IDs of all registered checks, in evaluation order
+    pub fn check_ids(&self) -> Vec<&str> {
+        self.checks.iter().map(|check| check.id()).collect()
+    }
+
+    /// Whether any check reads the content of `path`
+    pub fn reads(&self, path: &str) -> bool {
+        self.checks.iter().any(|check| check.reads(path))
+    }
+
+    /// Look up a single check by ID
+    pub fn find_check(&self, id: &str) -> Option<&dyn ComplianceCheck> {
+        self.checks
+            .iter()
+            .find(|check| check.id() == id)
+            .map(|check| check.as_ref())
     }
 
     /// Check compliance of a local repository
     pub async fn check_local(&self, path: &Path) -> Result<ComplianceStatus> {
         let repo_ref = RepoRef::new("local", "local", path.file_name().unwrap_or_default().to_string_lossy());
+        self.check(repo_ref, RepoContext::Local(path)).await
+    }
 
-        let mut results = Vec::new();
-
-        for check in &self.checks {
-            match check.check_local(path).await {
-                Ok(result) => results.push(result),
-                Err(e) => {
-                    tracing::warn!("Check {} failed: {}", check.id(), e);
-                    results.push(CheckResult {
-                        id: check.id().to_string(),
-                        name: check.name().to_string(),
-                        tier: check.tier(),
-                        passed: false,
-                        message: format!("Check failed: {}", e),
-                        details: None,
-                    });
-                }
-            }
-        }
+    /// Check compliance using fetched repository contents
+    pub async fn check_remote(&self, repo: RepoRef, contents: &RepoContents) -> Result<ComplianceStatus> {
+        self.check(repo, RepoContext::Remote(contents)).await
+    }
 
-        let tier = calculate_tier(&results);
-        let score = calculate_score(&results);
+    /// Run every check against `ctx` and score the results. A check that
+    /// errors fails rather than failing the scan.
+    pub async fn check(&self, repo: RepoRef, ctx: RepoContext<'_>) -> Result<ComplianceStatus> {
+        let results = self.run_checks(ctx).await;
+        let (score, tier) = score(&results, &self.scoring);
 
         Ok(ComplianceStatus {
-            repo: repo_ref,
+            repo,
             tier,
             score,
             checks: results,
             timestamp: chrono::Utc::now(),
+            standard: self.standard.clone(),
+            canonical: None,
+            evidence: Default::default(),
         })
     }
 
-    /// Check compliance using fetched repository contents
-    pub async fn check_remote(&self, repo: RepoRef, contents: &RepoContents) -> Result<ComplianceStatus> {
-        let mut results = Vec::new();
-
-        for check in &self.checks {
-            match check.check_remote(contents).await {
-                Ok(result) => results.push(result),
-                Err(e) => {
-                    tracing::warn!("Check {} failed: {}", check.id(), e);
-                    results.push(CheckResult {
-                        id: check.id().to_string(),
-                        name: check.name().to_string(),
-                        tier: check.tier(),
-                        passed: false,
-                        message: format!("Check failed: {}", e),
-                        details: None,
-                    });
+    /// Results of every check against `ctx`, in evaluation order, running
+    /// up to [`CHECK_CONCURRENCY`] checks at once
+    pub async fn run_checks(&self, ctx: RepoContext<'_>) -> Vec<CheckResult> {
+        // Built up front: a stream mapping each check to its future would be
+        // generic over the check's lifetime, which keeps it from being Send
+        let runs: Vec<_> = self.checks.iter().map(|check| self.run_check(check.as_ref(), ctx)).collect();
+        futures::stream::iter(runs).buffered(CHECK_CONCURRENCY).collect().await
+    }
+
+    async fn run_check(&self, check: &dyn ComplianceCheck, ctx: RepoContext<'_>) -> CheckResult {
+        self.sandbox.check(check, ctx).await.unwrap_or_else(|e| {
+            tracing::warn!("Check {} failed: {}", check.id(), e);
+            CheckResult::with_details(check, false, format!("Check failed: {}", e), None)
+        })
+    }
+
+    /// Check a repository that is linked to identities on other platforms.
+    /// Failed checks listed in `link.evidence_checks` pass if one of the
+    /// `linked` identities' contents satisfies them; the status records the
+    /// canonical identity and where each such check's evidence came from.
+    pub async fn check_linked(
+        &self,
+        repo: RepoRef,
+        contents: &RepoContents,
+        link: &identity::IdentityLink,
+        linked: &[(RepoRef, RepoContents)],
+    ) -> Result<ComplianceStatus> {
+        let mut status = self.check_remote(repo, contents).await?;
+
+        for result in status.checks.iter_mut() {
+            if result.passed || !link.evidence_checks.contains(&result.id) {
+                continue;
+            }
+            let Some(check) = self.find_check(&result.id) else {
+                continue;
+            };
+
+            for (identity, identity_contents) in linked {
+                match self.sandbox.check(check, RepoContext::Remote(identity_contents)).await {
+                    Ok(evidence) if evidence.passed => {
+                        *result = CheckResult {
+                            message: format!("{} (evidence from {})", evidence.message, identity),
+                            ..evidence
+                        };
+                        status.evidence.insert(result.id.clone(), identity.clone());
+                        break;
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Check {} failed on linked {}: {}", result.id, identity, e),
                 }
             }
         }
 
-        let tier = calculate_tier(&results);
-        let score = calculate_score(&results);
+        let (score, tier) = score(&status.checks, &self.scoring);
+        status.score = score;
+        status.tier = tier;
+        status.canonical = Some(link.canonical()?);
 
-        Ok(ComplianceStatus {
-            repo,
-            tier,
-            score,
-            checks: results,
-            timestamp: chrono::Utc::now(),
-        })
+        Ok(status)
     }
 }
 
-/// Calculate the highest tier where all required checks pass
-fn calculate_tier(results: &[CheckResult]) -> CertificationTier {
-    let tiers = [
-        CertificationTier::Rhodium,
-        CertificationTier::Gold,
-        CertificationTier::Silver,
-        CertificationTier::Bronze,
-    ];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::{Headers, RepoMetadata as PlatformMetadata};
+    use crate::events::RepoEvent;
+    use crate::RsrError;
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    /// Platform serving a fixed tree, recording the files downloaded
+    struct Tree {
+        files: BTreeMap<&'static str, &'static str>,
+        fetched: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl PlatformAdapter for Tree {
+        fn platform_id(&self) -> &'static str {
+            "tree"
+        }
+
+        fn verify_webhook(&self, _payload: &[u8], _headers: &Headers) -> Result<bool> {
+            Ok(false)
+        }
+
+        fn parse_webhook(&self, _payload: &[u8], _headers: &Headers) -> Result<RepoEvent> {
+            Err(RsrError::Platform("no webhooks".to_string()))
+        }
+
+        async fn post_status(&self, _repo: &RepoRef, _commit_sha: &str, _status: &ComplianceStatus) -> Result<()> {
+            Ok(())
+        }
 
-    for tier in tiers {
-        let tier_checks: Vec<_> = results.iter().filter(|r| r.tier <= tier).collect();
-        if tier_checks.iter().all(|r| r.passed) {
-            return tier;
+        async fn fetch_file(&self, repo: &RepoRef, path: &str) -> Result<Vec<u8>> {
+            self.fetched.lock().unwrap().push(path.to_string());
+            match self.files.get(path) {
+                Some(content) => Ok(content.as_bytes().to_vec()),
+                None => Err(RsrError::RepoNotFound {
+                    owner: repo.owner.clone(),
+                    repo: repo.repo.clone(),
+                }),
+            }
+        }
+
+        async fn list_files(&self, _repo: &RepoRef, _path: Option<&str>) -> Result<Vec<String>> {
+            Ok(self.files.keys().map(|path| path.to_string()).collect())
+        }
+
+        async fn get_metadata(&self, _repo: &RepoRef) -> Result<PlatformMetadata> {
+            Ok(PlatformMetadata {
+                default_branch: "main".to_string(),
+                ..Default::default()
+            })
         }
     }
 
-    CertificationTier::None
-}
+    #[tokio::test]
+    async fn fetch_downloads_only_the_files_checks_read() {
+        let tree = Tree {
+            files: BTreeMap::from([
+                ("LICENSE", "MIT License\n\nPermission is hereby granted, free of charge"),
+                (".gitignore", "target/\n"),
+                (".github/workflows/ci.yml", "on: push\n"),
+                ("src/main.rs", "fn main() {}\n"),
+                ("assets/logo.svg", "<svg/>"),
+            ]),
+            fetched: Mutex::new(Vec::new()),
+        };
+        let engine = ComplianceEngine::new();
+
+        let contents = RepoContents::fetch(&tree, &RepoRef::new("github", "acme", "app"), &engine).await.unwrap();
+
+        let mut fetched = tree.fetched.lock().unwrap().clone();
+        fetched.sort();
+        assert_eq!(fetched, [".github/workflows/ci.yml", ".gitignore", "LICENSE"]);
+        // Files no check reads are still listed
+        assert_eq!(contents.files.len(), 5);
+        let main = contents.files.iter().find(|file| file.path == "src/main.rs").unwrap();
+        assert!(main.content.is_none());
+        let license = contents.files.iter().find(|file| file.path == "LICENSE").unwrap();
+        assert!(license.content.as_deref().is_some_and(|text| text.starts_with("MIT")));
+        assert_eq!(contents.metadata.default_branch, "main");
+    }
+
+    /// Check whose evidence can't be read
+    struct Broken;
+
+    #[async_trait::async_trait]
+    impl ComplianceCheck for Broken {
+        fn id(&self) -> &str {
+            "test.broken"
+        }
+
+        fn name(&self) -> &str {
+            "Broken"
+        }
+
+        fn tier(&self) -> CertificationTier {
+            CertificationTier::Gold
+        }
+
+        async fn check_local(&self, _path: &Path) -> Result<CheckResult> {
+            Err(RsrError::Platform("unreadable".to_string()))
+        }
 
-/// Calculate a compliance score (0.0 - 1.0)
-fn calculate_score(results: &[CheckResult]) -> f32 {
-    if results.is_empty() {
-        return 0.0;
+        async fn check_remote(&self, _contents: &RepoContents) -> Result<CheckResult> {
+            Err(RsrError::Platform("unreadable".to_string()))
+        }
     }
 
-    let passed = results.iter().filter(|r| r.passed).count();
-    passed as f32 / results.len() as f32
+    #[tokio::test]
+    async fn a_check_that_errors_fails_at_its_tier() {
+        let mut engine = ComplianceEngine::new();
+        engine.checks.push(Box::new(Broken));
+
+        let results = engine.run_checks(RepoContext::Remote(&RepoContents::default())).await;
+
+        let broken = results.iter().find(|result| result.id == "test.broken").unwrap();
+        assert!(!broken.passed);
+        assert_eq!(broken.tier, CertificationTier::Gold);
+        assert_eq!(broken.name, "Broken");
+        assert!(broken.message.starts_with("Check failed: "), "{}", broken.message);
+        assert_eq!(Broken.tier_requirement(), CertificationTier::Gold);
+    }
+
+    #[test]
+    fn secrets_are_looked_for_in_configuration_and_key_files() {
+        let engine = ComplianceEngine::new();
+        let secrets = engine.find_check("bronze.no_secrets").unwrap();
+        for path in [".env", ".env.production", "config/app.yaml", "deploy/key.pem", ".npmrc"] {
+            assert!(secrets.reads(path), "{}", path);
+        }
+        for path in ["src/main.rs", "README.md", "assets/logo.svg"] {
+            assert!(!secrets.reads(path), "{}", path);
+        }
+    }
 }
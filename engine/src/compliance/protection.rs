@@ -0,0 +1,283 @@
+//! Branch protection and review policy
+//!
+//! Reads the default branch's protection rule through the platform adapter
+//! and checks what it enforces: that the branch is protected at all, that
+//! force pushes are refused, that pull requests need an approving review and
+//! a passing RSR status, and that commits must be signed. Each requirement
+//! is its own check at the tier it belongs to, so a repository with a basic
+//! rule still earns the lower tiers.
+
+use super::{ComplianceCheck, RepoContents};
+use crate::adapters::{BranchProtection, PlatformAdapter, STATUS_CONTEXT};
+use crate::{CertificationTier, CheckResult, Finding, RepoRef, Result, Severity};
+use std::path::Path;
+
+/// Get the branch protection checks
+pub fn get_checks() -> Vec<Box<dyn ComplianceCheck>> {
+    REQUIREMENTS
+        .iter()
+        .map(|requirement| Box::new(ProtectionCheck(requirement)) as Box<dyn ComplianceCheck>)
+        .collect()
+}
+
+/// Protection rule of a repository's default branch
+#[derive(Debug, Clone)]
+pub struct DefaultBranchProtection {
+    pub branch: String,
+    /// `None` if the branch isn't protected
+    pub rule: Option<BranchProtection>,
+}
+
+impl DefaultBranchProtection {
+    /// Read the protection rule of `branch`, or `None` if the platform can't
+    /// show it
+    pub async fn collect(adapter: &dyn PlatformAdapter, repo: &RepoRef, branch: &str) -> Option<Self> {
+        match adapter.get_branch_protection(repo, branch).await {
+            Ok(rule) => Some(Self {
+                branch: branch.to_string(),
+                rule,
+            }),
+            Err(e) => {
+                tracing::debug!("Couldn't read the branch protection of {}: {}", repo, e);
+                None
+            }
+        }
+    }
+}
+
+/// Something a protection rule should enforce
+struct Requirement {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    tier: CertificationTier,
+    /// Finding code when the rule doesn't enforce it
+    code: &'static str,
+    /// Why the rule falls short, or `None` if it meets the requirement
+    unmet: fn(&BranchProtection) -> Option<String>,
+    /// What a passing rule enforces
+    met: &'static str,
+    remediation: &'static str,
+}
+
+const REQUIREMENTS: [Requirement; 5] = [
+    Requirement {
+        id: "silver.branch_protection",
+        name: "Protected Default Branch",
+        description: "The default branch has a protection rule",
+        tier: CertificationTier::Silver,
+        code: "protection.unprotected",
+        unmet: |_| None,
+        met: "is protected",
+        remediation: "Add a branch protection rule for the default branch",
+    },
+    Requirement {
+        id: "silver.no_force_push",
+        name: "Force Pushes Disabled",
+        description: "The default branch refuses force pushes",
+        tier: CertificationTier::Silver,
+        code: "protection.force_push",
+        unmet: |rule| rule.allows_force_pushes.then(|| "allows force pushes".to_string()),
+        met: "refuses force pushes",
+        remediation: "Disable force pushes in the default branch's protection rule",
+    },
+    Requirement {
+        id: "gold.required_reviews",
+        name: "Required Reviews",
+        description: "Pull requests to the default branch need at least one approving review",
+        tier: CertificationTier::Gold,
+        code: "protection.no_reviews",
+        unmet: |rule| (rule.required_approvals == 0).then(|| "merges without an approving review".to_string()),
+        met: "requires an approving review",
+        remediation: "Require at least one approving review in the default branch's protection rule",
+    },
+    Requirement {
+        id: "gold.required_status_check",
+        name: "Required RSR Status",
+        description: "Pull requests to the default branch need a passing RSR status check",
+        tier: CertificationTier::Gold,
+        code: "protection.status_check",
+        unmet: |rule| {
+            if rule.required_status_checks.iter().any(|context| context == STATUS_CONTEXT) {
+                return None;
+            }
+            if rule.required_status_checks.is_empty() {
+                Some("requires no status checks".to_string())
+            } else {
+                Some(format!("requires {} but not {}", rule.required_status_checks.join(", "), STATUS_CONTEXT))
+            }
+        },
+        met: "requires the RSR status check",
+        remediation: "Add \"RSR / Compliance Check\" to the required status checks of the default branch",
+    },
+    Requirement {
+        id: "rhodium.signed_commits_required",
+        name: "Signed Commits Required",
+        description: "The default branch only accepts signed commits",
+        tier: CertificationTier::Rhodium,
+        code: "protection.unsigned_commits",
+        unmet: |rule| (!rule.requires_signed_commits).then(|| "accepts unsigned commits".to_string()),
+        met: "only accepts signed commits",
+        remediation: "Require signed commits in the default branch's protection rule",
+    },
+];
+
+/// Checks that the default branch's protection rule enforces a requirement
+struct ProtectionCheck(&'static Requirement);
+
+impl ProtectionCheck {
+    fn evaluate(&self, protection: &DefaultBranchProtection) -> CheckResult {
+        let requirement = self.0;
+        let branch = &protection.branch;
+        let unmet = match protection.rule {
+            Some(ref rule) => (requirement.unmet)(rule),
+            None => Some("is not protected".to_string()),
+        };
+
+        let Some(unmet) = unmet else {
+            return CheckResult::from_findings(self, true, format!("{} {}", branch, requirement.met), Vec::new());
+        };
+        let code = match protection.rule {
+            Some(_) => requirement.code,
+            None => "protection.unprotected",
+        };
+        let message = format!("{} {}", branch, unmet);
+        let finding = Finding {
+            code: code.to_string(),
+            severity: Severity::Error,
+            path: None,
+            message: message.clone(),
+            remediation: Some(requirement.remediation.to_string()),
+        };
+        CheckResult::from_findings(self, false, message, vec![finding])
+    }
+}
+
+#[async_trait::async_trait]
+impl ComplianceCheck for ProtectionCheck {
+    fn id(&self) -> &str {
+        self.0.id
+    }
+
+    fn name(&self) -> &str {
+        self.0.name
+    }
+
+    fn description(&self) -> &str {
+        self.0.description
+    }
+
+    fn tier(&self) -> CertificationTier {
+        self.0.tier
+    }
+
+    async fn check_local(&self, _path: &Path) -> Result<CheckResult> {
+        Ok(CheckResult::not_applicable(self, "branch protection is only visible to platform scans"))
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        match contents.protection {
+            Some(ref protection) => Ok(self.evaluate(protection)),
+            None => Ok(CheckResult::not_evaluated(
+                self,
+                "the default branch's protection rule couldn't be read (it needs admin access)",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compliance::NOT_EVALUATED;
+
+    type Weaken = fn(&mut BranchProtection);
+
+    /// A rule meeting every requirement
+    fn strict_rule() -> BranchProtection {
+        BranchProtection {
+            required_approvals: 1,
+            required_status_checks: vec!["ci/build".to_string(), STATUS_CONTEXT.to_string()],
+            allows_force_pushes: false,
+            requires_signed_commits: true,
+        }
+    }
+
+    fn protection(rule: Option<BranchProtection>) -> DefaultBranchProtection {
+        DefaultBranchProtection {
+            branch: "main".to_string(),
+            rule,
+        }
+    }
+
+    /// IDs of the requirements `rule` fails, with their finding codes
+    fn failed(rule: Option<BranchProtection>) -> Vec<(&'static str, String)> {
+        let protection = protection(rule);
+        REQUIREMENTS
+            .iter()
+            .map(|requirement| (requirement.id, ProtectionCheck(requirement).evaluate(&protection)))
+            .filter(|(_, result)| !result.passed)
+            .map(|(id, result)| (id, result.findings[0].code.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn strict_rule_meets_every_requirement() {
+        assert_eq!(failed(Some(strict_rule())), []);
+    }
+
+    #[test]
+    fn unprotected_branch_fails_every_requirement() {
+        let failures = failed(None);
+        assert_eq!(failures.len(), REQUIREMENTS.len());
+        assert!(failures.iter().all(|(_, code)| code == "protection.unprotected"), "{:?}", failures);
+    }
+
+    #[test]
+    fn each_shortfall_fails_only_its_requirement() {
+        // Each weakening of the strict rule, with the requirement it fails and its code
+        let cases: [(Weaken, &str, &str); 4] = [
+            (|rule| rule.allows_force_pushes = true, "silver.no_force_push", "protection.force_push"),
+            (|rule| rule.required_approvals = 0, "gold.required_reviews", "protection.no_reviews"),
+            (
+                |rule| rule.required_status_checks.retain(|context| context != STATUS_CONTEXT),
+                "gold.required_status_check",
+                "protection.status_check",
+            ),
+            (|rule| rule.requires_signed_commits = false, "rhodium.signed_commits_required", "protection.unsigned_commits"),
+        ];
+        for (weaken, id, code) in cases {
+            let mut rule = strict_rule();
+            weaken(&mut rule);
+            assert_eq!(failed(Some(rule)), [(id, code.to_string())]);
+        }
+    }
+
+    #[test]
+    fn status_check_message_names_the_required_contexts() {
+        let rule = BranchProtection {
+            required_status_checks: vec!["ci/build".to_string()],
+            ..strict_rule()
+        };
+        let result = ProtectionCheck(&REQUIREMENTS[3]).evaluate(&protection(Some(rule)));
+        assert_eq!(result.message, format!("main requires ci/build but not {}", STATUS_CONTEXT));
+    }
+
+    #[tokio::test]
+    async fn unreadable_rule_is_not_evaluated() {
+        for requirement in &REQUIREMENTS {
+            let result = ProtectionCheck(requirement).check_remote(&RepoContents::default()).await.unwrap();
+            assert!(!result.passed, "{}", requirement.id);
+            assert_eq!(result.findings[0].code, NOT_EVALUATED);
+        }
+    }
+
+    #[tokio::test]
+    async fn local_scans_do_not_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        for requirement in &REQUIREMENTS {
+            let result = ProtectionCheck(requirement).check_local(dir.path()).await.unwrap();
+            assert!(result.is_not_applicable(), "{}", requirement.id);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Tag and release consistency
+//!
+//! Release discipline means every version tag is published as a release with
+//! notes and artifacts, and every release still has its tag. Tags that were
+//! pushed but never released are the usual drift: the version exists in git,
+//! but users watching releases never hear of it.
+//!
+//! Tags and releases are collected while scanning. The check doesn't apply
+//! to local scans, passes for repositories with nothing released yet, and
+//! fails as not evaluated on platforms that can't list releases.
+
+use super::cadence::CadencePolicy;
+use super::{ComplianceCheck, RepoContents};
+use crate::adapters::{PlatformAdapter, Release};
+use crate::{CertificationTier, CheckResult, RepoRef, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Most tags and releases listed per scan
+pub const LISTING_LIMIT: usize = 100;
+
+fn default_tag_pattern() -> String {
+    r"^v?\d+\.\d+\.\d+([-+].*)?$".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_lookback_releases() -> usize {
+    20
+}
+
+/// What a tenant expects of its releases
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleasePolicy {
+    /// Tags naming a version; other tags aren't expected to be released
+    #[serde(default = "default_tag_pattern")]
+    pub tag_pattern: String,
+    /// Releases must have notes
+    #[serde(default = "default_true")]
+    pub require_notes: bool,
+    /// Releases must have files attached, beyond the generated source archives
+    #[serde(default = "default_true")]
+    pub require_artifacts: bool,
+    /// Latest version tags and releases to verify
+    #[serde(default = "default_lookback_releases")]
+    pub lookback: usize,
+    /// How often projects are expected to release, by maturity
+    #[serde(default)]
+    pub cadence: CadencePolicy,
+}
+
+impl Default for ReleasePolicy {
+    fn default() -> Self {
+        Self {
+            tag_pattern: default_tag_pattern(),
+            require_notes: true,
+            require_artifacts: true,
+            lookback: default_lookback_releases(),
+            cadence: CadencePolicy::default(),
+        }
+    }
+}
+
+/// Tags and releases of a repository, with the policy they are held to
+#[derive(Debug, Clone)]
+pub struct ReleaseHistory {
+    pub policy: ReleasePolicy,
+    /// Newest first, where the platform orders them
+    pub tags: Vec<String>,
+    /// Newest first, drafts included
+    pub releases: Vec<Release>,
+}
+
+impl ReleaseHistory {
+    /// List the repository's tags and releases, or `None` if the platform
+    /// can't list either
+    pub async fn collect(adapter: &dyn PlatformAdapter, repo: &RepoRef, policy: &ReleasePolicy) -> Option<Self> {
+        let listed = async {
+            let tags = adapter.list_tags(repo, LISTING_LIMIT).await?;
+            let releases = adapter.list_releases(repo, LISTING_LIMIT).await?;
+            Result::Ok((tags, releases))
+        };
+        match listed.await {
+            Ok((tags, releases)) => Some(Self {
+                policy: policy.clone(),
+                tags,
+                releases,
+            }),
+            Err(e) => {
+                tracing::debug!("Couldn't list the tags and releases of {}: {}", repo, e);
+                None
+            }
+        }
+    }
+
+    /// Why the history falls short of the policy, one finding per line
+    fn findings(&self, pattern: &Regex) -> Vec<String> {
+        let lookback = self.policy.lookback;
+        let published: Vec<&Release> = self.releases.iter().filter(|release| !release.draft).collect();
+        let mut findings = Vec::new();
+
+        for tag in self.tags.iter().filter(|tag| pattern.is_match(tag)).take(lookback) {
+            if published.iter().any(|release| &release.tag == tag) {
+                continue;
+            }
+            if self.releases.iter().any(|release| &release.tag == tag) {
+                findings.push(format!("{}: release is still a draft", tag));
+            } else {
+                findings.push(format!("{}: tagged but never released", tag));
+            }
+        }
+
+        for release in published.iter().take(lookback) {
+            // A release older than every listed tag may still have its tag
+            let listed_every_tag = self.tags.len() < LISTING_LIMIT;
+            if listed_every_tag && !self.tags.contains(&release.tag) {
+                findings.push(format!("{}: released but the tag is missing", release.tag));
+                continue;
+            }
+            if self.policy.require_notes && release.notes.as_deref().is_none_or(|notes| notes.trim().is_empty()) {
+                findings.push(format!("{}: release has no notes", release.tag));
+            }
+            if self.policy.require_artifacts && release.assets == 0 {
+                findings.push(format!("{}: release has no artifacts", release.tag));
+            }
+        }
+
+        findings
+    }
+}
+
+/// Check that version tags and releases match up
+pub struct ReleaseConsistencyCheck;
+
+#[async_trait::async_trait]
+impl ComplianceCheck for ReleaseConsistencyCheck {
+    fn id(&self) -> &str {
+        "silver.release_consistency"
+    }
+
+    fn name(&self) -> &str {
+        "Tags Match Releases"
+    }
+
+    fn tier(&self) -> CertificationTier {
+        CertificationTier::Silver
+    }
+
+    async fn check_local(&self, _path: &Path) -> Result<CheckResult> {
+        Ok(CheckResult::not_applicable(self, "releases are only compared by platform scans"))
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        let Some(ref history) = contents.releases else {
+            return Ok(CheckResult::not_evaluated(self, "tags and releases couldn't be read"));
+        };
+        let Ok(pattern) = Regex::new(&history.policy.tag_pattern) else {
+            return Ok(CheckResult::with_details(
+                self,
+                false,
+                format!("Invalid tag pattern {:?}", history.policy.tag_pattern),
+                None,
+            ));
+        };
+        if history.releases.is_empty() && !history.tags.iter().any(|tag| pattern.is_match(tag)) {
+            return Ok(CheckResult::with_details(self, true, "No version tags or releases yet".to_string(), None));
+        }
+
+        let findings = history.findings(&pattern);
+        if findings.is_empty() {
+            Ok(CheckResult::with_details(self, true, "Version tags and releases match".to_string(), None))
+        } else {
+            Ok(CheckResult::with_details(
+                self,
+                false,
+                format!("{} tag and release inconsistencies", findings.len()),
+                Some(findings.join("\n")),
+            ))
+        }
+    }
+}
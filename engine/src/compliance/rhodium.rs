@@ -11,6 +11,8 @@ pub fn get_checks() -> Vec<Box<dyn ComplianceCheck>> {
         Box::new(ReproducibleBuildsCheck),
         Box::new(ThreatModelCheck),
         Box::new(SlsaComplianceCheck),
+        Box::new(super::cadence::ReleaseCadenceCheck),
+        Box::new(super::vetting::VettedDependenciesCheck),
     ]
 }
 
@@ -19,11 +21,11 @@ pub fn get_checks() -> Vec<Box<dyn ComplianceCheck>> {
 
 #[async_trait::async_trait]
 impl ComplianceCheck for SbomCheck {
-    fn id(&self) -> &'static str {
+    fn id(&self) -> &str {
         "rhodium.sbom"
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "Software Bill of Materials"
     }
 
@@ -53,6 +55,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                     passed: true,
                     message: format!("Found SBOM: {}", name),
                     details: None,
+                    findings: Vec::new(),
                 });
             }
         }
@@ -71,6 +74,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                                 passed: true,
                                 message: "SBOM generation configured in CI".to_string(),
                                 details: None,
+                                findings: Vec::new(),
                             });
                         }
                     }
@@ -85,6 +89,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
             passed: false,
             message: "No SBOM found".to_string(),
             details: Some("Generate SBOM using CycloneDX or SPDX format".to_string()),
+            findings: Vec::new(),
         })
     }
 
@@ -101,6 +106,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
                     passed: true,
                     message: format!("Found SBOM: {}", file.path),
                     details: None,
+                    findings: Vec::new(),
                 });
             }
         }
@@ -112,28 +118,22 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
             passed: false,
             message: "No SBOM found".to_string(),
             details: None,
+            findings: Vec::new(),
         })
     }
 }
 
-/// Check for reproducible builds configuration
+/// Check for reproducible builds configuration. A hermetic build - a Nix
+/// flake with its `flake.lock`, Nix sources pinned with niv or npins, or Guix
+/// channels pinned in `channels.scm` - passes on its own, so projects that
+/// build only with Nix or Guix don't need a language lockfile. Otherwise a
+/// lockfile and one more indicator are needed.
 pub struct ReproducibleBuildsCheck;
 
-#[async_trait::async_trait]
-impl ComplianceCheck for ReproducibleBuildsCheck {
-    fn id(&self) -> &'static str {
-        "rhodium.reproducible_builds"
-    }
-
-    fn name(&self) -> &'static str {
-        "Reproducible Builds"
-    }
-
-    fn tier(&self) -> CertificationTier {
-        CertificationTier::Rhodium
-    }
-
-    async fn check_local(&self, path: &Path) -> Result<CheckResult> {
+impl ReproducibleBuildsCheck {
+    /// Judge the root files `read` returns the contents of
+    fn evaluate(&self, read: impl Fn(&str) -> Option<String>) -> CheckResult {
+        let hermetic = hermetic_builds(&read);
         let mut indicators = Vec::new();
 
         // Check for lock files (necessary but not sufficient)
@@ -147,101 +147,134 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
             "go.sum",
             "Gemfile.lock",
         ];
-
         for lock in lock_files {
-            if path.join(lock).exists() {
+            if read(lock).is_some() {
                 indicators.push(format!("Lock file: {}", lock));
             }
         }
 
-        // Check for containerized builds
-        let container_files = ["Dockerfile", "Containerfile"];
-        for cf in container_files {
-            if path.join(cf).exists() {
-                // Check if it uses pinned versions
-                if let Ok(content) = std::fs::read_to_string(path.join(cf)) {
-                    if content.contains("@sha256:") || content.contains("AS builder") {
-                        indicators.push("Containerized build with pinned images".to_string());
-                    }
+        // Check for containerized builds using pinned images
+        for cf in ["Dockerfile", "Containerfile"] {
+            if let Some(content) = read(cf) {
+                if content.contains("@sha256:") || content.contains("AS builder") {
+                    indicators.push("Containerized build with pinned images".to_string());
                 }
             }
         }
 
-        // Check for Nix/Bazel (strongly reproducible build systems)
-        if path.join("flake.nix").exists() || path.join("flake.lock").exists() {
-            indicators.push("Nix flake configuration".to_string());
+        // Nix and Guix without pins, and Bazel
+        if read("flake.nix").is_some() && read("flake.lock").is_none() {
+            indicators.push("Nix flake without flake.lock".to_string());
+        }
+        let guix = ["guix.scm", "manifest.scm"].iter().any(|name| read(name).is_some());
+        if guix && read("channels.scm").is_none() {
+            indicators.push("Guix manifest without pinned channels".to_string());
         }
-        if path.join("WORKSPACE").exists() || path.join("MODULE.bazel").exists() {
+        if read("WORKSPACE").is_some() || read("MODULE.bazel").is_some() {
             indicators.push("Bazel build system".to_string());
         }
 
-        // Require at least lock file + one other indicator
-        if indicators.len() >= 2 {
-            Ok(CheckResult {
-                id: self.id().to_string(),
-                name: self.name().to_string(),
-                tier: self.tier(),
-                passed: true,
-                message: "Reproducible build indicators found".to_string(),
-                details: Some(indicators.join("\n")),
-            })
+        if !hermetic.is_empty() {
+            let mut details = hermetic.clone();
+            details.extend(indicators);
+            CheckResult::with_details(
+                self,
+                true,
+                format!("Hermetic build: {}", hermetic.join(", ")),
+                Some(details.join("\n")),
+            )
+        } else if indicators.len() >= 2 {
+            CheckResult::with_details(
+                self,
+                true,
+                "Reproducible build indicators found".to_string(),
+                Some(indicators.join("\n")),
+            )
         } else if !indicators.is_empty() {
-            Ok(CheckResult {
-                id: self.id().to_string(),
-                name: self.name().to_string(),
-                tier: self.tier(),
-                passed: false,
-                message: "Partial reproducibility support".to_string(),
-                details: Some(format!(
-                    "Found: {}\nNeed: pinned containers, Nix, or Bazel",
+            CheckResult::with_details(
+                self,
+                false,
+                "Partial reproducibility support".to_string(),
+                Some(format!(
+                    "Found: {}\nNeed: pinned containers, Bazel, or a locked Nix flake or Guix channels",
                     indicators.join(", ")
                 )),
-            })
+            )
         } else {
-            Ok(CheckResult {
-                id: self.id().to_string(),
-                name: self.name().to_string(),
-                tier: self.tier(),
-                passed: false,
-                message: "No reproducible build configuration".to_string(),
-                details: Some("Add lock files and consider Nix/Bazel for full reproducibility".to_string()),
-            })
+            CheckResult::with_details(
+                self,
+                false,
+                "No reproducible build configuration".to_string(),
+                Some("Add lock files and consider Nix/Guix/Bazel for full reproducibility".to_string()),
+            )
         }
     }
+}
 
-    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
-        let reproducible_patterns = ["cargo.lock", "package-lock", "yarn.lock", "flake.nix", "bazel"];
-
-        let found: Vec<_> = contents
-            .files
+/// Hermetic build configurations among the root files `read` returns: those
+/// that pin every input they build from
+fn hermetic_builds(read: &impl Fn(&str) -> Option<String>) -> Vec<String> {
+    // Inputs of `registry` that a lockfile pins to a revision
+    let pinned = |lockfile: &str, registry: &str| {
+        crate::deps::parse(|name| if name == lockfile { read(name) } else { None })
             .iter()
-            .filter(|f| {
-                let path_lower = f.path.to_lowercase();
-                reproducible_patterns.iter().any(|p| path_lower.contains(p))
-            })
-            .map(|f| f.path.as_str())
-            .collect();
-
-        if found.len() >= 2 {
-            Ok(CheckResult {
-                id: self.id().to_string(),
-                name: self.name().to_string(),
-                tier: self.tier(),
-                passed: true,
-                message: "Reproducible build configuration found".to_string(),
-                details: Some(found.join(", ")),
-            })
-        } else {
-            Ok(CheckResult {
-                id: self.id().to_string(),
-                name: self.name().to_string(),
-                tier: self.tier(),
-                passed: false,
-                message: "Insufficient reproducible build configuration".to_string(),
-                details: None,
-            })
+            .filter(|dependency| dependency.registry == registry && !dependency.version.is_empty())
+            .count()
+    };
+
+    let mut found = Vec::new();
+    if read("flake.nix").is_some() {
+        match pinned("flake.lock", crate::deps::NIX) {
+            0 => {}
+            n => found.push(format!("Nix flake with {} locked inputs", n)),
+        }
+    }
+    let nix_expressions = ["default.nix", "shell.nix"].iter().any(|name| read(name).is_some());
+    let nix_pins = ["nix/sources.json", "npins/sources.json"].iter().any(|name| read(name).is_some());
+    if nix_expressions && nix_pins {
+        found.push("Nix with sources pinned by niv or npins".to_string());
+    }
+    if ["guix.scm", "manifest.scm"].iter().any(|name| read(name).is_some()) {
+        match pinned("channels.scm", crate::deps::GUIX) {
+            0 => {}
+            n => found.push(format!("Guix with {} channels pinned in channels.scm", n)),
         }
     }
+    found
+}
+
+#[async_trait::async_trait]
+impl ComplianceCheck for ReproducibleBuildsCheck {
+    fn id(&self) -> &str {
+        "rhodium.reproducible_builds"
+    }
+
+    fn name(&self) -> &str {
+        "Reproducible Builds"
+    }
+
+    fn tier(&self) -> CertificationTier {
+        CertificationTier::Rhodium
+    }
+
+    fn reads(&self, path: &str) -> bool {
+        // Other root files count by their presence alone
+        matches!(path, "Dockerfile" | "Containerfile" | "flake.lock" | "channels.scm")
+    }
+
+    async fn check_local(&self, path: &Path) -> Result<CheckResult> {
+        Ok(self.evaluate(|name| std::fs::read_to_string(path.join(name)).ok()))
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        Ok(self.evaluate(|name| {
+            contents
+                .files
+                .iter()
+                .find(|file| file.path == name)
+                .map(|file| file.content.clone().unwrap_or_default())
+        }))
+    }
 }
 
 /// Check for threat model documentation
@@ -249,11 +282,11 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
 
 #[async_trait::async_trait]
 impl ComplianceCheck for ThreatModelCheck {
-    fn id(&self) -> &'static str {
+    fn id(&self) -> &str {
         "rhodium.threat_model"
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "Threat Model"
     }
 
@@ -280,6 +313,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                     passed: true,
                     message: format!("Found threat model: {}", name),
                     details: None,
+                    findings: Vec::new(),
                 });
             }
         }
@@ -297,6 +331,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                         passed: true,
                         message: "Threat model found in SECURITY.md".to_string(),
                         details: None,
+                        findings: Vec::new(),
                     });
                 }
             }
@@ -309,6 +344,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
             passed: false,
             message: "No threat model documentation".to_string(),
             details: Some("Add THREAT_MODEL.md documenting security analysis".to_string()),
+            findings: Vec::new(),
         })
     }
 
@@ -325,6 +361,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
                     passed: true,
                     message: format!("Found threat model: {}", file.path),
                     details: None,
+                    findings: Vec::new(),
                 });
             }
         }
@@ -336,20 +373,22 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
             passed: false,
             message: "No threat model documentation".to_string(),
             details: None,
+            findings: Vec::new(),
         })
     }
 }
 
-/// Check for SLSA compliance
+/// Check for SLSA compliance. Remote scans look for the SLSA generators in
+/// CI pipelines.
 pub struct SlsaComplianceCheck;
 
 #[async_trait::async_trait]
 impl ComplianceCheck for SlsaComplianceCheck {
-    fn id(&self) -> &'static str {
+    fn id(&self) -> &str {
         "rhodium.slsa"
     }
 
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "SLSA Compliance"
     }
 
@@ -357,6 +396,10 @@ fn tier(&self) -> CertificationTier {
         CertificationTier::Rhodium
     }
 
+    fn reads(&self, path: &str) -> bool {
+        super::ci::is_pipeline(path)
+    }
+
     async fn check_local(&self, path: &Path) -> Result<CheckResult> {
         // Check for SLSA provenance generation in CI
         let ci_path = path.join(".github/workflows");
@@ -376,6 +419,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                                 passed: true,
                                 message: "SLSA provenance generation configured".to_string(),
                                 details: None,
+                                findings: Vec::new(),
                             });
                         }
                     }
@@ -392,6 +436,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
                 passed: true,
                 message: "SLSA attestations directory found".to_string(),
                 details: None,
+                findings: Vec::new(),
             });
         }
 
@@ -402,6 +447,7 @@ async fn check_local(&self, path: &Path) -> Result<CheckResult> {
             passed: false,
             message: "No SLSA compliance detected".to_string(),
             details: Some("Configure SLSA provenance generation (Level 2+)".to_string()),
+            findings: Vec::new(),
         })
     }
 
@@ -417,6 +463,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
                         passed: true,
                         message: "SLSA configuration found".to_string(),
                         details: Some(format!("In: {}", file.path)),
+                        findings: Vec::new(),
                     });
                 }
             }
@@ -429,6 +476,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
                     passed: true,
                     message: format!("SLSA-related file found: {}", file.path),
                     details: None,
+                    findings: Vec::new(),
                 });
             }
         }
@@ -440,6 +488,7 @@ async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
             passed: false,
             message: "No SLSA compliance detected".to_string(),
             details: None,
+            findings: Vec::new(),
         })
     }
 }
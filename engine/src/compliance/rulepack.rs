@@ -0,0 +1,642 @@
+//! Signed rulepacks - checks shipped outside engine releases
+//!
+//! A rulepack is a versioned TOML file of declarative checks, a policy
+//! fragment and remediation templates, published by the RSR standard body to
+//! a registry at `{registry}/{name}/{version}/rulepack.toml` with a detached
+//! Ed25519 signature alongside it (`rulepack.toml.sig`, base64). Packs are
+//! verified against trusted keys both when installed and when loaded, so a
+//! tampered file on disk is never run.
+//!
+//! Every status records the standard versions it was checked against.
+//! During a transition a repository can be pinned to older packs until a
+//! deadline, after which it is checked against the current ones.
+//!
+//! ```toml
+//! name = "rsr-core"
+//! version = "2025.1"
+//!
+//! [[checks]]
+//! id = "silver.codeowners"
+//! name = "Code Owners"
+//! tier = "silver"
+//! kind = "file_exists"
+//! paths = ["CODEOWNERS", ".github/CODEOWNERS"]
+//! remediation = "Add one of {paths} listing who reviews changes"
+//!
+//! [policy]
+//! retire = ["silver.contributing"]
+//! ```
+
+use super::catalog::{PinnedRepository, StandardCatalog};
+use super::sandbox::{Sandbox, SandboxLimits};
+use super::{ComplianceCheck, ComplianceEngine, RepoContents};
+use crate::adapters::http::{HttpLayer, RetryPolicy, SendVia};
+use crate::{CertificationTier, CheckResult, RepoRef, Result, RsrError};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// File name of a pack inside its installed directory
+const RULEPACK_FILE: &str = "rulepack.toml";
+
+/// File name of a pack's detached signature
+const SIGNATURE_FILE: &str = "rulepack.toml.sig";
+
+/// Memory a compiled `file_matches` pattern may use, so a pack can't ship a
+/// pattern that exhausts a worker's memory on the first file it scans
+const PATTERN_SIZE_LIMIT: usize = 1024 * 1024;
+
+/// Where rulepacks come from and which are loaded
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RulepackConfig {
+    /// Registry base URL packs are installed from
+    pub registry: Option<String>,
+    /// Local directory installed packs live in
+    #[serde(default = "default_directory")]
+    pub directory: PathBuf,
+    /// Base64 Ed25519 public keys packs must be signed with
+    #[serde(default)]
+    pub trusted_keys: Vec<String>,
+    /// Packs to load, as `name@version`
+    #[serde(default)]
+    pub packs: Vec<String>,
+    /// Repositories (`platform:owner/repo`) held on other packs while they
+    /// move to the current standard
+    #[serde(default)]
+    pub pins: HashMap<String, StandardPin>,
+    /// Limits pack checks run under
+    #[serde(default)]
+    pub sandbox: SandboxLimits,
+}
+
+/// A repository's pinned standard for a transition period
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StandardPin {
+    /// Packs to check against instead of `packs`, as `name@version`
+    pub packs: Vec<String>,
+    /// End of the transition; the repository moves to `packs` afterwards
+    pub until: DateTime<Utc>,
+}
+
+fn default_directory() -> PathBuf {
+    PathBuf::from(".rsr/rulepacks")
+}
+
+impl RulepackConfig {
+    /// Verify and load `specs` (`name@version`) from `directory`
+    pub fn load(&self, specs: &[String]) -> Result<Vec<Rulepack>> {
+        specs
+            .iter()
+            .map(|spec| {
+                let (name, version) = parse_spec(spec)?;
+                Rulepack::load(&self.directory, name, version, &self.trusted_keys)
+            })
+            .collect()
+    }
+
+    /// Packs that apply to `repo` at `now`
+    pub fn packs_for(&self, repo: &RepoRef, now: DateTime<Utc>) -> &[String] {
+        match self.pins.get(&pin_key(repo)) {
+            Some(pin) if pin.until > now => &pin.packs,
+            _ => &self.packs,
+        }
+    }
+}
+
+/// Key a repository is pinned under
+pub fn pin_key(repo: &RepoRef) -> String {
+    format!("{}:{}/{}", repo.platform, repo.owner, repo.repo)
+}
+
+/// Engines for the current standard and each distinct pinned one, loaded and
+/// verified together so a bad pin fails at startup rather than mid-scan
+#[derive(Default)]
+pub struct StandardEngines {
+    config: RulepackConfig,
+    current: ComplianceEngine,
+    pinned: HashMap<Vec<String>, ComplianceEngine>,
+}
+
+impl StandardEngines {
+    pub fn from_config(config: &RulepackConfig) -> Result<Self> {
+        // One sandbox for all of them, so pinned scans share the concurrency limit
+        let sandbox = Sandbox::new(config.sandbox.clone());
+
+        let mut pinned = HashMap::new();
+        for pin in config.pins.values() {
+            if pin.packs != config.packs && !pinned.contains_key(&pin.packs) {
+                let engine = ComplianceEngine::with_pack_specs(config, &pin.packs)?.with_sandbox(sandbox.clone());
+                pinned.insert(pin.packs.clone(), engine);
+            }
+        }
+
+        Ok(Self {
+            config: config.clone(),
+            current: ComplianceEngine::with_rulepacks(config)?.with_sandbox(sandbox),
+            pinned,
+        })
+    }
+
+    /// The current standard's engine
+    pub fn current(&self) -> &ComplianceEngine {
+        &self.current
+    }
+
+    /// Engine for the standard `repo` is held on, if pinned, else the current one
+    pub fn engine_for(&self, repo: &RepoRef) -> &ComplianceEngine {
+        let packs = self.config.packs_for(repo, Utc::now());
+        self.pinned.get(packs).unwrap_or(&self.current)
+    }
+
+    /// The current standard's catalog, then each pinned one's with the
+    /// repositories held on it
+    pub fn catalog(&self) -> Vec<StandardCatalog> {
+        let mut pinned: Vec<(&Vec<String>, StandardCatalog)> = self
+            .pinned
+            .iter()
+            .map(|(packs, engine)| (packs, engine.catalog()))
+            .collect();
+        pinned.sort_by_key(|(packs, _)| *packs);
+
+        for (repo, pin) in &self.config.pins {
+            if let Some((_, catalog)) = pinned.iter_mut().find(|(packs, _)| **packs == pin.packs) {
+                catalog.pinned.push(PinnedRepository {
+                    repo: repo.clone(),
+                    until: pin.until,
+                });
+            }
+        }
+
+        std::iter::once(self.current.catalog())
+            .chain(pinned.into_iter().map(|(_, mut catalog)| {
+                catalog.pinned.sort_by(|a, b| a.repo.cmp(&b.repo));
+                catalog
+            }))
+            .collect()
+    }
+}
+
+/// Split `name@version`
+pub fn parse_spec(spec: &str) -> Result<(&str, &str)> {
+    match spec.split_once('@') {
+        Some((name, version)) if valid_segment(name) && valid_segment(version) => Ok((name, version)),
+        _ => Err(RsrError::Config(format!("Invalid rulepack {}: expected name@version", spec))),
+    }
+}
+
+/// Names and versions become path segments, so keep them tame
+fn valid_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && !segment.starts_with('.')
+        && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+'))
+}
+
+/// A parsed, verified rulepack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rulepack {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub checks: Vec<RuleDefinition>,
+    #[serde(default)]
+    pub policy: PolicyFragment,
+    /// How the pack was verified; set once its signature checks out
+    #[serde(skip)]
+    pub provenance: Option<PackProvenance>,
+}
+
+/// What a pack was verified against, for auditing which rules ran
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackProvenance {
+    /// Hex SHA-256 of the pack file
+    pub sha256: String,
+    /// Detached signature, base64
+    pub signature: String,
+    /// Trusted key that verified the signature, base64
+    pub signed_by: String,
+}
+
+/// A declarative check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleDefinition {
+    /// Check id - replaces a built-in check with the same id
+    pub id: String,
+    pub name: String,
+    pub tier: CertificationTier,
+    #[serde(flatten)]
+    pub rule: Rule,
+    /// Shown when the check fails; `{paths}` and `{pattern}` are substituted
+    #[serde(default)]
+    pub remediation: Option<String>,
+}
+
+/// What a declarative check looks for. Paths are glob patterns relative to
+/// the repository root.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Rule {
+    /// At least one file matches `paths`
+    FileExists { paths: Vec<String> },
+    /// No file matches `paths`
+    FileAbsent { paths: Vec<String> },
+    /// A file matching `paths` has content matching the regex `pattern`
+    FileMatches { paths: Vec<String>, pattern: String },
+}
+
+impl Rule {
+    fn paths(&self) -> &[String] {
+        match self {
+            Self::FileExists { paths } | Self::FileAbsent { paths } | Self::FileMatches { paths, .. } => paths,
+        }
+    }
+}
+
+/// Scoring changes a pack makes when loaded
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyFragment {
+    /// Check ids the pack supersedes; they are removed from the engine
+    #[serde(default)]
+    pub retire: Vec<String>,
+    /// Score weight per tier
+    #[serde(default)]
+    pub tier_weights: BTreeMap<CertificationTier, f32>,
+}
+
+impl Rulepack {
+    /// Verify `content` against `signature` (base64) and parse it
+    pub fn verify_and_parse(content: &[u8], signature: &str, trusted_keys: &[String]) -> Result<Self> {
+        let signed_by = signing_key(content, signature, trusted_keys)?;
+
+        let text = std::str::from_utf8(content)
+            .map_err(|_| RsrError::Config("Rulepack is not valid UTF-8".to_string()))?;
+        let mut pack: Self =
+            toml::from_str(text).map_err(|e| RsrError::Config(format!("Invalid rulepack: {}", e)))?;
+        pack.validate()?;
+
+        pack.provenance = Some(PackProvenance {
+            sha256: hex::encode(Sha256::digest(content)),
+            signature: signature.trim().to_string(),
+            signed_by: signed_by.trim().to_string(),
+        });
+
+        Ok(pack)
+    }
+
+    /// Load an installed pack from `{directory}/{name}/{version}/`,
+    /// verifying its signature first
+    pub fn load(directory: &Path, name: &str, version: &str, trusted_keys: &[String]) -> Result<Self> {
+        let dir = directory.join(name).join(version);
+        let content = std::fs::read(dir.join(RULEPACK_FILE))?;
+        let signature = std::fs::read_to_string(dir.join(SIGNATURE_FILE))?;
+
+        let pack = Self::verify_and_parse(&content, &signature, trusted_keys)
+            .map_err(|e| RsrError::Config(format!("Rulepack {}@{}: {}", name, version, e)))?;
+        pack.expect_identity(name, version)?;
+
+        Ok(pack)
+    }
+
+    /// Declarative checks as engine checks
+    pub fn checks(&self) -> Vec<Box<dyn ComplianceCheck>> {
+        self.checks
+            .iter()
+            .map(|definition| Box::new(RulepackCheck::new(definition.clone())) as Box<dyn ComplianceCheck>)
+            .collect()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !valid_segment(&self.name) || !valid_segment(&self.version) {
+            return Err(RsrError::Config("Rulepack name and version must be simple path segments".to_string()));
+        }
+
+        let mut ids = HashSet::new();
+        for definition in &self.checks {
+            if !ids.insert(definition.id.as_str()) {
+                return Err(RsrError::Config(format!("Duplicate check id {}", definition.id)));
+            }
+            if definition.rule.paths().is_empty() {
+                return Err(RsrError::Config(format!("Check {} has no paths", definition.id)));
+            }
+            for path in definition.rule.paths() {
+                glob::Pattern::new(path)
+                    .map_err(|e| RsrError::Config(format!("Check {}: invalid path {}: {}", definition.id, path, e)))?;
+            }
+            if let Rule::FileMatches { ref pattern, .. } = definition.rule {
+                compile_pattern(pattern)
+                    .map_err(|e| RsrError::Config(format!("Check {}: invalid pattern: {}", definition.id, e)))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A validly signed pack can still be the wrong one, e.g. an older
+    /// release served in place of the requested version
+    fn expect_identity(&self, name: &str, version: &str) -> Result<()> {
+        if self.name != name || self.version != version {
+            return Err(RsrError::Config(format!(
+                "Expected rulepack {}@{}, got {}@{}",
+                name, version, self.name, self.version
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Check `signature` (base64 Ed25519) over `content` against each trusted key
+pub fn verify_signature(content: &[u8], signature: &str, trusted_keys: &[String]) -> Result<()> {
+    signing_key(content, signature, trusted_keys).map(|_| ())
+}
+
+/// The trusted key `signature` (base64 Ed25519) over `content` was made with
+pub fn signing_key<'k>(content: &[u8], signature: &str, trusted_keys: &'k [String]) -> Result<&'k str> {
+    if trusted_keys.is_empty() {
+        return Err(RsrError::InsecureConfiguration(
+            "no trusted keys configured for rulepacks".to_string(),
+        ));
+    }
+
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let signature: [u8; 64] = b64
+        .decode(signature.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| RsrError::Config("Rulepack signature must be a base64 Ed25519 signature".to_string()))?;
+    let signature = ed25519_dalek::Signature::from_bytes(&signature);
+
+    for encoded in trusted_keys {
+        let key: [u8; 32] = b64
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| RsrError::Config("Trusted rulepack keys must be base64 Ed25519 public keys".to_string()))?;
+        let key = ed25519_dalek::VerifyingKey::from_bytes(&key)
+            .map_err(|e| RsrError::Config(format!("Invalid trusted rulepack key: {}", e)))?;
+
+        if key.verify_strict(content, &signature).is_ok() {
+            return Ok(encoded.as_str());
+        }
+    }
+
+    Err(RsrError::Config("Rulepack signature does not match any trusted key".to_string()))
+}
+
+/// Downloads packs from a registry
+pub struct RulepackRegistry {
+    client: reqwest::Client,
+    http: HttpLayer,
+    url: reqwest::Url,
+}
+
+impl RulepackRegistry {
+    pub fn new(url: &str) -> Result<Self> {
+        // Joining relative paths needs a trailing slash on the base
+        let mut url = reqwest::Url::parse(url)
+            .map_err(|e| RsrError::Config(format!("Invalid rulepack registry: {}", e)))?;
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            http: HttpLayer::new("rulepack-registry", RetryPolicy::default()),
+            url,
+        })
+    }
+
+    /// Download `name@version`, verify it and write it under `directory`,
+    /// returning the installed pack. Nothing is written if verification fails.
+    pub async fn install(&self, name: &str, version: &str, directory: &Path, trusted_keys: &[String]) -> Result<Rulepack> {
+        parse_spec(&format!("{}@{}", name, version))?;
+
+        let content = self.fetch(name, version, RULEPACK_FILE).await?;
+        let signature = self.fetch(name, version, SIGNATURE_FILE).await?;
+        let signature = String::from_utf8(signature)
+            .map_err(|_| RsrError::Config("Rulepack signature is not valid UTF-8".to_string()))?;
+
+        let pack = Rulepack::verify_and_parse(&content, &signature, trusted_keys)?;
+        pack.expect_identity(name, version)?;
+
+        let dir = directory.join(name).join(version);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join(RULEPACK_FILE), &content)?;
+        std::fs::write(dir.join(SIGNATURE_FILE), signature)?;
+
+        tracing::info!("Installed rulepack {}@{} ({} checks)", name, version, pack.checks.len());
+        Ok(pack)
+    }
+
+    async fn fetch(&self, name: &str, version: &str, file: &str) -> Result<Vec<u8>> {
+        let url = self
+            .url
+            .join(&format!("{}/{}/{}", name, version, file))
+            .map_err(|e| RsrError::Config(format!("Invalid rulepack URL: {}", e)))?;
+
+        let response = self.client.get(url).send_via(&self.http).await?;
+        if !response.status().is_success() {
+            return Err(RsrError::Platform(format!(
+                "Fetching {} for rulepack {}@{} failed: {}",
+                file,
+                name,
+                version,
+                response.status()
+            )));
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+/// Compile a `file_matches` pattern within [`PATTERN_SIZE_LIMIT`]
+fn compile_pattern(pattern: &str) -> std::result::Result<regex::Regex, regex::Error> {
+    regex::RegexBuilder::new(pattern)
+        .size_limit(PATTERN_SIZE_LIMIT)
+        .dfa_size_limit(PATTERN_SIZE_LIMIT)
+        .build()
+}
+
+/// A check defined by a rulepack
+pub struct RulepackCheck {
+    definition: RuleDefinition,
+    paths: Vec<glob::Pattern>,
+    pattern: Option<regex::Regex>,
+}
+
+impl RulepackCheck {
+    /// Build a check; definitions from [`Rulepack`] are already validated,
+    /// so invalid globs or patterns simply never match
+    pub fn new(definition: RuleDefinition) -> Self {
+        let paths = definition
+            .rule
+            .paths()
+            .iter()
+            .filter_map(|path| glob::Pattern::new(path).ok())
+            .collect();
+        let pattern = match definition.rule {
+            Rule::FileMatches { ref pattern, .. } => compile_pattern(pattern).ok(),
+            _ => None,
+        };
+
+        Self {
+            definition,
+            paths,
+            pattern,
+        }
+    }
+
+    fn matches_path(&self, path: &str) -> bool {
+        self.paths.iter().any(|pattern| pattern.matches(path))
+    }
+
+    fn evaluate(&self, contents: &RepoContents) -> CheckResult {
+        let matching: Vec<_> = contents.files.iter().filter(|f| self.matches_path(&f.path)).collect();
+
+        let (passed, message) = match self.definition.rule {
+            Rule::FileExists { .. } => match matching.first() {
+                Some(file) => (true, format!("Found {}", file.path)),
+                None => (false, "No matching file found".to_string()),
+            },
+            Rule::FileAbsent { .. } => match matching.first() {
+                Some(file) => (false, format!("Found {}", file.path)),
+                None => (true, "No matching file present".to_string()),
+            },
+            Rule::FileMatches { .. } => {
+                let found = matching.iter().find(|file| {
+                    matches!((&self.pattern, &file.content), (Some(pattern), Some(content)) if pattern.is_match(content))
+                });
+                match found {
+                    Some(file) => (true, format!("{} contains the required content", file.path)),
+                    None if matching.is_empty() => (false, "No matching file found".to_string()),
+                    None => (false, "Required content not found".to_string()),
+                }
+            }
+        };
+
+        CheckResult {
+            id: self.definition.id.clone(),
+            name: self.definition.name.clone(),
+            tier: self.definition.tier,
+            passed,
+            message,
+            details: if passed { None } else { self.remediation() },
+            findings: Vec::new(),
+        }
+    }
+
+    fn remediation(&self) -> Option<String> {
+        let template = self.definition.remediation.as_ref()?;
+        let pattern = match self.definition.rule {
+            Rule::FileMatches { ref pattern, .. } => pattern.as_str(),
+            _ => "",
+        };
+        Some(
+            template
+                .replace("{paths}", &self.definition.rule.paths().join(", "))
+                .replace("{pattern}", pattern),
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl ComplianceCheck for RulepackCheck {
+    fn id(&self) -> &str {
+        &self.definition.id
+    }
+
+    fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    fn tier(&self) -> CertificationTier {
+        self.definition.tier
+    }
+
+    async fn check_local(&self, path: &Path) -> Result<CheckResult> {
+        Ok(self.evaluate(&RepoContents::from_dir(path)?))
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        Ok(self.evaluate(contents))
+    }
+
+    fn reads(&self, path: &str) -> bool {
+        matches!(self.definition.rule, Rule::FileMatches { .. }) && self.matches_path(path)
+    }
+
+    fn sandboxed(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Signer;
+
+    const PACK: &str = r#"
+name = "rsr-core"
+version = "2025.1"
+
+[[checks]]
+id = "silver.codeowners"
+name = "Code Owners"
+tier = "silver"
+kind = "file_exists"
+paths = ["CODEOWNERS"]
+"#;
+
+    fn signer() -> (ed25519_dalek::SigningKey, Vec<String>) {
+        let key = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+        let trusted = vec![base64::engine::general_purpose::STANDARD.encode(key.verifying_key().as_bytes())];
+        (key, trusted)
+    }
+
+    fn sign(key: &ed25519_dalek::SigningKey, content: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(key.sign(content).to_bytes())
+    }
+
+    #[test]
+    fn signed_pack_is_parsed_with_its_provenance() {
+        let (key, trusted) = signer();
+        let signature = sign(&key, PACK.as_bytes());
+
+        let pack = Rulepack::verify_and_parse(PACK.as_bytes(), &signature, &trusted).unwrap();
+        assert_eq!((pack.name.as_str(), pack.version.as_str()), ("rsr-core", "2025.1"));
+        assert_eq!(pack.provenance.unwrap().signed_by, trusted[0]);
+    }
+
+    #[test]
+    fn unsigned_pack_is_rejected() {
+        let (_, trusted) = signer();
+        assert!(Rulepack::verify_and_parse(PACK.as_bytes(), "", &trusted).is_err());
+        assert!(Rulepack::verify_and_parse(PACK.as_bytes(), "not base64!", &trusted).is_err());
+    }
+
+    #[test]
+    fn tampered_pack_is_rejected() {
+        let (key, trusted) = signer();
+        let signature = sign(&key, PACK.as_bytes());
+
+        let tampered = PACK.replace("CODEOWNERS", "README.md");
+        let err = Rulepack::verify_and_parse(tampered.as_bytes(), &signature, &trusted).unwrap_err();
+        assert!(err.to_string().contains("does not match any trusted key"), "{}", err);
+    }
+
+    #[test]
+    fn pack_signed_by_an_untrusted_key_is_rejected() {
+        let (_, trusted) = signer();
+        let other = ed25519_dalek::SigningKey::from_bytes(&[9; 32]);
+        let signature = sign(&other, PACK.as_bytes());
+
+        assert!(Rulepack::verify_and_parse(PACK.as_bytes(), &signature, &trusted).is_err());
+        assert!(matches!(
+            Rulepack::verify_and_parse(PACK.as_bytes(), &signature, &[]),
+            Err(RsrError::InsecureConfiguration(_))
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+//! Resource limits for checks the engine doesn't ship
+//!
+//! Built-in checks are trusted. Checks that report themselves as sandboxed -
+//! rulepack checks today, plugins later - run under [`SandboxLimits`]:
+//!
+//! - a repository with more files than `max_files`, or more file content
+//!   than `max_content_bytes`, is not handed to the check at all
+//! - at most `max_concurrent` sandboxed checks run at once across every scan
+//!   sharing the engine, so a slow one can't tie up a worker's scans
+//! - a check that doesn't finish within `timeout_secs`, or panics, fails
+//!   instead of taking its scan down
+//!
+//! A limit a check hits fails that check only; the rest of the scan runs.
+//! The timeout can only stop a check at an await point, so checks doing
+//! long synchronous work are held to the file and content limits instead.
+//!
+//! What a check allocates is not bounded: the content limit caps its input,
+//! not its memory, and a check that exhausts memory aborts the process,
+//! which `catch_unwind` can't contain. Sandboxed checks are declarative
+//! rulepack rules today, whose allocations follow from their input.
+
+use super::{ComplianceCheck, RepoContents, RepoContext};
+use crate::{CheckResult, Result, RsrError};
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Limits sandboxed checks run under
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SandboxLimits {
+    /// Files a repository may have for a sandboxed check to run on it
+    pub max_files: usize,
+    /// File content, in bytes, a sandboxed check may be handed. This bounds
+    /// the input, not what the check allocates.
+    #[serde(alias = "max_memory_bytes")]
+    pub max_content_bytes: u64,
+    /// Sandboxed checks running at once across all scans
+    pub max_concurrent: usize,
+    /// How long one sandboxed check may run
+    pub timeout_secs: u64,
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        Self {
+            max_files: 50_000,
+            max_content_bytes: 256 * 1024 * 1024,
+            max_concurrent: 8,
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl SandboxLimits {
+    /// Problems that make the limits unusable
+    pub fn validate(&self) -> Vec<String> {
+        let limits = [
+            ("max_files", self.max_files as u64),
+            ("max_content_bytes", self.max_content_bytes),
+            ("max_concurrent", self.max_concurrent as u64),
+            ("timeout_secs", self.timeout_secs),
+        ];
+        limits
+            .into_iter()
+            .filter(|(_, limit)| *limit == 0)
+            .map(|(name, _)| format!("rulepacks.sandbox.{}: must be at least 1", name))
+            .collect()
+    }
+
+    /// Fails if a repository of `files` files and `bytes` bytes of content
+    /// is too large to hand to a sandboxed check
+    fn admit(&self, files: usize, bytes: u64) -> Result<()> {
+        if files > self.max_files {
+            return Err(RsrError::Compliance(format!(
+                "repository has {} files, over the sandbox limit of {}",
+                files, self.max_files
+            )));
+        }
+        if bytes > self.max_content_bytes {
+            return Err(RsrError::Compliance(format!(
+                "repository has {} bytes of content, over the sandbox limit of {}",
+                bytes, self.max_content_bytes
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Runs sandboxed checks under shared limits. Clones share the concurrency
+/// limit, so engines for pinned standards draw on the same permits.
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    limits: SandboxLimits,
+    permits: Arc<Semaphore>,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::new(SandboxLimits::default())
+    }
+}
+
+impl Sandbox {
+    pub fn new(limits: SandboxLimits) -> Self {
+        let permits = Arc::new(Semaphore::new(limits.max_concurrent.max(1)));
+        Self { limits, permits }
+    }
+
+    pub fn limits(&self) -> &SandboxLimits {
+        &self.limits
+    }
+
+    /// Run `check` against `ctx`, within the limits if it is sandboxed
+    pub async fn check(&self, check: &dyn ComplianceCheck, ctx: RepoContext<'_>) -> Result<CheckResult> {
+        match ctx {
+            RepoContext::Local(path) => self.check_local(check, path).await,
+            RepoContext::Remote(contents) => self.check_remote(check, contents).await,
+        }
+    }
+
+    /// Run `check` on fetched contents, within the limits if it is sandboxed
+    pub async fn check_remote(&self, check: &dyn ComplianceCheck, contents: &RepoContents) -> Result<CheckResult> {
+        if !check.sandboxed() {
+            return check.check_remote(contents).await;
+        }
+
+        let bytes = contents
+            .files
+            .iter()
+            .filter_map(|file| file.content.as_ref())
+            .map(|content| content.len() as u64)
+            .sum();
+        self.limits.admit(contents.files.len(), bytes)?;
+        self.run(check, check.check_remote(contents)).await
+    }
+
+    /// Run `check` on a local checkout, within the limits if it is
+    /// sandboxed. The checkout is measured, off the async runtime, before
+    /// the check reads any of it.
+    pub async fn check_local(&self, check: &dyn ComplianceCheck, path: &Path) -> Result<CheckResult> {
+        if !check.sandboxed() {
+            return check.check_local(path).await;
+        }
+
+        let root = path.to_path_buf();
+        let (files, bytes) = tokio::task::spawn_blocking(move || measure(&root))
+            .await
+            .map_err(|e| RsrError::Compliance(format!("Measuring the checkout failed: {}", e)))??;
+        self.limits.admit(files, bytes)?;
+        self.run(check, check.check_local(path)).await
+    }
+
+    async fn run(&self, check: &dyn ComplianceCheck, run: impl Future<Output = Result<CheckResult>>) -> Result<CheckResult> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|_| RsrError::Compliance("sandbox is shut down".to_string()))?;
+
+        let timeout = Duration::from_secs(self.limits.timeout_secs);
+        match tokio::time::timeout(timeout, AssertUnwindSafe(run).catch_unwind()).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => {
+                tracing::error!("Sandboxed check {} panicked", check.id());
+                Err(RsrError::Compliance("check panicked".to_string()))
+            }
+            Err(_) => {
+                tracing::warn!("Sandboxed check {} timed out after {}s", check.id(), self.limits.timeout_secs);
+                Err(RsrError::Compliance(format!(
+                    "check exceeded the sandbox time limit of {}s",
+                    self.limits.timeout_secs
+                )))
+            }
+        }
+    }
+}
+
+/// Files in the checkout at `root` and their total size in bytes
+fn measure(root: &Path) -> Result<(usize, u64)> {
+    let mut files = Vec::new();
+    crate::adapters::local::walk_workdir(root, root, &mut files)?;
+    let bytes = files
+        .iter()
+        .filter_map(|file| std::fs::metadata(root.join(file)).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    Ok((files.len(), bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compliance::FileEntry;
+    use crate::CertificationTier;
+
+    /// Sandboxed check that never finishes
+    struct Stuck;
+
+    #[async_trait::async_trait]
+    impl ComplianceCheck for Stuck {
+        fn id(&self) -> &str {
+            "test.stuck"
+        }
+
+        fn name(&self) -> &str {
+            "Stuck"
+        }
+
+        fn tier(&self) -> CertificationTier {
+            CertificationTier::Bronze
+        }
+
+        async fn check_local(&self, _path: &Path) -> Result<CheckResult> {
+            std::future::pending().await
+        }
+
+        async fn check_remote(&self, _contents: &RepoContents) -> Result<CheckResult> {
+            std::future::pending().await
+        }
+
+        fn sandboxed(&self) -> bool {
+            true
+        }
+    }
+
+    fn contents(files: &[&str]) -> RepoContents {
+        RepoContents {
+            files: files
+                .iter()
+                .enumerate()
+                .map(|(i, content)| FileEntry {
+                    path: format!("file{}", i),
+                    content: Some(content.to_string()),
+                    size: content.len() as u64,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn a_check_over_the_time_limit_fails() {
+        let sandbox = Sandbox::new(SandboxLimits {
+            timeout_secs: 1,
+            ..Default::default()
+        });
+
+        let err = sandbox.check_remote(&Stuck, &contents(&["x"])).await.unwrap_err();
+        assert!(err.to_string().contains("time limit of 1s"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn a_repository_over_the_size_limits_is_not_handed_to_the_check() {
+        // The check would never finish, so only the admission check can answer
+        let too_many_files = Sandbox::new(SandboxLimits {
+            max_files: 2,
+            ..Default::default()
+        });
+        let err = too_many_files.check_remote(&Stuck, &contents(&["a", "b", "c"])).await.unwrap_err();
+        assert!(err.to_string().contains("3 files, over the sandbox limit of 2"), "{}", err);
+
+        let too_many_bytes = Sandbox::new(SandboxLimits {
+            max_content_bytes: 8,
+            ..Default::default()
+        });
+        let err = too_many_bytes.check_remote(&Stuck, &contents(&["12345", "6789"])).await.unwrap_err();
+        assert!(err.to_string().contains("9 bytes of content, over the sandbox limit of 8"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn a_checkout_over_the_content_limit_is_not_handed_to_the_check() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("big.txt"), "0123456789").unwrap();
+        let sandbox = Sandbox::new(SandboxLimits {
+            max_content_bytes: 8,
+            ..Default::default()
+        });
+
+        let err = sandbox.check_local(&Stuck, dir.path()).await.unwrap_err();
+        assert!(err.to_string().contains("10 bytes of content, over the sandbox limit of 8"), "{}", err);
+    }
+
+    #[test]
+    fn old_content_limit_name_still_parses() {
+        let limits: SandboxLimits = toml::from_str("max_memory_bytes = 1024").unwrap();
+        assert_eq!(limits.max_content_bytes, 1024);
+    }
+
+    #[test]
+    fn zero_limits_are_rejected() {
+        let limits = SandboxLimits {
+            timeout_secs: 0,
+            ..Default::default()
+        };
+        assert_eq!(limits.validate(), ["rulepacks.sandbox.timeout_secs: must be at least 1"]);
+    }
+}
@@ -0,0 +1,123 @@
+//! Scoring of check results into a score and certification tier
+//!
+//! This is the exact logic the engine uses, exposed so external tools can
+//! score results they gathered themselves.
+
+use crate::{CertificationTier, CheckResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Tiers that can be awarded, highest first
+const AWARDABLE_TIERS: [CertificationTier; 4] = [
+    CertificationTier::Rhodium,
+    CertificationTier::Gold,
+    CertificationTier::Silver,
+    CertificationTier::Bronze,
+];
+
+/// Knobs that affect scoring
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoringPolicy {
+    /// Check ids ignored for both tier and score
+    #[serde(default)]
+    pub excluded_checks: HashSet<String>,
+    /// Score weight per tier (checks of unlisted tiers weigh 1.0)
+    #[serde(default)]
+    pub tier_weights: BTreeMap<CertificationTier, f32>,
+}
+
+impl ScoringPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn exclude_check(mut self, id: impl Into<String>) -> Self {
+        self.excluded_checks.insert(id.into());
+        self
+    }
+
+    pub fn with_tier_weight(mut self, tier: CertificationTier, weight: f32) -> Self {
+        self.tier_weights.insert(tier, weight);
+        self
+    }
+
+    fn weight(&self, tier: CertificationTier) -> f32 {
+        self.tier_weights.get(&tier).copied().unwrap_or(1.0)
+    }
+}
+
+impl From<&crate::config::TierPolicy> for ScoringPolicy {
+    fn from(policy: &crate::config::TierPolicy) -> Self {
+        Self {
+            excluded_checks: policy.disabled_checks.iter().cloned().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Score check results: the weighted pass rate (0.0 - 1.0) and the highest
+/// tier for which every check at or below it passed. Excluded checks and
+/// checks that didn't apply to the scan count for neither.
+pub fn score(checks: &[CheckResult], policy: &ScoringPolicy) -> (f32, CertificationTier) {
+    let counted: Vec<&CheckResult> = checks
+        .iter()
+        .filter(|c| !policy.excluded_checks.contains(&c.id) && !c.is_not_applicable())
+        .collect();
+
+    (weighted_score(&counted, policy), highest_tier(&counted))
+}
+
+fn highest_tier(results: &[&CheckResult]) -> CertificationTier {
+    AWARDABLE_TIERS
+        .into_iter()
+        .find(|&tier| results.iter().filter(|r| r.tier <= tier).all(|r| r.passed))
+        .unwrap_or(CertificationTier::None)
+}
+
+fn weighted_score(results: &[&CheckResult], policy: &ScoringPolicy) -> f32 {
+    let total: f32 = results.iter().map(|r| policy.weight(r.tier)).sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+
+    let passed: f32 = results
+        .iter()
+        .filter(|r| r.passed)
+        .map(|r| policy.weight(r.tier))
+        .sum();
+    passed / total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compliance::flakiness::CiFlakinessCheck;
+    use crate::compliance::{ComplianceCheck, RepoContents};
+
+    fn passing(id: &str, tier: CertificationTier) -> CheckResult {
+        CheckResult {
+            id: id.to_string(),
+            name: id.to_string(),
+            tier,
+            passed: true,
+            message: "ok".to_string(),
+            details: None,
+            findings: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn checks_that_do_not_apply_count_for_neither_tier_nor_score() {
+        // Flakiness is opt-in; without CI runs it doesn't apply
+        let flakiness = CiFlakinessCheck.check_remote(&RepoContents::default()).await.unwrap();
+        assert!(flakiness.is_not_applicable());
+
+        let mut failing = passing("gold.other", CertificationTier::Gold);
+        failing.passed = false;
+        let checks = [passing("bronze.license", CertificationTier::Bronze), failing, flakiness];
+
+        let policy = ScoringPolicy::default();
+        assert_eq!(score(&checks, &policy), (0.5, CertificationTier::Silver));
+        assert_eq!(score(&[checks[0].clone(), checks[2].clone()], &policy), (1.0, CertificationTier::Rhodium));
+    }
+}
@@ -0,0 +1,143 @@
+//! Engine self-certification
+//!
+//! The certification authority requires the engine to meet its own
+//! standard: an installation whose own code fails Bronze may not issue Gold
+//! or Rhodium. The server scans the engine's repository at startup and caps
+//! every tier it issues accordingly; `rsr self-check` runs the same scan in
+//! CI.
+//!
+//! The repository is looked up at `RSR_SELF_SCAN_PATH`, falling back to the
+//! workspace the engine was built from. An installation without its source
+//! can't show that it passes, so it is treated as failing.
+
+use super::ComplianceEngine;
+use crate::{CertificationTier, CheckResult, ComplianceStatus, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the engine's own repository
+pub const SOURCE_ENV: &str = "RSR_SELF_SCAN_PATH";
+
+/// Tier the engine's own repository has to reach
+pub const REQUIRED_TIER: CertificationTier = CertificationTier::Bronze;
+
+/// Highest tier an installation that isn't self-certified may issue
+pub const UNCERTIFIED_CAP: CertificationTier = CertificationTier::Silver;
+
+/// The engine's own repository, if it can be found
+pub fn source_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(SOURCE_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).parent()?;
+    workspace.join("Cargo.toml").is_file().then(|| workspace.to_path_buf())
+}
+
+/// Result of scanning the engine's own repository. The default is an
+/// installation whose source wasn't scanned.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SelfCertification {
+    /// Repository that was scanned; `None` if it couldn't be found
+    pub source: Option<PathBuf>,
+    pub status: Option<ComplianceStatus>,
+}
+
+impl SelfCertification {
+    /// Scan the engine's repository with `engine`, wherever it is found
+    pub async fn run(engine: &ComplianceEngine) -> Result<Self> {
+        match source_path() {
+            Some(source) => Self::run_at(engine, &source).await,
+            None => Ok(Self {
+                source: None,
+                status: None,
+            }),
+        }
+    }
+
+    /// Scan the repository at `source` as the engine's own
+    pub async fn run_at(engine: &ComplianceEngine, source: &Path) -> Result<Self> {
+        if !source.is_dir() {
+            return Err(crate::RsrError::Config(format!(
+                "Engine source {} is not a directory (set {})",
+                source.display(),
+                SOURCE_ENV
+            )));
+        }
+
+        Ok(Self {
+            source: Some(source.to_path_buf()),
+            status: Some(engine.check_local(source).await?),
+        })
+    }
+
+    /// Whether the engine's own repository reaches [`REQUIRED_TIER`]
+    pub fn is_certified(&self) -> bool {
+        self.status.as_ref().is_some_and(|status| status.tier >= REQUIRED_TIER)
+    }
+
+    /// Required checks the engine's own repository fails
+    pub fn failing(&self) -> Vec<&CheckResult> {
+        self.status
+            .iter()
+            .flat_map(|status| &status.checks)
+            .filter(|check| check.tier <= REQUIRED_TIER && !check.passed)
+            .collect()
+    }
+
+    /// Highest tier this installation may issue
+    pub fn max_tier(&self) -> CertificationTier {
+        if self.is_certified() {
+            CertificationTier::Rhodium
+        } else {
+            UNCERTIFIED_CAP
+        }
+    }
+
+    /// Lower `status` to the highest tier this installation may issue.
+    /// Returns whether it was lowered.
+    pub fn cap(&self, status: &mut ComplianceStatus) -> bool {
+        let max = self.max_tier();
+        if status.tier <= max {
+            return false;
+        }
+        tracing::warn!(
+            "Issuing {} instead of {} to {}: the engine's own repository fails {}",
+            max,
+            status.tier,
+            status.repo,
+            REQUIRED_TIER
+        );
+        status.tier = max;
+        true
+    }
+
+    /// Log the outcome, once at startup
+    pub fn log(&self) {
+        match (&self.source, &self.status) {
+            (Some(source), Some(status)) if self.is_certified() => {
+                tracing::info!(
+                    "Engine self-scan of {}: {} ({:.0}%)",
+                    source.display(),
+                    status.tier,
+                    status.score * 100.0
+                );
+            }
+            (Some(source), Some(status)) => {
+                let failing: Vec<&str> = self.failing().iter().map(|check| check.id.as_str()).collect();
+                tracing::error!(
+                    "Engine self-scan of {}: {} fails {} ({}); issuing at most {}",
+                    source.display(),
+                    status.tier,
+                    REQUIRED_TIER,
+                    failing.join(", "),
+                    UNCERTIFIED_CAP
+                );
+            }
+            _ => tracing::error!(
+                "Engine source not found (set {}); issuing at most {}",
+                SOURCE_ENV,
+                UNCERTIFIED_CAP
+            ),
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! Signed history
+//!
+//! Samples the branch's latest commits and the newest tags and scores the
+//! share whose signature verifies. GPG, SSH and X.509 signatures (the kind
+//! Sigstore's gitsign makes) all count. Platform scans rely on the
+//! platform's own verification; local scans ask git, so the result depends
+//! on the keys and allowed signers configured where the scan runs.
+//!
+//! Lightweight tags can't be signed and count against the score like any
+//! other unsigned object. A signature that doesn't verify doesn't count
+//! either, but is reported separately from unsigned history since the fix
+//! is usually publishing the key rather than re-signing. A repository whose
+//! signatures can't be read, or with no history to sample, fails as not
+//! evaluated rather than passing on no evidence.
+
+use super::{ComplianceCheck, RepoContents};
+use crate::adapters::local::LocalRepoAdapter;
+use crate::adapters::{PlatformAdapter, Signature, SignatureFormat, SignedObject};
+use crate::{CertificationTier, CheckResult, Finding, RepoRef, Result, Severity};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+fn default_lookback_commits() -> usize {
+    50
+}
+
+fn default_lookback_tags() -> usize {
+    10
+}
+
+fn default_min_signed_percent() -> u32 {
+    90
+}
+
+/// How much of a repository's history a tenant expects to be signed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningPolicy {
+    /// Latest commits on the branch to sample
+    #[serde(default = "default_lookback_commits")]
+    pub lookback_commits: usize,
+    /// Newest tags to sample
+    #[serde(default = "default_lookback_tags")]
+    pub lookback_tags: usize,
+    /// Lowest share of sampled commits and tags, in percent, with a
+    /// verified signature
+    #[serde(default = "default_min_signed_percent")]
+    pub min_signed_percent: u32,
+}
+
+impl Default for SigningPolicy {
+    fn default() -> Self {
+        Self {
+            lookback_commits: default_lookback_commits(),
+            lookback_tags: default_lookback_tags(),
+            min_signed_percent: default_min_signed_percent(),
+        }
+    }
+}
+
+/// Sampled commits and tags with their signatures
+#[derive(Debug, Clone)]
+pub struct SignedHistory {
+    pub policy: SigningPolicy,
+    /// Newest first
+    pub commits: Vec<SignedObject>,
+    /// Newest first
+    pub tags: Vec<SignedObject>,
+}
+
+impl SignedHistory {
+    /// Read the signatures of the latest commits and tags, or `None` if the
+    /// platform can't show them. A repository without tags still has its
+    /// commits judged.
+    pub async fn collect(adapter: &dyn PlatformAdapter, repo: &RepoRef, policy: &SigningPolicy) -> Option<Self> {
+        let commits = match adapter.commit_signatures(repo, policy.lookback_commits).await {
+            Ok(commits) => commits,
+            Err(e) => {
+                tracing::debug!("Couldn't read the commit signatures of {}: {}", repo, e);
+                return None;
+            }
+        };
+        let tags = adapter
+            .tag_signatures(repo, policy.lookback_tags)
+            .await
+            .inspect_err(|e| tracing::debug!("Couldn't read the tag signatures of {}: {}", repo, e))
+            .unwrap_or_default();
+
+        Some(Self {
+            policy: policy.clone(),
+            commits,
+            tags,
+        })
+    }
+
+    fn sampled(&self) -> impl Iterator<Item = &SignedObject> {
+        self.commits.iter().chain(&self.tags)
+    }
+
+    /// Sampled objects with a verified signature
+    pub fn verified(&self) -> usize {
+        self.sampled()
+            .filter(|object| object.signature.as_ref().is_some_and(|signature| signature.verified))
+            .count()
+    }
+
+    /// Share of sampled objects with a verified signature, in percent
+    pub fn percent(&self) -> Option<f64> {
+        let total = self.commits.len() + self.tags.len();
+        (total > 0).then(|| self.verified() as f64 * 100.0 / total as f64)
+    }
+
+    /// Signed objects by format, e.g. "GPG: 40, SSH: 2"
+    fn formats(&self) -> String {
+        let mut counts: BTreeMap<Option<SignatureFormat>, usize> = BTreeMap::new();
+        for signature in self.sampled().filter_map(|object| object.signature.as_ref()) {
+            *counts.entry(signature.format).or_default() += 1;
+        }
+        counts
+            .into_iter()
+            .map(|(format, count)| format!("{}: {}", format.map_or("unknown", SignatureFormat::label), count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Finding for a `kind` ("commit" or "tag") whose signature doesn't verify
+fn unverified(kind: &str, id: &str, signature: &Signature) -> Finding {
+    Finding {
+        code: "signing.unverified".to_string(),
+        severity: Severity::Warning,
+        path: None,
+        message: format!(
+            "{} {} has a signature that doesn't verify: {}",
+            kind,
+            id,
+            signature.reason.as_deref().unwrap_or("unknown reason")
+        ),
+        remediation: Some("Publish the signing key, or add it to the allowed signers".to_string()),
+    }
+}
+
+/// Check that recent commits and tags carry verified signatures
+pub struct SignedCommitsCheck;
+
+impl SignedCommitsCheck {
+    fn evaluate(&self, history: &SignedHistory) -> CheckResult {
+        let Some(percent) = history.percent() else {
+            return CheckResult::not_evaluated(self, "no commits or tags to verify");
+        };
+        let total = history.commits.len() + history.tags.len();
+        let verified = history.verified();
+
+        let mut findings = vec![Finding {
+            code: "signing.summary".to_string(),
+            severity: Severity::Info,
+            path: None,
+            message: format!(
+                "{} of {} commits and {} of {} tags signed ({})",
+                history.commits.iter().filter(|commit| commit.signature.is_some()).count(),
+                history.commits.len(),
+                history.tags.iter().filter(|tag| tag.signature.is_some()).count(),
+                history.tags.len(),
+                match history.formats() {
+                    formats if formats.is_empty() => "no signatures".to_string(),
+                    formats => formats,
+                }
+            ),
+            remediation: None,
+        }];
+        for commit in &history.commits {
+            if let Some(signature) = commit.signature.as_ref().filter(|signature| !signature.verified) {
+                findings.push(unverified("commit", commit.id.get(..12).unwrap_or(&commit.id), signature));
+            }
+        }
+        for tag in &history.tags {
+            match tag.signature {
+                Some(ref signature) if !signature.verified => findings.push(unverified("tag", &tag.id, signature)),
+                Some(_) => {}
+                None => findings.push(Finding {
+                    code: "signing.unsigned_tag".to_string(),
+                    severity: Severity::Warning,
+                    path: None,
+                    message: format!("tag {} is not signed", tag.id),
+                    remediation: Some("Create release tags with `git tag -s`".to_string()),
+                }),
+            }
+        }
+
+        let minimum = history.policy.min_signed_percent;
+        if percent >= f64::from(minimum) {
+            return CheckResult::from_findings(
+                self,
+                true,
+                format!("{:.0}% of recent history is signed and verified ({} of {})", percent, verified, total),
+                findings,
+            );
+        }
+        let message = format!(
+            "Only {:.0}% of recent history is signed and verified ({} of {}), expected at least {}%",
+            percent, verified, total, minimum
+        );
+        findings.push(Finding {
+            code: "signing.below_threshold".to_string(),
+            severity: Severity::Error,
+            path: None,
+            message: message.clone(),
+            remediation: Some("Sign commits and tags (`git config commit.gpgsign true` and `tag.gpgsign true`)".to_string()),
+        });
+        CheckResult::from_findings(self, false, message, findings)
+    }
+}
+
+#[async_trait::async_trait]
+impl ComplianceCheck for SignedCommitsCheck {
+    fn id(&self) -> &str {
+        "gold.signed_commits"
+    }
+
+    fn name(&self) -> &str {
+        "Signed Commits"
+    }
+
+    fn description(&self) -> &str {
+        "Recent commits and release tags carry verified GPG, SSH or Sigstore signatures"
+    }
+
+    fn tier(&self) -> CertificationTier {
+        CertificationTier::Gold
+    }
+
+    async fn check_local(&self, path: &Path) -> Result<CheckResult> {
+        let Ok(adapter) = LocalRepoAdapter::open(path) else {
+            return Ok(CheckResult::not_evaluated(self, "not a git repository, so there's no history to verify"));
+        };
+        let repo = RepoRef::new("local", "local", path.display().to_string());
+        match SignedHistory::collect(&adapter, &repo, &SigningPolicy::default()).await {
+            Some(history) => Ok(self.evaluate(&history)),
+            None => Ok(CheckResult::not_evaluated(self, "the commit signatures couldn't be read")),
+        }
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        match contents.signing {
+            Some(ref history) => Ok(self.evaluate(history)),
+            None => Ok(CheckResult::not_evaluated(self, "the commit signatures couldn't be read")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compliance::NOT_EVALUATED;
+
+    fn signed(id: &str, verified: bool) -> SignedObject {
+        SignedObject {
+            id: id.to_string(),
+            signature: Some(Signature {
+                format: Some(SignatureFormat::Gpg),
+                verified,
+                reason: (!verified).then(|| "unknown_key".to_string()),
+            }),
+        }
+    }
+
+    fn unsigned(id: &str) -> SignedObject {
+        SignedObject {
+            id: id.to_string(),
+            signature: None,
+        }
+    }
+
+    fn history(commits: Vec<SignedObject>, tags: Vec<SignedObject>) -> SignedHistory {
+        SignedHistory {
+            policy: SigningPolicy::default(),
+            commits,
+            tags,
+        }
+    }
+
+    fn codes(result: &CheckResult) -> Vec<&str> {
+        result.findings.iter().map(|finding| finding.code.as_str()).collect()
+    }
+
+    #[test]
+    fn verified_history_passes() {
+        let commits = vec![signed("a1", true), signed("b2", true)];
+        let result = SignedCommitsCheck.evaluate(&history(commits, vec![signed("v1.0.0", true)]));
+        assert!(result.passed, "{}", result.message);
+        assert_eq!(codes(&result), ["signing.summary"]);
+    }
+
+    #[test]
+    fn unsigned_history_fails() {
+        let commits = vec![unsigned("a1"), unsigned("b2")];
+        let result = SignedCommitsCheck.evaluate(&history(commits, vec![unsigned("v1.0.0")]));
+        assert!(!result.passed);
+        assert_eq!(codes(&result), ["signing.summary", "signing.unsigned_tag", "signing.below_threshold"]);
+    }
+
+    #[test]
+    fn unverifiable_signatures_fail() {
+        let result = SignedCommitsCheck.evaluate(&history(vec![signed("0123456789abcdef", false)], Vec::new()));
+        assert!(!result.passed);
+        assert_eq!(codes(&result), ["signing.summary", "signing.unverified", "signing.below_threshold"]);
+        assert!(result.findings[1].message.starts_with("commit 0123456789ab has"), "{}", result.findings[1].message);
+    }
+
+    #[test]
+    fn empty_history_is_not_evaluated() {
+        let result = SignedCommitsCheck.evaluate(&history(Vec::new(), Vec::new()));
+        assert!(!result.passed);
+        assert_eq!(codes(&result), [NOT_EVALUATED]);
+    }
+
+    #[tokio::test]
+    async fn unreadable_signatures_are_not_evaluated() {
+        let result = SignedCommitsCheck.check_remote(&RepoContents::default()).await.unwrap();
+        assert!(!result.passed);
+        assert_eq!(codes(&result), [NOT_EVALUATED]);
+    }
+
+    #[tokio::test]
+    async fn directory_without_git_is_not_evaluated() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = SignedCommitsCheck.check_local(dir.path()).await.unwrap();
+        assert!(!result.passed);
+        assert_eq!(codes(&result), [NOT_EVALUATED]);
+    }
+}
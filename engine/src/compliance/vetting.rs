@@ -0,0 +1,369 @@
+//! Vetted dependencies
+//!
+//! Trusting a dependency doesn't require it to be certified itself, only
+//! that someone has read it. Rust projects record such reviews with
+//! cargo-vet (`supply-chain/audits.toml` for their own audits,
+//! `supply-chain/imports.lock` for audits imported from others) or with
+//! cargo-crev (`.crev` proof files). A direct crates.io dependency is vetted
+//! if:
+//! - an audit for the tenant's criteria (`safe-to-deploy` by default) covers
+//!   the version in use, directly or through delta audits from an audited
+//!   version,
+//! - the project trusts the crate's publisher for those criteria, or
+//! - a positive or strong crev review covers the version in use and no
+//!   negative or dangerous one does.
+//!
+//! Without a lockfile any vetted version counts. Organizations can keep
+//! shared audit sets in repositories of their own on the same platform,
+//! listed in `vetting.audit_repos`; their cargo-vet files count as if the
+//! project had them. crev signatures aren't verified: proofs are trusted as
+//! far as the repository holding them is.
+
+use super::dependencies::{cargo_dependencies, DirectDependency};
+use super::{ComplianceCheck, RepoContents};
+use crate::adapters::PlatformAdapter;
+use crate::{CertificationTier, CheckResult, RepoRef, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// cargo-vet files read from the project and from shared audit repositories
+const VET_FILES: [&str; 2] = ["supply-chain/audits.toml", "supply-chain/imports.lock"];
+
+fn default_criteria() -> String {
+    "safe-to-deploy".to_string()
+}
+
+fn default_min_vetted_percent() -> u32 {
+    100
+}
+
+/// Which audits a tenant accepts, and how many dependencies must have one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VettingPolicy {
+    /// cargo-vet criteria an audit must certify
+    #[serde(default = "default_criteria")]
+    pub criteria: String,
+    /// Repositories (`owner/repo`, on the scanned repository's platform)
+    /// holding audits shared across the organization
+    #[serde(default)]
+    pub audit_repos: Vec<String>,
+    /// Lowest share of direct Rust dependencies, in percent, that must be vetted
+    #[serde(default = "default_min_vetted_percent")]
+    pub min_vetted_percent: u32,
+}
+
+impl Default for VettingPolicy {
+    fn default() -> Self {
+        Self {
+            criteria: default_criteria(),
+            audit_repos: Vec::new(),
+            min_vetted_percent: default_min_vetted_percent(),
+        }
+    }
+}
+
+/// Shared cargo-vet files of a tenant, with the policy they are held to
+#[derive(Debug, Clone, Default)]
+pub struct AuditSets {
+    pub policy: VettingPolicy,
+    /// Contents of the shared repositories' cargo-vet files
+    pub shared: Vec<String>,
+}
+
+impl AuditSets {
+    /// Read the cargo-vet files of the policy's audit repositories. A
+    /// repository that can't be read is skipped.
+    pub async fn collect(adapter: &dyn PlatformAdapter, repo: &RepoRef, policy: &VettingPolicy) -> Self {
+        let mut shared = Vec::new();
+        for audit_repo in &policy.audit_repos {
+            let Some((owner, name)) = audit_repo.rsplit_once('/') else {
+                tracing::debug!("Skipping audit repository {}: expected owner/repo", audit_repo);
+                continue;
+            };
+            let audit_repo = RepoRef::new(repo.platform.clone(), owner, name);
+            match adapter.fetch_files(&audit_repo, &VET_FILES).await {
+                Ok(files) => shared.extend(files.into_values().filter_map(|bytes| String::from_utf8(bytes).ok())),
+                Err(e) => tracing::debug!("Couldn't read the audits in {}: {}", audit_repo, e),
+            }
+        }
+
+        Self {
+            policy: policy.clone(),
+            shared,
+        }
+    }
+}
+
+/// What the audits and reviews found say about each crate
+#[derive(Debug, Default)]
+struct Evidence {
+    /// Versions audited in full
+    audited: HashMap<String, HashSet<String>>,
+    /// Delta audits, from one version to another
+    deltas: HashMap<String, Vec<(String, String)>>,
+    /// Crates whose publisher is trusted
+    trusted: HashSet<String>,
+    /// Versions with a positive or strong crev review
+    approved: HashMap<String, HashSet<String>>,
+    /// Versions with a negative or dangerous crev review
+    rejected: HashMap<String, HashSet<String>>,
+}
+
+impl Evidence {
+    fn is_empty(&self) -> bool {
+        self.audited.is_empty() && self.deltas.is_empty() && self.trusted.is_empty() && self.approved.is_empty()
+    }
+
+    /// Record the audits and trusted publishers of a cargo-vet `audits.toml`
+    /// or `imports.lock`. Audits of violations are ignored.
+    fn add_vet_file(&mut self, content: &str, criteria: &str) {
+        let Ok(file) = content.parse::<toml::Table>() else {
+            return;
+        };
+        let Some(audits) = file.get("audits").and_then(toml::Value::as_table) else {
+            return;
+        };
+
+        // `imports.lock` nests each import's audits under `audits.<import>.audits`
+        let imported = audits
+            .values()
+            .filter_map(|import| import.get("audits").and_then(toml::Value::as_table));
+        for table in std::iter::once(audits).chain(imported) {
+            for (name, entries) in table {
+                for entry in entries.as_array().into_iter().flatten() {
+                    if !certifies(entry, criteria) || entry.get("violation").is_some() {
+                        continue;
+                    }
+                    if let Some(version) = entry.get("version").and_then(toml::Value::as_str) {
+                        self.audited.entry(name.clone()).or_default().insert(version.to_string());
+                    } else if let Some((from, to)) = entry
+                        .get("delta")
+                        .and_then(toml::Value::as_str)
+                        .and_then(|delta| delta.split_once("->"))
+                    {
+                        self.deltas
+                            .entry(name.clone())
+                            .or_default()
+                            .push((from.trim().to_string(), to.trim().to_string()));
+                    }
+                }
+            }
+        }
+
+        let trusted = file.get("trusted").and_then(toml::Value::as_table).into_iter().flatten();
+        for (name, entries) in trusted {
+            if entries.as_array().into_iter().flatten().any(|entry| certifies(entry, criteria)) {
+                self.trusted.insert(name.clone());
+            }
+        }
+    }
+
+    /// Record the crates.io package reviews of a file of crev proofs
+    fn add_crev_proofs(&mut self, content: &str) {
+        const BEGIN: &str = "-----BEGIN CREV PACKAGE REVIEW-----";
+        const SIGNATURE: &str = "-----BEGIN CREV PACKAGE REVIEW SIGNATURE-----";
+
+        for proof in content.split(BEGIN).skip(1) {
+            let body = proof.split(SIGNATURE).next().unwrap_or_default();
+            let (mut section, mut source, mut name, mut version, mut rating) = ("", "", "", "", "");
+            for line in body.lines() {
+                let Some((key, value)) = line.split_once(':') else {
+                    continue;
+                };
+                let value = value.trim().trim_matches('"');
+                if !line.starts_with(' ') {
+                    section = key;
+                    continue;
+                }
+                match (section, key.trim()) {
+                    ("package", "source") => source = value,
+                    ("package", "name") => name = value,
+                    ("package", "version") => version = value,
+                    ("review", "rating") => rating = value,
+                    _ => {}
+                }
+            }
+            if source != "https://crates.io" || name.is_empty() || version.is_empty() {
+                continue;
+            }
+            let verdicts = match rating {
+                "positive" | "strong" => &mut self.approved,
+                "negative" | "dangerous" => &mut self.rejected,
+                _ => continue,
+            };
+            verdicts.entry(name.to_string()).or_default().insert(version.to_string());
+        }
+    }
+
+    /// Versions of `name` covered by an audit, following delta audits
+    fn audited_versions(&self, name: &str) -> HashSet<&str> {
+        let mut versions = versions(&self.audited, name);
+        let deltas = self.deltas.get(name).map(Vec::as_slice).unwrap_or_default();
+        loop {
+            let reached: Vec<&str> = deltas
+                .iter()
+                .filter(|(from, to)| versions.contains(from.as_str()) && !versions.contains(to.as_str()))
+                .map(|(_, to)| to.as_str())
+                .collect();
+            if reached.is_empty() {
+                return versions;
+            }
+            versions.extend(reached);
+        }
+    }
+
+    fn is_vetted(&self, dependency: &DirectDependency) -> bool {
+        if self.trusted.contains(&dependency.name) {
+            return true;
+        }
+        let covers = |versions: &HashSet<&str>| match dependency.version {
+            Some(ref version) => versions.contains(version.as_str()),
+            None => !versions.is_empty(),
+        };
+        let rejected = dependency
+            .version
+            .as_deref()
+            .is_some_and(|version| versions(&self.rejected, &dependency.name).contains(version));
+        let approved = covers(&versions(&self.approved, &dependency.name)) && !rejected;
+        approved || covers(&self.audited_versions(&dependency.name))
+    }
+}
+
+/// Versions of `name` in a map of versions by crate
+fn versions<'a>(by_crate: &'a HashMap<String, HashSet<String>>, name: &str) -> HashSet<&'a str> {
+    by_crate.get(name).into_iter().flatten().map(String::as_str).collect()
+}
+
+/// Whether a cargo-vet audit or trust entry certifies `criteria`, or
+/// `safe-to-deploy` when `safe-to-run` (which it implies) is asked for
+fn certifies(entry: &toml::Value, criteria: &str) -> bool {
+    let certified: Vec<&str> = match entry.get("criteria") {
+        Some(toml::Value::String(single)) => vec![single.as_str()],
+        Some(toml::Value::Array(several)) => several.iter().filter_map(toml::Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    certified
+        .iter()
+        .any(|certified| *certified == criteria || (criteria == "safe-to-run" && *certified == "safe-to-deploy"))
+}
+
+/// Check that direct Rust dependencies have been audited or reviewed
+pub struct VettedDependenciesCheck;
+
+impl VettedDependenciesCheck {
+    /// Judge the dependencies of the manifests read through `read`, against
+    /// the audits it reads and the crev proofs in `crev_proofs`
+    fn evaluate(
+        &self,
+        read: impl Fn(&str) -> Option<String>,
+        crev_proofs: impl Iterator<Item = String>,
+        sets: Option<&AuditSets>,
+    ) -> CheckResult {
+        let Some(manifest) = read("Cargo.toml") else {
+            return CheckResult::not_evaluated(self, "no Cargo.toml; only Rust dependencies can be vetted");
+        };
+        let dependencies = cargo_dependencies(&manifest, read("Cargo.lock").as_deref());
+        if dependencies.is_empty() {
+            return CheckResult::with_details(self, true, "No Rust dependencies to vet".to_string(), None);
+        }
+
+        let default_policy = VettingPolicy::default();
+        let policy = sets.map_or(&default_policy, |sets| &sets.policy);
+        let mut evidence = Evidence::default();
+        let shared = sets.into_iter().flat_map(|sets| sets.shared.iter().cloned());
+        for content in VET_FILES.iter().filter_map(|path| read(path)).chain(shared) {
+            evidence.add_vet_file(&content, &policy.criteria);
+        }
+        for content in crev_proofs {
+            evidence.add_crev_proofs(&content);
+        }
+        if evidence.is_empty() {
+            return CheckResult::with_details(
+                self,
+                false,
+                format!("None of {} Rust dependencies are vetted: no cargo-vet audits or crev reviews", dependencies.len()),
+                None,
+            );
+        }
+
+        let total = dependencies.len();
+        let mut unvetted: Vec<String> = dependencies
+            .iter()
+            .filter(|dependency| !evidence.is_vetted(dependency))
+            .map(|dependency| match dependency.version {
+                Some(ref version) => format!("{} {}", dependency.name, version),
+                None => dependency.name.clone(),
+            })
+            .collect();
+        unvetted.sort();
+        let vetted = total - unvetted.len();
+        let details = (!unvetted.is_empty()).then(|| format!("Not vetted:\n{}", unvetted.join("\n")));
+
+        if vetted * 100 >= total * policy.min_vetted_percent as usize {
+            CheckResult::with_details(
+                self,
+                true,
+                format!("{} of {} Rust dependencies are vetted for {}", vetted, total, policy.criteria),
+                details,
+            )
+        } else {
+            CheckResult::with_details(
+                self,
+                false,
+                format!(
+                    "Only {} of {} Rust dependencies are vetted for {}, expected {}%",
+                    vetted, total, policy.criteria, policy.min_vetted_percent
+                ),
+                details,
+            )
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ComplianceCheck for VettedDependenciesCheck {
+    fn id(&self) -> &str {
+        "rhodium.vetted_dependencies"
+    }
+
+    fn name(&self) -> &str {
+        "Vetted Dependencies"
+    }
+
+    fn tier(&self) -> CertificationTier {
+        CertificationTier::Rhodium
+    }
+
+    fn reads(&self, path: &str) -> bool {
+        matches!(path, "Cargo.toml" | "Cargo.lock") || VET_FILES.contains(&path) || path.ends_with(".crev")
+    }
+
+    async fn check_local(&self, path: &Path) -> Result<CheckResult> {
+        let mut paths = Vec::new();
+        crate::adapters::local::walk_workdir(path, path, &mut paths)?;
+        let crev_proofs = paths
+            .iter()
+            .filter(|relative| relative.ends_with(".crev"))
+            .filter_map(|relative| std::fs::read_to_string(path.join(relative)).ok());
+
+        Ok(self.evaluate(|name| std::fs::read_to_string(path.join(name)).ok(), crev_proofs, None))
+    }
+
+    async fn check_remote(&self, contents: &RepoContents) -> Result<CheckResult> {
+        let read = |name: &str| {
+            contents
+                .files
+                .iter()
+                .find(|file| file.path == name)
+                .and_then(|file| file.content.clone())
+        };
+        let crev_proofs = contents
+            .files
+            .iter()
+            .filter(|file| file.path.ends_with(".crev"))
+            .filter_map(|file| file.content.clone());
+
+        Ok(self.evaluate(read, crev_proofs, contents.vetting.as_ref()))
+    }
+}
@@ -0,0 +1,205 @@
+//! Configuration bundles
+//!
+//! Organizations running more than one instance (staging, production)
+//! promote configuration between them as signed JSON bundles. A bundle
+//! carries the sections that describe how repositories are certified:
+//! - `policies`: tier policies, including the checks each one waives
+//!   (`disabled_checks`)
+//! - `hierarchy`: the enterprises organizations are grouped under
+//! - `notifications`: notification rules
+//!
+//! Adapters, scheduling, workers and the rest stay specific to each instance.
+//!
+//! The exporting instance signs the bundle with the Ed25519 key in
+//! `RSR_BUNDLE_SIGNING_KEY`; the importing one only applies bundles signed by
+//! a key in its `bundles.trusted_keys`. Importing rewrites the selected
+//! sections of the configuration file, keeping the previous file alongside
+//! as `<file>.bak`, and reloads it like any other edit.
+
+use super::{EngineConfig, NotificationRule, PolicyConfig};
+use crate::hierarchy::HierarchyConfig;
+use crate::{Result, RsrError};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::Signer;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Bundle format written by this engine
+pub const BUNDLE_FORMAT: u32 = 1;
+
+/// Environment variable holding the base64 Ed25519 secret key bundles are
+/// signed with
+pub const SIGNING_KEY_ENV: &str = "RSR_BUNDLE_SIGNING_KEY";
+
+/// Who bundles are accepted from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BundleConfig {
+    /// Base64 Ed25519 public keys of instances bundles may be imported from
+    #[serde(default)]
+    pub trusted_keys: Vec<String>,
+}
+
+impl BundleConfig {
+    pub fn validate(&self) -> Vec<String> {
+        self.trusted_keys
+            .iter()
+            .filter(|encoded| verifying_key(encoded).is_err())
+            .map(|encoded| format!("bundles.trusted_keys: {} is not a base64 Ed25519 public key", encoded))
+            .collect()
+    }
+}
+
+/// A configuration section bundles carry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleSection {
+    Policies,
+    Hierarchy,
+    Notifications,
+}
+
+impl BundleSection {
+    pub const ALL: [Self; 3] = [Self::Policies, Self::Hierarchy, Self::Notifications];
+
+    /// Top-level key of the section in the configuration file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Policies => "policies",
+            Self::Hierarchy => "hierarchy",
+            Self::Notifications => "notifications",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|section| section.as_str() == name.trim())
+            .ok_or_else(|| RsrError::Config(format!("Unknown bundle section {:?}", name)))
+    }
+}
+
+/// Configuration promoted between instances
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub format: u32,
+    pub exported_at: DateTime<Utc>,
+    /// SHA-256 of the configuration file it was exported from
+    pub source_digest: String,
+    pub policies: PolicyConfig,
+    pub hierarchy: HierarchyConfig,
+    pub notifications: Vec<NotificationRule>,
+}
+
+impl ConfigBundle {
+    /// Bundle the promotable sections of `config`
+    pub fn export(config: &EngineConfig, source_digest: &str) -> Self {
+        Self {
+            format: BUNDLE_FORMAT,
+            exported_at: Utc::now(),
+            source_digest: source_digest.to_string(),
+            policies: config.policies.clone(),
+            hierarchy: config.hierarchy.clone(),
+            notifications: config.notifications.clone(),
+        }
+    }
+
+    /// The section as a value of the configuration file
+    pub fn section(&self, section: BundleSection) -> Result<toml::Value> {
+        let value = match section {
+            BundleSection::Policies => toml::Value::try_from(&self.policies),
+            BundleSection::Hierarchy => toml::Value::try_from(&self.hierarchy),
+            BundleSection::Notifications => toml::Value::try_from(&self.notifications),
+        };
+        value.map_err(|e| RsrError::Config(format!("Bundle section {} can't be written: {}", section.as_str(), e)))
+    }
+}
+
+/// A bundle with its detached signature
+///
+/// The bundle is kept as the exact JSON that was signed, so re-encoding it
+/// can't invalidate the signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedBundle {
+    /// [`ConfigBundle`] as JSON
+    pub bundle: String,
+    /// Base64 Ed25519 signature over `bundle`
+    pub signature: String,
+    /// Base64 public key of the signer
+    pub signed_by: String,
+}
+
+impl SignedBundle {
+    /// Sign `bundle` with the base64 Ed25519 secret key `secret`
+    pub fn sign(bundle: &ConfigBundle, secret: &str) -> Result<Self> {
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let secret: [u8; 32] = b64
+            .decode(secret.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| RsrError::Config(format!("{} must be a base64 Ed25519 secret key", SIGNING_KEY_ENV)))?;
+        let key = ed25519_dalek::SigningKey::from_bytes(&secret);
+
+        let bundle = serde_json::to_string(bundle)?;
+        let signature = key.sign(bundle.as_bytes());
+
+        Ok(Self {
+            signature: b64.encode(signature.to_bytes()),
+            signed_by: b64.encode(key.verifying_key().to_bytes()),
+            bundle,
+        })
+    }
+
+    /// Sign with the key in [`SIGNING_KEY_ENV`]
+    pub fn sign_with_env(bundle: &ConfigBundle) -> Result<Self> {
+        let secret = std::env::var(SIGNING_KEY_ENV).map_err(|_| {
+            RsrError::InsecureConfiguration(format!("{} is not set; bundles can't be signed", SIGNING_KEY_ENV))
+        })?;
+        Self::sign(bundle, &secret)
+    }
+
+    /// SHA-256 of the signed bundle
+    pub fn digest(&self) -> String {
+        hex::encode(Sha256::digest(self.bundle.as_bytes()))
+    }
+
+    /// The bundle, once its signer is trusted and the signature holds
+    pub fn verify(&self, trusted_keys: &[String]) -> Result<ConfigBundle> {
+        if trusted_keys.is_empty() {
+            return Err(RsrError::InsecureConfiguration(
+                "no trusted keys configured for bundles".to_string(),
+            ));
+        }
+        if !trusted_keys.iter().any(|trusted| trusted.trim() == self.signed_by.trim()) {
+            return Err(RsrError::Config(format!("Bundle signer {} is not trusted", self.signed_by)));
+        }
+
+        let signature: [u8; 64] = base64::engine::general_purpose::STANDARD
+            .decode(self.signature.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| RsrError::Config("Bundle signature must be a base64 Ed25519 signature".to_string()))?;
+        verifying_key(&self.signed_by)?
+            .verify_strict(self.bundle.as_bytes(), &ed25519_dalek::Signature::from_bytes(&signature))
+            .map_err(|_| RsrError::Config("Bundle signature does not match its signer".to_string()))?;
+
+        let bundle: ConfigBundle = serde_json::from_str(&self.bundle)
+            .map_err(|e| RsrError::Config(format!("Invalid bundle: {}", e)))?;
+        if bundle.format != BUNDLE_FORMAT {
+            return Err(RsrError::Config(format!(
+                "Unsupported bundle format {} (expected {})",
+                bundle.format, BUNDLE_FORMAT
+            )));
+        }
+        Ok(bundle)
+    }
+}
+
+fn verifying_key(encoded: &str) -> Result<ed25519_dalek::VerifyingKey> {
+    let key: [u8; 32] = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| RsrError::Config("Bundle keys must be base64 Ed25519 public keys".to_string()))?;
+    ed25519_dalek::VerifyingKey::from_bytes(&key).map_err(|e| RsrError::Config(format!("Invalid bundle key: {}", e)))
+}
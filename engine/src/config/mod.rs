@@ -0,0 +1,843 @@
+//! Engine configuration with hot reload
+//!
+//! Policies, notification rules, adapter settings, scan scheduling, worker
+//! scaling bounds, job redelivery, publishing, rulepacks, linked identities,
+//! organization hierarchies, report retention and branding are read from a
+//! TOML file and can be reloaded at runtime (SIGHUP or the admin API).
+//! Database connections are not part of this file and are never reloaded
+//! (see [`crate::db::config`]).
+//!
+//! A reload parses and validates the new file before swapping it in, so a bad
+//! edit leaves the running configuration untouched. Every attempt is recorded
+//! in an audit log.
+//!
+//! Policies, hierarchies and notification rules can be promoted between
+//! instances as signed [`bundle`]s.
+
+pub mod bundle;
+
+use crate::adapters::{AdapterConfig, AdapterFactory};
+use crate::branding::Branding;
+use bundle::{BundleConfig, BundleSection, SignedBundle};
+use crate::compliance::authorship::AuthorPolicy;
+use crate::compliance::flakiness::CiPolicy;
+use crate::compliance::identity::{self, IdentityLink};
+use crate::compliance::releases::ReleasePolicy;
+use crate::compliance::signing::SigningPolicy;
+use crate::compliance::vetting::VettingPolicy;
+use crate::compliance::rulepack::{self, RulepackConfig};
+use crate::db::queue::DeliveryPolicy;
+use crate::db::retention::RetentionPolicy;
+use crate::hierarchy::HierarchyConfig;
+use crate::publish::PublishConfig;
+use crate::scheduler::{CalendarExclusion, SchedulerConfig};
+use crate::worker::ScalingPolicy;
+use crate::{CertificationTier, RepoRef, Result, RsrError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Maximum number of audit entries kept in memory
+const MAX_AUDIT_ENTRIES: usize = 256;
+
+/// Reloadable engine configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EngineConfig {
+    #[serde(default)]
+    pub policies: PolicyConfig,
+    #[serde(default)]
+    pub notifications: Vec<NotificationRule>,
+    /// Keyed by platform id
+    #[serde(default)]
+    pub adapters: HashMap<String, AdapterSettings>,
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    #[serde(default)]
+    pub workers: ScalingPolicy,
+    #[serde(default)]
+    pub queue: DeliveryPolicy,
+    /// Upload badges and reports to object storage after each rescan
+    #[serde(default)]
+    pub publish: Option<PublishConfig>,
+    /// Signed rulepacks loaded at startup
+    #[serde(default)]
+    pub rulepacks: RulepackConfig,
+    /// Mirrors of the same project on different platforms
+    #[serde(default)]
+    pub links: Vec<IdentityLink>,
+    /// Enterprises above top-level organizations
+    #[serde(default)]
+    pub hierarchy: HierarchyConfig,
+    /// How much report history to keep
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// Instances configuration bundles are accepted from
+    #[serde(default)]
+    pub bundles: BundleConfig,
+    /// Program and tier names, badge label and links in reports and reviews
+    #[serde(default)]
+    pub branding: Branding,
+}
+
+/// Certification policies - a default plus per-tenant overrides
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    #[serde(default)]
+    pub default: TierPolicy,
+    /// Keyed by tenant: an owner or parent group (`platform:owner`) or an
+    /// enterprise (`enterprise:name`)
+    #[serde(default)]
+    pub tenants: HashMap<String, TierPolicy>,
+}
+
+impl PolicyConfig {
+    /// Policy that applies to a repository, ignoring enterprises and teams.
+    /// Prefer [`EngineConfig::policy_for`].
+    pub fn policy_for(&self, repo: &RepoRef) -> TierPolicy {
+        self.effective_policy(&HierarchyConfig::default().ancestry(repo)).policy
+    }
+
+    /// Policy of the nearest unit in `ancestry` that has one, as declared.
+    /// Constraints from units further up are not applied; see
+    /// [`Self::effective_policy`].
+    pub fn policy_in(&self, ancestry: &[String]) -> &TierPolicy {
+        ancestry
+            .iter()
+            .find_map(|unit| self.tenants.get(unit))
+            .unwrap_or(&self.default)
+    }
+
+    /// Policy for a repository below `ancestry` (nearest unit first): the
+    /// nearest unit's policy, raised to every `minimum_tier` and keeping every
+    /// `required_checks` entry declared along the chain and in the default
+    pub fn effective_policy(&self, ancestry: &[String]) -> EffectivePolicy {
+        let chain: Vec<(&str, &TierPolicy)> = ancestry
+            .iter()
+            .filter_map(|unit| Some((unit.as_str(), self.tenants.get(unit)?)))
+            .chain(std::iter::once(("default", &self.default)))
+            .collect();
+        let (source, declared) = chain[0];
+        let mut policy = declared.clone();
+        let mut inherited = Vec::new();
+
+        for &(unit, constraining) in &chain {
+            if let Some(minimum) = constraining.minimum_tier.filter(|minimum| *minimum > policy.target_tier) {
+                inherited.push(format!("target_tier raised to {:?} by {}", minimum, unit));
+                policy.target_tier = minimum;
+            }
+            for check in &constraining.required_checks {
+                if policy.disabled_checks.contains(check) {
+                    inherited.push(format!("{} re-enabled by {}", check, unit));
+                    policy.disabled_checks.retain(|disabled| disabled != check);
+                }
+            }
+        }
+
+        EffectivePolicy {
+            policy,
+            source: source.to_string(),
+            chain: chain.iter().map(|(unit, _)| unit.to_string()).collect(),
+            inherited,
+        }
+    }
+}
+
+/// Policy a repository is held to, and where it came from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectivePolicy {
+    pub policy: TierPolicy,
+    /// Unit whose policy it is based on, or `default`
+    pub source: String,
+    /// Units with a policy, nearest first, then `default`
+    pub chain: Vec<String>,
+    /// How units further up changed the declared policy
+    pub inherited: Vec<String>,
+}
+
+/// Certification policy for a tenant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TierPolicy {
+    /// Tier repositories are expected to reach
+    pub target_tier: CertificationTier,
+    /// Lowest `target_tier` this unit and every unit below it may have
+    #[serde(default)]
+    pub minimum_tier: Option<CertificationTier>,
+    /// Check ids this unit and units below it may not disable
+    #[serde(default)]
+    pub required_checks: Vec<String>,
+    /// Check ids that are skipped for this tenant
+    #[serde(default)]
+    pub disabled_checks: Vec<String>,
+    /// Request changes on pull requests that drop compliance below `target_tier`
+    #[serde(default)]
+    pub review_gate: bool,
+    /// Commit authors accepted on the default branch
+    #[serde(default)]
+    pub authors: AuthorPolicy,
+    /// What version tags and releases are expected to look like
+    #[serde(default)]
+    pub releases: ReleasePolicy,
+    /// Whether CI runs are analyzed for flakiness, and how much is tolerated
+    #[serde(default)]
+    pub ci: CiPolicy,
+    /// How much of the latest history must be signed
+    #[serde(default)]
+    pub signing: SigningPolicy,
+    /// Which dependency audits count, and how many dependencies need one
+    #[serde(default)]
+    pub vetting: VettingPolicy,
+    /// How forks and mirrors use their upstream's certification
+    #[serde(default)]
+    pub upstream: UpstreamCompliance,
+}
+
+impl Default for TierPolicy {
+    fn default() -> Self {
+        Self {
+            target_tier: CertificationTier::Bronze,
+            minimum_tier: None,
+            required_checks: Vec::new(),
+            disabled_checks: Vec::new(),
+            review_gate: false,
+            authors: AuthorPolicy::default(),
+            releases: ReleasePolicy::default(),
+            ci: CiPolicy::default(),
+            signing: SigningPolicy::default(),
+            vetting: VettingPolicy::default(),
+            upstream: UpstreamCompliance::default(),
+        }
+    }
+}
+
+/// How a fork or mirror uses the certification of the repository it was
+/// copied from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpstreamCompliance {
+    /// Forks stand on their own
+    #[default]
+    Ignore,
+    /// Reports of forks show the upstream's certification alongside their own
+    Reference,
+    /// Forks that haven't been scanned yet report the upstream's certification
+    Inherit,
+}
+
+/// Events a notification rule can fire on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationTrigger {
+    /// Tier went up or down
+    TierChanged,
+    /// Tier fell below the tenant's target
+    BelowTarget,
+    /// A previously passing check failed
+    CheckRegressed,
+}
+
+/// Where to send notifications and when
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationRule {
+    pub name: String,
+    /// Webhook URL notifications are POSTed to
+    pub url: String,
+    pub on: Vec<NotificationTrigger>,
+    /// Tenants this rule applies to (empty means all)
+    #[serde(default)]
+    pub tenants: Vec<String>,
+}
+
+impl NotificationRule {
+    /// Whether this rule applies to a repository
+    pub fn matches(&self, repo: &RepoRef) -> bool {
+        self.tenants.is_empty() || self.tenants.contains(&crate::scheduler::tenant_key(repo))
+    }
+}
+
+/// Adapter settings - secrets are named by environment variable, never inlined
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AdapterSettings {
+    /// API base URL (for self-hosted instances)
+    pub api_url: Option<String>,
+    /// Environment variable holding the API token
+    pub api_token_env: Option<String>,
+    /// Environment variable holding the webhook secret
+    pub webhook_secret_env: Option<String>,
+    /// Report results as Check Runs instead of commit statuses (GitHub)
+    #[serde(default)]
+    pub use_check_runs: bool,
+    /// Accept sha1 webhook signatures from older GitHub Enterprise Server
+    #[serde(default)]
+    pub allow_legacy_signatures: bool,
+    /// Refuse webhooks when no secret is configured (defaults to on in release builds)
+    pub require_signature: Option<bool>,
+    /// Review label to vote on (Gerrit)
+    pub review_label: Option<String>,
+}
+
+impl AdapterSettings {
+    /// Build an adapter config, resolving secrets from the environment
+    pub fn to_adapter_config(&self) -> AdapterConfig {
+        let mut config = AdapterConfig::new()
+            .with_check_runs(self.use_check_runs)
+            .with_legacy_signatures(self.allow_legacy_signatures);
+        if let Some(required) = self.require_signature {
+            config = config.with_require_signature(required);
+        }
+        if let Some(ref url) = self.api_url {
+            config = config.with_api_url(url);
+        }
+        if let Some(ref label) = self.review_label {
+            config = config.with_review_label(label);
+        }
+        if let Some(token) = self.api_token_env.as_deref().and_then(|var| std::env::var(var).ok()) {
+            config = config.with_api_token(token);
+        }
+        if let Some(secret) = self.webhook_secret_env.as_deref().and_then(|var| std::env::var(var).ok()) {
+            config = config.with_webhook_secret(secret);
+        }
+        config
+    }
+}
+
+impl EngineConfig {
+    /// Parse configuration from TOML
+    pub fn from_toml(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| RsrError::Config(format!("Invalid configuration: {}", e)))
+    }
+
+    /// Parse and validate configuration, as a reload does
+    pub fn parse(content: &str) -> Result<Self> {
+        let config = Self::from_toml(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Adapter config for a platform (empty if the platform has no settings)
+    pub fn adapter_config(&self, platform: &str) -> AdapterConfig {
+        self.adapters
+            .get(&platform.to_lowercase())
+            .map(AdapterSettings::to_adapter_config)
+            .unwrap_or_default()
+    }
+
+    /// Policy that applies to a repository, inherited from the nearest unit
+    /// above it that has one and constrained by the units above that
+    pub fn policy_for(&self, repo: &RepoRef) -> TierPolicy {
+        self.effective_policy(repo).policy
+    }
+
+    /// [`Self::policy_for`], with the chain of units it was resolved from
+    pub fn effective_policy(&self, repo: &RepoRef) -> EffectivePolicy {
+        self.policies.effective_policy(&self.hierarchy.ancestry(repo))
+    }
+
+    /// Check the configuration for mistakes that parsing alone can't catch
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        for (platform, settings) in &self.adapters {
+            if !AdapterFactory::is_supported(platform) {
+                problems.push(format!("adapters.{}: unknown platform", platform));
+            }
+            if let Some(ref url) = settings.api_url {
+                if let Err(e) = reqwest::Url::parse(url) {
+                    problems.push(format!("adapters.{}.api_url: {}", platform, e));
+                }
+            }
+            for var in [&settings.api_token_env, &settings.webhook_secret_env].into_iter().flatten() {
+                if std::env::var(var).is_err() {
+                    problems.push(format!("adapters.{}: environment variable {} is not set", platform, var));
+                }
+            }
+        }
+
+        let mut names = HashSet::new();
+        for rule in &self.notifications {
+            if !names.insert(rule.name.as_str()) {
+                problems.push(format!("notifications.{}: duplicate rule name", rule.name));
+            }
+            match reqwest::Url::parse(&rule.url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                Ok(url) => problems.push(format!("notifications.{}: unsupported scheme {}", rule.name, url.scheme())),
+                Err(e) => problems.push(format!("notifications.{}.url: {}", rule.name, e)),
+            }
+            if rule.on.is_empty() {
+                problems.push(format!("notifications.{}: no triggers", rule.name));
+            }
+        }
+
+        let tier_policies = std::iter::once(("default", &self.policies.default))
+            .chain(self.policies.tenants.iter().map(|(t, p)| (t.as_str(), p)));
+        for (tenant, policy) in tier_policies {
+            for check in policy.required_checks.iter().filter(|check| policy.disabled_checks.contains(check)) {
+                problems.push(format!("policies.{}: {} is both required and disabled", tenant, check));
+            }
+            if !(1..=100).contains(&policy.authors.lookback_commits) {
+                problems.push(format!("policies.{}.authors.lookback_commits: must be 1-100", tenant));
+            }
+            if let Err(e) = regex::Regex::new(&policy.releases.tag_pattern) {
+                problems.push(format!("policies.{}.releases.tag_pattern: {}", tenant, e));
+            }
+            if !(1..=100).contains(&policy.releases.lookback) {
+                problems.push(format!("policies.{}.releases.lookback: must be 1-100", tenant));
+            }
+            if !(1..=100).contains(&policy.ci.lookback_runs) {
+                problems.push(format!("policies.{}.ci.lookback_runs: must be 1-100", tenant));
+            }
+            if policy.ci.max_flaky_percent > 100 {
+                problems.push(format!("policies.{}.ci.max_flaky_percent: must be 0-100", tenant));
+            }
+            if !(1..=100).contains(&policy.signing.lookback_commits) {
+                problems.push(format!("policies.{}.signing.lookback_commits: must be 1-100", tenant));
+            }
+            if !(1..=100).contains(&policy.signing.lookback_tags) {
+                problems.push(format!("policies.{}.signing.lookback_tags: must be 1-100", tenant));
+            }
+            if policy.signing.min_signed_percent > 100 {
+                problems.push(format!("policies.{}.signing.min_signed_percent: must be 0-100", tenant));
+            }
+            if policy.vetting.min_vetted_percent > 100 {
+                problems.push(format!("policies.{}.vetting.min_vetted_percent: must be 0-100", tenant));
+            }
+            let malformed = |audit_repo: &&String| {
+                audit_repo
+                    .rsplit_once('/')
+                    .is_none_or(|(owner, repo)| owner.is_empty() || repo.is_empty())
+            };
+            for audit_repo in policy.vetting.audit_repos.iter().filter(malformed) {
+                problems.push(format!("policies.{}.vetting.audit_repos: {} is not owner/repo", tenant, audit_repo));
+            }
+            for (maturity, expected) in policy.releases.cadence.levels() {
+                let limits = [
+                    ("max_days_between_releases", expected.max_days_between_releases),
+                    ("max_unreleased_days", expected.max_unreleased_days),
+                ];
+                for (name, _) in limits.into_iter().filter(|(_, limit)| *limit == Some(0)) {
+                    problems.push(format!(
+                        "policies.{}.releases.cadence.{}.{}: must be at least 1 (omit it for no limit)",
+                        tenant,
+                        maturity.as_str(),
+                        name
+                    ));
+                }
+            }
+        }
+
+        let policies = std::iter::once(("default".to_string(), &self.scheduler.default))
+            .chain(self.scheduler.tenants.iter().map(|(t, p)| (t.clone(), p)));
+        for (tenant, policy) in policies {
+            if policy.utc_offset_minutes.abs() >= 24 * 60 {
+                problems.push(format!("scheduler.{}: utc_offset_minutes out of range", tenant));
+            }
+            let quotas = [
+                ("per_repo_per_hour", policy.quota.per_repo_per_hour),
+                ("per_tenant_per_hour", policy.quota.per_tenant_per_hour),
+            ];
+            for (name, quota) in quotas {
+                if quota == Some(0) {
+                    problems.push(format!("scheduler.{}.quota.{}: must be at least 1 (omit it for no quota)", tenant, name));
+                }
+            }
+            for exclusion in &policy.exclusions {
+                match exclusion {
+                    CalendarExclusion::Freeze { start, end, .. } if start >= end => {
+                        problems.push(format!("scheduler.{}: freeze ends before it starts", tenant));
+                    }
+                    CalendarExclusion::Weekly { duration_minutes, .. }
+                        if *duration_minutes == 0 || *duration_minutes > 7 * 24 * 60 =>
+                    {
+                        problems.push(format!("scheduler.{}: weekly window must be 1m-7d", tenant));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if self.workers.max_workers == 0 || self.workers.min_workers > self.workers.max_workers {
+            problems.push("workers: need 0 <= min_workers <= max_workers and max_workers > 0".to_string());
+        }
+        if self.workers.scale_down_age_secs > self.workers.scale_up_age_secs {
+            problems.push("workers: scale_down_age_secs must not exceed scale_up_age_secs".to_string());
+        }
+        if self.queue.visibility_timeout_secs == 0 || self.queue.max_attempts == 0 {
+            problems.push("queue: visibility_timeout_secs and max_attempts must be positive".to_string());
+        }
+
+        if let Some(ref publish) = self.publish {
+            if let Err(e) = reqwest::Url::parse(&publish.endpoint) {
+                problems.push(format!("publish.endpoint: {}", e));
+            }
+            if publish.bucket.is_empty() {
+                problems.push("publish.bucket: must not be empty".to_string());
+            }
+            for var in [&publish.access_key_env, &publish.secret_key_env] {
+                if std::env::var(var).is_err() {
+                    problems.push(format!("publish: environment variable {} is not set", var));
+                }
+            }
+        }
+
+        if let Some(ref registry) = self.rulepacks.registry {
+            if let Err(e) = reqwest::Url::parse(registry) {
+                problems.push(format!("rulepacks.registry: {}", e));
+            }
+        }
+        if !self.rulepacks.packs.is_empty() && self.rulepacks.trusted_keys.is_empty() {
+            problems.push("rulepacks: packs are configured but trusted_keys is empty".to_string());
+        }
+        for spec in &self.rulepacks.packs {
+            if let Err(e) = rulepack::parse_spec(spec) {
+                problems.push(format!("rulepacks.packs: {}", e));
+            }
+        }
+        problems.extend(self.rulepacks.sandbox.validate());
+        for (repo, pin) in &self.rulepacks.pins {
+            for spec in &pin.packs {
+                if let Err(e) = rulepack::parse_spec(spec) {
+                    problems.push(format!("rulepacks.pins.{}: {}", repo, e));
+                }
+            }
+        }
+
+        problems.extend(identity::validate_links(&self.links));
+        problems.extend(self.hierarchy.validate());
+        problems.extend(self.retention.validate());
+        problems.extend(self.bundles.validate());
+        problems.extend(self.branding.validate());
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(RsrError::Config(problems.join("; ")))
+        }
+    }
+
+    /// Top-level sections that differ from `other`
+    fn changed_sections(&self, other: &Self) -> Vec<String> {
+        let mut changed = Vec::new();
+        if self.policies != other.policies {
+            changed.push("policies".to_string());
+        }
+        if self.notifications != other.notifications {
+            changed.push("notifications".to_string());
+        }
+        if self.adapters != other.adapters {
+            changed.push("adapters".to_string());
+        }
+        if self.scheduler != other.scheduler {
+            changed.push("scheduler".to_string());
+        }
+        if self.workers != other.workers {
+            changed.push("workers".to_string());
+        }
+        if self.rulepacks != other.rulepacks {
+            changed.push("rulepacks".to_string());
+        }
+        if self.links != other.links {
+            changed.push("links".to_string());
+        }
+        if self.hierarchy != other.hierarchy {
+            changed.push("hierarchy".to_string());
+        }
+        if self.retention != other.retention {
+            changed.push("retention".to_string());
+        }
+        if self.bundles != other.bundles {
+            changed.push("bundles".to_string());
+        }
+        changed
+    }
+}
+
+/// What triggered a reload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReloadSource {
+    Startup,
+    Signal,
+    Api { actor: Option<String> },
+    /// A configuration bundle was imported
+    Import {
+        actor: Option<String>,
+        /// SHA-256 of the bundle
+        bundle: String,
+        signed_by: String,
+    },
+}
+
+/// Result of a reload attempt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ReloadOutcome {
+    Applied,
+    Unchanged,
+    Rejected { reason: String },
+}
+
+/// Audit record for a configuration load or reload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigAuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub source: ReloadSource,
+    #[serde(flatten)]
+    pub outcome: ReloadOutcome,
+    /// Top-level sections that changed
+    pub changed: Vec<String>,
+    /// SHA-256 of the configuration that was running before
+    pub previous_digest: Option<String>,
+    /// SHA-256 of the file that was read
+    pub digest: Option<String>,
+}
+
+/// Result of importing a configuration bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleImport {
+    /// SHA-256 of the bundle
+    pub bundle: String,
+    pub signed_by: String,
+    /// Configuration the bundle was exported from
+    pub source_digest: String,
+    pub sections: Vec<BundleSection>,
+    /// Top-level sections that differ from the running configuration
+    pub changed: Vec<String>,
+    pub dry_run: bool,
+    /// Reload that applied the bundle, unless nothing was written
+    pub reload: Option<ConfigAuditEntry>,
+}
+
+/// Holds the running configuration and swaps it on reload
+pub struct ConfigStore {
+    path: PathBuf,
+    current: RwLock<Arc<EngineConfig>>,
+    digest: RwLock<String>,
+    audit: Mutex<Vec<ConfigAuditEntry>>,
+    /// Held while the file is rewritten by an import
+    writing: Mutex<()>,
+}
+
+impl ConfigStore {
+    /// Load and validate the configuration file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let (config, digest) = read_config(&path)?;
+
+        tracing::info!("Loaded configuration from {}", path.display());
+
+        let store = Self {
+            path,
+            current: RwLock::new(Arc::new(config)),
+            digest: RwLock::new(digest.clone()),
+            audit: Mutex::new(Vec::new()),
+            writing: Mutex::new(()),
+        };
+        store.record(ConfigAuditEntry {
+            timestamp: Utc::now(),
+            source: ReloadSource::Startup,
+            outcome: ReloadOutcome::Applied,
+            changed: Vec::new(),
+            previous_digest: None,
+            digest: Some(digest),
+        });
+
+        Ok(store)
+    }
+
+    /// Path the configuration is read from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Snapshot of the running configuration
+    pub fn current(&self) -> Arc<EngineConfig> {
+        self.current.read().expect("config lock poisoned").clone()
+    }
+
+    /// SHA-256 of the file the running configuration was read from
+    pub fn digest(&self) -> String {
+        self.digest.read().expect("config lock poisoned").clone()
+    }
+
+    /// Re-read the file, validate it and swap it in.
+    ///
+    /// Returns the audit entry the reload recorded. A file that can't be
+    /// read or doesn't validate is rejected: the running configuration is
+    /// kept and the entry's outcome says why.
+    pub fn reload(&self, source: ReloadSource) -> ConfigAuditEntry {
+        let previous_digest = self.digest.read().expect("config lock poisoned").clone();
+
+        let (config, digest) = match read_config(&self.path) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                tracing::warn!("Configuration reload rejected: {}", e);
+                let entry = ConfigAuditEntry {
+                    timestamp: Utc::now(),
+                    source,
+                    outcome: ReloadOutcome::Rejected { reason: e.to_string() },
+                    changed: Vec::new(),
+                    previous_digest: Some(previous_digest),
+                    digest: None,
+                };
+                self.record(entry.clone());
+                return entry;
+            }
+        };
+
+        let changed = config.changed_sections(&self.current());
+        let outcome = if changed.is_empty() {
+            ReloadOutcome::Unchanged
+        } else {
+            *self.current.write().expect("config lock poisoned") = Arc::new(config);
+            *self.digest.write().expect("config lock poisoned") = digest.clone();
+            ReloadOutcome::Applied
+        };
+
+        tracing::info!("Configuration reload {:?}, changed: {:?}", outcome, changed);
+
+        let entry = ConfigAuditEntry {
+            timestamp: Utc::now(),
+            source,
+            outcome,
+            changed,
+            previous_digest: Some(previous_digest),
+            digest: Some(digest),
+        };
+        self.record(entry.clone());
+
+        entry
+    }
+
+    /// Apply `sections` of a signed bundle to the configuration file and
+    /// reload it. The bundle must be signed by a trusted key and the merged
+    /// file must validate; otherwise the file is left untouched. A dry run
+    /// only reports what would change.
+    pub fn import(
+        &self,
+        signed: &SignedBundle,
+        sections: &[BundleSection],
+        dry_run: bool,
+        actor: Option<String>,
+    ) -> Result<BundleImport> {
+        if sections.is_empty() {
+            return Err(RsrError::Config("No bundle sections selected".to_string()));
+        }
+        let _writing = self.writing.lock().expect("config lock poisoned");
+        let bundle = signed.verify(&self.current().bundles.trusted_keys)?;
+
+        let content = std::fs::read_to_string(&self.path)
+            .map_err(|e| RsrError::Config(format!("Failed to read {}: {}", self.path.display(), e)))?;
+        // Only the imported sections are replaced; the rest of the file,
+        // comments included, is written back as it was
+        let mut document: toml_edit::DocumentMut = content
+            .parse()
+            .map_err(|e| RsrError::Config(format!("Invalid configuration: {}", e)))?;
+        for &section in sections {
+            document.insert(section.as_str(), section_item(section.as_str(), bundle.section(section)?)?);
+        }
+        let merged = document.to_string();
+        let config = EngineConfig::parse(&merged)?;
+
+        let mut import = BundleImport {
+            bundle: signed.digest(),
+            signed_by: signed.signed_by.clone(),
+            source_digest: bundle.source_digest,
+            sections: sections.to_vec(),
+            changed: config.changed_sections(&self.current()),
+            dry_run,
+            reload: None,
+        };
+        if dry_run || import.changed.is_empty() {
+            return Ok(import);
+        }
+
+        let backup = PathBuf::from(format!("{}.bak", self.path.display()));
+        let staged = PathBuf::from(format!("{}.import", self.path.display()));
+        std::fs::write(&backup, &content)?;
+        std::fs::write(&staged, &merged)?;
+        std::fs::rename(&staged, &self.path)?;
+        tracing::info!(
+            "Imported bundle {} signed by {} into {}",
+            import.bundle,
+            import.signed_by,
+            self.path.display()
+        );
+
+        let reload = self.reload(ReloadSource::Import {
+            actor,
+            bundle: import.bundle.clone(),
+            signed_by: import.signed_by.clone(),
+        });
+        if let ReloadOutcome::Rejected { ref reason } = reload.outcome {
+            return Err(RsrError::Config(reason.clone()));
+        }
+        import.reload = Some(reload);
+        Ok(import)
+    }
+
+    /// Audit log, oldest first
+    pub fn audit_log(&self) -> Vec<ConfigAuditEntry> {
+        self.audit.lock().expect("audit lock poisoned").clone()
+    }
+
+    fn record(&self, entry: ConfigAuditEntry) {
+        let mut audit = self.audit.lock().expect("audit lock poisoned");
+        if audit.len() >= MAX_AUDIT_ENTRIES {
+            audit.remove(0);
+        }
+        audit.push(entry);
+    }
+}
+
+/// Read, parse and validate a configuration file, returning it with its digest
+fn read_config(path: &Path) -> Result<(EngineConfig, String)> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| RsrError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+
+    let config = EngineConfig::parse(&content)?;
+
+    Ok((config, hex::encode(Sha256::digest(content.as_bytes()))))
+}
+
+/// `value` as the `name` item of a configuration file
+fn section_item(name: &str, value: toml::Value) -> Result<toml_edit::Item> {
+    let written = toml::to_string(&toml::Table::from_iter([(name.to_string(), value)]))
+        .map_err(|e| RsrError::Config(format!("Bundle section {} can't be written: {}", name, e)))?;
+    let mut fragment: toml_edit::DocumentMut = written
+        .parse()
+        .map_err(|e| RsrError::Config(format!("Bundle section {} can't be written: {}", name, e)))?;
+    Ok(fragment.remove(name).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use bundle::ConfigBundle;
+
+    #[test]
+    fn import_keeps_the_rest_of_the_file() {
+        let mut exported = EngineConfig::default();
+        exported.policies.default.target_tier = CertificationTier::Gold;
+        let secret = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+        let signed = SignedBundle::sign(&ConfigBundle::export(&exported, "source"), &secret).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rsr.toml");
+        let original = format!(
+            "# Managed by hand\n[bundles]\n# Promotions come from staging\ntrusted_keys = [\"{}\"]\n",
+            signed.signed_by
+        );
+        std::fs::write(&path, &original).unwrap();
+
+        let store = ConfigStore::load(&path).unwrap();
+        let import = store.import(&signed, &[BundleSection::Policies], false, None).unwrap();
+        assert!(import.changed.contains(&"policies".to_string()));
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with(&original), "{}", written);
+        assert_eq!(store.current().policies.default.target_tier, CertificationTier::Gold);
+    }
+}
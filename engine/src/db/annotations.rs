@@ -0,0 +1,170 @@
+//! Annotations on compliance reports
+//!
+//! Reports are immutable once stored. Annotations, such as "accepted risk,
+//! see ABC-123" on a failing check, are kept in their own table and refer to
+//! a report by repository and report time, so they can be added and removed
+//! without touching the report.
+
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::{Record, SurrealPool};
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+use crate::{Result, RsrError};
+#[cfg(feature = "documents-surrealdb")]
+use crate::RepoRef;
+use serde::{Deserialize, Serialize};
+
+/// Longest annotation message accepted
+pub const MAX_ANNOTATION_CHARS: usize = 2000;
+
+/// What an annotation says about a report or check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationKind {
+    /// The failure is known and accepted
+    AcceptedRisk,
+    /// The check got it wrong
+    FalsePositive,
+    /// Remediation is underway
+    InProgress,
+    Note,
+}
+
+impl AnnotationKind {
+    /// Label shown in reports and reviews
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::AcceptedRisk => "Accepted risk",
+            Self::FalsePositive => "False positive",
+            Self::InProgress => "In progress",
+            Self::Note => "Note",
+        }
+    }
+}
+
+/// An annotation on a report, or on one check in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Record ID, once stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub kind: AnnotationKind,
+    /// Check the annotation is about; `None` for the report as a whole
+    #[serde(default)]
+    pub check_id: Option<String>,
+    pub message: String,
+    /// Ticket or link with more context, e.g. `ABC-123`
+    #[serde(default)]
+    pub reference: Option<String>,
+    pub author: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Annotation {
+    /// Problems that keep the annotation from being stored
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+        if self.message.trim().is_empty() {
+            problems.push("message must not be empty".to_string());
+        }
+        if self.message.chars().count() > MAX_ANNOTATION_CHARS {
+            problems.push(format!("message is longer than {} characters", MAX_ANNOTATION_CHARS));
+        }
+        if self.author.trim().is_empty() {
+            problems.push("author must not be empty".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(RsrError::Config(problems.join("; ")))
+        }
+    }
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealPool {
+    /// Annotate the report stored for `repo` at `report_at`, returning the
+    /// annotation's record ID
+    pub async fn annotate_report(
+        &self,
+        repo: &RepoRef,
+        report_at: chrono::DateTime<chrono::Utc>,
+        annotation: &Annotation,
+    ) -> Result<String> {
+        annotation.validate()?;
+
+        // Timestamps are bound as RFC 3339 strings and cast, like reports'
+        let mut result = self
+            .client()
+            .query(
+                "CREATE report_annotation SET \
+                    platform = $platform, owner = $owner, repo = $repo, report_at = <datetime> $report_at, \
+                    kind = $annotation.kind, check_id = $annotation.check_id, message = $annotation.message, \
+                    reference = $annotation.reference, author = $annotation.author, \
+                    created_at = <datetime> $annotation.created_at \
+                 RETURN id",
+            )
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
+            .bind(("report_at", report_at.to_rfc3339()))
+            .bind(("annotation", annotation.clone()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB create failed", e))?;
+
+        let records: Vec<Record> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB create failed", e))?;
+        records
+            .into_iter()
+            .next()
+            .map(|record| record.id.to_string())
+            .ok_or_else(|| DbError::Backend("SurrealDB create returned no record".to_string()).into())
+    }
+
+    /// Annotations on the report stored for `repo` at `report_at`, oldest first
+    pub async fn get_annotations(
+        &self,
+        repo: &RepoRef,
+        report_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<Annotation>> {
+        let mut result = self
+            .client()
+            .query(
+                "SELECT <string> id AS id, kind, check_id, message, reference, author, \
+                    <string> created_at AS created_at \
+                 FROM ( \
+                    SELECT * FROM report_annotation \
+                    WHERE platform = $platform AND owner = $owner AND repo = $repo \
+                        AND report_at = <datetime> $report_at \
+                    ORDER BY created_at \
+                 )",
+            )
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
+            .bind(("report_at", report_at.to_rfc3339()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e).into())
+    }
+
+    /// Remove an annotation. Returns whether there was one to remove.
+    pub async fn remove_annotation(&self, annotation_id: &str) -> Result<bool> {
+        let mut result = self
+            .client()
+            .query("RETURN array::len((DELETE type::record($id) WHERE meta::tb(id) = 'report_annotation' RETURN BEFORE))")
+            .bind(("id", annotation_id.to_string()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB delete failed", e))?;
+
+        let removed: Option<usize> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB delete failed", e))?;
+        Ok(removed.unwrap_or_default() > 0)
+    }
+}
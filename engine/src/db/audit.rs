@@ -0,0 +1,169 @@
+//! Audit log
+//!
+//! Every status post, configuration change, badge issuance and manual
+//! override is recorded in the append-only `audit_event` table: who did it,
+//! what they did, and to what. The table refuses updates and deletes, so the
+//! trail can be handed to an auditor as it stands.
+
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::{Record, SurrealPool};
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+use crate::RepoRef;
+#[cfg(feature = "documents-surrealdb")]
+use crate::Result;
+use serde::{Deserialize, Serialize};
+
+/// Most events returned per page
+pub const MAX_AUDIT_PAGE: u32 = 100;
+
+/// Actor recorded for actions the engine takes on its own
+pub const ENGINE_ACTOR: &str = "rsr-engine";
+
+/// What was done
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// A compliance result was posted to a platform (review, comment or status)
+    StatusPosted,
+    /// The configuration was reloaded, or a reload was rejected
+    ConfigReloaded,
+    /// A configuration bundle from another instance was imported
+    ConfigImported,
+    /// Badges and a report were published for a repository
+    BadgeIssued,
+    AnnotationAdded,
+    AnnotationRemoved,
+    WebhookReplayed,
+    /// Quarantined webhooks were replayed in bulk
+    QuarantineReplayed,
+    DeadLettersRequeued,
+    WorkersScaled,
+    /// Maintenance or read-only mode was entered or left
+    ModeChanged,
+    /// Reports outside the retention policy were tombstoned
+    ReportsPruned,
+    /// Per-module log level overrides were replaced
+    LogLevelsChanged,
+}
+
+/// A recorded action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub actor: String,
+    pub action: AuditAction,
+    /// What the action was taken on, e.g. `github:owner/repo` or `config`
+    pub target: String,
+    #[serde(default)]
+    pub details: serde_json::Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Filters for reading the audit log; unset filters match everything
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub repo: Option<RepoRef>,
+    pub actor: Option<String>,
+    /// Only events at or after this time
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only events at or before this time
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// One page of the audit log, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditPage {
+    pub events: Vec<AuditEvent>,
+    /// Pass as `before` to fetch the next page; `None` on the last page
+    pub next: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Audit target for a repository, without its branch
+pub fn repo_target(repo: &RepoRef) -> String {
+    format!("{}:{}/{}", repo.platform, repo.owner, repo.repo)
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealPool {
+    /// Append an event to the audit log, returning its record ID
+    pub async fn record_audit(
+        &self,
+        actor: &str,
+        action: AuditAction,
+        target: &str,
+        details: serde_json::Value,
+    ) -> Result<String> {
+        let details = match details {
+            serde_json::Value::Null => serde_json::json!({}),
+            details => details,
+        };
+
+        let mut result = self
+            .client()
+            .query(
+                "CREATE audit_event SET actor = $actor, action = $action, target = $target, details = $details, \
+                    created_at = time::now() \
+                 RETURN id",
+            )
+            .bind(("actor", actor.to_string()))
+            .bind(("action", action))
+            .bind(("target", target.to_string()))
+            .bind(("details", details))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB create failed", e))?;
+
+        let records: Vec<Record> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB create failed", e))?;
+        records
+            .into_iter()
+            .next()
+            .map(|record| record.id.to_string())
+            .ok_or_else(|| DbError::Backend("SurrealDB create returned no record".to_string()).into())
+    }
+
+    /// One page of audit events matching `query`, newest first. Paged by
+    /// time like [`SurrealPool::get_compliance_history`]; `limit` is capped
+    /// at [`MAX_AUDIT_PAGE`].
+    pub async fn get_audit_events(
+        &self,
+        query: &AuditQuery,
+        limit: u32,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<AuditPage> {
+        let limit = limit.clamp(1, MAX_AUDIT_PAGE);
+
+        let mut result = self
+            .client()
+            .query(
+                "SELECT actor, action, target, details, <string> created_at AS created_at \
+                 FROM ( \
+                    SELECT * FROM audit_event \
+                    WHERE ($target = NONE OR target = $target) \
+                        AND ($actor = NONE OR actor = $actor) \
+                        AND ($since = NONE OR created_at >= <datetime> $since) \
+                        AND ($until = NONE OR created_at <= <datetime> $until) \
+                        AND ($before = NONE OR created_at < <datetime> $before) \
+                    ORDER BY created_at DESC LIMIT $limit \
+                 )",
+            )
+            .bind(("target", query.repo.as_ref().map(repo_target)))
+            .bind(("actor", query.actor.clone()))
+            .bind(("since", query.since.map(|since| since.to_rfc3339())))
+            .bind(("until", query.until.map(|until| until.to_rfc3339())))
+            .bind(("before", before.map(|before| before.to_rfc3339())))
+            .bind(("limit", limit + 1))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let mut events: Vec<AuditEvent> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+
+        let more = events.len() > limit as usize;
+        events.truncate(limit as usize);
+        let next = if more { events.last().map(|event| event.created_at) } else { None };
+
+        Ok(AuditPage { events, next })
+    }
+}
@@ -0,0 +1,141 @@
+//! Benchmark baselines
+//!
+//! `cargo bench` leaves criterion's estimates under `target/criterion`.
+//! `rsr bench` reads them, compares each benchmark's mean against the latest
+//! run recorded for the baseline branch, and fails on a regression beyond
+//! the threshold; with `--record` it stores the run as the branch's new
+//! baseline. Runs are kept in SurrealDB whichever document store the engine
+//! otherwise uses.
+
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::{Record, SurrealPool};
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// One benchmark's timing, in nanoseconds per iteration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    /// `group/function`, as criterion names its output directory
+    pub name: String,
+    pub mean_ns: f64,
+    pub std_dev_ns: f64,
+}
+
+/// Results of one `cargo bench` run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkRun {
+    /// Commit benchmarked
+    pub commit: String,
+    pub branch: String,
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+    pub results: Vec<BenchmarkResult>,
+}
+
+/// A benchmark slower than its baseline by more than the threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct Regression {
+    pub name: String,
+    pub baseline_ns: f64,
+    pub current_ns: f64,
+    /// Slowdown, in percent of the baseline
+    pub change_percent: f64,
+}
+
+/// The latest estimates of every benchmark under criterion's output
+/// directory, sorted by name
+pub fn read_criterion(dir: &Path) -> Result<Vec<BenchmarkResult>> {
+    #[derive(Deserialize)]
+    struct Estimate {
+        point_estimate: f64,
+    }
+
+    #[derive(Deserialize)]
+    struct Estimates {
+        mean: Estimate,
+        std_dev: Estimate,
+    }
+
+    let mut paths = Vec::new();
+    crate::adapters::local::walk_workdir(dir, dir, &mut paths)?;
+
+    let mut results = Vec::new();
+    for relative in paths {
+        let Some(name) = relative.strip_suffix("/new/estimates.json") else {
+            continue;
+        };
+        let estimates: Estimates = serde_json::from_slice(&std::fs::read(dir.join(&relative))?)?;
+        results.push(BenchmarkResult {
+            name: name.to_string(),
+            mean_ns: estimates.mean.point_estimate,
+            std_dev_ns: estimates.std_dev.point_estimate,
+        });
+    }
+    results.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(results)
+}
+
+/// Benchmarks in `current` whose mean is more than `threshold_percent`
+/// slower than in `baseline`. Benchmarks new since the baseline can't regress.
+pub fn regressions(baseline: &BenchmarkRun, current: &[BenchmarkResult], threshold_percent: f64) -> Vec<Regression> {
+    current
+        .iter()
+        .filter_map(|result| {
+            let before = baseline.results.iter().find(|before| before.name == result.name)?;
+            let change_percent = (result.mean_ns - before.mean_ns) * 100.0 / before.mean_ns;
+            (change_percent > threshold_percent).then(|| Regression {
+                name: result.name.clone(),
+                baseline_ns: before.mean_ns,
+                current_ns: result.mean_ns,
+                change_percent,
+            })
+        })
+        .collect()
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealPool {
+    /// Store a benchmark run, returning its record ID
+    pub async fn record_benchmark_run(&self, run: &BenchmarkRun) -> Result<String> {
+        let mut result = self
+            .client()
+            .query(
+                "CREATE benchmark_run SET commit = $run.commit, branch = $run.branch, \
+                    recorded_at = <datetime> $run.recorded_at, results = $run.results \
+                 RETURN id",
+            )
+            .bind(("run", run.clone()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB create failed", e))?;
+
+        let records: Vec<Record> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB create failed", e))?;
+        records
+            .into_iter()
+            .next()
+            .map(|record| record.id.to_string())
+            .ok_or_else(|| DbError::Backend("SurrealDB create returned no record".to_string()).into())
+    }
+
+    /// The latest benchmark run recorded for `branch`
+    pub async fn latest_benchmark_run(&self, branch: &str) -> Result<Option<BenchmarkRun>> {
+        let mut result = self
+            .client()
+            .query(
+                "SELECT commit, branch, <string> recorded_at AS recorded_at, results \
+                 FROM benchmark_run WHERE branch = $branch \
+                 ORDER BY recorded_at DESC LIMIT 1",
+            )
+            .bind(("branch", branch.to_string()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let runs: Vec<BenchmarkRun> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+        Ok(runs.into_iter().next())
+    }
+}
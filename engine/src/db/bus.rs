@@ -0,0 +1,146 @@
+//! Event bus between engine instances, on DragonflyDB pub/sub
+//!
+//! Instances broadcast compliance changes, cache invalidations, completed
+//! scans and configuration changes on a shared channel. Messages are JSON envelopes naming the
+//! instance that sent them, so subscribers can skip their own.
+//!
+//! Pub/sub is fire-and-forget: instances that are disconnected when a message
+//! is published never see it. Use the bus for notifications that are safe to
+//! miss, and the job queue for work that must happen.
+
+use super::cache::CacheBackend;
+use crate::{CertificationTier, RepoRef, Result};
+use futures::{Stream, StreamExt};
+use std::sync::Arc;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+/// Channel messages are published on by default
+pub const DEFAULT_CHANNEL: &str = "rsr:bus";
+
+/// Identifies this process on the bus
+static INSTANCE_ID: Lazy<String> = Lazy::new(|| {
+    format!(
+        "{:x}-{:x}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    )
+});
+
+/// Something other instances may want to know about
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BusMessage {
+    /// A repository's tier changed
+    ComplianceChanged {
+        repo: RepoRef,
+        previous: Option<CertificationTier>,
+        tier: CertificationTier,
+        score: f32,
+    },
+    /// Cached data for a repository is stale and should be dropped
+    CacheInvalidated { repo: RepoRef },
+    /// A scan finished, whether or not anything changed
+    ScanCompleted {
+        repo: RepoRef,
+        tier: CertificationTier,
+        score: f32,
+    },
+    /// An instance applied a new configuration
+    ConfigChanged {
+        /// SHA-256 of the configuration now running
+        digest: Option<String>,
+        /// Top-level sections that changed
+        changed: Vec<String>,
+    },
+}
+
+impl BusMessage {
+    /// Repository the message is about, if it's about one
+    pub fn repo(&self) -> Option<&RepoRef> {
+        match self {
+            Self::ComplianceChanged { repo, .. } | Self::CacheInvalidated { repo } | Self::ScanCompleted { repo, .. } => Some(repo),
+            Self::ConfigChanged { .. } => None,
+        }
+    }
+
+    /// Name of the message type, as used for its `type` tag
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ComplianceChanged { .. } => "compliance_changed",
+            Self::CacheInvalidated { .. } => "cache_invalidated",
+            Self::ScanCompleted { .. } => "scan_completed",
+            Self::ConfigChanged { .. } => "config_changed",
+        }
+    }
+}
+
+/// A message as sent on the wire
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusEnvelope {
+    /// Instance that published the message
+    pub origin: String,
+    pub sent_at: chrono::DateTime<chrono::Utc>,
+    pub message: BusMessage,
+}
+
+impl BusEnvelope {
+    /// Whether this instance published the message
+    pub fn is_local(&self) -> bool {
+        self.origin == *INSTANCE_ID
+    }
+}
+
+/// Typed publish/subscribe over one channel
+#[derive(Clone)]
+pub struct EventBus {
+    cache: Arc<dyn CacheBackend>,
+    channel: String,
+}
+
+impl EventBus {
+    pub fn new(cache: &Arc<dyn CacheBackend>) -> Self {
+        Self {
+            cache: cache.clone(),
+            channel: DEFAULT_CHANNEL.to_string(),
+        }
+    }
+
+    /// Use a different channel, e.g. to keep environments sharing a server apart
+    pub fn with_channel(mut self, channel: impl Into<String>) -> Self {
+        self.channel = channel.into();
+        self
+    }
+
+    /// This process's instance id, as stamped on the messages it publishes
+    pub fn instance_id() -> &'static str {
+        &INSTANCE_ID
+    }
+
+    /// Publish a message, returning how many subscribers received it
+    pub async fn publish(&self, message: BusMessage) -> Result<u64> {
+        let envelope = BusEnvelope {
+            origin: INSTANCE_ID.clone(),
+            sent_at: chrono::Utc::now(),
+            message,
+        };
+        self.cache.publish(&self.channel, &serde_json::to_string(&envelope)?).await
+    }
+
+    /// Subscribe to the channel; with DragonflyDB the stream ends if its
+    /// dedicated connection drops. Messages that don't decode (e.g. from a
+    /// newer version) are skipped.
+    pub async fn subscribe(&self) -> Result<impl Stream<Item = BusEnvelope> + Send + 'static> {
+        let payloads = self.cache.subscribe(&[&self.channel]).await?;
+
+        Ok(payloads.filter_map(|payload| async move {
+            match serde_json::from_str(&payload) {
+                Ok(envelope) => Some(envelope),
+                Err(e) => {
+                    tracing::warn!("Ignoring undecodable bus message: {}", e);
+                    None
+                }
+            }
+        }))
+    }
+}
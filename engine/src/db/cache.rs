@@ -4,43 +4,432 @@
 //! memory efficient. We use the standard redis crate to connect.
 //!
 //! Used for:
-//! - Webhook event queue
-//! - API response caching
+//! - Webhook event queue (implemented in `queue`)
+//! - Latest compliance status per repository
+//! - API response caching (including ETags for conditional platform requests)
 //! - Rate limiting
-//! - Session storage
+//! - Dashboard sessions (implemented in `session`)
+//! - Pub/sub between engine instances (typed in `bus`)
+//!
+//! The engine talks to the cache through [`CacheBackend`]. Setting
+//! `RSR_DRAGONFLY_URL=memory://` swaps DragonflyDB for an in-process
+//! [`MemoryCache`](super::memory::MemoryCache), for single-node and
+//! development use without a server.
+//!
+//! Keys are built with [`CacheKey`] as `rsr:{kind}[:v{schema}]:...`. Kinds
+//! holding serialized data carry a schema version, so changing a stored
+//! format means bumping [`CacheKind::schema_version`] rather than failing to
+//! decode old entries at runtime.
+
+use super::config::{DbConfig, DragonflyConfig};
+#[cfg(feature = "cache-dragonfly")]
+use super::error::DbError;
+use super::gc::GcReport;
+use super::queue::{ClaimedJob, DeliveryPolicy, JobConsumer, JobOutcome, NewJob, QueuePressure, QueuedJob};
+use super::session::Session;
+#[cfg(feature = "cache-dragonfly")]
+use crate::adapters::http::CachedResponse;
+use crate::adapters::http::EtagCache;
+use crate::{ComplianceStatus, RepoRef, Result};
+use futures::stream::BoxStream;
+#[cfg(feature = "cache-dragonfly")]
+use futures::StreamExt;
+#[cfg(feature = "cache-dragonfly")]
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+#[cfg(feature = "cache-dragonfly")]
+use once_cell::sync::Lazy;
+#[cfg(feature = "cache-dragonfly")]
+use redis::{AsyncCommands, IntoConnectionInfo};
+#[cfg(feature = "cache-dragonfly")]
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Reconnection attempts after the connection drops, with exponential
+/// backoff from 100ms up to `RECONNECT_MAX_DELAY_MS`
+#[cfg(feature = "cache-dragonfly")]
+const RECONNECT_RETRIES: usize = 6;
+#[cfg(feature = "cache-dragonfly")]
+const RECONNECT_MAX_DELAY_MS: u64 = 5_000;
+
+/// Kinds of cached data, each in its own key namespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheKind {
+    /// Latest compliance status of a repository (branch)
+    Compliance,
+    /// Intermediate check result for a commit
+    Check,
+    /// Latest scanned commit of a repository
+    CheckHead,
+    /// Processed webhook delivery ID
+    Delivery,
+    /// Conditional-request validators
+    Etag,
+    /// Distributed lock
+    Lock,
+    /// Job queue lists and sets
+    Queue,
+    /// Sliding-window request log
+    RateLimit,
+    /// Session data
+    Session,
+}
+
+impl CacheKind {
+    pub const ALL: [CacheKind; 9] = [
+        Self::Compliance,
+        Self::Check,
+        Self::CheckHead,
+        Self::Delivery,
+        Self::Etag,
+        Self::Lock,
+        Self::Queue,
+        Self::RateLimit,
+        Self::Session,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Compliance => "compliance",
+            Self::Check => "check",
+            Self::CheckHead => "check-head",
+            Self::Delivery => "delivery",
+            Self::Etag => "etag",
+            Self::Lock => "lock",
+            Self::Queue => "queue",
+            Self::RateLimit => "ratelimit",
+            Self::Session => "session",
+        }
+    }
+
+    /// Version of the stored format, for kinds holding serialized data.
+    /// Bumping it moves the kind to new keys, so entries in the old format
+    /// are never read back; they expire or are removed by GC.
+    pub fn schema_version(self) -> Option<u32> {
+        match self {
+            Self::Compliance | Self::Check | Self::Etag | Self::Session => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Prefix of this kind's keys across all schema versions, e.g. `rsr:compliance:`
+    pub fn namespace(self) -> String {
+        format!("rsr:{}:", self.as_str())
+    }
+
+    /// Prefix of this kind's current keys, e.g. `rsr:compliance:v1:`
+    pub fn prefix(self) -> String {
+        match self.schema_version() {
+            Some(version) => format!("{}v{}:", self.namespace(), version),
+            None => self.namespace(),
+        }
+    }
+
+    /// Kind of a key, from its namespace
+    pub fn of_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| key.starts_with(&kind.namespace()))
+    }
+}
+
+/// Structured cache key: `rsr:{kind}[:v{schema}]:{segment}:...`.
+///
+/// Repository segments take the `platform:owner/repo[@branch]` form, so
+/// repo-scoped keys can be found and migrated by pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    kind: CacheKind,
+    segments: Vec<String>,
+}
+
+impl CacheKey {
+    pub fn new(kind: CacheKind) -> Self {
+        Self {
+            kind,
+            segments: Vec::new(),
+        }
+    }
+
+    /// Repository identity, including its branch if set
+    pub fn repo(mut self, repo: &RepoRef) -> Self {
+        self.segments.push(match repo.branch {
+            Some(ref branch) => format!("{}@{}", repo_id(repo), branch),
+            None => repo_id(repo),
+        });
+        self
+    }
+
+    /// Repository identity, ignoring any branch
+    pub fn repo_id(mut self, repo: &RepoRef) -> Self {
+        self.segments.push(repo_id(repo));
+        self
+    }
+
+    pub fn segment(mut self, segment: impl std::fmt::Display) -> Self {
+        self.segments.push(segment.to_string());
+        self
+    }
+
+    pub fn kind(&self) -> CacheKind {
+        self.kind
+    }
+}
+
+impl std::fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.kind.prefix(), self.segments.join(":"))
+    }
+}
+
+/// Rename a repository's keys and replace it in the queue rings, so its
+/// waiting jobs are still found. Keys gone since they were listed are skipped.
+///
+/// KEYS: keys to rename (n), their new names (n), ready rings
+/// ARGV: n, old repository, new repository
+/// Returns: keys renamed
+#[cfg(feature = "cache-dragonfly")]
+static MIGRATE_REPO_KEYS: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        local n = tonumber(ARGV[1])
+        local renamed = 0
+        for i = 1, n do
+            if redis.call('EXISTS', KEYS[i]) == 1 then
+                redis.call('RENAME', KEYS[i], KEYS[n + i])
+                renamed = renamed + 1
+            end
+        end
+        for i = 2 * n + 1, #KEYS do
+            local at = redis.call('LPOS', KEYS[i], ARGV[2])
+            if at then
+                redis.call('LSET', KEYS[i], at, ARGV[3])
+            end
+        end
+        return renamed
+        "#,
+    )
+});
+
+/// Sliding-window log: one sorted-set member per request, scored by its time
+/// in milliseconds. Members are `{now}:{count}`, unique because requests in
+/// the same millisecond trim the same entries and so see increasing counts.
+///
+/// KEYS: request log
+/// ARGV: now (ms), window (ms), limit, consume flag
+/// Returns: allowed (1/0), requests in the window, ms until the oldest expires
+#[cfg(feature = "cache-dragonfly")]
+static SLIDING_WINDOW: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        local now = tonumber(ARGV[1])
+        local window = tonumber(ARGV[2])
+        redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now - window)
+        local count = redis.call('ZCARD', KEYS[1])
+        local allowed = 0
+        if count < tonumber(ARGV[3]) then
+            allowed = 1
+            if ARGV[4] == '1' then
+                redis.call('ZADD', KEYS[1], now, now .. ':' .. count)
+                redis.call('PEXPIRE', KEYS[1], window)
+                count = count + 1
+            end
+        end
+        local reset = 0
+        local oldest = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES')
+        if oldest[2] then
+            reset = tonumber(oldest[2]) + window - now
+        end
+        return {allowed, count, reset}
+        "#,
+    )
+});
+
+/// Outcome of a rate limit check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// Requests allowed per window
+    pub limit: u64,
+    /// Requests left in the current window
+    pub remaining: u64,
+    /// Seconds until the oldest request leaves the window and frees budget
+    pub reset_after_secs: u64,
+}
+
+impl RateLimitDecision {
+    /// How long a denied caller should wait before retrying
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        (!self.allowed).then_some(self.reset_after_secs.max(1))
+    }
+}
+
+/// Everything the engine stores in its cache. Implemented by
+/// [`DragonflyPool`] and, for single-node use, by
+/// [`MemoryCache`](super::memory::MemoryCache).
+#[async_trait::async_trait]
+pub trait CacheBackend: EtagCache {
+    async fn ping(&self) -> Result<()>;
+
+    /// Replace a dropped connection. Both backends reconnect on their own
+    /// (Dragonfly through its connection manager), so this is a no-op.
+    async fn reconnect(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Cache a repository's latest compliance status
+    async fn cache_compliance(&self, status: &ComplianceStatus, ttl_secs: u64) -> Result<()>;
+    /// A repository's cached compliance status
+    async fn get_compliance(&self, repo: &RepoRef) -> Result<Option<ComplianceStatus>>;
+    async fn cache_compliance_many(&self, statuses: &[ComplianceStatus], ttl_secs: u64) -> Result<()>;
+    /// Cached statuses in the order of `repos`
+    async fn get_compliance_many(&self, repos: &[RepoRef]) -> Result<Vec<Option<ComplianceStatus>>>;
+    async fn invalidate_compliance(&self, repo: &RepoRef) -> Result<()>;
+
+    /// Cache an intermediate check result, recording `commit_sha` as the
+    /// repository's latest scanned commit
+    async fn cache_check_result(&self, repo: &RepoRef, commit_sha: &str, check_id: &str, value: &str, ttl_secs: u64) -> Result<()>;
+    async fn get_check_result(&self, repo: &RepoRef, commit_sha: &str, check_id: &str) -> Result<Option<String>>;
+
+    /// Publish `payload` on `channel`, returning how many subscribers received it
+    async fn publish(&self, channel: &str, payload: &str) -> Result<u64>;
+    /// Payloads published on `channels` from now on
+    async fn subscribe(&self, channels: &[&str]) -> Result<BoxStream<'static, String>>;
+
+    /// Record a webhook delivery ID, returning false if it was already seen
+    async fn record_delivery(&self, platform: &str, delivery_id: &str, ttl_secs: u64) -> Result<bool>;
+    async fn forget_delivery(&self, platform: &str, delivery_id: &str) -> Result<()>;
+
+    /// Take one request from `key`'s sliding-window budget
+    async fn rate_limit(&self, key: &str, max_requests: u64, window_secs: u64) -> Result<RateLimitDecision>;
+    /// Whether `key` has budget left, without spending any
+    async fn rate_limit_check(&self, key: &str, max_requests: u64, window_secs: u64) -> Result<RateLimitDecision>;
+
+    /// Start a session, returning its ID; expiry slides on each read or touch
+    async fn create_session(&self, payload: &serde_json::Value, ttl_secs: u64) -> Result<String>;
+    /// Read a session, renewing its expiry
+    async fn get_session(&self, session_id: &str) -> Result<Option<Session<serde_json::Value>>>;
+    async fn touch_session(&self, session_id: &str) -> Result<bool>;
+    async fn revoke_session(&self, session_id: &str) -> Result<bool>;
+
+    /// Enqueue a job, returning its ID
+    async fn enqueue_job(&self, queue: &str, job: NewJob) -> Result<String>;
+    /// Queue a job from the outbox unless it was relayed in the last
+    /// `dedupe_secs`, returning whether it was queued
+    async fn relay_job(&self, queue: &str, job: &QueuedJob, dedupe_secs: u64) -> Result<bool>;
+    /// Consumer for a worker to claim jobs with
+    async fn consumer(&self, queue: &str) -> Result<Box<dyn JobConsumer>>;
+    async fn ack_job(&self, queue: &str, claimed: &ClaimedJob) -> Result<()>;
+    async fn fail_job(&self, queue: &str, claimed: &ClaimedJob, error: &str, policy: &DeliveryPolicy) -> Result<JobOutcome>;
+    /// Put a job back without spending an attempt, for a newer worker
+    async fn defer_job(&self, queue: &str, claimed: &ClaimedJob) -> Result<JobOutcome>;
+    /// Requeue jobs whose visibility timeout has passed
+    async fn requeue_expired(&self, queue: &str, policy: &DeliveryPolicy) -> Result<usize>;
+    /// Dead-lettered jobs, newest first
+    async fn dead_letters(&self, queue: &str, limit: usize) -> Result<Vec<QueuedJob>>;
+    /// Requeue the dead-lettered job with `id`, or all of them
+    async fn requeue_dead_letters(&self, queue: &str, id: Option<&str>) -> Result<usize>;
+    async fn queue_pressure(&self, queue: &str) -> Result<QueuePressure>;
+    /// Hold a job back in `slot`, returning the job it replaced there
+    async fn park_job(&self, queue: &str, slot: &str, job: NewJob) -> Result<Option<QueuedJob>>;
+    /// Parked jobs by slot
+    async fn parked_jobs(&self, queue: &str) -> Result<Vec<(String, QueuedJob)>>;
+    /// Queue the job parked in `slot` if it is still `job`
+    async fn unpark_job(&self, queue: &str, slot: &str, job: &QueuedJob) -> Result<bool>;
+
+    /// Move repo-scoped entries after a transfer or rename
+    async fn migrate_repo_keys(&self, from: &RepoRef, to: &RepoRef) -> Result<usize>;
+    /// Run one GC pass. With `dry_run` nothing is deleted.
+    async fn collect_garbage(&self, dry_run: bool) -> Result<GcReport>;
+}
+
+/// Connect to the backend named by `RSR_DRAGONFLY_URL` (see [`super::config`])
+pub async fn connect_from_env() -> Result<Arc<dyn CacheBackend>> {
+    connect_with(&DbConfig::load()?.dragonfly).await
+}
+
+/// Connect to DragonflyDB at `url`, or use an in-memory cache for `memory://`
+pub async fn connect(url: &str) -> Result<Arc<dyn CacheBackend>> {
+    connect_with(&DragonflyConfig {
+        url: url.to_string(),
+        ..Default::default()
+    })
+    .await
+}
+
+/// Connect to DragonflyDB as configured, or use an in-memory cache for `memory://`
+pub async fn connect_with(config: &DragonflyConfig) -> Result<Arc<dyn CacheBackend>> {
+    if config.url.starts_with("memory://") {
+        tracing::warn!("Using the in-memory cache; queues and sessions are lost on restart and not shared");
+        return Ok(Arc::new(super::memory::MemoryCache::new()));
+    }
 
-use crate::{Result, RsrError};
-use redis::aio::ConnectionManager;
-use redis::AsyncCommands;
+    #[cfg(feature = "cache-dragonfly")]
+    return Ok(Arc::new(DragonflyPool::connect_with(config).await?));
+    #[cfg(not(feature = "cache-dragonfly"))]
+    Err(crate::RsrError::Config(format!(
+        "RSR_DRAGONFLY_URL is {} but this build lacks the cache-dragonfly feature",
+        config.url
+    )))
+}
 
-/// DragonflyDB connection pool (Redis-compatible)
+/// DragonflyDB connection pool (Redis-compatible). Clones share the
+/// underlying connection.
+#[cfg(feature = "cache-dragonfly")]
+#[derive(Clone)]
 pub struct DragonflyPool {
     conn: ConnectionManager,
+    client: redis::Client,
     url: String,
 }
 
+#[cfg(feature = "cache-dragonfly")]
 impl DragonflyPool {
-    /// Connect from environment variables
-    pub async fn connect_from_env() -> Result<Self> {
-        let url = std::env::var("RSR_DRAGONFLY_URL")
-            .unwrap_or_else(|_| "redis://localhost:6379".to_string());
-
-        Self::connect(&url).await
+    /// Connect to DragonflyDB with default settings
+    pub async fn connect(url: &str) -> Result<Self> {
+        Self::connect_with(&DragonflyConfig {
+            url: url.to_string(),
+            ..Default::default()
+        })
+        .await
     }
 
-    /// Connect to DragonflyDB
-    pub async fn connect(url: &str) -> Result<Self> {
+    /// Connect to DragonflyDB, over TLS for `rediss://` URLs
+    pub async fn connect_with(config: &DragonflyConfig) -> Result<Self> {
+        let url = config.url.as_str();
         tracing::info!("Connecting to DragonflyDB: {}", url);
 
-        let client = redis::Client::open(url)
-            .map_err(|e| RsrError::Platform(format!("Redis client error: {}", e)))?;
+        let mut info = url
+            .into_connection_info()
+            .map_err(|e| DbError::redis("Invalid Redis URL", e))?;
+        if let Some(username) = &config.credentials.username {
+            info.redis.username = Some(username.clone());
+        }
+        if let Some(password) = &config.credentials.password {
+            info.redis.password = Some(password.clone());
+        }
 
-        let conn = ConnectionManager::new(client)
+        let client = if config.tls.is_custom() {
+            redis::Client::build_with_tls(info, config.tls.redis_certificates()?)
+        } else {
+            redis::Client::open(info)
+        }
+        .map_err(|e| DbError::redis("Redis client error", e))?;
+
+        // The manager reconnects in the background when the connection is lost;
+        // commands issued meanwhile fail instead of queueing indefinitely
+        let config = ConnectionManagerConfig::new()
+            .set_number_of_retries(RECONNECT_RETRIES)
+            .set_exponent_base(2)
+            .set_factor(100)
+            .set_max_delay(RECONNECT_MAX_DELAY_MS)
+            .set_response_timeout(config.response_timeout())
+            .set_connection_timeout(config.connect_timeout());
+
+        let conn = ConnectionManager::new_with_config(client.clone(), config)
             .await
-            .map_err(|e| RsrError::Platform(format!("Redis connection error: {}", e)))?;
+            .map_err(|e| DbError::redis("Redis connection error", e))?;
 
         Ok(Self {
             conn,
+            client,
             url: url.to_string(),
         })
     }
@@ -51,134 +440,560 @@ pub async fn ping(&self) -> Result<()> {
         redis::cmd("PING")
             .query_async::<String>(&mut conn)
             .await
-            .map_err(|e| RsrError::Platform(format!("Redis ping failed: {}", e)))?;
+            .map_err(|e| DbError::redis("Redis ping failed", e))?;
         tracing::debug!("DragonflyDB ping successful at {}", self.url);
         Ok(())
     }
 
-    /// Cache a compliance result
-    pub async fn cache_compliance(&self, key: &str, value: &str, ttl_secs: u64) -> Result<()> {
+    /// Cache a repository's latest compliance status
+    pub async fn cache_compliance(&self, status: &ComplianceStatus, ttl_secs: u64) -> Result<()> {
         let mut conn = self.conn.clone();
-        let cache_key = format!("rsr:compliance:{}", key);
 
-        conn.set_ex::<_, _, ()>(&cache_key, value, ttl_secs)
+        conn.set_ex::<_, _, ()>(compliance_key(&status.repo), serde_json::to_string(status)?, ttl_secs)
             .await
-            .map_err(|e| RsrError::Platform(format!("Redis set failed: {}", e)))?;
+            .map_err(|e| DbError::redis("Redis set failed", e))?;
 
-        tracing::debug!("Cached compliance result: {} (TTL: {}s)", key, ttl_secs);
+        tracing::debug!("Cached compliance status: {} (TTL: {}s)", status.repo, ttl_secs);
         Ok(())
     }
 
-    /// Get cached compliance result
-    pub async fn get_compliance(&self, key: &str) -> Result<Option<String>> {
+    /// Get a repository's cached compliance status. Entries that no longer
+    /// deserialize (e.g. written by an older version) are treated as misses.
+    pub async fn get_compliance(&self, repo: &RepoRef) -> Result<Option<ComplianceStatus>> {
+        let mut conn = self.conn.clone();
+
+        let cached: Option<String> = conn
+            .get(compliance_key(repo))
+            .await
+            .map_err(|e| DbError::redis("Redis get failed", e))?;
+
+        tracing::debug!("Cache lookup for {}: {:?}", repo, cached.is_some());
+        Ok(cached.and_then(|json| decode_status(repo, &json)))
+    }
+
+    /// Cache several repositories' statuses in one round trip
+    pub async fn cache_compliance_many(&self, statuses: &[ComplianceStatus], ttl_secs: u64) -> Result<()> {
+        if statuses.is_empty() {
+            return Ok(());
+        }
         let mut conn = self.conn.clone();
-        let cache_key = format!("rsr:compliance:{}", key);
 
-        let result: Option<String> = conn
-            .get(&cache_key)
+        let mut pipe = redis::pipe();
+        for status in statuses {
+            pipe.set_ex(compliance_key(&status.repo), serde_json::to_string(status)?, ttl_secs)
+                .ignore();
+        }
+        pipe.query_async::<()>(&mut conn)
             .await
-            .map_err(|e| RsrError::Platform(format!("Redis get failed: {}", e)))?;
+            .map_err(|e| DbError::redis("Redis set failed", e).into())
+    }
 
-        tracing::debug!("Cache lookup for {}: {:?}", key, result.is_some());
-        Ok(result)
+    /// Get several repositories' cached statuses in one round trip, in the
+    /// order of `repos`
+    pub async fn get_compliance_many(&self, repos: &[RepoRef]) -> Result<Vec<Option<ComplianceStatus>>> {
+        if repos.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.conn.clone();
+
+        let mut pipe = redis::pipe();
+        for repo in repos {
+            pipe.get(compliance_key(repo));
+        }
+        let cached: Vec<Option<String>> = pipe
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis get failed", e))?;
+
+        Ok(repos
+            .iter()
+            .zip(cached)
+            .map(|(repo, json)| json.and_then(|json| decode_status(repo, &json)))
+            .collect())
     }
 
-    /// Enqueue a job for background processing
-    pub async fn enqueue_job(&self, queue: &str, job: &str) -> Result<()> {
+    /// Drop a repository's cached status, e.g. after a rescan was requested
+    pub async fn invalidate_compliance(&self, repo: &RepoRef) -> Result<()> {
         let mut conn = self.conn.clone();
-        let queue_key = format!("rsr:queue:{}", queue);
 
-        conn.lpush::<_, _, ()>(&queue_key, job)
+        conn.del::<_, ()>(compliance_key(repo))
             .await
-            .map_err(|e| RsrError::Platform(format!("Redis lpush failed: {}", e)))?;
+            .map_err(|e| DbError::redis("Redis del failed", e).into())
+    }
+
+    /// Cache an intermediate check result for a commit.
+    ///
+    /// Also records `commit_sha` as the repository's latest scanned commit, so
+    /// results for older commits become superseded and are pruned by GC.
+    pub async fn cache_check_result(
+        &self,
+        repo: &RepoRef,
+        commit_sha: &str,
+        check_id: &str,
+        value: &str,
+        ttl_secs: u64,
+    ) -> Result<()> {
+        let mut conn = self.conn.clone();
+
+        redis::pipe()
+            .atomic()
+            .set_ex(check_result_key(repo, commit_sha, check_id), value, ttl_secs)
+            .ignore()
+            .set_ex(check_head_key(repo), commit_sha, ttl_secs)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis set failed", e))?;
 
-        tracing::debug!("Enqueued job to {}", queue);
         Ok(())
     }
 
-    /// Dequeue a job for processing (blocking with timeout)
-    pub async fn dequeue_job(&self, queue: &str, timeout_secs: u64) -> Result<Option<String>> {
+    /// Get a cached intermediate check result
+    pub async fn get_check_result(&self, repo: &RepoRef, commit_sha: &str, check_id: &str) -> Result<Option<String>> {
         let mut conn = self.conn.clone();
-        let queue_key = format!("rsr:queue:{}", queue);
 
-        let result: Option<(String, String)> = conn
-            .brpop(&queue_key, timeout_secs as f64)
+        conn.get(check_result_key(repo, commit_sha, check_id))
             .await
-            .map_err(|e| RsrError::Platform(format!("Redis brpop failed: {}", e)))?;
+            .map_err(|e| DbError::redis("Redis get failed", e).into())
+    }
+
+    /// Shared connection for other modules in the db layer
+    pub(super) fn connection(&self) -> ConnectionManager {
+        self.conn.clone()
+    }
 
-        Ok(result.map(|(_, job)| job))
+    /// Client for opening dedicated connections
+    pub(super) fn client(&self) -> &redis::Client {
+        &self.client
     }
 
-    /// Increment rate limit counter with sliding window
-    pub async fn rate_limit_increment(&self, key: &str, window_secs: u64) -> Result<u64> {
+    /// Publish `payload` on `channel`, returning how many subscribers received it
+    pub async fn publish(&self, channel: &str, payload: &str) -> Result<u64> {
         let mut conn = self.conn.clone();
-        let rate_key = format!("rsr:ratelimit:{}", key);
 
-        // Use INCR + EXPIRE for simple rate limiting
-        let count: u64 = conn
-            .incr(&rate_key, 1u64)
+        conn.publish(channel, payload)
             .await
-            .map_err(|e| RsrError::Platform(format!("Redis incr failed: {}", e)))?;
+            .map_err(|e| DbError::redis("Redis publish failed", e).into())
+    }
 
-        // Set expiry only on first increment
-        if count == 1 {
-            conn.expire::<_, ()>(&rate_key, window_secs as i64)
+    /// Subscribe to `channels` on a dedicated connection (a subscribed
+    /// connection can't run other commands)
+    pub async fn subscribe(&self, channels: &[&str]) -> Result<redis::aio::PubSub> {
+        let mut pubsub = self
+            .client
+            .get_async_pubsub()
+            .await
+            .map_err(|e| DbError::redis("Redis pub/sub connection failed", e))?;
+        for channel in channels {
+            pubsub
+                .subscribe(*channel)
                 .await
-                .map_err(|e| RsrError::Platform(format!("Redis expire failed: {}", e)))?;
+                .map_err(|e| DbError::redis("Redis subscribe failed", e))?;
         }
 
-        tracing::debug!("Rate limit {}: {} (window: {}s)", key, count, window_secs);
-        Ok(count)
+        Ok(pubsub)
     }
 
-    /// Check if rate limited (returns remaining requests, 0 if limited)
-    pub async fn rate_limit_check(&self, key: &str, max_requests: u64) -> Result<u64> {
+    /// Record a webhook delivery ID, returning false if it was already seen
+    /// within `ttl_secs` (a replay or duplicate delivery)
+    pub async fn record_delivery(&self, platform: &str, delivery_id: &str, ttl_secs: u64) -> Result<bool> {
         let mut conn = self.conn.clone();
-        let rate_key = format!("rsr:ratelimit:{}", key);
 
-        let count: u64 = conn
-            .get(&rate_key)
+        let first: Option<String> = redis::cmd("SET")
+            .arg(delivery_key(platform, delivery_id))
+            .arg(chrono::Utc::now().timestamp())
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_secs)
+            .query_async(&mut conn)
             .await
-            .unwrap_or(0);
+            .map_err(|e| DbError::redis("Redis set failed", e))?;
 
-        if count >= max_requests {
-            Ok(0)
-        } else {
-            Ok(max_requests - count)
-        }
+        Ok(first.is_some())
     }
 
-    /// Store session data
-    pub async fn set_session(&self, session_id: &str, data: &str, ttl_secs: u64) -> Result<()> {
+    /// Forget a delivery so the platform's retry of a failed delivery is accepted
+    pub async fn forget_delivery(&self, platform: &str, delivery_id: &str) -> Result<()> {
         let mut conn = self.conn.clone();
-        let session_key = format!("rsr:session:{}", session_id);
 
-        conn.set_ex::<_, _, ()>(&session_key, data, ttl_secs)
+        conn.del::<_, ()>(delivery_key(platform, delivery_id))
             .await
-            .map_err(|e| RsrError::Platform(format!("Redis session set failed: {}", e)))?;
+            .map_err(|e| DbError::redis("Redis del failed", e).into())
+    }
 
-        Ok(())
+    /// Take one request from `key`'s sliding-window budget of
+    /// `max_requests` per `window_secs`. Denied requests don't count.
+    pub async fn rate_limit(&self, key: &str, max_requests: u64, window_secs: u64) -> Result<RateLimitDecision> {
+        self.sliding_window(key, max_requests, window_secs, true).await
+    }
+
+    /// Whether `key` has budget left, without spending any
+    pub async fn rate_limit_check(&self, key: &str, max_requests: u64, window_secs: u64) -> Result<RateLimitDecision> {
+        self.sliding_window(key, max_requests, window_secs, false).await
+    }
+
+    async fn sliding_window(&self, key: &str, max_requests: u64, window_secs: u64, consume: bool) -> Result<RateLimitDecision> {
+        let mut conn = self.conn.clone();
+        let window_ms = window_secs.saturating_mul(1000).max(1);
+
+        let (allowed, count, reset_ms): (u8, u64, u64) = SLIDING_WINDOW
+            .key(CacheKey::new(CacheKind::RateLimit).segment(key).to_string())
+            .arg(chrono::Utc::now().timestamp_millis())
+            .arg(window_ms)
+            .arg(max_requests)
+            .arg(if consume { "1" } else { "0" })
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis rate limit failed", e))?;
+
+        let decision = RateLimitDecision {
+            allowed: allowed == 1,
+            limit: max_requests,
+            remaining: max_requests.saturating_sub(count),
+            reset_after_secs: reset_ms.div_ceil(1000),
+        };
+        tracing::debug!("Rate limit {}: {}/{} (window: {}s)", key, count, max_requests, window_secs);
+        Ok(decision)
+    }
+
+    /// Move every repo-scoped key from `from` to `to` after a transfer or rename.
+    ///
+    /// Keys embed the repository reference (`platform:owner/repo`) as a whole
+    /// segment, so they are found by pattern and matched segment by segment.
+    /// The keys are renamed, and queue rings pointed at the new repository,
+    /// in one script. RENAME keeps each key's TTL.
+    pub async fn migrate_repo_keys(&self, from: &RepoRef, to: &RepoRef) -> Result<usize> {
+        let from_id = repo_id(from);
+        let to_id = repo_id(to);
+
+        let renames: Vec<(String, String)> = self
+            .scan_keys(&format!("rsr:*:{}*", escape_glob(&from_id)))
+            .await?
+            .into_iter()
+            .filter_map(|key| rename_repo_segment(&key, &from_id, &to_id).map(|renamed| (key, renamed)))
+            .collect();
+        if renames.is_empty() {
+            return Ok(0);
+        }
+        let rings = self.scan_keys(&format!("{}*:ready:*", CacheKind::Queue.prefix())).await?;
+
+        let mut script = MIGRATE_REPO_KEYS.prepare_invoke();
+        for (key, _) in &renames {
+            script.key(key);
+        }
+        for (_, renamed) in &renames {
+            script.key(renamed);
+        }
+        for ring in &rings {
+            script.key(ring);
+        }
+        let migrated: usize = script
+            .arg(renames.len())
+            .arg(&from_id)
+            .arg(&to_id)
+            .invoke_async(&mut self.conn.clone())
+            .await
+            .map_err(|e| DbError::redis("Redis rename failed", e))?;
+
+        tracing::info!("Migrated {} cache keys {} -> {}", migrated, from_id, to_id);
+        Ok(migrated)
+    }
+
+    /// Every key matching a SCAN `pattern`
+    async fn scan_keys(&self, pattern: &str) -> Result<Vec<String>> {
+        let mut conn = self.conn.clone();
+        let mut iter = conn
+            .scan_match::<_, String>(pattern)
+            .await
+            .map_err(|e| DbError::redis("Redis scan failed", e))?;
+
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+}
+
+#[cfg(feature = "cache-dragonfly")]
+#[async_trait::async_trait]
+impl CacheBackend for DragonflyPool {
+    async fn ping(&self) -> Result<()> {
+        DragonflyPool::ping(self).await
+    }
+
+    async fn cache_compliance(&self, status: &ComplianceStatus, ttl_secs: u64) -> Result<()> {
+        DragonflyPool::cache_compliance(self, status, ttl_secs).await
+    }
+
+    async fn get_compliance(&self, repo: &RepoRef) -> Result<Option<ComplianceStatus>> {
+        DragonflyPool::get_compliance(self, repo).await
+    }
+
+    async fn cache_compliance_many(&self, statuses: &[ComplianceStatus], ttl_secs: u64) -> Result<()> {
+        DragonflyPool::cache_compliance_many(self, statuses, ttl_secs).await
+    }
+
+    async fn get_compliance_many(&self, repos: &[RepoRef]) -> Result<Vec<Option<ComplianceStatus>>> {
+        DragonflyPool::get_compliance_many(self, repos).await
+    }
+
+    async fn invalidate_compliance(&self, repo: &RepoRef) -> Result<()> {
+        DragonflyPool::invalidate_compliance(self, repo).await
+    }
+
+    async fn cache_check_result(&self, repo: &RepoRef, commit_sha: &str, check_id: &str, value: &str, ttl_secs: u64) -> Result<()> {
+        DragonflyPool::cache_check_result(self, repo, commit_sha, check_id, value, ttl_secs).await
+    }
+
+    async fn get_check_result(&self, repo: &RepoRef, commit_sha: &str, check_id: &str) -> Result<Option<String>> {
+        DragonflyPool::get_check_result(self, repo, commit_sha, check_id).await
+    }
+
+    async fn publish(&self, channel: &str, payload: &str) -> Result<u64> {
+        DragonflyPool::publish(self, channel, payload).await
+    }
+
+    async fn subscribe(&self, channels: &[&str]) -> Result<BoxStream<'static, String>> {
+        let pubsub = DragonflyPool::subscribe(self, channels).await?;
+        Ok(pubsub
+            .into_on_message()
+            .filter_map(|msg| async move { msg.get_payload().ok() })
+            .boxed())
+    }
+
+    async fn record_delivery(&self, platform: &str, delivery_id: &str, ttl_secs: u64) -> Result<bool> {
+        DragonflyPool::record_delivery(self, platform, delivery_id, ttl_secs).await
+    }
+
+    async fn forget_delivery(&self, platform: &str, delivery_id: &str) -> Result<()> {
+        DragonflyPool::forget_delivery(self, platform, delivery_id).await
+    }
+
+    async fn rate_limit(&self, key: &str, max_requests: u64, window_secs: u64) -> Result<RateLimitDecision> {
+        DragonflyPool::rate_limit(self, key, max_requests, window_secs).await
+    }
+
+    async fn rate_limit_check(&self, key: &str, max_requests: u64, window_secs: u64) -> Result<RateLimitDecision> {
+        DragonflyPool::rate_limit_check(self, key, max_requests, window_secs).await
+    }
+
+    async fn create_session(&self, payload: &serde_json::Value, ttl_secs: u64) -> Result<String> {
+        DragonflyPool::create_session(self, payload, ttl_secs).await
+    }
+
+    async fn get_session(&self, session_id: &str) -> Result<Option<Session<serde_json::Value>>> {
+        DragonflyPool::get_session(self, session_id).await
+    }
+
+    async fn touch_session(&self, session_id: &str) -> Result<bool> {
+        DragonflyPool::touch_session(self, session_id).await
+    }
+
+    async fn revoke_session(&self, session_id: &str) -> Result<bool> {
+        DragonflyPool::revoke_session(self, session_id).await
+    }
+
+    async fn enqueue_job(&self, queue: &str, job: NewJob) -> Result<String> {
+        DragonflyPool::enqueue_job(self, queue, job).await
+    }
+
+    async fn relay_job(&self, queue: &str, job: &QueuedJob, dedupe_secs: u64) -> Result<bool> {
+        DragonflyPool::relay_job(self, queue, job, dedupe_secs).await
+    }
+
+    async fn consumer(&self, queue: &str) -> Result<Box<dyn JobConsumer>> {
+        Ok(Box::new(DragonflyPool::consumer(self, queue).await?))
+    }
+
+    async fn ack_job(&self, queue: &str, claimed: &ClaimedJob) -> Result<()> {
+        DragonflyPool::ack_job(self, queue, claimed).await
+    }
+
+    async fn fail_job(&self, queue: &str, claimed: &ClaimedJob, error: &str, policy: &DeliveryPolicy) -> Result<JobOutcome> {
+        DragonflyPool::fail_job(self, queue, claimed, error, policy).await
+    }
+
+    async fn defer_job(&self, queue: &str, claimed: &ClaimedJob) -> Result<JobOutcome> {
+        DragonflyPool::defer_job(self, queue, claimed).await
+    }
+
+    async fn requeue_expired(&self, queue: &str, policy: &DeliveryPolicy) -> Result<usize> {
+        DragonflyPool::requeue_expired(self, queue, policy).await
+    }
+
+    async fn dead_letters(&self, queue: &str, limit: usize) -> Result<Vec<QueuedJob>> {
+        DragonflyPool::dead_letters(self, queue, limit).await
+    }
+
+    async fn requeue_dead_letters(&self, queue: &str, id: Option<&str>) -> Result<usize> {
+        DragonflyPool::requeue_dead_letters(self, queue, id).await
     }
 
-    /// Get session data
-    pub async fn get_session(&self, session_id: &str) -> Result<Option<String>> {
+    async fn queue_pressure(&self, queue: &str) -> Result<QueuePressure> {
+        DragonflyPool::queue_pressure(self, queue).await
+    }
+
+    async fn park_job(&self, queue: &str, slot: &str, job: NewJob) -> Result<Option<QueuedJob>> {
+        DragonflyPool::park_job(self, queue, slot, job).await
+    }
+
+    async fn parked_jobs(&self, queue: &str) -> Result<Vec<(String, QueuedJob)>> {
+        DragonflyPool::parked_jobs(self, queue).await
+    }
+
+    async fn unpark_job(&self, queue: &str, slot: &str, job: &QueuedJob) -> Result<bool> {
+        DragonflyPool::unpark_job(self, queue, slot, job).await
+    }
+
+    async fn migrate_repo_keys(&self, from: &RepoRef, to: &RepoRef) -> Result<usize> {
+        DragonflyPool::migrate_repo_keys(self, from, to).await
+    }
+
+    async fn collect_garbage(&self, dry_run: bool) -> Result<GcReport> {
+        DragonflyPool::collect_garbage(self, dry_run).await
+    }
+}
+
+/// Conditional-request validators, keyed `rsr:etag:v1:{platform}:{digest}`.
+/// Each entry is a hash of the ETag, the body it validates and the next-page link.
+#[cfg(feature = "cache-dragonfly")]
+#[async_trait::async_trait]
+impl EtagCache for DragonflyPool {
+    async fn get_etag(&self, key: &str) -> Result<Option<CachedResponse>> {
         let mut conn = self.conn.clone();
-        let session_key = format!("rsr:session:{}", session_id);
 
-        conn.get(&session_key)
+        let mut fields: HashMap<String, Vec<u8>> = conn
+            .hgetall(CacheKey::new(CacheKind::Etag).segment(key).to_string())
             .await
-            .map_err(|e| RsrError::Platform(format!("Redis session get failed: {}", e)))
+            .map_err(|e| DbError::redis("Redis hgetall failed", e))?;
+
+        let (Some(etag), Some(body)) = (fields.remove("etag"), fields.remove("body")) else {
+            return Ok(None);
+        };
+
+        Ok(Some(CachedResponse {
+            etag: String::from_utf8_lossy(&etag).into_owned(),
+            body,
+            next_page: fields
+                .remove("next")
+                .map(|next| String::from_utf8_lossy(&next).into_owned()),
+        }))
     }
 
-    /// Delete session
-    pub async fn delete_session(&self, session_id: &str) -> Result<()> {
+    async fn put_etag(&self, key: &str, entry: &CachedResponse, ttl_secs: u64) -> Result<()> {
         let mut conn = self.conn.clone();
-        let session_key = format!("rsr:session:{}", session_id);
+        let cache_key = CacheKey::new(CacheKind::Etag).segment(key).to_string();
+
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .del(&cache_key)
+            .ignore()
+            .hset(&cache_key, "etag", &entry.etag)
+            .ignore()
+            .hset(&cache_key, "body", entry.body.as_slice())
+            .ignore();
+        if let Some(ref next) = entry.next_page {
+            pipe.hset(&cache_key, "next", next).ignore();
+        }
+        pipe.expire(&cache_key, ttl_secs as i64).ignore();
 
-        conn.del::<_, ()>(&session_key)
+        pipe.query_async::<()>(&mut conn)
             .await
-            .map_err(|e| RsrError::Platform(format!("Redis session del failed: {}", e)))?;
+            .map_err(|e| DbError::redis("Redis set failed", e))?;
 
         Ok(())
     }
 }
+
+/// Key for a repository's cached status
+pub(super) fn compliance_key(repo: &RepoRef) -> String {
+    CacheKey::new(CacheKind::Compliance).repo(repo).to_string()
+}
+
+pub(super) fn decode_status(repo: &RepoRef, json: &str) -> Option<ComplianceStatus> {
+    serde_json::from_str(json)
+        .map_err(|e| tracing::warn!("Discarding unreadable cached status for {}: {}", repo, e))
+        .ok()
+}
+
+/// Key marking a processed webhook delivery
+pub(super) fn delivery_key(platform: &str, delivery_id: &str) -> String {
+    CacheKey::new(CacheKind::Delivery).segment(platform).segment(delivery_id).to_string()
+}
+
+/// Key for an intermediate check result
+pub(super) fn check_result_key(repo: &RepoRef, commit_sha: &str, check_id: &str) -> String {
+    CacheKey::new(CacheKind::Check)
+        .repo_id(repo)
+        .segment(commit_sha)
+        .segment(check_id)
+        .to_string()
+}
+
+/// Key holding the latest scanned commit of a repository
+pub(super) fn check_head_key(repo: &RepoRef) -> String {
+    CacheKey::new(CacheKind::CheckHead).repo_id(repo).to_string()
+}
+
+/// Repository identity used in keys - the display form without a branch
+pub(super) fn repo_id(repo: &RepoRef) -> String {
+    format!("{}:{}/{}", repo.platform, repo.owner, repo.repo)
+}
+
+/// `key` with the repository segment `from_id` replaced by `to_id`, or
+/// `None` if no segment is `from_id`. A segment is the whole identity, so
+/// `github:acme/app` doesn't match `github:acme/app-web`; it may carry a
+/// branch (`@main`).
+pub(super) fn rename_repo_segment(key: &str, from_id: &str, to_id: &str) -> Option<String> {
+    key.match_indices(from_id).find_map(|(at, _)| {
+        let (before, after) = (&key[..at], &key[at + from_id.len()..]);
+        let whole = before.ends_with(':') && (after.is_empty() || after.starts_with([':', '@']));
+        whole.then(|| format!("{}{}{}", before, to_id, after))
+    })
+}
+
+/// Escape glob metacharacters, for a SCAN pattern matching `text` literally
+#[cfg(feature = "cache-dragonfly")]
+fn escape_glob(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_segments_are_renamed_whole() {
+        let (from, to) = ("github:acme/app", "github:other/app");
+        let renamed = |key: &str| rename_repo_segment(key, from, to);
+
+        let cases = [
+            ("rsr:compliance:v1:github:acme/app", "rsr:compliance:v1:github:other/app"),
+            ("rsr:compliance:v1:github:acme/app@main", "rsr:compliance:v1:github:other/app@main"),
+            ("rsr:check:v1:github:acme/app:abc123:bronze.license", "rsr:check:v1:github:other/app:abc123:bronze.license"),
+            ("rsr:queue:events:jobs:high:github:acme/app", "rsr:queue:events:jobs:high:github:other/app"),
+        ];
+        for (key, expected) in cases {
+            assert_eq!(renamed(key).as_deref(), Some(expected));
+        }
+        // Same-prefix siblings and lookalikes keep their keys
+        assert_eq!(renamed("rsr:compliance:v1:github:acme/app-web"), None);
+        assert_eq!(renamed("rsr:check:v1:github:acme/application:abc123:bronze.license"), None);
+        assert_eq!(renamed("rsr:compliance:v1:mygithub:acme/app"), None);
+    }
+
+    #[cfg(feature = "cache-dragonfly")]
+    #[test]
+    fn glob_metacharacters_are_escaped() {
+        assert_eq!(escape_glob("gitea:acme/[app]*?"), r"gitea:acme/\[app\]\*\?");
+        assert_eq!(escape_glob(r"a\b"), r"a\\b");
+    }
+}
@@ -0,0 +1,89 @@
+//! CI run history
+//!
+//! Completed workflow runs reported by webhooks (`workflow_run` on GitHub),
+//! so a scan can tell whether a branch's build passes without asking the
+//! platform. Only the latest completed run of
+//! each workflow on each branch is kept.
+
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::SurrealPool;
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+use crate::events::{WorkflowConclusion, WorkflowEvent};
+#[cfg(feature = "documents-surrealdb")]
+use crate::{RepoRef, Result};
+use serde::{Deserialize, Serialize};
+
+/// The latest completed run of a workflow on a branch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CiRun {
+    pub workflow: String,
+    pub branch: String,
+    pub commit_sha: String,
+    pub conclusion: WorkflowConclusion,
+    pub completed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl CiRun {
+    /// The run a workflow event reports, if it has completed
+    pub fn from_event(event: &WorkflowEvent, completed_at: chrono::DateTime<chrono::Utc>) -> Option<Self> {
+        Some(Self {
+            workflow: event.workflow_name.clone(),
+            branch: event.branch.clone(),
+            commit_sha: event.commit_sha.clone(),
+            conclusion: event.conclusion?,
+            completed_at,
+        })
+    }
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealPool {
+    /// Record a completed run as its workflow's latest on its branch, unless
+    /// a later one is already recorded
+    pub async fn record_ci_run(&self, repo: &RepoRef, run: &CiRun) -> Result<()> {
+        self.client()
+            .query(
+                "LET $at = <datetime> $completed_at; \
+                 UPSERT ci_run SET platform = $platform, owner = $owner, repo = $repo, \
+                    branch = $run.branch, workflow = $run.workflow, \
+                    commit_sha = IF completed_at > $at THEN commit_sha ELSE $run.commit_sha END, \
+                    conclusion = IF completed_at > $at THEN conclusion ELSE $run.conclusion END, \
+                    completed_at = IF completed_at > $at THEN completed_at ELSE $at END \
+                 WHERE platform = $platform AND owner = $owner AND repo = $repo \
+                    AND branch = $run.branch AND workflow = $run.workflow;",
+            )
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
+            .bind(("run", run.clone()))
+            .bind(("completed_at", run.completed_at.to_rfc3339()))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| DbError::surreal("SurrealDB upsert failed", e))?;
+
+        Ok(())
+    }
+
+    /// The latest completed run of each workflow on `branch`, by workflow name
+    pub async fn latest_ci_runs(&self, repo: &RepoRef, branch: &str) -> Result<Vec<CiRun>> {
+        let mut result = self
+            .client()
+            .query(
+                "SELECT workflow, branch, commit_sha, conclusion, <string> completed_at AS completed_at \
+                 FROM ci_run \
+                 WHERE platform = $platform AND owner = $owner AND repo = $repo AND branch = $branch \
+                 ORDER BY workflow",
+            )
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
+            .bind(("branch", branch.to_string()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        Ok(result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?)
+    }
+}
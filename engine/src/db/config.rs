@@ -0,0 +1,647 @@
+//! Connection settings for the database backends
+//!
+//! Each backend's address, credentials, TLS and connect timeout. Settings
+//! are read from the TOML file named by `RSR_DB_CONFIG`, if set, with one
+//! table per backend:
+//!
+//! ```toml
+//! [dragonfly]
+//! url = "rediss://cache.internal:6380"
+//! username = "rsr"
+//! password_file = "/run/secrets/dragonfly"
+//!
+//! [surrealdb]
+//! url = "wss://docs.internal:8000"
+//! auth = "namespace"
+//! username = "rsr"
+//! password_file = "/run/secrets/surrealdb"
+//! tls = { mode = "require", ca_bundle = "/etc/rsr/ca.pem" }
+//!
+//! [arangodb]
+//! url = "https://graphs.internal:8529"
+//! auth = "basic"
+//! ```
+//!
+//! Environment variables override the file: `RSR_{BACKEND}_URL`, `_USER`,
+//! `_PASS`, `_PASS_FILE`, `_TLS` (`auto` or `require`), `_CA_BUNDLE`,
+//! `_CLIENT_CERT`, `_CLIENT_KEY` and `_CONNECT_TIMEOUT_SECS`, where the
+//! backend is `DRAGONFLY`, `SURREALDB` or `ARANGODB`; plus
+//! `RSR_DRAGONFLY_RESPONSE_TIMEOUT_SECS`, `RSR_SURREALDB_NS`,
+//! `RSR_SURREALDB_DB`, `RSR_SURREALDB_AUTH`, `RSR_ARANGODB_DB` and
+//! `RSR_ARANGODB_AUTH`.
+//!
+//! Without an `auth` setting, SurrealDB signs in as root and ArangoDB with a
+//! JWT when a username is set, and neither signs in when none is.
+//!
+//! A table for any other backend, or a URL whose scheme the backend doesn't
+//! speak, is rejected rather than ignored.
+//!
+//! TLS follows the URL scheme (`rediss://`, `wss://`, `https://`); mode
+//! `require` refuses a plaintext URL instead. A CA bundle replaces the public
+//! roots, and a client certificate and key enable mutual TLS. The ArangoDB
+//! driver builds its own HTTP client, so it trusts the system roots and
+//! can't present a client certificate.
+
+use crate::{Result, RsrError};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Connection settings for every backend
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DbConfig {
+    pub dragonfly: DragonflyConfig,
+    pub surrealdb: SurrealConfig,
+    pub arangodb: ArangoConfig,
+}
+
+impl DbConfig {
+    /// Settings from `RSR_DB_CONFIG` and the environment, validated, with
+    /// password files read
+    pub fn load() -> Result<Self> {
+        let mut config = match std::env::var("RSR_DB_CONFIG") {
+            Ok(path) => Self::from_file(Path::new(&path))?,
+            Err(_) => Self::default(),
+        };
+        config.apply_env();
+        config.validate()?;
+        config.dragonfly.credentials.read_password_file("dragonfly")?;
+        config.surrealdb.credentials.read_password_file("surrealdb")?;
+        config.arangodb.credentials.read_password_file("arangodb")?;
+        Ok(config)
+    }
+
+    /// Parse settings from a TOML file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| RsrError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+        toml::from_str(&content)
+            .map_err(|e| RsrError::Config(format!("Invalid database configuration {}: {}", path.display(), e)))
+    }
+
+    /// Override settings with those set in the environment
+    pub fn apply_env(&mut self) {
+        self.dragonfly.url = env_or("RSR_DRAGONFLY_URL", &self.dragonfly.url);
+        self.dragonfly.credentials.apply_env("RSR_DRAGONFLY");
+        self.dragonfly.tls.apply_env("RSR_DRAGONFLY");
+        apply_env_secs("RSR_DRAGONFLY_CONNECT_TIMEOUT_SECS", &mut self.dragonfly.connect_timeout_secs);
+        apply_env_secs("RSR_DRAGONFLY_RESPONSE_TIMEOUT_SECS", &mut self.dragonfly.response_timeout_secs);
+
+        self.surrealdb.url = env_or("RSR_SURREALDB_URL", &self.surrealdb.url);
+        self.surrealdb.namespace = env_or("RSR_SURREALDB_NS", &self.surrealdb.namespace);
+        self.surrealdb.database = env_or("RSR_SURREALDB_DB", &self.surrealdb.database);
+        if let Some(auth) = env_parse("RSR_SURREALDB_AUTH") {
+            self.surrealdb.auth = Some(auth);
+        }
+        self.surrealdb.credentials.apply_env("RSR_SURREALDB");
+        self.surrealdb.tls.apply_env("RSR_SURREALDB");
+        apply_env_secs("RSR_SURREALDB_CONNECT_TIMEOUT_SECS", &mut self.surrealdb.connect_timeout_secs);
+
+        self.arangodb.url = env_or("RSR_ARANGODB_URL", &self.arangodb.url);
+        self.arangodb.database = env_or("RSR_ARANGODB_DB", &self.arangodb.database);
+        if let Some(auth) = env_parse("RSR_ARANGODB_AUTH") {
+            self.arangodb.auth = Some(auth);
+        }
+        self.arangodb.credentials.apply_env("RSR_ARANGODB");
+        self.arangodb.tls.apply_env("RSR_ARANGODB");
+        apply_env_secs("RSR_ARANGODB_CONNECT_TIMEOUT_SECS", &mut self.arangodb.connect_timeout_secs);
+    }
+
+    /// Check the settings for mistakes that parsing alone can't catch
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        let dragonfly_schemes = ["redis", "rediss", "redis+unix", "unix", "memory"];
+        problems.extend(unknown_scheme("dragonfly", &self.dragonfly.url, &dragonfly_schemes));
+        problems.extend(unknown_scheme("surrealdb", &self.surrealdb.url, &["ws", "wss", "http", "https", "mem"]));
+        problems.extend(unknown_scheme("arangodb", &self.arangodb.url, &["http", "https", "memory"]));
+
+        problems.extend(self.dragonfly.tls.validate("dragonfly", &self.dragonfly.url, &["rediss"], &["memory"]));
+        problems.extend(self.dragonfly.credentials.validate("dragonfly"));
+        if self.dragonfly.connect_timeout_secs == 0 || self.dragonfly.response_timeout_secs == 0 {
+            problems.push("dragonfly: timeouts must be at least 1 second".to_string());
+        }
+
+        problems.extend(self.surrealdb.tls.validate("surrealdb", &self.surrealdb.url, &["wss", "https"], &["mem"]));
+        problems.extend(self.surrealdb.credentials.validate("surrealdb"));
+        // In-process backends are never signed in to
+        let (auth, in_process) = (self.surrealdb.auth(), scheme(&self.surrealdb.url) == "mem");
+        if auth != SurrealAuth::None && self.surrealdb.credentials.username.is_none() && !in_process {
+            problems.push(format!("surrealdb: {:?} auth needs a username", auth).to_lowercase());
+        }
+        if self.surrealdb.connect_timeout_secs == 0 {
+            problems.push("surrealdb.connect_timeout_secs: must be at least 1".to_string());
+        }
+
+        problems.extend(self.arangodb.tls.validate("arangodb", &self.arangodb.url, &["https"], &["memory"]));
+        problems.extend(self.arangodb.credentials.validate("arangodb"));
+        if self.arangodb.tls.ca_bundle.is_some() || self.arangodb.tls.client_cert.is_some() {
+            problems.push(
+                "arangodb.tls: CA bundles and client certificates aren't supported; \
+                 add the CA to the system trust store"
+                    .to_string(),
+            );
+        }
+        let (auth, in_process) = (self.arangodb.auth(), scheme(&self.arangodb.url) == "memory");
+        if auth != ArangoAuth::None && self.arangodb.credentials.username.is_none() && !in_process {
+            problems.push(format!("arangodb: {:?} auth needs a username", auth).to_lowercase());
+        }
+        if self.arangodb.connect_timeout_secs == 0 {
+            problems.push("arangodb.connect_timeout_secs: must be at least 1".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(RsrError::Config(problems.join("; ")))
+        }
+    }
+}
+
+/// DragonflyDB (or Redis) connection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DragonflyConfig {
+    /// `redis://` or `rediss://` address, or `memory://` for the in-process cache
+    pub url: String,
+    /// ACL user and password, overriding any in the URL
+    #[serde(flatten)]
+    pub credentials: Credentials,
+    pub tls: TlsConfig,
+    pub connect_timeout_secs: u64,
+    /// How long a command may wait for its reply
+    pub response_timeout_secs: u64,
+}
+
+impl Default for DragonflyConfig {
+    fn default() -> Self {
+        Self {
+            url: "redis://localhost:6379".to_string(),
+            credentials: Credentials::default(),
+            tls: TlsConfig::default(),
+            connect_timeout_secs: 5,
+            response_timeout_secs: 5,
+        }
+    }
+}
+
+impl DragonflyConfig {
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+
+    pub fn response_timeout(&self) -> Duration {
+        Duration::from_secs(self.response_timeout_secs)
+    }
+}
+
+/// SurrealDB connection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SurrealConfig {
+    /// `ws://`, `wss://`, `http://` or `https://` address, or `mem://` for an
+    /// embedded database (which needs no credentials)
+    pub url: String,
+    pub namespace: String,
+    pub database: String,
+    /// Level the user is defined at; see [`SurrealConfig::auth`]
+    pub auth: Option<SurrealAuth>,
+    #[serde(flatten)]
+    pub credentials: Credentials,
+    pub tls: TlsConfig,
+    pub connect_timeout_secs: u64,
+}
+
+impl Default for SurrealConfig {
+    fn default() -> Self {
+        Self {
+            url: "ws://localhost:8000".to_string(),
+            namespace: "rsr".to_string(),
+            database: "compliance".to_string(),
+            auth: None,
+            credentials: Credentials {
+                username: Some("root".to_string()),
+                password: Some("root".to_string()),
+                password_file: None,
+            },
+            tls: TlsConfig::default(),
+            connect_timeout_secs: 10,
+        }
+    }
+}
+
+impl SurrealConfig {
+    /// How to sign in: as set, else as root if a username is given and not
+    /// at all if none is
+    pub fn auth(&self) -> SurrealAuth {
+        self.auth.unwrap_or(match self.credentials.username {
+            Some(_) => SurrealAuth::Root,
+            None => SurrealAuth::None,
+        })
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+}
+
+/// How to sign in to SurrealDB
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SurrealAuth {
+    /// Root user
+    #[default]
+    Root,
+    /// User defined on the namespace
+    Namespace,
+    /// User defined on the database
+    Database,
+    /// Don't sign in, for servers allowing guest access
+    None,
+}
+
+impl std::str::FromStr for SurrealAuth {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "root" => Ok(Self::Root),
+            "namespace" | "ns" => Ok(Self::Namespace),
+            "database" | "db" => Ok(Self::Database),
+            "none" => Ok(Self::None),
+            other => Err(format!("unknown SurrealDB auth {}", other)),
+        }
+    }
+}
+
+/// ArangoDB connection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArangoConfig {
+    /// `http://` or `https://` address, or `memory://` for the in-process graph
+    pub url: String,
+    pub database: String,
+    /// How to authenticate; see [`ArangoConfig::auth`]
+    pub auth: Option<ArangoAuth>,
+    #[serde(flatten)]
+    pub credentials: Credentials,
+    /// Only `mode` applies; see the module docs
+    pub tls: TlsConfig,
+    pub connect_timeout_secs: u64,
+}
+
+impl Default for ArangoConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:8529".to_string(),
+            database: "rsr_graphs".to_string(),
+            auth: None,
+            credentials: Credentials {
+                username: Some("root".to_string()),
+                password: Some(String::new()),
+                password_file: None,
+            },
+            tls: TlsConfig::default(),
+            connect_timeout_secs: 10,
+        }
+    }
+}
+
+impl ArangoConfig {
+    /// How to authenticate: as set, else with a JWT if a username is given
+    /// and not at all if none is
+    pub fn auth(&self) -> ArangoAuth {
+        self.auth.unwrap_or(match self.credentials.username {
+            Some(_) => ArangoAuth::Jwt,
+            None => ArangoAuth::None,
+        })
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+}
+
+/// How to authenticate to ArangoDB
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArangoAuth {
+    /// Exchange the credentials for a JWT once per connection
+    #[default]
+    Jwt,
+    /// Send the credentials with every request
+    Basic,
+    /// No authentication, for servers running without it
+    None,
+}
+
+impl std::str::FromStr for ArangoAuth {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "jwt" => Ok(Self::Jwt),
+            "basic" => Ok(Self::Basic),
+            "none" => Ok(Self::None),
+            other => Err(format!("unknown ArangoDB auth {}", other)),
+        }
+    }
+}
+
+/// A user and password. The password is given inline or read from
+/// `password_file` (such as a mounted secret) when the settings are loaded.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Credentials {
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub password_file: Option<PathBuf>,
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("password_file", &self.password_file)
+            .finish()
+    }
+}
+
+impl Credentials {
+    /// Username, or empty if there is none
+    pub fn username(&self) -> &str {
+        self.username.as_deref().unwrap_or_default()
+    }
+
+    /// Password, or empty if there is none
+    pub fn password(&self) -> &str {
+        self.password.as_deref().unwrap_or_default()
+    }
+
+    fn apply_env(&mut self, prefix: &str) {
+        if let Ok(username) = std::env::var(format!("{}_USER", prefix)) {
+            self.username = Some(username);
+        }
+        if let Ok(password) = std::env::var(format!("{}_PASS", prefix)) {
+            self.password = Some(password);
+            self.password_file = None;
+        }
+        if let Ok(path) = std::env::var(format!("{}_PASS_FILE", prefix)) {
+            self.password_file = Some(PathBuf::from(path));
+        }
+    }
+
+    fn validate(&self, section: &str) -> Vec<String> {
+        match (&self.password_file, &self.username) {
+            (Some(_), None) => vec![format!("{}.password_file: set without a username", section)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Replace the password with the contents of `password_file`, less a
+    /// trailing newline
+    fn read_password_file(&mut self, section: &str) -> Result<()> {
+        if let Some(path) = self.password_file.take() {
+            let password = std::fs::read_to_string(&path).map_err(|e| {
+                RsrError::Config(format!("{}.password_file: failed to read {}: {}", section, path.display(), e))
+            })?;
+            self.password = Some(password.trim_end_matches(['\r', '\n']).to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Lower-cased scheme of `url`, empty if it has none
+fn scheme(url: &str) -> String {
+    url.split_once("://").map(|(scheme, _)| scheme.to_lowercase()).unwrap_or_default()
+}
+
+/// A problem if `url` names a scheme other than `schemes`
+fn unknown_scheme(section: &str, url: &str, schemes: &[&str]) -> Option<String> {
+    let scheme = scheme(url);
+    (!schemes.contains(&scheme.as_str())).then(|| {
+        format!("{}.url: unsupported scheme in {} (use {}://)", section, url, schemes.join("://, "))
+    })
+}
+
+/// Whether a backend must be reached over TLS
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsMode {
+    /// TLS if the URL scheme asks for it
+    #[default]
+    Auto,
+    /// Refuse a plaintext URL
+    Require,
+}
+
+impl std::str::FromStr for TlsMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "require" | "required" => Ok(Self::Require),
+            other => Err(format!("unknown TLS mode {}", other)),
+        }
+    }
+}
+
+/// TLS settings for a backend connection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    pub mode: TlsMode,
+    /// PEM certificates to trust instead of the public roots
+    pub ca_bundle: Option<PathBuf>,
+    /// PEM certificate chain and key presented for mutual TLS
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    /// Whether a CA bundle or client certificate is configured
+    pub fn is_custom(&self) -> bool {
+        self.ca_bundle.is_some() || self.client_cert.is_some()
+    }
+
+    fn apply_env(&mut self, prefix: &str) {
+        if let Some(mode) = env_parse(&format!("{}_TLS", prefix)) {
+            self.mode = mode;
+        }
+        for (suffix, path) in [
+            ("CA_BUNDLE", &mut self.ca_bundle),
+            ("CLIENT_CERT", &mut self.client_cert),
+            ("CLIENT_KEY", &mut self.client_key),
+        ] {
+            if let Ok(value) = std::env::var(format!("{}_{}", prefix, suffix)) {
+                *path = Some(PathBuf::from(value));
+            }
+        }
+    }
+
+    /// Problems with the TLS settings for `url`, given the schemes that
+    /// connect over TLS and those that never leave the process
+    fn validate(&self, section: &str, url: &str, tls_schemes: &[&str], local_schemes: &[&str]) -> Vec<String> {
+        let mut problems = Vec::new();
+        let scheme = scheme(url);
+
+        if local_schemes.contains(&scheme.as_str()) {
+            return problems;
+        }
+        let tls = tls_schemes.contains(&scheme.as_str());
+        if self.mode == TlsMode::Require && !tls {
+            problems.push(format!(
+                "{}.url: TLS is required but {}:// is not a TLS scheme (use {}://)",
+                section, scheme, tls_schemes[0]
+            ));
+        } else if !tls && self.is_custom() {
+            problems.push(format!("{}.tls: certificates are set but {}:// doesn't use TLS", section, scheme));
+        }
+        if self.client_cert.is_some() != self.client_key.is_some() {
+            problems.push(format!("{}.tls: client_cert and client_key must be set together", section));
+        }
+        problems
+    }
+
+    #[cfg(feature = "cache-dragonfly")]
+    /// The CA bundle and client certificate in the form the Redis client
+    /// takes them
+    pub fn redis_certificates(&self) -> Result<redis::TlsCertificates> {
+        let client_tls = match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => Some(redis::ClientTlsConfig {
+                client_cert: read_pem(cert)?,
+                client_key: read_pem(key)?,
+            }),
+            _ => None,
+        };
+        let root_cert = self.ca_bundle.as_deref().map(read_pem).transpose()?;
+        Ok(redis::TlsCertificates { client_tls, root_cert })
+    }
+
+    /// A rustls client trusting the CA bundle (or the public roots) and
+    /// presenting the client certificate, if one is set
+    pub fn rustls_config(&self) -> Result<rustls::ClientConfig> {
+        use rustls::pki_types::pem::PemObject;
+        use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+        let invalid = |path: &Path, e: rustls::pki_types::pem::Error| {
+            RsrError::Config(format!("Invalid PEM in {}: {}", path.display(), e))
+        };
+
+        let mut roots = rustls::RootCertStore::empty();
+        match &self.ca_bundle {
+            Some(path) => {
+                let certs = CertificateDer::pem_file_iter(path)
+                    .and_then(|certs| certs.collect::<std::result::Result<Vec<_>, _>>())
+                    .map_err(|e| invalid(path, e))?;
+                let (added, ignored) = roots.add_parsable_certificates(certs);
+                if added == 0 {
+                    return Err(RsrError::Config(format!("No usable certificates in {}", path.display())));
+                }
+                if ignored > 0 {
+                    tracing::warn!("Ignored {} unparsable certificates in {}", ignored, path.display());
+                }
+            }
+            None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        }
+
+        let builder = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| RsrError::Config(format!("TLS setup failed: {}", e)))?
+            .with_root_certificates(roots);
+
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => {
+                let chain = CertificateDer::pem_file_iter(cert)
+                    .and_then(|certs| certs.collect::<std::result::Result<Vec<_>, _>>())
+                    .map_err(|e| invalid(cert, e))?;
+                let key = PrivateKeyDer::from_pem_file(key).map_err(|e| invalid(key, e))?;
+                builder
+                    .with_client_auth_cert(chain, key)
+                    .map_err(|e| RsrError::Config(format!("Invalid client certificate: {}", e)))
+            }
+            _ => Ok(builder.with_no_client_auth()),
+        }
+    }
+}
+
+#[cfg(feature = "cache-dragonfly")]
+fn read_pem(path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).map_err(|e| RsrError::Config(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+fn env_or(var: &str, current: &str) -> String {
+    std::env::var(var).unwrap_or_else(|_| current.to_string())
+}
+
+/// A setting from the environment, ignoring (with a warning) values that
+/// don't parse
+fn env_parse<T: std::str::FromStr<Err = String>>(var: &str) -> Option<T> {
+    let value = std::env::var(var).ok()?;
+    value
+        .parse()
+        .map_err(|e| tracing::warn!("Ignoring {}: {}", var, e))
+        .ok()
+}
+
+fn apply_env_secs(var: &str, secs: &mut u64) {
+    if let Some(value) = std::env::var(var).ok().and_then(|v| v.parse().ok()) {
+        *secs = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_valid() {
+        DbConfig::default().validate().unwrap();
+    }
+
+    #[test]
+    fn unknown_backend_table_is_rejected() {
+        let err = toml::from_str::<DbConfig>("[mongodb]\nurl = \"mongodb://localhost\"\n").unwrap_err();
+        assert!(err.to_string().contains("unknown field `mongodb`"), "{}", err);
+
+        // A misspelled backend would otherwise fall back to the defaults silently
+        assert!(toml::from_str::<DbConfig>("[surreal]\nurl = \"wss://docs.internal\"\n").is_err());
+    }
+
+    #[test]
+    fn url_a_backend_does_not_speak_is_rejected() {
+        let mut config = DbConfig::default();
+        config.dragonfly.url = "postgres://localhost/rsr".to_string();
+        config.arangodb.url = "mem://".to_string();
+
+        let problems = config.validate().unwrap_err().to_string();
+        assert!(problems.contains("dragonfly.url: unsupported scheme in postgres://localhost/rsr"), "{}", problems);
+        assert!(problems.contains("arangodb.url: unsupported scheme in mem://"), "{}", problems);
+        assert!(!problems.contains("surrealdb"), "{}", problems);
+    }
+
+    #[test]
+    fn in_process_backends_are_accepted() {
+        let config: DbConfig = toml::from_str(
+            "[dragonfly]\nurl = \"memory://\"\n[surrealdb]\nurl = \"mem://\"\n[arangodb]\nurl = \"memory://\"\n",
+        )
+        .unwrap();
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn auth_follows_the_username_unless_set() {
+        let config: DbConfig = toml::from_str("[surrealdb]\nurl = \"wss://docs.internal\"\n").unwrap();
+        assert_eq!(config.surrealdb.auth(), SurrealAuth::None);
+        assert_eq!(config.arangodb.auth(), ArangoAuth::Jwt);
+        config.validate().unwrap();
+
+        let config: DbConfig = toml::from_str("[arangodb]\nurl = \"https://graphs.internal\"\nauth = \"basic\"\n").unwrap();
+        let problems = config.validate().unwrap_err().to_string();
+        assert!(problems.contains("arangodb: basic auth needs a username"), "{}", problems);
+    }
+}
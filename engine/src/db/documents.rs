@@ -1,70 +1,599 @@
-//! SurrealDB document store
+//! Document store
 //!
 //! Used for:
 //! - Compliance reports
 //! - Repository metadata
 //! - User/organization data
 //! - Audit history
+//! - Webhook archive (raw deliveries, for replay)
+//!
+//! The engine talks to the store through [`DocumentStore`]. SurrealDB is the
+//! default backend; with the `documents-postgres` feature, setting
+//! `RSR_POSTGRES_URL` uses [`PostgresStore`](super::postgres::PostgresStore)
+//! instead, and with `documents-sqlite`, setting `RSR_SQLITE_PATH` uses an
+//! embedded [`SqliteStore`](super::sqlite::SqliteStore) file.
+//!
+//! The schema is managed by the migrations in [`super::migrations`].
 
-use crate::{ComplianceStatus, Result, RsrError};
+use super::annotations::Annotation;
+use super::audit::{AuditAction, AuditPage, AuditQuery};
+use super::ci_runs::CiRun;
+use super::config::{DbConfig, SurrealConfig};
+#[cfg(feature = "documents-surrealdb")]
+use super::config::{Credentials, SurrealAuth};
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+use super::migrations::Migration;
+use super::orgs::OrgSummary;
+use super::outbox::{BuildJob, OutboxEntry};
+use super::registry::RegisteredRepository;
+use super::retention::{PruneReport, RetentionPolicy};
+use super::trends::{ComplianceTrend, TrendWindow};
+use crate::adapters::strict::ParseWarning;
+use crate::{ComplianceStatus, RepoRef, Result};
 use serde::{Deserialize, Serialize};
-use surrealdb::engine::remote::ws::{Client, Ws};
-use surrealdb::opt::auth::Root;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+#[cfg(feature = "documents-surrealdb")]
+use surrealdb::engine::any::Any;
+#[cfg(feature = "documents-surrealdb")]
+use surrealdb::opt::auth::{Database, Namespace, Root};
+#[cfg(feature = "documents-surrealdb")]
 use surrealdb::Surreal;
 
+/// Most reports returned per page of history
+pub const MAX_HISTORY_PAGE: u32 = 100;
+
+/// Reports read per query while verifying a chain
+pub(super) const CHAIN_PAGE: u32 = 500;
+
+#[cfg(any(feature = "documents-surrealdb", feature = "documents-sqlite"))]
+/// Attempts at appending a report when other reports for the same
+/// repository keep landing first
+pub(super) const CHAIN_ATTEMPTS: usize = 3;
+
+#[cfg(feature = "documents-surrealdb")]
+/// Error thrown when the chain's head moved between reading and appending
+const CHAIN_MOVED: &str = "report chain moved";
+
+#[cfg(feature = "documents-surrealdb")]
 /// SurrealDB connection pool
 pub struct SurrealPool {
-    client: Surreal<Client>,
-    #[allow(dead_code)]
-    url: String,
+    client: std::sync::RwLock<Surreal<Any>>,
+    target: SurrealTarget,
 }
 
+#[cfg(feature = "documents-surrealdb")]
+/// Where and as whom a [`SurrealPool`] connects, kept for reconnecting
+struct SurrealTarget(SurrealConfig);
+
+#[cfg(feature = "documents-surrealdb")]
 /// Record ID wrapper for SurrealDB responses
 #[derive(Debug, Deserialize)]
-struct Record {
-    #[allow(dead_code)]
-    id: surrealdb::RecordId,
+pub(super) struct Record {
+    pub(super) id: surrealdb::RecordId,
 }
 
-/// Compliance report as stored in SurrealDB
+/// Compliance report as stored
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct ComplianceReport {
-    platform: String,
-    owner: String,
-    repo: String,
-    tier: String,
-    score: f32,
-    checks: serde_json::Value,
-    created_at: chrono::DateTime<chrono::Utc>,
+pub(super) struct ComplianceReport {
+    pub(super) platform: String,
+    pub(super) owner: String,
+    pub(super) repo: String,
+    pub(super) tier: String,
+    pub(super) score: f32,
+    pub(super) checks: serde_json::Value,
+    pub(super) created_at: chrono::DateTime<chrono::Utc>,
+    /// Missing on reports stored before standards were recorded
+    #[serde(default)]
+    pub(super) standard: Vec<String>,
+    #[serde(default)]
+    pub(super) canonical: Option<crate::RepoRef>,
+    #[serde(default)]
+    pub(super) evidence: std::collections::BTreeMap<String, crate::RepoRef>,
+    /// Content digest, also the record key; missing on reports stored
+    /// before reports were chained
+    #[serde(default)]
+    pub(super) digest: Option<String>,
+    /// Digest of the repository's previous report
+    #[serde(default)]
+    pub(super) previous_digest: Option<String>,
 }
 
-/// Webhook event record
+impl ComplianceReport {
+    pub(super) fn from_status(status: &ComplianceStatus) -> Result<Self> {
+        Ok(Self {
+            platform: status.repo.platform.clone(),
+            owner: status.repo.owner.clone(),
+            repo: status.repo.repo.clone(),
+            tier: serde_json::to_value(status.tier)?.as_str().unwrap_or_default().to_string(),
+            score: status.score,
+            checks: serde_json::to_value(&status.checks)?,
+            created_at: status.timestamp,
+            standard: status.standard.clone(),
+            canonical: status.canonical.clone(),
+            evidence: status.evidence.clone(),
+            digest: None,
+            previous_digest: None,
+        })
+    }
+
+    /// SHA-256 of the report's content and the digest it chains from.
+    ///
+    /// The repository's identity is left out: transfers move reports to the
+    /// new owner/name, and a report moved to another repository breaks both
+    /// chains anyway. The content is hashed in [`canonical_json`] form, so
+    /// the digest doesn't depend on how a backend reads it back.
+    pub(super) fn content_digest(&self) -> Result<String> {
+        // Backends store -0.0 as 0.0
+        let score = if self.score == 0.0 { 0.0 } else { self.score };
+        let content = serde_json::json!({
+            "tier": self.tier,
+            "score": score,
+            "checks": self.checks,
+            "standard": self.standard,
+            "canonical": self.canonical,
+            "evidence": self.evidence,
+            "created_at": self.created_at.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true),
+            "previous_digest": self.previous_digest,
+        });
+        Ok(hex::encode(Sha256::digest(serde_json::to_vec(&canonical_json(content))?)))
+    }
+
+    /// Chain the report onto `previous`, setting its digest
+    pub(super) fn chained(mut self, previous: Option<String>) -> Result<Self> {
+        self.previous_digest = previous;
+        self.digest = Some(self.content_digest()?);
+        Ok(self)
+    }
+
+    pub(super) fn into_status(self) -> ComplianceStatus {
+        let tier = stored_tier(&self.tier);
+        let checks: Vec<crate::CheckResult> = serde_json::from_value(self.checks).unwrap_or_default();
+
+        ComplianceStatus {
+            repo: crate::RepoRef::new(&self.platform, &self.owner, &self.repo),
+            tier,
+            score: self.score,
+            checks,
+            timestamp: self.created_at,
+            standard: self.standard,
+            canonical: self.canonical,
+            evidence: self.evidence,
+        }
+    }
+}
+
+/// `value` with every object's keys in sorted order and null members
+/// dropped, at every depth. serde_json keeps insertion order when another
+/// crate enables `preserve_order`, so sorting can't be left to the map type,
+/// and SurrealDB reads a null member back as a missing one.
+fn canonical_json(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().filter(|(_, value)| !value.is_null()).collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            serde_json::Value::Object(entries.into_iter().map(|(key, value)| (key, canonical_json(value))).collect())
+        }
+        serde_json::Value::Array(items) => serde_json::Value::Array(items.into_iter().map(canonical_json).collect()),
+        other => other,
+    }
+}
+
+/// Parse a tier as stored. Older reports stored it capitalized.
+pub(super) fn stored_tier(tier: &str) -> crate::CertificationTier {
+    serde_json::from_value(serde_json::Value::String(tier.to_lowercase())).unwrap_or(crate::CertificationTier::None)
+}
+
+/// One page of a repository's compliance history, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPage {
+    pub reports: Vec<ComplianceStatus>,
+    /// Pass as `before` to fetch the next page; `None` on the last page
+    pub next: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Result of checking a repository's report chain
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainVerification {
+    pub repo: RepoRef,
+    /// Reports checked
+    pub reports: usize,
+    /// Reports stored before reports were chained, which can't be verified
+    pub unchained: usize,
+    /// Digest of the latest report. Compare with a copy kept elsewhere to
+    /// detect removal of the newest reports, which the chain alone can't.
+    pub head: Option<String>,
+    /// Every break found, oldest first; empty if the chain is intact
+    pub problems: Vec<String>,
+}
+
+impl ChainVerification {
+    pub(super) fn new(repo: &RepoRef) -> Self {
+        Self {
+            repo: RepoRef::new(&repo.platform, &repo.owner, &repo.repo),
+            reports: 0,
+            unchained: 0,
+            head: None,
+            problems: Vec::new(),
+        }
+    }
+
+    pub fn is_intact(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Check the next report, oldest first: it must match its digest and
+    /// link to the report before it
+    pub(super) fn check(&mut self, report: &ComplianceReport) -> Result<()> {
+        self.reports += 1;
+        let at = report.created_at.to_rfc3339();
+
+        let Some(ref digest) = report.digest else {
+            self.unchained += 1;
+            if self.head.is_some() {
+                self.problems.push(format!("report at {} is not chained", at));
+            }
+            return Ok(());
+        };
+        if report.previous_digest != self.head {
+            self.problems.push(format!(
+                "report at {} does not follow the report before it (expected previous {}, found {})",
+                at,
+                self.head.as_deref().unwrap_or("none"),
+                report.previous_digest.as_deref().unwrap_or("none")
+            ));
+        }
+        if &report.content_digest()? != digest {
+            self.problems.push(format!("report at {} does not match its digest", at));
+        }
+        self.head = Some(digest.clone());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "documents-surrealdb")]
+/// Redirect from a repository's previous identity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RepoRedirect {
+    to_owner: String,
+    to_repo: String,
+}
+
+/// Outcome of a webhook's signature check when it was received
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationOutcome {
+    /// Signature valid, or the platform is allowed to send unsigned webhooks
+    Verified,
+    /// Signature missing or invalid
+    Rejected,
+    /// Refused because no webhook secret is configured
+    Unconfigured,
+}
+
+/// A webhook delivery as received, archived so it can be replayed
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct WebhookEvent {
-    platform: String,
-    event_type: String,
-    payload: serde_json::Value,
-    processed: bool,
+pub struct WebhookEvent {
+    pub platform: String,
+    /// Parsed event type, or `unknown` if the payload could not be parsed
+    pub event_type: String,
+    pub delivery_id: Option<String>,
+    /// Request headers, lowercased
+    pub headers: HashMap<String, String>,
+    /// Raw request body
+    pub payload: String,
+    pub verification: VerificationOutcome,
+    pub processed: bool,
+    /// Why parsing, queueing or processing last failed
+    pub error: Option<String>,
+    /// Version of the engine that last failed to parse the payload, while
+    /// it is quarantined
+    #[serde(default)]
+    pub quarantined_by: Option<String>,
+    /// What a strict parse found unexpected in the payload
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parse_warnings: Vec<ParseWarning>,
+    pub received_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An archived webhook with its record ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedWebhook {
+    pub id: String,
+    #[serde(flatten)]
+    pub event: WebhookEvent,
+}
+
+#[cfg(feature = "documents-surrealdb")]
+/// An archived webhook as read from SurrealDB
+#[derive(Debug, Deserialize)]
+pub(super) struct ArchivedRow {
+    id: surrealdb::RecordId,
+    #[serde(flatten)]
+    event: WebhookEvent,
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl From<ArchivedRow> for ArchivedWebhook {
+    fn from(row: ArchivedRow) -> Self {
+        Self {
+            id: row.id.to_string(),
+            event: row.event,
+        }
+    }
+}
+
+/// Everything the engine keeps in its document store. Implemented by
+/// [`SurrealPool`] and, behind their features, by
+/// [`PostgresStore`](super::postgres::PostgresStore) and
+/// [`SqliteStore`](super::sqlite::SqliteStore).
+#[async_trait::async_trait]
+pub trait DocumentStore: Send + Sync {
+    async fn ping(&self) -> Result<()>;
+
+    /// Replace a dropped connection. The SQL stores' pools replace broken
+    /// connections themselves and keep this default.
+    async fn reconnect(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Migrations not yet applied, in order. Fails if an applied migration
+    /// has changed or is unknown to this build.
+    async fn pending_migrations(&self) -> Result<Vec<Migration>>;
+    /// Apply pending migrations, returning the ones applied
+    async fn migrate(&self) -> Result<Vec<Migration>>;
+
+    /// Store a compliance report, chaining it onto the repository's previous
+    /// one, and return its record ID
+    async fn store_compliance(&self, status: &ComplianceStatus) -> Result<String>;
+    /// Walk a repository's reports oldest first, checking each one's digest
+    /// and link to the report before it
+    async fn verify_report_chain(&self, repo: &RepoRef) -> Result<ChainVerification>;
+    /// One page of a repository's compliance history, newest first, keyed
+    /// by time: pass the returned `next` as `before` to continue. `limit` is
+    /// capped at [`MAX_HISTORY_PAGE`].
+    async fn get_compliance_history(
+        &self,
+        platform: &str,
+        owner: &str,
+        repo: &str,
+        limit: u32,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<HistoryPage>;
+
+    /// Latest compliance report for a repository
+    async fn get_latest_compliance(&self, platform: &str, owner: &str, repo: &str) -> Result<Option<ComplianceStatus>> {
+        tracing::debug!("Getting latest compliance for {}/{}/{}", platform, owner, repo);
+
+        let page = self.get_compliance_history(platform, owner, repo, 1, None).await?;
+        Ok(page.reports.into_iter().next())
+    }
+
+    /// The report that was current for a repository at `as_of`: the last
+    /// one stored at or before that time
+    async fn get_compliance_as_of(
+        &self,
+        platform: &str,
+        owner: &str,
+        repo: &str,
+        as_of: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<ComplianceStatus>> {
+        // `before` is exclusive; timestamps are stored to the nanosecond
+        let before = as_of + chrono::Duration::nanoseconds(1);
+        let page = self.get_compliance_history(platform, owner, repo, 1, Some(before)).await?;
+        Ok(page.reports.into_iter().next())
+    }
+
+    /// Archive a webhook delivery, returning its record ID
+    async fn store_webhook_event(&self, event: &WebhookEvent) -> Result<String>;
+    async fn get_webhook_event(&self, event_id: &str) -> Result<Option<ArchivedWebhook>>;
+    /// Mark a webhook event as processed, clearing any earlier error
+    async fn mark_event_processed(&self, event_id: &str) -> Result<()>;
+    /// Record why a webhook event could not be processed
+    async fn mark_event_failed(&self, event_id: &str, error: &str) -> Result<()>;
+    /// Verified webhook events that failed to parse, queue or process, newest first
+    async fn get_failed_events(&self, limit: u32) -> Result<Vec<ArchivedWebhook>>;
+    /// Quarantine an archived webhook whose payload failed to parse, under
+    /// this engine's version
+    async fn quarantine_event(&self, event_id: &str, error: &str) -> Result<()>;
+    /// Take an archived webhook out of quarantine once its payload parses
+    async fn release_quarantine(&self, event_id: &str) -> Result<()>;
+    /// Quarantined webhooks, newest first
+    async fn get_quarantined_events(&self, limit: u32) -> Result<Vec<ArchivedWebhook>>;
+
+    /// Archive a webhook delivery and write the job that processes it to the
+    /// outbox, in one transaction. `job` builds the job from the event's
+    /// record ID. Returns the record ID and the entry to relay.
+    async fn store_webhook_event_with_job(
+        &self,
+        event: &WebhookEvent,
+        queue: &str,
+        job: &BuildJob,
+    ) -> Result<(String, OutboxEntry)>;
+    /// Outbox entries not yet relayed, oldest first
+    async fn pending_outbox(&self, limit: u32) -> Result<Vec<OutboxEntry>>;
+    /// Remove the outbox entry of a relayed job
+    async fn remove_outbox_entry(&self, job_id: &str) -> Result<()>;
+    /// Record why relaying an outbox entry failed
+    async fn mark_outbox_failed(&self, job_id: &str, error: &str) -> Result<()>;
+
+    /// Move every stored record for a repository to its new owner/name and
+    /// leave a redirect behind, in one transaction
+    async fn transfer_repository(&self, from: &RepoRef, to: &RepoRef) -> Result<()>;
+    /// Resolve a repository's previous identity to its current one
+    async fn resolve_redirect(&self, platform: &str, owner: &str, repo: &str) -> Result<Option<RepoRef>>;
+
+    /// Record a completed CI run as its workflow's latest on its branch,
+    /// unless a later one is already recorded
+    async fn record_ci_run(&self, repo: &RepoRef, run: &CiRun) -> Result<()>;
+    /// The latest completed run of each workflow on `branch`, by workflow name
+    async fn latest_ci_runs(&self, repo: &RepoRef, branch: &str) -> Result<Vec<CiRun>>;
+
+    /// Annotate the report stored for `repo` at `report_at`, returning the
+    /// annotation's record ID
+    async fn annotate_report(
+        &self,
+        repo: &RepoRef,
+        report_at: chrono::DateTime<chrono::Utc>,
+        annotation: &Annotation,
+    ) -> Result<String>;
+    /// Annotations on the report stored for `repo` at `report_at`, oldest first
+    async fn get_annotations(&self, repo: &RepoRef, report_at: chrono::DateTime<chrono::Utc>) -> Result<Vec<Annotation>>;
+    /// Remove an annotation. Returns whether there was one to remove.
+    async fn remove_annotation(&self, annotation_id: &str) -> Result<bool>;
+
+    /// Append an event to the audit log, returning its record ID
+    async fn record_audit(
+        &self,
+        actor: &str,
+        action: AuditAction,
+        target: &str,
+        details: serde_json::Value,
+    ) -> Result<String>;
+    /// One page of audit events matching `query`, newest first
+    async fn get_audit_events(
+        &self,
+        query: &AuditQuery,
+        limit: u32,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<AuditPage>;
+
+    /// Add a repository to the registry, or reactivate one
+    async fn register_repository(&self, repo: &RepoRef, adapter: Option<&str>) -> Result<RegisteredRepository>;
+    /// Take a repository out of the registry's listings. Returns whether it was active.
+    async fn deactivate_repository(&self, repo: &RepoRef) -> Result<bool>;
+    /// Mark a repository as deleted on its platform, keeping its report history
+    async fn mark_repository_deleted(&self, repo: &RepoRef) -> Result<()>;
+    /// Active repositories that still exist, optionally only those on one
+    /// platform and under one owner. An owner includes its subgroups.
+    async fn list_repositories(&self, platform: Option<&str>, owner: Option<&str>) -> Result<Vec<RegisteredRepository>>;
+    /// A repository's registry entry, active or not
+    async fn get_repository(&self, repo: &RepoRef) -> Result<Option<RegisteredRepository>>;
+
+    /// Aggregate a repository's reports in `window` into daily or weekly
+    /// buckets, oldest first
+    async fn get_compliance_trend(
+        &self,
+        platform: &str,
+        owner: &str,
+        repo: &str,
+        window: TrendWindow,
+    ) -> Result<ComplianceTrend>;
+
+    /// Summarize the latest compliance status of every repository under `owner`
+    async fn get_org_summary(&self, platform: &str, owner: &str) -> Result<OrgSummary>;
+
+    /// Tombstone every report outside `policy`. On a dry run, only counts them.
+    async fn prune_reports(&self, policy: &RetentionPolicy, dry_run: bool) -> Result<PruneReport>;
+    /// Record that a badge was issued from a repository's report stored at
+    /// `at`, so it is never pruned
+    async fn mark_badge_issued(&self, repo: &RepoRef, at: chrono::DateTime<chrono::Utc>) -> Result<()>;
 }
 
+/// Connect to the document store named by the environment: Postgres if
+/// `RSR_POSTGRES_URL` is set, else SQLite if `RSR_SQLITE_PATH` is, else
+/// SurrealDB
+pub async fn connect_from_env() -> Result<Arc<dyn DocumentStore>> {
+    connect_with(&DbConfig::load()?.surrealdb).await
+}
+
+/// [`connect_from_env`], connecting to SurrealDB with `surreal` if neither
+/// SQL store is configured
+pub async fn connect_with(surreal: &SurrealConfig) -> Result<Arc<dyn DocumentStore>> {
+    match (std::env::var("RSR_POSTGRES_URL"), std::env::var("RSR_SQLITE_PATH")) {
+        #[cfg(feature = "documents-postgres")]
+        (Ok(url), _) => Ok(Arc::new(super::postgres::PostgresStore::connect(&url).await?)),
+        #[cfg(not(feature = "documents-postgres"))]
+        (Ok(_), _) => Err(crate::RsrError::Config(
+            "RSR_POSTGRES_URL is set but this build lacks the documents-postgres feature".to_string(),
+        )),
+        #[cfg(feature = "documents-sqlite")]
+        (_, Ok(path)) => Ok(Arc::new(super::sqlite::SqliteStore::open(&path).await?)),
+        #[cfg(not(feature = "documents-sqlite"))]
+        (_, Ok(_)) => Err(crate::RsrError::Config(
+            "RSR_SQLITE_PATH is set but this build lacks the documents-sqlite feature".to_string(),
+        )),
+        #[cfg(feature = "documents-surrealdb")]
+        _ => Ok(Arc::new(SurrealPool::connect_with(surreal.clone()).await?)),
+        #[cfg(not(feature = "documents-surrealdb"))]
+        _ => Err(crate::RsrError::Config(format!(
+            "Neither RSR_POSTGRES_URL nor RSR_SQLITE_PATH is set, and this build lacks the documents-surrealdb \
+             feature for {}",
+            surreal.url
+        ))),
+    }
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealTarget {
+    async fn connect(&self) -> Result<Surreal<Any>> {
+        let config = &self.0;
+        tracing::info!("Connecting to SurrealDB: {}/{}/{}", config.url, config.namespace, config.database);
+
+        let mut options = surrealdb::opt::Config::new();
+        if config.tls.is_custom() {
+            options = options.rustls(config.tls.rustls_config()?);
+        }
+
+        let client = tokio::time::timeout(
+            config.connect_timeout(),
+            surrealdb::engine::any::connect((config.url.as_str(), options)),
+        )
+        .await
+        .map_err(|_| {
+            DbError::Backend(format!("SurrealDB connection timed out after {}s", config.connect_timeout_secs))
+        })?
+        .map_err(|e| DbError::surreal("SurrealDB connection failed", e))?;
+
+        if !config.url.starts_with("mem://") {
+            let (username, password) = (config.credentials.username(), config.credentials.password());
+            let signin = match config.auth() {
+                SurrealAuth::Root => client.signin(Root { username, password }).await.map(drop),
+                SurrealAuth::Namespace => client
+                    .signin(Namespace {
+                        namespace: &config.namespace,
+                        username,
+                        password,
+                    })
+                    .await
+                    .map(drop),
+                SurrealAuth::Database => client
+                    .signin(Database {
+                        namespace: &config.namespace,
+                        database: &config.database,
+                        username,
+                        password,
+                    })
+                    .await
+                    .map(drop),
+                SurrealAuth::None => Ok(()),
+            };
+            signin.map_err(|e| DbError::surreal("SurrealDB auth failed", e))?;
+        }
+
+        client
+            .use_ns(&config.namespace)
+            .use_db(&config.database)
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB use ns/db failed", e))?;
+
+        Ok(client)
+    }
+}
+
+#[cfg(feature = "documents-surrealdb")]
 impl SurrealPool {
-    /// Connect from environment variables
+    /// Connect with the settings from the environment (see [`super::config`])
     pub async fn connect_from_env() -> Result<Self> {
-        let url = std::env::var("RSR_SURREALDB_URL")
-            .unwrap_or_else(|_| "ws://localhost:8000".to_string());
-        let namespace = std::env::var("RSR_SURREALDB_NS")
-            .unwrap_or_else(|_| "rsr".to_string());
-        let database = std::env::var("RSR_SURREALDB_DB")
-            .unwrap_or_else(|_| "compliance".to_string());
-        let username = std::env::var("RSR_SURREALDB_USER")
-            .unwrap_or_else(|_| "root".to_string());
-        let password = std::env::var("RSR_SURREALDB_PASS")
-            .unwrap_or_else(|_| "root".to_string());
-
-        Self::connect(&url, &namespace, &database, &username, &password).await
+        Self::connect_with(DbConfig::load()?.surrealdb).await
     }
 
-    /// Connect to SurrealDB
+    /// Connect to SurrealDB as a root user. `url` is a `ws://`, `wss://`,
+    /// `http://` or `https://` server address, or `mem://` for an embedded
+    /// in-memory database (requires the `surrealdb-mem` feature), which
+    /// needs no credentials.
     pub async fn connect(
         url: &str,
         namespace: &str,
@@ -72,177 +601,194 @@ pub async fn connect(
         username: &str,
         password: &str,
     ) -> Result<Self> {
-        tracing::info!("Connecting to SurrealDB: {}/{}/{}", url, namespace, database);
-
-        // Strip ws:// or wss:// prefix if present for the Ws connector
-        let addr = url
-            .strip_prefix("ws://")
-            .or_else(|| url.strip_prefix("wss://"))
-            .unwrap_or(url);
-
-        let client = Surreal::new::<Ws>(addr)
-            .await
-            .map_err(|e| RsrError::Platform(format!("SurrealDB connection failed: {}", e)))?;
-
-        client
-            .signin(Root { username, password })
-            .await
-            .map_err(|e| RsrError::Platform(format!("SurrealDB auth failed: {}", e)))?;
-
-        client
-            .use_ns(namespace)
-            .use_db(database)
-            .await
-            .map_err(|e| RsrError::Platform(format!("SurrealDB use ns/db failed: {}", e)))?;
+        Self::connect_with(SurrealConfig {
+            url: url.to_string(),
+            namespace: namespace.to_string(),
+            database: database.to_string(),
+            credentials: Credentials {
+                username: Some(username.to_string()),
+                password: Some(password.to_string()),
+                password_file: None,
+            },
+            ..Default::default()
+        })
+        .await
+    }
 
+    /// Connect to SurrealDB as configured
+    pub async fn connect_with(config: SurrealConfig) -> Result<Self> {
+        let target = SurrealTarget(config);
         Ok(Self {
-            client,
-            url: url.to_string(),
+            client: std::sync::RwLock::new(target.connect().await?),
+            target,
         })
     }
 
+    /// Client for the current connection
+    pub(super) fn client(&self) -> Surreal<Any> {
+        self.client.read().expect("surreal client lock poisoned").clone()
+    }
+
+    /// Replace the connection with a fresh one
+    pub async fn reconnect(&self) -> Result<()> {
+        let client = self.target.connect().await?;
+        *self.client.write().expect("surreal client lock poisoned") = client;
+        tracing::info!("Reconnected to SurrealDB: {}", self.target.0.url);
+        Ok(())
+    }
+
     /// Ping the database
     pub async fn ping(&self) -> Result<()> {
         // SurrealDB doesn't have a ping, but we can run a simple query
-        self.client
+        self.client()
             .query("RETURN 1")
             .await
-            .map_err(|e| RsrError::Platform(format!("SurrealDB ping failed: {}", e)))?;
+            .map_err(|e| DbError::surreal("SurrealDB ping failed", e))?;
         tracing::debug!("SurrealDB ping successful");
         Ok(())
     }
 
-    /// Run database migrations
-    pub async fn migrate(&self) -> Result<()> {
-        tracing::info!("Running SurrealDB migrations");
-
-        let migrations = r#"
-            -- Repository table
-            DEFINE TABLE repository SCHEMALESS;
-            DEFINE FIELD platform ON repository TYPE string;
-            DEFINE FIELD owner ON repository TYPE string;
-            DEFINE FIELD name ON repository TYPE string;
-            DEFINE INDEX repo_idx ON repository COLUMNS platform, owner, name UNIQUE;
-
-            -- Compliance report table
-            DEFINE TABLE compliance_report SCHEMALESS;
-            DEFINE FIELD platform ON compliance_report TYPE string;
-            DEFINE FIELD owner ON compliance_report TYPE string;
-            DEFINE FIELD repo ON compliance_report TYPE string;
-            DEFINE FIELD tier ON compliance_report TYPE string;
-            DEFINE FIELD score ON compliance_report TYPE float;
-            DEFINE FIELD checks ON compliance_report TYPE array;
-            DEFINE FIELD created_at ON compliance_report TYPE datetime DEFAULT time::now();
-            DEFINE INDEX report_time_idx ON compliance_report COLUMNS platform, owner, repo, created_at;
-
-            -- Webhook event table
-            DEFINE TABLE webhook_event SCHEMALESS;
-            DEFINE FIELD platform ON webhook_event TYPE string;
-            DEFINE FIELD event_type ON webhook_event TYPE string;
-            DEFINE FIELD payload ON webhook_event TYPE object;
-            DEFINE FIELD processed ON webhook_event TYPE bool DEFAULT false;
-            DEFINE FIELD created_at ON webhook_event TYPE datetime DEFAULT time::now();
-        "#;
+    /// Store a compliance report, returning its record ID. A registered
+    /// repository's last scan time is updated with it, and cached summaries
+    /// of its owner dropped.
+    ///
+    /// Reports are content-addressed and chained per repository: each one's
+    /// digest covers the previous report's digest, so editing or removing a
+    /// stored report shows up in [`SurrealPool::verify_report_chain`].
+    pub async fn store_compliance(&self, status: &ComplianceStatus) -> Result<String> {
+        tracing::debug!("Storing compliance report for {}", status.repo);
 
-        self.client
-            .query(migrations)
-            .await
-            .map_err(|e| RsrError::Platform(format!("SurrealDB migration failed: {}", e)))?;
+        for attempt in 1..=CHAIN_ATTEMPTS {
+            let previous = self.chain_head(&status.repo).await?;
+            let report = ComplianceReport::from_status(status)?.chained(previous)?;
+            let digest = report.digest.clone().unwrap_or_default();
 
-        tracing::info!("SurrealDB migrations complete");
-        Ok(())
-    }
+            // The head is checked again inside the transaction, so two reports
+            // stored at once can't both chain onto the same predecessor.
+            // Timestamps are bound as RFC 3339 strings and cast, since the field is a datetime.
+            let stored = self
+                .client()
+                .query(format!(
+                    "BEGIN TRANSACTION; \
+                     LET $head = (SELECT digest, created_at FROM compliance_report \
+                        WHERE platform = $report.platform AND owner = $report.owner AND repo = $report.repo \
+                        ORDER BY created_at DESC LIMIT 1)[0].digest; \
+                     IF ($head ?? '') != ($report.previous_digest ?? '') {{ THROW '{}' }}; \
+                     CREATE type::thing('compliance_report', $report.digest) SET \
+                        platform = $report.platform, owner = $report.owner, repo = $report.repo, \
+                        tier = $report.tier, score = $report.score, checks = $report.checks, \
+                        standard = $report.standard, canonical = $report.canonical, evidence = $report.evidence, \
+                        digest = $report.digest, previous_digest = $report.previous_digest, \
+                        created_at = <datetime> $report.created_at; \
+                     UPDATE repository SET last_scanned_at = <datetime> $report.created_at \
+                        WHERE platform = $report.platform AND owner = $report.owner AND name = $report.repo; \
+                     DELETE org_summary WHERE platform = $report.platform \
+                        AND ($report.owner = owner OR string::starts_with($report.owner, owner + '/')); \
+                     COMMIT TRANSACTION;",
+                    CHAIN_MOVED
+                ))
+                .bind(("report", report))
+                .await
+                .and_then(surrealdb::Response::check);
 
-    /// Store a compliance report
-    pub async fn store_compliance(&self, status: &ComplianceStatus) -> Result<String> {
-        tracing::debug!("Storing compliance report for {}", status.repo);
+            match stored {
+                Ok(_) => {
+                    let id = surrealdb::RecordId::from_table_key("compliance_report", digest).to_string();
+                    tracing::debug!("Stored compliance report with ID: {}", id);
+                    return Ok(id);
+                }
+                Err(e) if e.to_string().contains(CHAIN_MOVED) && attempt < CHAIN_ATTEMPTS => {
+                    tracing::debug!("Report chain for {} moved, retrying", status.repo);
+                }
+                Err(e) => return Err(DbError::surreal("SurrealDB create failed", e).into()),
+            }
+        }
 
-        let report = ComplianceReport {
-            platform: status.repo.platform.clone(),
-            owner: status.repo.owner.clone(),
-            repo: status.repo.repo.clone(),
-            tier: format!("{:?}", status.tier),
-            score: status.score,
-            checks: serde_json::to_value(&status.checks)
-                .map_err(|e| RsrError::Json(e))?,
-            created_at: status.timestamp,
-        };
+        Err(DbError::Backend(format!("Report chain for {} kept moving", status.repo)).into())
+    }
 
-        let result: Option<Record> = self.client
-            .create("compliance_report")
-            .content(report)
+    /// Digest of a repository's latest report, if it has one. SurrealDB
+    /// only orders by selected fields, so `created_at` is selected too.
+    async fn chain_head(&self, repo: &RepoRef) -> Result<Option<String>> {
+        let mut result = self
+            .client()
+            .query(
+                "SELECT digest, created_at FROM compliance_report \
+                 WHERE platform = $platform AND owner = $owner AND repo = $repo \
+                 ORDER BY created_at DESC LIMIT 1",
+            )
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
             .await
-            .map_err(|e| RsrError::Platform(format!("SurrealDB create failed: {}", e)))?;
-
-        let id = result
-            .map(|r| r.id.to_string())
-            .unwrap_or_else(|| "unknown".to_string());
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
 
-        tracing::debug!("Stored compliance report with ID: {}", id);
-        Ok(id)
+        let head: Option<String> = result
+            .take((0, "digest"))
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+        Ok(head)
     }
 
-    /// Get latest compliance report for a repository
-    pub async fn get_latest_compliance(
-        &self,
-        platform: &str,
-        owner: &str,
-        repo: &str,
-    ) -> Result<Option<ComplianceStatus>> {
-        tracing::debug!("Getting latest compliance for {}/{}/{}", platform, owner, repo);
+    /// Walk a repository's reports oldest first, recomputing each digest and
+    /// checking it links to the report before it.
+    pub async fn verify_report_chain(&self, repo: &RepoRef) -> Result<ChainVerification> {
+        let mut verification = ChainVerification::new(repo);
+        let mut after: Option<chrono::DateTime<chrono::Utc>> = None;
 
-        // Clone strings to satisfy 'static lifetime requirement
-        let platform = platform.to_string();
-        let owner = owner.to_string();
-        let repo = repo.to_string();
+        loop {
+            let mut result = self
+                .client()
+                .query(
+                    "SELECT tier, score, checks, standard, canonical, evidence, digest, previous_digest, \
+                        <string> created_at AS created_at, platform, owner, repo \
+                     FROM ( \
+                        SELECT * FROM compliance_report \
+                        WHERE platform = $platform AND owner = $owner AND repo = $repo \
+                            AND ($after = NONE OR created_at > <datetime> $after) \
+                        ORDER BY created_at LIMIT $limit \
+                     )",
+                )
+                .bind(("platform", repo.platform.clone()))
+                .bind(("owner", repo.owner.clone()))
+                .bind(("repo", repo.repo.clone()))
+                .bind(("after", after.map(|after| after.to_rfc3339())))
+                .bind(("limit", CHAIN_PAGE))
+                .await
+                .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
 
-        let mut result = self.client
-            .query("SELECT * FROM compliance_report WHERE platform = $platform AND owner = $owner AND repo = $repo ORDER BY created_at DESC LIMIT 1")
-            .bind(("platform", platform))
-            .bind(("owner", owner))
-            .bind(("repo", repo.clone()))
-            .await
-            .map_err(|e| RsrError::Platform(format!("SurrealDB query failed: {}", e)))?;
+            let reports: Vec<ComplianceReport> = result
+                .take(0)
+                .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+            let page_len = reports.len();
 
-        let reports: Vec<ComplianceReport> = result
-            .take(0)
-            .map_err(|e| RsrError::Platform(format!("SurrealDB take failed: {}", e)))?;
-
-        if let Some(report) = reports.into_iter().next() {
-            let tier = match report.tier.as_str() {
-                "Bronze" => crate::CertificationTier::Bronze,
-                "Silver" => crate::CertificationTier::Silver,
-                "Gold" => crate::CertificationTier::Gold,
-                "Rhodium" => crate::CertificationTier::Rhodium,
-                _ => crate::CertificationTier::None,
-            };
+            for report in reports {
+                after = Some(report.created_at);
+                verification.check(&report)?;
+            }
 
-            let checks: Vec<crate::CheckResult> = serde_json::from_value(report.checks)
-                .unwrap_or_default();
-
-            Ok(Some(ComplianceStatus {
-                repo: crate::RepoRef::new(&report.platform, &report.owner, &report.repo),
-                tier,
-                score: report.score,
-                checks,
-                timestamp: report.created_at,
-            }))
-        } else {
-            Ok(None)
+            if page_len < CHAIN_PAGE as usize {
+                return Ok(verification);
+            }
         }
     }
 
-    /// Get compliance history for a repository
+    /// Get one page of a repository's compliance history, newest first.
+    ///
+    /// Pages are keyed by time rather than offset, so reports stored while
+    /// paging don't shift later pages: pass the returned `next` as `before`
+    /// to continue. `limit` is capped at [`MAX_HISTORY_PAGE`].
     pub async fn get_compliance_history(
         &self,
         platform: &str,
         owner: &str,
         repo: &str,
         limit: u32,
-    ) -> Result<Vec<ComplianceStatus>> {
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<HistoryPage> {
+        let limit = limit.clamp(1, MAX_HISTORY_PAGE);
         tracing::debug!(
-            "Getting compliance history for {}/{}/{} (limit: {})",
-            platform, owner, repo, limit
+            "Getting compliance history for {}/{}/{} (limit: {}, before: {:?})",
+            platform, owner, repo, limit, before
         );
 
         // Clone strings to satisfy 'static lifetime requirement
@@ -250,67 +796,48 @@ pub async fn get_compliance_history(
         let owner = owner.to_string();
         let repo = repo.to_string();
 
-        let mut result = self.client
-            .query("SELECT * FROM compliance_report WHERE platform = $platform AND owner = $owner AND repo = $repo ORDER BY created_at DESC LIMIT $limit")
+        // One extra row tells us whether there is another page. Timestamps
+        // are read back as strings, after sorting on the datetimes.
+        let mut result = self.client()
+            .query(
+                "SELECT platform, owner, repo, tier, score, checks, standard, canonical, evidence, \
+                    <string> created_at AS created_at \
+                 FROM ( \
+                    SELECT * FROM compliance_report \
+                    WHERE platform = $platform AND owner = $owner AND repo = $repo AND deleted_at = NONE \
+                        AND ($before = NONE OR created_at < <datetime> $before) \
+                    ORDER BY created_at DESC LIMIT $limit \
+                 )",
+            )
             .bind(("platform", platform))
             .bind(("owner", owner))
             .bind(("repo", repo))
-            .bind(("limit", limit))
+            .bind(("before", before.map(|before| before.to_rfc3339())))
+            .bind(("limit", limit + 1))
             .await
-            .map_err(|e| RsrError::Platform(format!("SurrealDB query failed: {}", e)))?;
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
 
-        let reports: Vec<ComplianceReport> = result
+        let mut reports: Vec<ComplianceReport> = result
             .take(0)
-            .map_err(|e| RsrError::Platform(format!("SurrealDB take failed: {}", e)))?;
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
 
-        let statuses = reports
-            .into_iter()
-            .map(|report| {
-                let tier = match report.tier.as_str() {
-                    "Bronze" => crate::CertificationTier::Bronze,
-                    "Silver" => crate::CertificationTier::Silver,
-                    "Gold" => crate::CertificationTier::Gold,
-                    "Rhodium" => crate::CertificationTier::Rhodium,
-                    _ => crate::CertificationTier::None,
-                };
-
-                let checks: Vec<crate::CheckResult> = serde_json::from_value(report.checks)
-                    .unwrap_or_default();
-
-                ComplianceStatus {
-                    repo: crate::RepoRef::new(&report.platform, &report.owner, &report.repo),
-                    tier,
-                    score: report.score,
-                    checks,
-                    timestamp: report.created_at,
-                }
-            })
-            .collect();
+        let more = reports.len() > limit as usize;
+        reports.truncate(limit as usize);
+        let reports: Vec<ComplianceStatus> = reports.into_iter().map(ComplianceReport::into_status).collect();
+        let next = if more { reports.last().map(|status| status.timestamp) } else { None };
 
-        Ok(statuses)
+        Ok(HistoryPage { reports, next })
     }
 
-    /// Store a webhook event for processing
-    pub async fn store_webhook_event(
-        &self,
-        platform: &str,
-        event_type: &str,
-        payload: &serde_json::Value,
-    ) -> Result<String> {
-        tracing::debug!("Storing webhook event: {}/{}", platform, event_type);
-
-        let event = WebhookEvent {
-            platform: platform.to_string(),
-            event_type: event_type.to_string(),
-            payload: payload.clone(),
-            processed: false,
-        };
+    /// Archive a webhook delivery, returning its record ID
+    pub async fn store_webhook_event(&self, event: &WebhookEvent) -> Result<String> {
+        tracing::debug!("Archiving webhook event: {}/{}", event.platform, event.event_type);
 
-        let result: Option<Record> = self.client
+        let result: Option<Record> = self.client()
             .create("webhook_event")
-            .content(event)
+            .content(event.clone())
             .await
-            .map_err(|e| RsrError::Platform(format!("SurrealDB create failed: {}", e)))?;
+            .map_err(|e| DbError::surreal("SurrealDB create failed", e))?;
 
         let id = result
             .map(|r| r.id.to_string())
@@ -319,30 +846,371 @@ pub async fn store_webhook_event(
         Ok(id)
     }
 
-    /// Mark a webhook event as processed
+    /// Get an archived webhook by record ID
+    pub async fn get_webhook_event(&self, event_id: &str) -> Result<Option<ArchivedWebhook>> {
+        let event_id = event_id.to_string();
+        let mut result = self.client()
+            .query("SELECT * FROM type::record($id)")
+            .bind(("id", event_id))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let events: Vec<ArchivedRow> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+
+        Ok(events.into_iter().next().map(ArchivedWebhook::from))
+    }
+
+    /// Mark a webhook event as processed, clearing any earlier error
     pub async fn mark_event_processed(&self, event_id: &str) -> Result<()> {
         let event_id = event_id.to_string();
-        self.client
-            .query("UPDATE $id SET processed = true")
+        self.client()
+            .query("UPDATE type::record($id) SET processed = true, error = NONE")
             .bind(("id", event_id))
             .await
-            .map_err(|e| RsrError::Platform(format!("SurrealDB update failed: {}", e)))?;
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| DbError::surreal("SurrealDB update failed", e))?;
+
+        Ok(())
+    }
+
+    /// Record why a webhook event could not be processed
+    pub async fn mark_event_failed(&self, event_id: &str, error: &str) -> Result<()> {
+        let event_id = event_id.to_string();
+        let error = error.to_string();
+        self.client()
+            .query("UPDATE type::record($id) SET processed = false, error = $error")
+            .bind(("id", event_id))
+            .bind(("error", error))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| DbError::surreal("SurrealDB update failed", e))?;
 
         Ok(())
     }
 
     /// Get unprocessed webhook events
     pub async fn get_pending_events(&self, limit: u32) -> Result<Vec<serde_json::Value>> {
-        let mut result = self.client
-            .query("SELECT * FROM webhook_event WHERE processed = false ORDER BY created_at ASC LIMIT $limit")
+        let mut result = self.client()
+            .query("SELECT * FROM webhook_event WHERE processed = false ORDER BY received_at ASC LIMIT $limit")
             .bind(("limit", limit))
             .await
-            .map_err(|e| RsrError::Platform(format!("SurrealDB query failed: {}", e)))?;
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
 
         let events: Vec<serde_json::Value> = result
             .take(0)
-            .map_err(|e| RsrError::Platform(format!("SurrealDB take failed: {}", e)))?;
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
 
         Ok(events)
     }
+
+    /// Verified webhook events that failed to parse, queue or process, newest first
+    pub async fn get_failed_events(&self, limit: u32) -> Result<Vec<ArchivedWebhook>> {
+        let mut result = self.client()
+            .query("SELECT * FROM webhook_event WHERE processed = false AND error != NONE AND verification = 'verified' ORDER BY received_at DESC LIMIT $limit")
+            .bind(("limit", limit))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let events: Vec<ArchivedRow> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+
+        Ok(events.into_iter().map(ArchivedWebhook::from).collect())
+    }
+
+    /// Move every stored record for a repository to its new owner/name.
+    ///
+    /// Reports keep their history and annotations, the registry entry is renamed, and a redirect
+    /// is recorded so old report URLs resolve. Existing redirects that pointed at
+    /// the old identity are re-pointed so chains of transfers collapse to one hop.
+    /// Runs as a single SurrealDB transaction.
+    pub async fn transfer_repository(&self, from: &RepoRef, to: &RepoRef) -> Result<()> {
+        tracing::info!("Transferring stored data from {} to {}", from, to);
+
+        let transfer = r#"
+            BEGIN TRANSACTION;
+
+            UPDATE compliance_report
+                SET owner = $to_owner, repo = $to_repo
+                WHERE platform = $platform AND owner = $from_owner AND repo = $from_repo;
+
+            UPDATE report_annotation
+                SET owner = $to_owner, repo = $to_repo
+                WHERE platform = $platform AND owner = $from_owner AND repo = $from_repo;
+
+            UPDATE repository
+                SET owner = $to_owner, name = $to_repo
+                WHERE platform = $platform AND owner = $from_owner AND name = $from_repo;
+
+            UPDATE repo_redirect
+                SET to_owner = $to_owner, to_repo = $to_repo
+                WHERE platform = $platform AND to_owner = $from_owner AND to_repo = $from_repo;
+
+            DELETE ci_run
+                WHERE platform = $platform AND owner = $to_owner AND repo = $to_repo;
+
+            UPDATE ci_run
+                SET owner = $to_owner, repo = $to_repo
+                WHERE platform = $platform AND owner = $from_owner AND repo = $from_repo;
+
+            DELETE repo_redirect
+                WHERE platform = $platform AND from_owner = $to_owner AND from_repo = $to_repo;
+
+            UPSERT repo_redirect
+                SET platform = $platform,
+                    from_owner = $from_owner,
+                    from_repo = $from_repo,
+                    to_owner = $to_owner,
+                    to_repo = $to_repo,
+                    created_at = time::now()
+                WHERE platform = $platform AND from_owner = $from_owner AND from_repo = $from_repo;
+
+            DELETE org_summary
+                WHERE platform = $platform
+                    AND ($from_owner = owner OR string::starts_with($from_owner, owner + '/')
+                        OR $to_owner = owner OR string::starts_with($to_owner, owner + '/'));
+
+            COMMIT TRANSACTION;
+        "#;
+
+        self.client()
+            .query(transfer)
+            .bind(("platform", from.platform.clone()))
+            .bind(("from_owner", from.owner.clone()))
+            .bind(("from_repo", from.repo.clone()))
+            .bind(("to_owner", to.owner.clone()))
+            .bind(("to_repo", to.repo.clone()))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| DbError::surreal("SurrealDB transfer failed", e))?;
+
+        Ok(())
+    }
+
+    /// Resolve a repository's previous identity to its current one
+    pub async fn resolve_redirect(&self, platform: &str, owner: &str, repo: &str) -> Result<Option<RepoRef>> {
+        let platform = platform.to_string();
+        let owner = owner.to_string();
+        let repo = repo.to_string();
+
+        let mut result = self.client()
+            .query("SELECT to_owner, to_repo FROM repo_redirect WHERE platform = $platform AND from_owner = $owner AND from_repo = $repo LIMIT 1")
+            .bind(("platform", platform.clone()))
+            .bind(("owner", owner))
+            .bind(("repo", repo))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let redirects: Vec<RepoRedirect> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+
+        Ok(redirects
+            .into_iter()
+            .next()
+            .map(|r| RepoRef::new(platform, r.to_owner, r.to_repo)))
+    }
+}
+
+#[async_trait::async_trait]
+#[cfg(feature = "documents-surrealdb")]
+impl DocumentStore for SurrealPool {
+    async fn ping(&self) -> Result<()> {
+        SurrealPool::ping(self).await
+    }
+
+    async fn reconnect(&self) -> Result<()> {
+        SurrealPool::reconnect(self).await
+    }
+
+    async fn pending_migrations(&self) -> Result<Vec<Migration>> {
+        SurrealPool::pending_migrations(self).await
+    }
+
+    async fn migrate(&self) -> Result<Vec<Migration>> {
+        SurrealPool::migrate(self).await
+    }
+
+    async fn store_compliance(&self, status: &ComplianceStatus) -> Result<String> {
+        SurrealPool::store_compliance(self, status).await
+    }
+
+    async fn verify_report_chain(&self, repo: &RepoRef) -> Result<ChainVerification> {
+        SurrealPool::verify_report_chain(self, repo).await
+    }
+
+    async fn get_compliance_history(
+        &self,
+        platform: &str,
+        owner: &str,
+        repo: &str,
+        limit: u32,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<HistoryPage> {
+        SurrealPool::get_compliance_history(self, platform, owner, repo, limit, before).await
+    }
+
+    async fn store_webhook_event(&self, event: &WebhookEvent) -> Result<String> {
+        SurrealPool::store_webhook_event(self, event).await
+    }
+
+    async fn get_webhook_event(&self, event_id: &str) -> Result<Option<ArchivedWebhook>> {
+        SurrealPool::get_webhook_event(self, event_id).await
+    }
+
+    async fn mark_event_processed(&self, event_id: &str) -> Result<()> {
+        SurrealPool::mark_event_processed(self, event_id).await
+    }
+
+    async fn mark_event_failed(&self, event_id: &str, error: &str) -> Result<()> {
+        SurrealPool::mark_event_failed(self, event_id, error).await
+    }
+
+    async fn get_failed_events(&self, limit: u32) -> Result<Vec<ArchivedWebhook>> {
+        SurrealPool::get_failed_events(self, limit).await
+    }
+
+    async fn quarantine_event(&self, event_id: &str, error: &str) -> Result<()> {
+        SurrealPool::quarantine_event(self, event_id, error).await
+    }
+
+    async fn release_quarantine(&self, event_id: &str) -> Result<()> {
+        SurrealPool::release_quarantine(self, event_id).await
+    }
+
+    async fn get_quarantined_events(&self, limit: u32) -> Result<Vec<ArchivedWebhook>> {
+        SurrealPool::get_quarantined_events(self, limit).await
+    }
+
+    async fn store_webhook_event_with_job(
+        &self,
+        event: &WebhookEvent,
+        queue: &str,
+        job: &BuildJob,
+    ) -> Result<(String, OutboxEntry)> {
+        SurrealPool::store_webhook_event_with_job(self, event, queue, job).await
+    }
+
+    async fn pending_outbox(&self, limit: u32) -> Result<Vec<OutboxEntry>> {
+        SurrealPool::pending_outbox(self, limit).await
+    }
+
+    async fn remove_outbox_entry(&self, job_id: &str) -> Result<()> {
+        SurrealPool::remove_outbox_entry(self, job_id).await
+    }
+
+    async fn mark_outbox_failed(&self, job_id: &str, error: &str) -> Result<()> {
+        SurrealPool::mark_outbox_failed(self, job_id, error).await
+    }
+
+    async fn transfer_repository(&self, from: &RepoRef, to: &RepoRef) -> Result<()> {
+        SurrealPool::transfer_repository(self, from, to).await
+    }
+
+    async fn resolve_redirect(&self, platform: &str, owner: &str, repo: &str) -> Result<Option<RepoRef>> {
+        SurrealPool::resolve_redirect(self, platform, owner, repo).await
+    }
+
+    async fn record_ci_run(&self, repo: &RepoRef, run: &CiRun) -> Result<()> {
+        SurrealPool::record_ci_run(self, repo, run).await
+    }
+
+    async fn latest_ci_runs(&self, repo: &RepoRef, branch: &str) -> Result<Vec<CiRun>> {
+        SurrealPool::latest_ci_runs(self, repo, branch).await
+    }
+
+    async fn annotate_report(
+        &self,
+        repo: &RepoRef,
+        report_at: chrono::DateTime<chrono::Utc>,
+        annotation: &Annotation,
+    ) -> Result<String> {
+        SurrealPool::annotate_report(self, repo, report_at, annotation).await
+    }
+
+    async fn get_annotations(&self, repo: &RepoRef, report_at: chrono::DateTime<chrono::Utc>) -> Result<Vec<Annotation>> {
+        SurrealPool::get_annotations(self, repo, report_at).await
+    }
+
+    async fn remove_annotation(&self, annotation_id: &str) -> Result<bool> {
+        SurrealPool::remove_annotation(self, annotation_id).await
+    }
+
+    async fn record_audit(
+        &self,
+        actor: &str,
+        action: AuditAction,
+        target: &str,
+        details: serde_json::Value,
+    ) -> Result<String> {
+        SurrealPool::record_audit(self, actor, action, target, details).await
+    }
+
+    async fn get_audit_events(
+        &self,
+        query: &AuditQuery,
+        limit: u32,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<AuditPage> {
+        SurrealPool::get_audit_events(self, query, limit, before).await
+    }
+
+    async fn register_repository(&self, repo: &RepoRef, adapter: Option<&str>) -> Result<RegisteredRepository> {
+        SurrealPool::register_repository(self, repo, adapter).await
+    }
+
+    async fn deactivate_repository(&self, repo: &RepoRef) -> Result<bool> {
+        SurrealPool::deactivate_repository(self, repo).await
+    }
+
+    async fn mark_repository_deleted(&self, repo: &RepoRef) -> Result<()> {
+        SurrealPool::mark_repository_deleted(self, repo).await
+    }
+
+    async fn list_repositories(&self, platform: Option<&str>, owner: Option<&str>) -> Result<Vec<RegisteredRepository>> {
+        SurrealPool::list_repositories(self, platform, owner).await
+    }
+
+    async fn get_repository(&self, repo: &RepoRef) -> Result<Option<RegisteredRepository>> {
+        SurrealPool::get_repository(self, repo).await
+    }
+
+    async fn get_compliance_trend(
+        &self,
+        platform: &str,
+        owner: &str,
+        repo: &str,
+        window: TrendWindow,
+    ) -> Result<ComplianceTrend> {
+        SurrealPool::get_compliance_trend(self, platform, owner, repo, window).await
+    }
+
+    async fn get_org_summary(&self, platform: &str, owner: &str) -> Result<OrgSummary> {
+        SurrealPool::get_org_summary(self, platform, owner).await
+    }
+
+    async fn prune_reports(&self, policy: &RetentionPolicy, dry_run: bool) -> Result<PruneReport> {
+        SurrealPool::prune_reports(self, policy, dry_run).await
+    }
+
+    async fn mark_badge_issued(&self, repo: &RepoRef, at: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        SurrealPool::mark_badge_issued(self, repo, at).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_json_ignores_key_order_and_null_members() {
+        let written = serde_json::json!({"b": [{"y": 1, "x": null, "w": 2}], "a": "text"});
+        let read_back: serde_json::Value = serde_json::from_str(r#"{"a": "text", "b": [{"w": 2, "y": 1}]}"#).unwrap();
+
+        let (written, read_back) = (canonical_json(written), canonical_json(read_back));
+        assert_eq!(serde_json::to_string(&written).unwrap(), r#"{"a":"text","b":[{"w":2,"y":1}]}"#);
+        assert_eq!(serde_json::to_string(&written).unwrap(), serde_json::to_string(&read_back).unwrap());
+    }
 }
@@ -0,0 +1,139 @@
+//! Database errors
+//!
+//! Each backend maps its driver's errors onto one taxonomy, so callers can
+//! tell a failure worth retrying from one that will fail the same way again:
+//! - `Connection` and `Timeout`: the backend was unreachable, dropped the
+//!   connection or answered too slowly. Retryable.
+//! - `Serialization`: a value couldn't be encoded for, or decoded from, the
+//!   backend. Fatal.
+//! - `ConstraintViolation`: the write broke a unique or other constraint.
+//!   Fatal, though a caller expecting races may retry with fresh data.
+//! - `NotFound`: what was addressed doesn't exist. Fatal.
+//! - `Backend`: anything else the backend reported. Retried, since it can't
+//!   be told apart from a transient fault.
+//!
+//! Backend errors reach callers as [`RsrError::Db`](crate::RsrError::Db).
+
+use std::fmt::Display;
+
+pub type DbResult<T> = std::result::Result<T, DbError>;
+
+/// A failed database operation
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    #[error("{0}")]
+    Connection(String),
+
+    #[error("{0}")]
+    Timeout(String),
+
+    #[error("{0}")]
+    Serialization(String),
+
+    #[error("{0}")]
+    ConstraintViolation(String),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    Backend(String),
+}
+
+impl DbError {
+    /// Whether the operation may succeed if tried again
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Connection(_) | Self::Timeout(_) | Self::Backend(_))
+    }
+
+    /// Short name of the variant, for metrics and logs
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Connection(_) => "connection",
+            Self::Timeout(_) => "timeout",
+            Self::Serialization(_) => "serialization",
+            Self::ConstraintViolation(_) => "constraint_violation",
+            Self::NotFound(_) => "not_found",
+            Self::Backend(_) => "backend",
+        }
+    }
+
+    #[cfg(feature = "cache-dragonfly")]
+    /// Classify a Dragonfly/Redis error, described as `context`
+    pub fn redis(context: impl Display, e: redis::RedisError) -> Self {
+        let message = format!("{}: {}", context, e);
+        if e.is_timeout() {
+            Self::Timeout(message)
+        } else if e.is_connection_dropped() || e.is_connection_refusal() || e.is_io_error() {
+            Self::Connection(message)
+        } else if e.kind() == redis::ErrorKind::TypeError {
+            Self::Serialization(message)
+        } else {
+            Self::Backend(message)
+        }
+    }
+
+    #[cfg(feature = "documents-surrealdb")]
+    /// Classify a SurrealDB error, described as `context`
+    pub fn surreal(context: impl Display, e: surrealdb::Error) -> Self {
+        use surrealdb::error::{Api, Db};
+
+        let message = format!("{}: {}", context, e);
+        match e {
+            surrealdb::Error::Db(Db::QueryTimedout) => Self::Timeout(message),
+            surrealdb::Error::Db(Db::RecordExists { .. } | Db::IndexExists { .. }) => Self::ConstraintViolation(message),
+            surrealdb::Error::Api(Api::Http(_) | Api::Ws(_) | Api::ConnectionUninitialised) => Self::Connection(message),
+            _ => Self::Backend(message),
+        }
+    }
+
+    /// Classify an ArangoDB error, described as `context`
+    #[cfg(feature = "graphs-arangodb")]
+    pub fn arango(context: impl Display, e: arangors::ClientError) -> Self {
+        // ArangoDB error numbers; see `lib/Basics/errors.dat` upstream
+        const UNIQUE_CONSTRAINT_VIOLATED: u16 = 1210;
+        const QUERY_KILLED: u16 = 1500;
+
+        let message = format!("{}: {}", context, e);
+        match e {
+            arangors::ClientError::HttpClient(_) => Self::Connection(message),
+            arangors::ClientError::Serde(_) => Self::Serialization(message),
+            arangors::ClientError::Arango(e) => match (e.code(), e.error_num()) {
+                (_, UNIQUE_CONSTRAINT_VIOLATED) => Self::ConstraintViolation(message),
+                (_, QUERY_KILLED) | (408 | 504, _) => Self::Timeout(message),
+                (404, _) => Self::NotFound(message),
+                (503, _) => Self::Connection(message),
+                _ => Self::Backend(message),
+            },
+            _ => Self::Backend(message),
+        }
+    }
+
+    /// Classify a Postgres or SQLite error, described as `context`
+    #[cfg(any(feature = "documents-postgres", feature = "documents-sqlite"))]
+    pub fn sqlx(context: impl Display, e: sqlx::Error) -> Self {
+        let message = format!("{}: {}", context, e);
+        match e {
+            sqlx::Error::PoolTimedOut => Self::Timeout(message),
+            sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::PoolClosed | sqlx::Error::WorkerCrashed => {
+                Self::Connection(message)
+            }
+            sqlx::Error::RowNotFound => Self::NotFound(message),
+            sqlx::Error::Decode(_) | sqlx::Error::ColumnDecode { .. } | sqlx::Error::TypeNotFound { .. } => {
+                Self::Serialization(message)
+            }
+            sqlx::Error::Database(ref db)
+                if db.is_unique_violation() || db.is_foreign_key_violation() || db.is_check_violation() =>
+            {
+                Self::ConstraintViolation(message)
+            }
+            _ => Self::Backend(message),
+        }
+    }
+}
+
+impl From<serde_json::Error> for DbError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Serialization(e.to_string())
+    }
+}
@@ -0,0 +1,255 @@
+//! Cache garbage collection
+//!
+//! Long-running installations accumulate keys that nothing will read again.
+//! A GC pass scans the `rsr:` keyspace and removes:
+//! - queue bookkeeping whose queue no longer exists
+//! - ETags, locks, delivery IDs, sessions and cached results that were written without a TTL
+//! - intermediate check results superseded by a newer commit
+//! - entries written in an older schema version of their kind
+//!
+//! Reclaimed space is estimated with `MEMORY USAGE` before deletion.
+
+#[cfg(feature = "cache-dragonfly")]
+use super::cache::{CacheKind, DragonflyPool};
+#[cfg(feature = "cache-dragonfly")]
+use super::error::DbError;
+#[cfg(feature = "cache-dragonfly")]
+use crate::Result;
+use once_cell::sync::Lazy;
+#[cfg(feature = "cache-dragonfly")]
+use redis::AsyncCommands;
+use serde::Serialize;
+use std::collections::BTreeMap;
+#[cfg(feature = "cache-dragonfly")]
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[cfg(feature = "cache-dragonfly")]
+/// Keys deleted per round trip
+const DELETE_BATCH: usize = 500;
+
+#[cfg(feature = "cache-dragonfly")]
+/// Kinds whose keys must always carry a TTL
+const EXPIRING_KINDS: [CacheKind; 7] = [
+    CacheKind::Compliance,
+    CacheKind::Etag,
+    CacheKind::Lock,
+    CacheKind::Check,
+    CacheKind::CheckHead,
+    CacheKind::Delivery,
+    CacheKind::Session,
+];
+
+/// Cumulative totals across GC runs, for the metrics endpoint
+static TOTALS: Lazy<Mutex<GcTotals>> = Lazy::new(Default::default);
+
+/// Why a key was collected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GcRule {
+    /// Queue bookkeeping for a queue that no longer exists
+    OrphanedQueueIndex,
+    /// Key in an expiring namespace that was written without a TTL
+    MissingTtl,
+    /// Check result for a commit older than the repository's latest scan
+    SupersededCheckResult,
+    /// Entry in a schema version its kind no longer reads
+    StaleSchema,
+    /// Entry past its TTL that the in-memory backend hadn't dropped yet
+    Expired,
+}
+
+impl GcRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OrphanedQueueIndex => "orphaned_queue_index",
+            Self::MissingTtl => "missing_ttl",
+            Self::SupersededCheckResult => "superseded_check_result",
+            Self::StaleSchema => "stale_schema",
+            Self::Expired => "expired",
+        }
+    }
+}
+
+/// Keys and bytes reclaimed by one rule
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct GcCount {
+    pub keys: u64,
+    pub bytes: u64,
+}
+
+/// Outcome of a GC pass
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcReport {
+    pub scanned: u64,
+    pub by_rule: BTreeMap<GcRule, GcCount>,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub duration_ms: u64,
+    pub dry_run: bool,
+}
+
+impl GcReport {
+    pub fn keys_deleted(&self) -> u64 {
+        self.by_rule.values().map(|c| c.keys).sum()
+    }
+
+    pub fn bytes_reclaimed(&self) -> u64 {
+        self.by_rule.values().map(|c| c.bytes).sum()
+    }
+}
+
+/// Totals since process start
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GcTotals {
+    pub runs: u64,
+    pub by_rule: BTreeMap<GcRule, GcCount>,
+    pub last_run: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Totals since process start
+pub fn gc_totals() -> GcTotals {
+    TOTALS.lock().expect("gc totals lock poisoned").clone()
+}
+
+/// Add a finished pass to the totals (unless it was a dry run) and log it
+pub(super) fn record_run(report: &GcReport) {
+    if !report.dry_run {
+        let mut totals = TOTALS.lock().expect("gc totals lock poisoned");
+        totals.runs += 1;
+        totals.last_run = report.started_at;
+        for (rule, count) in &report.by_rule {
+            let total = totals.by_rule.entry(*rule).or_default();
+            total.keys += count.keys;
+            total.bytes += count.bytes;
+        }
+    }
+
+    tracing::info!(
+        "Cache GC{}: scanned {} keys, {} {} ({} bytes)",
+        if report.dry_run { " (dry run)" } else { "" },
+        report.scanned,
+        if report.dry_run { "would delete" } else { "deleted" },
+        report.keys_deleted(),
+        report.bytes_reclaimed()
+    );
+}
+
+#[cfg(feature = "cache-dragonfly")]
+impl DragonflyPool {
+    /// Run one GC pass over the cache. With `dry_run` nothing is deleted.
+    pub async fn collect_garbage(&self, dry_run: bool) -> Result<GcReport> {
+        let started = std::time::Instant::now();
+        let mut report = GcReport {
+            started_at: Some(chrono::Utc::now()),
+            dry_run,
+            ..Default::default()
+        };
+
+        let mut conn = self.connection();
+        let keys: Vec<String> = {
+            let mut iter = conn
+                .scan_match::<_, String>("rsr:*")
+                .await
+                .map_err(|e| DbError::redis("Redis scan failed", e))?;
+            let mut keys = Vec::new();
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+            keys
+        };
+
+        let mut heads: HashMap<String, Option<String>> = HashMap::new();
+        let mut doomed: Vec<(String, GcRule)> = Vec::new();
+
+        for key in keys {
+            report.scanned += 1;
+            if let Some(rule) = self.classify(&key, &mut heads).await? {
+                doomed.push((key, rule));
+            }
+        }
+
+        for batch in doomed.chunks(DELETE_BATCH) {
+            for (key, rule) in batch {
+                let bytes: Option<u64> = redis::cmd("MEMORY")
+                    .arg("USAGE")
+                    .arg(key)
+                    .query_async(&mut conn)
+                    .await
+                    .unwrap_or(None);
+
+                let count = report.by_rule.entry(*rule).or_default();
+                count.keys += 1;
+                count.bytes += bytes.unwrap_or(0);
+            }
+
+            if !dry_run {
+                let keys: Vec<&str> = batch.iter().map(|(key, _)| key.as_str()).collect();
+                conn.del::<_, ()>(keys)
+                    .await
+                    .map_err(|e| DbError::redis("Redis del failed", e))?;
+            }
+        }
+
+        report.duration_ms = started.elapsed().as_millis() as u64;
+
+        record_run(&report);
+        Ok(report)
+    }
+
+    /// Decide whether a key should be collected
+    async fn classify(&self, key: &str, heads: &mut HashMap<String, Option<String>>) -> Result<Option<GcRule>> {
+        let mut conn = self.connection();
+
+        let kind = CacheKind::of_key(key);
+        if kind.is_some_and(|kind| !key.starts_with(&kind.prefix())) {
+            return Ok(Some(GcRule::StaleSchema));
+        }
+
+        if let Some(queue_key) = key
+            .strip_suffix(":enqueued")
+            .filter(|_| kind == Some(CacheKind::Queue))
+        {
+            let exists: bool = conn
+                .exists(queue_key)
+                .await
+                .map_err(|e| DbError::redis("Redis exists failed", e))?;
+            return Ok((!exists).then_some(GcRule::OrphanedQueueIndex));
+        }
+
+        if kind.is_some_and(|kind| EXPIRING_KINDS.contains(&kind)) {
+            // -1 means the key exists without an expiry
+            let ttl: i64 = conn
+                .ttl(key)
+                .await
+                .map_err(|e| DbError::redis("Redis ttl failed", e))?;
+            if ttl == -1 {
+                return Ok(Some(GcRule::MissingTtl));
+            }
+        }
+
+        // rsr:check:v{n}:{platform}:{owner}/{repo}:{sha}:{check}
+        if let Some(rest) = key.strip_prefix(&CacheKind::Check.prefix()) {
+            let mut parts = rest.rsplitn(3, ':');
+            let (Some(_check), Some(sha), Some(repo)) = (parts.next(), parts.next(), parts.next()) else {
+                return Ok(None);
+            };
+
+            if !heads.contains_key(repo) {
+                let head: Option<String> = conn
+                    .get(format!("{}{}", CacheKind::CheckHead.prefix(), repo))
+                    .await
+                    .map_err(|e| DbError::redis("Redis get failed", e))?;
+                heads.insert(repo.to_string(), head);
+            }
+
+            let superseded = match heads.get(repo).and_then(Option::as_deref) {
+                Some(head) => head != sha,
+                // No head means the repo's scan state expired; its results are orphaned
+                None => true,
+            };
+            return Ok(superseded.then_some(GcRule::SupersededCheckResult));
+        }
+
+        Ok(None)
+    }
+}
@@ -2,148 +2,427 @@
 //!
 //! Used for:
 //! - Dependency graphs
-//! - Repository relationships
+//! - Repository relationships (forks and mirrors)
+//! - Organization hierarchies
 //! - Compliance inheritance
 //! - Impact analysis
+//!
+//! The engine talks to the graph through [`GraphStore`]. Builds with the
+//! `graphs-memory` feature can set `RSR_ARANGODB_URL=memory://` to keep the
+//! graph in process instead (see [`MemoryGraph`](super::graphs_memory::MemoryGraph)).
 
-use crate::{Result, RsrError};
-use arangors::client::reqwest::ReqwestClient;
-use arangors::{AqlQuery, Connection, Database};
+use super::config::{ArangoConfig, DbConfig};
+#[cfg(feature = "graphs-arangodb")]
+use super::config::{ArangoAuth, Credentials};
+#[cfg(feature = "graphs-arangodb")]
+use super::error::DbError;
+use crate::config::{EffectivePolicy, PolicyConfig};
+use crate::{RepoRef, Result};
+#[cfg(feature = "graphs-arangodb")]
+use arangors::graph::{EdgeDefinition, Graph};
+#[cfg(feature = "graphs-arangodb")]
+use arangors::transaction::{TransactionCollections, TransactionSettings};
+#[cfg(feature = "graphs-arangodb")]
+use arangors::{AqlQuery, ClientError, Database, GenericConnection};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "graphs-arangodb")]
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Deepest organization nesting traversed: GitLab allows 20 levels of
+/// subgroups, plus a team below them and an enterprise above them
+pub(super) const MAX_HIERARCHY_DEPTH: u32 = 23;
+
+/// Deepest dependency followed: a package this many hops from the
+/// repository, through the repositories its dependencies are developed in
+pub(super) const MAX_DEPENDENCY_DEPTH: u32 = 20;
+
+/// Edges a traversal follows to reach `MAX_DEPENDENCY_DEPTH` packages deep:
+/// a package edge for each level and a repository edge between levels
+pub(super) const MAX_DEPENDENCY_EDGES: u32 = 2 * MAX_DEPENDENCY_DEPTH - 1;
+
+/// Longest chain of forks followed, e.g. a fork of a fork of a mirror
+pub(super) const MAX_FORK_DEPTH: u32 = 20;
+
+/// Most dependency paths returned when explaining an impact
+pub(super) const MAX_IMPACT_PATHS: u32 = 50;
+
+/// Named graph over every edge collection
+#[cfg(feature = "graphs-arangodb")]
+const GRAPH_NAME: &str = "dependency_graph";
+
+#[cfg(feature = "graphs-arangodb")]
+const VERTEX_COLLECTIONS: [&str; 4] = ["repositories", "packages", "vulnerabilities", "organizations"];
+
+/// Edge collections, with the vertex collections they lead from and to
+#[cfg(feature = "graphs-arangodb")]
+const EDGE_DEFINITIONS: [(&str, &[&str], &[&str]); 5] = [
+    ("depends_on", &["repositories"], &["packages"]),
+    ("affects", &["vulnerabilities"], &["packages"]),
+    ("forks", &["repositories"], &["repositories"]),
+    ("hosted_at", &["packages"], &["repositories"]),
+    ("member_of", &["repositories", "organizations"], &["organizations"]),
+];
+
+/// Everything the engine keeps in its graph. Implemented by [`ArangoPool`]
+/// and, behind the `graphs-memory` feature, by
+/// [`MemoryGraph`](super::graphs_memory::MemoryGraph).
+#[async_trait::async_trait]
+pub trait GraphStore: Send + Sync {
+    async fn ping(&self) -> Result<()>;
+
+    /// Replace a dropped connection, e.g. after the server restarted and
+    /// forgot the session's JWT
+    async fn reconnect(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Create whatever the store needs; safe to run again
+    async fn migrate(&self) -> Result<()>;
+
+    /// Record that a repository depends on a registry package, updating
+    /// the version if it already does
+    async fn add_dependency(&self, repo_key: &str, registry: &str, package_name: &str, package_version: &str) -> Result<()>;
+    /// Replace a registered repository's direct dependencies with those
+    /// found in one scan of its manifests
+    async fn upsert_dependency_snapshot(&self, repo_key: &str, dependencies: Vec<Dependency>) -> Result<DependencySnapshot>;
+    /// Packages a repository depends on, directly or through the
+    /// repositories its dependencies are developed in, each at its
+    /// shallowest depth
+    async fn get_dependencies(&self, repo_key: &str) -> Result<Vec<Dependency>>;
+    /// Repositories depending on a package a vulnerability affects, directly
+    /// or transitively
+    async fn get_affected_repos(&self, vulnerability_id: &str) -> Result<Vec<String>>;
+    /// Each chain of dependencies from a repository to a package affected by
+    /// a vulnerability, known by its id or an alias, shortest first
+    async fn explain_impact(&self, vulnerability_id: &str, repo_key: &str) -> Result<Vec<ImpactPath>>;
+    /// Repositories depending on the packages developed in this one,
+    /// directly or transitively
+    async fn get_dependents(&self, repo_key: &str) -> Result<Vec<String>>;
+    /// Depth of a repository's dependency tree, 0 without dependencies
+    async fn get_dependency_depth(&self, repo_key: &str) -> Result<u32>;
+
+    /// Add a vulnerability affecting a package
+    async fn add_vulnerability(&self, vuln: &Vulnerability, package_key: &str) -> Result<()>;
+    /// Store an advisory from `source`, merged into any vulnerability
+    /// sharing an id with it, and link it to the packages it affects.
+    /// Returns the key it is stored under.
+    async fn upsert_advisory(
+        &self,
+        vuln: &Vulnerability,
+        source: &str,
+        updated_at: chrono::DateTime<chrono::Utc>,
+        packages: &[(String, String)],
+    ) -> Result<String>;
+    /// Latest update time among the advisories stored from `source`
+    async fn latest_advisory_update(&self, source: &str) -> Result<Option<chrono::DateTime<chrono::Utc>>>;
+
+    /// Register a repository, returning its key
+    async fn register_repository(&self, platform: &str, owner: &str, repo: &str) -> Result<String>;
+    /// Link a registry package to the repository it is developed in,
+    /// replacing any earlier link
+    async fn link_package_repository(&self, registry: &str, package_name: &str, repo_key: &str) -> Result<()>;
+    /// Repository a registry package was discovered in, if any
+    async fn get_package_repository(&self, registry: &str, package_name: &str) -> Result<Option<RepoRef>>;
+
+    /// Record that a registered repository is a fork or mirror of another,
+    /// replacing any earlier upstream
+    async fn link_fork(&self, fork_key: &str, upstream_key: &str, kind: UpstreamKind) -> Result<()>;
+    /// Repositories a fork or mirror descends from, nearest first
+    async fn get_upstreams(&self, repo_key: &str) -> Result<Vec<RepoRef>>;
+    /// Every repository in a repository's fork network, root first
+    async fn get_fork_network(&self, repo_key: &str) -> Result<Vec<ForkMember>>;
+
+    /// Place a repository under the units in `ancestry`, nearest first
+    async fn register_hierarchy(&self, repo_key: &str, ancestry: &[String]) -> Result<()>;
+    /// Units a registered repository sits under, nearest first
+    async fn get_ancestry(&self, repo_key: &str) -> Result<Vec<String>>;
+    /// Repositories at any depth below an organizational unit
+    async fn get_organization_repositories(&self, unit: &str) -> Result<Vec<RepoRef>>;
+
+    /// Re-key a repository after a transfer or rename, keeping its
+    /// dependency, fork and package edges. Organization membership is
+    /// dropped, to be registered again under the new owner.
+    async fn transfer_repository(&self, from: &RepoRef, to: &RepoRef) -> Result<String>;
+
+    /// Policy a registered repository is held to, resolved along the units
+    /// it is registered under: each unit's constraints carry down to the
+    /// teams and repositories below it
+    async fn effective_policy(&self, repo_key: &str, policies: &PolicyConfig) -> Result<EffectivePolicy> {
+        Ok(policies.effective_policy(&self.get_ancestry(repo_key).await?))
+    }
+}
+
+/// Connect to the graph store named by `RSR_ARANGODB_URL`: in process for
+/// `memory://` (optionally followed by a snapshot path), else ArangoDB.
+/// Each needs its feature, `graphs-memory` or `graphs-arangodb`.
+pub async fn connect_from_env() -> Result<Arc<dyn GraphStore>> {
+    connect_with(&DbConfig::load()?.arangodb).await
+}
+
+/// [`connect_from_env`], with the given settings
+pub async fn connect_with(config: &ArangoConfig) -> Result<Arc<dyn GraphStore>> {
+    match config.url.strip_prefix("memory://") {
+        #[cfg(feature = "graphs-memory")]
+        Some(snapshot) => {
+            let snapshot = (!snapshot.is_empty()).then(|| std::path::PathBuf::from(snapshot));
+            Ok(Arc::new(super::graphs_memory::MemoryGraph::open(snapshot)?))
+        }
+        #[cfg(not(feature = "graphs-memory"))]
+        Some(_) => Err(crate::RsrError::Config(
+            "RSR_ARANGODB_URL is memory:// but this build lacks the graphs-memory feature".to_string(),
+        )),
+        #[cfg(feature = "graphs-arangodb")]
+        None => Ok(Arc::new(ArangoPool::connect_with(config.clone()).await?)),
+        #[cfg(not(feature = "graphs-arangodb"))]
+        None => Err(crate::RsrError::Config(format!(
+            "RSR_ARANGODB_URL is {} but this build lacks the graphs-arangodb feature",
+            config.url
+        ))),
+    }
+}
+
+/// HTTP client for ArangoDB on the engine's rustls-only `reqwest`. The
+/// client arangors ships enables reqwest's default features, which would
+/// link OpenSSL through native-tls.
+#[cfg(feature = "graphs-arangodb")]
+#[derive(Debug, Clone)]
+pub struct ArangoClient {
+    client: reqwest::Client,
+    headers: http::HeaderMap,
+}
+
+#[cfg(feature = "graphs-arangodb")]
+#[async_trait::async_trait]
+impl arangors::client::ClientExt for ArangoClient {
+    fn new<U: Into<Option<http::HeaderMap>>>(headers: U) -> std::result::Result<Self, ClientError> {
+        let headers = headers.into().unwrap_or_default();
+        let client = reqwest::Client::builder()
+            .default_headers(headers.clone())
+            .build()
+            .map_err(|e| ClientError::HttpClient(e.to_string()))?;
+        Ok(Self { client, headers })
+    }
+
+    fn headers(&mut self) -> &mut http::HeaderMap<http::HeaderValue> {
+        &mut self.headers
+    }
+
+    async fn request(&self, request: http::Request<String>) -> std::result::Result<http::Response<String>, ClientError> {
+        let request = reqwest::Request::try_from(request).map_err(|e| ClientError::HttpClient(e.to_string()))?;
+        let response = self
+            .client
+            .execute(request)
+            .await
+            .map_err(|e| ClientError::HttpClient(e.to_string()))?;
+
+        let mut builder = http::Response::builder()
+            .status(response.status())
+            .version(response.version());
+        for (name, value) in response.headers() {
+            builder = builder.header(name, value);
+        }
+        let body = response.text().await.map_err(|e| ClientError::HttpClient(e.to_string()))?;
+        builder.body(body).map_err(|e| ClientError::HttpClient(e.to_string()))
+    }
+}
+
+#[cfg(feature = "graphs-arangodb")]
+type Connection = GenericConnection<ArangoClient>;
 
 /// ArangoDB connection pool
+#[cfg(feature = "graphs-arangodb")]
 pub struct ArangoPool {
-    db: Database<ReqwestClient>,
-    #[allow(dead_code)]
-    url: String,
+    db: std::sync::RwLock<Database<ArangoClient>>,
+    target: ArangoTarget,
 }
 
+/// Where and as whom to connect, kept to reconnect
+#[cfg(feature = "graphs-arangodb")]
+struct ArangoTarget(ArangoConfig);
+
+#[cfg(feature = "graphs-arangodb")]
+impl ArangoTarget {
+    /// Authenticate and open the database, creating it if it doesn't exist yet
+    async fn connect(&self) -> Result<Database<ArangoClient>> {
+        let config = &self.0;
+        tracing::info!("Connecting to ArangoDB: {}/{}", config.url, config.database);
+
+        let (username, password) = (config.credentials.username(), config.credentials.password());
+        let establish = async {
+            match config.auth() {
+                ArangoAuth::Jwt => Connection::establish_jwt(&config.url, username, password).await,
+                ArangoAuth::Basic => Connection::establish_basic_auth(&config.url, username, password).await,
+                ArangoAuth::None => Connection::establish_without_auth(&config.url).await,
+            }
+        };
+        let conn = tokio::time::timeout(config.connect_timeout(), establish)
+            .await
+            .map_err(|_| {
+                DbError::Backend(format!("ArangoDB connection timed out after {}s", config.connect_timeout_secs))
+            })?
+            .map_err(|e| DbError::arango("ArangoDB connection failed", e))?;
+
+        match conn.db(&config.database).await {
+            Ok(db) => Ok(db),
+            Err(e) if is_not_found(&e) => {
+                tracing::info!("Creating ArangoDB database {}", config.database);
+                Ok(conn
+                    .create_database(&config.database)
+                    .await
+                    .map_err(|e| DbError::arango(format!("Failed to create database {}", config.database), e))?)
+            }
+            Err(e) => Err(DbError::arango("ArangoDB database access failed", e).into()),
+        }
+    }
+}
+
+#[cfg(feature = "graphs-arangodb")]
 impl ArangoPool {
-    /// Connect from environment variables
+    /// Connect with the settings from the environment (see [`super::config`])
     pub async fn connect_from_env() -> Result<Self> {
-        let url = std::env::var("RSR_ARANGODB_URL")
-            .unwrap_or_else(|_| "http://localhost:8529".to_string());
-        let database = std::env::var("RSR_ARANGODB_DB")
-            .unwrap_or_else(|_| "rsr_graphs".to_string());
-        let username = std::env::var("RSR_ARANGODB_USER")
-            .unwrap_or_else(|_| "root".to_string());
-        let password = std::env::var("RSR_ARANGODB_PASS")
-            .unwrap_or_else(|_| "".to_string());
-
-        Self::connect(&url, &database, &username, &password).await
+        Self::connect_with(DbConfig::load()?.arangodb).await
     }
 
-    /// Connect to ArangoDB
+    /// Connect to ArangoDB with JWT authentication, creating the database
+    /// if it doesn't exist yet
     pub async fn connect(url: &str, database: &str, username: &str, password: &str) -> Result<Self> {
-        tracing::info!("Connecting to ArangoDB: {}/{}", url, database);
-
-        let conn = Connection::establish_basic_auth(url, username, password)
-            .await
-            .map_err(|e| RsrError::Platform(format!("ArangoDB connection failed: {}", e)))?;
+        Self::connect_with(ArangoConfig {
+            url: url.to_string(),
+            database: database.to_string(),
+            credentials: Credentials {
+                username: Some(username.to_string()),
+                password: Some(password.to_string()),
+                password_file: None,
+            },
+            ..Default::default()
+        })
+        .await
+    }
 
-        let db = conn
-            .db(database)
-            .await
-            .map_err(|e| RsrError::Platform(format!("ArangoDB database access failed: {}", e)))?;
+    /// Connect to ArangoDB as configured, creating the database if it
+    /// doesn't exist yet
+    pub async fn connect_with(config: ArangoConfig) -> Result<Self> {
+        let target = ArangoTarget(config);
+        let db = target.connect().await?;
 
         Ok(Self {
-            db,
-            url: url.to_string(),
+            db: std::sync::RwLock::new(db),
+            target,
         })
     }
 
+    /// Handle on the current connection
+    fn db(&self) -> Database<ArangoClient> {
+        self.db.read().expect("arango database lock poisoned").clone()
+    }
+
+    /// Authenticate again and replace the connection, e.g. after the server
+    /// restarted or the JWT expired. Requests already running finish on the
+    /// old connection.
+    pub async fn reconnect(&self) -> Result<()> {
+        let db = self.target.connect().await?;
+        *self.db.write().expect("arango database lock poisoned") = db;
+        tracing::info!("Reconnected to ArangoDB: {}/{}", self.target.0.url, self.target.0.database);
+        Ok(())
+    }
+
     /// Ping the database
     pub async fn ping(&self) -> Result<()> {
         // Run a simple AQL query to verify connection
-        self.db
+        self.db()
             .aql_str::<serde_json::Value>("RETURN 1")
             .await
-            .map_err(|e| RsrError::Platform(format!("ArangoDB ping failed: {}", e)))?;
+            .map_err(|e| DbError::arango("ArangoDB ping failed", e))?;
         tracing::debug!("ArangoDB ping successful");
         Ok(())
     }
 
-    /// Run database migrations
+    /// Create the collections and the named graph. Safe to run again, and
+    /// alongside another instance doing the same.
     pub async fn migrate(&self) -> Result<()> {
         tracing::info!("Running ArangoDB migrations");
 
-        // Create vertex collections
-        let collections = ["repositories", "packages", "vulnerabilities"];
-        for name in collections {
-            if self.db.collection(name).await.is_err() {
-                self.db
-                    .create_collection(name)
-                    .await
-                    .map_err(|e| RsrError::Platform(format!("Failed to create collection {}: {}", name, e)))?;
-                tracing::debug!("Created collection: {}", name);
-            }
+        for name in VERTEX_COLLECTIONS {
+            self.ensure_collection(name, false).await?;
         }
-
-        // Create edge collections
-        let edge_collections = ["depends_on", "affects", "forks"];
-        for name in edge_collections {
-            if self.db.collection(name).await.is_err() {
-                // Use raw create for edge collection
-                match self.db.create_edge_collection(name).await {
-                    Ok(_) => tracing::debug!("Created edge collection: {}", name),
-                    Err(_) => tracing::debug!("Edge collection {} may already exist", name),
-                }
-            }
+        for (name, _, _) in EDGE_DEFINITIONS {
+            self.ensure_collection(name, true).await?;
         }
-
-        // Create graph if it doesn't exist
         self.create_dependency_graph().await?;
 
         tracing::info!("ArangoDB migrations complete");
         Ok(())
     }
 
-    /// Create the dependency graph
+    /// Create a collection unless it exists
+    async fn ensure_collection(&self, name: &str, edge: bool) -> Result<()> {
+        match self.db().collection(name).await {
+            Ok(_) => return Ok(()),
+            Err(e) if is_not_found(&e) => {}
+            Err(e) => return Err(DbError::arango(format!("Failed to look up collection {}", name), e).into()),
+        }
+
+        let created = if edge {
+            self.db().create_edge_collection(name).await.map(|_| ())
+        } else {
+            self.db().create_collection(name).await.map(|_| ())
+        };
+        match created {
+            Ok(()) => tracing::debug!("Created collection: {}", name),
+            // Another instance created it first
+            Err(e) if is_conflict(&e) => {}
+            Err(e) => return Err(DbError::arango(format!("Failed to create collection {}", name), e).into()),
+        }
+        Ok(())
+    }
+
+    /// Create the named graph over every edge collection, for tools that
+    /// browse the graph. Queries name their edge collections directly.
     async fn create_dependency_graph(&self) -> Result<()> {
-        // Check if graph exists via AQL
-        let existing: Vec<serde_json::Value> = self.db
-            .aql_str("FOR g IN _graphs() FILTER g._key == 'dependency_graph' RETURN g")
-            .await
-            .unwrap_or_default();
-
-        if existing.is_empty() {
-            // Create graph via AQL (arangors graph API is complex)
-            let create_graph_aql = r#"
-                LET edgeDefs = [
-                    { collection: "depends_on", from: ["repositories"], to: ["packages"] },
-                    { collection: "affects", from: ["vulnerabilities"], to: ["packages"] },
-                    { collection: "forks", from: ["repositories"], to: ["repositories"] }
-                ]
-                INSERT { _key: "dependency_graph", edgeDefinitions: edgeDefs } INTO _graphs
-                RETURN NEW
-            "#;
-            self.db
-                .aql_str::<serde_json::Value>(create_graph_aql)
-                .await
-                .map_err(|e| RsrError::Platform(format!("Failed to create graph: {}", e)))?;
-            tracing::debug!("Created dependency_graph");
+        match self.db().graph(GRAPH_NAME).await {
+            Ok(_) => return Ok(()),
+            Err(e) if is_not_found(&e) => {}
+            Err(e) => return Err(DbError::arango(format!("Failed to look up graph {}", GRAPH_NAME), e).into()),
         }
 
+        let edge_definitions = EDGE_DEFINITIONS
+            .iter()
+            .map(|(collection, from, to)| EdgeDefinition {
+                collection: collection.to_string(),
+                from: from.iter().map(|name| name.to_string()).collect(),
+                to: to.iter().map(|name| name.to_string()).collect(),
+            })
+            .collect();
+        let graph = Graph::builder()
+            .name(GRAPH_NAME.to_string())
+            .edge_definitions(edge_definitions)
+            .build();
+
+        match self.db().create_graph(graph, false).await {
+            Ok(_) => tracing::debug!("Created {}", GRAPH_NAME),
+            Err(e) if is_conflict(&e) => {}
+            Err(e) => return Err(DbError::arango(format!("Failed to create graph {}", GRAPH_NAME), e).into()),
+        }
         Ok(())
     }
 
-    /// Add a dependency relationship
+    /// Record that a repository depends on a registry package. The package
+    /// vertex is shared by every version; the version is kept on the edge,
+    /// and adding the dependency again updates it.
     pub async fn add_dependency(
         &self,
         repo_key: &str,
+        registry: &str,
         package_name: &str,
         package_version: &str,
     ) -> Result<()> {
-        tracing::debug!(
-            "Adding dependency: {} -> {}@{}",
-            repo_key, package_name, package_version
-        );
+        let package_key = registry_package_key(registry, package_name);
+        tracing::debug!("Adding dependency: {} -> {}@{}", repo_key, package_key, package_version);
 
-        // Ensure package exists
-        let package_key = format!("{}@{}", package_name, package_version);
         let upsert_package = r#"
             UPSERT { _key: @key }
-            INSERT { _key: @key, name: @name, version: @version }
+            INSERT { _key: @key, name: @name, registry: @registry }
             UPDATE {}
             IN packages
             RETURN NEW
@@ -152,139 +431,368 @@ pub async fn add_dependency(
             .query(upsert_package)
             .bind_var("key", package_key.clone())
             .bind_var("name", package_name.to_string())
-            .bind_var("version", package_version.to_string())
+            .bind_var("registry", registry.to_string())
             .build();
-        self.db
+        self.db()
             .aql_query::<serde_json::Value>(aql)
             .await
-            .map_err(|e| RsrError::Platform(format!("Failed to upsert package: {}", e)))?;
+            .map_err(|e| DbError::arango("Failed to upsert package", e))?;
 
-        // Create edge
-        let edge_key = format!("{}__{}", repo_key, package_key);
-        let create_edge = r#"
+        let upsert_edge = r#"
             UPSERT { _key: @key }
             INSERT {
                 _key: @key,
                 _from: CONCAT("repositories/", @repo),
-                _to: CONCAT("packages/", @package)
+                _to: CONCAT("packages/", @package),
+                version: @version
             }
-            UPDATE {}
+            UPDATE { version: @version }
             IN depends_on
             RETURN NEW
         "#;
         let aql = AqlQuery::builder()
-            .query(create_edge)
-            .bind_var("key", edge_key)
+            .query(upsert_edge)
+            .bind_var("key", format!("{}__{}", repo_key, package_key))
             .bind_var("repo", repo_key.to_string())
             .bind_var("package", package_key)
+            .bind_var("version", package_version.to_string())
             .build();
-        self.db
+        self.db()
             .aql_query::<serde_json::Value>(aql)
             .await
-            .map_err(|e| RsrError::Platform(format!("Failed to create dependency edge: {}", e)))?;
+            .map_err(|e| DbError::arango("Failed to create dependency edge", e))?;
 
         Ok(())
     }
 
-    /// Get all dependencies for a repository
+    /// Replace a repository's direct dependencies with `dependencies`, as
+    /// found in one scan of its manifests. Edges are diffed against the
+    /// stored ones: new dependencies are inserted, changed versions updated
+    /// and dependencies no longer listed removed, in one transaction, and
+    /// the repository's snapshot version is bumped. Entries not marked
+    /// `direct` are skipped, since transitive dependencies are reached
+    /// through the repositories their packages are developed in.
+    ///
+    /// The repository must have been registered.
+    pub async fn upsert_dependency_snapshot(&self, repo_key: &str, dependencies: Vec<Dependency>) -> Result<DependencySnapshot> {
+        // Listed twice, the last entry wins
+        let wanted: BTreeMap<String, Dependency> = dependencies
+            .into_iter()
+            .filter(|dep| dep.direct)
+            .map(|dep| (format!("{}__{}", repo_key, registry_package_key(&dep.registry, &dep.name)), dep))
+            .collect();
+
+        let settings = TransactionSettings::builder()
+            .collections(
+                TransactionCollections::builder()
+                    .write(vec![
+                        "repositories".to_string(),
+                        "packages".to_string(),
+                        "depends_on".to_string(),
+                    ])
+                    .build(),
+            )
+            .build();
+
+        let tx = self.db()
+            .begin_transaction(settings)
+            .await
+            .map_err(|e| DbError::arango("Failed to begin transaction", e))?;
+
+        let applied = async {
+            let existing_edges = r#"
+                FOR e IN depends_on
+                    FILTER e._from == CONCAT("repositories/", @repo)
+                    RETURN [e._key, NOT_NULL(e.version, "")]
+            "#;
+            let aql = AqlQuery::builder()
+                .query(existing_edges)
+                .bind_var("repo", repo_key.to_string())
+                .build();
+            let existing: BTreeMap<String, String> = tx
+                .aql_query::<(String, String)>(aql)
+                .await?
+                .into_iter()
+                .collect();
+
+            let mut snapshot = DependencySnapshot::default();
+            let mut inserted = Vec::new();
+            let mut updated = Vec::new();
+            for (key, dep) in &wanted {
+                match existing.get(key) {
+                    None => {
+                        let package_key = registry_package_key(&dep.registry, &dep.name);
+                        inserted.push(serde_json::json!({
+                            "_key": key,
+                            "_from": format!("repositories/{}", repo_key),
+                            "_to": format!("packages/{}", package_key),
+                            "version": dep.version,
+                            "package": { "_key": package_key, "name": dep.name, "registry": dep.registry },
+                        }));
+                    }
+                    Some(version) if *version != dep.version => {
+                        updated.push(serde_json::json!({ "_key": key, "version": dep.version }));
+                    }
+                    Some(_) => snapshot.unchanged += 1,
+                }
+            }
+            let stale: Vec<serde_json::Value> = existing
+                .keys()
+                .filter(|key| !wanted.contains_key(*key))
+                .map(|key| serde_json::Value::String(key.clone()))
+                .collect();
+            (snapshot.added, snapshot.updated, snapshot.removed) = (inserted.len(), updated.len(), stale.len());
+
+            let statements = [
+                (
+                    r#"
+                        FOR edge IN @edges
+                            UPSERT { _key: edge.package._key }
+                            INSERT edge.package
+                            UPDATE {}
+                            IN packages
+                            RETURN NEW._key
+                    "#,
+                    inserted.clone(),
+                ),
+                (
+                    r#"
+                        FOR edge IN @edges
+                            INSERT UNSET(edge, "package") IN depends_on
+                            RETURN NEW._key
+                    "#,
+                    inserted,
+                ),
+                (
+                    r#"
+                        FOR edge IN @edges
+                            UPDATE edge IN depends_on
+                            RETURN NEW._key
+                    "#,
+                    updated,
+                ),
+                (
+                    r#"
+                        FOR key IN @edges
+                            REMOVE key IN depends_on
+                            RETURN OLD._key
+                    "#,
+                    stale,
+                ),
+            ];
+            for (statement, edges) in statements {
+                if edges.is_empty() {
+                    continue;
+                }
+                let aql = AqlQuery::builder()
+                    .query(statement)
+                    .bind_var("edges", serde_json::Value::Array(edges))
+                    .build();
+                tx.aql_query::<serde_json::Value>(aql).await?;
+            }
+
+            let bump_version = r#"
+                LET repo = DOCUMENT("repositories", @repo)
+                UPDATE repo WITH {
+                    dependency_snapshot: {
+                        version: NOT_NULL(repo.dependency_snapshot.version, 0) + 1,
+                        dependencies: @count,
+                        taken_at: DATE_ISO8601(DATE_NOW())
+                    }
+                } IN repositories
+                RETURN NEW.dependency_snapshot.version
+            "#;
+            let aql = AqlQuery::builder()
+                .query(bump_version)
+                .bind_var("repo", repo_key.to_string())
+                .bind_var("count", wanted.len())
+                .build();
+            snapshot.version = tx.aql_query::<u64>(aql).await?.into_iter().next().unwrap_or_default();
+            Ok::<_, ClientError>(snapshot)
+        };
+
+        let snapshot = match applied.await {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                let _ = tx.abort().await;
+                return Err(DbError::arango(format!("Failed to store dependency snapshot for {}", repo_key), e).into());
+            }
+        };
+        tx.commit()
+            .await
+            .map_err(|e| DbError::arango("Failed to commit dependency snapshot", e))?;
+
+        tracing::debug!(
+            "Dependency snapshot {} of {}: {} added, {} updated, {} removed",
+            snapshot.version,
+            repo_key,
+            snapshot.added,
+            snapshot.updated,
+            snapshot.removed
+        );
+        Ok(snapshot)
+    }
+
+    /// Packages a repository depends on, directly or through the
+    /// repositories its dependencies are developed in. A package reached
+    /// more than one way is reported at its shallowest depth.
     pub async fn get_dependencies(&self, repo_key: &str) -> Result<Vec<Dependency>> {
         tracing::debug!("Getting dependencies for {}", repo_key);
 
         let aql_query = r#"
-            FOR v, e, p IN 1..10 OUTBOUND CONCAT("repositories/", @repo)
-                GRAPH 'dependency_graph'
+            FOR v, e, p IN 1..@max_edges OUTBOUND CONCAT("repositories/", @repo) depends_on, hosted_at
+                OPTIONS { order: "bfs", uniqueVertices: "global" }
                 FILTER IS_SAME_COLLECTION("packages", v)
-                RETURN DISTINCT {
+                LET depth = (LENGTH(p.edges) + 1) / 2
+                SORT depth, v.registry, v.name
+                RETURN {
+                    registry: NOT_NULL(v.registry, ""),
                     name: v.name,
-                    version: v.version,
-                    depth: LENGTH(p.edges),
-                    direct: LENGTH(p.edges) == 1
+                    version: NOT_NULL(e.version, ""),
+                    depth: depth,
+                    direct: depth == 1
                 }
         "#;
 
         let aql = AqlQuery::builder()
             .query(aql_query)
             .bind_var("repo", repo_key.to_string())
+            .bind_var("max_edges", MAX_DEPENDENCY_EDGES)
             .build();
 
-        let deps: Vec<Dependency> = self.db
+        self.db()
             .aql_query(aql)
             .await
-            .map_err(|e| RsrError::Platform(format!("Failed to get dependencies: {}", e)))?;
-
-        Ok(deps)
+            .map_err(|e| DbError::arango("Failed to get dependencies", e).into())
     }
 
-    /// Get repositories affected by a vulnerability
+    /// Repositories depending on a package a vulnerability affects, directly
+    /// or transitively. Every version the edges record counts as affected.
     pub async fn get_affected_repos(&self, vulnerability_id: &str) -> Result<Vec<String>> {
         tracing::debug!("Getting repos affected by {}", vulnerability_id);
 
         let aql_query = r#"
-            FOR v IN 1..10 INBOUND CONCAT("vulnerabilities/", @vuln)
-                GRAPH 'dependency_graph'
-                FILTER IS_SAME_COLLECTION("repositories", v)
-                RETURN DISTINCT v._key
+            FOR package IN 1..1 OUTBOUND CONCAT("vulnerabilities/", @vuln) affects
+                FOR v IN 1..@max_edges INBOUND package depends_on, hosted_at
+                    OPTIONS { order: "bfs", uniqueVertices: "global" }
+                    FILTER IS_SAME_COLLECTION("repositories", v)
+                    COLLECT key = v._key
+                    SORT key
+                    RETURN key
         "#;
 
         let aql = AqlQuery::builder()
             .query(aql_query)
             .bind_var("vuln", vulnerability_id.to_string())
+            .bind_var("max_edges", MAX_DEPENDENCY_EDGES)
             .build();
 
-        let repos: Vec<String> = self.db
+        self.db()
             .aql_query(aql)
             .await
-            .map_err(|e| RsrError::Platform(format!("Failed to get affected repos: {}", e)))?;
+            .map_err(|e| DbError::arango("Failed to get affected repos", e).into())
+    }
+
+    /// How a repository is affected by a vulnerability, known by its id or
+    /// one of its aliases: each chain of dependencies from the repository to
+    /// an affected package, shortest first. Empty if the repository isn't
+    /// affected; at most `MAX_IMPACT_PATHS` are returned.
+    pub async fn explain_impact(&self, vulnerability_id: &str, repo_key: &str) -> Result<Vec<ImpactPath>> {
+        tracing::debug!("Explaining impact of {} on {}", vulnerability_id, repo_key);
 
-        Ok(repos)
+        let aql_query = r#"
+            LET vuln = FIRST(
+                FOR v IN vulnerabilities
+                    FILTER v._key == @vuln OR @vuln IN NOT_NULL(v.aliases, [])
+                    RETURN v
+            )
+            LET affected = vuln == null ? [] : (
+                FOR package IN 1..1 OUTBOUND vuln affects
+                    RETURN package._id
+            )
+            FOR v, e, p IN 1..@max_edges OUTBOUND CONCAT("repositories/", @repo) depends_on, hosted_at
+                OPTIONS { order: "bfs", uniqueVertices: "path" }
+                FILTER v._id IN affected
+                LIMIT @max_paths
+                LET steps = (
+                    FOR i IN 0..LENGTH(p.edges) - 1
+                        FILTER IS_SAME_COLLECTION("depends_on", p.edges[i])
+                        RETURN {
+                            registry: NOT_NULL(p.vertices[i + 1].registry, ""),
+                            name: p.vertices[i + 1].name,
+                            version: NOT_NULL(p.edges[i].version, ""),
+                            depth: (i + 2) / 2,
+                            dependent: p.vertices[i]._key
+                        }
+                )
+                RETURN { vulnerability: vuln._key, depth: LENGTH(steps), packages: steps }
+        "#;
+
+        let aql = AqlQuery::builder()
+            .query(aql_query)
+            .bind_var("vuln", vulnerability_id.to_string())
+            .bind_var("repo", repo_key.to_string())
+            .bind_var("max_edges", MAX_DEPENDENCY_EDGES)
+            .bind_var("max_paths", MAX_IMPACT_PATHS)
+            .build();
+
+        self.db()
+            .aql_query(aql)
+            .await
+            .map_err(|e| DbError::arango("Failed to explain impact", e).into())
     }
 
-    /// Calculate compliance impact (repos depending on this one)
+    /// Repositories depending on the packages developed in this one,
+    /// directly or transitively: the repositories a compliance change here
+    /// has an impact on
     pub async fn get_dependents(&self, repo_key: &str) -> Result<Vec<String>> {
         tracing::debug!("Getting dependents of {}", repo_key);
 
         let aql_query = r#"
-            FOR v IN 1..10 INBOUND CONCAT("repositories/", @repo)
-                GRAPH 'dependency_graph'
-                FILTER IS_SAME_COLLECTION("repositories", v)
-                RETURN DISTINCT v._key
+            FOR v IN 1..@max_edges INBOUND CONCAT("repositories/", @repo) depends_on, hosted_at
+                OPTIONS { order: "bfs", uniqueVertices: "global" }
+                FILTER IS_SAME_COLLECTION("repositories", v) AND v._key != @repo
+                SORT v._key
+                RETURN v._key
         "#;
 
         let aql = AqlQuery::builder()
             .query(aql_query)
             .bind_var("repo", repo_key.to_string())
+            .bind_var("max_edges", MAX_DEPENDENCY_EDGES)
             .build();
 
-        let repos: Vec<String> = self.db
+        self.db()
             .aql_query(aql)
             .await
-            .map_err(|e| RsrError::Platform(format!("Failed to get dependents: {}", e)))?;
-
-        Ok(repos)
+            .map_err(|e| DbError::arango("Failed to get dependents", e).into())
     }
 
-    /// Get dependency tree depth
+    /// Depth of a repository's dependency tree: 0 without dependencies, 1
+    /// with only direct ones, and so on
     pub async fn get_dependency_depth(&self, repo_key: &str) -> Result<u32> {
         tracing::debug!("Getting dependency depth for {}", repo_key);
 
         let aql_query = r#"
-            LET paths = (
-                FOR v, e, p IN 1..100 OUTBOUND CONCAT("repositories/", @repo)
-                    GRAPH 'dependency_graph'
-                    RETURN LENGTH(p.edges)
+            LET depths = (
+                FOR v, e, p IN 1..@max_edges OUTBOUND CONCAT("repositories/", @repo) depends_on, hosted_at
+                    OPTIONS { order: "bfs", uniqueVertices: "global" }
+                    FILTER IS_SAME_COLLECTION("packages", v)
+                    RETURN (LENGTH(p.edges) + 1) / 2
             )
-            RETURN LENGTH(paths) > 0 ? MAX(paths) : 0
+            RETURN LENGTH(depths) > 0 ? MAX(depths) : 0
         "#;
 
         let aql = AqlQuery::builder()
             .query(aql_query)
             .bind_var("repo", repo_key.to_string())
+            .bind_var("max_edges", MAX_DEPENDENCY_EDGES)
             .build();
 
-        let depths: Vec<u32> = self.db
+        let depths: Vec<u32> = self.db()
             .aql_query(aql)
             .await
-            .map_err(|e| RsrError::Platform(format!("Failed to get dependency depth: {}", e)))?;
+            .map_err(|e| DbError::arango("Failed to get dependency depth", e))?;
 
         Ok(depths.into_iter().next().unwrap_or(0))
     }
@@ -315,14 +823,14 @@ pub async fn add_vulnerability(&self, vuln: &Vulnerability, package_key: &str) -
             .bind_var("key", vuln.id.clone())
             .bind_var("severity", vuln.severity.clone())
             .try_bind("affected", &vuln.affected_versions)
-            .map_err(|e| RsrError::Platform(format!("Failed to serialize affected_versions: {}", e)))?
+            .map_err(|e| DbError::Serialization(format!("Failed to serialize affected_versions: {}", e)))?
             .try_bind("patched", &vuln.patched_versions)
-            .map_err(|e| RsrError::Platform(format!("Failed to serialize patched_versions: {}", e)))?
+            .map_err(|e| DbError::Serialization(format!("Failed to serialize patched_versions: {}", e)))?
             .build();
-        self.db
+        self.db()
             .aql_query::<serde_json::Value>(aql)
             .await
-            .map_err(|e| RsrError::Platform(format!("Failed to upsert vulnerability: {}", e)))?;
+            .map_err(|e| DbError::arango("Failed to upsert vulnerability", e))?;
 
         // Create affects edge
         let edge_key = format!("{}__{}", vuln.id, package_key);
@@ -343,17 +851,152 @@ pub async fn add_vulnerability(&self, vuln: &Vulnerability, package_key: &str) -
             .bind_var("vuln", vuln.id.clone())
             .bind_var("package", package_key.to_string())
             .build();
-        self.db
+        self.db()
             .aql_query::<serde_json::Value>(aql)
             .await
-            .map_err(|e| RsrError::Platform(format!("Failed to create affects edge: {}", e)))?;
+            .map_err(|e| DbError::arango("Failed to create affects edge", e))?;
 
         Ok(())
     }
 
+    /// Store an advisory from `source` (e.g. `ghsa`) and link it to the
+    /// packages it affects, given as (registry, name). An advisory sharing
+    /// an id with a stored vulnerability - its own id or an alias such as a
+    /// CVE - is merged into that vertex rather than added alongside it.
+    /// Returns the key the advisory is stored under.
+    pub async fn upsert_advisory(
+        &self,
+        vuln: &Vulnerability,
+        source: &str,
+        updated_at: chrono::DateTime<chrono::Utc>,
+        packages: &[(String, String)],
+    ) -> Result<String> {
+        let ids: Vec<String> = std::iter::once(vuln.id.clone()).chain(vuln.aliases.iter().cloned()).collect();
+
+        let find_existing = r#"
+            FOR v IN vulnerabilities
+                FILTER v._key IN @ids OR LENGTH(INTERSECTION(NOT_NULL(v.aliases, []), @ids)) > 0
+                SORT v._key
+                LIMIT 1
+                RETURN v._key
+        "#;
+        let aql = AqlQuery::builder()
+            .query(find_existing)
+            .try_bind("ids", &ids)
+            .map_err(|e| DbError::Serialization(format!("Failed to serialize advisory ids: {}", e)))?
+            .build();
+        let existing: Vec<String> = self.db()
+            .aql_query(aql)
+            .await
+            .map_err(|e| DbError::arango(format!("Failed to look up advisory {}", vuln.id), e))?;
+        let key = existing.into_iter().next().unwrap_or_else(|| vuln.id.clone());
+        tracing::debug!("Upserting advisory {} from {} as {}", vuln.id, source, key);
+
+        let upsert_vuln = r#"
+            UPSERT { _key: @key }
+            INSERT {
+                _key: @key,
+                severity: @severity,
+                affected_versions: @affected,
+                patched_versions: @patched,
+                aliases: REMOVE_VALUE(@ids, @key),
+                source_updated_at: { [@source]: @updated_at }
+            }
+            UPDATE {
+                severity: @severity,
+                affected_versions: UNION_DISTINCT(NOT_NULL(OLD.affected_versions, []), @affected),
+                patched_versions: UNION_DISTINCT(NOT_NULL(OLD.patched_versions, []), @patched),
+                aliases: REMOVE_VALUE(UNION_DISTINCT(NOT_NULL(OLD.aliases, []), @ids), @key),
+                source_updated_at: MERGE(NOT_NULL(OLD.source_updated_at, {}), { [@source]: @updated_at })
+            }
+            IN vulnerabilities
+            RETURN NEW._key
+        "#;
+        let aql = AqlQuery::builder()
+            .query(upsert_vuln)
+            .bind_var("key", key.clone())
+            .bind_var("severity", vuln.severity.clone())
+            .bind_var("source", source.to_string())
+            .try_bind("updated_at", updated_at)
+            .map_err(|e| DbError::Serialization(format!("Failed to serialize updated_at: {}", e)))?
+            .try_bind("ids", &ids)
+            .map_err(|e| DbError::Serialization(format!("Failed to serialize advisory ids: {}", e)))?
+            .try_bind("affected", &vuln.affected_versions)
+            .map_err(|e| DbError::Serialization(format!("Failed to serialize affected_versions: {}", e)))?
+            .try_bind("patched", &vuln.patched_versions)
+            .map_err(|e| DbError::Serialization(format!("Failed to serialize patched_versions: {}", e)))?
+            .build();
+        self.db()
+            .aql_query::<serde_json::Value>(aql)
+            .await
+            .map_err(|e| DbError::arango(format!("Failed to upsert advisory {}", vuln.id), e))?;
+
+        let affected: Vec<serde_json::Value> = packages
+            .iter()
+            .map(|(registry, name)| {
+                serde_json::json!({
+                    "_key": registry_package_key(registry, name),
+                    "registry": registry,
+                    "name": name,
+                })
+            })
+            .collect();
+        if affected.is_empty() {
+            return Ok(key);
+        }
+
+        let link_packages = r#"
+            FOR package IN @packages
+                UPSERT { _key: package._key }
+                INSERT package
+                UPDATE {}
+                IN packages
+                UPSERT { _key: CONCAT(@vuln, "__", package._key) }
+                INSERT {
+                    _key: CONCAT(@vuln, "__", package._key),
+                    _from: CONCAT("vulnerabilities/", @vuln),
+                    _to: CONCAT("packages/", package._key)
+                }
+                UPDATE {}
+                IN affects
+                RETURN NEW._key
+        "#;
+        let aql = AqlQuery::builder()
+            .query(link_packages)
+            .bind_var("vuln", key.clone())
+            .bind_var("packages", serde_json::Value::Array(affected))
+            .build();
+        self.db()
+            .aql_query::<serde_json::Value>(aql)
+            .await
+            .map_err(|e| DbError::arango(format!("Failed to link advisory {} to its packages", vuln.id), e))?;
+
+        Ok(key)
+    }
+
+    /// Latest update time among the advisories stored from `source`, to
+    /// resume ingestion from
+    pub async fn latest_advisory_update(&self, source: &str) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let latest = r#"
+            FOR v IN vulnerabilities
+                FILTER HAS(NOT_NULL(v.source_updated_at, {}), @source)
+                COLLECT AGGREGATE latest = MAX(v.source_updated_at[@source])
+                RETURN latest
+        "#;
+        let aql = AqlQuery::builder()
+            .query(latest)
+            .bind_var("source", source.to_string())
+            .build();
+        let found: Vec<Option<chrono::DateTime<chrono::Utc>>> = self.db()
+            .aql_query(aql)
+            .await
+            .map_err(|e| DbError::arango(format!("Failed to find the latest {} advisory", source), e))?;
+        Ok(found.into_iter().next().flatten())
+    }
+
     /// Register a repository in the graph
     pub async fn register_repository(&self, platform: &str, owner: &str, repo: &str) -> Result<String> {
-        let key = format!("{}__{}_{}", platform, owner, repo);
+        let key = repository_key(platform, owner, repo);
 
         let upsert = r#"
             UPSERT { _key: @key }
@@ -371,28 +1014,577 @@ pub async fn register_repository(&self, platform: &str, owner: &str, repo: &str)
             .bind_var("repo", repo.to_string())
             .build();
 
-        let keys: Vec<String> = self.db
+        let keys: Vec<String> = self.db()
             .aql_query(aql)
             .await
-            .map_err(|e| RsrError::Platform(format!("Failed to register repository: {}", e)))?;
+            .map_err(|e| DbError::arango("Failed to register repository", e))?;
 
         Ok(keys.into_iter().next().unwrap_or(key))
     }
+
+    /// Link a registry package to the repository it is developed in. A
+    /// package has one repository; linking it again replaces the old one.
+    pub async fn link_package_repository(&self, registry: &str, package_name: &str, repo_key: &str) -> Result<()> {
+        let package_key = registry_package_key(registry, package_name);
+        tracing::debug!("Linking package {} -> {}", package_key, repo_key);
+
+        let upsert_package = r#"
+            UPSERT { _key: @key }
+            INSERT { _key: @key, name: @name, registry: @registry }
+            UPDATE {}
+            IN packages
+            RETURN NEW
+        "#;
+        let aql = AqlQuery::builder()
+            .query(upsert_package)
+            .bind_var("key", package_key.clone())
+            .bind_var("name", package_name.to_string())
+            .bind_var("registry", registry.to_string())
+            .build();
+        self.db()
+            .aql_query::<serde_json::Value>(aql)
+            .await
+            .map_err(|e| DbError::arango("Failed to upsert package", e))?;
+
+        let upsert_edge = r#"
+            UPSERT { _key: @key }
+            INSERT {
+                _key: @key,
+                _from: CONCAT("packages/", @key),
+                _to: CONCAT("repositories/", @repo)
+            }
+            UPDATE { _to: CONCAT("repositories/", @repo) }
+            IN hosted_at
+            RETURN NEW
+        "#;
+        let aql = AqlQuery::builder()
+            .query(upsert_edge)
+            .bind_var("key", package_key)
+            .bind_var("repo", repo_key.to_string())
+            .build();
+        self.db()
+            .aql_query::<serde_json::Value>(aql)
+            .await
+            .map_err(|e| DbError::arango("Failed to create hosted_at edge", e))?;
+
+        Ok(())
+    }
+
+    /// Repository a registry package was discovered in, if any
+    pub async fn get_package_repository(&self, registry: &str, package_name: &str) -> Result<Option<RepoRef>> {
+        let aql_query = r#"
+            FOR v IN 1..1 OUTBOUND CONCAT("packages/", @package) hosted_at
+                RETURN { platform: v.platform, owner: v.owner, repo: v.repo }
+        "#;
+        let aql = AqlQuery::builder()
+            .query(aql_query)
+            .bind_var("package", registry_package_key(registry, package_name))
+            .build();
+
+        let repos: Vec<RepoRef> = self.db()
+            .aql_query(aql)
+            .await
+            .map_err(|e| DbError::arango("Failed to look up package repository", e))?;
+
+        Ok(repos.into_iter().next())
+    }
+
+    /// Record that a repository is a fork or mirror of another. A
+    /// repository has one upstream; linking it again replaces the old one.
+    /// Both repositories must have been registered.
+    pub async fn link_fork(&self, fork_key: &str, upstream_key: &str, kind: UpstreamKind) -> Result<()> {
+        tracing::debug!("Linking {} as a {:?} of {}", fork_key, kind, upstream_key);
+
+        // Matched on `_from` rather than a key, so a transferred fork keeps its edge
+        let upsert_edge = r#"
+            UPSERT { _from: CONCAT("repositories/", @fork) }
+            INSERT {
+                _from: CONCAT("repositories/", @fork),
+                _to: CONCAT("repositories/", @upstream),
+                kind: @kind
+            }
+            UPDATE { _to: CONCAT("repositories/", @upstream), kind: @kind }
+            IN forks
+            RETURN NEW
+        "#;
+        let aql = AqlQuery::builder()
+            .query(upsert_edge)
+            .bind_var("fork", fork_key.to_string())
+            .bind_var("upstream", upstream_key.to_string())
+            .bind_var("kind", serde_json::json!(kind))
+            .build();
+        self.db()
+            .aql_query::<serde_json::Value>(aql)
+            .await
+            .map_err(|e| DbError::arango("Failed to create forks edge", e))?;
+
+        Ok(())
+    }
+
+    /// Repositories a fork or mirror descends from, nearest first
+    pub async fn get_upstreams(&self, repo_key: &str) -> Result<Vec<RepoRef>> {
+        let aql_query = r#"
+            FOR v IN 1..@depth OUTBOUND CONCAT("repositories/", @repo) forks
+                OPTIONS { order: "bfs", uniqueVertices: "global" }
+                FILTER v._key != @repo
+                RETURN { platform: v.platform, owner: v.owner, repo: v.repo }
+        "#;
+        let aql = AqlQuery::builder()
+            .query(aql_query)
+            .bind_var("repo", repo_key.to_string())
+            .bind_var("depth", MAX_FORK_DEPTH)
+            .build();
+
+        self.db()
+            .aql_query(aql)
+            .await
+            .map_err(|e| DbError::arango("Failed to get upstreams", e).into())
+    }
+
+    /// Every repository in a repository's fork network: the root the network
+    /// descends from first, then its forks and mirrors, nearest first. Empty
+    /// if the repository isn't registered.
+    pub async fn get_fork_network(&self, repo_key: &str) -> Result<Vec<ForkMember>> {
+        tracing::debug!("Getting fork network of {}", repo_key);
+
+        let aql_query = r#"
+            LET root = LAST(
+                FOR v IN 0..@depth OUTBOUND CONCAT("repositories/", @repo) forks
+                    OPTIONS { order: "bfs", uniqueVertices: "global" }
+                    RETURN v
+            )
+            FILTER root != null
+            FOR v, e, p IN 0..@depth INBOUND root forks
+                OPTIONS { order: "bfs", uniqueVertices: "global" }
+                RETURN {
+                    key: v._key,
+                    repository: { platform: v.platform, owner: v.owner, repo: v.repo },
+                    upstream: e == null ? null : PARSE_IDENTIFIER(e._to).key,
+                    kind: e == null ? null : e.kind,
+                    depth: LENGTH(p.edges)
+                }
+        "#;
+        let aql = AqlQuery::builder()
+            .query(aql_query)
+            .bind_var("repo", repo_key.to_string())
+            .bind_var("depth", MAX_FORK_DEPTH)
+            .build();
+
+        self.db()
+            .aql_query(aql)
+            .await
+            .map_err(|e| DbError::arango("Failed to get fork network", e).into())
+    }
+
+    /// Place a repository in its organization hierarchy. `ancestry` lists
+    /// the units above it, nearest first (see [`crate::hierarchy`]). Each
+    /// vertex has one parent; registering again moves it.
+    pub async fn register_hierarchy(&self, repo_key: &str, ancestry: &[String]) -> Result<()> {
+        let organizations: Vec<serde_json::Value> = ancestry
+            .iter()
+            .map(|unit| {
+                let name = unit.rsplit(['/', ':', '#']).next().unwrap_or(unit);
+                serde_json::json!({ "_key": organization_key(unit), "unit": unit, "name": name })
+            })
+            .collect();
+
+        let children = std::iter::once(format!("repositories/{}", repo_key))
+            .chain(ancestry.iter().map(|unit| format!("organizations/{}", organization_key(unit))));
+        let edges: Vec<serde_json::Value> = children
+            .zip(ancestry)
+            .map(|(child, parent)| {
+                serde_json::json!({
+                    "_key": child.replace('/', "__"),
+                    "_from": child,
+                    "_to": format!("organizations/{}", organization_key(parent)),
+                })
+            })
+            .collect();
+
+        let upsert_organizations = r#"
+            FOR org IN @organizations
+                UPSERT { _key: org._key }
+                INSERT org
+                UPDATE {}
+                IN organizations
+                RETURN NEW._key
+        "#;
+        let aql = AqlQuery::builder()
+            .query(upsert_organizations)
+            .bind_var("organizations", serde_json::Value::Array(organizations))
+            .build();
+        self.db()
+            .aql_query::<serde_json::Value>(aql)
+            .await
+            .map_err(|e| DbError::arango("Failed to upsert organizations", e))?;
+
+        let upsert_edges = r#"
+            FOR edge IN @edges
+                UPSERT { _key: edge._key }
+                INSERT edge
+                UPDATE { _to: edge._to }
+                IN member_of
+                RETURN NEW._key
+        "#;
+        let aql = AqlQuery::builder()
+            .query(upsert_edges)
+            .bind_var("edges", serde_json::Value::Array(edges))
+            .build();
+        self.db()
+            .aql_query::<serde_json::Value>(aql)
+            .await
+            .map_err(|e| DbError::arango("Failed to create member_of edges", e))?;
+
+        Ok(())
+    }
+
+    /// Units a registered repository sits under, nearest first
+    pub async fn get_ancestry(&self, repo_key: &str) -> Result<Vec<String>> {
+        let aql_query = r#"
+            FOR v IN 1..@depth OUTBOUND CONCAT("repositories/", @repo) member_of
+                RETURN v.unit
+        "#;
+        let aql = AqlQuery::builder()
+            .query(aql_query)
+            .bind_var("repo", repo_key.to_string())
+            .bind_var("depth", MAX_HIERARCHY_DEPTH)
+            .build();
+
+        self.db()
+            .aql_query(aql)
+            .await
+            .map_err(|e| DbError::arango("Failed to get ancestry", e).into())
+    }
+
+    /// Repositories at any depth below an organizational unit
+    pub async fn get_organization_repositories(&self, unit: &str) -> Result<Vec<RepoRef>> {
+        let aql_query = r#"
+            FOR v IN 1..@depth INBOUND CONCAT("organizations/", @org) member_of
+                FILTER IS_SAME_COLLECTION("repositories", v)
+                RETURN DISTINCT { platform: v.platform, owner: v.owner, repo: v.repo }
+        "#;
+        let aql = AqlQuery::builder()
+            .query(aql_query)
+            .bind_var("org", organization_key(unit))
+            .bind_var("depth", MAX_HIERARCHY_DEPTH)
+            .build();
+
+        self.db()
+            .aql_query(aql)
+            .await
+            .map_err(|e| DbError::arango("Failed to list organization repositories", e).into())
+    }
+
+    /// Re-key a repository vertex after a transfer or rename.
+    ///
+    /// ArangoDB keys are immutable, so the vertex is copied under the new key,
+    /// every `depends_on`/`forks`/`hosted_at` edge is re-pointed, and the old vertex is
+    /// removed - all inside one stream transaction. Organization membership
+    /// is dropped, to be registered again under the new owner.
+    pub async fn transfer_repository(&self, from: &RepoRef, to: &RepoRef) -> Result<String> {
+        let old_key = repository_key(&from.platform, &from.owner, &from.repo);
+        let new_key = repository_key(&to.platform, &to.owner, &to.repo);
+
+        tracing::info!("Transferring graph vertex {} -> {}", old_key, new_key);
+
+        let settings = TransactionSettings::builder()
+            .collections(
+                TransactionCollections::builder()
+                    .write(vec![
+                        "repositories".to_string(),
+                        "depends_on".to_string(),
+                        "forks".to_string(),
+                        "hosted_at".to_string(),
+                        "member_of".to_string(),
+                    ])
+                    .build(),
+            )
+            .build();
+
+        let tx = self.db()
+            .begin_transaction(settings)
+            .await
+            .map_err(|e| DbError::arango("Failed to begin transaction", e))?;
+
+        let statements = [
+            r#"
+                LET old = DOCUMENT("repositories", @old)
+                FILTER old != null
+                UPSERT { _key: @new }
+                INSERT MERGE(UNSET(old, "_id", "_key", "_rev"), {
+                    _key: @new, owner: @owner, repo: @repo, transferred_from: @old
+                })
+                UPDATE { owner: @owner, repo: @repo, transferred_from: @old }
+                IN repositories
+                RETURN NEW._key
+            "#,
+            r#"
+                FOR e IN depends_on
+                    FILTER e._from == CONCAT("repositories/", @old)
+                    UPDATE e WITH { _from: CONCAT("repositories/", @new) } IN depends_on
+                    RETURN NEW._key
+            "#,
+            r#"
+                FOR e IN forks
+                    FILTER e._from == CONCAT("repositories/", @old) OR e._to == CONCAT("repositories/", @old)
+                    UPDATE e WITH {
+                        _from: e._from == CONCAT("repositories/", @old) ? CONCAT("repositories/", @new) : e._from,
+                        _to: e._to == CONCAT("repositories/", @old) ? CONCAT("repositories/", @new) : e._to
+                    } IN forks
+                    RETURN NEW._key
+            "#,
+            r#"
+                FOR e IN hosted_at
+                    FILTER e._to == CONCAT("repositories/", @old)
+                    UPDATE e WITH { _to: CONCAT("repositories/", @new) } IN hosted_at
+                    RETURN NEW._key
+            "#,
+            r#"
+                FOR e IN member_of
+                    FILTER e._from == CONCAT("repositories/", @old)
+                    REMOVE e IN member_of
+                    RETURN OLD._key
+            "#,
+            r#"
+                REMOVE { _key: @old } IN repositories OPTIONS { ignoreErrors: true }
+                RETURN OLD._key
+            "#,
+        ];
+
+        for statement in statements {
+            let mut builder = AqlQuery::builder()
+                .query(statement)
+                .bind_var("old", old_key.clone());
+            if statement.contains("@new") {
+                builder = builder.bind_var("new", new_key.clone());
+            }
+            if statement.contains("@owner") {
+                builder = builder
+                    .bind_var("owner", to.owner.clone())
+                    .bind_var("repo", to.repo.clone());
+            }
+
+            if let Err(e) = tx.aql_query::<serde_json::Value>(builder.build()).await {
+                let _ = tx.abort().await;
+                return Err(DbError::arango("Failed to transfer repository vertex", e).into());
+            }
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| DbError::arango("Failed to commit transfer", e))?;
+
+        Ok(new_key)
+    }
+}
+
+#[cfg(feature = "graphs-arangodb")]
+#[async_trait::async_trait]
+impl GraphStore for ArangoPool {
+    async fn ping(&self) -> Result<()> {
+        ArangoPool::ping(self).await
+    }
+
+    async fn reconnect(&self) -> Result<()> {
+        ArangoPool::reconnect(self).await
+    }
+
+    async fn migrate(&self) -> Result<()> {
+        ArangoPool::migrate(self).await
+    }
+
+    async fn add_dependency(&self, repo_key: &str, registry: &str, package_name: &str, package_version: &str) -> Result<()> {
+        ArangoPool::add_dependency(self, repo_key, registry, package_name, package_version).await
+    }
+
+    async fn upsert_dependency_snapshot(&self, repo_key: &str, dependencies: Vec<Dependency>) -> Result<DependencySnapshot> {
+        ArangoPool::upsert_dependency_snapshot(self, repo_key, dependencies).await
+    }
+
+    async fn get_dependencies(&self, repo_key: &str) -> Result<Vec<Dependency>> {
+        ArangoPool::get_dependencies(self, repo_key).await
+    }
+
+    async fn get_affected_repos(&self, vulnerability_id: &str) -> Result<Vec<String>> {
+        ArangoPool::get_affected_repos(self, vulnerability_id).await
+    }
+
+    async fn explain_impact(&self, vulnerability_id: &str, repo_key: &str) -> Result<Vec<ImpactPath>> {
+        ArangoPool::explain_impact(self, vulnerability_id, repo_key).await
+    }
+
+    async fn get_dependents(&self, repo_key: &str) -> Result<Vec<String>> {
+        ArangoPool::get_dependents(self, repo_key).await
+    }
+
+    async fn get_dependency_depth(&self, repo_key: &str) -> Result<u32> {
+        ArangoPool::get_dependency_depth(self, repo_key).await
+    }
+
+    async fn add_vulnerability(&self, vuln: &Vulnerability, package_key: &str) -> Result<()> {
+        ArangoPool::add_vulnerability(self, vuln, package_key).await
+    }
+
+    async fn upsert_advisory(
+        &self,
+        vuln: &Vulnerability,
+        source: &str,
+        updated_at: chrono::DateTime<chrono::Utc>,
+        packages: &[(String, String)],
+    ) -> Result<String> {
+        ArangoPool::upsert_advisory(self, vuln, source, updated_at, packages).await
+    }
+
+    async fn latest_advisory_update(&self, source: &str) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        ArangoPool::latest_advisory_update(self, source).await
+    }
+
+    async fn register_repository(&self, platform: &str, owner: &str, repo: &str) -> Result<String> {
+        ArangoPool::register_repository(self, platform, owner, repo).await
+    }
+
+    async fn link_package_repository(&self, registry: &str, package_name: &str, repo_key: &str) -> Result<()> {
+        ArangoPool::link_package_repository(self, registry, package_name, repo_key).await
+    }
+
+    async fn get_package_repository(&self, registry: &str, package_name: &str) -> Result<Option<RepoRef>> {
+        ArangoPool::get_package_repository(self, registry, package_name).await
+    }
+
+    async fn link_fork(&self, fork_key: &str, upstream_key: &str, kind: UpstreamKind) -> Result<()> {
+        ArangoPool::link_fork(self, fork_key, upstream_key, kind).await
+    }
+
+    async fn get_upstreams(&self, repo_key: &str) -> Result<Vec<RepoRef>> {
+        ArangoPool::get_upstreams(self, repo_key).await
+    }
+
+    async fn get_fork_network(&self, repo_key: &str) -> Result<Vec<ForkMember>> {
+        ArangoPool::get_fork_network(self, repo_key).await
+    }
+
+    async fn register_hierarchy(&self, repo_key: &str, ancestry: &[String]) -> Result<()> {
+        ArangoPool::register_hierarchy(self, repo_key, ancestry).await
+    }
+
+    async fn get_ancestry(&self, repo_key: &str) -> Result<Vec<String>> {
+        ArangoPool::get_ancestry(self, repo_key).await
+    }
+
+    async fn get_organization_repositories(&self, unit: &str) -> Result<Vec<RepoRef>> {
+        ArangoPool::get_organization_repositories(self, unit).await
+    }
+
+    async fn transfer_repository(&self, from: &RepoRef, to: &RepoRef) -> Result<String> {
+        ArangoPool::transfer_repository(self, from, to).await
+    }
+}
+
+#[cfg(feature = "graphs-arangodb")]
+fn is_not_found(e: &ClientError) -> bool {
+    matches!(e, ClientError::Arango(e) if e.code() == 404)
+}
+
+#[cfg(feature = "graphs-arangodb")]
+fn is_conflict(e: &ClientError) -> bool {
+    matches!(e, ClientError::Arango(e) if e.code() == 409)
+}
+
+/// Vertex key for a repository
+pub fn repository_key(platform: &str, owner: &str, repo: &str) -> String {
+    format!("{}__{}_{}", platform, owner, repo)
+}
+
+/// Vertex key for a package in a registry, independent of version. Names
+/// are percent-encoded since scoped npm names contain `/`.
+pub fn registry_package_key(registry: &str, package_name: &str) -> String {
+    format!("{}:{}", registry, urlencoding::encode(package_name))
+}
+
+/// Vertex key for an organizational unit (`platform:path` or
+/// `enterprise:name`), percent-encoded since group paths contain `/`
+pub fn organization_key(unit: &str) -> String {
+    urlencoding::encode(unit).into_owned()
 }
 
 /// Dependency information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Dependency {
+    /// Registry the package comes from (`crates`, `npm`, ...)
+    #[serde(default)]
+    pub registry: String,
     pub name: String,
     pub version: String,
     pub depth: u32,
     pub direct: bool,
 }
 
+/// How a repository relates to the one it was copied from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpstreamKind {
+    /// Forked on the same platform
+    Fork,
+    /// Mirrored, possibly from another platform
+    Mirror,
+}
+
+/// A repository in a fork network
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkMember {
+    pub key: String,
+    pub repository: RepoRef,
+    /// Key of the repository it was forked or mirrored from; `None` for the root
+    pub upstream: Option<String>,
+    pub kind: Option<UpstreamKind>,
+    /// Forks between it and the root
+    pub depth: u32,
+}
+
+/// A package on a dependency path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImpactStep {
+    pub registry: String,
+    pub name: String,
+    /// Version the dependent repository uses
+    pub version: String,
+    /// 1 for the repository's own dependency
+    pub depth: u32,
+    /// Key of the repository depending on the package
+    pub dependent: String,
+}
+
+/// Chain of dependencies from a repository to a vulnerable package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImpactPath {
+    /// Key of the vulnerability, which may differ from the alias asked about
+    pub vulnerability: String,
+    /// Depth of the vulnerable package
+    pub depth: u32,
+    /// From the repository's direct dependency to the vulnerable package
+    pub packages: Vec<ImpactStep>,
+}
+
+/// Outcome of [`GraphStore::upsert_dependency_snapshot`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DependencySnapshot {
+    /// The repository's snapshot count, including this one
+    pub version: u64,
+    pub added: usize,
+    /// Dependencies whose version changed
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
 /// Vulnerability information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Vulnerability {
     pub id: String,
+    /// Other ids the vulnerability is known by, e.g. its CVE
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// A [`Severity`](crate::events::Severity), lowercase
     pub severity: String,
     pub affected_versions: Vec<String>,
     pub patched_versions: Vec<String>,
@@ -0,0 +1,944 @@
+//! In-process graph store on petgraph
+//!
+//! Selected with `RSR_ARANGODB_URL=memory://` in builds with the
+//! `graphs-memory` feature, so the CLI and small installs get dependency,
+//! impact, fork and hierarchy analysis without running ArangoDB. Queries
+//! follow the same rules as the AQL ones in [`graphs`](super::graphs):
+//! the same depth limits, breadth-first order and vertex uniqueness.
+//!
+//! The graph lives in one process. With `memory:///path/to/graph.json` it is
+//! read from that file at startup and written back after every change,
+//! through a temporary file renamed into place; without a path it is lost on
+//! restart. Either way it is not shared with other instances.
+
+use super::error::DbError;
+use super::graphs::{
+    organization_key, registry_package_key, repository_key, Dependency, DependencySnapshot, ForkMember, GraphStore,
+    ImpactPath, ImpactStep, UpstreamKind, Vulnerability, MAX_DEPENDENCY_EDGES, MAX_FORK_DEPTH, MAX_HIERARCHY_DEPTH,
+    MAX_IMPACT_PATHS,
+};
+use crate::{RepoRef, Result, RsrError};
+use chrono::{DateTime, Utc};
+use petgraph::stable_graph::{EdgeIndex, NodeIndex, StableDiGraph};
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use petgraph::Direction;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+
+/// Snapshot format written by this engine
+const SNAPSHOT_FORMAT: u32 = 1;
+
+/// A vertex, named by its collection as in ArangoDB
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "collection", rename_all = "snake_case")]
+enum Vertex {
+    Repositories {
+        key: String,
+        platform: String,
+        owner: String,
+        repo: String,
+        /// Dependency snapshots taken
+        #[serde(default)]
+        snapshot_version: u64,
+        #[serde(default)]
+        transferred_from: Option<String>,
+    },
+    Packages {
+        key: String,
+        registry: String,
+        name: String,
+    },
+    Vulnerabilities {
+        key: String,
+        severity: String,
+        #[serde(default)]
+        aliases: Vec<String>,
+        #[serde(default)]
+        affected_versions: Vec<String>,
+        #[serde(default)]
+        patched_versions: Vec<String>,
+        /// Update time of the advisory each source last stored
+        #[serde(default)]
+        source_updated_at: BTreeMap<String, DateTime<Utc>>,
+    },
+    Organizations {
+        key: String,
+        unit: String,
+        name: String,
+    },
+}
+
+impl Vertex {
+    fn id(&self) -> String {
+        match self {
+            Self::Repositories { key, .. } => vertex_id("repositories", key),
+            Self::Packages { key, .. } => vertex_id("packages", key),
+            Self::Vulnerabilities { key, .. } => vertex_id("vulnerabilities", key),
+            Self::Organizations { key, .. } => vertex_id("organizations", key),
+        }
+    }
+
+    fn key(&self) -> &str {
+        match self {
+            Self::Repositories { key, .. }
+            | Self::Packages { key, .. }
+            | Self::Vulnerabilities { key, .. }
+            | Self::Organizations { key, .. } => key,
+        }
+    }
+
+    fn repository(&self) -> Option<RepoRef> {
+        match self {
+            Self::Repositories { platform, owner, repo, .. } => Some(RepoRef::new(platform, owner, repo)),
+            _ => None,
+        }
+    }
+}
+
+/// An edge, named by its collection as in ArangoDB
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "collection", rename_all = "snake_case")]
+enum Edge {
+    DependsOn { version: String },
+    Affects,
+    Forks { kind: UpstreamKind },
+    HostedAt,
+    MemberOf,
+}
+
+impl Edge {
+    /// Whether dependency traversals follow the edge
+    fn is_dependency(&self) -> bool {
+        matches!(self, Self::DependsOn { .. } | Self::HostedAt)
+    }
+}
+
+/// An edge as written to a snapshot, between vertex ids
+#[derive(Debug, Serialize, Deserialize)]
+struct EdgeRecord {
+    from: String,
+    to: String,
+    #[serde(flatten)]
+    edge: Edge,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    format: u32,
+    vertices: Vec<Vertex>,
+    edges: Vec<EdgeRecord>,
+}
+
+/// ArangoDB-style `collection/key` vertex id
+fn vertex_id(collection: &str, key: &str) -> String {
+    format!("{}/{}", collection, key)
+}
+
+/// Graph with its vertices indexed by id
+#[derive(Default)]
+struct Graph {
+    graph: StableDiGraph<Vertex, Edge>,
+    ids: HashMap<String, NodeIndex>,
+}
+
+impl Graph {
+    fn from_snapshot(snapshot: Snapshot) -> Result<Self> {
+        if snapshot.format != SNAPSHOT_FORMAT {
+            return Err(RsrError::Config(format!(
+                "Unsupported graph snapshot format {} (expected {})",
+                snapshot.format, SNAPSHOT_FORMAT
+            )));
+        }
+
+        let mut graph = Self::default();
+        for vertex in snapshot.vertices {
+            graph.upsert(vertex, |_, _| {});
+        }
+        for record in snapshot.edges {
+            let (Some(from), Some(to)) = (graph.find(&record.from), graph.find(&record.to)) else {
+                return Err(RsrError::Config(format!(
+                    "Graph snapshot has an edge from {} to {} without both vertices",
+                    record.from, record.to
+                )));
+            };
+            graph.graph.add_edge(from, to, record.edge);
+        }
+        Ok(graph)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let vertices = self.graph.node_weights().cloned().collect();
+        let edges = self
+            .graph
+            .edge_references()
+            .map(|edge| EdgeRecord {
+                from: self.graph[edge.source()].id(),
+                to: self.graph[edge.target()].id(),
+                edge: edge.weight().clone(),
+            })
+            .collect();
+        Snapshot {
+            format: SNAPSHOT_FORMAT,
+            vertices,
+            edges,
+        }
+    }
+
+    fn find(&self, id: &str) -> Option<NodeIndex> {
+        self.ids.get(id).copied()
+    }
+
+    fn repository(&self, key: &str) -> Option<NodeIndex> {
+        self.find(&vertex_id("repositories", key))
+    }
+
+    /// A vertex `find` must return, or an error naming what is missing
+    fn require(&self, collection: &str, key: &str) -> Result<NodeIndex> {
+        self.find(&vertex_id(collection, key))
+            .ok_or_else(|| DbError::NotFound(format!("No {} vertex {} in the graph", collection, key)).into())
+    }
+
+    /// Insert `vertex`, or apply `update` to the one with its id
+    fn upsert(&mut self, vertex: Vertex, update: impl FnOnce(&mut Vertex, Vertex)) -> NodeIndex {
+        let id = vertex.id();
+        match self.ids.get(&id) {
+            Some(&index) => {
+                update(&mut self.graph[index], vertex);
+                index
+            }
+            None => {
+                let index = self.graph.add_node(vertex);
+                self.ids.insert(id, index);
+                index
+            }
+        }
+    }
+
+    fn upsert_package(&mut self, registry: &str, name: &str) -> NodeIndex {
+        let package = Vertex::Packages {
+            key: registry_package_key(registry, name),
+            registry: registry.to_string(),
+            name: name.to_string(),
+        };
+        self.upsert(package, |_, _| {})
+    }
+
+    /// Edges leaving `from` that `matches` accepts
+    fn outgoing(&self, from: NodeIndex, matches: impl Fn(&Edge) -> bool) -> Vec<EdgeIndex> {
+        self.graph
+            .edges_directed(from, Direction::Outgoing)
+            .filter(|edge| matches(edge.weight()))
+            .map(|edge| edge.id())
+            .collect()
+    }
+
+    /// Replace the edges leaving `from` that `matches` accepts with one to `to`
+    fn replace_outgoing(&mut self, from: NodeIndex, to: NodeIndex, edge: Edge, matches: impl Fn(&Edge) -> bool) {
+        for existing in self.outgoing(from, matches) {
+            self.graph.remove_edge(existing);
+        }
+        self.graph.add_edge(from, to, edge);
+    }
+
+    /// Vertices reachable from `start` in `direction` over edges `follow`
+    /// accepts, breadth first, each visited once, with the edge it was
+    /// reached over and how many edges away it is
+    fn traverse(
+        &self,
+        start: NodeIndex,
+        direction: Direction,
+        max_edges: u32,
+        follow: impl Fn(&Edge) -> bool,
+    ) -> Vec<(NodeIndex, EdgeIndex, u32)> {
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        let mut reached = Vec::new();
+        while let Some((vertex, depth)) = queue.pop_front() {
+            if depth == max_edges {
+                continue;
+            }
+            for edge in self.graph.edges_directed(vertex, direction) {
+                if !follow(edge.weight()) {
+                    continue;
+                }
+                let next = match direction {
+                    Direction::Outgoing => edge.target(),
+                    Direction::Incoming => edge.source(),
+                };
+                if visited.insert(next) {
+                    reached.push((next, edge.id(), depth + 1));
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+        reached
+    }
+
+    fn dependencies(&self, repo_key: &str) -> Vec<Dependency> {
+        let Some(start) = self.repository(repo_key) else {
+            return Vec::new();
+        };
+
+        let mut dependencies: Vec<Dependency> = self
+            .traverse(start, Direction::Outgoing, MAX_DEPENDENCY_EDGES, Edge::is_dependency)
+            .into_iter()
+            .filter_map(|(vertex, edge, edges)| {
+                let Vertex::Packages { registry, name, .. } = &self.graph[vertex] else {
+                    return None;
+                };
+                let version = match &self.graph[edge] {
+                    Edge::DependsOn { version } => version.clone(),
+                    _ => String::new(),
+                };
+                let depth = edges.div_ceil(2);
+                Some(Dependency {
+                    registry: registry.clone(),
+                    name: name.clone(),
+                    version,
+                    depth,
+                    direct: depth == 1,
+                })
+            })
+            .collect();
+        dependencies.sort_by(|a, b| (a.depth, &a.registry, &a.name).cmp(&(b.depth, &b.registry, &b.name)));
+        dependencies
+    }
+
+    /// Vulnerability stored under `id` or with it as an alias
+    fn find_vulnerability(&self, id: &str) -> Option<NodeIndex> {
+        self.find(&vertex_id("vulnerabilities", id)).or_else(|| {
+            let mut matching: Vec<NodeIndex> = self
+                .graph
+                .node_indices()
+                .filter(|&index| {
+                    matches!(&self.graph[index], Vertex::Vulnerabilities { aliases, .. } if aliases.iter().any(|alias| alias == id))
+                })
+                .collect();
+            matching.sort_by(|a, b| self.graph[*a].key().cmp(self.graph[*b].key()));
+            matching.into_iter().next()
+        })
+    }
+
+    fn affected_packages(&self, vulnerability: NodeIndex) -> Vec<NodeIndex> {
+        self.graph
+            .edges_directed(vulnerability, Direction::Outgoing)
+            .filter(|edge| *edge.weight() == Edge::Affects)
+            .map(|edge| edge.target())
+            .collect()
+    }
+
+    /// Dependency paths from `start` to any of `targets`, breadth first with
+    /// no vertex repeated on a path, as the edges taken
+    fn dependency_paths(&self, start: NodeIndex, targets: &HashSet<NodeIndex>, limit: usize) -> Vec<Vec<EdgeIndex>> {
+        let mut paths = Vec::new();
+        let mut queue: VecDeque<(NodeIndex, Vec<EdgeIndex>)> = VecDeque::from([(start, Vec::new())]);
+        while let Some((vertex, path)) = queue.pop_front() {
+            if path.len() as u32 == MAX_DEPENDENCY_EDGES {
+                continue;
+            }
+            for edge in self.graph.edges_directed(vertex, Direction::Outgoing) {
+                if !edge.weight().is_dependency() {
+                    continue;
+                }
+                let next = edge.target();
+                let revisits = next == start || path.iter().any(|&taken| self.graph.edge_endpoints(taken).map(|(_, to)| to) == Some(next));
+                if revisits {
+                    continue;
+                }
+                let mut extended = path.clone();
+                extended.push(edge.id());
+                if targets.contains(&next) {
+                    paths.push(extended.clone());
+                    if paths.len() == limit {
+                        return paths;
+                    }
+                }
+                queue.push_back((next, extended));
+            }
+        }
+        paths
+    }
+
+    fn impact_path(&self, vulnerability: &str, path: &[EdgeIndex]) -> ImpactPath {
+        let packages: Vec<ImpactStep> = path
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &edge)| {
+                let Edge::DependsOn { version } = &self.graph[edge] else {
+                    return None;
+                };
+                let (from, to) = self.graph.edge_endpoints(edge)?;
+                let Vertex::Packages { registry, name, .. } = &self.graph[to] else {
+                    return None;
+                };
+                Some(ImpactStep {
+                    registry: registry.clone(),
+                    name: name.clone(),
+                    version: version.clone(),
+                    depth: (i as u32 + 2) / 2,
+                    dependent: self.graph[from].key().to_string(),
+                })
+            })
+            .collect();
+        ImpactPath {
+            vulnerability: vulnerability.to_string(),
+            depth: packages.len() as u32,
+            packages,
+        }
+    }
+
+    /// Keys of the repositories reached from `start` against the direction
+    /// of dependency edges, i.e. those depending on it
+    fn dependent_repositories(&self, start: NodeIndex) -> Vec<String> {
+        self.traverse(start, Direction::Incoming, MAX_DEPENDENCY_EDGES, Edge::is_dependency)
+            .into_iter()
+            .filter(|(vertex, _, _)| matches!(self.graph[*vertex], Vertex::Repositories { .. }))
+            .map(|(vertex, _, _)| self.graph[vertex].key().to_string())
+            .collect()
+    }
+
+    fn link_affected(&mut self, vulnerability: NodeIndex, package: NodeIndex) {
+        let linked = self
+            .graph
+            .edges_directed(vulnerability, Direction::Outgoing)
+            .any(|edge| *edge.weight() == Edge::Affects && edge.target() == package);
+        if !linked {
+            self.graph.add_edge(vulnerability, package, Edge::Affects);
+        }
+    }
+}
+
+/// Graph store held in process, optionally persisted to a snapshot file
+pub struct MemoryGraph {
+    graph: RwLock<Graph>,
+    /// Snapshot file, locked while it is written
+    snapshot: Option<Mutex<PathBuf>>,
+}
+
+impl MemoryGraph {
+    /// Graph kept only in memory
+    pub fn new() -> Self {
+        Self {
+            graph: RwLock::new(Graph::default()),
+            snapshot: None,
+        }
+    }
+
+    /// Graph persisted to `snapshot`, loaded from it if it exists
+    pub fn open(snapshot: Option<PathBuf>) -> Result<Self> {
+        let Some(path) = snapshot else {
+            tracing::warn!("Using the in-memory graph; it is lost on restart and not shared");
+            return Ok(Self::new());
+        };
+
+        let graph = match std::fs::read(&path) {
+            Ok(bytes) => {
+                let snapshot: Snapshot = serde_json::from_slice(&bytes)
+                    .map_err(|e| RsrError::Config(format!("Invalid graph snapshot {}: {}", path.display(), e)))?;
+                Graph::from_snapshot(snapshot)?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Graph::default(),
+            Err(e) => return Err(DbError::Backend(format!("Failed to read graph snapshot {}: {}", path.display(), e)).into()),
+        };
+        tracing::info!(
+            "Using the in-memory graph with {} vertices, persisted to {}",
+            graph.graph.node_count(),
+            path.display()
+        );
+
+        Ok(Self {
+            graph: RwLock::new(graph),
+            snapshot: Some(Mutex::new(path)),
+        })
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Graph> {
+        self.graph.read().expect("memory graph lock poisoned")
+    }
+
+    /// Apply a change, then write the snapshot if there is one
+    fn write<T>(&self, change: impl FnOnce(&mut Graph) -> Result<T>) -> Result<T> {
+        let changed = change(&mut self.graph.write().expect("memory graph lock poisoned"))?;
+        self.persist()?;
+        Ok(changed)
+    }
+
+    /// Write the graph as it is now. Writers take turns, and each writes the
+    /// latest graph, so an older snapshot never replaces a newer one.
+    fn persist(&self) -> Result<()> {
+        let Some(ref snapshot) = self.snapshot else {
+            return Ok(());
+        };
+        let path = snapshot.lock().expect("memory graph snapshot lock poisoned");
+
+        let contents = serde_json::to_vec(&self.read().snapshot())?;
+        let staged = path.with_extension("tmp");
+        std::fs::write(&staged, contents)
+            .and_then(|()| std::fs::rename(&staged, &*path))
+            .map_err(|e| DbError::Backend(format!("Failed to write graph snapshot {}: {}", path.display(), e)).into())
+    }
+}
+
+impl Default for MemoryGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl GraphStore for MemoryGraph {
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn migrate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn add_dependency(&self, repo_key: &str, registry: &str, package_name: &str, package_version: &str) -> Result<()> {
+        self.write(|graph| {
+            let repo = graph.require("repositories", repo_key)?;
+            let package = graph.upsert_package(registry, package_name);
+            let existing = graph
+                .outgoing(repo, |edge| matches!(edge, Edge::DependsOn { .. }))
+                .into_iter()
+                .find(|&edge| graph.graph.edge_endpoints(edge).map(|(_, to)| to) == Some(package));
+            let version = package_version.to_string();
+            match existing {
+                Some(edge) => graph.graph[edge] = Edge::DependsOn { version },
+                None => {
+                    graph.graph.add_edge(repo, package, Edge::DependsOn { version });
+                }
+            }
+            Ok(())
+        })
+    }
+
+    async fn upsert_dependency_snapshot(&self, repo_key: &str, dependencies: Vec<Dependency>) -> Result<DependencySnapshot> {
+        // Listed twice, the last entry wins
+        let wanted: BTreeMap<String, Dependency> = dependencies
+            .into_iter()
+            .filter(|dep| dep.direct)
+            .map(|dep| (registry_package_key(&dep.registry, &dep.name), dep))
+            .collect();
+
+        let snapshot = self.write(|graph| {
+            let repo = graph.require("repositories", repo_key)?;
+
+            let mut existing: HashMap<String, EdgeIndex> = HashMap::new();
+            for edge in graph.outgoing(repo, |edge| matches!(edge, Edge::DependsOn { .. })) {
+                if let Some((_, package)) = graph.graph.edge_endpoints(edge) {
+                    existing.insert(graph.graph[package].key().to_string(), edge);
+                }
+            }
+
+            let mut snapshot = DependencySnapshot::default();
+            for (key, dep) in &wanted {
+                match existing.remove(key) {
+                    None => {
+                        let package = graph.upsert_package(&dep.registry, &dep.name);
+                        graph.graph.add_edge(repo, package, Edge::DependsOn { version: dep.version.clone() });
+                        snapshot.added += 1;
+                    }
+                    Some(edge) if graph.graph[edge] != (Edge::DependsOn { version: dep.version.clone() }) => {
+                        graph.graph[edge] = Edge::DependsOn { version: dep.version.clone() };
+                        snapshot.updated += 1;
+                    }
+                    Some(_) => snapshot.unchanged += 1,
+                }
+            }
+            snapshot.removed = existing.len();
+            for (_, stale) in existing {
+                graph.graph.remove_edge(stale);
+            }
+
+            if let Vertex::Repositories { snapshot_version, .. } = &mut graph.graph[repo] {
+                *snapshot_version += 1;
+                snapshot.version = *snapshot_version;
+            }
+            Ok(snapshot)
+        })?;
+
+        tracing::debug!(
+            "Dependency snapshot {} of {}: {} added, {} updated, {} removed",
+            snapshot.version,
+            repo_key,
+            snapshot.added,
+            snapshot.updated,
+            snapshot.removed
+        );
+        Ok(snapshot)
+    }
+
+    async fn get_dependencies(&self, repo_key: &str) -> Result<Vec<Dependency>> {
+        Ok(self.read().dependencies(repo_key))
+    }
+
+    async fn get_affected_repos(&self, vulnerability_id: &str) -> Result<Vec<String>> {
+        let graph = self.read();
+        let Some(vulnerability) = graph.find(&vertex_id("vulnerabilities", vulnerability_id)) else {
+            return Ok(Vec::new());
+        };
+
+        let mut affected: Vec<String> = graph
+            .affected_packages(vulnerability)
+            .into_iter()
+            .flat_map(|package| graph.dependent_repositories(package))
+            .collect();
+        affected.sort();
+        affected.dedup();
+        Ok(affected)
+    }
+
+    async fn explain_impact(&self, vulnerability_id: &str, repo_key: &str) -> Result<Vec<ImpactPath>> {
+        let graph = self.read();
+        let (Some(vulnerability), Some(repo)) = (graph.find_vulnerability(vulnerability_id), graph.repository(repo_key)) else {
+            return Ok(Vec::new());
+        };
+
+        let targets: HashSet<NodeIndex> = graph.affected_packages(vulnerability).into_iter().collect();
+        let key = graph.graph[vulnerability].key().to_string();
+        Ok(graph
+            .dependency_paths(repo, &targets, MAX_IMPACT_PATHS as usize)
+            .iter()
+            .map(|path| graph.impact_path(&key, path))
+            .collect())
+    }
+
+    async fn get_dependents(&self, repo_key: &str) -> Result<Vec<String>> {
+        let graph = self.read();
+        let Some(repo) = graph.repository(repo_key) else {
+            return Ok(Vec::new());
+        };
+
+        let mut dependents = graph.dependent_repositories(repo);
+        dependents.sort();
+        Ok(dependents)
+    }
+
+    async fn get_dependency_depth(&self, repo_key: &str) -> Result<u32> {
+        Ok(self
+            .read()
+            .dependencies(repo_key)
+            .iter()
+            .map(|dep| dep.depth)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn add_vulnerability(&self, vuln: &Vulnerability, package_key: &str) -> Result<()> {
+        self.write(|graph| {
+            let package = graph.require("packages", package_key)?;
+            let vertex = Vertex::Vulnerabilities {
+                key: vuln.id.clone(),
+                severity: vuln.severity.clone(),
+                aliases: vuln.aliases.clone(),
+                affected_versions: vuln.affected_versions.clone(),
+                patched_versions: vuln.patched_versions.clone(),
+                source_updated_at: BTreeMap::new(),
+            };
+            let vulnerability = graph.upsert(vertex, |stored, new| {
+                if let (
+                    Vertex::Vulnerabilities { severity, aliases, affected_versions, patched_versions, .. },
+                    Vertex::Vulnerabilities {
+                        severity: new_severity,
+                        aliases: new_aliases,
+                        affected_versions: new_affected,
+                        patched_versions: new_patched,
+                        ..
+                    },
+                ) = (stored, new)
+                {
+                    (*severity, *affected_versions, *patched_versions) = (new_severity, new_affected, new_patched);
+                    for alias in new_aliases {
+                        if !aliases.contains(&alias) {
+                            aliases.push(alias);
+                        }
+                    }
+                }
+            });
+            graph.link_affected(vulnerability, package);
+            Ok(())
+        })
+    }
+
+    async fn upsert_advisory(
+        &self,
+        vuln: &Vulnerability,
+        source: &str,
+        updated_at: DateTime<Utc>,
+        packages: &[(String, String)],
+    ) -> Result<String> {
+        let ids: Vec<String> = std::iter::once(vuln.id.clone()).chain(vuln.aliases.iter().cloned()).collect();
+
+        self.write(|graph| {
+            let mut existing: Vec<&str> = graph
+                .graph
+                .node_weights()
+                .filter_map(|vertex| match vertex {
+                    Vertex::Vulnerabilities { key, aliases, .. }
+                        if ids.contains(key) || aliases.iter().any(|alias| ids.contains(alias)) =>
+                    {
+                        Some(key.as_str())
+                    }
+                    _ => None,
+                })
+                .collect();
+            existing.sort();
+            let key = existing.first().map(|key| key.to_string()).unwrap_or_else(|| vuln.id.clone());
+            tracing::debug!("Upserting advisory {} from {} as {}", vuln.id, source, key);
+
+            let vertex = Vertex::Vulnerabilities {
+                key: key.clone(),
+                severity: vuln.severity.clone(),
+                aliases: ids.iter().filter(|id| **id != key).cloned().collect(),
+                affected_versions: vuln.affected_versions.clone(),
+                patched_versions: vuln.patched_versions.clone(),
+                source_updated_at: BTreeMap::from([(source.to_string(), updated_at)]),
+            };
+            let vulnerability = graph.upsert(vertex, |stored, new| {
+                let (
+                    Vertex::Vulnerabilities { key, severity, aliases, affected_versions, patched_versions, source_updated_at },
+                    Vertex::Vulnerabilities {
+                        severity: new_severity,
+                        aliases: new_aliases,
+                        affected_versions: new_affected,
+                        patched_versions: new_patched,
+                        source_updated_at: new_updated_at,
+                        ..
+                    },
+                ) = (stored, new)
+                else {
+                    return;
+                };
+                *severity = new_severity;
+                for (stored, new) in [
+                    (&mut *affected_versions, new_affected),
+                    (&mut *patched_versions, new_patched),
+                    (&mut *aliases, new_aliases),
+                ] {
+                    for value in new {
+                        if !stored.contains(&value) {
+                            stored.push(value);
+                        }
+                    }
+                }
+                let key = key.clone();
+                aliases.retain(|alias| *alias != key);
+                source_updated_at.extend(new_updated_at);
+            });
+
+            for (registry, name) in packages {
+                let package = graph.upsert_package(registry, name);
+                graph.link_affected(vulnerability, package);
+            }
+            Ok(key)
+        })
+    }
+
+    async fn latest_advisory_update(&self, source: &str) -> Result<Option<DateTime<Utc>>> {
+        Ok(self
+            .read()
+            .graph
+            .node_weights()
+            .filter_map(|vertex| match vertex {
+                Vertex::Vulnerabilities { source_updated_at, .. } => source_updated_at.get(source).copied(),
+                _ => None,
+            })
+            .max())
+    }
+
+    async fn register_repository(&self, platform: &str, owner: &str, repo: &str) -> Result<String> {
+        let key = repository_key(platform, owner, repo);
+        self.write(|graph| {
+            let vertex = Vertex::Repositories {
+                key: key.clone(),
+                platform: platform.to_string(),
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                snapshot_version: 0,
+                transferred_from: None,
+            };
+            graph.upsert(vertex, |_, _| {});
+            Ok(())
+        })?;
+        Ok(key)
+    }
+
+    async fn link_package_repository(&self, registry: &str, package_name: &str, repo_key: &str) -> Result<()> {
+        self.write(|graph| {
+            let repo = graph.require("repositories", repo_key)?;
+            let package = graph.upsert_package(registry, package_name);
+            graph.replace_outgoing(package, repo, Edge::HostedAt, |edge| *edge == Edge::HostedAt);
+            Ok(())
+        })
+    }
+
+    async fn get_package_repository(&self, registry: &str, package_name: &str) -> Result<Option<RepoRef>> {
+        let graph = self.read();
+        let Some(package) = graph.find(&vertex_id("packages", &registry_package_key(registry, package_name))) else {
+            return Ok(None);
+        };
+        Ok(graph
+            .graph
+            .edges_directed(package, Direction::Outgoing)
+            .filter(|edge| *edge.weight() == Edge::HostedAt)
+            .find_map(|edge| graph.graph[edge.target()].repository()))
+    }
+
+    async fn link_fork(&self, fork_key: &str, upstream_key: &str, kind: UpstreamKind) -> Result<()> {
+        tracing::debug!("Linking {} as a {:?} of {}", fork_key, kind, upstream_key);
+        self.write(|graph| {
+            let fork = graph.require("repositories", fork_key)?;
+            let upstream = graph.require("repositories", upstream_key)?;
+            graph.replace_outgoing(fork, upstream, Edge::Forks { kind }, |edge| matches!(edge, Edge::Forks { .. }));
+            Ok(())
+        })
+    }
+
+    async fn get_upstreams(&self, repo_key: &str) -> Result<Vec<RepoRef>> {
+        let graph = self.read();
+        let Some(repo) = graph.repository(repo_key) else {
+            return Ok(Vec::new());
+        };
+        Ok(graph
+            .traverse(repo, Direction::Outgoing, MAX_FORK_DEPTH, |edge| matches!(edge, Edge::Forks { .. }))
+            .into_iter()
+            .filter_map(|(vertex, _, _)| graph.graph[vertex].repository())
+            .collect())
+    }
+
+    async fn get_fork_network(&self, repo_key: &str) -> Result<Vec<ForkMember>> {
+        let graph = self.read();
+        let Some(repo) = graph.repository(repo_key) else {
+            return Ok(Vec::new());
+        };
+        let is_fork = |edge: &Edge| matches!(edge, Edge::Forks { .. });
+
+        let root = graph
+            .traverse(repo, Direction::Outgoing, MAX_FORK_DEPTH, is_fork)
+            .last()
+            .map(|(vertex, _, _)| *vertex)
+            .unwrap_or(repo);
+        let member = |vertex: NodeIndex, upstream: Option<(EdgeIndex, NodeIndex)>, depth: u32| {
+            let kind = upstream.and_then(|(edge, _)| match graph.graph[edge] {
+                Edge::Forks { kind } => Some(kind),
+                _ => None,
+            });
+            graph.graph[vertex].repository().map(|repository| ForkMember {
+                key: graph.graph[vertex].key().to_string(),
+                repository,
+                upstream: upstream.map(|(_, upstream)| graph.graph[upstream].key().to_string()),
+                kind,
+                depth,
+            })
+        };
+
+        let forks = graph.traverse(root, Direction::Incoming, MAX_FORK_DEPTH, is_fork);
+        Ok(member(root, None, 0)
+            .into_iter()
+            .chain(forks.into_iter().filter_map(|(vertex, edge, depth)| {
+                let (_, upstream) = graph.graph.edge_endpoints(edge)?;
+                member(vertex, Some((edge, upstream)), depth)
+            }))
+            .collect())
+    }
+
+    async fn register_hierarchy(&self, repo_key: &str, ancestry: &[String]) -> Result<()> {
+        self.write(|graph| {
+            let mut child = graph.require("repositories", repo_key)?;
+            for unit in ancestry {
+                let organization = Vertex::Organizations {
+                    key: organization_key(unit),
+                    unit: unit.clone(),
+                    name: unit.rsplit(['/', ':', '#']).next().unwrap_or(unit).to_string(),
+                };
+                let parent = graph.upsert(organization, |_, _| {});
+                graph.replace_outgoing(child, parent, Edge::MemberOf, |edge| *edge == Edge::MemberOf);
+                child = parent;
+            }
+            Ok(())
+        })
+    }
+
+    async fn get_ancestry(&self, repo_key: &str) -> Result<Vec<String>> {
+        let graph = self.read();
+        let Some(repo) = graph.repository(repo_key) else {
+            return Ok(Vec::new());
+        };
+        Ok(graph
+            .traverse(repo, Direction::Outgoing, MAX_HIERARCHY_DEPTH, |edge| *edge == Edge::MemberOf)
+            .into_iter()
+            .filter_map(|(vertex, _, _)| match &graph.graph[vertex] {
+                Vertex::Organizations { unit, .. } => Some(unit.clone()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn get_organization_repositories(&self, unit: &str) -> Result<Vec<RepoRef>> {
+        let graph = self.read();
+        let Some(organization) = graph.find(&vertex_id("organizations", &organization_key(unit))) else {
+            return Ok(Vec::new());
+        };
+        Ok(graph
+            .traverse(organization, Direction::Incoming, MAX_HIERARCHY_DEPTH, |edge| *edge == Edge::MemberOf)
+            .into_iter()
+            .filter_map(|(vertex, _, _)| graph.graph[vertex].repository())
+            .collect())
+    }
+
+    async fn transfer_repository(&self, from: &RepoRef, to: &RepoRef) -> Result<String> {
+        let old_key = repository_key(&from.platform, &from.owner, &from.repo);
+        let new_key = repository_key(&to.platform, &to.owner, &to.repo);
+
+        tracing::info!("Transferring graph vertex {} -> {}", old_key, new_key);
+
+        self.write(|graph| {
+            let Some(old) = graph.repository(&old_key).filter(|_| old_key != new_key) else {
+                return Ok(());
+            };
+            let Vertex::Repositories { platform, snapshot_version, .. } = graph.graph[old].clone() else {
+                return Ok(());
+            };
+
+            let vertex = Vertex::Repositories {
+                key: new_key.clone(),
+                platform,
+                owner: to.owner.clone(),
+                repo: to.repo.clone(),
+                snapshot_version,
+                transferred_from: Some(old_key.clone()),
+            };
+            let new = graph.upsert(vertex, |stored, _| {
+                if let Vertex::Repositories { owner, repo, transferred_from, .. } = stored {
+                    (*owner, *repo, *transferred_from) = (to.owner.clone(), to.repo.clone(), Some(old_key.clone()));
+                }
+            });
+
+            // Membership is dropped with the old vertex; the rest moves over
+            let moved: Vec<(NodeIndex, NodeIndex, Edge)> = graph
+                .graph
+                .edges_directed(old, Direction::Outgoing)
+                .chain(graph.graph.edges_directed(old, Direction::Incoming))
+                .filter(|edge| *edge.weight() != Edge::MemberOf)
+                .map(|edge| {
+                    let source = if edge.source() == old { new } else { edge.source() };
+                    let target = if edge.target() == old { new } else { edge.target() };
+                    (source, target, edge.weight().clone())
+                })
+                .collect();
+            for (source, target, edge) in moved {
+                graph.graph.add_edge(source, target, edge);
+            }
+
+            graph.graph.remove_node(old);
+            graph.ids.remove(&vertex_id("repositories", &old_key));
+            Ok(())
+        })?;
+
+        Ok(new_key)
+    }
+}
@@ -0,0 +1,266 @@
+//! Database health
+//!
+//! Each backend is pinged every `RSR_DB_HEALTH_INTERVAL_SECS` (default 15,
+//! 0 disables the probes), recording how long it took to answer and how
+//! often it didn't. A ping that fails or takes longer than
+//! [`PROBE_TIMEOUT`] marks the backend unhealthy and reconnects it; while
+//! reconnecting keeps failing, attempts back off exponentially up to
+//! [`MAX_RECONNECT_BACKOFF`] so a backend that is down isn't hammered.
+//!
+//! Liveness and readiness are told apart: the process is alive while it
+//! answers at all, and ready (see [`Readiness`]) depending on its backends.
+//! With the cache up but the document or graph store unreachable the engine
+//! is degraded, not down: reports are still served from the cache, while
+//! scans can't be recorded.
+
+use crate::Result;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Longest a ping may take before the backend counts as down
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Wait before the second reconnect attempt, doubled after each failure
+const MIN_RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Longest wait between reconnect attempts
+pub const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Seconds between health probes, or `None` if they are disabled
+pub fn probe_interval() -> Option<Duration> {
+    let secs = std::env::var("RSR_DB_HEALTH_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(15u64);
+    (secs > 0).then_some(Duration::from_secs(secs))
+}
+
+/// Whether a backend is answering
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendStatus {
+    /// Not probed yet
+    #[default]
+    Unknown,
+    /// Answered the last probe in time
+    Up,
+    /// Configured, but didn't answer the last probe
+    Unreachable,
+    /// Not enabled: the engine is running without its databases
+    Disabled,
+}
+
+/// What the probes have seen of one backend
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BackendHealth {
+    pub status: BackendStatus,
+    /// How long the last answered ping took
+    pub latency_ms: Option<u64>,
+    /// Failed probes since the last answered one
+    pub consecutive_failures: u32,
+    /// Failed probes since startup
+    pub failures: u64,
+    /// Reconnects after which the backend answered again
+    pub reconnects: u64,
+    pub last_error: Option<String>,
+    pub checked_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl BackendHealth {
+    pub fn is_up(&self) -> bool {
+        self.status == BackendStatus::Up
+    }
+}
+
+/// Database health status
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DatabaseHealth {
+    pub cache: BackendHealth,
+    pub documents: BackendHealth,
+    pub graphs: BackendHealth,
+}
+
+impl DatabaseHealth {
+    /// Health of an engine running without databases
+    pub fn disabled() -> Self {
+        let disabled = BackendHealth {
+            status: BackendStatus::Disabled,
+            ..Default::default()
+        };
+        Self {
+            cache: disabled.clone(),
+            documents: disabled.clone(),
+            graphs: disabled,
+        }
+    }
+
+    /// Whether every backend answered its last probe
+    pub fn healthy(&self) -> bool {
+        self.backends().iter().all(|(_, backend)| backend.is_up())
+    }
+
+    /// Whether the engine can serve with its backends in this state
+    pub fn readiness(self) -> Readiness {
+        let mut reasons = Vec::new();
+        let mut status = ReadinessStatus::Ready;
+        for (backend, health) in self.backends() {
+            let (level, reason) = match health.status {
+                BackendStatus::Up => continue,
+                BackendStatus::Disabled => (ReadinessStatus::Degraded, format!("{} is not enabled", backend)),
+                BackendStatus::Unknown => (ReadinessStatus::NotReady, format!("{} has not been probed yet", backend)),
+                BackendStatus::Unreachable => {
+                    // Reports are served from the cache, so only losing it takes the engine out
+                    let level = match backend {
+                        "cache" => ReadinessStatus::NotReady,
+                        _ => ReadinessStatus::Degraded,
+                    };
+                    let error = health.last_error.as_deref().unwrap_or("no answer");
+                    (level, format!("{} is unreachable: {}", backend, error))
+                }
+            };
+            status = status.max(level);
+            reasons.push(reason);
+        }
+
+        Readiness {
+            status,
+            reasons,
+            databases: self,
+        }
+    }
+
+    /// Each backend's health, named for metrics
+    pub fn backends(&self) -> [(&'static str, &BackendHealth); 3] {
+        [
+            ("cache", &self.cache),
+            ("documents", &self.documents),
+            ("graphs", &self.graphs),
+        ]
+    }
+}
+
+/// How ready the engine is to serve, worst last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessStatus {
+    Ready,
+    /// Serving cached results; scans aren't recorded
+    Degraded,
+    NotReady,
+}
+
+/// Readiness of the engine, with why it isn't fully ready
+#[derive(Debug, Clone, Serialize)]
+pub struct Readiness {
+    pub status: ReadinessStatus,
+    /// One per backend that isn't up
+    pub reasons: Vec<String>,
+    pub databases: DatabaseHealth,
+}
+
+impl Readiness {
+    /// Whether traffic should be routed to the engine
+    pub fn serves_traffic(&self) -> bool {
+        self.status != ReadinessStatus::NotReady
+    }
+}
+
+/// One backend's health, and when it may next be reconnected
+#[derive(Default)]
+pub(super) struct Monitor {
+    state: Mutex<MonitorState>,
+}
+
+#[derive(Default)]
+struct MonitorState {
+    health: BackendHealth,
+    backoff: Duration,
+    /// Earliest next reconnect attempt; `None` reconnects on the next failure
+    retry_at: Option<Instant>,
+}
+
+impl Monitor {
+    pub fn snapshot(&self) -> BackendHealth {
+        self.state.lock().expect("db health lock poisoned").health.clone()
+    }
+
+    /// Ping the backend, reconnecting it if the ping fails and no reconnect
+    /// is backing off, and record the outcome
+    pub async fn check<P, PF, R, RF>(&self, ping: P, reconnect: R) -> BackendHealth
+    where
+        P: Fn() -> PF,
+        PF: Future<Output = Result<()>>,
+        R: FnOnce() -> RF,
+        RF: Future<Output = Result<()>>,
+    {
+        let error = match timed(ping()).await {
+            Ok(latency) => return self.answered(latency, false),
+            Err(e) => e,
+        };
+        if !self.failed(error) {
+            return self.snapshot();
+        }
+
+        match tokio::time::timeout(PROBE_TIMEOUT, reconnect()).await {
+            Ok(Ok(())) => match timed(ping()).await {
+                Ok(latency) => self.answered(latency, true),
+                Err(e) => {
+                    self.reconnect_failed(e);
+                    self.snapshot()
+                }
+            },
+            Ok(Err(e)) => {
+                self.reconnect_failed(format!("reconnect failed: {}", e));
+                self.snapshot()
+            }
+            Err(_) => {
+                self.reconnect_failed("reconnect timed out".to_string());
+                self.snapshot()
+            }
+        }
+    }
+
+    fn answered(&self, latency: Duration, reconnected: bool) -> BackendHealth {
+        let mut state = self.state.lock().expect("db health lock poisoned");
+        state.backoff = Duration::ZERO;
+        state.retry_at = None;
+        let health = &mut state.health;
+        health.status = BackendStatus::Up;
+        health.latency_ms = Some(latency.as_millis() as u64);
+        health.consecutive_failures = 0;
+        health.reconnects += u64::from(reconnected);
+        health.checked_at = Some(chrono::Utc::now());
+        health.clone()
+    }
+
+    /// Record a failed probe; true if a reconnect is due
+    fn failed(&self, error: String) -> bool {
+        let mut state = self.state.lock().expect("db health lock poisoned");
+        let health = &mut state.health;
+        health.status = BackendStatus::Unreachable;
+        health.consecutive_failures += 1;
+        health.failures += 1;
+        health.last_error = Some(error);
+        health.checked_at = Some(chrono::Utc::now());
+        !matches!(state.retry_at, Some(at) if Instant::now() < at)
+    }
+
+    fn reconnect_failed(&self, error: String) {
+        let mut state = self.state.lock().expect("db health lock poisoned");
+        state.backoff = (state.backoff * 2).clamp(MIN_RECONNECT_BACKOFF, MAX_RECONNECT_BACKOFF);
+        state.retry_at = Some(Instant::now() + state.backoff);
+        state.health.last_error = Some(error);
+    }
+}
+
+/// How long `ping` took to answer, or why it didn't
+async fn timed(ping: impl Future<Output = Result<()>>) -> std::result::Result<Duration, String> {
+    let started = Instant::now();
+    match tokio::time::timeout(PROBE_TIMEOUT, ping).await {
+        Ok(Ok(())) => Ok(started.elapsed()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("no answer within {}s", PROBE_TIMEOUT.as_secs())),
+    }
+}
@@ -0,0 +1,869 @@
+//! In-process cache backend for single-node and development use
+//!
+//! Selected with `RSR_DRAGONFLY_URL=memory://`. It behaves like the
+//! DragonflyDB backend within one process - TTLs, sliding-window rate
+//! limits, sessions, fair priority queues with leases and dead letters, and
+//! pub/sub - but nothing survives a restart and nothing is shared with other
+//! instances, so it is no substitute for DragonflyDB in a deployment with
+//! more than one engine.
+//!
+//! Expired entries are dropped when read and by GC.
+
+use super::cache::{self, CacheBackend, CacheKey, CacheKind, RateLimitDecision};
+use super::gc::{self, GcReport, GcRule};
+use super::queue::{self, ClaimedJob, DeliveryPolicy, JobConsumer, JobOutcome, JobPriority, NewJob, QueuePressure, QueuedJob};
+use super::session::{self, Session};
+use crate::adapters::http::{CachedResponse, EtagCache};
+use crate::{ComplianceStatus, RepoRef, Result, RsrError};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Notify};
+
+/// Messages a slow subscriber may fall behind by before it misses some
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone)]
+enum Value {
+    Text(String),
+    Etag(CachedResponse),
+    Session {
+        payload: serde_json::Value,
+        created_at: chrono::DateTime<chrono::Utc>,
+        ttl_secs: u64,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    value: Value,
+    expires_at: Option<Instant>,
+}
+
+impl Entry {
+    fn new(value: Value, ttl_secs: u64) -> Self {
+        Self {
+            value,
+            expires_at: Some(Instant::now() + Duration::from_secs(ttl_secs)),
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+/// One queue: per-priority rings of repositories with waiting jobs, leased
+/// jobs keyed by their stored form, dead letters newest first, retries
+/// waiting out their backoff with when they are due, and parked jobs by slot
+#[derive(Default)]
+struct MemoryQueue {
+    rings: BTreeMap<JobPriority, VecDeque<String>>,
+    jobs: HashMap<(JobPriority, String), VecDeque<QueuedJob>>,
+    processing: HashMap<String, i64>,
+    dead: VecDeque<QueuedJob>,
+    delayed: Vec<(i64, QueuedJob)>,
+    parked: HashMap<String, QueuedJob>,
+}
+
+impl MemoryQueue {
+    fn push(&mut self, job: QueuedJob) {
+        let jobs = self.jobs.entry((job.priority, job.repo.clone())).or_default();
+        if jobs.is_empty() {
+            self.rings.entry(job.priority).or_default().push_back(job.repo.clone());
+        }
+        jobs.push_back(job);
+    }
+
+    /// Take up to `max` jobs, highest priority first, repositories in turn,
+    /// after queueing retries whose backoff has passed
+    fn claim(&mut self, max: usize, deadline: i64) -> Vec<ClaimedJob> {
+        let now = chrono::Utc::now().timestamp();
+        let (due, waiting): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.delayed).into_iter().partition(|(at, _)| *at <= now);
+        self.delayed = waiting;
+        for (_, mut job) in due {
+            job.enqueued_at = now;
+            self.push(job);
+        }
+
+        let mut claimed = Vec::new();
+        for priority in JobPriority::ALL {
+            let Some(ring) = self.rings.get_mut(&priority) else {
+                continue;
+            };
+            while claimed.len() < max {
+                let Some(repo) = ring.pop_front() else {
+                    break;
+                };
+                let key = (priority, repo);
+                let Some(job) = self.jobs.get_mut(&key).and_then(VecDeque::pop_front) else {
+                    continue;
+                };
+                if self.jobs.get(&key).is_some_and(|jobs| !jobs.is_empty()) {
+                    ring.push_back(key.1);
+                } else {
+                    self.jobs.remove(&key);
+                }
+
+                let raw = serde_json::to_string(&job).unwrap_or_default();
+                self.processing.insert(raw.clone(), deadline);
+                claimed.push(ClaimedJob::from_raw(raw));
+            }
+        }
+        claimed
+    }
+
+    /// Requeue, delay or dead-letter a leased job; `Reclaimed` if it is no
+    /// longer leased
+    fn release(&mut self, raw: &str, mut job: QueuedJob, error: &str, policy: &DeliveryPolicy) -> JobOutcome {
+        if self.processing.remove(raw).is_none() {
+            return JobOutcome::Reclaimed;
+        }
+
+        job.attempts += 1;
+        job.last_error = Some(error.to_string());
+        if job.attempts >= policy.max_attempts {
+            tracing::warn!("Job {} dead-lettered after {} attempts: {}", job.id, job.attempts, error);
+            self.dead.push_front(job);
+            JobOutcome::DeadLettered
+        } else {
+            let now = chrono::Utc::now().timestamp();
+            let delay = policy.retry_delay_secs(job.attempts);
+            if delay > 0 {
+                self.delayed.push((now + delay as i64, job));
+            } else {
+                job.enqueued_at = now;
+                self.push(job);
+            }
+            JobOutcome::Retried
+        }
+    }
+
+    fn waiting(&self) -> impl Iterator<Item = &QueuedJob> {
+        self.jobs.values().flatten()
+    }
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<String, Entry>,
+    /// Request times in milliseconds, oldest first
+    rate_limits: HashMap<String, VecDeque<i64>>,
+    queues: HashMap<String, MemoryQueue>,
+}
+
+impl State {
+    /// Live entry at `key`, dropping it if it has expired
+    fn live(&mut self, key: &str) -> Option<&mut Entry> {
+        if self.entries.get(key).is_some_and(|entry| entry.is_expired(Instant::now())) {
+            self.entries.remove(key);
+        }
+        self.entries.get_mut(key)
+    }
+
+    fn text(&mut self, key: &str) -> Option<String> {
+        match self.live(key)?.value {
+            Value::Text(ref text) => Some(text.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Cache backend held entirely in this process. Clones share their data.
+#[derive(Clone, Default)]
+pub struct MemoryCache {
+    state: Arc<Mutex<State>>,
+    channels: Arc<Mutex<HashMap<String, broadcast::Sender<String>>>>,
+    /// Wakes consumers blocked on an empty queue
+    signals: Arc<Mutex<HashMap<String, Arc<Notify>>>>,
+}
+
+impl MemoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("memory cache lock poisoned")
+    }
+
+    fn signal(&self, queue: &str) -> Arc<Notify> {
+        self.signals
+            .lock()
+            .expect("memory cache lock poisoned")
+            .entry(queue.to_string())
+            .or_default()
+            .clone()
+    }
+
+    fn set_text(&self, key: String, value: &str, ttl_secs: u64) {
+        self.state().entries.insert(key, Entry::new(Value::Text(value.to_string()), ttl_secs));
+    }
+
+    fn sliding_window(&self, key: &str, max_requests: u64, window_secs: u64, consume: bool) -> RateLimitDecision {
+        let now = chrono::Utc::now().timestamp_millis();
+        let window_ms = window_secs.saturating_mul(1000).max(1) as i64;
+
+        let mut state = self.state();
+        let log = state
+            .rate_limits
+            .entry(CacheKey::new(CacheKind::RateLimit).segment(key).to_string())
+            .or_default();
+        while log.front().is_some_and(|&at| at <= now - window_ms) {
+            log.pop_front();
+        }
+
+        let allowed = (log.len() as u64) < max_requests;
+        if allowed && consume {
+            log.push_back(now);
+        }
+        let count = log.len() as u64;
+        let reset_ms = log.front().map_or(0, |&oldest| (oldest + window_ms - now).max(0) as u64);
+
+        RateLimitDecision {
+            allowed,
+            limit: max_requests,
+            remaining: max_requests.saturating_sub(count),
+            reset_after_secs: reset_ms.div_ceil(1000),
+        }
+    }
+}
+
+/// Claims jobs from one in-memory queue
+struct MemoryConsumer {
+    cache: MemoryCache,
+    queue: String,
+}
+
+#[async_trait::async_trait]
+impl JobConsumer for MemoryConsumer {
+    async fn next_batch(&mut self, max: usize, timeout_secs: u64, policy: &DeliveryPolicy) -> Result<Vec<ClaimedJob>> {
+        let signal = self.cache.signal(&self.queue);
+        // Register for wake-ups before looking, so an enqueue in between isn't missed
+        let notified = signal.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        let claim = |cache: &MemoryCache| {
+            let deadline = chrono::Utc::now().timestamp() + policy.visibility_timeout_secs as i64;
+            cache
+                .state()
+                .queues
+                .entry(self.queue.clone())
+                .or_default()
+                .claim(max.max(1), deadline)
+        };
+
+        let claimed = claim(&self.cache);
+        if !claimed.is_empty() {
+            return Ok(claimed);
+        }
+
+        if tokio::time::timeout(Duration::from_secs(timeout_secs), notified).await.is_err() {
+            return Ok(Vec::new());
+        }
+        Ok(claim(&self.cache))
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheBackend for MemoryCache {
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn cache_compliance(&self, status: &ComplianceStatus, ttl_secs: u64) -> Result<()> {
+        self.set_text(cache::compliance_key(&status.repo), &serde_json::to_string(status)?, ttl_secs);
+        Ok(())
+    }
+
+    async fn get_compliance(&self, repo: &RepoRef) -> Result<Option<ComplianceStatus>> {
+        let cached = self.state().text(&cache::compliance_key(repo));
+        Ok(cached.and_then(|json| cache::decode_status(repo, &json)))
+    }
+
+    async fn cache_compliance_many(&self, statuses: &[ComplianceStatus], ttl_secs: u64) -> Result<()> {
+        for status in statuses {
+            self.cache_compliance(status, ttl_secs).await?;
+        }
+        Ok(())
+    }
+
+    async fn get_compliance_many(&self, repos: &[RepoRef]) -> Result<Vec<Option<ComplianceStatus>>> {
+        let mut statuses = Vec::with_capacity(repos.len());
+        for repo in repos {
+            statuses.push(self.get_compliance(repo).await?);
+        }
+        Ok(statuses)
+    }
+
+    async fn invalidate_compliance(&self, repo: &RepoRef) -> Result<()> {
+        self.state().entries.remove(&cache::compliance_key(repo));
+        Ok(())
+    }
+
+    async fn cache_check_result(&self, repo: &RepoRef, commit_sha: &str, check_id: &str, value: &str, ttl_secs: u64) -> Result<()> {
+        let mut state = self.state();
+        state.entries.insert(
+            cache::check_result_key(repo, commit_sha, check_id),
+            Entry::new(Value::Text(value.to_string()), ttl_secs),
+        );
+        state
+            .entries
+            .insert(cache::check_head_key(repo), Entry::new(Value::Text(commit_sha.to_string()), ttl_secs));
+        Ok(())
+    }
+
+    async fn get_check_result(&self, repo: &RepoRef, commit_sha: &str, check_id: &str) -> Result<Option<String>> {
+        Ok(self.state().text(&cache::check_result_key(repo, commit_sha, check_id)))
+    }
+
+    async fn publish(&self, channel: &str, payload: &str) -> Result<u64> {
+        let channels = self.channels.lock().expect("memory cache lock poisoned");
+        // Sending fails only when nobody is subscribed
+        Ok(channels
+            .get(channel)
+            .and_then(|sender| sender.send(payload.to_string()).ok())
+            .unwrap_or(0) as u64)
+    }
+
+    async fn subscribe(&self, channels: &[&str]) -> Result<BoxStream<'static, String>> {
+        let mut senders = self.channels.lock().expect("memory cache lock poisoned");
+        let receivers: Vec<_> = channels
+            .iter()
+            .map(|channel| {
+                senders
+                    .entry(channel.to_string())
+                    .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+                    .subscribe()
+            })
+            .collect();
+
+        let streams = receivers.into_iter().map(|receiver| {
+            futures::stream::unfold(receiver, |mut receiver| async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(payload) => return Some((payload, receiver)),
+                        Err(broadcast::error::RecvError::Lagged(missed)) => {
+                            tracing::warn!("In-memory subscriber fell behind, {} messages dropped", missed);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            })
+            .boxed()
+        });
+
+        Ok(futures::stream::select_all(streams).boxed())
+    }
+
+    async fn record_delivery(&self, platform: &str, delivery_id: &str, ttl_secs: u64) -> Result<bool> {
+        let key = cache::delivery_key(platform, delivery_id);
+        let mut state = self.state();
+        if state.live(&key).is_some() {
+            return Ok(false);
+        }
+        let now = chrono::Utc::now().timestamp().to_string();
+        state.entries.insert(key, Entry::new(Value::Text(now), ttl_secs));
+        Ok(true)
+    }
+
+    async fn forget_delivery(&self, platform: &str, delivery_id: &str) -> Result<()> {
+        self.state().entries.remove(&cache::delivery_key(platform, delivery_id));
+        Ok(())
+    }
+
+    async fn rate_limit(&self, key: &str, max_requests: u64, window_secs: u64) -> Result<RateLimitDecision> {
+        Ok(self.sliding_window(key, max_requests, window_secs, true))
+    }
+
+    async fn rate_limit_check(&self, key: &str, max_requests: u64, window_secs: u64) -> Result<RateLimitDecision> {
+        Ok(self.sliding_window(key, max_requests, window_secs, false))
+    }
+
+    async fn create_session(&self, payload: &serde_json::Value, ttl_secs: u64) -> Result<String> {
+        if ttl_secs == 0 {
+            return Err(RsrError::Config("Session TTL must be at least one second".to_string()));
+        }
+
+        let id = session::new_session_id()?;
+        let value = Value::Session {
+            payload: payload.clone(),
+            created_at: chrono::Utc::now(),
+            ttl_secs,
+        };
+        self.state().entries.insert(session::session_key(&id), Entry::new(value, ttl_secs));
+        Ok(id)
+    }
+
+    async fn get_session(&self, session_id: &str) -> Result<Option<Session<serde_json::Value>>> {
+        let mut state = self.state();
+        let Some(entry) = state.live(&session::session_key(session_id)) else {
+            return Ok(None);
+        };
+        let Value::Session { ref payload, created_at, ttl_secs } = entry.value else {
+            return Ok(None);
+        };
+
+        let session = Session {
+            id: session_id.to_string(),
+            payload: payload.clone(),
+            created_at,
+            ttl_secs,
+        };
+        entry.expires_at = Some(Instant::now() + Duration::from_secs(ttl_secs));
+        Ok(Some(session))
+    }
+
+    async fn touch_session(&self, session_id: &str) -> Result<bool> {
+        let mut state = self.state();
+        let Some(entry) = state.live(&session::session_key(session_id)) else {
+            return Ok(false);
+        };
+        let Value::Session { ttl_secs, .. } = entry.value else {
+            return Ok(false);
+        };
+
+        entry.expires_at = Some(Instant::now() + Duration::from_secs(ttl_secs));
+        Ok(true)
+    }
+
+    async fn revoke_session(&self, session_id: &str) -> Result<bool> {
+        let mut state = self.state();
+        let live = state.live(&session::session_key(session_id)).is_some();
+        state.entries.remove(&session::session_key(session_id));
+        Ok(live)
+    }
+
+    async fn enqueue_job(&self, queue: &str, job: NewJob) -> Result<String> {
+        let job = QueuedJob::new(job);
+        let id = job.id.clone();
+        tracing::debug!("Enqueued {} job {} to {} ({}, {})", job.kind, id, queue, job.priority.as_str(), job.repo);
+        self.state().queues.entry(queue.to_string()).or_default().push(job);
+        self.signal(queue).notify_one();
+
+        Ok(id)
+    }
+
+    async fn relay_job(&self, queue: &str, job: &QueuedJob, dedupe_secs: u64) -> Result<bool> {
+        let key = queue::relayed_key(queue, &job.id);
+        {
+            let mut state = self.state();
+            if state.live(&key).is_some() {
+                return Ok(false);
+            }
+            state.entries.insert(key, Entry::new(Value::Text("1".to_string()), dedupe_secs.max(1)));
+            state.queues.entry(queue.to_string()).or_default().push(job.clone());
+        }
+        self.signal(queue).notify_one();
+
+        Ok(true)
+    }
+
+    async fn consumer(&self, queue: &str) -> Result<Box<dyn JobConsumer>> {
+        Ok(Box::new(MemoryConsumer {
+            cache: self.clone(),
+            queue: queue.to_string(),
+        }))
+    }
+
+    async fn ack_job(&self, queue: &str, claimed: &ClaimedJob) -> Result<()> {
+        if let Some(queue) = self.state().queues.get_mut(queue) {
+            queue.processing.remove(&claimed.raw);
+        }
+        Ok(())
+    }
+
+    async fn fail_job(&self, queue: &str, claimed: &ClaimedJob, error: &str, policy: &DeliveryPolicy) -> Result<JobOutcome> {
+        let outcome = self
+            .state()
+            .queues
+            .entry(queue.to_string())
+            .or_default()
+            .release(&claimed.raw, claimed.job.clone(), error, policy);
+        if outcome == JobOutcome::Retried {
+            self.signal(queue).notify_one();
+        }
+        Ok(outcome)
+    }
+
+    async fn defer_job(&self, queue: &str, claimed: &ClaimedJob) -> Result<JobOutcome> {
+        {
+            let mut state = self.state();
+            let memory_queue = state.queues.entry(queue.to_string()).or_default();
+            if memory_queue.processing.remove(&claimed.raw).is_none() {
+                return Ok(JobOutcome::Reclaimed);
+            }
+            let mut job = claimed.job.clone();
+            job.deferrals += 1;
+            job.enqueued_at = chrono::Utc::now().timestamp();
+            memory_queue.push(job);
+        }
+        self.signal(queue).notify_one();
+        Ok(JobOutcome::Retried)
+    }
+
+    async fn requeue_expired(&self, queue: &str, policy: &DeliveryPolicy) -> Result<usize> {
+        let now = chrono::Utc::now().timestamp();
+        let released = {
+            let mut state = self.state();
+            let Some(memory_queue) = state.queues.get_mut(queue) else {
+                return Ok(0);
+            };
+
+            let expired: Vec<String> = memory_queue
+                .processing
+                .iter()
+                .filter(|(_, &deadline)| deadline <= now)
+                .map(|(raw, _)| raw.clone())
+                .collect();
+            for raw in &expired {
+                let job = QueuedJob::decode(raw);
+                tracing::warn!("Job {} on {} exceeded its visibility timeout", job.id, queue);
+                memory_queue.release(raw, job, "visibility timeout expired", policy);
+            }
+            expired.len()
+        };
+
+        if released > 0 {
+            self.signal(queue).notify_waiters();
+        }
+        Ok(released)
+    }
+
+    async fn dead_letters(&self, queue: &str, limit: usize) -> Result<Vec<QueuedJob>> {
+        Ok(self
+            .state()
+            .queues
+            .get(queue)
+            .map(|queue| queue.dead.iter().take(limit).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn requeue_dead_letters(&self, queue: &str, id: Option<&str>) -> Result<usize> {
+        let now = chrono::Utc::now().timestamp();
+        let requeued = {
+            let mut state = self.state();
+            let Some(memory_queue) = state.queues.get_mut(queue) else {
+                return Ok(0);
+            };
+
+            let (requeue, keep): (Vec<_>, Vec<_>) = std::mem::take(&mut memory_queue.dead)
+                .into_iter()
+                .partition(|job| id.is_none_or(|id| id == job.id));
+            memory_queue.dead = keep.into();
+
+            let requeued = requeue.len();
+            for mut job in requeue {
+                job.attempts = 0;
+                job.enqueued_at = now;
+                memory_queue.push(job);
+            }
+            requeued
+        };
+
+        if requeued > 0 {
+            self.signal(queue).notify_waiters();
+        }
+        Ok(requeued)
+    }
+
+    async fn queue_pressure(&self, queue: &str) -> Result<QueuePressure> {
+        let state = self.state();
+        let mut pressure = QueuePressure {
+            queue: queue.to_string(),
+            depth_by_priority: JobPriority::ALL.iter().map(|&priority| (priority, 0)).collect(),
+            ..Default::default()
+        };
+        let Some(memory_queue) = state.queues.get(queue) else {
+            return Ok(pressure);
+        };
+
+        for job in memory_queue.waiting() {
+            pressure.depth += 1;
+            *pressure.depth_by_priority.entry(job.priority).or_default() += 1;
+        }
+        pressure.oldest_age_secs = memory_queue
+            .waiting()
+            .map(|job| job.enqueued_at)
+            .min()
+            .map(|oldest| (chrono::Utc::now().timestamp() - oldest).max(0) as u64);
+        pressure.in_flight = memory_queue.processing.len() as u64;
+        pressure.dead_letters = memory_queue.dead.len() as u64;
+        pressure.delayed = memory_queue.delayed.len() as u64;
+        pressure.parked = memory_queue.parked.len() as u64;
+
+        Ok(pressure)
+    }
+
+    async fn park_job(&self, queue: &str, slot: &str, job: NewJob) -> Result<Option<QueuedJob>> {
+        let job = QueuedJob::new(job);
+        tracing::debug!("Parked job {} on {} in {}", job.id, queue, slot);
+        Ok(self
+            .state()
+            .queues
+            .entry(queue.to_string())
+            .or_default()
+            .parked
+            .insert(slot.to_string(), job))
+    }
+
+    async fn parked_jobs(&self, queue: &str) -> Result<Vec<(String, QueuedJob)>> {
+        Ok(self
+            .state()
+            .queues
+            .get(queue)
+            .map(|queue| queue.parked.iter().map(|(slot, job)| (slot.clone(), job.clone())).collect())
+            .unwrap_or_default())
+    }
+
+    async fn unpark_job(&self, queue: &str, slot: &str, job: &QueuedJob) -> Result<bool> {
+        {
+            let mut state = self.state();
+            let Some(memory_queue) = state.queues.get_mut(queue) else {
+                return Ok(false);
+            };
+            if memory_queue.parked.get(slot) != Some(job) {
+                return Ok(false);
+            }
+
+            let mut job = memory_queue.parked.remove(slot).expect("parked job was just found");
+            job.enqueued_at = chrono::Utc::now().timestamp();
+            memory_queue.push(job);
+        }
+
+        self.signal(queue).notify_one();
+        Ok(true)
+    }
+
+    async fn migrate_repo_keys(&self, from: &RepoRef, to: &RepoRef) -> Result<usize> {
+        let from_id = cache::repo_id(from);
+        let to_id = cache::repo_id(to);
+
+        let mut state = self.state();
+        let renames: Vec<(String, String)> = state
+            .entries
+            .keys()
+            .filter_map(|key| cache::rename_repo_segment(key, &from_id, &to_id).map(|renamed| (key.clone(), renamed)))
+            .collect();
+        for (key, renamed) in &renames {
+            if let Some(entry) = state.entries.remove(key) {
+                state.entries.insert(renamed.clone(), entry);
+            }
+        }
+
+        // Waiting jobs move with the repository, keeping its turn in the rings
+        for queue in state.queues.values_mut() {
+            for ring in queue.rings.values_mut() {
+                for repo in ring.iter_mut().filter(|repo| **repo == from_id) {
+                    repo.clone_from(&to_id);
+                }
+            }
+            for priority in JobPriority::ALL {
+                if let Some(jobs) = queue.jobs.remove(&(priority, from_id.clone())) {
+                    queue.jobs.entry((priority, to_id.clone())).or_default().extend(jobs);
+                }
+            }
+        }
+
+        if !renames.is_empty() {
+            tracing::info!("Migrated {} cache keys {} -> {}", renames.len(), from_id, to_id);
+        }
+        Ok(renames.len())
+    }
+
+    async fn collect_garbage(&self, dry_run: bool) -> Result<GcReport> {
+        let started = Instant::now();
+        let mut report = GcReport {
+            started_at: Some(chrono::Utc::now()),
+            dry_run,
+            ..Default::default()
+        };
+
+        {
+            let mut state = self.state();
+            let now = Instant::now();
+            report.scanned = state.entries.len() as u64;
+            let expired: Vec<String> = state
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.is_expired(now))
+                .map(|(key, _)| key.clone())
+                .collect();
+
+            let count = report.by_rule.entry(GcRule::Expired).or_default();
+            count.keys = expired.len() as u64;
+            if !dry_run {
+                for key in &expired {
+                    state.entries.remove(key);
+                }
+                state.rate_limits.retain(|_, log| !log.is_empty());
+            }
+        }
+
+        report.duration_ms = started.elapsed().as_millis() as u64;
+        gc::record_run(&report);
+        Ok(report)
+    }
+}
+
+#[async_trait::async_trait]
+impl EtagCache for MemoryCache {
+    async fn get_etag(&self, key: &str) -> Result<Option<CachedResponse>> {
+        let mut state = self.state();
+        match state.live(&CacheKey::new(CacheKind::Etag).segment(key).to_string()) {
+            Some(Entry {
+                value: Value::Etag(ref cached),
+                ..
+            }) => Ok(Some(cached.clone())),
+            _ => Ok(None),
+        }
+    }
+
+    async fn put_etag(&self, key: &str, entry: &CachedResponse, ttl_secs: u64) -> Result<()> {
+        self.state().entries.insert(
+            CacheKey::new(CacheKind::Etag).segment(key).to_string(),
+            Entry::new(Value::Etag(entry.clone()), ttl_secs),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Scan;
+
+    impl queue::JobPayload for Scan {
+        const KIND: &'static str = "scan";
+        const SCHEMA_VERSION: u32 = 1;
+    }
+
+    fn status(repo: &RepoRef) -> ComplianceStatus {
+        ComplianceStatus::builder().repo(repo.clone()).build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn migration_leaves_same_prefix_siblings_alone() {
+        let cache = MemoryCache::new();
+        let (app, web) = (RepoRef::new("github", "acme", "app"), RepoRef::new("github", "acme", "app-web"));
+        for repo in [&app, &web] {
+            cache.cache_compliance(&status(repo), 3600).await.unwrap();
+            cache.cache_check_result(repo, "abc123", "bronze.license", "pass", 3600).await.unwrap();
+        }
+
+        let moved = RepoRef::new("github", "other", "app");
+        // The compliance status, the check result and the latest scanned commit
+        assert_eq!(cache.migrate_repo_keys(&app, &moved).await.unwrap(), 3);
+
+        assert!(cache.get_compliance(&app).await.unwrap().is_none());
+        assert!(cache.get_compliance(&moved).await.unwrap().is_some());
+        assert!(cache.get_check_result(&moved, "abc123", "bronze.license").await.unwrap().is_some());
+        assert!(cache.get_compliance(&web).await.unwrap().is_some());
+        assert!(cache.get_check_result(&web, "abc123", "bronze.license").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn migrated_repository_keeps_its_waiting_jobs() {
+        let cache = MemoryCache::new();
+        let (app, moved) = (RepoRef::new("github", "acme", "app"), RepoRef::new("github", "other", "app"));
+        let job = NewJob::new(&Scan, JobPriority::High, cache::repo_id(&app)).unwrap();
+        cache.enqueue_job("events", job).await.unwrap();
+
+        cache.migrate_repo_keys(&app, &moved).await.unwrap();
+        {
+            let state = cache.state();
+            let queue = &state.queues["events"];
+            assert_eq!(queue.rings[&JobPriority::High], [cache::repo_id(&moved)]);
+            assert!(queue.jobs.contains_key(&(JobPriority::High, cache::repo_id(&moved))));
+        }
+
+        let mut consumer = cache.consumer("events").await.unwrap();
+        let claimed = consumer.next_batch(10, 0, &DeliveryPolicy::default()).await.unwrap();
+        assert_eq!(claimed.len(), 1);
+    }
+
+    async fn enqueue_one(cache: &MemoryCache) -> Box<dyn JobConsumer> {
+        let app = RepoRef::new("github", "acme", "app");
+        let job = NewJob::new(&Scan, JobPriority::High, cache::repo_id(&app)).unwrap();
+        cache.enqueue_job("events", job).await.unwrap();
+        cache.consumer("events").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn expired_lease_is_delivered_again() {
+        let cache = MemoryCache::new();
+        let mut consumer = enqueue_one(&cache).await;
+        let policy = DeliveryPolicy {
+            visibility_timeout_secs: 0,
+            retry_backoff_secs: 0,
+            ..Default::default()
+        };
+
+        let first = consumer.next_batch(1, 0, &policy).await.unwrap().remove(0);
+        assert_eq!(cache.requeue_expired("events", &policy).await.unwrap(), 1);
+
+        let second = consumer.next_batch(1, 0, &policy).await.unwrap().remove(0);
+        assert_eq!(second.job.id, first.job.id);
+        assert_eq!(second.job.attempts, 1);
+        assert_eq!(second.job.last_error.as_deref(), Some("visibility timeout expired"));
+        // The worker that lost the lease can no longer fail the job
+        assert_eq!(cache.fail_job("events", &first, "late", &policy).await.unwrap(), JobOutcome::Reclaimed);
+    }
+
+    #[tokio::test]
+    async fn failed_job_waits_out_its_backoff() {
+        let cache = MemoryCache::new();
+        let mut consumer = enqueue_one(&cache).await;
+        let policy = DeliveryPolicy {
+            retry_backoff_secs: 60,
+            ..Default::default()
+        };
+
+        let claimed = consumer.next_batch(1, 0, &policy).await.unwrap().remove(0);
+        assert_eq!(cache.fail_job("events", &claimed, "boom", &policy).await.unwrap(), JobOutcome::Retried);
+        assert!(consumer.next_batch(1, 0, &policy).await.unwrap().is_empty());
+        let pressure = cache.queue_pressure("events").await.unwrap();
+        assert_eq!((pressure.depth, pressure.delayed), (0, 1));
+
+        {
+            let mut state = cache.state();
+            let (due, _) = &mut state.queues.get_mut("events").unwrap().delayed[0];
+            *due = chrono::Utc::now().timestamp() - 1;
+        }
+        let retried = consumer.next_batch(1, 0, &policy).await.unwrap();
+        assert_eq!(retried.len(), 1);
+        assert_eq!(retried[0].job.attempts, 1);
+        assert_eq!(cache.queue_pressure("events").await.unwrap().delayed, 0);
+    }
+
+    #[tokio::test]
+    async fn job_is_dead_lettered_after_max_attempts() {
+        let cache = MemoryCache::new();
+        let mut consumer = enqueue_one(&cache).await;
+        let policy = DeliveryPolicy {
+            max_attempts: 2,
+            retry_backoff_secs: 0,
+            ..Default::default()
+        };
+
+        let claimed = consumer.next_batch(1, 0, &policy).await.unwrap().remove(0);
+        assert_eq!(cache.fail_job("events", &claimed, "boom", &policy).await.unwrap(), JobOutcome::Retried);
+        let claimed = consumer.next_batch(1, 0, &policy).await.unwrap().remove(0);
+        assert_eq!(cache.fail_job("events", &claimed, "boom", &policy).await.unwrap(), JobOutcome::DeadLettered);
+
+        assert!(consumer.next_batch(1, 0, &policy).await.unwrap().is_empty());
+        let dead = cache.dead_letters("events", 10).await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!((dead[0].id.as_str(), dead[0].attempts), (claimed.job.id.as_str(), 2));
+
+        assert_eq!(cache.requeue_dead_letters("events", None).await.unwrap(), 1);
+        assert_eq!(consumer.next_batch(1, 0, &policy).await.unwrap()[0].job.attempts, 0);
+    }
+}
@@ -0,0 +1,216 @@
+//! Versioned SurrealDB schema migrations
+//!
+//! Migrations live in `engine/migrations/surrealdb` as numbered `.surql`
+//! files and are compiled into the binary. The Postgres store keeps its own
+//! list in `engine/migrations/postgres`, checked the same way. Each one is applied at most once,
+//! inside a transaction that also records it in `schema_migrations` with a
+//! checksum of its statements. A migration edited after it was applied is
+//! refused rather than silently skipped; add a new migration instead.
+//!
+//! Statements use `IF NOT EXISTS`, so databases created before migrations
+//! were tracked pick up where they are.
+
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::SurrealPool;
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+use crate::{Result, RsrError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// A schema migration
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub statements: &'static str,
+}
+
+impl Migration {
+    /// Hex SHA-256 of the statements, recorded when the migration is applied
+    pub fn checksum(&self) -> String {
+        hex::encode(Sha256::digest(self.statements.as_bytes()))
+    }
+}
+
+/// Every migration, in the order they are applied
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial",
+        statements: include_str!("../../migrations/surrealdb/0001_initial.surql"),
+    },
+    Migration {
+        version: 2,
+        name: "repo_redirects",
+        statements: include_str!("../../migrations/surrealdb/0002_repo_redirects.surql"),
+    },
+    Migration {
+        version: 3,
+        name: "report_provenance",
+        statements: include_str!("../../migrations/surrealdb/0003_report_provenance.surql"),
+    },
+    Migration {
+        version: 4,
+        name: "report_annotations",
+        statements: include_str!("../../migrations/surrealdb/0004_report_annotations.surql"),
+    },
+    Migration {
+        version: 5,
+        name: "repository_registry",
+        statements: include_str!("../../migrations/surrealdb/0005_repository_registry.surql"),
+    },
+    Migration {
+        version: 6,
+        name: "audit_events",
+        statements: include_str!("../../migrations/surrealdb/0006_audit_events.surql"),
+    },
+    Migration {
+        version: 7,
+        name: "report_chain",
+        statements: include_str!("../../migrations/surrealdb/0007_report_chain.surql"),
+    },
+    Migration {
+        version: 8,
+        name: "org_summary",
+        statements: include_str!("../../migrations/surrealdb/0008_org_summary.surql"),
+    },
+    Migration {
+        version: 9,
+        name: "report_retention",
+        statements: include_str!("../../migrations/surrealdb/0009_report_retention.surql"),
+    },
+    Migration {
+        version: 10,
+        name: "webhook_quarantine",
+        statements: include_str!("../../migrations/surrealdb/0010_webhook_quarantine.surql"),
+    },
+    Migration {
+        version: 11,
+        name: "benchmark_runs",
+        statements: include_str!("../../migrations/surrealdb/0011_benchmark_runs.surql"),
+    },
+    Migration {
+        version: 12,
+        name: "outbox",
+        statements: include_str!("../../migrations/surrealdb/0012_outbox.surql"),
+    },
+    Migration {
+        version: 13,
+        name: "webhook_parse_warnings",
+        statements: include_str!("../../migrations/surrealdb/0013_webhook_parse_warnings.surql"),
+    },
+    Migration {
+        version: 14,
+        name: "ci_runs",
+        statements: include_str!("../../migrations/surrealdb/0014_ci_runs.surql"),
+    },
+];
+
+#[cfg(feature = "documents-surrealdb")]
+/// Table recording applied migrations, created before anything else runs
+const BOOTSTRAP: &str = r#"
+    DEFINE TABLE IF NOT EXISTS schema_migrations SCHEMAFULL;
+    DEFINE FIELD IF NOT EXISTS version ON schema_migrations TYPE int;
+    DEFINE FIELD IF NOT EXISTS name ON schema_migrations TYPE string;
+    DEFINE FIELD IF NOT EXISTS checksum ON schema_migrations TYPE string;
+    DEFINE FIELD IF NOT EXISTS applied_at ON schema_migrations TYPE datetime DEFAULT time::now();
+"#;
+
+/// A migration as recorded in `schema_migrations`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Entries of `known` missing from `applied`, in order. Fails if an applied
+/// migration has changed or is not in `known`.
+pub(super) fn pending(known: &[Migration], applied: &[AppliedMigration]) -> Result<Vec<Migration>> {
+    let mut problems = Vec::new();
+    for record in applied {
+        match known.iter().find(|migration| migration.version == record.version) {
+            Some(migration) if migration.checksum() != record.checksum => problems.push(format!(
+                "migration {} ({}) changed since it was applied",
+                record.version, record.name
+            )),
+            Some(_) => {}
+            None => problems.push(format!(
+                "migration {} ({}) is applied but unknown to this build",
+                record.version, record.name
+            )),
+        }
+    }
+    if !problems.is_empty() {
+        return Err(RsrError::Config(problems.join("; ")));
+    }
+
+    Ok(known
+        .iter()
+        .filter(|migration| !applied.iter().any(|record| record.version == migration.version))
+        .copied()
+        .collect())
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealPool {
+    /// Migrations recorded as applied, oldest first
+    pub async fn applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        self.client()
+            .query(BOOTSTRAP)
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| DbError::surreal("SurrealDB migration bootstrap failed", e))?;
+
+        let mut result = self
+            .client()
+            .query("SELECT version, name, checksum, <string> applied_at AS applied_at FROM schema_migrations ORDER BY version")
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e).into())
+    }
+
+    /// Migrations not yet applied, in order. Fails if an applied migration
+    /// has changed or is unknown to this build.
+    pub async fn pending_migrations(&self) -> Result<Vec<Migration>> {
+        let applied = self.applied_migrations().await?;
+        pending(MIGRATIONS, &applied)
+    }
+
+    /// Apply pending migrations, each in its own transaction, returning the
+    /// ones applied
+    pub async fn migrate(&self) -> Result<Vec<Migration>> {
+        let pending = self.pending_migrations().await?;
+        if pending.is_empty() {
+            tracing::info!("SurrealDB schema is up to date");
+        }
+
+        for migration in &pending {
+            tracing::info!("Applying SurrealDB migration {} ({})", migration.version, migration.name);
+
+            let transaction = format!(
+                "BEGIN TRANSACTION;\n{}\n\
+                 CREATE type::thing('schema_migrations', $version) SET version = $version, name = $name, checksum = $checksum;\n\
+                 COMMIT TRANSACTION;",
+                migration.statements
+            );
+            self.client()
+                .query(transaction)
+                .bind(("version", migration.version))
+                .bind(("name", migration.name))
+                .bind(("checksum", migration.checksum()))
+                .await
+                .and_then(surrealdb::Response::check)
+                .map_err(|e| {
+                    DbError::surreal(format!("SurrealDB migration {} ({}) failed", migration.version, migration.name), e)
+                })?;
+        }
+
+        Ok(pending)
+    }
+}
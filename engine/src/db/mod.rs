@@ -1,33 +1,79 @@
 //! Database abstraction layer for RSR compliance data
 //!
 //! Multi-database architecture:
-//! - DragonflyDB: Caching, job queues (Redis-compatible)
-//! - SurrealDB: Documents, compliance reports
-//! - ArangoDB: Dependency graphs, relationships
+//! - DragonflyDB: Caching, job queues, event bus (Redis-compatible)
+//! - SurrealDB (or Postgres, or embedded SQLite): Documents, compliance reports
+//! - ArangoDB (or an in-process petgraph): Dependency graphs, relationships
 
+pub mod annotations;
+pub mod audit;
+pub mod benchmarks;
+pub mod bus;
 pub mod cache;
+pub mod ci_runs;
+pub mod config;
 pub mod documents;
+pub mod error;
+pub mod gc;
 pub mod graphs;
+#[cfg(feature = "graphs-memory")]
+pub mod graphs_memory;
+pub mod health;
+pub mod memory;
+pub mod migrations;
+pub mod orgs;
+pub mod outbox;
+#[cfg(feature = "documents-postgres")]
+pub mod postgres;
+pub mod quarantine;
+pub mod queue;
+pub mod registry;
+pub mod retention;
+pub mod session;
+#[cfg(any(feature = "documents-postgres", feature = "documents-sqlite"))]
+mod sql;
+#[cfg(feature = "documents-sqlite")]
+pub mod sqlite;
+pub mod trends;
 
-use crate::Result;
+pub use self::error::{DbError, DbResult};
+pub use self::health::{BackendHealth, BackendStatus, DatabaseHealth, Readiness};
+use crate::adapters::http::{CachedResponse, EtagCache};
+use crate::{ComplianceStatus, RepoRef, Result};
 
-/// Initialize all database connections
+/// Initialize all database connections, with the settings from
+/// `RSR_DB_CONFIG` and the environment
 pub async fn init() -> Result<DatabasePool> {
-    let cache = cache::DragonflyPool::connect_from_env().await?;
-    let docs = documents::SurrealPool::connect_from_env().await?;
-    let graphs = graphs::ArangoPool::connect_from_env().await?;
+    let config = config::DbConfig::load()?;
+    let cache = cache::connect_with(&config.dragonfly).await?;
+    let docs = documents::connect_with(&config.surrealdb).await?;
+    let graphs = graphs::connect_with(&config.arangodb).await?;
 
-    Ok(DatabasePool { cache, docs, graphs })
+    Ok(DatabasePool::new(cache, docs, graphs))
 }
 
-/// Combined database pool
+/// Combined database pool, tracking each backend's health
 pub struct DatabasePool {
-    pub cache: cache::DragonflyPool,
-    pub docs: documents::SurrealPool,
-    pub graphs: graphs::ArangoPool,
+    pub cache: std::sync::Arc<dyn cache::CacheBackend>,
+    pub docs: std::sync::Arc<dyn documents::DocumentStore>,
+    pub graphs: std::sync::Arc<dyn graphs::GraphStore>,
+    health: [health::Monitor; 3],
 }
 
 impl DatabasePool {
+    pub fn new(
+        cache: std::sync::Arc<dyn cache::CacheBackend>,
+        docs: std::sync::Arc<dyn documents::DocumentStore>,
+        graphs: std::sync::Arc<dyn graphs::GraphStore>,
+    ) -> Self {
+        Self {
+            cache,
+            docs,
+            graphs,
+            health: Default::default(),
+        }
+    }
+
     /// Run database migrations
     pub async fn migrate(&self) -> Result<()> {
         self.docs.migrate().await?;
@@ -35,20 +81,125 @@ pub async fn migrate(&self) -> Result<()> {
         Ok(())
     }
 
-    /// Health check all databases
-    pub async fn health_check(&self) -> Result<DatabaseHealth> {
-        Ok(DatabaseHealth {
-            cache: self.cache.ping().await.is_ok(),
-            documents: self.docs.ping().await.is_ok(),
-            graphs: self.graphs.ping().await.is_ok(),
-        })
+    /// Move all stored data for a transferred or renamed repository.
+    ///
+    /// The graph and cache move first. Documents are the source of truth and
+    /// go last, in one transaction that commits the transfer: once the
+    /// redirect is recorded, old report URLs resolve. If a step fails, the
+    /// steps before it are moved back, so the repository stays whole under
+    /// its old identity for the event to be retried.
+    ///
+    /// A repository's attestations are its chained reports, which move with
+    /// the documents. Their digests leave the repository's identity out, so
+    /// the chain still verifies under the new name.
+    pub async fn transfer_repository(&self, from: &RepoRef, to: &RepoRef) -> Result<()> {
+        self.graphs.transfer_repository(from, to).await?;
+        if let Err(e) = self.cache.migrate_repo_keys(from, to).await {
+            self.undo_transfer(from, to, false).await;
+            return Err(e);
+        }
+        if let Err(e) = self.docs.transfer_repository(from, to).await {
+            self.undo_transfer(from, to, true).await;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Move a failed transfer's graph vertex, and with `cache` its cache
+    /// keys, back to `from`. Best effort: a failure is logged, since the
+    /// transfer's own error is the one to report. Organization membership,
+    /// which the graph drops on a transfer, comes back with the next scan.
+    async fn undo_transfer(&self, from: &RepoRef, to: &RepoRef, cache: bool) {
+        if cache {
+            if let Err(e) = self.cache.migrate_repo_keys(to, from).await {
+                tracing::error!("Failed to move the cache keys of {} back to {}: {}", to, from, e);
+            }
+        }
+        if let Err(e) = self.graphs.transfer_repository(to, from).await {
+            tracing::error!("Failed to move the graph vertex of {} back to {}: {}", to, from, e);
+        }
+    }
+
+    /// Mark a repository as deleted, keeping its report history
+    pub async fn mark_repository_deleted(&self, repo: &RepoRef) -> Result<()> {
+        self.docs.mark_repository_deleted(repo).await
+    }
+
+    /// Latest compliance of the nearest upstream of a fork or mirror that
+    /// has been scanned, for forks that inherit or reference their
+    /// upstream's certification. The status names the upstream it is for.
+    pub async fn inherit_compliance_from_upstream(&self, repo: &RepoRef) -> Result<Option<ComplianceStatus>> {
+        let repo_key = graphs::repository_key(&repo.platform, &repo.owner, &repo.repo);
+        for upstream in self.graphs.get_upstreams(&repo_key).await? {
+            let status = match self.cache.get_compliance(&upstream).await {
+                Ok(Some(status)) => Some(status),
+                _ => {
+                    self.docs
+                        .get_latest_compliance(&upstream.platform, &upstream.owner, &upstream.repo)
+                        .await?
+                }
+            };
+            if status.is_some() {
+                return Ok(status);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Record an action in the audit log. Best effort: a failure is logged
+    /// rather than undoing an action that already happened.
+    pub async fn audit(&self, actor: &str, action: audit::AuditAction, target: &str, details: serde_json::Value) {
+        if let Err(e) = self.docs.record_audit(actor, action, target, details).await {
+            tracing::warn!("Failed to record {:?} on {} by {} in the audit log: {}", action, target, actor, e);
+        }
+    }
+
+    /// Probe every backend, reconnecting those that don't answer (see
+    /// [`health`])
+    pub async fn health_check(&self) -> DatabaseHealth {
+        let [cache, documents, graphs] = &self.health;
+        let (cache, documents, graphs) = tokio::join!(
+            cache.check(|| self.cache.ping(), || self.cache.reconnect()),
+            documents.check(|| self.docs.ping(), || self.docs.reconnect()),
+            graphs.check(|| self.graphs.ping(), || self.graphs.reconnect()),
+        );
+        DatabaseHealth { cache, documents, graphs }
+    }
+
+    /// Whether the engine can serve, from the last probes. Backends that
+    /// haven't been probed yet (the probes are disabled, or the first hasn't
+    /// run) are probed now.
+    pub async fn readiness(&self) -> Readiness {
+        let health = self.health();
+        let unprobed = health
+            .backends()
+            .iter()
+            .any(|(_, backend)| backend.status == BackendStatus::Unknown);
+        if unprobed {
+            self.health_check().await.readiness()
+        } else {
+            health.readiness()
+        }
+    }
+
+    /// Health as of the last probe
+    pub fn health(&self) -> DatabaseHealth {
+        let [cache, documents, graphs] = &self.health;
+        DatabaseHealth {
+            cache: cache.snapshot(),
+            documents: documents.snapshot(),
+            graphs: graphs.snapshot(),
+        }
     }
 }
 
-/// Database health status
-#[derive(Debug, Clone, serde::Serialize)]
-pub struct DatabaseHealth {
-    pub cache: bool,
-    pub documents: bool,
-    pub graphs: bool,
+#[async_trait::async_trait]
+impl EtagCache for DatabasePool {
+    async fn get_etag(&self, key: &str) -> Result<Option<CachedResponse>> {
+        self.cache.get_etag(key).await
+    }
+
+    async fn put_etag(&self, key: &str, entry: &CachedResponse, ttl_secs: u64) -> Result<()> {
+        self.cache.put_etag(key, entry, ttl_secs).await
+    }
 }
@@ -0,0 +1,236 @@
+//! Owner summaries
+//!
+//! The latest report of every repository under an owner (including its
+//! subgroups), rolled up into tier counts, a mean score, the lowest-scoring
+//! repositories and those whose certification has lapsed. Deactivated and
+//! deleted repositories are left out.
+//!
+//! Summaries are cached in `org_summary`. Storing or transferring a report
+//! drops the cached summaries of the owner and its parent groups; other
+//! changes, such as deactivating a repository or a certification lapsing,
+//! show up once the cached copy is [`SUMMARY_TTL_SECS`] old.
+
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::stored_tier;
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::SurrealPool;
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+use crate::{CertificationTier, RepoRef};
+#[cfg(feature = "documents-surrealdb")]
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(feature = "documents-surrealdb")]
+use std::collections::HashSet;
+
+/// Seconds a cached summary is served before it is recomputed
+pub const SUMMARY_TTL_SECS: u64 = 15 * 60;
+
+/// Repositories listed as worst offenders
+pub const WORST_OFFENDERS: usize = 10;
+
+/// Days a certification stays valid after the report that granted it,
+/// from `RSR_CERTIFICATION_VALIDITY_DAYS` (default 90)
+pub fn certification_validity() -> chrono::Duration {
+    let days = std::env::var("RSR_CERTIFICATION_VALIDITY_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(90);
+    chrono::Duration::days(days)
+}
+
+/// A repository's latest report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoStanding {
+    pub repo: RepoRef,
+    pub tier: CertificationTier,
+    pub score: f32,
+    pub scanned_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Compliance across every repository under an owner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrgSummary {
+    pub platform: String,
+    pub owner: String,
+    /// Repositories with at least one report
+    pub repositories: usize,
+    /// Repositories by the tier of their latest report, lapsed or not
+    pub tiers: BTreeMap<CertificationTier, usize>,
+    /// `None` if no repository has a report
+    pub mean_score: Option<f32>,
+    /// Lowest scores first, at most [`WORST_OFFENDERS`]
+    pub worst_offenders: Vec<RepoStanding>,
+    /// Certified repositories whose latest report is older than the
+    /// certification validity, oldest first
+    pub expired: Vec<RepoStanding>,
+    pub computed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl OrgSummary {
+    pub(super) fn from_standings(platform: &str, owner: &str, standings: Vec<RepoStanding>, validity: chrono::Duration) -> Self {
+        let computed_at = chrono::Utc::now();
+
+        let mut tiers = BTreeMap::new();
+        for standing in &standings {
+            *tiers.entry(standing.tier).or_default() += 1;
+        }
+        let mean_score = (!standings.is_empty())
+            .then(|| standings.iter().map(|standing| standing.score).sum::<f32>() / standings.len() as f32);
+
+        let mut expired: Vec<RepoStanding> = standings
+            .iter()
+            .filter(|standing| standing.tier > CertificationTier::None && standing.scanned_at + validity < computed_at)
+            .cloned()
+            .collect();
+        expired.sort_by_key(|standing| standing.scanned_at);
+
+        let mut worst_offenders = standings;
+        worst_offenders.sort_by(|a, b| a.score.total_cmp(&b.score).then_with(|| a.tier.cmp(&b.tier)));
+        let repositories = worst_offenders.len();
+        worst_offenders.truncate(WORST_OFFENDERS);
+
+        Self {
+            platform: platform.to_string(),
+            owner: owner.to_string(),
+            repositories,
+            tiers,
+            mean_score,
+            worst_offenders,
+            expired,
+            computed_at,
+        }
+    }
+}
+
+#[cfg(feature = "documents-surrealdb")]
+/// Latest report of one repository as selected by SurrealDB
+#[derive(Debug, Deserialize)]
+struct LatestRow {
+    owner: String,
+    repo: String,
+    tier: String,
+    score: f32,
+    scanned_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[cfg(feature = "documents-surrealdb")]
+/// A repository's identity in the registry
+#[derive(Debug, Deserialize)]
+struct RegistryName {
+    owner: String,
+    name: String,
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealPool {
+    /// Summarize the latest compliance status of every repository under
+    /// `owner`, from the cache if a summary was computed recently
+    pub async fn get_org_summary(&self, platform: &str, owner: &str) -> Result<OrgSummary> {
+        match self.cached_org_summary(platform, owner).await {
+            Ok(Some(summary)) => return Ok(summary),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to read cached summary for {}:{}: {}", platform, owner, e),
+        }
+
+        let summary = OrgSummary::from_standings(
+            platform,
+            owner,
+            self.latest_standings(platform, owner).await?,
+            certification_validity(),
+        );
+
+        // Caching is best effort; the summary is already computed
+        if let Err(e) = self.cache_org_summary(&summary).await {
+            tracing::warn!("Failed to cache summary for {}:{}: {}", platform, owner, e);
+        }
+        Ok(summary)
+    }
+
+    async fn cached_org_summary(&self, platform: &str, owner: &str) -> Result<Option<OrgSummary>> {
+        let mut result = self
+            .client()
+            .query(format!(
+                "SELECT VALUE summary FROM org_summary \
+                 WHERE platform = $platform AND owner = $owner AND computed_at > time::now() - {}s \
+                 LIMIT 1",
+                SUMMARY_TTL_SECS
+            ))
+            .bind(("platform", platform.to_string()))
+            .bind(("owner", owner.to_string()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let summaries: Vec<OrgSummary> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+        Ok(summaries.into_iter().next())
+    }
+
+    async fn cache_org_summary(&self, summary: &OrgSummary) -> Result<()> {
+        self.client()
+            .query(
+                "UPSERT org_summary SET platform = $platform, owner = $owner, summary = $summary, \
+                    computed_at = <datetime> $computed_at \
+                 WHERE platform = $platform AND owner = $owner",
+            )
+            .bind(("platform", summary.platform.clone()))
+            .bind(("owner", summary.owner.clone()))
+            .bind(("computed_at", summary.computed_at.to_rfc3339()))
+            .bind(("summary", serde_json::to_value(summary)?))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| DbError::surreal("SurrealDB upsert failed", e))?;
+        Ok(())
+    }
+
+    /// Latest report of each active repository under `owner`
+    async fn latest_standings(&self, platform: &str, owner: &str) -> Result<Vec<RepoStanding>> {
+        // Reports are sorted before grouping so each group's last entry is
+        // its latest report. Timestamps are read back as strings.
+        let mut result = self
+            .client()
+            .query(
+                "SELECT owner, repo, tier, score, <string> scanned_at AS scanned_at \
+                 FROM ( \
+                    SELECT owner, repo, array::last(tier) AS tier, array::last(score) AS score, \
+                        array::last(created_at) AS scanned_at \
+                    FROM ( \
+                        SELECT owner, repo, tier, score, created_at FROM compliance_report \
+                        WHERE platform = $platform AND deleted_at = NONE \
+                            AND (owner = $owner OR string::starts_with(owner, $owner + '/')) \
+                        ORDER BY created_at \
+                    ) \
+                    GROUP BY owner, repo \
+                 ); \
+                 SELECT owner, name FROM repository \
+                 WHERE platform = $platform \
+                    AND (owner = $owner OR string::starts_with(owner, $owner + '/')) \
+                    AND (active = false OR deleted_at != NONE)",
+            )
+            .bind(("platform", platform.to_string()))
+            .bind(("owner", owner.to_string()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let rows: Vec<LatestRow> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+        let retired: Vec<RegistryName> = result
+            .take(1)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+        let retired: HashSet<(String, String)> = retired.into_iter().map(|entry| (entry.owner, entry.name)).collect();
+
+        Ok(rows
+            .into_iter()
+            .filter(|row| !retired.contains(&(row.owner.clone(), row.repo.clone())))
+            .map(|row| RepoStanding {
+                repo: RepoRef::new(platform, &row.owner, &row.repo),
+                tier: stored_tier(&row.tier),
+                score: row.score,
+                scanned_at: row.scanned_at,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,242 @@
+//! Transactional outbox between the document store and the job queues
+//!
+//! Archiving a webhook and queueing the job that processes it touch two
+//! stores, so a crash between the writes used to lose the event. Instead the
+//! job is written to an `outbox` table in the same transaction as the
+//! archived event, and relayed onto its queue afterwards: straight away by
+//! the webhook handler, and by a background relay for anything left behind.
+//!
+//! An outbox entry is keyed by its job's ID, fixed when it is written. The
+//! queue remembers relayed IDs for [`RELAY_DEDUPE_SECS`], so an entry relayed
+//! twice - by a relay that died before removing it, or by two replicas at
+//! once - is only queued once.
+
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::WebhookEvent;
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::SurrealPool;
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+use super::queue::{NewJob, QueuedJob};
+use super::DatabasePool;
+use crate::Result;
+#[cfg(feature = "documents-surrealdb")]
+use crate::RsrError;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long a relayed job's ID is remembered
+pub const RELAY_DEDUPE_SECS: u64 = 24 * 60 * 60;
+
+/// Entries read from the outbox at a time
+const RELAY_BATCH: u32 = 100;
+
+/// Builds the job for an archived event from its record ID
+pub type BuildJob = dyn Fn(&str) -> Result<NewJob> + Send + Sync;
+
+/// Seconds between background relays, from `RSR_OUTBOX_RELAY_INTERVAL_SECS`
+/// (default 5); `None` if set to 0
+pub fn relay_interval() -> Option<Duration> {
+    let secs = std::env::var("RSR_OUTBOX_RELAY_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5u64);
+    (secs > 0).then_some(Duration::from_secs(secs))
+}
+
+/// A job written to the outbox and not yet relayed
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    pub queue: String,
+    /// The job as it will be queued; its ID keys the entry
+    pub job: QueuedJob,
+    /// Failed relays so far
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl OutboxEntry {
+    pub(super) fn new(queue: &str, job: NewJob) -> Self {
+        Self {
+            queue: queue.to_string(),
+            job: QueuedJob::new(job),
+            attempts: 0,
+            last_error: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+}
+
+/// An outbox entry as stored, with the job serialized as on the queue
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct OutboxRow {
+    pub(super) queue: String,
+    pub(super) job: String,
+    #[serde(default)]
+    pub(super) attempts: u32,
+    #[serde(default)]
+    pub(super) last_error: Option<String>,
+    pub(super) created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl OutboxRow {
+    pub(super) fn from_entry(entry: &OutboxEntry) -> Result<Self> {
+        Ok(Self {
+            queue: entry.queue.clone(),
+            job: serde_json::to_string(&entry.job)?,
+            attempts: entry.attempts,
+            last_error: entry.last_error.clone(),
+            created_at: entry.created_at,
+        })
+    }
+}
+
+impl From<OutboxRow> for OutboxEntry {
+    fn from(row: OutboxRow) -> Self {
+        Self {
+            queue: row.queue,
+            job: QueuedJob::decode(&row.job),
+            attempts: row.attempts,
+            last_error: row.last_error,
+            created_at: row.created_at,
+        }
+    }
+}
+
+/// What a relay pass did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RelayReport {
+    pub relayed: usize,
+    /// Entries whose job had already been queued
+    pub duplicates: usize,
+    /// Entries left for the next pass
+    pub failed: usize,
+}
+
+/// Queue an entry's job and remove the entry. Returns false if the job had
+/// already been relayed.
+pub async fn relay_entry(db: &DatabasePool, entry: &OutboxEntry) -> Result<bool> {
+    let queued = db.cache.relay_job(&entry.queue, &entry.job, RELAY_DEDUPE_SECS).await?;
+    db.docs.remove_outbox_entry(&entry.job.id).await?;
+    Ok(queued)
+}
+
+/// Relay every pending entry, oldest first. Stops at the first entry that
+/// can't be relayed, since the cache is most likely down and the rest would
+/// fail the same way.
+pub async fn relay(db: &DatabasePool) -> Result<RelayReport> {
+    let mut report = RelayReport::default();
+
+    loop {
+        let pending = db.docs.pending_outbox(RELAY_BATCH).await?;
+        let full = pending.len() == RELAY_BATCH as usize;
+
+        for entry in pending {
+            match relay_entry(db, &entry).await {
+                Ok(true) => report.relayed += 1,
+                Ok(false) => report.duplicates += 1,
+                Err(e) => {
+                    tracing::warn!("Failed to relay {} job {}: {}", entry.job.kind, entry.job.id, e);
+                    db.docs.mark_outbox_failed(&entry.job.id, &e.to_string()).await?;
+                    report.failed += 1;
+                    return Ok(report);
+                }
+            }
+        }
+
+        if !full {
+            return Ok(report);
+        }
+    }
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealPool {
+    /// Archive a webhook delivery and write the job processing it to the
+    /// outbox, in one transaction. Returns the event's record ID and the
+    /// outbox entry.
+    pub async fn store_webhook_event_with_job(
+        &self,
+        event: &WebhookEvent,
+        queue: &str,
+        job: &BuildJob,
+    ) -> Result<(String, OutboxEntry)> {
+        tracing::debug!("Archiving webhook event with outbox job: {}/{}", event.platform, event.event_type);
+
+        let mut bytes = [0u8; 16];
+        getrandom::fill(&mut bytes)
+            .map_err(|e| RsrError::Platform(format!("Failed to generate webhook event ID: {}", e)))?;
+        let key = hex::encode(bytes);
+        let event_id = surrealdb::RecordId::from_table_key("webhook_event", key.as_str()).to_string();
+        let entry = OutboxEntry::new(queue, job(&event_id)?);
+
+        // Timestamps are bound as RFC 3339 strings and cast, since the fields are datetimes
+        self.client()
+            .query(
+                "BEGIN TRANSACTION; \
+                 CREATE type::thing('webhook_event', $key) SET \
+                    platform = $event.platform, event_type = $event.event_type, \
+                    delivery_id = $event.delivery_id, headers = $event.headers, payload = $event.payload, \
+                    verification = $event.verification, processed = $event.processed, error = $event.error, \
+                    quarantined_by = $event.quarantined_by, parse_warnings = $event.parse_warnings, \
+                    received_at = <datetime> $received_at; \
+                 CREATE type::thing('outbox', $job_id) SET \
+                    queue = $entry.queue, job = $entry.job, attempts = $entry.attempts, \
+                    last_error = $entry.last_error, created_at = <datetime> $created_at; \
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("key", key))
+            .bind(("event", event.clone()))
+            .bind(("received_at", event.received_at.to_rfc3339()))
+            .bind(("job_id", entry.job.id.clone()))
+            .bind(("entry", OutboxRow::from_entry(&entry)?))
+            .bind(("created_at", entry.created_at.to_rfc3339()))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| DbError::surreal("SurrealDB create failed", e))?;
+
+        Ok((event_id, entry))
+    }
+
+    /// Outbox entries not yet relayed, oldest first
+    pub async fn pending_outbox(&self, limit: u32) -> Result<Vec<OutboxEntry>> {
+        let mut result = self
+            .client()
+            .query(
+                "SELECT queue, job, attempts, last_error, <string> created_at AS created_at \
+                 FROM outbox ORDER BY created_at LIMIT $limit",
+            )
+            .bind(("limit", limit))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let rows: Vec<OutboxRow> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+        Ok(rows.into_iter().map(OutboxEntry::from).collect())
+    }
+
+    /// Remove a relayed outbox entry
+    pub async fn remove_outbox_entry(&self, job_id: &str) -> Result<()> {
+        self.client()
+            .query("DELETE type::thing('outbox', $id)")
+            .bind(("id", job_id.to_string()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB delete failed", e))?;
+
+        Ok(())
+    }
+
+    /// Record why relaying an outbox entry failed
+    pub async fn mark_outbox_failed(&self, job_id: &str, error: &str) -> Result<()> {
+        self.client()
+            .query("UPDATE type::thing('outbox', $id) SET attempts += 1, last_error = $error")
+            .bind(("id", job_id.to_string()))
+            .bind(("error", error.to_string()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB update failed", e))?;
+
+        Ok(())
+    }
+}
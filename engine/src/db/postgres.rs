@@ -0,0 +1,1334 @@
+//! Postgres document store
+//!
+//! An alternative to SurrealDB for deployments that already run Postgres,
+//! enabled with the `documents-postgres` feature and selected by setting
+//! `RSR_POSTGRES_URL`. It keeps the same records as [`SurrealPool`], with
+//! the same guarantees: chained reports and audit events are protected by
+//! triggers, and reports are appended one at a time per repository.
+//!
+//! Postgres stores timestamps to the microsecond, so report times are
+//! truncated to microseconds before a report is chained; its digest then
+//! matches what is read back.
+//!
+//! The schema is managed by its own migrations in `engine/migrations/postgres`,
+//! checked against `schema_migrations` the same way as SurrealDB's.
+//!
+//! [`SurrealPool`]: super::documents::SurrealPool
+
+use super::annotations::{Annotation, AnnotationKind};
+use super::audit::{repo_target, AuditAction, AuditEvent, AuditPage, AuditQuery, MAX_AUDIT_PAGE};
+use super::ci_runs::CiRun;
+use super::documents::{
+    stored_tier, ArchivedWebhook, ChainVerification, ComplianceReport, DocumentStore, HistoryPage,
+    VerificationOutcome, WebhookEvent, CHAIN_PAGE, MAX_HISTORY_PAGE,
+};
+use super::error::DbError;
+use super::migrations::{self, AppliedMigration, Migration};
+use super::orgs::{certification_validity, OrgSummary, RepoStanding, SUMMARY_TTL_SECS};
+use super::outbox::{BuildJob, OutboxEntry, OutboxRow};
+use super::quarantine::ENGINE_VERSION;
+use super::registry::RegisteredRepository;
+use super::retention::{PruneReport, RetentionPolicy};
+use super::sql::{parse_text, record_id, record_key, text, PoolSettings};
+use super::trends::{self, ComplianceTrend, TrendBucket, TrendInterval, TrendWindow};
+use crate::adapters::strict::ParseWarning;
+use crate::{ComplianceStatus, RepoRef, Result, RsrError};
+use chrono::SubsecRound;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::types::Json;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Every Postgres migration, in the order they are applied
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial",
+        statements: include_str!("../../migrations/postgres/0001_initial.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "webhook_quarantine",
+        statements: include_str!("../../migrations/postgres/0002_webhook_quarantine.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "outbox",
+        statements: include_str!("../../migrations/postgres/0003_outbox.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "webhook_parse_warnings",
+        statements: include_str!("../../migrations/postgres/0004_webhook_parse_warnings.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "ci_runs",
+        statements: include_str!("../../migrations/postgres/0005_ci_runs.sql"),
+    },
+];
+
+/// Table recording applied migrations, created before anything else runs
+const BOOTSTRAP: &str = "CREATE TABLE IF NOT EXISTS schema_migrations ( \
+    version INTEGER PRIMARY KEY, \
+    name TEXT NOT NULL, \
+    checksum TEXT NOT NULL, \
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT now() \
+)";
+
+/// Columns selected for a stored report
+const REPORT_FIELDS: &str = "platform, owner, repo, tier, score, checks, standard, canonical, evidence, \
+    created_at, digest, previous_digest";
+
+/// Postgres connection pool
+pub struct PostgresStore {
+    pool: PgPool,
+}
+
+/// Compliance report as stored in Postgres
+#[derive(Debug, sqlx::FromRow)]
+struct ReportRow {
+    platform: String,
+    owner: String,
+    repo: String,
+    tier: String,
+    score: f32,
+    checks: serde_json::Value,
+    standard: Json<Vec<String>>,
+    canonical: Option<Json<RepoRef>>,
+    evidence: Json<BTreeMap<String, RepoRef>>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    digest: String,
+    previous_digest: Option<String>,
+}
+
+impl From<ReportRow> for ComplianceReport {
+    fn from(row: ReportRow) -> Self {
+        Self {
+            platform: row.platform,
+            owner: row.owner,
+            repo: row.repo,
+            tier: row.tier,
+            score: row.score,
+            checks: row.checks,
+            created_at: row.created_at,
+            standard: row.standard.0,
+            canonical: row.canonical.map(|canonical| canonical.0),
+            evidence: row.evidence.0,
+            digest: Some(row.digest),
+            previous_digest: row.previous_digest,
+        }
+    }
+}
+
+/// Archived webhook as stored in Postgres
+#[derive(Debug, sqlx::FromRow)]
+struct WebhookRow {
+    id: i64,
+    platform: String,
+    event_type: String,
+    delivery_id: Option<String>,
+    headers: Json<HashMap<String, String>>,
+    payload: String,
+    verification: String,
+    processed: bool,
+    error: Option<String>,
+    quarantined_by: Option<String>,
+    parse_warnings: Json<Vec<ParseWarning>>,
+    received_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<WebhookRow> for ArchivedWebhook {
+    fn from(row: WebhookRow) -> Self {
+        Self {
+            id: record_id("webhook_event", row.id),
+            event: WebhookEvent {
+                platform: row.platform,
+                event_type: row.event_type,
+                delivery_id: row.delivery_id,
+                headers: row.headers.0,
+                payload: row.payload,
+                verification: parse_text(&row.verification).unwrap_or(VerificationOutcome::Rejected),
+                processed: row.processed,
+                error: row.error,
+                quarantined_by: row.quarantined_by,
+                parse_warnings: row.parse_warnings.0,
+                received_at: row.received_at,
+            },
+        }
+    }
+}
+
+/// Latest CI run of a workflow as stored in Postgres
+#[derive(Debug, sqlx::FromRow)]
+struct CiRunRow {
+    workflow: String,
+    branch: String,
+    commit_sha: String,
+    conclusion: String,
+    completed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl CiRunRow {
+    /// The run, unless its conclusion is one this build doesn't know
+    fn into_run(self) -> Option<CiRun> {
+        Some(CiRun {
+            conclusion: parse_text(&self.conclusion)?,
+            workflow: self.workflow,
+            branch: self.branch,
+            commit_sha: self.commit_sha,
+            completed_at: self.completed_at,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct AnnotationRow {
+    id: i64,
+    kind: String,
+    check_id: Option<String>,
+    message: String,
+    reference: Option<String>,
+    author: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct AuditRow {
+    actor: String,
+    action: String,
+    target: String,
+    details: serde_json::Value,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct RegistryRow {
+    platform: String,
+    owner: String,
+    name: String,
+    adapter: Option<String>,
+    active: bool,
+    registered_at: chrono::DateTime<chrono::Utc>,
+    last_scanned_at: Option<chrono::DateTime<chrono::Utc>>,
+    deactivated_at: Option<chrono::DateTime<chrono::Utc>>,
+    deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<RegistryRow> for RegisteredRepository {
+    fn from(row: RegistryRow) -> Self {
+        Self {
+            repo: RepoRef::new(row.platform, row.owner, row.name),
+            adapter: row.adapter,
+            active: row.active,
+            registered_at: row.registered_at,
+            last_scanned_at: row.last_scanned_at,
+            deactivated_at: row.deactivated_at,
+            deleted_at: row.deleted_at,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct MigrationRow {
+    version: i32,
+    name: String,
+    checksum: String,
+    applied_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct BucketRow {
+    bucket: chrono::DateTime<chrono::Utc>,
+    reports: i64,
+    min_score: f32,
+    max_score: f32,
+    avg_score: f64,
+    opening_tier: String,
+    closing_tier: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct StandingRow {
+    owner: String,
+    repo: String,
+    tier: String,
+    score: f32,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct RepoRow {
+    platform: String,
+    owner: String,
+    repo: String,
+}
+
+fn query_failed(e: sqlx::Error) -> DbError {
+    DbError::sqlx("Postgres query failed", e)
+}
+
+/// Insert an archived webhook, returning its serial key
+async fn insert_webhook_event<'e>(executor: impl sqlx::PgExecutor<'e>, event: &WebhookEvent) -> Result<i64> {
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO webhook_event \
+            (platform, event_type, delivery_id, headers, payload, verification, processed, error, quarantined_by, \
+            parse_warnings, received_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) \
+         RETURNING id",
+    )
+    .bind(&event.platform)
+    .bind(&event.event_type)
+    .bind(&event.delivery_id)
+    .bind(Json(&event.headers))
+    .bind(&event.payload)
+    .bind(text(event.verification)?)
+    .bind(event.processed)
+    .bind(&event.error)
+    .bind(&event.quarantined_by)
+    .bind(Json(&event.parse_warnings))
+    .bind(event.received_at)
+    .fetch_one(executor)
+    .await
+    .map_err(|e| DbError::sqlx("Postgres insert failed", e))?;
+
+    Ok(id)
+}
+
+impl PostgresStore {
+    /// Connect to Postgres at `url`. `RSR_POSTGRES_MAX_CONNECTIONS` sets the
+    /// pool size (default 10); see [`PoolSettings`] for the other knobs.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let settings = PoolSettings::from_env("POSTGRES", 10);
+
+        // The URL may carry a password, so it isn't logged
+        tracing::info!(
+            "Connecting to Postgres (pool of {} to {})",
+            settings.min_connections,
+            settings.max_connections
+        );
+
+        let pool = settings
+            .apply(PgPoolOptions::new())
+            .connect(url)
+            .await
+            .map_err(|e| DbError::sqlx("Postgres connection failed", e))?;
+
+        Ok(Self { pool })
+    }
+
+    /// Migrations recorded as applied, oldest first
+    pub async fn applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        sqlx::query(BOOTSTRAP)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("Postgres migration bootstrap failed", e))?;
+
+        let rows: Vec<MigrationRow> =
+            sqlx::query_as("SELECT version, name, checksum, applied_at FROM schema_migrations ORDER BY version")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(query_failed)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| AppliedMigration {
+                version: row.version as u32,
+                name: row.name,
+                checksum: row.checksum,
+                applied_at: row.applied_at,
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl DocumentStore for PostgresStore {
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("Postgres ping failed", e))?;
+        tracing::debug!("Postgres ping successful");
+        Ok(())
+    }
+
+    async fn pending_migrations(&self) -> Result<Vec<Migration>> {
+        let applied = self.applied_migrations().await?;
+        migrations::pending(MIGRATIONS, &applied)
+    }
+
+    /// Apply pending migrations, each in its own transaction
+    async fn migrate(&self) -> Result<Vec<Migration>> {
+        let pending = self.pending_migrations().await?;
+        if pending.is_empty() {
+            tracing::info!("Postgres schema is up to date");
+        }
+
+        for migration in &pending {
+            tracing::info!("Applying Postgres migration {} ({})", migration.version, migration.name);
+
+            let failed = |e: sqlx::Error| {
+                DbError::sqlx(format!("Postgres migration {} ({}) failed", migration.version, migration.name), e)
+            };
+            let mut tx = self.pool.begin().await.map_err(failed)?;
+            sqlx::Executor::execute(&mut *tx, sqlx::raw_sql(migration.statements)).await.map_err(failed)?;
+            sqlx::query("INSERT INTO schema_migrations (version, name, checksum) VALUES ($1, $2, $3)")
+                .bind(migration.version as i32)
+                .bind(migration.name)
+                .bind(migration.checksum())
+                .execute(&mut *tx)
+                .await
+                .map_err(failed)?;
+            tx.commit().await.map_err(failed)?;
+        }
+
+        Ok(pending)
+    }
+
+    /// Reports for one repository are appended under a transaction-scoped
+    /// advisory lock, so two stored at once can't both chain onto the same
+    /// predecessor.
+    async fn store_compliance(&self, status: &ComplianceStatus) -> Result<String> {
+        tracing::debug!("Storing compliance report for {}", status.repo);
+
+        let mut status = status.clone();
+        status.timestamp = status.timestamp.trunc_subsecs(6);
+
+        let mut tx = self.pool.begin().await.map_err(query_failed)?;
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!("compliance_report:{}", repo_target(&status.repo)))
+            .execute(&mut *tx)
+            .await
+            .map_err(query_failed)?;
+
+        let previous: Option<String> = sqlx::query_scalar(
+            "SELECT digest FROM compliance_report \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 \
+             ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(&status.repo.platform)
+        .bind(&status.repo.owner)
+        .bind(&status.repo.repo)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(query_failed)?;
+
+        let report = ComplianceReport::from_status(&status)?.chained(previous)?;
+        let digest = report.digest.clone().unwrap_or_default();
+
+        sqlx::query(
+            "INSERT INTO compliance_report \
+                (digest, previous_digest, platform, owner, repo, tier, score, checks, standard, canonical, evidence, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        )
+        .bind(&digest)
+        .bind(&report.previous_digest)
+        .bind(&report.platform)
+        .bind(&report.owner)
+        .bind(&report.repo)
+        .bind(&report.tier)
+        .bind(report.score)
+        .bind(&report.checks)
+        .bind(Json(&report.standard))
+        .bind(report.canonical.as_ref().map(Json))
+        .bind(Json(&report.evidence))
+        .bind(report.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| DbError::sqlx("Postgres insert failed", e))?;
+
+        sqlx::query("UPDATE repository SET last_scanned_at = $4 WHERE platform = $1 AND owner = $2 AND name = $3")
+            .bind(&report.platform)
+            .bind(&report.owner)
+            .bind(&report.repo)
+            .bind(report.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(query_failed)?;
+
+        sqlx::query("DELETE FROM org_summary WHERE platform = $1 AND ($2 = owner OR starts_with($2, owner || '/'))")
+            .bind(&report.platform)
+            .bind(&report.owner)
+            .execute(&mut *tx)
+            .await
+            .map_err(query_failed)?;
+
+        tx.commit().await.map_err(query_failed)?;
+
+        let id = format!("compliance_report:{}", digest);
+        tracing::debug!("Stored compliance report with ID: {}", id);
+        Ok(id)
+    }
+
+    async fn verify_report_chain(&self, repo: &RepoRef) -> Result<ChainVerification> {
+        let mut verification = ChainVerification::new(repo);
+        let mut after: Option<chrono::DateTime<chrono::Utc>> = None;
+
+        loop {
+            let rows: Vec<ReportRow> = sqlx::query_as(&format!(
+                "SELECT {} FROM compliance_report \
+                 WHERE platform = $1 AND owner = $2 AND repo = $3 \
+                    AND ($4::timestamptz IS NULL OR created_at > $4) \
+                 ORDER BY created_at LIMIT $5",
+                REPORT_FIELDS
+            ))
+            .bind(&repo.platform)
+            .bind(&repo.owner)
+            .bind(&repo.repo)
+            .bind(after)
+            .bind(i64::from(CHAIN_PAGE))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(query_failed)?;
+            let page_len = rows.len();
+
+            for row in rows {
+                after = Some(row.created_at);
+                verification.check(&ComplianceReport::from(row))?;
+            }
+
+            if page_len < CHAIN_PAGE as usize {
+                return Ok(verification);
+            }
+        }
+    }
+
+    async fn get_compliance_history(
+        &self,
+        platform: &str,
+        owner: &str,
+        repo: &str,
+        limit: u32,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<HistoryPage> {
+        let limit = limit.clamp(1, MAX_HISTORY_PAGE);
+
+        // One extra row tells us whether there is another page
+        let mut rows: Vec<ReportRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM compliance_report \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 AND deleted_at IS NULL \
+                AND ($4::timestamptz IS NULL OR created_at < $4) \
+             ORDER BY created_at DESC LIMIT $5",
+            REPORT_FIELDS
+        ))
+        .bind(platform)
+        .bind(owner)
+        .bind(repo)
+        .bind(before)
+        .bind(i64::from(limit) + 1)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        let more = rows.len() > limit as usize;
+        rows.truncate(limit as usize);
+        let reports: Vec<ComplianceStatus> = rows
+            .into_iter()
+            .map(|row| ComplianceReport::from(row).into_status())
+            .collect();
+        let next = if more { reports.last().map(|status| status.timestamp) } else { None };
+
+        Ok(HistoryPage { reports, next })
+    }
+
+    /// Postgres rounds timestamps to the microsecond, so this compares with
+    /// `as_of` directly rather than paging from just after it
+    async fn get_compliance_as_of(
+        &self,
+        platform: &str,
+        owner: &str,
+        repo: &str,
+        as_of: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<ComplianceStatus>> {
+        let row: Option<ReportRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM compliance_report \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 AND deleted_at IS NULL AND created_at <= $4 \
+             ORDER BY created_at DESC LIMIT 1",
+            REPORT_FIELDS
+        ))
+        .bind(platform)
+        .bind(owner)
+        .bind(repo)
+        .bind(as_of)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        Ok(row.map(|row| ComplianceReport::from(row).into_status()))
+    }
+
+    async fn store_webhook_event(&self, event: &WebhookEvent) -> Result<String> {
+        tracing::debug!("Archiving webhook event: {}/{}", event.platform, event.event_type);
+
+        let id = insert_webhook_event(&self.pool, event).await?;
+        Ok(record_id("webhook_event", id))
+    }
+
+    async fn get_webhook_event(&self, event_id: &str) -> Result<Option<ArchivedWebhook>> {
+        let Some(id) = record_key("webhook_event", event_id) else {
+            return Ok(None);
+        };
+
+        let row: Option<WebhookRow> = sqlx::query_as("SELECT * FROM webhook_event WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(query_failed)?;
+
+        Ok(row.map(ArchivedWebhook::from))
+    }
+
+    async fn mark_event_processed(&self, event_id: &str) -> Result<()> {
+        let id = record_key("webhook_event", event_id)
+            .ok_or_else(|| RsrError::Config(format!("Invalid webhook event ID {}", event_id)))?;
+
+        sqlx::query("UPDATE webhook_event SET processed = true, error = NULL WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("Postgres update failed", e))?;
+
+        Ok(())
+    }
+
+    async fn mark_event_failed(&self, event_id: &str, error: &str) -> Result<()> {
+        let id = record_key("webhook_event", event_id)
+            .ok_or_else(|| RsrError::Config(format!("Invalid webhook event ID {}", event_id)))?;
+
+        sqlx::query("UPDATE webhook_event SET processed = false, error = $2 WHERE id = $1")
+            .bind(id)
+            .bind(error)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("Postgres update failed", e))?;
+
+        Ok(())
+    }
+
+    async fn get_failed_events(&self, limit: u32) -> Result<Vec<ArchivedWebhook>> {
+        let rows: Vec<WebhookRow> = sqlx::query_as(
+            "SELECT * FROM webhook_event \
+             WHERE processed = false AND error IS NOT NULL AND verification = 'verified' \
+             ORDER BY received_at DESC LIMIT $1",
+        )
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        Ok(rows.into_iter().map(ArchivedWebhook::from).collect())
+    }
+
+    async fn quarantine_event(&self, event_id: &str, error: &str) -> Result<()> {
+        let id = record_key("webhook_event", event_id)
+            .ok_or_else(|| RsrError::Config(format!("Invalid webhook event ID {}", event_id)))?;
+
+        sqlx::query("UPDATE webhook_event SET processed = false, error = $2, quarantined_by = $3 WHERE id = $1")
+            .bind(id)
+            .bind(error)
+            .bind(ENGINE_VERSION)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("Postgres update failed", e))?;
+
+        Ok(())
+    }
+
+    async fn release_quarantine(&self, event_id: &str) -> Result<()> {
+        let id = record_key("webhook_event", event_id)
+            .ok_or_else(|| RsrError::Config(format!("Invalid webhook event ID {}", event_id)))?;
+
+        sqlx::query(
+            "UPDATE webhook_event SET error = NULL, quarantined_by = NULL WHERE id = $1 AND quarantined_by IS NOT NULL",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("Postgres update failed", e))?;
+
+        Ok(())
+    }
+
+    async fn get_quarantined_events(&self, limit: u32) -> Result<Vec<ArchivedWebhook>> {
+        let rows: Vec<WebhookRow> = sqlx::query_as(
+            "SELECT * FROM webhook_event WHERE quarantined_by IS NOT NULL AND processed = false \
+             ORDER BY received_at DESC LIMIT $1",
+        )
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        Ok(rows.into_iter().map(ArchivedWebhook::from).collect())
+    }
+
+    async fn store_webhook_event_with_job(
+        &self,
+        event: &WebhookEvent,
+        queue: &str,
+        job: &BuildJob,
+    ) -> Result<(String, OutboxEntry)> {
+        tracing::debug!("Archiving webhook event with outbox job: {}/{}", event.platform, event.event_type);
+
+        let failed = |e: sqlx::Error| DbError::sqlx("Postgres insert failed", e);
+        let mut tx = self.pool.begin().await.map_err(failed)?;
+
+        let event_id = record_id("webhook_event", insert_webhook_event(&mut *tx, event).await?);
+        let entry = OutboxEntry::new(queue, job(&event_id)?);
+        let row = OutboxRow::from_entry(&entry)?;
+
+        sqlx::query("INSERT INTO outbox (job_id, queue, job, attempts, created_at) VALUES ($1, $2, $3, 0, $4)")
+            .bind(&entry.job.id)
+            .bind(&row.queue)
+            .bind(&row.job)
+            .bind(entry.created_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+
+        tx.commit().await.map_err(failed)?;
+        Ok((event_id, entry))
+    }
+
+    async fn pending_outbox(&self, limit: u32) -> Result<Vec<OutboxEntry>> {
+        let rows: Vec<(String, String, i32, Option<String>, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(
+            "SELECT queue, job, attempts, last_error, created_at FROM outbox ORDER BY created_at LIMIT $1",
+        )
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(queue, job, attempts, last_error, created_at)| {
+                OutboxEntry::from(OutboxRow {
+                    queue,
+                    job,
+                    attempts: attempts as u32,
+                    last_error,
+                    created_at,
+                })
+            })
+            .collect())
+    }
+
+    async fn remove_outbox_entry(&self, job_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM outbox WHERE job_id = $1")
+            .bind(job_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("Postgres delete failed", e))?;
+
+        Ok(())
+    }
+
+    async fn mark_outbox_failed(&self, job_id: &str, error: &str) -> Result<()> {
+        sqlx::query("UPDATE outbox SET attempts = attempts + 1, last_error = $2 WHERE job_id = $1")
+            .bind(job_id)
+            .bind(error)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("Postgres update failed", e))?;
+
+        Ok(())
+    }
+
+    async fn transfer_repository(&self, from: &RepoRef, to: &RepoRef) -> Result<()> {
+        tracing::info!("Transferring stored data from {} to {}", from, to);
+
+        let failed = |e: sqlx::Error| DbError::sqlx("Postgres transfer failed", e);
+        let mut tx = self.pool.begin().await.map_err(failed)?;
+
+        sqlx::query("UPDATE compliance_report SET owner = $4, repo = $5 WHERE platform = $1 AND owner = $2 AND repo = $3")
+            .bind(&from.platform)
+            .bind(&from.owner)
+            .bind(&from.repo)
+            .bind(&to.owner)
+            .bind(&to.repo)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+
+        sqlx::query("UPDATE report_annotation SET owner = $4, repo = $5 WHERE platform = $1 AND owner = $2 AND repo = $3")
+            .bind(&from.platform)
+            .bind(&from.owner)
+            .bind(&from.repo)
+            .bind(&to.owner)
+            .bind(&to.repo)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+
+        sqlx::query("UPDATE repository SET owner = $4, name = $5 WHERE platform = $1 AND owner = $2 AND name = $3")
+            .bind(&from.platform)
+            .bind(&from.owner)
+            .bind(&from.repo)
+            .bind(&to.owner)
+            .bind(&to.repo)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+
+        sqlx::query("DELETE FROM ci_run WHERE platform = $1 AND owner = $2 AND repo = $3")
+            .bind(&to.platform)
+            .bind(&to.owner)
+            .bind(&to.repo)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+
+        sqlx::query("UPDATE ci_run SET owner = $4, repo = $5 WHERE platform = $1 AND owner = $2 AND repo = $3")
+            .bind(&from.platform)
+            .bind(&from.owner)
+            .bind(&from.repo)
+            .bind(&to.owner)
+            .bind(&to.repo)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+
+        sqlx::query("UPDATE repo_redirect SET to_owner = $4, to_repo = $5 WHERE platform = $1 AND to_owner = $2 AND to_repo = $3")
+            .bind(&from.platform)
+            .bind(&from.owner)
+            .bind(&from.repo)
+            .bind(&to.owner)
+            .bind(&to.repo)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+
+        sqlx::query("DELETE FROM repo_redirect WHERE platform = $1 AND from_owner = $2 AND from_repo = $3")
+            .bind(&to.platform)
+            .bind(&to.owner)
+            .bind(&to.repo)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+
+        sqlx::query(
+            "INSERT INTO repo_redirect (platform, from_owner, from_repo, to_owner, to_repo) \
+             VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (platform, from_owner, from_repo) \
+             DO UPDATE SET to_owner = EXCLUDED.to_owner, to_repo = EXCLUDED.to_repo, created_at = now()",
+        )
+        .bind(&from.platform)
+        .bind(&from.owner)
+        .bind(&from.repo)
+        .bind(&to.owner)
+        .bind(&to.repo)
+        .execute(&mut *tx)
+        .await
+        .map_err(failed)?;
+
+        sqlx::query(
+            "DELETE FROM org_summary WHERE platform = $1 \
+             AND ($2 = owner OR starts_with($2, owner || '/') OR $3 = owner OR starts_with($3, owner || '/'))",
+        )
+        .bind(&from.platform)
+        .bind(&from.owner)
+        .bind(&to.owner)
+        .execute(&mut *tx)
+        .await
+        .map_err(failed)?;
+
+        tx.commit().await.map_err(failed)?;
+
+        Ok(())
+    }
+
+    async fn resolve_redirect(&self, platform: &str, owner: &str, repo: &str) -> Result<Option<RepoRef>> {
+        let redirect: Option<(String, String)> = sqlx::query_as(
+            "SELECT to_owner, to_repo FROM repo_redirect WHERE platform = $1 AND from_owner = $2 AND from_repo = $3",
+        )
+        .bind(platform)
+        .bind(owner)
+        .bind(repo)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        Ok(redirect.map(|(to_owner, to_repo)| RepoRef::new(platform, to_owner, to_repo)))
+    }
+
+    async fn record_ci_run(&self, repo: &RepoRef, run: &CiRun) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO ci_run (platform, owner, repo, branch, workflow, commit_sha, conclusion, completed_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8) \
+             ON CONFLICT (platform, owner, repo, branch, workflow) \
+             DO UPDATE SET commit_sha = EXCLUDED.commit_sha, conclusion = EXCLUDED.conclusion, \
+                completed_at = EXCLUDED.completed_at \
+             WHERE ci_run.completed_at <= EXCLUDED.completed_at",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(&run.branch)
+        .bind(&run.workflow)
+        .bind(&run.commit_sha)
+        .bind(text(run.conclusion)?)
+        .bind(run.completed_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("Postgres upsert failed", e))?;
+
+        Ok(())
+    }
+
+    async fn latest_ci_runs(&self, repo: &RepoRef, branch: &str) -> Result<Vec<CiRun>> {
+        let rows: Vec<CiRunRow> = sqlx::query_as(
+            "SELECT workflow, branch, commit_sha, conclusion, completed_at FROM ci_run \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 AND branch = $4 ORDER BY workflow",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(branch)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        Ok(rows.into_iter().filter_map(CiRunRow::into_run).collect())
+    }
+
+    async fn annotate_report(
+        &self,
+        repo: &RepoRef,
+        report_at: chrono::DateTime<chrono::Utc>,
+        annotation: &Annotation,
+    ) -> Result<String> {
+        annotation.validate()?;
+
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO report_annotation \
+                (platform, owner, repo, report_at, kind, check_id, message, reference, author, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) \
+             RETURNING id",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(report_at)
+        .bind(text(annotation.kind)?)
+        .bind(&annotation.check_id)
+        .bind(&annotation.message)
+        .bind(&annotation.reference)
+        .bind(&annotation.author)
+        .bind(annotation.created_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("Postgres insert failed", e))?;
+
+        Ok(record_id("report_annotation", id))
+    }
+
+    async fn get_annotations(&self, repo: &RepoRef, report_at: chrono::DateTime<chrono::Utc>) -> Result<Vec<Annotation>> {
+        let rows: Vec<AnnotationRow> = sqlx::query_as(
+            "SELECT id, kind, check_id, message, reference, author, created_at FROM report_annotation \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 AND report_at = $4 \
+             ORDER BY created_at",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(report_at)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Annotation {
+                id: Some(record_id("report_annotation", row.id)),
+                kind: parse_text(&row.kind).unwrap_or(AnnotationKind::Note),
+                check_id: row.check_id,
+                message: row.message,
+                reference: row.reference,
+                author: row.author,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+
+    async fn remove_annotation(&self, annotation_id: &str) -> Result<bool> {
+        let Some(id) = record_key("report_annotation", annotation_id) else {
+            return Ok(false);
+        };
+
+        let removed = sqlx::query("DELETE FROM report_annotation WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("Postgres delete failed", e))?;
+        Ok(removed.rows_affected() > 0)
+    }
+
+    async fn record_audit(
+        &self,
+        actor: &str,
+        action: AuditAction,
+        target: &str,
+        details: serde_json::Value,
+    ) -> Result<String> {
+        let details = match details {
+            serde_json::Value::Null => serde_json::json!({}),
+            details => details,
+        };
+
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO audit_event (actor, action, target, details) VALUES ($1, $2, $3, $4) RETURNING id",
+        )
+        .bind(actor)
+        .bind(text(action)?)
+        .bind(target)
+        .bind(details)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("Postgres insert failed", e))?;
+
+        Ok(record_id("audit_event", id))
+    }
+
+    async fn get_audit_events(
+        &self,
+        query: &AuditQuery,
+        limit: u32,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<AuditPage> {
+        let limit = limit.clamp(1, MAX_AUDIT_PAGE);
+
+        let rows: Vec<AuditRow> = sqlx::query_as(
+            "SELECT actor, action, target, details, created_at FROM audit_event \
+             WHERE ($1::text IS NULL OR target = $1) \
+                AND ($2::text IS NULL OR actor = $2) \
+                AND ($3::timestamptz IS NULL OR created_at >= $3) \
+                AND ($4::timestamptz IS NULL OR created_at <= $4) \
+                AND ($5::timestamptz IS NULL OR created_at < $5) \
+             ORDER BY created_at DESC LIMIT $6",
+        )
+        .bind(query.repo.as_ref().map(repo_target))
+        .bind(&query.actor)
+        .bind(query.since)
+        .bind(query.until)
+        .bind(before)
+        .bind(i64::from(limit) + 1)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        let mut events = rows
+            .into_iter()
+            .map(|row| {
+                let action = parse_text(&row.action)
+                    .ok_or_else(|| DbError::Serialization(format!("Unknown audit action {}", row.action)))?;
+                Ok(AuditEvent {
+                    actor: row.actor,
+                    action,
+                    target: row.target,
+                    details: row.details,
+                    created_at: row.created_at,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let more = events.len() > limit as usize;
+        events.truncate(limit as usize);
+        let next = if more { events.last().map(|event| event.created_at) } else { None };
+
+        Ok(AuditPage { events, next })
+    }
+
+    async fn register_repository(&self, repo: &RepoRef, adapter: Option<&str>) -> Result<RegisteredRepository> {
+        tracing::info!("Registering {}", repo);
+
+        let row: RegistryRow = sqlx::query_as(
+            "INSERT INTO repository (platform, owner, name, adapter) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (platform, owner, name) DO UPDATE SET \
+                adapter = COALESCE(EXCLUDED.adapter, repository.adapter), active = true, \
+                deactivated_at = NULL, deleted_at = NULL \
+             RETURNING *",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(adapter)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("Postgres upsert failed", e))?;
+
+        Ok(row.into())
+    }
+
+    async fn deactivate_repository(&self, repo: &RepoRef) -> Result<bool> {
+        tracing::info!("Deactivating {}", repo);
+
+        let deactivated = sqlx::query(
+            "UPDATE repository SET active = false, deactivated_at = now() \
+             WHERE platform = $1 AND owner = $2 AND name = $3 AND active",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("Postgres update failed", e))?;
+
+        Ok(deactivated.rows_affected() > 0)
+    }
+
+    async fn mark_repository_deleted(&self, repo: &RepoRef) -> Result<()> {
+        tracing::info!("Marking {} as deleted", repo);
+
+        sqlx::query("UPDATE repository SET deleted_at = now() WHERE platform = $1 AND owner = $2 AND name = $3")
+            .bind(&repo.platform)
+            .bind(&repo.owner)
+            .bind(&repo.repo)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("Postgres update failed", e))?;
+
+        Ok(())
+    }
+
+    async fn list_repositories(&self, platform: Option<&str>, owner: Option<&str>) -> Result<Vec<RegisteredRepository>> {
+        let rows: Vec<RegistryRow> = sqlx::query_as(
+            "SELECT * FROM repository \
+             WHERE active AND deleted_at IS NULL \
+                AND ($1::text IS NULL OR platform = $1) \
+                AND ($2::text IS NULL OR owner = $2 OR starts_with(owner, $2 || '/')) \
+             ORDER BY platform, owner, name",
+        )
+        .bind(platform)
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        Ok(rows.into_iter().map(RegisteredRepository::from).collect())
+    }
+
+    async fn get_repository(&self, repo: &RepoRef) -> Result<Option<RegisteredRepository>> {
+        let row: Option<RegistryRow> =
+            sqlx::query_as("SELECT * FROM repository WHERE platform = $1 AND owner = $2 AND name = $3")
+                .bind(&repo.platform)
+                .bind(&repo.owner)
+                .bind(&repo.repo)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(query_failed)?;
+
+        Ok(row.map(RegisteredRepository::from))
+    }
+
+    async fn get_compliance_trend(
+        &self,
+        platform: &str,
+        owner: &str,
+        repo: &str,
+        window: TrendWindow,
+    ) -> Result<ComplianceTrend> {
+        if window.since >= window.until {
+            return Err(RsrError::Config("Trend window must start before it ends".to_string()));
+        }
+        // `date_trunc` weeks start on Monday
+        let unit = match window.interval {
+            TrendInterval::Day => "day",
+            TrendInterval::Week => "week",
+        };
+
+        let rows: Vec<BucketRow> = sqlx::query_as(
+            "SELECT date_trunc($6, created_at, 'UTC') AS bucket, count(*) AS reports, \
+                min(score) AS min_score, max(score) AS max_score, avg(score) AS avg_score, \
+                (array_agg(tier ORDER BY created_at))[1] AS opening_tier, \
+                (array_agg(tier ORDER BY created_at DESC))[1] AS closing_tier \
+             FROM compliance_report \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 AND deleted_at IS NULL \
+                AND created_at >= $4 AND created_at < $5 \
+             GROUP BY bucket \
+             ORDER BY bucket",
+        )
+        .bind(platform)
+        .bind(owner)
+        .bind(repo)
+        .bind(window.since)
+        .bind(window.until)
+        .bind(unit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        let buckets: Vec<TrendBucket> = rows
+            .into_iter()
+            .map(|row| TrendBucket {
+                start: row.bucket,
+                reports: row.reports as usize,
+                min_score: row.min_score,
+                max_score: row.max_score,
+                avg_score: row.avg_score as f32,
+                opening_tier: stored_tier(&row.opening_tier),
+                closing_tier: stored_tier(&row.closing_tier),
+            })
+            .collect();
+
+        Ok(ComplianceTrend {
+            window,
+            transitions: trends::transitions(&buckets),
+            buckets,
+        })
+    }
+
+    async fn get_org_summary(&self, platform: &str, owner: &str) -> Result<OrgSummary> {
+        let cached: std::result::Result<Option<Json<OrgSummary>>, sqlx::Error> = sqlx::query_scalar(
+            "SELECT summary FROM org_summary \
+             WHERE platform = $1 AND owner = $2 AND computed_at > now() - make_interval(secs => $3)",
+        )
+        .bind(platform)
+        .bind(owner)
+        .bind(SUMMARY_TTL_SECS as f64)
+        .fetch_optional(&self.pool)
+        .await;
+        match cached {
+            Ok(Some(summary)) => return Ok(summary.0),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to read cached summary for {}:{}: {}", platform, owner, e),
+        }
+
+        // Latest live report of each repository that is still active
+        let rows: Vec<StandingRow> = sqlx::query_as(
+            "SELECT DISTINCT ON (report.owner, report.repo) report.owner, report.repo, report.tier, report.score, \
+                report.created_at \
+             FROM compliance_report report \
+             WHERE report.platform = $1 AND report.deleted_at IS NULL \
+                AND (report.owner = $2 OR starts_with(report.owner, $2 || '/')) \
+                AND NOT EXISTS ( \
+                    SELECT 1 FROM repository \
+                    WHERE repository.platform = report.platform AND repository.owner = report.owner \
+                        AND repository.name = report.repo \
+                        AND (NOT repository.active OR repository.deleted_at IS NOT NULL) \
+                ) \
+             ORDER BY report.owner, report.repo, report.created_at DESC",
+        )
+        .bind(platform)
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        let standings = rows
+            .into_iter()
+            .map(|row| RepoStanding {
+                repo: RepoRef::new(platform, &row.owner, &row.repo),
+                tier: stored_tier(&row.tier),
+                score: row.score,
+                scanned_at: row.created_at,
+            })
+            .collect();
+        let summary = OrgSummary::from_standings(platform, owner, standings, certification_validity());
+
+        // Caching is best effort; the summary is already computed
+        let cached = sqlx::query(
+            "INSERT INTO org_summary (platform, owner, summary, computed_at) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (platform, owner) DO UPDATE SET summary = EXCLUDED.summary, computed_at = EXCLUDED.computed_at",
+        )
+        .bind(platform)
+        .bind(owner)
+        .bind(Json(&summary))
+        .bind(summary.computed_at)
+        .execute(&self.pool)
+        .await;
+        if let Err(e) = cached {
+            tracing::warn!("Failed to cache summary for {}:{}: {}", platform, owner, e);
+        }
+        Ok(summary)
+    }
+
+    /// Every repository is pruned in one statement: reports are ranked
+    /// newest first among their repository's live reports, and those past
+    /// the count limit (or, with only an age limit, all but the latest) and
+    /// older than the age limit are tombstoned.
+    async fn prune_reports(&self, policy: &RetentionPolicy, dry_run: bool) -> Result<PruneReport> {
+        let started_at = chrono::Utc::now();
+        let mut report = PruneReport {
+            started_at: Some(started_at),
+            dry_run,
+            ..Default::default()
+        };
+        if policy.is_unlimited() {
+            return Ok(report);
+        }
+
+        let older_than = policy
+            .max_age_days
+            .map(|days| started_at - chrono::Duration::days(i64::from(days)));
+        let keep = policy.max_reports.unwrap_or(1);
+
+        let scanned: i64 = sqlx::query_scalar(
+            "SELECT count(*) FROM (SELECT DISTINCT platform, owner, repo FROM compliance_report WHERE deleted_at IS NULL) live",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(query_failed)?;
+        report.scanned = scanned as usize;
+
+        let prunable = "WITH ranked AS ( \
+                SELECT digest, badge_issued_at, created_at, \
+                    row_number() OVER (PARTITION BY platform, owner, repo ORDER BY created_at DESC) AS rank \
+                FROM compliance_report WHERE deleted_at IS NULL \
+             ), prunable AS ( \
+                SELECT digest FROM ranked \
+                WHERE rank > $1 AND badge_issued_at IS NULL \
+                    AND ($2::timestamptz IS NULL OR created_at < $2) \
+             ) ";
+        let statement = if dry_run {
+            format!(
+                "{}SELECT platform, owner, repo FROM compliance_report WHERE digest IN (SELECT digest FROM prunable)",
+                prunable
+            )
+        } else {
+            format!(
+                "{}UPDATE compliance_report SET deleted_at = $3 WHERE digest IN (SELECT digest FROM prunable) \
+                 RETURNING platform, owner, repo",
+                prunable
+            )
+        };
+        let mut query = sqlx::query_as::<_, RepoRow>(&statement).bind(i64::from(keep)).bind(older_than);
+        if !dry_run {
+            query = query.bind(started_at);
+        }
+        let pruned: Vec<RepoRow> = query
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("Postgres update failed", e))?;
+
+        report.tombstoned = pruned.len();
+        report.repositories = pruned
+            .iter()
+            .map(|row| (&row.platform, &row.owner, &row.repo))
+            .collect::<HashSet<_>>()
+            .len();
+
+        tracing::info!(
+            "Report pruning{} tombstoned {} reports across {} repositories",
+            if dry_run { " (dry run)" } else { "" },
+            report.tombstoned,
+            report.repositories
+        );
+        Ok(report)
+    }
+
+    async fn mark_badge_issued(&self, repo: &RepoRef, at: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        sqlx::query(
+            "UPDATE compliance_report SET badge_issued_at = now() \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 AND created_at = $4",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("Postgres update failed", e))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+//! Quarantine for webhooks that failed to parse
+//!
+//! A verified delivery the adapter couldn't parse, such as an event type
+//! this engine doesn't support yet, is archived with the parse error and the
+//! version of the engine that rejected it. Once an upgrade fixes the parser,
+//! the quarantined payloads can be replayed; those that still fail stay
+//! quarantined under the new version.
+
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::{ArchivedRow, ArchivedWebhook, SurrealPool};
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+#[cfg(feature = "documents-surrealdb")]
+use crate::Result;
+use serde::Serialize;
+
+/// Version of this engine, recorded against the payloads it quarantines
+pub const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Most quarantined webhooks replayed in one pass
+pub const MAX_REPLAY_BATCH: u32 = 500;
+
+/// Outcome of replaying quarantined webhooks
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QuarantineReplay {
+    /// Quarantined webhooks looked at
+    pub scanned: usize,
+    /// Parsed and queued again
+    pub replayed: usize,
+    /// Still failing, now quarantined under this version
+    pub still_failing: usize,
+    /// Quarantined by this version already, so not retried
+    pub skipped: usize,
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealPool {
+    /// Quarantine an archived webhook whose payload failed to parse
+    pub async fn quarantine_event(&self, event_id: &str, error: &str) -> Result<()> {
+        self.client()
+            .query("UPDATE type::record($id) SET processed = false, error = $error, quarantined_by = $version")
+            .bind(("id", event_id.to_string()))
+            .bind(("error", error.to_string()))
+            .bind(("version", ENGINE_VERSION))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| DbError::surreal("SurrealDB update failed", e))?;
+
+        Ok(())
+    }
+
+    /// Take an archived webhook out of quarantine once its payload parses
+    pub async fn release_quarantine(&self, event_id: &str) -> Result<()> {
+        self.client()
+            .query("UPDATE type::record($id) SET error = NONE, quarantined_by = NONE WHERE quarantined_by != NONE")
+            .bind(("id", event_id.to_string()))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| DbError::surreal("SurrealDB update failed", e))?;
+
+        Ok(())
+    }
+
+    /// Quarantined webhooks, newest first
+    pub async fn get_quarantined_events(&self, limit: u32) -> Result<Vec<ArchivedWebhook>> {
+        let mut result = self
+            .client()
+            .query(
+                "SELECT * FROM webhook_event WHERE quarantined_by != NONE AND processed = false \
+                 ORDER BY received_at DESC LIMIT $limit",
+            )
+            .bind(("limit", limit))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let events: Vec<ArchivedRow> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+        Ok(events.into_iter().map(ArchivedWebhook::from).collect())
+    }
+}
@@ -0,0 +1,1109 @@
+//! Reliable job queues on DragonflyDB
+//!
+//! Jobs are delivered at least once. A consumer atomically moves each job
+//! from the queue onto a processing list and leases it for the visibility
+//! timeout. Acknowledged jobs are removed. Failed jobs, and jobs whose lease
+//! ran out because their worker died, go back on the queue with one more
+//! attempt counted; after `max_attempts` they are moved to a dead-letter list
+//! and stay there until an operator requeues them. A failed job waits out a
+//! backoff that doubles with each attempt before it can be claimed again.
+//!
+//! Jobs carry a priority and a fairness key (the repository). Higher
+//! priorities are always served first. Within a priority, repositories take
+//! turns, so one repository flooding the queue can't starve the others.
+//!
+//! Each job is stored in an envelope naming its type and the schema version
+//! of its payload (see [`JobPayload`]), with the trace context of the request
+//! it was queued for. During a rolling upgrade a worker that claims a job
+//! written in a newer schema than it understands puts it back without
+//! spending an attempt, for an upgraded worker to take; only after
+//! `MAX_DEFERRALS` such returns is it failed like any other job. Envelope
+//! fields a worker doesn't know are kept when it requeues the job.
+//!
+//! Keys for queue `q`:
+//! - `rsr:queue:{q}:jobs:{priority}:{repo}` - a repository's waiting jobs,
+//!   pushed on the left and taken from the right
+//! - `rsr:queue:{q}:ready:{priority}` - round-robin ring of repositories with
+//!   waiting jobs
+//! - `rsr:queue:{q}:waiting` - every waiting job, scored by enqueue time
+//! - `rsr:queue:{q}:signal` - wake-ups for blocked consumers
+//! - `rsr:queue:{q}:processing` - jobs claimed by a consumer
+//! - `rsr:queue:{q}:leases` - visibility deadline of each claimed job
+//! - `rsr:queue:{q}:dead` - dead-lettered jobs, newest first
+//! - `rsr:queue:{q}:delayed` - failed jobs waiting out their retry backoff,
+//!   scored by when they are due
+//! - `rsr:queue:{q}:parked` - jobs held back by a scan quota, one per slot
+//! - `rsr:queue:{q}:relayed:{id}` - jobs relayed from the outbox, kept a
+//!   while so a second relay of the same entry is dropped
+//! - `rsr:queue:{q}` - jobs queued before priorities existed, drained last
+
+use super::cache::{CacheKey, CacheKind};
+#[cfg(feature = "cache-dragonfly")]
+use super::cache::DragonflyPool;
+use super::error::DbError;
+use crate::scheduler::ScanTrigger;
+use crate::Result;
+#[cfg(feature = "cache-dragonfly")]
+use once_cell::sync::Lazy;
+#[cfg(feature = "cache-dragonfly")]
+use redis::AsyncCommands;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "cache-dragonfly")]
+/// Wake-ups kept for blocked consumers; more would only cause empty claims
+const MAX_SIGNALS: isize = 64;
+
+/// Times a job in a newer schema is put back for an upgraded worker before
+/// it is failed instead
+pub const MAX_DEFERRALS: u32 = 20;
+
+/// Longest a failed job waits before its next delivery
+pub const MAX_RETRY_BACKOFF_SECS: u64 = 3600;
+
+#[cfg(feature = "cache-dragonfly")]
+/// Due retries moved back to the queue per claim
+const MAX_PROMOTIONS: isize = 100;
+
+#[cfg(feature = "cache-dragonfly")]
+/// Jobs waiting, on the legacy list, in flight, dead-lettered, delayed and
+/// parked, and the oldest waiting job with its enqueue time
+type PressureCounts = (u64, u64, u64, u64, u64, u64, Vec<(String, i64)>);
+
+/// Add a job to its repository's list, putting the repository in the ring
+/// if it had nothing waiting. Given a relay marker, does nothing if the
+/// marker is already set.
+///
+/// KEYS: repository's jobs, ready ring, waiting set, signal, [relay marker]
+/// ARGV: stored job, repository, enqueue timestamp, signal limit, [marker TTL]
+#[cfg(feature = "cache-dragonfly")]
+static ENQUEUE_JOB: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        if KEYS[5] and not redis.call('SET', KEYS[5], '1', 'NX', 'EX', ARGV[5]) then
+            return 0
+        end
+        redis.call('LPUSH', KEYS[1], ARGV[1])
+        if redis.call('LLEN', KEYS[1]) == 1 then
+            redis.call('LPUSH', KEYS[2], ARGV[2])
+        end
+        redis.call('ZADD', KEYS[3], ARGV[3], ARGV[1])
+        redis.call('LPUSH', KEYS[4], '1')
+        redis.call('LTRIM', KEYS[4], 0, tonumber(ARGV[4]) - 1)
+        return 1
+        "#,
+    )
+});
+
+/// Move a job off a list only if it is still there, so a job can't be
+/// requeued twice by racing workers or reapers. The destination is either
+/// the dead-letter list or, with ARGV[5] = '1', the job's repository list.
+///
+/// KEYS: source list, leases, destination, ready ring, waiting set, signal
+/// ARGV: stored job, replacement, repository, enqueue timestamp, requeue flag, signal limit
+#[cfg(feature = "cache-dragonfly")]
+static MOVE_JOB: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        if redis.call('LREM', KEYS[1], 1, ARGV[1]) == 0 then
+            return 0
+        end
+        redis.call('ZREM', KEYS[2], ARGV[1])
+        redis.call('LPUSH', KEYS[3], ARGV[2])
+        if ARGV[5] == '1' then
+            if redis.call('LLEN', KEYS[3]) == 1 then
+                redis.call('LPUSH', KEYS[4], ARGV[3])
+            end
+            redis.call('ZADD', KEYS[5], ARGV[4], ARGV[2])
+            redis.call('LPUSH', KEYS[6], '1')
+            redis.call('LTRIM', KEYS[6], 0, tonumber(ARGV[6]) - 1)
+        end
+        return 1
+        "#,
+    )
+});
+
+/// Claim up to ARGV[1] jobs, highest priority first, one job per repository
+/// per turn of the ring. Repository lists are found through the rings, so
+/// Dragonfly has to be told the script touches undeclared keys.
+///
+/// KEYS: ready rings (high, normal, low), legacy list, processing, leases, waiting set, signal
+/// ARGV: max jobs, lease deadline, jobs key prefix, priority names (high, normal, low)
+#[cfg(feature = "cache-dragonfly")]
+static CLAIM_JOBS: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"--!df flags=allow-undeclared-keys
+        local max = tonumber(ARGV[1])
+        local claimed = {}
+        for level = 1, 3 do
+            while #claimed < max do
+                local repo = redis.call('RPOP', KEYS[level])
+                if not repo then
+                    break
+                end
+                local jobs = ARGV[3] .. ARGV[3 + level] .. ':' .. repo
+                local job = redis.call('RPOP', jobs)
+                if job then
+                    table.insert(claimed, job)
+                end
+                if redis.call('LLEN', jobs) > 0 then
+                    redis.call('LPUSH', KEYS[level], repo)
+                end
+            end
+        end
+        while #claimed < max do
+            local job = redis.call('RPOP', KEYS[4])
+            if not job then
+                break
+            end
+            table.insert(claimed, job)
+        end
+        for _, job in ipairs(claimed) do
+            redis.call('LPUSH', KEYS[5], job)
+            redis.call('ZADD', KEYS[6], ARGV[2], job)
+            redis.call('ZREM', KEYS[7], job)
+        end
+        if #claimed < max then
+            redis.call('DEL', KEYS[8])
+        end
+        return claimed
+        "#,
+    )
+});
+
+/// Move a claimed job to the delayed set to wait out its backoff, only if it
+/// is still claimed.
+///
+/// KEYS: processing, leases, delayed set
+/// ARGV: stored job, replacement, due timestamp
+#[cfg(feature = "cache-dragonfly")]
+static DELAY_JOB: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        if redis.call('LREM', KEYS[1], 1, ARGV[1]) == 0 then
+            return 0
+        end
+        redis.call('ZREM', KEYS[2], ARGV[1])
+        redis.call('ZADD', KEYS[3], ARGV[3], ARGV[2])
+        return 1
+        "#,
+    )
+});
+
+/// Put a retry whose backoff has passed on its repository's list, unless
+/// another consumer already did.
+///
+/// KEYS: delayed set, repository's jobs, ready ring, waiting set, signal
+/// ARGV: stored job, repository, enqueue timestamp, signal limit
+#[cfg(feature = "cache-dragonfly")]
+static PROMOTE_JOB: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        if redis.call('ZREM', KEYS[1], ARGV[1]) == 0 then
+            return 0
+        end
+        redis.call('LPUSH', KEYS[2], ARGV[1])
+        if redis.call('LLEN', KEYS[2]) == 1 then
+            redis.call('LPUSH', KEYS[3], ARGV[2])
+        end
+        redis.call('ZADD', KEYS[4], ARGV[3], ARGV[1])
+        redis.call('LPUSH', KEYS[5], '1')
+        redis.call('LTRIM', KEYS[5], 0, tonumber(ARGV[4]) - 1)
+        return 1
+        "#,
+    )
+});
+
+/// Put a parked job on its repository's list, unless it was replaced since
+/// it was read.
+///
+/// KEYS: parked hash, repository's jobs, ready ring, waiting set, signal
+/// ARGV: slot, parked job, stored job, repository, enqueue timestamp, signal limit
+#[cfg(feature = "cache-dragonfly")]
+static UNPARK_JOB: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        if redis.call('HGET', KEYS[1], ARGV[1]) ~= ARGV[2] then
+            return 0
+        end
+        redis.call('HDEL', KEYS[1], ARGV[1])
+        redis.call('LPUSH', KEYS[2], ARGV[3])
+        if redis.call('LLEN', KEYS[2]) == 1 then
+            redis.call('LPUSH', KEYS[3], ARGV[4])
+        end
+        redis.call('ZADD', KEYS[4], ARGV[5], ARGV[3])
+        redis.call('LPUSH', KEYS[5], '1')
+        redis.call('LTRIM', KEYS[5], 0, tonumber(ARGV[6]) - 1)
+        return 1
+        "#,
+    )
+});
+
+/// Distinguishes jobs enqueued in the same nanosecond
+static JOB_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Redelivery settings shared by a queue's consumers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeliveryPolicy {
+    /// How long a claimed job may run before it is handed to another worker
+    pub visibility_timeout_secs: u64,
+    /// Deliveries before a job is dead-lettered
+    pub max_attempts: u32,
+    /// Wait before the first retry of a failed job, doubling with each
+    /// further attempt up to [`MAX_RETRY_BACKOFF_SECS`]; 0 retries at once
+    pub retry_backoff_secs: u64,
+}
+
+impl DeliveryPolicy {
+    /// Seconds a job that has failed `attempts` times waits before its next
+    /// delivery
+    pub fn retry_delay_secs(&self, attempts: u32) -> u64 {
+        let doublings = attempts.saturating_sub(1).min(32);
+        self.retry_backoff_secs.saturating_mul(1 << doublings).min(MAX_RETRY_BACKOFF_SECS)
+    }
+}
+
+impl Default for DeliveryPolicy {
+    fn default() -> Self {
+        Self {
+            visibility_timeout_secs: 300,
+            max_attempts: 5,
+            retry_backoff_secs: 10,
+        }
+    }
+}
+
+/// Order in which waiting jobs are served
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    /// Webhook-triggered and manual work
+    High,
+    /// Scheduled rescans
+    #[default]
+    Normal,
+    /// Backfills
+    Low,
+}
+
+impl JobPriority {
+    /// Highest first
+    pub const ALL: [JobPriority; 3] = [Self::High, Self::Normal, Self::Low];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::High => "high",
+            Self::Normal => "normal",
+            Self::Low => "low",
+        }
+    }
+}
+
+impl From<ScanTrigger> for JobPriority {
+    fn from(trigger: ScanTrigger) -> Self {
+        match trigger {
+            ScanTrigger::Webhook | ScanTrigger::Manual => Self::High,
+            ScanTrigger::Scheduled => Self::Normal,
+            ScanTrigger::Backfill => Self::Low,
+        }
+    }
+}
+
+/// A payload placed on a queue
+pub trait JobPayload: Serialize + DeserializeOwned {
+    /// Job type recorded on the envelope, e.g. `event`
+    const KIND: &'static str;
+    /// Version of the payload format. Bump it when workers running the
+    /// previous version can't handle the new form; fields added with a serde
+    /// default don't need a bump.
+    const SCHEMA_VERSION: u32;
+}
+
+/// W3C trace context of the request a job was queued for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceContext {
+    pub traceparent: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracestate: Option<String>,
+}
+
+impl TraceContext {
+    /// Trace context from `traceparent` and `tracestate` header values, if
+    /// `traceparent` is well formed (`version-traceid-parentid-flags`)
+    pub fn parse(traceparent: &str, tracestate: Option<&str>) -> Option<Self> {
+        let traceparent = traceparent.trim();
+        let parts: Vec<&str> = traceparent.split('-').collect();
+        let well_formed = parts.len() >= 4
+            && [2, 32, 16, 2].iter().zip(&parts).all(|(&len, part)| {
+                part.len() == len && part.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+            })
+            && parts[1].bytes().any(|b| b != b'0');
+        well_formed.then(|| Self {
+            traceparent: traceparent.to_string(),
+            tracestate: tracestate.map(str::trim).filter(|state| !state.is_empty()).map(str::to_string),
+        })
+    }
+
+    /// The 32 hex digit trace id
+    pub fn trace_id(&self) -> &str {
+        self.traceparent.split('-').nth(1).unwrap_or_default()
+    }
+}
+
+/// A job to enqueue
+#[derive(Debug, Clone)]
+pub struct NewJob {
+    kind: String,
+    schema_version: u32,
+    payload: String,
+    priority: JobPriority,
+    repo: String,
+    trace: Option<TraceContext>,
+}
+
+impl NewJob {
+    /// `repo` is the fairness key: jobs of one repository take turns with
+    /// others at the same priority
+    pub fn new<T: JobPayload>(payload: &T, priority: JobPriority, repo: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            kind: T::KIND.to_string(),
+            schema_version: T::SCHEMA_VERSION,
+            payload: serde_json::to_string(payload)?,
+            priority,
+            repo: repo.into(),
+            trace: None,
+        })
+    }
+
+    pub fn with_trace(mut self, trace: Option<TraceContext>) -> Self {
+        self.trace = trace;
+        self
+    }
+}
+
+/// Whether a worker can handle a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    Compatible,
+    /// Written in a schema newer than the worker understands
+    Newer,
+    /// A different type of job than the worker handles
+    OtherKind,
+}
+
+/// A job as stored on the queue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedJob {
+    pub id: String,
+    /// Payload type; empty for jobs queued before envelopes were typed
+    #[serde(rename = "type", default)]
+    pub kind: String,
+    /// Payload schema version; 0 for jobs queued before it was recorded
+    #[serde(default)]
+    pub schema_version: u32,
+    pub payload: String,
+    #[serde(default)]
+    pub priority: JobPriority,
+    /// Fairness key - jobs with the same key take turns with other keys
+    #[serde(default)]
+    pub repo: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<TraceContext>,
+    /// Failed or timed-out deliveries so far
+    #[serde(default)]
+    pub attempts: u32,
+    /// Times a worker too old for its schema put it back
+    #[serde(default)]
+    pub deferrals: u32,
+    pub enqueued_at: i64,
+    /// Why the last delivery failed
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Envelope fields written by a newer engine, kept when requeued
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl QueuedJob {
+    pub(super) fn new(job: NewJob) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: format!(
+                "{:x}-{:x}-{:x}",
+                now.timestamp_nanos_opt().unwrap_or_default(),
+                std::process::id(),
+                JOB_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+            ),
+            kind: job.kind,
+            schema_version: job.schema_version,
+            payload: job.payload,
+            priority: job.priority,
+            repo: job.repo,
+            trace: job.trace,
+            attempts: 0,
+            deferrals: 0,
+            enqueued_at: now.timestamp(),
+            last_error: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Decode a stored job. An envelope with a field this engine can't read
+    /// (e.g. a priority added later) keeps what can be read, with defaults
+    /// for the rest. Bare payloads queued before jobs carried delivery
+    /// bookkeeping are treated as a first attempt.
+    pub(super) fn decode(raw: &str) -> Self {
+        if let Ok(job) = serde_json::from_str(raw) {
+            return job;
+        }
+        if let Ok(serde_json::Value::Object(mut envelope)) = serde_json::from_str(raw) {
+            if let (Some(id), Some(payload)) = (take(&mut envelope, "id"), take(&mut envelope, "payload")) {
+                return Self {
+                    id,
+                    kind: take(&mut envelope, "type").unwrap_or_default(),
+                    schema_version: take(&mut envelope, "schema_version").unwrap_or_default(),
+                    payload,
+                    priority: take(&mut envelope, "priority").unwrap_or_default(),
+                    repo: take(&mut envelope, "repo").unwrap_or_default(),
+                    trace: take(&mut envelope, "trace"),
+                    attempts: take(&mut envelope, "attempts").unwrap_or_default(),
+                    deferrals: take(&mut envelope, "deferrals").unwrap_or_default(),
+                    enqueued_at: take(&mut envelope, "enqueued_at").unwrap_or_else(|| chrono::Utc::now().timestamp()),
+                    last_error: take(&mut envelope, "last_error"),
+                    extra: envelope.into_iter().collect(),
+                };
+            }
+        }
+
+        Self {
+            id: String::new(),
+            kind: String::new(),
+            schema_version: 0,
+            payload: raw.to_string(),
+            priority: JobPriority::default(),
+            repo: String::new(),
+            trace: None,
+            attempts: 0,
+            deferrals: 0,
+            enqueued_at: chrono::Utc::now().timestamp(),
+            last_error: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Whether a worker handling `kind` payloads up to `schema_version` can
+    /// take this job. Jobs queued before envelopes were typed are assumed to
+    /// be for the queue's worker.
+    pub fn compatibility(&self, kind: &str, schema_version: u32) -> Compatibility {
+        if !self.kind.is_empty() && self.kind != kind {
+            Compatibility::OtherKind
+        } else if self.schema_version > schema_version {
+            Compatibility::Newer
+        } else {
+            Compatibility::Compatible
+        }
+    }
+
+    /// The payload, decoded
+    pub fn decode_payload<T: JobPayload>(&self) -> Result<T> {
+        serde_json::from_str(&self.payload).map_err(|e| {
+            DbError::Serialization(format!(
+                "Job {} has an unreadable {} payload (schema {}): {}",
+                self.id, T::KIND, self.schema_version, e
+            ))
+            .into()
+        })
+    }
+}
+
+/// Remove a field from an envelope, if it decodes as `T`
+fn take<T: DeserializeOwned>(envelope: &mut serde_json::Map<String, serde_json::Value>, field: &str) -> Option<T> {
+    let value = envelope.remove(field)?;
+    serde_json::from_value(value).ok()
+}
+
+/// A job taken from the queue; acknowledge or fail it once handled
+#[derive(Debug, Clone)]
+pub struct ClaimedJob {
+    /// Stored form, which identifies this entry on the processing list
+    pub(super) raw: String,
+    pub job: QueuedJob,
+}
+
+impl ClaimedJob {
+    pub(super) fn from_raw(raw: String) -> Self {
+        Self {
+            job: QueuedJob::decode(&raw),
+            raw,
+        }
+    }
+}
+
+/// Source of claimed jobs for one queue, held by a worker across batches
+#[async_trait::async_trait]
+pub trait JobConsumer: Send {
+    /// Claim up to `max` jobs, blocking until one is available or the timeout
+    /// passes. Claimed jobs are leased for the policy's visibility timeout.
+    async fn next_batch(&mut self, max: usize, timeout_secs: u64, policy: &DeliveryPolicy) -> Result<Vec<ClaimedJob>>;
+}
+
+/// What happened to a failed job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobOutcome {
+    /// Back on the queue for another attempt
+    Retried,
+    /// Out of attempts, moved to the dead-letter list
+    DeadLettered,
+    /// No longer claimed - its lease expired and it was already requeued
+    Reclaimed,
+}
+
+/// Backlog of a job queue, used for autoscaling decisions
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueuePressure {
+    pub queue: String,
+    pub depth: u64,
+    /// Waiting jobs at each priority
+    pub depth_by_priority: BTreeMap<JobPriority, u64>,
+    /// Seconds the oldest waiting job has been queued
+    pub oldest_age_secs: Option<u64>,
+    /// Jobs claimed by a worker and not yet acknowledged
+    pub in_flight: u64,
+    pub dead_letters: u64,
+    /// Failed jobs waiting out their retry backoff
+    pub delayed: u64,
+    /// Jobs held back by a scan quota
+    pub parked: u64,
+}
+
+/// Marker of a job relayed from the outbox
+pub(super) fn relayed_key(queue: &str, job_id: &str) -> String {
+    CacheKey::new(CacheKind::Queue).segment(queue).segment("relayed").segment(job_id).to_string()
+}
+
+#[cfg(feature = "cache-dragonfly")]
+/// Keys of one queue
+struct QueueKeys {
+    base: String,
+    waiting: String,
+    signal: String,
+    processing: String,
+    leases: String,
+    dead: String,
+    delayed: String,
+    parked: String,
+}
+
+#[cfg(feature = "cache-dragonfly")]
+impl QueueKeys {
+    fn new(queue: &str) -> Self {
+        let key = |part: &str| CacheKey::new(CacheKind::Queue).segment(queue).segment(part).to_string();
+        Self {
+            base: CacheKey::new(CacheKind::Queue).segment(queue).to_string(),
+            waiting: key("waiting"),
+            signal: key("signal"),
+            processing: key("processing"),
+            leases: key("leases"),
+            dead: key("dead"),
+            delayed: key("delayed"),
+            parked: key("parked"),
+        }
+    }
+
+    fn jobs_prefix(&self) -> String {
+        format!("{}:jobs:", self.base)
+    }
+
+    fn jobs(&self, priority: JobPriority, repo: &str) -> String {
+        format!("{}{}:{}", self.jobs_prefix(), priority.as_str(), repo)
+    }
+
+    fn ready(&self, priority: JobPriority) -> String {
+        format!("{}:ready:{}", self.base, priority.as_str())
+    }
+}
+
+#[cfg(feature = "cache-dragonfly")]
+impl DragonflyPool {
+    /// Enqueue a job for background processing, returning its ID
+    pub async fn enqueue_job(&self, queue: &str, job: NewJob) -> Result<String> {
+        let mut conn = self.connection();
+        let keys = QueueKeys::new(queue);
+        let job = QueuedJob::new(job);
+
+        ENQUEUE_JOB
+            .key(keys.jobs(job.priority, &job.repo))
+            .key(keys.ready(job.priority))
+            .key(&keys.waiting)
+            .key(&keys.signal)
+            .arg(serde_json::to_string(&job)?)
+            .arg(&job.repo)
+            .arg(job.enqueued_at)
+            .arg(MAX_SIGNALS)
+            .invoke_async::<()>(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis enqueue failed", e))?;
+
+        tracing::debug!("Enqueued {} job {} to {} ({}, {})", job.kind, job.id, queue, job.priority.as_str(), job.repo);
+        Ok(job.id)
+    }
+
+    /// Queue a job written ahead to the outbox, unless a job with its ID was
+    /// relayed in the last `dedupe_secs`. Returns whether it was queued.
+    pub async fn relay_job(&self, queue: &str, job: &QueuedJob, dedupe_secs: u64) -> Result<bool> {
+        let mut conn = self.connection();
+        let keys = QueueKeys::new(queue);
+
+        let queued: i64 = ENQUEUE_JOB
+            .key(keys.jobs(job.priority, &job.repo))
+            .key(keys.ready(job.priority))
+            .key(&keys.waiting)
+            .key(&keys.signal)
+            .key(relayed_key(queue, &job.id))
+            .arg(serde_json::to_string(job)?)
+            .arg(&job.repo)
+            .arg(job.enqueued_at)
+            .arg(MAX_SIGNALS)
+            .arg(dedupe_secs.max(1))
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis enqueue failed", e))?;
+
+        if queued == 1 {
+            tracing::debug!("Relayed {} job {} to {} ({}, {})", job.kind, job.id, queue, job.priority.as_str(), job.repo);
+        }
+        Ok(queued == 1)
+    }
+
+    /// Open a dedicated connection for blocking reads from a queue.
+    ///
+    /// A blocking move on the shared multiplexed connection would stall every
+    /// other command behind it, so long-lived consumers get their own.
+    pub async fn consumer(&self, queue: &str) -> Result<QueueConsumer> {
+        let conn = self
+            .client()
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| DbError::redis("Redis connection error", e))?;
+
+        Ok(QueueConsumer {
+            conn,
+            keys: QueueKeys::new(queue),
+        })
+    }
+
+    /// Claim a single job (blocking with timeout). Workers should hold a
+    /// `QueueConsumer` instead; this opens a connection per call.
+    pub async fn dequeue_job(&self, queue: &str, timeout_secs: u64, policy: &DeliveryPolicy) -> Result<Option<ClaimedJob>> {
+        let mut consumer = self.consumer(queue).await?;
+        Ok(consumer.next_batch(1, timeout_secs, policy).await?.pop())
+    }
+
+    /// Remove a handled job from the processing list
+    pub async fn ack_job(&self, queue: &str, claimed: &ClaimedJob) -> Result<()> {
+        let mut conn = self.connection();
+        let keys = QueueKeys::new(queue);
+
+        redis::pipe()
+            .atomic()
+            .lrem(&keys.processing, 1, &claimed.raw)
+            .ignore()
+            .zrem(&keys.leases, &claimed.raw)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis ack failed", e).into())
+    }
+
+    /// Record a failed delivery: requeue the job, or dead-letter it once it
+    /// has used up its attempts
+    pub async fn fail_job(&self, queue: &str, claimed: &ClaimedJob, error: &str, policy: &DeliveryPolicy) -> Result<JobOutcome> {
+        let keys = QueueKeys::new(queue);
+        self.release(&keys, &claimed.raw, claimed.job.clone(), error, policy).await
+    }
+
+    /// Put a claimed job back on the queue without spending an attempt, for
+    /// a worker that understands its schema. `Reclaimed` if it was no
+    /// longer claimed.
+    pub async fn defer_job(&self, queue: &str, claimed: &ClaimedJob) -> Result<JobOutcome> {
+        let mut conn = self.connection();
+        let keys = QueueKeys::new(queue);
+
+        let mut job = claimed.job.clone();
+        job.deferrals += 1;
+        let now = chrono::Utc::now().timestamp();
+        let moved: i32 = MOVE_JOB
+            .key(&keys.processing)
+            .key(&keys.leases)
+            .key(keys.jobs(job.priority, &job.repo))
+            .key(keys.ready(job.priority))
+            .key(&keys.waiting)
+            .key(&keys.signal)
+            .arg(&claimed.raw)
+            .arg(serde_json::to_string(&job)?)
+            .arg(&job.repo)
+            .arg(now)
+            .arg(1)
+            .arg(MAX_SIGNALS)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis defer failed", e))?;
+
+        Ok(if moved == 1 { JobOutcome::Retried } else { JobOutcome::Reclaimed })
+    }
+
+    /// Requeue jobs whose visibility timeout has passed, counting each as a
+    /// failed attempt. Returns how many were released.
+    pub async fn requeue_expired(&self, queue: &str, policy: &DeliveryPolicy) -> Result<usize> {
+        let mut conn = self.connection();
+        let keys = QueueKeys::new(queue);
+        let now = chrono::Utc::now().timestamp();
+
+        let expired: Vec<String> = conn
+            .zrangebyscore(&keys.leases, "-inf", now)
+            .await
+            .map_err(|e| DbError::redis("Redis zrangebyscore failed", e))?;
+
+        let mut released = 0;
+        for raw in expired {
+            let job = QueuedJob::decode(&raw);
+            tracing::warn!("Job {} on {} exceeded its visibility timeout", job.id, queue);
+            if self.release(&keys, &raw, job, "visibility timeout expired", policy).await? != JobOutcome::Reclaimed {
+                released += 1;
+            }
+        }
+
+        // Leases whose job was acknowledged after being released
+        conn.zrembyscore::<_, _, _, ()>(&keys.leases, "-inf", now)
+            .await
+            .map_err(|e| DbError::redis("Redis zremrangebyscore failed", e))?;
+
+        Ok(released)
+    }
+
+    /// Dead-lettered jobs, newest first
+    pub async fn dead_letters(&self, queue: &str, limit: usize) -> Result<Vec<QueuedJob>> {
+        let mut conn = self.connection();
+        let keys = QueueKeys::new(queue);
+
+        let raw: Vec<String> = conn
+            .lrange(&keys.dead, 0, limit as isize - 1)
+            .await
+            .map_err(|e| DbError::redis("Redis lrange failed", e))?;
+
+        Ok(raw.iter().map(|raw| QueuedJob::decode(raw)).collect())
+    }
+
+    /// Put dead-lettered jobs back on the queue with a fresh attempt count -
+    /// the one with `id`, or all of them. Returns how many were requeued.
+    pub async fn requeue_dead_letters(&self, queue: &str, id: Option<&str>) -> Result<usize> {
+        let mut conn = self.connection();
+        let keys = QueueKeys::new(queue);
+        let now = chrono::Utc::now().timestamp();
+
+        let dead: Vec<String> = conn
+            .lrange(&keys.dead, 0, -1)
+            .await
+            .map_err(|e| DbError::redis("Redis lrange failed", e))?;
+
+        let mut requeued = 0;
+        for raw in dead {
+            let mut job = QueuedJob::decode(&raw);
+            if id.is_some_and(|id| id != job.id) {
+                continue;
+            }
+
+            job.attempts = 0;
+            job.enqueued_at = now;
+            let moved: i32 = MOVE_JOB
+                .key(&keys.dead)
+                .key(&keys.leases)
+                .key(keys.jobs(job.priority, &job.repo))
+                .key(keys.ready(job.priority))
+                .key(&keys.waiting)
+                .key(&keys.signal)
+                .arg(&raw)
+                .arg(serde_json::to_string(&job)?)
+                .arg(&job.repo)
+                .arg(now)
+                .arg(1)
+                .arg(MAX_SIGNALS)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(|e| DbError::redis("Redis requeue failed", e))?;
+            requeued += moved as usize;
+        }
+
+        Ok(requeued)
+    }
+
+    /// Queue depth and age of the oldest waiting job
+    pub async fn queue_pressure(&self, queue: &str) -> Result<QueuePressure> {
+        let mut conn = self.connection();
+        let keys = QueueKeys::new(queue);
+
+        let (depth, legacy, in_flight, dead_letters, delayed, parked, oldest): PressureCounts = redis::pipe()
+            .zcard(&keys.waiting)
+            .llen(&keys.base)
+            .llen(&keys.processing)
+            .llen(&keys.dead)
+            .zcard(&keys.delayed)
+            .hlen(&keys.parked)
+            .zrange_withscores(&keys.waiting, 0, 0)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis llen failed", e))?;
+
+        // Per-priority depth is summed over each ring's repositories
+        let mut depth_by_priority = BTreeMap::new();
+        for priority in JobPriority::ALL {
+            let repos: Vec<String> = conn
+                .lrange(keys.ready(priority), 0, -1)
+                .await
+                .map_err(|e| DbError::redis("Redis lrange failed", e))?;
+            if repos.is_empty() {
+                depth_by_priority.insert(priority, 0);
+                continue;
+            }
+
+            let mut pipe = redis::pipe();
+            for repo in &repos {
+                pipe.llen(keys.jobs(priority, repo));
+            }
+            let lengths: Vec<u64> = pipe
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| DbError::redis("Redis llen failed", e))?;
+            depth_by_priority.insert(priority, lengths.iter().sum());
+        }
+
+        let oldest_age_secs = oldest
+            .first()
+            .map(|(_, ts)| (chrono::Utc::now().timestamp() - ts).max(0) as u64);
+
+        Ok(QueuePressure {
+            queue: queue.to_string(),
+            depth: depth + legacy,
+            depth_by_priority,
+            oldest_age_secs,
+            in_flight,
+            dead_letters,
+            delayed,
+            parked,
+        })
+    }
+
+    /// Hold a job back in `slot` until [`Self::unpark_job`] queues it,
+    /// returning the job it replaced there
+    pub async fn park_job(&self, queue: &str, slot: &str, job: NewJob) -> Result<Option<QueuedJob>> {
+        let mut conn = self.connection();
+        let keys = QueueKeys::new(queue);
+        let job = QueuedJob::new(job);
+
+        let (replaced,): (Option<String>,) = redis::pipe()
+            .atomic()
+            .hget(&keys.parked, slot)
+            .hset(&keys.parked, slot, serde_json::to_string(&job)?)
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis park failed", e))?;
+
+        tracing::debug!("Parked job {} on {} in {}", job.id, queue, slot);
+        Ok(replaced.map(|raw| QueuedJob::decode(&raw)))
+    }
+
+    /// Parked jobs by slot
+    pub async fn parked_jobs(&self, queue: &str) -> Result<Vec<(String, QueuedJob)>> {
+        let mut conn = self.connection();
+        let keys = QueueKeys::new(queue);
+
+        let parked: Vec<(String, String)> = conn
+            .hgetall(&keys.parked)
+            .await
+            .map_err(|e| DbError::redis("Redis hgetall failed", e))?;
+
+        Ok(parked.into_iter().map(|(slot, raw)| (slot, QueuedJob::decode(&raw))).collect())
+    }
+
+    /// Queue the job parked in `slot`, if it is still `job`. Returns false if
+    /// the slot is empty or a newer job replaced it meanwhile.
+    pub async fn unpark_job(&self, queue: &str, slot: &str, job: &QueuedJob) -> Result<bool> {
+        let mut conn = self.connection();
+        let keys = QueueKeys::new(queue);
+        let now = chrono::Utc::now().timestamp();
+
+        let mut queued = job.clone();
+        queued.enqueued_at = now;
+        let moved: i32 = UNPARK_JOB
+            .key(&keys.parked)
+            .key(keys.jobs(job.priority, &job.repo))
+            .key(keys.ready(job.priority))
+            .key(&keys.waiting)
+            .key(&keys.signal)
+            .arg(slot)
+            .arg(serde_json::to_string(job)?)
+            .arg(serde_json::to_string(&queued)?)
+            .arg(&job.repo)
+            .arg(now)
+            .arg(MAX_SIGNALS)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis unpark failed", e))?;
+
+        Ok(moved == 1)
+    }
+
+    /// Move a claimed job back to the queue, to wait out its backoff, or on
+    /// to the dead-letter list
+    async fn release(&self, keys: &QueueKeys, raw: &str, mut job: QueuedJob, error: &str, policy: &DeliveryPolicy) -> Result<JobOutcome> {
+        let mut conn = self.connection();
+
+        job.attempts += 1;
+        job.last_error = Some(error.to_string());
+        let (destination, outcome) = if job.attempts >= policy.max_attempts {
+            (keys.dead.clone(), JobOutcome::DeadLettered)
+        } else {
+            (keys.jobs(job.priority, &job.repo), JobOutcome::Retried)
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let delay = policy.retry_delay_secs(job.attempts);
+        if outcome == JobOutcome::Retried && delay > 0 {
+            let moved: i32 = DELAY_JOB
+                .key(&keys.processing)
+                .key(&keys.leases)
+                .key(&keys.delayed)
+                .arg(raw)
+                .arg(serde_json::to_string(&job)?)
+                .arg(now + delay as i64)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(|e| DbError::redis("Redis release failed", e))?;
+            return Ok(if moved == 1 { outcome } else { JobOutcome::Reclaimed });
+        }
+
+        let moved: i32 = MOVE_JOB
+            .key(&keys.processing)
+            .key(&keys.leases)
+            .key(destination)
+            .key(keys.ready(job.priority))
+            .key(&keys.waiting)
+            .key(&keys.signal)
+            .arg(raw)
+            .arg(serde_json::to_string(&job)?)
+            .arg(&job.repo)
+            .arg(now)
+            .arg(i32::from(outcome == JobOutcome::Retried))
+            .arg(MAX_SIGNALS)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis release failed", e))?;
+
+        if moved == 0 {
+            return Ok(JobOutcome::Reclaimed);
+        }
+        if outcome == JobOutcome::DeadLettered {
+            tracing::warn!("Job {} dead-lettered after {} attempts: {}", job.id, job.attempts, error);
+        }
+        Ok(outcome)
+    }
+}
+
+/// Blocking consumer for one job queue, holding its own connection
+#[cfg(feature = "cache-dragonfly")]
+pub struct QueueConsumer {
+    conn: redis::aio::MultiplexedConnection,
+    keys: QueueKeys,
+}
+
+#[cfg(feature = "cache-dragonfly")]
+impl QueueConsumer {
+    /// Claim up to `max` jobs, blocking until one is available or the timeout
+    /// passes. Claimed jobs are leased for the policy's visibility timeout.
+    pub async fn next_batch(&mut self, max: usize, timeout_secs: u64, policy: &DeliveryPolicy) -> Result<Vec<ClaimedJob>> {
+        let claimed = self.claim(max, policy).await?;
+        if !claimed.is_empty() {
+            return Ok(claimed);
+        }
+
+        let woken: Option<(String, String)> = self
+            .conn
+            .brpop(&self.keys.signal, timeout_secs as f64)
+            .await
+            .map_err(|e| DbError::redis("Redis brpop failed", e))?;
+        if woken.is_none() {
+            return Ok(Vec::new());
+        }
+
+        self.claim(max, policy).await
+    }
+
+    /// Put retries whose backoff has passed back on their repositories' lists
+    async fn promote_due(&mut self) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let due: Vec<String> = self
+            .conn
+            .zrangebyscore_limit(&self.keys.delayed, "-inf", now, 0, MAX_PROMOTIONS)
+            .await
+            .map_err(|e| DbError::redis("Redis zrangebyscore failed", e))?;
+
+        for raw in due {
+            let job = QueuedJob::decode(&raw);
+            PROMOTE_JOB
+                .key(&self.keys.delayed)
+                .key(self.keys.jobs(job.priority, &job.repo))
+                .key(self.keys.ready(job.priority))
+                .key(&self.keys.waiting)
+                .key(&self.keys.signal)
+                .arg(&raw)
+                .arg(&job.repo)
+                .arg(now)
+                .arg(MAX_SIGNALS)
+                .invoke_async::<i32>(&mut self.conn)
+                .await
+                .map_err(|e| DbError::redis("Redis promote failed", e))?;
+        }
+        Ok(())
+    }
+
+    async fn claim(&mut self, max: usize, policy: &DeliveryPolicy) -> Result<Vec<ClaimedJob>> {
+        self.promote_due().await?;
+        let deadline = chrono::Utc::now().timestamp() + policy.visibility_timeout_secs as i64;
+
+        let mut invocation = CLAIM_JOBS.prepare_invoke();
+        for priority in JobPriority::ALL {
+            invocation.key(self.keys.ready(priority));
+        }
+        invocation
+            .key(&self.keys.base)
+            .key(&self.keys.processing)
+            .key(&self.keys.leases)
+            .key(&self.keys.waiting)
+            .key(&self.keys.signal)
+            .arg(max.max(1))
+            .arg(deadline)
+            .arg(self.keys.jobs_prefix());
+        for priority in JobPriority::ALL {
+            invocation.arg(priority.as_str());
+        }
+
+        let claimed: Vec<String> = invocation
+            .invoke_async(&mut self.conn)
+            .await
+            .map_err(|e| DbError::redis("Redis claim failed", e))?;
+
+        Ok(claimed.into_iter().map(ClaimedJob::from_raw).collect())
+    }
+}
+
+#[cfg(feature = "cache-dragonfly")]
+#[async_trait::async_trait]
+impl JobConsumer for QueueConsumer {
+    async fn next_batch(&mut self, max: usize, timeout_secs: u64, policy: &DeliveryPolicy) -> Result<Vec<ClaimedJob>> {
+        QueueConsumer::next_batch(self, max, timeout_secs, policy).await
+    }
+}
@@ -0,0 +1,200 @@
+//! Repository registry
+//!
+//! The repositories the engine has onboarded, which adapter settings each
+//! one uses, and when each was last scanned. Deactivating a repository takes
+//! it out of the registry's listings without touching its report history;
+//! registering it again brings it back.
+
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::SurrealPool;
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+use crate::RepoRef;
+#[cfg(feature = "documents-surrealdb")]
+use crate::Result;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "documents-surrealdb")]
+/// Columns selected for a registry entry; datetimes are read back as strings
+const ENTRY_FIELDS: &str = "platform, owner, name, adapter, active, \
+    <string> registered_at AS registered_at, \
+    (IF last_scanned_at != NONE THEN <string> last_scanned_at END) AS last_scanned_at, \
+    (IF deactivated_at != NONE THEN <string> deactivated_at END) AS deactivated_at, \
+    (IF deleted_at != NONE THEN <string> deleted_at END) AS deleted_at";
+
+/// A repository in the registry
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredRepository {
+    pub repo: RepoRef,
+    /// Key of the `[adapters]` settings to use; the repository's platform if unset
+    pub adapter: Option<String>,
+    /// Onboarded and not deactivated
+    pub active: bool,
+    pub registered_at: chrono::DateTime<chrono::Utc>,
+    pub last_scanned_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub deactivated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the repository was deleted on its platform
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl RegisteredRepository {
+    /// Key of the `[adapters]` settings that apply
+    pub fn adapter_key(&self) -> &str {
+        self.adapter.as_deref().unwrap_or(&self.repo.platform)
+    }
+}
+
+/// Registry entry as stored in SurrealDB
+#[derive(Debug, Deserialize)]
+struct RegistryEntry {
+    platform: String,
+    owner: String,
+    name: String,
+    #[serde(default)]
+    adapter: Option<String>,
+    #[serde(default = "default_active")]
+    active: bool,
+    registered_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    last_scanned_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    deactivated_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn default_active() -> bool {
+    true
+}
+
+impl From<RegistryEntry> for RegisteredRepository {
+    fn from(entry: RegistryEntry) -> Self {
+        Self {
+            repo: RepoRef::new(entry.platform, entry.owner, entry.name),
+            adapter: entry.adapter,
+            active: entry.active,
+            registered_at: entry.registered_at,
+            last_scanned_at: entry.last_scanned_at,
+            deactivated_at: entry.deactivated_at,
+            deleted_at: entry.deleted_at,
+        }
+    }
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealPool {
+    /// Add a repository to the registry, or reactivate one that was
+    /// deactivated or marked deleted. With no `adapter`, an entry keeps the
+    /// adapter it was registered with.
+    pub async fn register_repository(&self, repo: &RepoRef, adapter: Option<&str>) -> Result<RegisteredRepository> {
+        tracing::info!("Registering {}", repo);
+
+        self.client()
+            .query(
+                "UPSERT repository SET platform = $platform, owner = $owner, name = $repo, \
+                    adapter = $adapter ?? adapter, active = true, registered_at = registered_at ?? time::now(), \
+                    deactivated_at = NONE, deleted_at = NONE \
+                 WHERE platform = $platform AND owner = $owner AND name = $repo",
+            )
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
+            .bind(("adapter", adapter.map(str::to_string)))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| DbError::surreal("SurrealDB upsert failed", e))?;
+
+        self.get_repository(repo)
+            .await?
+            .ok_or_else(|| DbError::NotFound(format!("{} was not registered", repo)).into())
+    }
+
+    /// Take a repository out of the registry's listings, keeping its report
+    /// history. Returns whether it was active.
+    pub async fn deactivate_repository(&self, repo: &RepoRef) -> Result<bool> {
+        tracing::info!("Deactivating {}", repo);
+
+        let mut result = self
+            .client()
+            .query(
+                "RETURN array::len((UPDATE repository SET active = false, deactivated_at = time::now() \
+                    WHERE platform = $platform AND owner = $owner AND name = $repo AND active != false \
+                    RETURN BEFORE))",
+            )
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB update failed", e))?;
+
+        let deactivated: Option<usize> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB update failed", e))?;
+        Ok(deactivated.unwrap_or_default() > 0)
+    }
+
+    /// Mark a repository as deleted on its platform.
+    ///
+    /// Reports are kept - certifications that were issued must stay auditable.
+    pub async fn mark_repository_deleted(&self, repo: &RepoRef) -> Result<()> {
+        tracing::info!("Marking {} as deleted", repo);
+
+        self.client()
+            .query("UPDATE repository SET deleted_at = time::now() WHERE platform = $platform AND owner = $owner AND name = $repo")
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB update failed", e))?;
+
+        Ok(())
+    }
+
+    /// Active repositories that still exist, optionally only those on one
+    /// platform and under one owner. An owner includes its subgroups.
+    pub async fn list_repositories(
+        &self,
+        platform: Option<&str>,
+        owner: Option<&str>,
+    ) -> Result<Vec<RegisteredRepository>> {
+        let mut result = self
+            .client()
+            .query(format!(
+                "SELECT {} FROM repository \
+                 WHERE active != false AND deleted_at = NONE \
+                    AND ($platform = NONE OR platform = $platform) \
+                    AND ($owner = NONE OR owner = $owner OR string::starts_with(owner, $owner + '/')) \
+                 ORDER BY platform, owner, name",
+                ENTRY_FIELDS
+            ))
+            .bind(("platform", platform.map(str::to_string)))
+            .bind(("owner", owner.map(str::to_string)))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let entries: Vec<RegistryEntry> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+        Ok(entries.into_iter().map(RegisteredRepository::from).collect())
+    }
+
+    /// A repository's registry entry, active or not
+    pub async fn get_repository(&self, repo: &RepoRef) -> Result<Option<RegisteredRepository>> {
+        let mut result = self
+            .client()
+            .query(format!(
+                "SELECT {} FROM repository WHERE platform = $platform AND owner = $owner AND name = $repo LIMIT 1",
+                ENTRY_FIELDS
+            ))
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let entries: Vec<RegistryEntry> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+        Ok(entries.into_iter().next().map(RegisteredRepository::from))
+    }
+}
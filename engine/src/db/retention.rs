@@ -0,0 +1,199 @@
+//! Report retention
+//!
+//! Stored reports can be limited to the newest `max_reports` per repository,
+//! the last `max_age_days` of history, or both. A report is pruned only
+//! once it falls outside every configured limit, and a repository's latest
+//! report is always kept.
+//!
+//! Pruning tombstones reports rather than deleting them: a pruned report
+//! gets a `deleted_at` and drops out of history, trends and summaries, but
+//! stays in the hash chain, which can still be verified end to end.
+//! Reports a badge was issued from are never pruned.
+
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::SurrealPool;
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+#[cfg(feature = "documents-surrealdb")]
+use crate::{RepoRef, Result};
+use serde::{Deserialize, Serialize};
+
+/// How much report history to keep. With neither limit set, nothing is
+/// pruned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Newest reports kept per repository
+    pub max_reports: Option<u32>,
+    /// Days of history kept per repository
+    pub max_age_days: Option<u32>,
+}
+
+impl RetentionPolicy {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_reports.is_none() && self.max_age_days.is_none()
+    }
+
+    /// Problems with the configured limits
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.max_reports == Some(0) {
+            problems.push("retention.max_reports: must keep at least one report".to_string());
+        }
+        if self.max_age_days == Some(0) {
+            problems.push("retention.max_age_days: must be positive".to_string());
+        }
+        problems
+    }
+}
+
+/// Outcome of a pruning pass
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PruneReport {
+    /// Repositories with live reports
+    pub scanned: usize,
+    /// Repositories that had reports pruned
+    pub repositories: usize,
+    /// Reports tombstoned, or that would be on a dry run
+    pub tombstoned: usize,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub dry_run: bool,
+}
+
+#[cfg(feature = "documents-surrealdb")]
+/// Condition matching one repository's reports outside the retention
+/// limits. Expects `$platform`, `$owner`, `$repo`, `$beyond` and `$older_than`.
+const PRUNABLE: &str = "platform = $platform AND owner = $owner AND repo = $repo \
+    AND deleted_at = NONE AND badge_issued_at = NONE \
+    AND created_at <= <datetime> $beyond \
+    AND ($older_than = NONE OR created_at < <datetime> $older_than)";
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealPool {
+    /// Tombstone every report outside `policy`. On a dry run, only counts
+    /// them.
+    pub async fn prune_reports(&self, policy: &RetentionPolicy, dry_run: bool) -> Result<PruneReport> {
+        let started_at = chrono::Utc::now();
+        let mut report = PruneReport {
+            started_at: Some(started_at),
+            dry_run,
+            ..Default::default()
+        };
+        if policy.is_unlimited() {
+            return Ok(report);
+        }
+
+        let older_than = policy
+            .max_age_days
+            .map(|days| started_at - chrono::Duration::days(i64::from(days)));
+
+        for repo in self.repositories_with_reports().await? {
+            report.scanned += 1;
+            let pruned = self.prune_repository(&repo, policy, older_than, started_at, dry_run).await?;
+            if pruned > 0 {
+                tracing::debug!("Pruning {} reports of {}", pruned, repo);
+                report.repositories += 1;
+                report.tombstoned += pruned;
+            }
+        }
+
+        tracing::info!(
+            "Report pruning{} tombstoned {} reports across {} repositories",
+            if dry_run { " (dry run)" } else { "" },
+            report.tombstoned,
+            report.repositories
+        );
+        Ok(report)
+    }
+
+    /// Record that a badge was issued from a repository's report stored at
+    /// `at`, so it is never pruned
+    pub async fn mark_badge_issued(&self, repo: &RepoRef, at: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        self.client()
+            .query(
+                "UPDATE compliance_report SET badge_issued_at = time::now() \
+                 WHERE platform = $platform AND owner = $owner AND repo = $repo AND created_at = <datetime> $at",
+            )
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
+            .bind(("at", at.to_rfc3339()))
+            .await
+            .and_then(surrealdb::Response::check)
+            .map_err(|e| DbError::surreal("SurrealDB update failed", e))?;
+        Ok(())
+    }
+
+    async fn repositories_with_reports(&self) -> Result<Vec<RepoRef>> {
+        let mut result = self
+            .client()
+            .query("SELECT platform, owner, repo FROM compliance_report WHERE deleted_at = NONE GROUP BY platform, owner, repo")
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e).into())
+    }
+
+    /// Tombstone one repository's reports outside `policy`, returning how many
+    async fn prune_repository(
+        &self,
+        repo: &RepoRef,
+        policy: &RetentionPolicy,
+        older_than: Option<chrono::DateTime<chrono::Utc>>,
+        now: chrono::DateTime<chrono::Utc>,
+        dry_run: bool,
+    ) -> Result<usize> {
+        // The newest report past the count limit; with only an age limit,
+        // the one before the latest
+        let keep = policy.max_reports.unwrap_or(1);
+        let mut result = self
+            .client()
+            .query(
+                "SELECT VALUE <string> created_at FROM ( \
+                    SELECT created_at FROM compliance_report \
+                    WHERE platform = $platform AND owner = $owner AND repo = $repo AND deleted_at = NONE \
+                    ORDER BY created_at DESC LIMIT 1 START $keep \
+                 )",
+            )
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
+            .bind(("keep", keep))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let beyond: Vec<chrono::DateTime<chrono::Utc>> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+        let Some(beyond) = beyond.into_iter().next() else {
+            return Ok(0);
+        };
+
+        let statement = if dry_run {
+            format!("RETURN array::len((SELECT VALUE id FROM compliance_report WHERE {}))", PRUNABLE)
+        } else {
+            format!(
+                "RETURN array::len((UPDATE compliance_report SET deleted_at = <datetime> $now WHERE {} RETURN BEFORE))",
+                PRUNABLE
+            )
+        };
+        let mut result = self
+            .client()
+            .query(statement)
+            .bind(("platform", repo.platform.clone()))
+            .bind(("owner", repo.owner.clone()))
+            .bind(("repo", repo.repo.clone()))
+            .bind(("beyond", beyond.to_rfc3339()))
+            .bind(("older_than", older_than.map(|at| at.to_rfc3339())))
+            .bind(("now", now.to_rfc3339()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB update failed", e))?;
+
+        let pruned: Option<usize> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB update failed", e))?;
+        Ok(pruned.unwrap_or_default())
+    }
+}
@@ -0,0 +1,153 @@
+//! Dashboard sessions on DragonflyDB
+//!
+//! Each session is a hash under `rsr:session:v1:{id}` holding the
+//! serde-encoded payload, its creation time and its idle timeout. Expiry
+//! slides: reading or touching a session pushes its expiry back by the full
+//! timeout, so a session only ends after it has gone unused for that long,
+//! or when it is revoked.
+//!
+//! Session IDs are 256 random bits from the OS, hex-encoded.
+
+use super::cache::{CacheKey, CacheKind};
+#[cfg(feature = "cache-dragonfly")]
+use super::cache::DragonflyPool;
+#[cfg(feature = "cache-dragonfly")]
+use super::error::DbError;
+use crate::{Result, RsrError};
+#[cfg(feature = "cache-dragonfly")]
+use once_cell::sync::Lazy;
+#[cfg(feature = "cache-dragonfly")]
+use redis::AsyncCommands;
+#[cfg(feature = "cache-dragonfly")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "cache-dragonfly")]
+use serde::Serialize;
+
+/// Read a session and renew its expiry in one step, so a session can't
+/// expire between the two.
+///
+/// KEYS: session
+/// Returns `[payload, created_at, idle timeout]`, or nil if there is no session.
+#[cfg(feature = "cache-dragonfly")]
+static GET_AND_RENEW: Lazy<redis::Script> = Lazy::new(|| {
+    redis::Script::new(
+        r#"
+        local fields = redis.call('HMGET', KEYS[1], 'payload', 'created_at', 'ttl')
+        if not fields[1] then
+            return nil
+        end
+        redis.call('EXPIRE', KEYS[1], tonumber(fields[3]))
+        return fields
+        "#,
+    )
+});
+
+/// A session as read back from the cache
+#[derive(Debug, Clone)]
+pub struct Session<T> {
+    pub id: String,
+    pub payload: T,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Idle time after which the session expires
+    pub ttl_secs: u64,
+}
+
+pub(super) fn session_key(session_id: &str) -> String {
+    CacheKey::new(CacheKind::Session).segment(session_id).to_string()
+}
+
+pub(super) fn new_session_id() -> Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes)
+        .map_err(|e| RsrError::Platform(format!("Failed to generate session ID: {}", e)))?;
+    Ok(hex::encode(bytes))
+}
+
+#[cfg(feature = "cache-dragonfly")]
+impl DragonflyPool {
+    /// Start a session holding `payload`, returning its ID. The session
+    /// expires after `ttl_secs` without being read or touched.
+    pub async fn create_session<T: Serialize>(&self, payload: &T, ttl_secs: u64) -> Result<String> {
+        if ttl_secs == 0 {
+            return Err(RsrError::Config("Session TTL must be at least one second".to_string()));
+        }
+
+        let id = new_session_id()?;
+        let key = session_key(&id);
+        let payload = serde_json::to_string(payload)?;
+
+        let mut conn = self.connection();
+        redis::pipe()
+            .atomic()
+            .hset_multiple(
+                &key,
+                &[
+                    ("payload", payload),
+                    ("created_at", chrono::Utc::now().to_rfc3339()),
+                    ("ttl", ttl_secs.to_string()),
+                ],
+            )
+            .ignore()
+            .expire(&key, ttl_secs as i64)
+            .ignore()
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis session create failed", e))?;
+
+        Ok(id)
+    }
+
+    /// Read a session, renewing its expiry. Returns `None` if it expired,
+    /// was revoked or never existed.
+    pub async fn get_session<T: DeserializeOwned>(&self, session_id: &str) -> Result<Option<Session<T>>> {
+        let mut conn = self.connection();
+        let fields: Option<(String, String, u64)> = GET_AND_RENEW
+            .key(session_key(session_id))
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| DbError::redis("Redis session get failed", e))?;
+
+        let Some((payload, created_at, ttl_secs)) = fields else {
+            return Ok(None);
+        };
+        let created_at = chrono::DateTime::parse_from_rfc3339(&created_at)
+            .map_err(|e| DbError::Serialization(format!("Corrupt session {}: {}", session_id, e)))?
+            .with_timezone(&chrono::Utc);
+
+        Ok(Some(Session {
+            id: session_id.to_string(),
+            payload: serde_json::from_str(&payload)?,
+            created_at,
+            ttl_secs,
+        }))
+    }
+
+    /// Renew a session's expiry without reading it, e.g. on activity that
+    /// doesn't need the payload. Returns whether the session still exists.
+    pub async fn touch_session(&self, session_id: &str) -> Result<bool> {
+        let mut conn = self.connection();
+        let ttl_secs: Option<u64> = conn
+            .hget(session_key(session_id), "ttl")
+            .await
+            .map_err(|e| DbError::redis("Redis session touch failed", e))?;
+        let Some(ttl_secs) = ttl_secs else {
+            return Ok(false);
+        };
+
+        // EXPIRE reports false if the session expired since the read
+        conn.expire(session_key(session_id), ttl_secs as i64)
+            .await
+            .map_err(|e| DbError::redis("Redis session touch failed", e).into())
+    }
+
+    /// End a session now. Returns whether there was one to end.
+    pub async fn revoke_session(&self, session_id: &str) -> Result<bool> {
+        let mut conn = self.connection();
+        let removed: u64 = conn
+            .del(session_key(session_id))
+            .await
+            .map_err(|e| DbError::redis("Redis session revoke failed", e))?;
+
+        Ok(removed > 0)
+    }
+}
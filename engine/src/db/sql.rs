@@ -0,0 +1,71 @@
+//! Helpers shared by the SQL document stores
+
+use crate::Result;
+use std::time::Duration;
+
+/// Pool sizing of a SQL store, read from `RSR_<BACKEND>_MAX_CONNECTIONS`,
+/// `_MIN_CONNECTIONS`, `_IDLE_TIMEOUT_SECS` (0 keeps idle connections open)
+/// and `_ACQUIRE_TIMEOUT_SECS`
+pub(super) struct PoolSettings {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub idle_timeout: Option<Duration>,
+    pub acquire_timeout: Duration,
+}
+
+impl PoolSettings {
+    pub fn from_env(backend: &str, default_max_connections: u32) -> Self {
+        let var = |name: &str| -> Option<u64> {
+            std::env::var(format!("RSR_{}_{}", backend, name))
+                .ok()
+                .and_then(|v| v.parse().ok())
+        };
+        let max_connections = var("MAX_CONNECTIONS")
+            .and_then(|v| u32::try_from(v).ok())
+            .unwrap_or(default_max_connections)
+            .max(1);
+
+        Self {
+            max_connections,
+            min_connections: var("MIN_CONNECTIONS")
+                .and_then(|v| u32::try_from(v).ok())
+                .unwrap_or(0)
+                .min(max_connections),
+            idle_timeout: Some(var("IDLE_TIMEOUT_SECS").unwrap_or(600))
+                .filter(|&secs| secs > 0)
+                .map(Duration::from_secs),
+            acquire_timeout: Duration::from_secs(var("ACQUIRE_TIMEOUT_SECS").unwrap_or(30).max(1)),
+        }
+    }
+
+    pub fn apply<DB: sqlx::Database>(&self, options: sqlx::pool::PoolOptions<DB>) -> sqlx::pool::PoolOptions<DB> {
+        options
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .idle_timeout(self.idle_timeout)
+            .acquire_timeout(self.acquire_timeout)
+    }
+}
+
+/// Record ID returned for a row of a table keyed by a serial
+pub(super) fn record_id(table: &str, id: i64) -> String {
+    format!("{}:{}", table, id)
+}
+
+/// Serial key of a record ID from [`record_id`], or a bare key
+pub(super) fn record_key(table: &str, id: &str) -> Option<i64> {
+    id.strip_prefix(table)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .unwrap_or(id)
+        .parse()
+        .ok()
+}
+
+/// A unit-like enum as stored in a text column
+pub(super) fn text<T: serde::Serialize>(value: T) -> Result<String> {
+    Ok(serde_json::to_value(value)?.as_str().unwrap_or_default().to_string())
+}
+
+pub(super) fn parse_text<T: serde::de::DeserializeOwned>(value: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_string())).ok()
+}
@@ -0,0 +1,1322 @@
+//! Embedded SQLite document store
+//!
+//! For small teams running the whole engine as one binary: with the
+//! `documents-sqlite` feature, setting `RSR_SQLITE_PATH` keeps documents in
+//! a local database file (created if missing) instead of a SurrealDB or
+//! Postgres server. Pair it with `RSR_DRAGONFLY_URL=memory://` so no cache
+//! server is needed either.
+//!
+//! Records and guarantees are the same as [`SurrealPool`]'s: chained reports
+//! and audit events are protected by triggers, and a report that loses a race
+//! to chain onto the same predecessor is retried. Timestamps are stored as
+//! RFC 3339 text with nanoseconds, which sorts correctly and keeps report
+//! digests intact. Trends are aggregated here rather than in the database.
+//!
+//! The schema is managed by its own migrations in `engine/migrations/sqlite`,
+//! checked against `schema_migrations` the same way as SurrealDB's.
+//!
+//! [`SurrealPool`]: super::documents::SurrealPool
+
+use super::annotations::{Annotation, AnnotationKind};
+use super::audit::{repo_target, AuditAction, AuditEvent, AuditPage, AuditQuery, MAX_AUDIT_PAGE};
+use super::ci_runs::CiRun;
+use super::documents::{
+    stored_tier, ArchivedWebhook, ChainVerification, ComplianceReport, DocumentStore, HistoryPage,
+    VerificationOutcome, WebhookEvent, CHAIN_ATTEMPTS, CHAIN_PAGE, MAX_HISTORY_PAGE,
+};
+use super::error::DbError;
+use super::migrations::{self, AppliedMigration, Migration};
+use super::orgs::{certification_validity, OrgSummary, RepoStanding, SUMMARY_TTL_SECS};
+use super::outbox::{BuildJob, OutboxEntry, OutboxRow};
+use super::quarantine::ENGINE_VERSION;
+use super::registry::RegisteredRepository;
+use super::retention::{PruneReport, RetentionPolicy};
+use super::sql::{parse_text, record_id, record_key, text, PoolSettings};
+use super::trends::{self, ComplianceTrend, TrendBucket, TrendInterval, TrendWindow};
+use crate::adapters::strict::ParseWarning;
+use crate::{ComplianceStatus, RepoRef, Result, RsrError};
+use chrono::Datelike;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use sqlx::types::Json;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Every SQLite migration, in the order they are applied
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial",
+        statements: include_str!("../../migrations/sqlite/0001_initial.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "outbox",
+        statements: include_str!("../../migrations/sqlite/0002_outbox.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "webhook_parse_warnings",
+        statements: include_str!("../../migrations/sqlite/0003_webhook_parse_warnings.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "ci_runs",
+        statements: include_str!("../../migrations/sqlite/0004_ci_runs.sql"),
+    },
+];
+
+/// Table recording applied migrations, created before anything else runs
+const BOOTSTRAP: &str = "CREATE TABLE IF NOT EXISTS schema_migrations ( \
+    version INTEGER PRIMARY KEY, \
+    name TEXT NOT NULL, \
+    checksum TEXT NOT NULL, \
+    applied_at TEXT NOT NULL \
+)";
+
+/// Columns selected for a stored report
+const REPORT_FIELDS: &str = "platform, owner, repo, tier, score, checks, standard, canonical, evidence, \
+    created_at, digest, previous_digest";
+
+/// Matches rows whose `owner` is `$owner` or one of its subgroups, with the
+/// owner bound at `$n`
+fn owner_or_subgroup(column: &str, n: usize) -> String {
+    format!(
+        "({column} = ${n} OR substr({column}, 1, length(${n}) + 1) = ${n} || '/')",
+        column = column,
+        n = n
+    )
+}
+
+/// A timestamp as stored: RFC 3339 in UTC with nanoseconds, so that text
+/// order is time order
+fn stamp(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
+}
+
+fn parse_stamp(at: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(at)
+        .map(|at| at.with_timezone(&chrono::Utc))
+        .map_err(|e| DbError::Serialization(format!("Invalid timestamp {} in SQLite: {}", at, e)).into())
+}
+
+fn parse_optional_stamp(at: Option<String>) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+    at.as_deref().map(parse_stamp).transpose()
+}
+
+fn query_failed(e: sqlx::Error) -> DbError {
+    DbError::sqlx("SQLite query failed", e)
+}
+
+/// Insert an archived webhook, returning its serial key
+async fn insert_webhook_event<'e>(executor: impl sqlx::SqliteExecutor<'e>, event: &WebhookEvent) -> Result<i64> {
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO webhook_event \
+            (platform, event_type, delivery_id, headers, payload, verification, processed, error, quarantined_by, \
+            parse_warnings, received_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) \
+         RETURNING id",
+    )
+    .bind(&event.platform)
+    .bind(&event.event_type)
+    .bind(&event.delivery_id)
+    .bind(Json(&event.headers))
+    .bind(&event.payload)
+    .bind(text(event.verification)?)
+    .bind(event.processed)
+    .bind(&event.error)
+    .bind(&event.quarantined_by)
+    .bind(Json(&event.parse_warnings))
+    .bind(stamp(event.received_at))
+    .fetch_one(executor)
+    .await
+    .map_err(|e| DbError::sqlx("SQLite insert failed", e))?;
+
+    Ok(id)
+}
+
+/// Whether a failed insert lost a race to another writer
+fn lost_race(e: &sqlx::Error) -> bool {
+    match e {
+        // Unique constraint, or a write from a stale snapshot (SQLITE_BUSY_SNAPSHOT)
+        sqlx::Error::Database(e) => e.is_unique_violation() || e.code().as_deref() == Some("517"),
+        _ => false,
+    }
+}
+
+/// SQLite document store
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+/// Compliance report as stored in SQLite
+#[derive(Debug, sqlx::FromRow)]
+struct ReportRow {
+    platform: String,
+    owner: String,
+    repo: String,
+    tier: String,
+    score: f32,
+    checks: Json<serde_json::Value>,
+    standard: Json<Vec<String>>,
+    canonical: Option<Json<RepoRef>>,
+    evidence: Json<BTreeMap<String, RepoRef>>,
+    created_at: String,
+    digest: String,
+    previous_digest: Option<String>,
+}
+
+impl ReportRow {
+    fn into_report(self) -> Result<ComplianceReport> {
+        Ok(ComplianceReport {
+            platform: self.platform,
+            owner: self.owner,
+            repo: self.repo,
+            tier: self.tier,
+            score: self.score,
+            checks: self.checks.0,
+            created_at: parse_stamp(&self.created_at)?,
+            standard: self.standard.0,
+            canonical: self.canonical.map(|canonical| canonical.0),
+            evidence: self.evidence.0,
+            digest: Some(self.digest),
+            previous_digest: self.previous_digest,
+        })
+    }
+}
+
+/// Archived webhook as stored in SQLite
+#[derive(Debug, sqlx::FromRow)]
+struct WebhookRow {
+    id: i64,
+    platform: String,
+    event_type: String,
+    delivery_id: Option<String>,
+    headers: Json<HashMap<String, String>>,
+    payload: String,
+    verification: String,
+    processed: bool,
+    error: Option<String>,
+    quarantined_by: Option<String>,
+    parse_warnings: Json<Vec<ParseWarning>>,
+    received_at: String,
+}
+
+impl WebhookRow {
+    fn into_archived(self) -> Result<ArchivedWebhook> {
+        Ok(ArchivedWebhook {
+            id: record_id("webhook_event", self.id),
+            event: WebhookEvent {
+                platform: self.platform,
+                event_type: self.event_type,
+                delivery_id: self.delivery_id,
+                headers: self.headers.0,
+                payload: self.payload,
+                verification: parse_text(&self.verification).unwrap_or(VerificationOutcome::Rejected),
+                processed: self.processed,
+                error: self.error,
+                quarantined_by: self.quarantined_by,
+                parse_warnings: self.parse_warnings.0,
+                received_at: parse_stamp(&self.received_at)?,
+            },
+        })
+    }
+}
+
+/// Latest CI run of a workflow as stored in SQLite
+#[derive(Debug, sqlx::FromRow)]
+struct CiRunRow {
+    workflow: String,
+    branch: String,
+    commit_sha: String,
+    conclusion: String,
+    completed_at: String,
+}
+
+impl CiRunRow {
+    /// The run, unless its conclusion is one this build doesn't know
+    fn into_run(self) -> Result<Option<CiRun>> {
+        let Some(conclusion) = parse_text(&self.conclusion) else {
+            return Ok(None);
+        };
+        Ok(Some(CiRun {
+            conclusion,
+            completed_at: parse_stamp(&self.completed_at)?,
+            workflow: self.workflow,
+            branch: self.branch,
+            commit_sha: self.commit_sha,
+        }))
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct AnnotationRow {
+    id: i64,
+    kind: String,
+    check_id: Option<String>,
+    message: String,
+    reference: Option<String>,
+    author: String,
+    created_at: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct AuditRow {
+    actor: String,
+    action: String,
+    target: String,
+    details: Json<serde_json::Value>,
+    created_at: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct RegistryRow {
+    platform: String,
+    owner: String,
+    name: String,
+    adapter: Option<String>,
+    active: bool,
+    registered_at: String,
+    last_scanned_at: Option<String>,
+    deactivated_at: Option<String>,
+    deleted_at: Option<String>,
+}
+
+impl RegistryRow {
+    fn into_registered(self) -> Result<RegisteredRepository> {
+        Ok(RegisteredRepository {
+            repo: RepoRef::new(self.platform, self.owner, self.name),
+            adapter: self.adapter,
+            active: self.active,
+            registered_at: parse_stamp(&self.registered_at)?,
+            last_scanned_at: parse_optional_stamp(self.last_scanned_at)?,
+            deactivated_at: parse_optional_stamp(self.deactivated_at)?,
+            deleted_at: parse_optional_stamp(self.deleted_at)?,
+        })
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct MigrationRow {
+    version: i64,
+    name: String,
+    checksum: String,
+    applied_at: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct TrendRow {
+    tier: String,
+    score: f32,
+    created_at: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct StandingRow {
+    owner: String,
+    repo: String,
+    tier: String,
+    score: f32,
+    created_at: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct RepoRow {
+    platform: String,
+    owner: String,
+    repo: String,
+}
+
+impl SqliteStore {
+    /// Open the database file at `path`, creating it if missing.
+    /// `RSR_SQLITE_MAX_CONNECTIONS` sets the pool size (default 4); see
+    /// [`PoolSettings`] for the other knobs.
+    ///
+    /// `sqlite::memory:` (or `:memory:`) opens a database held in memory on
+    /// one connection kept for the store's lifetime, since each connection
+    /// to an in-memory database sees a database of its own. Nothing survives
+    /// the store being dropped.
+    pub async fn open(path: &str) -> Result<Self> {
+        if matches!(path, "sqlite::memory:" | ":memory:") {
+            return Self::open_in_memory().await;
+        }
+        let settings = PoolSettings::from_env("SQLITE", 4);
+
+        tracing::info!("Opening SQLite documents store: {}", path);
+
+        // WAL lets readers carry on while a report is being written
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .busy_timeout(std::time::Duration::from_secs(5));
+        let pool = settings
+            .apply(SqlitePoolOptions::new())
+            .connect_with(options)
+            .await
+            .map_err(|e| DbError::sqlx("SQLite open failed", e))?;
+
+        Ok(Self { pool })
+    }
+
+    async fn open_in_memory() -> Result<Self> {
+        tracing::info!("Opening in-memory SQLite documents store");
+
+        let options = SqliteConnectOptions::new().in_memory(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(options)
+            .await
+            .map_err(|e| DbError::sqlx("SQLite open failed", e))?;
+
+        Ok(Self { pool })
+    }
+
+    /// Migrations recorded as applied, oldest first
+    pub async fn applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
+        sqlx::query(BOOTSTRAP)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("SQLite migration bootstrap failed", e))?;
+
+        let rows: Vec<MigrationRow> =
+            sqlx::query_as("SELECT version, name, checksum, applied_at FROM schema_migrations ORDER BY version")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(query_failed)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(AppliedMigration {
+                    version: row.version as u32,
+                    name: row.name,
+                    checksum: row.checksum,
+                    applied_at: parse_stamp(&row.applied_at)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Chain a report onto the head of its repository's chain. Database
+    /// errors are kept apart so that a lost race can be retried.
+    async fn append_report(&self, status: &ComplianceStatus) -> std::result::Result<Result<String>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let previous: Option<String> = sqlx::query_scalar(
+            "SELECT digest FROM compliance_report \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 \
+             ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(&status.repo.platform)
+        .bind(&status.repo.owner)
+        .bind(&status.repo.repo)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let report = match ComplianceReport::from_status(status).and_then(|report| report.chained(previous)) {
+            Ok(report) => report,
+            Err(e) => return Ok(Err(e)),
+        };
+        let digest = report.digest.clone().unwrap_or_default();
+        let created_at = stamp(report.created_at);
+
+        // previous_digest is unique, so of two reports chaining onto the same
+        // predecessor only one is inserted
+        sqlx::query(
+            "INSERT INTO compliance_report \
+                (digest, previous_digest, platform, owner, repo, tier, score, checks, standard, canonical, evidence, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        )
+        .bind(&digest)
+        .bind(&report.previous_digest)
+        .bind(&report.platform)
+        .bind(&report.owner)
+        .bind(&report.repo)
+        .bind(&report.tier)
+        .bind(report.score)
+        .bind(Json(&report.checks))
+        .bind(Json(&report.standard))
+        .bind(report.canonical.as_ref().map(Json))
+        .bind(Json(&report.evidence))
+        .bind(&created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE repository SET last_scanned_at = $4 WHERE platform = $1 AND owner = $2 AND name = $3")
+            .bind(&report.platform)
+            .bind(&report.owner)
+            .bind(&report.repo)
+            .bind(&created_at)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "DELETE FROM org_summary WHERE platform = $1 \
+             AND ($2 = owner OR substr($2, 1, length(owner) + 1) = owner || '/')",
+        )
+        .bind(&report.platform)
+        .bind(&report.owner)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Ok(digest))
+    }
+
+    async fn webhook_rows(&self, condition: &str, limit: u32) -> Result<Vec<ArchivedWebhook>> {
+        let rows: Vec<WebhookRow> = sqlx::query_as(&format!(
+            "SELECT * FROM webhook_event WHERE {} ORDER BY received_at DESC LIMIT $1",
+            condition
+        ))
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        rows.into_iter().map(WebhookRow::into_archived).collect()
+    }
+
+    async fn update_webhook(&self, event_id: &str, statement: &str, error: Option<&str>) -> Result<()> {
+        let id = record_key("webhook_event", event_id)
+            .ok_or_else(|| RsrError::Config(format!("Invalid webhook event ID {}", event_id)))?;
+
+        sqlx::query(statement)
+            .bind(id)
+            .bind(error)
+            .bind(ENGINE_VERSION)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("SQLite update failed", e))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl DocumentStore for SqliteStore {
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("SQLite ping failed", e))?;
+        Ok(())
+    }
+
+    async fn pending_migrations(&self) -> Result<Vec<Migration>> {
+        let applied = self.applied_migrations().await?;
+        migrations::pending(MIGRATIONS, &applied)
+    }
+
+    /// Apply pending migrations, each in its own transaction
+    async fn migrate(&self) -> Result<Vec<Migration>> {
+        let pending = self.pending_migrations().await?;
+        if pending.is_empty() {
+            tracing::info!("SQLite schema is up to date");
+        }
+
+        for migration in &pending {
+            tracing::info!("Applying SQLite migration {} ({})", migration.version, migration.name);
+
+            let failed = |e: sqlx::Error| {
+                DbError::sqlx(format!("SQLite migration {} ({}) failed", migration.version, migration.name), e)
+            };
+            let mut tx = self.pool.begin().await.map_err(failed)?;
+            sqlx::Executor::execute(&mut *tx, sqlx::raw_sql(migration.statements)).await.map_err(failed)?;
+            sqlx::query("INSERT INTO schema_migrations (version, name, checksum, applied_at) VALUES ($1, $2, $3, $4)")
+                .bind(i64::from(migration.version))
+                .bind(migration.name)
+                .bind(migration.checksum())
+                .bind(stamp(chrono::Utc::now()))
+                .execute(&mut *tx)
+                .await
+                .map_err(failed)?;
+            tx.commit().await.map_err(failed)?;
+        }
+
+        Ok(pending)
+    }
+
+    async fn store_compliance(&self, status: &ComplianceStatus) -> Result<String> {
+        tracing::debug!("Storing compliance report for {}", status.repo);
+
+        for attempt in 1..=CHAIN_ATTEMPTS {
+            match self.append_report(status).await {
+                Ok(stored) => {
+                    let id = format!("compliance_report:{}", stored?);
+                    tracing::debug!("Stored compliance report with ID: {}", id);
+                    return Ok(id);
+                }
+                Err(ref e) if lost_race(e) && attempt < CHAIN_ATTEMPTS => {
+                    tracing::debug!("Report chain for {} moved, retrying", status.repo);
+                }
+                Err(e) => return Err(DbError::sqlx("SQLite insert failed", e).into()),
+            }
+        }
+
+        Err(DbError::Backend(format!("Report chain for {} kept moving", status.repo)).into())
+    }
+
+    async fn verify_report_chain(&self, repo: &RepoRef) -> Result<ChainVerification> {
+        let mut verification = ChainVerification::new(repo);
+        let mut after: Option<String> = None;
+
+        loop {
+            let rows: Vec<ReportRow> = sqlx::query_as(&format!(
+                "SELECT {} FROM compliance_report \
+                 WHERE platform = $1 AND owner = $2 AND repo = $3 AND ($4 IS NULL OR created_at > $4) \
+                 ORDER BY created_at LIMIT $5",
+                REPORT_FIELDS
+            ))
+            .bind(&repo.platform)
+            .bind(&repo.owner)
+            .bind(&repo.repo)
+            .bind(&after)
+            .bind(i64::from(CHAIN_PAGE))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(query_failed)?;
+            let page_len = rows.len();
+
+            for row in rows {
+                after = Some(row.created_at.clone());
+                verification.check(&row.into_report()?)?;
+            }
+
+            if page_len < CHAIN_PAGE as usize {
+                return Ok(verification);
+            }
+        }
+    }
+
+    async fn get_compliance_history(
+        &self,
+        platform: &str,
+        owner: &str,
+        repo: &str,
+        limit: u32,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<HistoryPage> {
+        let limit = limit.clamp(1, MAX_HISTORY_PAGE);
+
+        // One extra row tells us whether there is another page
+        let rows: Vec<ReportRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM compliance_report \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 AND deleted_at IS NULL \
+                AND ($4 IS NULL OR created_at < $4) \
+             ORDER BY created_at DESC LIMIT $5",
+            REPORT_FIELDS
+        ))
+        .bind(platform)
+        .bind(owner)
+        .bind(repo)
+        .bind(before.map(stamp))
+        .bind(i64::from(limit) + 1)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        let more = rows.len() > limit as usize;
+        let reports = rows
+            .into_iter()
+            .take(limit as usize)
+            .map(|row| Ok(row.into_report()?.into_status()))
+            .collect::<Result<Vec<ComplianceStatus>>>()?;
+        let next = if more { reports.last().map(|status| status.timestamp) } else { None };
+
+        Ok(HistoryPage { reports, next })
+    }
+
+    async fn store_webhook_event(&self, event: &WebhookEvent) -> Result<String> {
+        tracing::debug!("Archiving webhook event: {}/{}", event.platform, event.event_type);
+
+        let id = insert_webhook_event(&self.pool, event).await?;
+        Ok(record_id("webhook_event", id))
+    }
+
+    async fn get_webhook_event(&self, event_id: &str) -> Result<Option<ArchivedWebhook>> {
+        let Some(id) = record_key("webhook_event", event_id) else {
+            return Ok(None);
+        };
+
+        let row: Option<WebhookRow> = sqlx::query_as("SELECT * FROM webhook_event WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(query_failed)?;
+
+        row.map(WebhookRow::into_archived).transpose()
+    }
+
+    async fn mark_event_processed(&self, event_id: &str) -> Result<()> {
+        self.update_webhook(event_id, "UPDATE webhook_event SET processed = 1, error = NULL WHERE id = $1", None)
+            .await
+    }
+
+    async fn mark_event_failed(&self, event_id: &str, error: &str) -> Result<()> {
+        self.update_webhook(event_id, "UPDATE webhook_event SET processed = 0, error = $2 WHERE id = $1", Some(error))
+            .await
+    }
+
+    async fn get_failed_events(&self, limit: u32) -> Result<Vec<ArchivedWebhook>> {
+        self.webhook_rows("processed = 0 AND error IS NOT NULL AND verification = 'verified'", limit)
+            .await
+    }
+
+    async fn quarantine_event(&self, event_id: &str, error: &str) -> Result<()> {
+        self.update_webhook(
+            event_id,
+            "UPDATE webhook_event SET processed = 0, error = $2, quarantined_by = $3 WHERE id = $1",
+            Some(error),
+        )
+        .await
+    }
+
+    async fn release_quarantine(&self, event_id: &str) -> Result<()> {
+        self.update_webhook(
+            event_id,
+            "UPDATE webhook_event SET error = NULL, quarantined_by = NULL WHERE id = $1 AND quarantined_by IS NOT NULL",
+            None,
+        )
+        .await
+    }
+
+    async fn get_quarantined_events(&self, limit: u32) -> Result<Vec<ArchivedWebhook>> {
+        self.webhook_rows("quarantined_by IS NOT NULL AND processed = 0", limit).await
+    }
+
+    async fn store_webhook_event_with_job(
+        &self,
+        event: &WebhookEvent,
+        queue: &str,
+        job: &BuildJob,
+    ) -> Result<(String, OutboxEntry)> {
+        tracing::debug!("Archiving webhook event with outbox job: {}/{}", event.platform, event.event_type);
+
+        let failed = |e: sqlx::Error| DbError::sqlx("SQLite insert failed", e);
+        let mut tx = self.pool.begin().await.map_err(failed)?;
+
+        let event_id = record_id("webhook_event", insert_webhook_event(&mut *tx, event).await?);
+        let entry = OutboxEntry::new(queue, job(&event_id)?);
+        let row = OutboxRow::from_entry(&entry)?;
+
+        sqlx::query("INSERT INTO outbox (job_id, queue, job, attempts, created_at) VALUES ($1, $2, $3, 0, $4)")
+            .bind(&entry.job.id)
+            .bind(&row.queue)
+            .bind(&row.job)
+            .bind(stamp(entry.created_at))
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+
+        tx.commit().await.map_err(failed)?;
+        Ok((event_id, entry))
+    }
+
+    async fn pending_outbox(&self, limit: u32) -> Result<Vec<OutboxEntry>> {
+        let rows: Vec<(String, String, i32, Option<String>, String)> = sqlx::query_as(
+            "SELECT queue, job, attempts, last_error, created_at FROM outbox ORDER BY created_at LIMIT $1",
+        )
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        rows.into_iter()
+            .map(|(queue, job, attempts, last_error, created_at)| {
+                Ok(OutboxEntry::from(OutboxRow {
+                    queue,
+                    job,
+                    attempts: attempts as u32,
+                    last_error,
+                    created_at: parse_stamp(&created_at)?,
+                }))
+            })
+            .collect()
+    }
+
+    async fn remove_outbox_entry(&self, job_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM outbox WHERE job_id = $1")
+            .bind(job_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("SQLite delete failed", e))?;
+
+        Ok(())
+    }
+
+    async fn mark_outbox_failed(&self, job_id: &str, error: &str) -> Result<()> {
+        sqlx::query("UPDATE outbox SET attempts = attempts + 1, last_error = $2 WHERE job_id = $1")
+            .bind(job_id)
+            .bind(error)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("SQLite update failed", e))?;
+
+        Ok(())
+    }
+
+    async fn transfer_repository(&self, from: &RepoRef, to: &RepoRef) -> Result<()> {
+        tracing::info!("Transferring stored data from {} to {}", from, to);
+
+        let failed = |e: sqlx::Error| DbError::sqlx("SQLite transfer failed", e);
+        let mut tx = self.pool.begin().await.map_err(failed)?;
+
+        // SQLite allows parameters to go unused, so every statement takes the same ones
+        let statements = [
+            "UPDATE compliance_report SET owner = $4, repo = $5 WHERE platform = $1 AND owner = $2 AND repo = $3",
+            "UPDATE report_annotation SET owner = $4, repo = $5 WHERE platform = $1 AND owner = $2 AND repo = $3",
+            "UPDATE repository SET owner = $4, name = $5 WHERE platform = $1 AND owner = $2 AND name = $3",
+            "DELETE FROM ci_run WHERE platform = $1 AND owner = $4 AND repo = $5",
+            "UPDATE ci_run SET owner = $4, repo = $5 WHERE platform = $1 AND owner = $2 AND repo = $3",
+            "UPDATE repo_redirect SET to_owner = $4, to_repo = $5 WHERE platform = $1 AND to_owner = $2 AND to_repo = $3",
+            "DELETE FROM repo_redirect WHERE platform = $1 AND from_owner = $4 AND from_repo = $5",
+            "INSERT INTO repo_redirect (platform, from_owner, from_repo, to_owner, to_repo, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT (platform, from_owner, from_repo) \
+             DO UPDATE SET to_owner = excluded.to_owner, to_repo = excluded.to_repo, created_at = excluded.created_at",
+            "DELETE FROM org_summary WHERE platform = $1 \
+             AND ($2 = owner OR substr($2, 1, length(owner) + 1) = owner || '/' \
+                OR $4 = owner OR substr($4, 1, length(owner) + 1) = owner || '/')",
+        ];
+        let now = stamp(chrono::Utc::now());
+        for statement in statements {
+            sqlx::query(statement)
+                .bind(&from.platform)
+                .bind(&from.owner)
+                .bind(&from.repo)
+                .bind(&to.owner)
+                .bind(&to.repo)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await
+                .map_err(failed)?;
+        }
+        tx.commit().await.map_err(failed)?;
+
+        Ok(())
+    }
+
+    async fn resolve_redirect(&self, platform: &str, owner: &str, repo: &str) -> Result<Option<RepoRef>> {
+        let redirect: Option<(String, String)> = sqlx::query_as(
+            "SELECT to_owner, to_repo FROM repo_redirect WHERE platform = $1 AND from_owner = $2 AND from_repo = $3",
+        )
+        .bind(platform)
+        .bind(owner)
+        .bind(repo)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        Ok(redirect.map(|(to_owner, to_repo)| RepoRef::new(platform, to_owner, to_repo)))
+    }
+
+    async fn record_ci_run(&self, repo: &RepoRef, run: &CiRun) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO ci_run (platform, owner, repo, branch, workflow, commit_sha, conclusion, completed_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8) \
+             ON CONFLICT (platform, owner, repo, branch, workflow) \
+             DO UPDATE SET commit_sha = EXCLUDED.commit_sha, conclusion = EXCLUDED.conclusion, \
+                completed_at = EXCLUDED.completed_at \
+             WHERE ci_run.completed_at <= EXCLUDED.completed_at",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(&run.branch)
+        .bind(&run.workflow)
+        .bind(&run.commit_sha)
+        .bind(text(run.conclusion)?)
+        .bind(stamp(run.completed_at))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("SQLite upsert failed", e))?;
+
+        Ok(())
+    }
+
+    async fn latest_ci_runs(&self, repo: &RepoRef, branch: &str) -> Result<Vec<CiRun>> {
+        let rows: Vec<CiRunRow> = sqlx::query_as(
+            "SELECT workflow, branch, commit_sha, conclusion, completed_at FROM ci_run \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 AND branch = $4 ORDER BY workflow",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(branch)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        let mut runs = Vec::new();
+        for row in rows {
+            runs.extend(row.into_run()?);
+        }
+        Ok(runs)
+    }
+
+    async fn annotate_report(
+        &self,
+        repo: &RepoRef,
+        report_at: chrono::DateTime<chrono::Utc>,
+        annotation: &Annotation,
+    ) -> Result<String> {
+        annotation.validate()?;
+
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO report_annotation \
+                (platform, owner, repo, report_at, kind, check_id, message, reference, author, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) \
+             RETURNING id",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(stamp(report_at))
+        .bind(text(annotation.kind)?)
+        .bind(&annotation.check_id)
+        .bind(&annotation.message)
+        .bind(&annotation.reference)
+        .bind(&annotation.author)
+        .bind(stamp(annotation.created_at))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("SQLite insert failed", e))?;
+
+        Ok(record_id("report_annotation", id))
+    }
+
+    async fn get_annotations(&self, repo: &RepoRef, report_at: chrono::DateTime<chrono::Utc>) -> Result<Vec<Annotation>> {
+        let rows: Vec<AnnotationRow> = sqlx::query_as(
+            "SELECT id, kind, check_id, message, reference, author, created_at FROM report_annotation \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 AND report_at = $4 \
+             ORDER BY created_at",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(stamp(report_at))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(Annotation {
+                    id: Some(record_id("report_annotation", row.id)),
+                    kind: parse_text(&row.kind).unwrap_or(AnnotationKind::Note),
+                    check_id: row.check_id,
+                    message: row.message,
+                    reference: row.reference,
+                    author: row.author,
+                    created_at: parse_stamp(&row.created_at)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn remove_annotation(&self, annotation_id: &str) -> Result<bool> {
+        let Some(id) = record_key("report_annotation", annotation_id) else {
+            return Ok(false);
+        };
+
+        let removed = sqlx::query("DELETE FROM report_annotation WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("SQLite delete failed", e))?;
+        Ok(removed.rows_affected() > 0)
+    }
+
+    async fn record_audit(
+        &self,
+        actor: &str,
+        action: AuditAction,
+        target: &str,
+        details: serde_json::Value,
+    ) -> Result<String> {
+        let details = match details {
+            serde_json::Value::Null => serde_json::json!({}),
+            details => details,
+        };
+
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO audit_event (actor, action, target, details, created_at) VALUES ($1, $2, $3, $4, $5) \
+             RETURNING id",
+        )
+        .bind(actor)
+        .bind(text(action)?)
+        .bind(target)
+        .bind(Json(details))
+        .bind(stamp(chrono::Utc::now()))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("SQLite insert failed", e))?;
+
+        Ok(record_id("audit_event", id))
+    }
+
+    async fn get_audit_events(
+        &self,
+        query: &AuditQuery,
+        limit: u32,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<AuditPage> {
+        let limit = limit.clamp(1, MAX_AUDIT_PAGE);
+
+        let rows: Vec<AuditRow> = sqlx::query_as(
+            "SELECT actor, action, target, details, created_at FROM audit_event \
+             WHERE ($1 IS NULL OR target = $1) \
+                AND ($2 IS NULL OR actor = $2) \
+                AND ($3 IS NULL OR created_at >= $3) \
+                AND ($4 IS NULL OR created_at <= $4) \
+                AND ($5 IS NULL OR created_at < $5) \
+             ORDER BY created_at DESC LIMIT $6",
+        )
+        .bind(query.repo.as_ref().map(repo_target))
+        .bind(&query.actor)
+        .bind(query.since.map(stamp))
+        .bind(query.until.map(stamp))
+        .bind(before.map(stamp))
+        .bind(i64::from(limit) + 1)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        let mut events = rows
+            .into_iter()
+            .map(|row| {
+                let action = parse_text(&row.action)
+                    .ok_or_else(|| DbError::Serialization(format!("Unknown audit action {}", row.action)))?;
+                Ok(AuditEvent {
+                    actor: row.actor,
+                    action,
+                    target: row.target,
+                    details: row.details.0,
+                    created_at: parse_stamp(&row.created_at)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let more = events.len() > limit as usize;
+        events.truncate(limit as usize);
+        let next = if more { events.last().map(|event| event.created_at) } else { None };
+
+        Ok(AuditPage { events, next })
+    }
+
+    async fn register_repository(&self, repo: &RepoRef, adapter: Option<&str>) -> Result<RegisteredRepository> {
+        tracing::info!("Registering {}", repo);
+
+        let row: RegistryRow = sqlx::query_as(
+            "INSERT INTO repository (platform, owner, name, adapter, registered_at) VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (platform, owner, name) DO UPDATE SET \
+                adapter = coalesce(excluded.adapter, repository.adapter), active = 1, \
+                deactivated_at = NULL, deleted_at = NULL \
+             RETURNING *",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(adapter)
+        .bind(stamp(chrono::Utc::now()))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("SQLite upsert failed", e))?;
+
+        row.into_registered()
+    }
+
+    async fn deactivate_repository(&self, repo: &RepoRef) -> Result<bool> {
+        tracing::info!("Deactivating {}", repo);
+
+        let deactivated = sqlx::query(
+            "UPDATE repository SET active = 0, deactivated_at = $4 \
+             WHERE platform = $1 AND owner = $2 AND name = $3 AND active",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(stamp(chrono::Utc::now()))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("SQLite update failed", e))?;
+
+        Ok(deactivated.rows_affected() > 0)
+    }
+
+    async fn mark_repository_deleted(&self, repo: &RepoRef) -> Result<()> {
+        tracing::info!("Marking {} as deleted", repo);
+
+        sqlx::query("UPDATE repository SET deleted_at = $4 WHERE platform = $1 AND owner = $2 AND name = $3")
+            .bind(&repo.platform)
+            .bind(&repo.owner)
+            .bind(&repo.repo)
+            .bind(stamp(chrono::Utc::now()))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("SQLite update failed", e))?;
+
+        Ok(())
+    }
+
+    async fn list_repositories(&self, platform: Option<&str>, owner: Option<&str>) -> Result<Vec<RegisteredRepository>> {
+        let rows: Vec<RegistryRow> = sqlx::query_as(&format!(
+            "SELECT * FROM repository \
+             WHERE active AND deleted_at IS NULL \
+                AND ($1 IS NULL OR platform = $1) \
+                AND ($2 IS NULL OR {}) \
+             ORDER BY platform, owner, name",
+            owner_or_subgroup("owner", 2)
+        ))
+        .bind(platform)
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        rows.into_iter().map(RegistryRow::into_registered).collect()
+    }
+
+    async fn get_repository(&self, repo: &RepoRef) -> Result<Option<RegisteredRepository>> {
+        let row: Option<RegistryRow> =
+            sqlx::query_as("SELECT * FROM repository WHERE platform = $1 AND owner = $2 AND name = $3")
+                .bind(&repo.platform)
+                .bind(&repo.owner)
+                .bind(&repo.repo)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(query_failed)?;
+
+        row.map(RegistryRow::into_registered).transpose()
+    }
+
+    async fn get_compliance_trend(
+        &self,
+        platform: &str,
+        owner: &str,
+        repo: &str,
+        window: TrendWindow,
+    ) -> Result<ComplianceTrend> {
+        if window.since >= window.until {
+            return Err(RsrError::Config("Trend window must start before it ends".to_string()));
+        }
+
+        let rows: Vec<TrendRow> = sqlx::query_as(
+            "SELECT tier, score, created_at FROM compliance_report \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 AND deleted_at IS NULL \
+                AND created_at >= $4 AND created_at < $5 \
+             ORDER BY created_at",
+        )
+        .bind(platform)
+        .bind(owner)
+        .bind(repo)
+        .bind(stamp(window.since))
+        .bind(stamp(window.until))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        // Rows are oldest first, so each bucket opens with its first row
+        let mut buckets: Vec<TrendBucket> = Vec::new();
+        let mut total = 0.0;
+        for row in rows {
+            let start = bucket_start(parse_stamp(&row.created_at)?, window.interval);
+            let tier = stored_tier(&row.tier);
+            match buckets.last_mut() {
+                Some(bucket) if bucket.start == start => {
+                    bucket.reports += 1;
+                    bucket.min_score = bucket.min_score.min(row.score);
+                    bucket.max_score = bucket.max_score.max(row.score);
+                    bucket.closing_tier = tier;
+                    total += row.score;
+                    bucket.avg_score = total / bucket.reports as f32;
+                }
+                _ => {
+                    total = row.score;
+                    buckets.push(TrendBucket {
+                        start,
+                        reports: 1,
+                        min_score: row.score,
+                        max_score: row.score,
+                        avg_score: row.score,
+                        opening_tier: tier,
+                        closing_tier: tier,
+                    });
+                }
+            }
+        }
+
+        Ok(ComplianceTrend {
+            window,
+            transitions: trends::transitions(&buckets),
+            buckets,
+        })
+    }
+
+    async fn get_org_summary(&self, platform: &str, owner: &str) -> Result<OrgSummary> {
+        let fresh_after = stamp(chrono::Utc::now() - chrono::Duration::seconds(SUMMARY_TTL_SECS as i64));
+        let cached: std::result::Result<Option<Json<OrgSummary>>, sqlx::Error> = sqlx::query_scalar(
+            "SELECT summary FROM org_summary WHERE platform = $1 AND owner = $2 AND computed_at > $3",
+        )
+        .bind(platform)
+        .bind(owner)
+        .bind(fresh_after)
+        .fetch_optional(&self.pool)
+        .await;
+        match cached {
+            Ok(Some(summary)) => return Ok(summary.0),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to read cached summary for {}:{}: {}", platform, owner, e),
+        }
+
+        // Latest live report of each repository that is still active
+        let rows: Vec<StandingRow> = sqlx::query_as(&format!(
+            "SELECT owner, repo, tier, score, created_at FROM ( \
+                SELECT owner, repo, tier, score, created_at, \
+                    row_number() OVER (PARTITION BY owner, repo ORDER BY created_at DESC) AS rank \
+                FROM compliance_report report \
+                WHERE platform = $1 AND deleted_at IS NULL AND {} \
+                    AND NOT EXISTS ( \
+                        SELECT 1 FROM repository \
+                        WHERE repository.platform = report.platform AND repository.owner = report.owner \
+                            AND repository.name = report.repo \
+                            AND (NOT repository.active OR repository.deleted_at IS NOT NULL) \
+                    ) \
+             ) WHERE rank = 1",
+            owner_or_subgroup("report.owner", 2)
+        ))
+        .bind(platform)
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(query_failed)?;
+
+        let standings = rows
+            .into_iter()
+            .map(|row| {
+                Ok(RepoStanding {
+                    repo: RepoRef::new(platform, &row.owner, &row.repo),
+                    tier: stored_tier(&row.tier),
+                    score: row.score,
+                    scanned_at: parse_stamp(&row.created_at)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let summary = OrgSummary::from_standings(platform, owner, standings, certification_validity());
+
+        // Caching is best effort; the summary is already computed
+        let cached = sqlx::query(
+            "INSERT INTO org_summary (platform, owner, summary, computed_at) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (platform, owner) DO UPDATE SET summary = excluded.summary, computed_at = excluded.computed_at",
+        )
+        .bind(platform)
+        .bind(owner)
+        .bind(Json(&summary))
+        .bind(stamp(summary.computed_at))
+        .execute(&self.pool)
+        .await;
+        if let Err(e) = cached {
+            tracing::warn!("Failed to cache summary for {}:{}: {}", platform, owner, e);
+        }
+        Ok(summary)
+    }
+
+    /// Every repository is pruned in one statement, ranking reports newest
+    /// first among their repository's live reports as the Postgres store does
+    async fn prune_reports(&self, policy: &RetentionPolicy, dry_run: bool) -> Result<PruneReport> {
+        let started_at = chrono::Utc::now();
+        let mut report = PruneReport {
+            started_at: Some(started_at),
+            dry_run,
+            ..Default::default()
+        };
+        if policy.is_unlimited() {
+            return Ok(report);
+        }
+
+        let older_than = policy
+            .max_age_days
+            .map(|days| started_at - chrono::Duration::days(i64::from(days)));
+        let keep = policy.max_reports.unwrap_or(1);
+
+        let scanned: i64 = sqlx::query_scalar(
+            "SELECT count(*) FROM (SELECT DISTINCT platform, owner, repo FROM compliance_report WHERE deleted_at IS NULL)",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(query_failed)?;
+        report.scanned = scanned as usize;
+
+        let prunable = "WITH ranked AS ( \
+                SELECT digest, badge_issued_at, created_at, \
+                    row_number() OVER (PARTITION BY platform, owner, repo ORDER BY created_at DESC) AS rank \
+                FROM compliance_report WHERE deleted_at IS NULL \
+             ), prunable AS ( \
+                SELECT digest FROM ranked \
+                WHERE rank > $1 AND badge_issued_at IS NULL AND ($2 IS NULL OR created_at < $2) \
+             ) ";
+        let statement = if dry_run {
+            format!(
+                "{}SELECT platform, owner, repo FROM compliance_report WHERE digest IN (SELECT digest FROM prunable)",
+                prunable
+            )
+        } else {
+            format!(
+                "{}UPDATE compliance_report SET deleted_at = $3 WHERE digest IN (SELECT digest FROM prunable) \
+                 RETURNING platform, owner, repo",
+                prunable
+            )
+        };
+        let pruned: Vec<RepoRow> = sqlx::query_as(&statement)
+            .bind(i64::from(keep))
+            .bind(older_than.map(stamp))
+            .bind(stamp(started_at))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| DbError::sqlx("SQLite update failed", e))?;
+
+        report.tombstoned = pruned.len();
+        report.repositories = pruned
+            .iter()
+            .map(|row| (&row.platform, &row.owner, &row.repo))
+            .collect::<HashSet<_>>()
+            .len();
+
+        tracing::info!(
+            "Report pruning{} tombstoned {} reports across {} repositories",
+            if dry_run { " (dry run)" } else { "" },
+            report.tombstoned,
+            report.repositories
+        );
+        Ok(report)
+    }
+
+    async fn mark_badge_issued(&self, repo: &RepoRef, at: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        sqlx::query(
+            "UPDATE compliance_report SET badge_issued_at = $5 \
+             WHERE platform = $1 AND owner = $2 AND repo = $3 AND created_at = $4",
+        )
+        .bind(&repo.platform)
+        .bind(&repo.owner)
+        .bind(&repo.repo)
+        .bind(stamp(at))
+        .bind(stamp(chrono::Utc::now()))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DbError::sqlx("SQLite update failed", e))?;
+
+        Ok(())
+    }
+}
+
+/// Start of the trend bucket `at` falls in: midnight UTC, or midnight on
+/// the Monday of its week
+fn bucket_start(at: chrono::DateTime<chrono::Utc>, interval: TrendInterval) -> chrono::DateTime<chrono::Utc> {
+    let day = at.date_naive();
+    let day = match interval {
+        TrendInterval::Day => day,
+        TrendInterval::Week => day - chrono::Duration::days(i64::from(day.weekday().num_days_from_monday())),
+    };
+    day.and_time(chrono::NaiveTime::MIN).and_utc()
+}
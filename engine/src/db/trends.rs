@@ -0,0 +1,205 @@
+//! Compliance trends
+//!
+//! Daily or weekly aggregates of a repository's stored reports, computed by
+//! SurrealDB so charting a trend line doesn't mean pulling every report.
+
+use super::documents::stored_tier;
+#[cfg(feature = "documents-surrealdb")]
+use super::documents::SurrealPool;
+#[cfg(feature = "documents-surrealdb")]
+use super::error::DbError;
+use crate::CertificationTier;
+#[cfg(feature = "documents-surrealdb")]
+use crate::{Result, RsrError};
+use serde::{Deserialize, Serialize};
+
+/// Most buckets returned for one trend
+pub const MAX_TREND_BUCKETS: u32 = 366;
+
+/// Width of a trend bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendInterval {
+    #[default]
+    Day,
+    /// Weeks start on Monday
+    Week,
+}
+
+impl TrendInterval {
+    fn duration(&self) -> chrono::Duration {
+        match self {
+            Self::Day => chrono::Duration::days(1),
+            Self::Week => chrono::Duration::weeks(1),
+        }
+    }
+
+    #[cfg(feature = "documents-surrealdb")]
+    /// Bucket width and offset from the Unix epoch as SurrealQL durations.
+    /// The epoch was a Thursday, so weekly buckets are shifted to Monday.
+    fn surql(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::Day => ("1d", "0d"),
+            Self::Week => ("1w", "4d"),
+        }
+    }
+}
+
+/// Time range of a trend, split into buckets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TrendWindow {
+    pub interval: TrendInterval,
+    pub since: chrono::DateTime<chrono::Utc>,
+    pub until: chrono::DateTime<chrono::Utc>,
+}
+
+impl TrendWindow {
+    /// The last `buckets` intervals up to now, capped at [`MAX_TREND_BUCKETS`]
+    pub fn last(interval: TrendInterval, buckets: u32) -> Self {
+        let until = chrono::Utc::now();
+        let since = until - interval.duration() * buckets.clamp(1, MAX_TREND_BUCKETS) as i32;
+        Self { interval, since, until }
+    }
+}
+
+/// Aggregates over the reports stored in one bucket
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendBucket {
+    /// Start of the bucket
+    pub start: chrono::DateTime<chrono::Utc>,
+    pub reports: usize,
+    pub min_score: f32,
+    pub max_score: f32,
+    pub avg_score: f32,
+    /// Tier of the first report in the bucket
+    pub opening_tier: CertificationTier,
+    /// Tier of the last report in the bucket
+    pub closing_tier: CertificationTier,
+}
+
+/// A change of tier between consecutive reports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TierTransition {
+    /// Start of the bucket the change shows up in
+    pub bucket: chrono::DateTime<chrono::Utc>,
+    pub from: CertificationTier,
+    pub to: CertificationTier,
+}
+
+/// A repository's compliance trend. Buckets without reports are left out.
+#[derive(Debug, Clone, Serialize)]
+pub struct ComplianceTrend {
+    pub window: TrendWindow,
+    pub buckets: Vec<TrendBucket>,
+    pub transitions: Vec<TierTransition>,
+}
+
+/// Bucket as aggregated by SurrealDB
+#[derive(Debug, Deserialize)]
+struct BucketRow {
+    bucket: chrono::DateTime<chrono::Utc>,
+    reports: usize,
+    min_score: f32,
+    max_score: f32,
+    avg_score: f32,
+    opening_tier: String,
+    closing_tier: String,
+}
+
+impl From<BucketRow> for TrendBucket {
+    fn from(row: BucketRow) -> Self {
+        Self {
+            start: row.bucket,
+            reports: row.reports,
+            min_score: row.min_score,
+            max_score: row.max_score,
+            avg_score: row.avg_score,
+            opening_tier: stored_tier(&row.opening_tier),
+            closing_tier: stored_tier(&row.closing_tier),
+        }
+    }
+}
+
+/// Tier changes within and between buckets. A bucket whose reports moved
+/// through several tiers shows only the change from its first to its last.
+pub(super) fn transitions(buckets: &[TrendBucket]) -> Vec<TierTransition> {
+    let mut transitions = Vec::new();
+    let mut previous: Option<CertificationTier> = None;
+
+    for bucket in buckets {
+        let mut from = previous.unwrap_or(bucket.opening_tier);
+        for to in [bucket.opening_tier, bucket.closing_tier] {
+            if to != from {
+                transitions.push(TierTransition {
+                    bucket: bucket.start,
+                    from,
+                    to,
+                });
+                from = to;
+            }
+        }
+        previous = Some(bucket.closing_tier);
+    }
+
+    transitions
+}
+
+#[cfg(feature = "documents-surrealdb")]
+impl SurrealPool {
+    /// Aggregate a repository's reports in `window` into daily or weekly
+    /// buckets, oldest first
+    pub async fn get_compliance_trend(
+        &self,
+        platform: &str,
+        owner: &str,
+        repo: &str,
+        window: TrendWindow,
+    ) -> Result<ComplianceTrend> {
+        if window.since >= window.until {
+            return Err(RsrError::Config("Trend window must start before it ends".to_string()));
+        }
+        let (interval, offset) = window.interval.surql();
+
+        // Reports are sorted before grouping so each bucket's first and last
+        // tier are its opening and closing tiers
+        let mut result = self
+            .client()
+            .query(format!(
+                "SELECT <string> bucket AS bucket, reports, min_score, max_score, avg_score, opening_tier, closing_tier \
+                 FROM ( \
+                    SELECT bucket, count() AS reports, math::min(score) AS min_score, math::max(score) AS max_score, \
+                        math::mean(score) AS avg_score, array::first(tier) AS opening_tier, \
+                        array::last(tier) AS closing_tier \
+                    FROM ( \
+                        SELECT score, tier, created_at, time::floor(created_at - {offset}, {interval}) + {offset} AS bucket \
+                        FROM compliance_report \
+                        WHERE platform = $platform AND owner = $owner AND repo = $repo AND deleted_at = NONE \
+                            AND created_at >= <datetime> $since AND created_at < <datetime> $until \
+                        ORDER BY created_at \
+                    ) \
+                    GROUP BY bucket \
+                 ) \
+                 ORDER BY bucket",
+                offset = offset,
+                interval = interval
+            ))
+            .bind(("platform", platform.to_string()))
+            .bind(("owner", owner.to_string()))
+            .bind(("repo", repo.to_string()))
+            .bind(("since", window.since.to_rfc3339()))
+            .bind(("until", window.until.to_rfc3339()))
+            .await
+            .map_err(|e| DbError::surreal("SurrealDB query failed", e))?;
+
+        let rows: Vec<BucketRow> = result
+            .take(0)
+            .map_err(|e| DbError::surreal("SurrealDB take failed", e))?;
+        let buckets: Vec<TrendBucket> = rows.into_iter().map(TrendBucket::from).collect();
+
+        Ok(ComplianceTrend {
+            window,
+            transitions: transitions(&buckets),
+            buckets,
+        })
+    }
+}
@@ -0,0 +1,49 @@
+//! `Cargo.lock`
+
+use super::{dependency, CRATES};
+use crate::db::graphs::Dependency;
+use std::collections::HashSet;
+
+/// Registry packages in a lockfile. Packages without a `source` are the
+/// workspace's own crates; what they list is direct. Git and path
+/// dependencies are skipped.
+pub(super) fn parse_lockfile(lockfile: &str) -> Vec<Dependency> {
+    let Ok(lock) = lockfile.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+    let packages: Vec<&toml::Table> = lock
+        .get("package")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(toml::Value::as_table)
+        .collect();
+
+    // Entries are `name`, or `name version` when more than one version is locked
+    let mut direct: HashSet<(&str, Option<&str>)> = HashSet::new();
+    for member in packages.iter().filter(|package| !package.contains_key("source")) {
+        let listed = member.get("dependencies").and_then(toml::Value::as_array).into_iter().flatten();
+        for entry in listed.filter_map(toml::Value::as_str) {
+            let mut parts = entry.split_whitespace();
+            if let Some(name) = parts.next() {
+                direct.insert((name, parts.next()));
+            }
+        }
+    }
+
+    packages
+        .iter()
+        .filter(|package| {
+            package
+                .get("source")
+                .and_then(toml::Value::as_str)
+                .is_some_and(|source| source.starts_with("registry+") || source.starts_with("sparse+"))
+        })
+        .filter_map(|package| {
+            let name = package.get("name")?.as_str()?;
+            let version = package.get("version")?.as_str()?;
+            let is_direct = direct.contains(&(name, None)) || direct.contains(&(name, Some(version)));
+            Some(dependency(CRATES, name, version, is_direct))
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+//! `go.sum` and `go.mod`
+
+use super::{dependency, GO};
+use crate::db::graphs::Dependency;
+use std::collections::HashMap;
+
+/// Modules with checksums in `go.sum`. Requirements in `go.mod` not marked
+/// `// indirect` are direct, at the version `go.mod` selects; `go.sum` can
+/// list several versions of one module, of which the last is kept otherwise.
+pub(super) fn parse_go_sum(sum: &str, go_mod: Option<&str>) -> Vec<Dependency> {
+    let required: HashMap<String, (String, bool)> = go_mod
+        .map(requirements)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(module, version, direct)| (module, (version, direct)))
+        .collect();
+
+    let mut order: Vec<&str> = Vec::new();
+    let mut versions: HashMap<&str, &str> = HashMap::new();
+    for line in sum.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(module), Some(version)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        // `/go.mod` lines only pin the module's own go.mod, for version selection
+        if version.ends_with("/go.mod") {
+            continue;
+        }
+        if versions.insert(module, version).is_none() {
+            order.push(module);
+        }
+    }
+
+    order
+        .into_iter()
+        .map(|module| match required.get(module) {
+            Some((version, true)) => dependency(GO, module, version, true),
+            _ => dependency(GO, module, versions[module], false),
+        })
+        .collect()
+}
+
+/// Modules `go.mod` requires, for repositories without a `go.sum`
+pub(super) fn parse_go_mod(go_mod: &str) -> Vec<Dependency> {
+    requirements(go_mod)
+        .into_iter()
+        .map(|(module, version, direct)| dependency(GO, &module, &version, direct))
+        .collect()
+}
+
+/// `require` directives, single-line or in blocks, as (module, version, direct)
+fn requirements(go_mod: &str) -> Vec<(String, String, bool)> {
+    let mut found = Vec::new();
+    let mut in_block = false;
+    for line in go_mod.lines() {
+        let (code, comment) = line.split_once("//").unwrap_or((line, ""));
+        let code = code.trim();
+        let spec = if in_block {
+            if code == ")" {
+                in_block = false;
+                continue;
+            }
+            code
+        } else if let Some(rest) = code.strip_prefix("require") {
+            let rest = rest.trim();
+            if rest == "(" {
+                in_block = true;
+                continue;
+            }
+            rest
+        } else {
+            continue;
+        };
+
+        let mut fields = spec.split_whitespace();
+        if let (Some(module), Some(version)) = (fields.next(), fields.next()) {
+            let direct = comment.trim() != "indirect";
+            found.push((module.to_string(), version.to_string(), direct));
+        }
+    }
+    found
+}
@@ -0,0 +1,68 @@
+//! `channels.scm` and `manifest.scm`
+//!
+//! Guix has no lockfile: reproducing a build means pinning the channels
+//! (`guix describe -f channels > channels.scm`) and listing the packages in
+//! a manifest. Both are Scheme, read here for their literals rather than
+//! evaluated.
+
+use super::{dependency, GUIX};
+use crate::db::graphs::Dependency;
+
+/// Channels, all direct, at the commit they are pinned to (empty if they
+/// follow a branch)
+pub(super) fn parse_channels(channels: &str) -> Vec<Dependency> {
+    channels
+        .split("(channel")
+        .skip(1)
+        .filter_map(|channel| {
+            let name = field(channel, "name")?.trim_start_matches('\'');
+            let commit = field(channel, "commit").unwrap_or("");
+            Some(dependency(GUIX, name, commit.trim_matches('"'), true))
+        })
+        .collect()
+}
+
+/// Packages a manifest asks for with `specifications->manifest`, all direct.
+/// Specifications are `name`, `name@version` or either with `:output`.
+pub(super) fn parse_manifest(manifest: &str) -> Vec<Dependency> {
+    let Some((_, specifications)) = manifest.split_once("specifications->manifest") else {
+        return Vec::new();
+    };
+
+    string_literals(specifications)
+        .map(|specification| {
+            let package = specification.split(':').next().unwrap_or(specification);
+            let (name, version) = package.split_once('@').unwrap_or((package, ""));
+            dependency(GUIX, name, version, true)
+        })
+        .collect()
+}
+
+/// The value of `(key value)` in an S-expression, unparsed. `guix describe`
+/// puts long values on the line after their key.
+fn field<'a>(sexp: &'a str, key: &str) -> Option<&'a str> {
+    let opening = format!("({}", key);
+    let (start, _) = sexp
+        .match_indices(&opening)
+        .find(|(index, _)| sexp[index + opening.len()..].starts_with(char::is_whitespace))?;
+    let value = sexp[start + opening.len()..].trim_start();
+    let end = value.find(')').unwrap_or(value.len());
+    Some(value[..end].trim())
+}
+
+/// Contents of the double-quoted strings in `source`, up to its closing paren
+fn string_literals(source: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0i32;
+    let end = source
+        .char_indices()
+        .find(|&(_, c)| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth < 0
+        })
+        .map_or(source.len(), |(index, _)| index);
+    source[..end].split('"').skip(1).step_by(2).filter(|literal| !literal.is_empty())
+}
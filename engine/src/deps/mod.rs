@@ -0,0 +1,160 @@
+//! Dependency manifests
+//!
+//! Parses the lockfiles committed at a repository's root into
+//! [`Dependency`] records and stores them in the graph, so impact analysis
+//! works from what repositories actually depend on. Supported:
+//! - `Cargo.lock` (crates)
+//! - `package-lock.json` and `pnpm-lock.yaml` (npm)
+//! - `go.sum`, with `go.mod` telling direct requirements apart (go)
+//! - `requirements.txt` and `poetry.lock`, with `pyproject.toml` (pypi)
+//! - `Gemfile.lock` (rubygems)
+//! - `flake.lock` (nix: flake inputs, named `owner/repo` or by URL)
+//! - `channels.scm` and `manifest.scm` (guix: pinned channels and the
+//!   packages a manifest asks for)
+//!
+//! Lockfiles list transitive dependencies as well; those are kept with
+//! `direct: false` and a depth of 2, since lockfiles don't record how deep
+//! they are. Only direct dependencies become graph edges - the rest are
+//! reached through the repositories their packages are developed in.
+
+mod cargo;
+mod go;
+mod guix;
+mod nix;
+mod npm;
+mod python;
+mod ruby;
+
+use crate::adapters::PlatformAdapter;
+use crate::db::graphs::{Dependency, DependencySnapshot, GraphStore};
+use crate::{RepoRef, Result, RsrError};
+use std::collections::HashMap;
+
+/// Lockfiles read from a repository's root
+pub const LOCKFILES: [&str; 9] = [
+    "Cargo.lock",
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "go.sum",
+    "requirements.txt",
+    "poetry.lock",
+    "Gemfile.lock",
+    "flake.lock",
+    "channels.scm",
+];
+
+/// Manifests read alongside the lockfiles to tell direct dependencies apart
+pub const MANIFESTS: [&str; 4] = ["package.json", "go.mod", "pyproject.toml", "manifest.scm"];
+
+/// Registry names, as used in package keys
+pub const CRATES: &str = "crates";
+pub const NPM: &str = "npm";
+pub const GO: &str = "go";
+pub const PYPI: &str = "pypi";
+pub const RUBYGEMS: &str = "rubygems";
+pub const NIX: &str = "nix";
+pub const GUIX: &str = "guix";
+
+/// Dependencies listed by the lockfiles `read` returns the contents of. A
+/// package listed by more than one lockfile is reported once, as direct if
+/// any of them says so.
+pub fn parse(read: impl Fn(&str) -> Option<String>) -> Vec<Dependency> {
+    let mut found = Vec::new();
+    if let Some(lock) = read("Cargo.lock") {
+        found.extend(cargo::parse_lockfile(&lock));
+    }
+    let package_json = read("package.json");
+    if let Some(lock) = read("package-lock.json") {
+        found.extend(npm::parse_package_lock(&lock, package_json.as_deref()));
+    }
+    if let Some(lock) = read("pnpm-lock.yaml") {
+        found.extend(npm::parse_pnpm_lock(&lock));
+    }
+    let go_mod = read("go.mod");
+    if let Some(sum) = read("go.sum") {
+        found.extend(go::parse_go_sum(&sum, go_mod.as_deref()));
+    } else if let Some(ref go_mod) = go_mod {
+        found.extend(go::parse_go_mod(go_mod));
+    }
+    if let Some(requirements) = read("requirements.txt") {
+        found.extend(python::parse_requirements(&requirements));
+    }
+    if let Some(lock) = read("poetry.lock") {
+        found.extend(python::parse_poetry_lock(&lock, read("pyproject.toml").as_deref()));
+    }
+    if let Some(lock) = read("Gemfile.lock") {
+        found.extend(ruby::parse_gemfile_lock(&lock));
+    }
+    if let Some(lock) = read("flake.lock") {
+        found.extend(nix::parse_flake_lock(&lock));
+    }
+    if let Some(channels) = read("channels.scm") {
+        found.extend(guix::parse_channels(&channels));
+    }
+    if let Some(manifest) = read("manifest.scm") {
+        found.extend(guix::parse_manifest(&manifest));
+    }
+
+    let mut merged: Vec<Dependency> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    for dependency in found {
+        let key = (dependency.registry.clone(), dependency.name.clone());
+        match index.get(&key) {
+            Some(&i) if dependency.direct && !merged[i].direct => merged[i] = dependency,
+            Some(_) => {}
+            None => {
+                index.insert(key, merged.len());
+                merged.push(dependency);
+            }
+        }
+    }
+    merged
+}
+
+/// Dependencies in one lockfile or manifest, named as at a repository's
+/// root. Where [`parse`] skips a file it can't read, this fails on JSON or
+/// TOML that doesn't parse, so malformed input shows up when checking a
+/// single file or fuzzing.
+pub fn parse_file(name: &str, contents: &str) -> Result<Vec<Dependency>> {
+    if !LOCKFILES.contains(&name) && !MANIFESTS.contains(&name) {
+        return Err(RsrError::Parse(format!("{} is not a supported lockfile or manifest", name)));
+    }
+    match name {
+        "package-lock.json" | "package.json" | "flake.lock" => {
+            serde_json::from_str::<serde_json::Value>(contents)?;
+        }
+        "Cargo.lock" | "poetry.lock" | "pyproject.toml" => {
+            contents
+                .parse::<toml::Table>()
+                .map_err(|e| RsrError::Parse(format!("{}: {}", name, e)))?;
+        }
+        _ => {}
+    }
+    Ok(parse(|file| (file == name).then(|| contents.to_string())))
+}
+
+/// Fetch and parse the lockfiles at the root of `repo` (at `repo.branch`, or
+/// its default branch)
+pub async fn fetch(adapter: &dyn PlatformAdapter, repo: &RepoRef) -> Result<Vec<Dependency>> {
+    let paths: Vec<&str> = LOCKFILES.iter().chain(MANIFESTS.iter()).copied().collect();
+    let files = adapter.fetch_files(repo, &paths).await?;
+    Ok(parse(|name| files.get(name).and_then(|bytes| String::from_utf8(bytes.clone()).ok())))
+}
+
+/// Replace the direct dependencies the graph records for `repo` with those
+/// its lockfiles list, registering the repository if needed
+pub async fn ingest(adapter: &dyn PlatformAdapter, repo: &RepoRef, graphs: &dyn GraphStore) -> Result<DependencySnapshot> {
+    let dependencies = fetch(adapter, repo).await?;
+    let repo_key = graphs.register_repository(&repo.platform, &repo.owner, &repo.repo).await?;
+    graphs.upsert_dependency_snapshot(&repo_key, dependencies).await
+}
+
+fn dependency(registry: &str, name: &str, version: &str, direct: bool) -> Dependency {
+    Dependency {
+        registry: registry.to_string(),
+        name: name.to_string(),
+        version: version.to_string(),
+        depth: if direct { 1 } else { 2 },
+        direct,
+    }
+}
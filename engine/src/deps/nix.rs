@@ -0,0 +1,47 @@
+//! `flake.lock`
+
+use super::{dependency, NIX};
+use crate::db::graphs::Dependency;
+use std::collections::HashSet;
+
+/// Flake inputs locked to a revision. Inputs hosted on a forge are named
+/// `owner/repo`, others by their URL; `path` inputs are skipped. Inputs the
+/// root flake lists are direct, the rest come in through other inputs.
+pub(super) fn parse_flake_lock(lockfile: &str) -> Vec<Dependency> {
+    let Ok(lock) = serde_json::from_str::<serde_json::Value>(lockfile) else {
+        return Vec::new();
+    };
+    let Some(nodes) = lock["nodes"].as_object() else {
+        return Vec::new();
+    };
+    let root = lock["root"].as_str().unwrap_or("root");
+
+    // Inputs given as a path of names `follow` another input rather than adding one
+    let direct: HashSet<&str> = nodes
+        .get(root)
+        .and_then(|node| node["inputs"].as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(_, target)| target.as_str())
+        .collect();
+
+    nodes
+        .iter()
+        .filter(|(key, _)| key.as_str() != root)
+        .filter_map(|(key, node)| {
+            let locked = node.get("locked")?;
+            let name = match locked["type"].as_str()? {
+                "github" | "gitlab" | "sourcehut" => {
+                    format!("{}/{}", locked["owner"].as_str()?, locked["repo"].as_str()?)
+                }
+                "path" => return None,
+                _ => locked["url"].as_str()?.to_string(),
+            };
+            let version = locked["rev"]
+                .as_str()
+                .or_else(|| locked["narHash"].as_str())
+                .unwrap_or("");
+            Some(dependency(NIX, &name, version, direct.contains(key.as_str())))
+        })
+        .collect()
+}
@@ -0,0 +1,159 @@
+//! `package-lock.json` and `pnpm-lock.yaml`
+
+use super::{dependency, NPM};
+use crate::db::graphs::Dependency;
+use serde_json::Value;
+use std::collections::HashSet;
+
+const DEPENDENCY_FIELDS: [&str; 3] = ["dependencies", "devDependencies", "optionalDependencies"];
+
+/// Packages locked by npm. Lockfile versions 2 and 3 record what the root
+/// package depends on; version 1 doesn't, so `package.json` is consulted.
+/// Linked workspace packages and non-registry versions (git, file, aliases)
+/// are skipped.
+pub(super) fn parse_package_lock(lockfile: &str, package_json: Option<&str>) -> Vec<Dependency> {
+    let Ok(lock) = serde_json::from_str::<Value>(lockfile) else {
+        return Vec::new();
+    };
+
+    if let Some(packages) = lock.get("packages").and_then(Value::as_object) {
+        let direct = listed_names(packages.get("").unwrap_or(&Value::Null));
+        return packages
+            .iter()
+            .filter(|(path, entry)| !path.is_empty() && entry.get("link").and_then(Value::as_bool) != Some(true))
+            .filter_map(|(path, entry)| {
+                let installed = path.rsplit_once("node_modules/").map(|(_, name)| name)?;
+                let name = entry.get("name").and_then(Value::as_str).unwrap_or(installed);
+                let version = registry_version(entry.get("version"))?;
+                let top_level = path.as_str() == format!("node_modules/{}", name);
+                Some(dependency(NPM, name, version, top_level && direct.contains(name)))
+            })
+            .collect();
+    }
+
+    let direct = package_json
+        .and_then(|manifest| serde_json::from_str::<Value>(manifest).ok())
+        .map(|manifest| listed_names(&manifest))
+        .unwrap_or_default();
+    let mut found = Vec::new();
+    if let Some(dependencies) = lock.get("dependencies").and_then(Value::as_object) {
+        for (name, entry) in dependencies {
+            collect_v1(name, entry, direct.contains(name.as_str()), &mut found);
+        }
+    }
+    found
+}
+
+/// Version 1 nests dependencies that couldn't be hoisted under their dependent
+fn collect_v1(name: &str, entry: &Value, direct: bool, found: &mut Vec<Dependency>) {
+    if let Some(version) = registry_version(entry.get("version")) {
+        found.push(dependency(NPM, name, version, direct));
+    }
+    if let Some(nested) = entry.get("dependencies").and_then(Value::as_object) {
+        for (name, entry) in nested {
+            collect_v1(name, entry, false, found);
+        }
+    }
+}
+
+/// Names a package's manifest (or the root entry of a lockfile) depends on
+fn listed_names(manifest: &Value) -> HashSet<String> {
+    DEPENDENCY_FIELDS
+        .iter()
+        .filter_map(|field| manifest.get(field).and_then(Value::as_object))
+        .flat_map(|listed| listed.keys().cloned())
+        .collect()
+}
+
+/// Registry versions start with a digit; git URLs, file paths and
+/// `npm:` aliases don't
+fn registry_version(version: Option<&Value>) -> Option<&str> {
+    version
+        .and_then(Value::as_str)
+        .filter(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Packages locked by pnpm. The subset of YAML pnpm writes is read line by
+/// line: `packages` keys name every locked package, and the root importer
+/// (or, before lockfile version 9 in single-package repositories, the
+/// top-level dependency sections) lists the direct ones.
+pub(super) fn parse_pnpm_lock(lockfile: &str) -> Vec<Dependency> {
+    let mut legacy_keys = false;
+    let mut section = "";
+    let mut importer = "";
+    let mut in_group = false;
+    let mut direct: HashSet<String> = HashSet::new();
+    let mut locked: Vec<(String, String)> = Vec::new();
+
+    for line in lockfile.lines() {
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - content.len();
+        let key = yaml_key(content);
+
+        match (section, indent) {
+            (_, 0) => {
+                section = key;
+                if key == "lockfileVersion" {
+                    // Version 5 keys are `/name/version` rather than `/name@version`
+                    let value = content.split_once(':').map_or("", |(_, value)| value);
+                    legacy_keys = value.trim().trim_matches(|c| c == '\'' || c == '"').starts_with('5');
+                }
+            }
+            ("importers", 2) => importer = key,
+            ("importers", 4) => in_group = DEPENDENCY_FIELDS.contains(&key),
+            ("importers", 6) if in_group && importer == "." => {
+                direct.insert(key.to_string());
+            }
+            ("dependencies" | "devDependencies" | "optionalDependencies", 2) => {
+                direct.insert(key.to_string());
+            }
+            ("packages", 2) => {
+                if let Some(package) = pnpm_package(key, legacy_keys) {
+                    locked.push(package);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    locked
+        .into_iter()
+        .map(|(name, version)| {
+            let is_direct = direct.contains(&name);
+            dependency(NPM, &name, &version, is_direct)
+        })
+        .collect()
+}
+
+/// Key of a `key:` or `key: value` line, unquoted
+fn yaml_key(content: &str) -> &str {
+    if let Some(quote) = content.chars().next().filter(|c| *c == '\'' || *c == '"') {
+        let rest = &content[1..];
+        return rest.find(quote).map_or(rest, |end| &rest[..end]);
+    }
+    match content.find(": ") {
+        Some(end) => &content[..end],
+        None => content.strip_suffix(':').unwrap_or(content),
+    }
+}
+
+/// Name and version of a `packages` key: `name@version` (version 9),
+/// `/name@version` (version 6) or `/name/version` (version 5), each with an
+/// optional peer dependency suffix
+fn pnpm_package(key: &str, legacy_keys: bool) -> Option<(String, String)> {
+    let key = key.strip_prefix('/').unwrap_or(key);
+    let key = key.split('(').next().unwrap_or(key);
+    let (name, version) = if legacy_keys {
+        let (name, version) = key.rsplit_once('/')?;
+        (name, version.split('_').next().unwrap_or(version))
+    } else {
+        key.rsplit_once('@')?
+    };
+    if name.is_empty() || !version.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((name.to_string(), version.to_string()))
+}
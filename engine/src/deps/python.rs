@@ -0,0 +1,133 @@
+//! `requirements.txt`, `poetry.lock` and `pyproject.toml`
+
+use super::{dependency, PYPI};
+use crate::db::graphs::Dependency;
+use std::collections::HashSet;
+
+/// Requirements, all taken as direct: a requirements file can't say which
+/// entries were pinned by hand and which by `pip freeze`. Versions are only
+/// recorded for `==` pins. Options, includes, editable installs, URLs and
+/// local paths are skipped.
+pub(super) fn parse_requirements(requirements: &str) -> Vec<Dependency> {
+    requirements
+        .lines()
+        .filter_map(|line| {
+            let line = match line.find(" #") {
+                Some(comment) => &line[..comment],
+                None => line,
+            }
+            .trim();
+            if line.is_empty()
+                || line.starts_with(['#', '-', '.', '/'])
+                || line.contains("://")
+                || line.contains(" @ ")
+            {
+                return None;
+            }
+            let (name, version) = requirement(line)?;
+            Some(dependency(PYPI, &name, version, true))
+        })
+        .collect()
+}
+
+/// Packages locked by Poetry. Those `pyproject.toml` lists - under
+/// `[tool.poetry]` or in `[project]` - are direct.
+pub(super) fn parse_poetry_lock(lockfile: &str, pyproject: Option<&str>) -> Vec<Dependency> {
+    let Ok(lock) = lockfile.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+    let direct = pyproject
+        .and_then(|manifest| manifest.parse::<toml::Table>().ok())
+        .map(|manifest| declared_names(&manifest))
+        .unwrap_or_default();
+
+    lock.get("package")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|package| {
+            let name = normalize(package.get("name")?.as_str()?);
+            let version = package.get("version")?.as_str()?;
+            let is_direct = direct.contains(&name);
+            Some(dependency(PYPI, &name, version, is_direct))
+        })
+        .collect()
+}
+
+/// Normalized names of the packages `pyproject.toml` depends on
+fn declared_names(manifest: &toml::Table) -> HashSet<String> {
+    let mut names = HashSet::new();
+
+    let poetry = manifest.get("tool").and_then(|tool| tool.get("poetry"));
+    let mut tables: Vec<&toml::Value> = ["dependencies", "dev-dependencies"]
+        .iter()
+        .filter_map(|field| poetry.and_then(|poetry| poetry.get(field)))
+        .collect();
+    if let Some(groups) = poetry.and_then(|poetry| poetry.get("group")).and_then(toml::Value::as_table) {
+        tables.extend(groups.values().filter_map(|group| group.get("dependencies")));
+    }
+    for table in tables.into_iter().filter_map(toml::Value::as_table) {
+        names.extend(table.keys().filter(|name| name.as_str() != "python").map(|name| normalize(name)));
+    }
+
+    let project = manifest.get("project");
+    let mut specs: Vec<&toml::Value> = project
+        .and_then(|project| project.get("dependencies"))
+        .and_then(toml::Value::as_array)
+        .map(|listed| listed.iter().collect())
+        .unwrap_or_default();
+    if let Some(optional) = project
+        .and_then(|project| project.get("optional-dependencies"))
+        .and_then(toml::Value::as_table)
+    {
+        specs.extend(optional.values().filter_map(toml::Value::as_array).flatten());
+    }
+    names.extend(
+        specs
+            .into_iter()
+            .filter_map(toml::Value::as_str)
+            .filter_map(|spec| requirement(spec).map(|(name, _)| name)),
+    );
+
+    names
+}
+
+/// Normalized name and pinned version (empty unless pinned with `==`) of a
+/// PEP 508 requirement
+fn requirement(spec: &str) -> Option<(String, &str)> {
+    let spec = spec.split(';').next().unwrap_or(spec).trim();
+    let end = spec
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(spec.len());
+    let name = &spec[..end];
+    if name.is_empty() {
+        return None;
+    }
+
+    let rest = spec[end..].trim_start();
+    let rest = match rest.strip_prefix('[') {
+        Some(extras) => extras.split_once(']').map_or("", |(_, rest)| rest).trim_start(),
+        None => rest,
+    };
+    let version = rest
+        .strip_prefix("==")
+        .map(str::trim)
+        .filter(|version| !version.contains([',', '*']) && !version.starts_with('='))
+        .unwrap_or("");
+    Some((normalize(name), version))
+}
+
+/// PEP 503 normalized name: lowercase, runs of `-`, `_` and `.` as one `-`
+fn normalize(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+    normalized
+}
@@ -0,0 +1,47 @@
+//! `Gemfile.lock`
+
+use super::{dependency, RUBYGEMS};
+use crate::db::graphs::Dependency;
+use std::collections::HashSet;
+
+/// Gems locked from a `GEM` source. `DEPENDENCIES` lists what the Gemfile
+/// asks for, which is direct; gems from `GIT` and `PATH` sources are skipped.
+pub(super) fn parse_gemfile_lock(lockfile: &str) -> Vec<Dependency> {
+    let mut section = "";
+    let mut in_specs = false;
+    let mut locked: Vec<(&str, &str)> = Vec::new();
+    let mut direct: HashSet<&str> = HashSet::new();
+
+    for line in lockfile.lines() {
+        let content = line.trim_start();
+        if content.is_empty() {
+            continue;
+        }
+        let indent = line.len() - content.len();
+        if indent == 0 {
+            section = content;
+            in_specs = false;
+            continue;
+        }
+
+        match (section, indent) {
+            ("GEM", 2) => in_specs = content == "specs:",
+            // `name (version)`; gems they depend on follow, indented further
+            ("GEM", 4) if in_specs => {
+                if let Some((name, version)) = content.split_once(" (") {
+                    locked.push((name, version.trim_end_matches(')')));
+                }
+            }
+            ("DEPENDENCIES", 2) => {
+                let name = content.split_whitespace().next().unwrap_or(content);
+                direct.insert(name.trim_end_matches('!'));
+            }
+            _ => {}
+        }
+    }
+
+    locked
+        .into_iter()
+        .map(|(name, version)| dependency(RUBYGEMS, name, version, direct.contains(name)))
+        .collect()
+}
@@ -0,0 +1,348 @@
+//! Repository discovery from package registries
+//!
+//! Given a package name on crates.io or npm, the registry's metadata names
+//! the repository the package is developed in. Discovery registers that
+//! repository in the graph and links the package vertex to it, so "is my
+//! dependency's repository certified?" can be answered by package name.
+//!
+//! Discovery runs as jobs on its own queue: registry lookups are slow and
+//! rate limited, and nothing waits on the result.
+
+use crate::adapters::http::{HttpLayer, RetryPolicy, SendVia};
+use crate::db::queue::{JobPayload, JobPriority, NewJob};
+use crate::db::DatabasePool;
+use crate::hierarchy::HierarchyConfig;
+use crate::worker::JobHandler;
+use crate::{RepoRef, Result, RsrError};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Queue discovery jobs are placed on
+pub const DISCOVERY_QUEUE: &str = "discovery";
+
+/// Package registries repositories can be discovered from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageRegistry {
+    Crates,
+    Npm,
+}
+
+impl PackageRegistry {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Crates => "crates",
+            Self::Npm => "npm",
+        }
+    }
+}
+
+impl std::str::FromStr for PackageRegistry {
+    type Err = RsrError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "crates" | "crates.io" | "cargo" => Ok(Self::Crates),
+            "npm" => Ok(Self::Npm),
+            other => Err(RsrError::Config(format!("Unknown package registry: {}", other))),
+        }
+    }
+}
+
+impl std::fmt::Display for PackageRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Looks up package metadata on the public registries
+pub struct RegistryClient {
+    client: reqwest::Client,
+    http: HttpLayer,
+}
+
+impl Default for RegistryClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RegistryClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            http: HttpLayer::new("package-registry", RetryPolicy::default()),
+        }
+    }
+
+    /// Registry metadata for a package
+    async fn metadata(&self, registry: PackageRegistry, package: &str) -> Result<serde_json::Value> {
+        let url = match registry {
+            PackageRegistry::Crates => format!("https://crates.io/api/v1/crates/{}", urlencoding::encode(package)),
+            // Scoped names keep their `@` but encode the `/`
+            PackageRegistry::Npm => format!("https://registry.npmjs.org/{}", package.replace('/', "%2F")),
+        };
+
+        // crates.io rejects requests without a User-Agent
+        let response = self
+            .client
+            .get(&url)
+            .header("User-Agent", "RSR-Certified/0.1")
+            .header("Accept", "application/json")
+            .send_via(&self.http)
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(RsrError::Platform(format!("No package {} on {}", package, registry)));
+        }
+        if !response.status().is_success() {
+            return Err(RsrError::Platform(format!(
+                "Looking up {} on {} failed: {}",
+                package,
+                registry,
+                response.status()
+            )));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Repository URL from the package's metadata, if it names one
+    pub async fn repository_url(&self, registry: PackageRegistry, package: &str) -> Result<Option<String>> {
+        let metadata = self.metadata(registry, package).await?;
+        Ok(repository_url(registry, &metadata))
+    }
+
+    /// Maintenance signals for a package, at `version` if the version in use
+    /// is known
+    pub async fn package_health(&self, registry: PackageRegistry, package: &str, version: Option<&str>) -> Result<PackageHealth> {
+        let metadata = self.metadata(registry, package).await?;
+        let repository = repository_url(registry, &metadata).and_then(|url| parse_repository_url(&url));
+        let parse_date = |value: &serde_json::Value| {
+            value
+                .as_str()
+                .and_then(|date| chrono::DateTime::parse_from_rfc3339(date).ok())
+                .map(|date| date.with_timezone(&chrono::Utc))
+        };
+
+        let health = match registry {
+            PackageRegistry::Crates => {
+                let versions = metadata["versions"].as_array().cloned().unwrap_or_default();
+                let yanked = match version {
+                    Some(version) => versions
+                        .iter()
+                        .any(|v| v["num"].as_str() == Some(version) && v["yanked"].as_bool() == Some(true)),
+                    // Without a lockfile, only a crate with nothing installable is yanked
+                    None => !versions.is_empty() && versions.iter().all(|v| v["yanked"].as_bool() == Some(true)),
+                };
+                PackageHealth {
+                    latest_release: versions.iter().filter_map(|v| parse_date(&v["created_at"])).max(),
+                    yanked,
+                    deprecated: None,
+                    repository,
+                }
+            }
+            PackageRegistry::Npm => {
+                // npm can't yank; deprecation is its equivalent signal
+                let version = version.or_else(|| metadata["dist-tags"]["latest"].as_str());
+                let deprecated = version
+                    .and_then(|version| metadata["versions"][version]["deprecated"].as_str())
+                    .map(str::to_string);
+                let latest_release = metadata["time"].as_object().and_then(|times| {
+                    times
+                        .iter()
+                        .filter(|(key, _)| !matches!(key.as_str(), "created" | "modified"))
+                        .filter_map(|(_, date)| parse_date(date))
+                        .max()
+                });
+                PackageHealth {
+                    latest_release,
+                    yanked: false,
+                    deprecated,
+                    repository,
+                }
+            }
+        };
+
+        Ok(health)
+    }
+
+    /// The repository a package is developed in, if the registry names one on
+    /// a platform we support
+    pub async fn resolve(&self, registry: PackageRegistry, package: &str) -> Result<Option<RepoRef>> {
+        let Some(url) = self.repository_url(registry, package).await? else {
+            return Ok(None);
+        };
+
+        let repo = parse_repository_url(&url);
+        if repo.is_none() {
+            tracing::info!("{} on {} names an unsupported repository: {}", package, registry, url);
+        }
+        Ok(repo)
+    }
+}
+
+/// Maintenance signals for a package from its registry
+#[derive(Debug, Clone, Default)]
+pub struct PackageHealth {
+    /// When the most recent version was published
+    pub latest_release: Option<chrono::DateTime<chrono::Utc>>,
+    /// The version in use (or every version) was yanked
+    pub yanked: bool,
+    /// Deprecation message on the version in use
+    pub deprecated: Option<String>,
+    /// Repository the package is developed in, on a supported platform
+    pub repository: Option<RepoRef>,
+}
+
+fn repository_url(registry: PackageRegistry, metadata: &serde_json::Value) -> Option<String> {
+    let repository = match registry {
+        PackageRegistry::Crates => metadata["crate"]["repository"].as_str(),
+        // `repository` is either a string or `{ type, url, directory }`
+        PackageRegistry::Npm => metadata["repository"]
+            .as_str()
+            .or_else(|| metadata["repository"]["url"].as_str()),
+    };
+
+    repository.map(str::to_string).filter(|url| !url.trim().is_empty())
+}
+
+/// Parse the repository URL forms found in package metadata: web and clone
+/// URLs (`git+https://`, `git://`, `ssh://git@`, `git@host:`), npm's
+/// `github:owner/repo` shorthands and bare `owner/repo` (GitHub). Paths into
+/// the repository, e.g. `/tree/main/crates/foo`, are dropped.
+pub fn parse_repository_url(url: &str) -> Option<RepoRef> {
+    let url = url.trim();
+
+    let (host, path) = if let Some((scheme, rest)) = url.split_once("://") {
+        if !matches!(scheme, "http" | "https" | "git" | "ssh" | "git+https" | "git+http" | "git+ssh") {
+            return None;
+        }
+        let rest = rest.rsplit_once('@').map_or(rest, |(_, rest)| rest);
+        let (authority, path) = rest.split_once('/')?;
+        // Drop any port
+        (authority.split(':').next()?, path)
+    } else if let Some(rest) = url.strip_prefix("git@") {
+        rest.split_once(':')?
+    } else if let Some((shorthand, path)) = url.split_once(':') {
+        let host = match shorthand {
+            "github" => "github.com",
+            "gitlab" => "gitlab.com",
+            "bitbucket" => "bitbucket.org",
+            _ => return None,
+        };
+        (host, path)
+    } else {
+        match url.split_once('/') {
+            // Scheme-less web URL, e.g. `gitlab.com/group/repo`
+            Some((host, path)) if host.contains('.') => (host, path),
+            _ => ("github.com", url),
+        }
+    };
+
+    let platform = match host.to_ascii_lowercase().trim_start_matches("www.") {
+        "github.com" => "github",
+        "gitlab.com" => "gitlab",
+        "bitbucket.org" => "bitbucket",
+        "git.sr.ht" => "sourcehut",
+        _ => return None,
+    };
+
+    let path = path.split(['?', '#']).next()?;
+    let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    let segments = match platform {
+        // Nested groups: everything up to GitLab's `/-/` separator
+        "gitlab" => {
+            let end = segments.iter().position(|segment| *segment == "-").unwrap_or(segments.len());
+            &segments[..end]
+        }
+        _ => &segments[..segments.len().min(2)],
+    };
+
+    let (repo, owner) = segments.split_last()?;
+    if owner.is_empty() {
+        return None;
+    }
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+
+    Some(RepoRef::new(platform, owner.join("/"), repo))
+}
+
+/// Request to discover a package's repository, as placed on the queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveryJob {
+    pub registry: PackageRegistry,
+    pub package: String,
+}
+
+impl DiscoveryJob {
+    pub fn new(registry: PackageRegistry, package: impl Into<String>) -> Self {
+        Self {
+            registry,
+            package: package.into(),
+        }
+    }
+
+    /// Queue the job behind scans, which users are waiting on
+    pub async fn enqueue(&self, db: &DatabasePool) -> Result<String> {
+        let key = format!("{}:{}", self.registry, self.package);
+        db.cache
+            .enqueue_job(DISCOVERY_QUEUE, NewJob::new(self, JobPriority::Low, key)?)
+            .await
+    }
+}
+
+impl JobPayload for DiscoveryJob {
+    const KIND: &'static str = "discovery";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// Resolve a package's repository, register it and link the package to it.
+/// Returns `None` if the registry names no supported repository.
+pub async fn discover(db: &DatabasePool, registries: &RegistryClient, job: &DiscoveryJob) -> Result<Option<RepoRef>> {
+    let Some(repo) = registries.resolve(job.registry, &job.package).await? else {
+        return Ok(None);
+    };
+
+    let repo_key = db.graphs.register_repository(&repo.platform, &repo.owner, &repo.repo).await?;
+    db.graphs
+        .link_package_repository(job.registry.as_str(), &job.package, &repo_key)
+        .await?;
+    // Enterprises are only known to the server's configuration; scans add them
+    db.graphs
+        .register_hierarchy(&repo_key, &HierarchyConfig::default().ancestry(&repo))
+        .await?;
+
+    tracing::info!("Discovered {} on {} at {}", job.package, job.registry, repo);
+    Ok(Some(repo))
+}
+
+/// Processes discovery jobs taken off the discovery queue
+pub struct DiscoveryJobHandler {
+    db: Arc<DatabasePool>,
+    registries: RegistryClient,
+}
+
+impl DiscoveryJobHandler {
+    pub fn new(db: Arc<DatabasePool>) -> Self {
+        Self {
+            db,
+            registries: RegistryClient::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl JobHandler for DiscoveryJobHandler {
+    fn kind(&self) -> &'static str {
+        DiscoveryJob::KIND
+    }
+
+    fn schema_version(&self) -> u32 {
+        DiscoveryJob::SCHEMA_VERSION
+    }
+
+    async fn handle(&self, job: String) -> Result<()> {
+        let job: DiscoveryJob = serde_json::from_str(&job)?;
+        discover(&self.db, &self.registries, &job).await.map(|_| ())
+    }
+}
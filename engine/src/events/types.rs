@@ -16,6 +16,11 @@ pub enum RepoEvent {
     SecurityAlert(SecurityAlertEvent),
     WorkflowRun(WorkflowEvent),
     Comment(CommentEvent),
+    Repository(RepositoryEvent),
+    Organization(OrganizationEvent),
+    CheckSuite(CheckSuiteEvent),
+    Deployment(DeploymentEvent),
+    BranchProtection(BranchProtectionEvent),
 }
 
 /// Push event - commits pushed to a branch
@@ -41,6 +46,10 @@ pub struct PullRequestEvent {
     pub body: Option<String>,
     pub source_branch: String,
     pub target_branch: String,
+    /// Latest commit of the source branch, which statuses are posted on.
+    /// Empty in jobs queued before it was recorded.
+    #[serde(default)]
+    pub head_sha: String,
     pub author: User,
     pub draft: bool,
 }
@@ -145,6 +154,30 @@ pub enum Severity {
     Unknown,
 }
 
+impl Severity {
+    /// Severity from a platform's label. GitHub calls medium `moderate`, in
+    /// webhooks and advisories alike.
+    pub fn from_label(label: &str) -> Self {
+        match label.to_lowercase().as_str() {
+            "critical" => Self::Critical,
+            "high" => Self::High,
+            "medium" | "moderate" => Self::Medium,
+            "low" => Self::Low,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Critical => "critical",
+            Self::High => "high",
+            Self::Medium => "medium",
+            Self::Low => "low",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
 /// CI/CD workflow event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowEvent {
@@ -174,7 +207,7 @@ pub enum WorkflowStatus {
     Completed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum WorkflowConclusion {
     Success,
@@ -214,6 +247,147 @@ pub enum CommentType {
     Review,
 }
 
+/// Check suite event - a CI app's aggregate result for a commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckSuiteEvent {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub action: CheckSuiteAction,
+    /// App that owns the suite, e.g. "github-actions"
+    pub app: Option<String>,
+    pub status: WorkflowStatus,
+    pub conclusion: Option<WorkflowConclusion>,
+    pub branch: Option<String>,
+    pub commit_sha: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckSuiteAction {
+    Requested,
+    Rerequested,
+    Completed,
+}
+
+/// Deployment created, or a deployment's status changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploymentEvent {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub deployment_id: u64,
+    pub environment: String,
+    pub state: DeploymentState,
+    pub branch: String,
+    pub commit_sha: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentState {
+    Created,
+    Pending,
+    Queued,
+    InProgress,
+    Success,
+    Failure,
+    Error,
+    Inactive,
+}
+
+/// Branch protection rule created, changed or removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchProtectionEvent {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub action: BranchProtectionAction,
+    /// Branch name pattern the rule applies to
+    pub pattern: String,
+    pub required_approvals: u32,
+    pub required_status_checks: Vec<String>,
+    pub enforce_admins: bool,
+    pub allow_force_pushes: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BranchProtectionAction {
+    Created,
+    Edited,
+    Deleted,
+}
+
+/// Repository lifecycle event (creation, transfer, rename, archival, deletion)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryEvent {
+    pub repo_owner: String,
+    pub repo_name: String,
+    pub action: RepositoryAction,
+    /// Owner before a transfer
+    pub previous_owner: Option<String>,
+    /// Name before a rename
+    pub previous_name: Option<String>,
+    /// `owner/repo` a fork was made from, on the same platform
+    #[serde(default)]
+    pub upstream: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepositoryAction {
+    Created,
+    Transferred,
+    Renamed,
+    Archived,
+    Unarchived,
+    Deleted,
+    /// The repository was created as a fork of `upstream`
+    Forked,
+}
+
+impl RepositoryEvent {
+    /// Identity the repository had before this event, if it changed
+    pub fn previous_identity(&self) -> Option<(&str, &str)> {
+        if self.previous_owner.is_none() && self.previous_name.is_none() {
+            return None;
+        }
+
+        Some((
+            self.previous_owner.as_deref().unwrap_or(&self.repo_owner),
+            self.previous_name.as_deref().unwrap_or(&self.repo_name),
+        ))
+    }
+}
+
+/// Organization/group event - lifecycle, membership and permission changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizationEvent {
+    /// Full path of the organization or group
+    pub org: String,
+    pub action: OrganizationAction,
+    /// Set when a membership change is scoped to one repository
+    pub repo_name: Option<String>,
+    /// Member affected by a membership change
+    pub member: Option<User>,
+    /// Team affected by a permission change, instead of a single member
+    pub team: Option<String>,
+    /// Platform-specific role name, e.g. "Maintainer"
+    pub access_level: Option<String>,
+    /// Full path before a rename
+    pub previous_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrganizationAction {
+    Created,
+    Deleted,
+    Renamed,
+    MemberAdded,
+    MemberRemoved,
+    MemberUpdated,
+}
+
 /// Commit representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Commit {
@@ -236,6 +410,24 @@ pub struct User {
 }
 
 impl RepoEvent {
+    /// Event type, as used in the serialized `type` tag
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Push(_) => "push",
+            Self::PullRequest(_) => "pull_request",
+            Self::Issue(_) => "issue",
+            Self::Release(_) => "release",
+            Self::SecurityAlert(_) => "security_alert",
+            Self::WorkflowRun(_) => "workflow_run",
+            Self::Comment(_) => "comment",
+            Self::Repository(_) => "repository",
+            Self::Organization(_) => "organization",
+            Self::CheckSuite(_) => "check_suite",
+            Self::Deployment(_) => "deployment",
+            Self::BranchProtection(_) => "branch_protection",
+        }
+    }
+
     /// Get the repository owner from any event type
     pub fn repo_owner(&self) -> &str {
         match self {
@@ -246,6 +438,11 @@ pub fn repo_owner(&self) -> &str {
             Self::SecurityAlert(e) => &e.repo_owner,
             Self::WorkflowRun(e) => &e.repo_owner,
             Self::Comment(e) => &e.repo_owner,
+            Self::Repository(e) => &e.repo_owner,
+            Self::Organization(e) => &e.org,
+            Self::CheckSuite(e) => &e.repo_owner,
+            Self::Deployment(e) => &e.repo_owner,
+            Self::BranchProtection(e) => &e.repo_owner,
         }
     }
 
@@ -259,6 +456,11 @@ pub fn repo_name(&self) -> &str {
             Self::SecurityAlert(e) => &e.repo_name,
             Self::WorkflowRun(e) => &e.repo_name,
             Self::Comment(e) => &e.repo_name,
+            Self::Repository(e) => &e.repo_name,
+            Self::Organization(e) => e.repo_name.as_deref().unwrap_or_default(),
+            Self::CheckSuite(e) => &e.repo_name,
+            Self::Deployment(e) => &e.repo_name,
+            Self::BranchProtection(e) => &e.repo_name,
         }
     }
 }
@@ -0,0 +1,226 @@
+//! Organization hierarchies
+//!
+//! A repository's owner is the bottom of a chain of organizational units.
+//! GitLab owners are group paths, so `gitlab:acme/platform/tools` sits under
+//! `gitlab:acme/platform`, which sits under `gitlab:acme`. Enterprises group
+//! top-level organizations and groups, possibly across platforms, and are
+//! declared in configuration:
+//!
+//! ```toml
+//! [hierarchy.enterprises]
+//! acme = ["github:acme-web", "gitlab:acme"]
+//! ```
+//!
+//! Teams sit between an organization and the repositories they own, and are
+//! declared the same way. A repository belongs to at most one team:
+//!
+//! ```toml
+//! [hierarchy.teams]
+//! "github:acme-web#payments" = ["github:acme-web/checkout", "github:acme-web/ledger"]
+//! ```
+//!
+//! Units are named `platform:path`, `platform:path#team` for teams, or
+//! `enterprise:name` for enterprises. Tenant policies are looked up along the
+//! chain, nearest unit first, and constraints set higher up (see
+//! [`PolicyConfig::effective_policy`]) carry down to every unit below. Reports
+//! roll compliance up to every level.
+
+use crate::config::PolicyConfig;
+use crate::{CertificationTier, ComplianceStatus, RepoRef};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Prefix of enterprise unit names
+const ENTERPRISE_PREFIX: &str = "enterprise:";
+
+/// Separates a team from the organization in team unit names
+const TEAM_SEPARATOR: char = '#';
+
+/// Enterprises above top-level organizations
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HierarchyConfig {
+    /// Top-level units (`platform:owner`) by enterprise name
+    #[serde(default)]
+    pub enterprises: HashMap<String, Vec<String>>,
+    /// Repositories (`platform:owner/repo`) by the team (`platform:path#team`)
+    /// that owns them
+    #[serde(default)]
+    pub teams: HashMap<String, Vec<String>>,
+}
+
+impl HierarchyConfig {
+    /// Units a repository belongs to, nearest first: its team if it has one,
+    /// its owner, each parent group, then its enterprise if it has one
+    pub fn ancestry(&self, repo: &RepoRef) -> Vec<String> {
+        let mut units: Vec<String> = self.team_of(repo).map(String::from).into_iter().collect();
+        units.extend(owner_units(repo));
+        if let Some(top) = units.last() {
+            if let Some(enterprise) = self.enterprise_of(top) {
+                units.push(enterprise_unit(enterprise));
+            }
+        }
+        units
+    }
+
+    /// Enterprise a top-level unit is declared in
+    pub fn enterprise_of(&self, unit: &str) -> Option<&str> {
+        self.enterprises
+            .iter()
+            .find(|(_, members)| members.iter().any(|member| member == unit))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Team owning a repository (ignoring its branch)
+    pub fn team_of(&self, repo: &RepoRef) -> Option<&str> {
+        let key = format!("{}:{}/{}", repo.platform, repo.owner, repo.repo);
+        self.teams
+            .iter()
+            .find(|(_, repos)| repos.contains(&key))
+            .map(|(team, _)| team.as_str())
+    }
+
+    /// Problems with the declared enterprises and teams
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut seen: HashMap<&str, &str> = HashMap::new();
+
+        for (name, members) in &self.enterprises {
+            if name.is_empty() || name.contains(':') {
+                problems.push(format!("hierarchy.enterprises.{}: invalid enterprise name", name));
+            }
+            for member in members {
+                match member.split_once(':') {
+                    Some((platform, owner)) if !platform.is_empty() && !owner.is_empty() && !owner.contains('/') => {}
+                    _ => problems.push(format!(
+                        "hierarchy.enterprises.{}: {} is not a top-level platform:owner",
+                        name, member
+                    )),
+                }
+                if let Some(other) = seen.insert(member, name) {
+                    if other != name {
+                        problems.push(format!("hierarchy.enterprises: {} is in both {} and {}", member, other, name));
+                    }
+                }
+            }
+        }
+
+        let mut owners: HashMap<&str, &str> = HashMap::new();
+        for (team, repos) in &self.teams {
+            let named = team.split_once(TEAM_SEPARATOR).filter(|(organization, name)| {
+                let platform_path = organization.split_once(':');
+                !name.is_empty() && platform_path.is_some_and(|(platform, path)| !platform.is_empty() && !path.is_empty())
+            });
+            let Some((organization, _)) = named else {
+                problems.push(format!("hierarchy.teams.{}: expected platform:path#team", team));
+                continue;
+            };
+            for repo in repos {
+                match repo.parse::<RepoRef>() {
+                    Ok(parsed) if owner_units(&parsed).iter().any(|unit| unit == organization) => {}
+                    Ok(_) => problems.push(format!("hierarchy.teams.{}: {} is not under {}", team, repo, organization)),
+                    Err(e) => problems.push(format!("hierarchy.teams.{}: {}", team, e)),
+                }
+                if let Some(other) = owners.insert(repo, team) {
+                    if other != team {
+                        problems.push(format!("hierarchy.teams: {} is owned by both {} and {}", repo, other, team));
+                    }
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+/// Unit name for an enterprise
+pub fn enterprise_unit(name: &str) -> String {
+    format!("{}{}", ENTERPRISE_PREFIX, name)
+}
+
+/// The owner and each of its parent groups, nearest first
+fn owner_units(repo: &RepoRef) -> Vec<String> {
+    let segments: Vec<&str> = repo.owner.split('/').filter(|segment| !segment.is_empty()).collect();
+    (1..=segments.len())
+        .rev()
+        .map(|len| format!("{}:{}", repo.platform, segments[..len].join("/")))
+        .collect()
+}
+
+/// Whether `unit` is `ancestor` or sits below it
+pub fn is_within(unit: &str, ancestor: &str) -> bool {
+    unit == ancestor
+        || (ancestor.starts_with(ENTERPRISE_PREFIX) && !unit.starts_with(ENTERPRISE_PREFIX))
+        || unit
+            .strip_prefix(ancestor)
+            .is_some_and(|rest| rest.starts_with('/') || rest.starts_with(TEAM_SEPARATOR))
+}
+
+/// Compliance of the repositories at or below one unit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rollup {
+    pub unit: String,
+    /// Unit directly above, if any
+    pub parent: Option<String>,
+    /// Levels below the top of the unit's hierarchy
+    pub depth: usize,
+    pub repositories: usize,
+    /// Repositories at Bronze or above
+    pub certified: usize,
+    /// Repositories below the target tier of the policy that applies to them
+    pub below_target: usize,
+    pub average_score: f32,
+    pub tiers: BTreeMap<CertificationTier, usize>,
+}
+
+/// Roll reports up to every unit above them. Units are ordered so each
+/// comes directly before the units below it.
+pub fn rollup<'a>(
+    reports: impl IntoIterator<Item = &'a ComplianceStatus>,
+    hierarchy: &HierarchyConfig,
+    policies: &PolicyConfig,
+) -> Vec<Rollup> {
+    let mut rollups: BTreeMap<Vec<String>, (Rollup, f32)> = BTreeMap::new();
+
+    for status in reports {
+        let ancestry = hierarchy.ancestry(&status.repo);
+        let below_target = status.tier < policies.effective_policy(&ancestry).policy.target_tier;
+
+        for (i, unit) in ancestry.iter().enumerate() {
+            // Keyed by the path from the top so sorting nests units under their parents
+            let path: Vec<String> = ancestry[i..].iter().rev().cloned().collect();
+            let (rollup, total_score) = rollups.entry(path).or_insert_with(|| {
+                (
+                    Rollup {
+                        unit: unit.clone(),
+                        parent: ancestry.get(i + 1).cloned(),
+                        depth: ancestry.len() - 1 - i,
+                        repositories: 0,
+                        certified: 0,
+                        below_target: 0,
+                        average_score: 0.0,
+                        tiers: BTreeMap::new(),
+                    },
+                    0.0,
+                )
+            });
+
+            rollup.repositories += 1;
+            if status.tier > CertificationTier::None {
+                rollup.certified += 1;
+            }
+            if below_target {
+                rollup.below_target += 1;
+            }
+            *rollup.tiers.entry(status.tier).or_default() += 1;
+            *total_score += status.score;
+        }
+    }
+
+    rollups
+        .into_values()
+        .map(|(mut rollup, total_score)| {
+            rollup.average_score = total_score / rollup.repositories as f32;
+            rollup
+        })
+        .collect()
+}
@@ -4,10 +4,22 @@
 //! for repository certification across GitHub, GitLab, Bitbucket, and more.
 
 pub mod adapters;
+pub mod advisories;
+pub mod badge;
+pub mod branding;
 pub mod compliance;
+pub mod config;
 pub mod db;
+pub mod deps;
+pub mod discovery;
 pub mod events;
+pub mod hierarchy;
+pub mod logging;
+pub mod publish;
+pub mod report;
+pub mod scheduler;
 pub mod server;
+pub mod worker;
 
 use thiserror::Error;
 
@@ -26,12 +38,18 @@ pub enum RsrError {
     #[error("Webhook verification failed")]
     WebhookVerification,
 
+    #[error("Insecure configuration: {0}")]
+    InsecureConfiguration(String),
+
     #[error("Repository not found: {owner}/{repo}")]
     RepoNotFound { owner: String, repo: String },
 
     #[error("Rate limited by platform")]
     RateLimited,
 
+    #[error("Database error: {0}")]
+    Db(#[from] db::DbError),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -40,10 +58,24 @@ pub enum RsrError {
 
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
+
+    #[error("Parse error: {0}")]
+    Parse(String),
 }
 
 pub type Result<T> = std::result::Result<T, RsrError>;
 
+impl RsrError {
+    /// Whether the operation may succeed if tried again. Only database
+    /// errors are known to be fatal; anything else is assumed transient.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Db(e) => e.is_retryable(),
+            _ => true,
+        }
+    }
+}
+
 /// RSR certification tiers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -136,6 +168,31 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
+impl std::str::FromStr for RepoRef {
+    type Err = RsrError;
+
+    /// Parse the `Display` form, `platform:owner/repo[@branch]`. Owners may
+    /// contain slashes (GitLab subgroups).
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || RsrError::Config(format!("Invalid repository {}: expected platform:owner/repo", s));
+        let (platform, path) = s.split_once(':').ok_or_else(invalid)?;
+        let (path, branch) = match path.split_once('@') {
+            Some((path, branch)) => (path, Some(branch)),
+            None => (path, None),
+        };
+        let (owner, repo) = path.rsplit_once('/').ok_or_else(invalid)?;
+        if platform.is_empty() || owner.is_empty() || repo.is_empty() || branch == Some("") {
+            return Err(invalid());
+        }
+
+        let repo = Self::new(platform, owner, repo);
+        Ok(match branch {
+            Some(branch) => repo.with_branch(branch),
+            None => repo,
+        })
+    }
+}
+
 /// Compliance status for a repository
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ComplianceStatus {
@@ -144,6 +201,86 @@ pub struct ComplianceStatus {
     pub score: f32,
     pub checks: Vec<CheckResult>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Standard versions (`name@version`) the checks were run against: the
+    /// engine's built-in standard followed by any rulepacks
+    #[serde(default)]
+    pub standard: Vec<String>,
+    /// Canonical identity when `repo` is one of several linked mirrors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical: Option<RepoRef>,
+    /// Checks satisfied by evidence from a linked identity, by check id
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub evidence: std::collections::BTreeMap<String, RepoRef>,
+}
+
+impl ComplianceStatus {
+    /// Build a status from check results, scored with the engine's own logic
+    pub fn builder() -> ComplianceStatusBuilder {
+        ComplianceStatusBuilder::default()
+    }
+}
+
+/// Builder for [`ComplianceStatus`] - tier and score are always derived from
+/// the checks, never set directly
+#[derive(Debug, Clone, Default)]
+pub struct ComplianceStatusBuilder {
+    repo: Option<RepoRef>,
+    checks: Vec<CheckResult>,
+    timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    policy: compliance::ScoringPolicy,
+    standard: Option<Vec<String>>,
+}
+
+impl ComplianceStatusBuilder {
+    pub fn repo(mut self, repo: RepoRef) -> Self {
+        self.repo = Some(repo);
+        self
+    }
+
+    pub fn check(mut self, check: CheckResult) -> Self {
+        self.checks.push(check);
+        self
+    }
+
+    pub fn checks(mut self, checks: impl IntoIterator<Item = CheckResult>) -> Self {
+        self.checks.extend(checks);
+        self
+    }
+
+    /// Defaults to now
+    pub fn timestamp(mut self, timestamp: chrono::DateTime<chrono::Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn policy(mut self, policy: compliance::ScoringPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Defaults to the built-in standard
+    pub fn standard(mut self, standard: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.standard = Some(standard.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn build(self) -> Result<ComplianceStatus> {
+        let repo = self
+            .repo
+            .ok_or_else(|| RsrError::Compliance("ComplianceStatus requires a repository".to_string()))?;
+        let (score, tier) = compliance::score(&self.checks, &self.policy);
+
+        Ok(ComplianceStatus {
+            repo,
+            tier,
+            score,
+            checks: self.checks,
+            timestamp: self.timestamp.unwrap_or_else(chrono::Utc::now),
+            standard: self.standard.unwrap_or_else(|| vec![compliance::builtin_standard()]),
+            canonical: None,
+            evidence: Default::default(),
+        })
+    }
 }
 
 /// Result of a single compliance check
@@ -155,8 +292,38 @@ pub struct CheckResult {
     pub passed: bool,
     pub message: String,
     pub details: Option<String>,
+    /// What the check found, file by file, for checks that report it.
+    /// Omitted when empty, so reports from before findings hash as stored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<Finding>,
+}
+
+/// One thing a check found, with how to fix it when it's a problem
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Finding {
+    /// Stable identifier, e.g. `license.unidentified`
+    pub code: String,
+    pub severity: Severity,
+    /// File the finding is about, relative to the repository root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+/// How much a finding matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Recorded for the report, e.g. the license detected
+    Info,
+    /// Worth fixing, but the check still passes
+    Warning,
+    /// Fails the check
+    Error,
 }
 
 // Re-export commonly used types
-pub use compliance::ComplianceEngine;
+pub use compliance::{score, ComplianceEngine, ScoringPolicy};
 pub use events::RepoEvent;
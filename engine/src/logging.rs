@@ -0,0 +1,156 @@
+//! Logging profiles and runtime level overrides
+//!
+//! Logs are written in one of three profiles: `pretty` multi-line output for
+//! people reading a terminal, `json` lines for log aggregation, or `compact`
+//! single lines for the CLI. The profile is chosen at startup with
+//! `--log-profile` or `RSR_LOG_PROFILE`.
+//!
+//! The base filter comes from `RUST_LOG`, or the `--log-level` flag if that
+//! is unset. Operators can layer per-module level overrides on top through
+//! the admin API, so turning up `rsr_engine::adapters::github` while chasing
+//! a parsing bug doesn't mean a redeploy. Overrides last until replaced or
+//! the process restarts.
+
+use crate::{Result, RsrError};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, Layer, Registry};
+
+/// The installed logger's controls, set by [`init`]
+static CONTROL: OnceCell<LogControl> = OnceCell::new();
+
+/// How log lines are formatted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogProfile {
+    /// Multi-line, human readable
+    Pretty,
+    /// One JSON object per line
+    Json,
+    /// One short line per event
+    Compact,
+}
+
+impl std::str::FromStr for LogProfile {
+    type Err = RsrError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            "compact" => Ok(Self::Compact),
+            _ => Err(RsrError::Config(format!(
+                "Invalid log profile {}: expected pretty, json or compact",
+                s
+            ))),
+        }
+    }
+}
+
+/// The filter in force and how it was built
+#[derive(Debug, Clone, Serialize)]
+pub struct LogSettings {
+    pub profile: LogProfile,
+    /// Filter from `RUST_LOG` or `--log-level`
+    pub base: String,
+    /// Level by module path, applied over the base filter
+    pub overrides: BTreeMap<String, String>,
+}
+
+impl LogSettings {
+    /// The combined filter directives
+    pub fn directives(&self) -> String {
+        std::iter::once(self.base.clone())
+            .chain(self.overrides.iter().map(|(module, level)| format!("{}={}", module, level)))
+            .filter(|directive| !directive.is_empty())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Changes the installed logger's filter
+pub struct LogControl {
+    handle: reload::Handle<EnvFilter, Registry>,
+    settings: Mutex<LogSettings>,
+}
+
+impl LogControl {
+    pub fn settings(&self) -> LogSettings {
+        self.settings.lock().expect("log settings lock poisoned").clone()
+    }
+
+    /// Replace the per-module overrides. Levels are `off`, `error`, `warn`,
+    /// `info`, `debug` or `trace`; an invalid entry rejects the whole set.
+    pub fn set_overrides(&self, overrides: BTreeMap<String, String>) -> Result<LogSettings> {
+        let mut problems = Vec::new();
+        for (module, level) in &overrides {
+            if module.is_empty() || module.contains([',', '=', ' ', '[', '{']) {
+                problems.push(format!("{}: not a module path", module));
+            }
+            if level.parse::<LevelFilter>().is_err() {
+                problems.push(format!("{}: invalid level {}", module, level));
+            }
+        }
+        if !problems.is_empty() {
+            return Err(RsrError::Config(problems.join("; ")));
+        }
+
+        let mut settings = self.settings.lock().expect("log settings lock poisoned");
+        let updated = LogSettings {
+            overrides: overrides
+                .into_iter()
+                .map(|(module, level)| (module, level.to_lowercase()))
+                .collect(),
+            ..settings.clone()
+        };
+        let filter = EnvFilter::try_new(updated.directives()).map_err(|e| RsrError::Config(e.to_string()))?;
+        self.handle
+            .reload(filter)
+            .map_err(|e| RsrError::Config(format!("Failed to apply log filter: {}", e)))?;
+
+        tracing::info!("Log filter is now {}", updated.directives());
+        *settings = updated.clone();
+        Ok(updated)
+    }
+}
+
+/// Install the global logger. `default_filter` applies when `RUST_LOG` is
+/// unset.
+pub fn init(profile: LogProfile, default_filter: &str) -> Result<()> {
+    let base = std::env::var(EnvFilter::DEFAULT_ENV).unwrap_or_else(|_| default_filter.to_string());
+    let filter = EnvFilter::try_new(&base).map_err(|e| RsrError::Config(format!("Invalid log filter {}: {}", base, e)))?;
+    let (filter, handle) = reload::Layer::new(filter);
+
+    let format = match profile {
+        LogProfile::Pretty => tracing_subscriber::fmt::layer().pretty().boxed(),
+        LogProfile::Json => tracing_subscriber::fmt::layer().json().flatten_event(true).boxed(),
+        LogProfile::Compact => tracing_subscriber::fmt::layer().compact().boxed(),
+    };
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(format)
+        .try_init()
+        .map_err(|e| RsrError::Config(format!("Failed to install logger: {}", e)))?;
+
+    let settings = LogSettings {
+        profile,
+        base,
+        overrides: BTreeMap::new(),
+    };
+    // `try_init` above fails on a second call, so this is the first
+    let _ = CONTROL.set(LogControl {
+        handle,
+        settings: Mutex::new(settings),
+    });
+    Ok(())
+}
+
+/// Controls of the logger installed by [`init`], if any
+pub fn control() -> Option<&'static LogControl> {
+    CONTROL.get()
+}
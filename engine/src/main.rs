@@ -3,9 +3,15 @@
 //! Run compliance checks locally or start the webhook server.
 
 use clap::{Parser, Subcommand};
+use rsr_engine::badge::{self, BadgeOptions};
+use rsr_engine::branding::Branding;
+use rsr_engine::compliance::rulepack::{self, RulepackRegistry};
+use rsr_engine::compliance::selfcheck::SelfCertification;
+use rsr_engine::config::EngineConfig;
+use rsr_engine::logging::{self, LogProfile};
+use rsr_engine::report;
 use rsr_engine::{CertificationTier, ComplianceEngine};
-use std::path::PathBuf;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "rsr")]
@@ -17,6 +23,11 @@ struct Cli {
     /// Log level (trace, debug, info, warn, error)
     #[arg(long, default_value = "info", global = true)]
     log_level: String,
+
+    /// Log format (pretty, json, compact); defaults to pretty for `serve`
+    /// and compact otherwise
+    #[arg(long, env = "RSR_LOG_PROFILE", global = true)]
+    log_profile: Option<LogProfile>,
 }
 
 #[derive(Subcommand)]
@@ -31,7 +42,7 @@ enum Commands {
         #[arg(short, long, default_value = "gold")]
         tier: String,
 
-        /// Output format (text, json, badge)
+        /// Output format (text, json, badge, html)
         #[arg(short, long, default_value = "text")]
         format: String,
 
@@ -40,6 +51,35 @@ enum Commands {
         strict: bool,
     },
 
+    /// Run a single check against a fixture directory, for check authors
+    CheckDev {
+        /// ID of the check to run (e.g. bronze.license)
+        check_id: String,
+
+        /// Fixture tree to check
+        #[arg(long)]
+        fixture: PathBuf,
+
+        /// Expected outcome (pass, fail); exit with error if not met
+        #[arg(long)]
+        expect: Option<String>,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Check the engine's own repository; fails unless it reaches Bronze,
+    /// without which the server won't issue Gold or Rhodium
+    SelfCheck {
+        /// Engine repository (defaults to RSR_SELF_SCAN_PATH, then the build workspace)
+        path: Option<PathBuf>,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
     /// Start the webhook server
     Serve {
         /// Host to bind to
@@ -53,6 +93,10 @@ enum Commands {
         /// Platforms to enable (comma-separated)
         #[arg(long, default_value = "github,gitlab,bitbucket")]
         platforms: String,
+
+        /// Engine configuration file (reloaded on SIGHUP)
+        #[arg(short, long, env = "RSR_CONFIG")]
+        config: Option<PathBuf>,
     },
 
     /// Generate a compliance badge
@@ -64,9 +108,92 @@ enum Commands {
         #[arg(short, long)]
         output: Option<PathBuf>,
 
-        /// Badge style (flat, flat-square, for-the-badge)
+        /// Badge style (flat, flat-square, plastic, for-the-badge)
         #[arg(short, long, default_value = "flat")]
         style: String,
+
+        /// Engine configuration supplying the badge label and tier codes
+        #[arg(short, long, env = "RSR_CONFIG")]
+        config: Option<PathBuf>,
+    },
+
+    /// Export HTML reports for local repositories as a static site
+    Export {
+        /// Paths to repositories (defaults to current directory)
+        #[arg(default_value = ".")]
+        paths: Vec<PathBuf>,
+
+        /// Output directory
+        #[arg(short, long, default_value = "rsr-report")]
+        output: PathBuf,
+
+        /// Owner or organization shown for the repositories
+        #[arg(long, default_value = "local")]
+        owner: String,
+
+        /// Summary page title (defaults to "<program> compliance summary")
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Engine configuration supplying the program and tier names and links
+        #[arg(short, long, env = "RSR_CONFIG")]
+        config: Option<PathBuf>,
+    },
+
+    /// Download, verify and install a signed rulepack
+    InstallRulepack {
+        /// Rulepack to install (name@version)
+        spec: String,
+
+        /// Engine configuration supplying the registry, trusted keys and directory
+        #[arg(short, long, env = "RSR_CONFIG")]
+        config: Option<PathBuf>,
+
+        /// Registry URL (overrides the configuration)
+        #[arg(long)]
+        registry: Option<String>,
+
+        /// Trusted base64 Ed25519 public key (repeatable, added to the configuration's)
+        #[arg(long = "trusted-key")]
+        trusted_keys: Vec<String>,
+
+        /// Install directory (overrides the configuration)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+
+    /// Apply pending database schema migrations
+    Migrate {
+        /// Print the pending document store migrations' statements without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Compare `cargo bench` results against the stored baseline
+    Bench {
+        /// Criterion output directory
+        #[arg(long, default_value = "target/criterion")]
+        criterion_dir: PathBuf,
+
+        /// Branch whose latest recorded run is the baseline
+        #[arg(long, default_value = "main")]
+        baseline_branch: String,
+
+        /// Slowdown of a benchmark's mean, in percent, that fails the comparison
+        #[arg(long, default_value_t = 10.0)]
+        threshold: f64,
+
+        /// Record the run as the latest for `--branch`
+        #[arg(long)]
+        record: bool,
+
+        /// Commit benchmarked
+        #[arg(long, env = "GITHUB_SHA", default_value = "unknown")]
+        commit: String,
+
+        /// Branch benchmarked
+        #[arg(long, env = "GITHUB_REF_NAME", default_value = "main")]
+        branch: String,
     },
 
     /// Initialize RSR configuration in a repository
@@ -86,13 +213,11 @@ async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
     // Initialize logging
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| format!("rsr={}", cli.log_level).into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    let profile = cli.log_profile.unwrap_or(match cli.command {
+        Commands::Serve { .. } => LogProfile::Pretty,
+        _ => LogProfile::Compact,
+    });
+    logging::init(profile, &format!("rsr={}", cli.log_level))?;
 
     match cli.command {
         Commands::Check {
@@ -103,19 +228,63 @@ async fn main() -> anyhow::Result<()> {
         } => {
             run_check(&path, &tier, &format, strict).await?;
         }
+        Commands::CheckDev {
+            check_id,
+            fixture,
+            expect,
+            format,
+        } => {
+            run_check_dev(&check_id, &fixture, expect.as_deref(), &format).await?;
+        }
+        Commands::SelfCheck { path, format } => {
+            run_self_check(path.as_deref(), &format).await?;
+        }
         Commands::Serve {
             host,
             port,
             platforms,
+            config,
         } => {
-            run_server(&host, port, &platforms).await?;
+            run_server(&host, port, &platforms, config.as_deref()).await?;
         }
         Commands::Badge {
             tier,
             output,
             style,
+            config,
+        } => {
+            generate_badge(&tier, output.as_deref(), &style, config.as_deref())?;
+        }
+        Commands::Export {
+            paths,
+            output,
+            owner,
+            title,
+            config,
         } => {
-            generate_badge(&tier, output.as_deref(), &style)?;
+            export_reports(&paths, &output, &owner, title, config.as_deref()).await?;
+        }
+        Commands::InstallRulepack {
+            spec,
+            config,
+            registry,
+            trusted_keys,
+            dir,
+        } => {
+            install_rulepack(&spec, config.as_deref(), registry, trusted_keys, dir).await?;
+        }
+        Commands::Migrate { dry_run } => {
+            run_migrations(dry_run).await?;
+        }
+        Commands::Bench {
+            criterion_dir,
+            baseline_branch,
+            threshold,
+            record,
+            commit,
+            branch,
+        } => {
+            compare_benchmarks(&criterion_dir, &baseline_branch, threshold, record, commit, branch).await?;
         }
         Commands::Init { path, tier } => {
             init_config(&path, &tier)?;
@@ -125,7 +294,7 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn run_check(path: &PathBuf, tier: &str, format: &str, strict: bool) -> anyhow::Result<()> {
+async fn run_check(path: &Path, tier: &str, format: &str, strict: bool) -> anyhow::Result<()> {
     let engine = ComplianceEngine::new();
     let target_tier = parse_tier(tier)?;
 
@@ -139,9 +308,12 @@ async fn run_check(path: &PathBuf, tier: &str, format: &str, strict: bool) -> an
             println!("{}", serde_json::to_string_pretty(&status)?);
         }
         "badge" => {
-            let badge = generate_badge_svg(&status.tier, "flat");
+            let badge = badge::render(status.tier, status.score, &BadgeOptions::default());
             println!("{}", badge);
         }
+        "html" => {
+            println!("{}", report::render_report(&status));
+        }
         _ => {
             print_status(&status);
         }
@@ -159,20 +331,213 @@ async fn run_check(path: &PathBuf, tier: &str, format: &str, strict: bool) -> an
     Ok(())
 }
 
-async fn run_server(host: &str, port: u16, platforms: &str) -> anyhow::Result<()> {
+/// Outcome of one check in one mode, with what it was run against
+#[derive(serde::Serialize)]
+struct CheckDevRun {
+    mode: &'static str,
+    elapsed_ms: f64,
+    result: rsr_engine::CheckResult,
+}
+
+async fn run_check_dev(
+    check_id: &str,
+    fixture: &std::path::Path,
+    expect: Option<&str>,
+    format: &str,
+) -> anyhow::Result<()> {
+    let engine = ComplianceEngine::new();
+    let Some(check) = engine.find_check(check_id) else {
+        anyhow::bail!(
+            "Unknown check: {}. Available: {}",
+            check_id,
+            engine.check_ids().join(", ")
+        );
+    };
+
+    let expect_pass = match expect {
+        None => None,
+        Some("pass") => Some(true),
+        Some("fail") => Some(false),
+        Some(other) => anyhow::bail!("Unknown expectation: {}. Use: pass, fail", other),
+    };
+
+    if !fixture.is_dir() {
+        anyhow::bail!("Fixture is not a directory: {}", fixture.display());
+    }
+
+    // Remote checks see the fixture the way a platform adapter would present it
+    let contents = rsr_engine::compliance::RepoContents::from_dir(fixture)?;
+
+    let mut runs = Vec::new();
+    for mode in ["local", "remote"] {
+        let started = std::time::Instant::now();
+        let outcome = match mode {
+            "local" => check.check_local(fixture).await,
+            _ => check.check_remote(&contents).await,
+        };
+        let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+        let result = outcome.unwrap_or_else(|e| rsr_engine::CheckResult {
+            id: check.id().to_string(),
+            name: check.name().to_string(),
+            tier: check.tier(),
+            passed: false,
+            message: format!("Check failed: {}", e),
+            details: None,
+            findings: Vec::new(),
+        });
+        runs.push(CheckDevRun { mode, elapsed_ms, result });
+    }
+
+    if format == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "check": check.id(),
+                "fixture": fixture,
+                "files": contents.files.iter().map(|f| &f.path).collect::<Vec<_>>(),
+                "runs": runs,
+            }))?
+        );
+    } else {
+        println!("\nCheck:   {} ({}) [{}]", check.id(), check.name(), check.tier().code());
+        println!("Fixture: {}", fixture.display());
+        println!("Files:   {}", contents.files.len());
+        for file in &contents.files {
+            let note = if file.content.is_some() { "" } else { " (binary)" };
+            println!("           {} {}B{}", file.path, file.size, note);
+        }
+
+        for run in &runs {
+            let icon = if run.result.passed { "✓" } else { "✗" };
+            println!("{}", "-".repeat(60));
+            println!("{} {:6} {:.2}ms - {}", icon, run.mode, run.elapsed_ms, run.result.message);
+            if let Some(ref details) = run.result.details {
+                for line in details.lines() {
+                    println!("           {}", line);
+                }
+            }
+        }
+        println!("{}", "-".repeat(60));
+    }
+
+    if runs[0].result.passed != runs[1].result.passed {
+        tracing::warn!("Local and remote results disagree for {}", check.id());
+    }
+
+    if let Some(expected) = expect_pass {
+        if runs.iter().any(|run| run.result.passed != expected) {
+            tracing::error!(
+                "Check {} did not {} as expected",
+                check.id(),
+                if expected { "pass" } else { "fail" }
+            );
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_server(
+    host: &str,
+    port: u16,
+    platforms: &str,
+    config: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
     let enabled_platforms: Vec<&str> = platforms.split(',').map(|s| s.trim()).collect();
 
     tracing::info!("Starting RSR server on {}:{}", host, port);
     tracing::info!("Enabled platforms: {:?}", enabled_platforms);
 
-    rsr_engine::server::run(host, port, &enabled_platforms).await?;
+    rsr_engine::server::run(host, port, &enabled_platforms, config).await?;
+
+    Ok(())
+}
+
+async fn export_reports(
+    paths: &[PathBuf],
+    output: &std::path::Path,
+    owner: &str,
+    title: Option<String>,
+    config: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
+    let branding = load_branding(config)?;
+    let title = title.unwrap_or_else(|| format!("{} compliance summary", branding.program));
+    let engine = ComplianceEngine::new();
+    let mut reports = Vec::new();
+
+    for path in paths {
+        // Canonicalize so "." is reported under the directory's name
+        let path = path.canonicalize()?;
+        let mut status = engine.check_local(&path).await?;
+        status.repo.owner = owner.to_string();
+        reports.push(status);
+    }
+
+    let written = report::export_static(output, &title, &reports, engine.standard(), &branding)?;
+    tracing::info!("Wrote {} files to {}", written.len(), output.display());
 
     Ok(())
 }
 
-fn generate_badge(tier: &str, output: Option<&std::path::Path>, style: &str) -> anyhow::Result<()> {
+async fn install_rulepack(
+    spec: &str,
+    config: Option<&std::path::Path>,
+    registry: Option<String>,
+    trusted_keys: Vec<String>,
+    dir: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let mut settings = match config {
+        Some(path) => EngineConfig::from_toml(&std::fs::read_to_string(path)?)?.rulepacks,
+        None => Default::default(),
+    };
+    settings.trusted_keys.extend(trusted_keys);
+    let registry = registry
+        .or(settings.registry)
+        .ok_or_else(|| anyhow::anyhow!("No rulepack registry: pass --registry or set rulepacks.registry"))?;
+    let directory = dir.unwrap_or(settings.directory);
+
+    let (name, version) = rulepack::parse_spec(spec)?;
+    let pack = RulepackRegistry::new(&registry)?
+        .install(name, version, &directory, &settings.trusted_keys)
+        .await?;
+
+    println!(
+        "Installed {}@{} to {} ({} checks, {} retired)",
+        pack.name,
+        pack.version,
+        directory.join(name).join(version).display(),
+        pack.checks.len(),
+        pack.policy.retire.len()
+    );
+    if !settings.packs.iter().any(|p| p == spec) {
+        println!("Add \"{}\" to rulepacks.packs to load it", spec);
+    }
+
+    Ok(())
+}
+
+/// Branding from an engine configuration file, or the standard names
+fn load_branding(config: Option<&std::path::Path>) -> anyhow::Result<Branding> {
+    Ok(match config {
+        Some(path) => EngineConfig::from_toml(&std::fs::read_to_string(path)?)?.branding,
+        None => Branding::default(),
+    })
+}
+
+fn generate_badge(
+    tier: &str,
+    output: Option<&std::path::Path>,
+    style: &str,
+    config: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
     let cert_tier = parse_tier(tier)?;
-    let svg = generate_badge_svg(&cert_tier, style);
+    let options = BadgeOptions {
+        style: style.parse()?,
+        ..BadgeOptions::branded(&load_branding(config)?)
+    };
+    let svg = badge::render(cert_tier, 0.0, &options);
 
     match output {
         Some(path) => {
@@ -187,7 +552,119 @@ fn generate_badge(tier: &str, output: Option<&std::path::Path>, style: &str) ->
     Ok(())
 }
 
-fn init_config(path: &PathBuf, tier: &str) -> anyhow::Result<()> {
+async fn run_self_check(path: Option<&std::path::Path>, format: &str) -> anyhow::Result<()> {
+    let engine = ComplianceEngine::new();
+    let certification = match path {
+        Some(path) => SelfCertification::run_at(&engine, path).await?,
+        None => SelfCertification::run(&engine).await?,
+    };
+
+    match (format, &certification.status) {
+        ("json", _) => println!("{}", serde_json::to_string_pretty(&certification)?),
+        (_, Some(status)) => print_status(status),
+        (_, None) => {}
+    }
+
+    if !certification.is_certified() {
+        certification.log();
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+async fn run_migrations(dry_run: bool) -> anyhow::Result<()> {
+    if dry_run {
+        let docs = rsr_engine::db::documents::connect_from_env().await?;
+        let pending = docs.pending_migrations().await?;
+        if pending.is_empty() {
+            println!("No pending document store migrations");
+        }
+        for migration in pending {
+            println!("-- Migration {} ({}), checksum {}", migration.version, migration.name, migration.checksum());
+            println!("{}", migration.statements.trim());
+            println!();
+        }
+        return Ok(());
+    }
+
+    // Graph collections are created idempotently alongside the documents schema
+    rsr_engine::db::init().await?.migrate().await?;
+    tracing::info!("Migrations complete");
+
+    Ok(())
+}
+
+/// Benchmark history is only kept in SurrealDB
+#[cfg(not(feature = "documents-surrealdb"))]
+async fn compare_benchmarks(_: &std::path::Path, _: &str, _: f64, _: bool, _: String, _: String) -> anyhow::Result<()> {
+    anyhow::bail!("Benchmark history is kept in SurrealDB, which this build lacks (feature documents-surrealdb)")
+}
+
+#[cfg(feature = "documents-surrealdb")]
+async fn compare_benchmarks(
+    criterion_dir: &std::path::Path,
+    baseline_branch: &str,
+    threshold: f64,
+    record: bool,
+    commit: String,
+    branch: String,
+) -> anyhow::Result<()> {
+    use rsr_engine::db::benchmarks::{self, BenchmarkRun};
+
+    let results = benchmarks::read_criterion(criterion_dir)?;
+    if results.is_empty() {
+        anyhow::bail!("No criterion results under {}; run `cargo bench` first", criterion_dir.display());
+    }
+
+    let store = rsr_engine::db::documents::SurrealPool::connect_from_env().await?;
+    let regressions = match store.latest_benchmark_run(baseline_branch).await? {
+        Some(baseline) => {
+            println!("Baseline: {} on {} ({})", baseline.commit, baseline.branch, baseline.recorded_at);
+            for result in &results {
+                match baseline.results.iter().find(|before| before.name == result.name) {
+                    Some(before) => println!(
+                        "  {:<40} {:>12.0} ns  ({:+.1}%)",
+                        result.name,
+                        result.mean_ns,
+                        (result.mean_ns - before.mean_ns) * 100.0 / before.mean_ns
+                    ),
+                    None => println!("  {:<40} {:>12.0} ns  (new)", result.name, result.mean_ns),
+                }
+            }
+            benchmarks::regressions(&baseline, &results, threshold)
+        }
+        None => {
+            println!("No baseline recorded for {}", baseline_branch);
+            Vec::new()
+        }
+    };
+
+    if record {
+        let run = BenchmarkRun {
+            commit,
+            branch,
+            recorded_at: chrono::Utc::now(),
+            results,
+        };
+        let id = store.record_benchmark_run(&run).await?;
+        tracing::info!("Recorded benchmark run {} for {}", id, run.branch);
+    }
+
+    if !regressions.is_empty() {
+        for regression in &regressions {
+            eprintln!(
+                "Regression: {} went from {:.0} ns to {:.0} ns ({:+.1}%)",
+                regression.name, regression.baseline_ns, regression.current_ns, regression.change_percent
+            );
+        }
+        anyhow::bail!("{} benchmark(s) regressed by more than {}%", regressions.len(), threshold);
+    }
+
+    Ok(())
+}
+
+fn init_config(path: &Path, tier: &str) -> anyhow::Result<()> {
     let config_path = path.join(".rsr.toml");
 
     if config_path.exists() {
@@ -202,6 +679,8 @@ fn init_config(path: &PathBuf, tier: &str) -> anyhow::Result<()> {
 [compliance]
 target_tier = "{tier}"
 strict_mode = false
+# Sets the release cadence expected: experimental, active, stable or maintenance
+maturity = "active"
 
 [checks]
 # License configuration
@@ -270,32 +749,3 @@ fn print_status(status: &rsr_engine::ComplianceStatus) {
     println!("{}", "-".repeat(60));
     println!();
 }
-
-fn generate_badge_svg(tier: &CertificationTier, _style: &str) -> String {
-    let color = tier.color();
-    let label = tier.code();
-
-    format!(
-        r##"<svg xmlns="https://www.w3.org/2000/svg" width="120" height="20">
-<linearGradient id="b" x2="0" y2="100%">
-<stop offset="0" stop-color="#bbb" stop-opacity=".1"/>
-<stop offset="1" stop-opacity=".1"/>
-</linearGradient>
-<clipPath id="a">
-<rect width="120" height="20" rx="3" fill="#fff"/>
-</clipPath>
-<g clip-path="url(#a)">
-<path fill="#555" d="M0 0h45v20H0z"/>
-<path fill="{}" d="M45 0h75v20H45z"/>
-<path fill="url(#b)" d="M0 0h120v20H0z"/>
-</g>
-<g fill="#fff" text-anchor="middle" font-family="Verdana,sans-serif" font-size="11">
-<text x="22.5" y="15" fill="#010101" fill-opacity=".3">RSR</text>
-<text x="22.5" y="14">RSR</text>
-<text x="82.5" y="15" fill="#010101" fill-opacity=".3">{}</text>
-<text x="82.5" y="14">{}</text>
-</g>
-</svg>"##,
-        color, label, label
-    )
-}
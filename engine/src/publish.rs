@@ -0,0 +1,301 @@
+//! Publishing badges and reports to object storage
+//!
+//! After a repository is rescanned its badges, HTML report and status JSON
+//! are uploaded to an S3-compatible bucket with `Cache-Control` headers, so
+//! README badges can be served from a CDN in front of the bucket instead of
+//! the engine's API. Google Cloud Storage is supported through its
+//! S3-compatible XML API (`https://storage.googleapis.com` with HMAC keys).
+//!
+//! Objects are written under `{prefix}{platform}/{owner}/{repo}/`:
+//! - `badge.svg` - tier badge
+//! - `badge-percent.svg`, `badge-grade.svg` - score badges
+//! - `report.html` - self-contained report
+//! - `status.json` - the raw compliance status
+
+use crate::adapters::http::{HttpLayer, RetryPolicy, SendVia};
+use crate::badge::{self, BadgeOptions, BadgeValue};
+use crate::branding::Branding;
+use crate::{report, ComplianceStatus, Result, RsrError};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Where and how to publish - secrets are named by environment variable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishConfig {
+    /// S3-compatible endpoint, e.g. `https://s3.eu-west-1.amazonaws.com`
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    /// Key prefix, e.g. `rsr/`
+    #[serde(default)]
+    pub prefix: String,
+    /// Address the bucket as `{endpoint}/{bucket}` rather than `{bucket}.{host}`
+    #[serde(default = "default_path_style")]
+    pub path_style: bool,
+    #[serde(default = "default_access_key_env")]
+    pub access_key_env: String,
+    #[serde(default = "default_secret_key_env")]
+    pub secret_key_env: String,
+    /// `max-age` for badges and reports
+    #[serde(default = "default_max_age")]
+    pub max_age_secs: u64,
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_path_style() -> bool {
+    true
+}
+
+fn default_access_key_env() -> String {
+    "AWS_ACCESS_KEY_ID".to_string()
+}
+
+fn default_secret_key_env() -> String {
+    "AWS_SECRET_ACCESS_KEY".to_string()
+}
+
+fn default_max_age() -> u64 {
+    300
+}
+
+/// Storage for published objects
+#[async_trait::async_trait]
+pub trait ObjectStore: Send + Sync {
+    async fn put_object(&self, key: &str, body: Vec<u8>, content_type: &str, cache_control: &str) -> Result<()>;
+}
+
+/// S3-compatible bucket, signed with SigV4
+pub struct S3Store {
+    client: reqwest::Client,
+    http: HttpLayer,
+    endpoint: reqwest::Url,
+    bucket: String,
+    region: String,
+    path_style: bool,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl S3Store {
+    /// Build a store, resolving credentials from the environment
+    pub fn from_config(config: &PublishConfig) -> Result<Self> {
+        let endpoint = reqwest::Url::parse(&config.endpoint)
+            .map_err(|e| RsrError::Config(format!("Invalid publish endpoint: {}", e)))?;
+        let env = |name: &str| {
+            std::env::var(name)
+                .map_err(|_| RsrError::Config(format!("Publishing requires environment variable {}", name)))
+        };
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            http: HttpLayer::new("object-storage", RetryPolicy::default()),
+            endpoint,
+            bucket: config.bucket.clone(),
+            region: config.region.clone(),
+            path_style: config.path_style,
+            access_key_id: env(&config.access_key_env)?,
+            secret_access_key: env(&config.secret_key_env)?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+
+    /// URL of an object, and the canonical URI it is signed with
+    fn object_url(&self, key: &str) -> Result<(reqwest::Url, String)> {
+        let encoded: Vec<_> = key.split('/').map(|segment| urlencoding::encode(segment).into_owned()).collect();
+        let mut path = format!("/{}", encoded.join("/"));
+        let mut url = self.endpoint.clone();
+
+        if self.path_style {
+            path = format!("/{}{}", urlencoding::encode(&self.bucket), path);
+        } else {
+            let host = url
+                .host_str()
+                .ok_or_else(|| RsrError::Config("Publish endpoint has no host".to_string()))?;
+            url.set_host(Some(&format!("{}.{}", self.bucket, host)))
+                .map_err(|e| RsrError::Config(format!("Invalid bucket host: {}", e)))?;
+        }
+        url.set_path(&path);
+
+        Ok((url, path))
+    }
+
+    /// `Authorization` header value for a PUT
+    fn sign(&self, host: &str, path: &str, payload_hash: &str, now: chrono::DateTime<chrono::Utc>) -> String {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+
+        let mut canonical_headers = format!(
+            "host:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n",
+            host, payload_hash, amz_date
+        );
+        let mut signed_headers = String::from("host;x-amz-content-sha256;x-amz-date");
+        if let Some(ref token) = self.session_token {
+            canonical_headers.push_str(&format!("x-amz-security-token:{}\n", token));
+            signed_headers.push_str(";x-amz-security-token");
+        }
+
+        let canonical_request = format!("PUT\n{}\n\n{}\n{}\n{}", path, canonical_headers, signed_headers, payload_hash);
+
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let key = [self.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(hmac_sha256(format!("AWS4{}", self.secret_access_key).as_bytes(), date.as_bytes()), |key, part| {
+                hmac_sha256(&key, part.as_bytes())
+            });
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectStore for S3Store {
+    async fn put_object(&self, key: &str, body: Vec<u8>, content_type: &str, cache_control: &str) -> Result<()> {
+        let (url, path) = self.object_url(key)?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(RsrError::Config("Publish endpoint has no host".to_string())),
+        };
+
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let now = chrono::Utc::now();
+        let authorization = self.sign(&host, &path, &payload_hash, now);
+
+        let mut request = self
+            .client
+            .put(url)
+            .header("Content-Type", content_type)
+            .header("Cache-Control", cache_control)
+            .header("X-Amz-Content-Sha256", &payload_hash)
+            .header("X-Amz-Date", now.format("%Y%m%dT%H%M%SZ").to_string())
+            .header("Authorization", authorization)
+            .body(body);
+        if let Some(ref token) = self.session_token {
+            request = request.header("X-Amz-Security-Token", token);
+        }
+
+        let response = request.send_via(&self.http).await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(RsrError::Platform(format!("Upload of {} failed ({}): {}", key, status, text)));
+        }
+
+        Ok(())
+    }
+}
+
+/// Uploads a repository's rendered badges and report
+pub struct Publisher {
+    store: Box<dyn ObjectStore>,
+    prefix: String,
+    max_age_secs: u64,
+    branding: Branding,
+}
+
+impl Publisher {
+    pub fn new(store: Box<dyn ObjectStore>) -> Self {
+        Self {
+            store,
+            prefix: String::new(),
+            max_age_secs: default_max_age(),
+            branding: Branding::default(),
+        }
+    }
+
+    /// Publish to the S3-compatible bucket described by `config`
+    pub fn from_config(config: &PublishConfig) -> Result<Self> {
+        Ok(Self::new(Box::new(S3Store::from_config(config)?))
+            .with_prefix(&config.prefix)
+            .with_max_age(config.max_age_secs))
+    }
+
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn with_max_age(mut self, max_age_secs: u64) -> Self {
+        self.max_age_secs = max_age_secs;
+        self
+    }
+
+    /// Label badges and reports with the deployment's names
+    pub fn with_branding(mut self, branding: Branding) -> Self {
+        self.branding = branding;
+        self
+    }
+
+    /// Key prefix under which `status`'s objects are written
+    pub fn object_prefix(&self, status: &ComplianceStatus) -> String {
+        format!(
+            "{}{}/{}/{}/",
+            self.prefix, status.repo.platform, status.repo.owner, status.repo.repo
+        )
+    }
+
+    /// Upload the badges, report and status JSON for `status`, returning the
+    /// keys written
+    pub async fn publish(&self, status: &ComplianceStatus) -> Result<Vec<String>> {
+        let prefix = self.object_prefix(status);
+        // CDNs may serve a stale copy briefly while revalidating
+        let cache_control = format!(
+            "public, max-age={}, stale-while-revalidate={}",
+            self.max_age_secs, self.max_age_secs
+        );
+
+        let badge = |value| {
+            let options = BadgeOptions {
+                value,
+                ..BadgeOptions::branded(&self.branding)
+            };
+            badge::render(status.tier, status.score, &options).into_bytes()
+        };
+        let objects = [
+            ("badge.svg", badge(BadgeValue::Tier), "image/svg+xml"),
+            ("badge-percent.svg", badge(BadgeValue::Percent), "image/svg+xml"),
+            ("badge-grade.svg", badge(BadgeValue::Grade), "image/svg+xml"),
+            (
+                "report.html",
+                report::render_annotated_report(status, &[], &self.branding).into_bytes(),
+                "text/html; charset=utf-8",
+            ),
+            ("status.json", serde_json::to_vec(status)?, "application/json"),
+        ];
+
+        let mut written = Vec::new();
+        for (name, body, content_type) in objects {
+            let key = format!("{}{}", prefix, name);
+            self.store.put_object(&key, body, content_type, &cache_control).await?;
+            written.push(key);
+        }
+
+        tracing::info!("Published {} objects for {}", written.len(), status.repo);
+        Ok(written)
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
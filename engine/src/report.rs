@@ -0,0 +1,378 @@
+//! HTML compliance reports
+//!
+//! Reports are single self-contained pages with inline styles and badge, no
+//! scripts and no external assets. They can be published as-is, e.g. on
+//! GitHub Pages, without exposing the engine's server.
+//!
+//! Pages use semantic landmarks, captioned tables and a skip link. Pass/fail
+//! is always spelled out rather than conveyed by color alone, and colors meet
+//! WCAG AA contrast in both light and dark schemes.
+//!
+//! Titles, tier names and the badge follow the deployment's [`Branding`],
+//! and its links are listed in the footer.
+
+use crate::badge::{self, escape, BadgeOptions, BadgeValue, Palette};
+use crate::branding::Branding;
+use crate::config::PolicyConfig;
+use crate::db::annotations::Annotation;
+use crate::hierarchy::{self, HierarchyConfig};
+use crate::{CertificationTier, ComplianceStatus, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const TIERS: [CertificationTier; 4] = [
+    CertificationTier::Bronze,
+    CertificationTier::Silver,
+    CertificationTier::Gold,
+    CertificationTier::Rhodium,
+];
+
+const STYLE: &str = r#"
+:root { color-scheme: light dark; --fg: #1a1a1a; --bg: #fff; --muted: #555; --border: #ccc; --pass: #116329; --fail: #a40e26; --link: #0550ae; }
+@media (prefers-color-scheme: dark) {
+  :root { --fg: #e6e6e6; --bg: #161616; --muted: #b0b0b0; --border: #444; --pass: #56d364; --fail: #ff7b72; --link: #79c0ff; }
+}
+body { font: 16px/1.5 system-ui, sans-serif; color: var(--fg); background: var(--bg); margin: 0 auto; max-width: 60rem; padding: 1rem; }
+a { color: var(--link); }
+a:focus, summary:focus { outline: 3px solid var(--link); outline-offset: 2px; }
+.skip { position: absolute; left: -999px; }
+.skip:focus { left: 1rem; top: 1rem; background: var(--bg); padding: .5rem; }
+.visually-hidden { position: absolute; width: 1px; height: 1px; overflow: hidden; clip: rect(0 0 0 0); white-space: nowrap; }
+table { border-collapse: collapse; width: 100%; margin: 1rem 0; }
+caption { text-align: left; font-weight: bold; padding: .5rem 0; }
+th, td { border: 1px solid var(--border); padding: .4rem .6rem; text-align: left; vertical-align: top; }
+dl { display: grid; grid-template-columns: max-content 1fr; gap: .25rem 1rem; }
+dt { font-weight: bold; }
+dd { margin: 0; }
+.pass { color: var(--pass); font-weight: bold; }
+.fail { color: var(--fail); font-weight: bold; }
+pre { white-space: pre-wrap; }
+footer { color: var(--muted); font-size: .875rem; margin-top: 2rem; }
+"#;
+
+/// Render a repository's compliance report as a standalone HTML page
+pub fn render_report(status: &ComplianceStatus) -> String {
+    render_annotated_report(status, &[], &Branding::default())
+}
+
+/// Render a report with its annotations: report-wide ones under the summary
+/// and check ones beside the check's result
+pub fn render_annotated_report(status: &ComplianceStatus, annotations: &[Annotation], branding: &Branding) -> String {
+    page(
+        &report_title(status, branding),
+        &report_body(status, annotations, branding),
+        None,
+        branding,
+    )
+}
+
+/// Render a summary of several repositories' reports, linking each to the
+/// page written by [`export_static`]. Repositories checked against a
+/// standard other than `current` are flagged as drifted.
+pub fn render_summary(title: &str, reports: &[ComplianceStatus], current: &[String], branding: &Branding) -> String {
+    let mut reports: Vec<_> = reports.iter().collect();
+    reports.sort_by(|a, b| b.tier.cmp(&a.tier).then_with(|| a.repo.to_string().cmp(&b.repo.to_string())));
+
+    let mut body = format!("<h1>{}</h1>\n", escape(title));
+
+    body.push_str("<table>\n<caption>Repositories by tier</caption>\n<thead><tr><th scope=\"col\">Tier</th><th scope=\"col\">Repositories</th></tr></thead>\n<tbody>\n");
+    for tier in std::iter::once(CertificationTier::None).chain(TIERS).rev() {
+        let count = reports.iter().filter(|r| r.tier == tier).count();
+        body.push_str(&format!(
+            "<tr><th scope=\"row\">{} {}</th><td>{}</td></tr>\n",
+            escape(branding.tier_name(tier)),
+            escape(branding.tier_code(tier)),
+            count
+        ));
+    }
+    body.push_str("</tbody>\n</table>\n");
+
+    let mut standards: BTreeMap<&[String], usize> = BTreeMap::new();
+    for status in &reports {
+        *standards.entry(&status.standard).or_default() += 1;
+    }
+    body.push_str("<table>\n<caption>Repositories by standard version</caption>\n<thead><tr><th scope=\"col\">Standard</th><th scope=\"col\">Repositories</th><th scope=\"col\">Status</th></tr></thead>\n<tbody>\n");
+    for (standard, count) in standards {
+        body.push_str(&format!(
+            "<tr><th scope=\"row\">{}</th><td>{}</td><td>{}</td></tr>\n",
+            standard_text(standard),
+            count,
+            drift(standard, current)
+        ));
+    }
+    body.push_str("</tbody>\n</table>\n");
+
+    // Only worth a table when repositories sit under more than one unit
+    let rollups = hierarchy::rollup(reports.iter().copied(), &HierarchyConfig::default(), &PolicyConfig::default());
+    if rollups.len() > 1 {
+        body.push_str("<table>\n<caption>Compliance by organization</caption>\n<thead><tr><th scope=\"col\">Organization</th><th scope=\"col\">Repositories</th><th scope=\"col\">Certified</th><th scope=\"col\">Below target</th><th scope=\"col\">Average score</th></tr></thead>\n<tbody>\n");
+        for rollup in rollups {
+            body.push_str(&format!(
+                "<tr><th scope=\"row\" style=\"padding-left: {}rem\">{}</th><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                0.6 + rollup.depth as f32 * 1.5,
+                escape(&rollup.unit),
+                rollup.repositories,
+                rollup.certified,
+                rollup.below_target,
+                badge::format_percent(rollup.average_score, "en")
+            ));
+        }
+        body.push_str("</tbody>\n</table>\n");
+    }
+
+    body.push_str(
+        "<table>\n<caption>Compliance by repository</caption>\n<thead><tr><th scope=\"col\">Repository</th><th scope=\"col\">Tier</th><th scope=\"col\">Score</th><th scope=\"col\">Checks passed</th><th scope=\"col\">Standard</th><th scope=\"col\">Checked</th></tr></thead>\n<tbody>\n",
+    );
+    for status in reports {
+        let passed = status.checks.iter().filter(|c| c.passed).count();
+        body.push_str(&format!(
+            "<tr><th scope=\"row\"><a href=\"{}\">{}/{}</a></th><td>{} {}</td><td>{}</td><td>{} of {}</td><td>{}<br>{}</td><td>{}</td></tr>\n",
+            escape(&report_path(status).to_string_lossy().replace('\\', "/")),
+            escape(&status.repo.owner),
+            escape(&status.repo.repo),
+            escape(branding.tier_name(status.tier)),
+            escape(branding.tier_code(status.tier)),
+            badge::format_percent(status.score, "en"),
+            passed,
+            status.checks.len(),
+            standard_text(&status.standard),
+            drift(&status.standard, current),
+            time(status.timestamp)
+        ));
+    }
+    body.push_str("</tbody>\n</table>\n");
+
+    page(title, &body, None, branding)
+}
+
+/// Write a summary page and one report page per repository under `dir`,
+/// returning the files written. `dir` can be published as a static site.
+pub fn export_static(
+    dir: &Path,
+    title: &str,
+    reports: &[ComplianceStatus],
+    current: &[String],
+    branding: &Branding,
+) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    std::fs::create_dir_all(dir)?;
+
+    let index = dir.join("index.html");
+    std::fs::write(&index, render_summary(title, reports, current, branding))?;
+    written.push(index);
+
+    for status in reports {
+        let path = dir.join(report_path(status));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let body = report_body(status, &[], branding);
+        std::fs::write(&path, page(&report_title(status, branding), &body, Some("../../index.html"), branding))?;
+        written.push(path);
+    }
+
+    // Stop GitHub Pages running the files through Jekyll
+    let nojekyll = dir.join(".nojekyll");
+    std::fs::write(&nojekyll, "")?;
+    written.push(nojekyll);
+
+    Ok(written)
+}
+
+/// Location of a repository's page relative to the export root
+pub fn report_path(status: &ComplianceStatus) -> PathBuf {
+    PathBuf::from(path_segment(&status.repo.owner))
+        .join(path_segment(&status.repo.repo))
+        .join("index.html")
+}
+
+/// Keep a path segment inside the export directory
+fn path_segment(name: &str) -> String {
+    let segment: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '-' })
+        .collect();
+    let segment = segment.trim_start_matches('.');
+    if segment.is_empty() {
+        "_".to_string()
+    } else {
+        segment.to_string()
+    }
+}
+
+/// Standard versions, one per line; empty for reports that predate them
+fn standard_text(standard: &[String]) -> String {
+    if standard.is_empty() {
+        return "Unknown".to_string();
+    }
+    standard.iter().map(|s| format!("<code>{}</code>", escape(s))).collect::<Vec<_>>().join("<br>")
+}
+
+/// Canonical identity of a linked repository
+fn canonical_text(status: &ComplianceStatus) -> String {
+    status
+        .canonical
+        .as_ref()
+        .map(|canonical| format!("<dt>Canonical source</dt><dd><code>{}</code></dd>\n", escape(&canonical.to_string())))
+        .unwrap_or_default()
+}
+
+/// Whether a report's standard matches the current one
+fn drift(standard: &[String], current: &[String]) -> &'static str {
+    if standard == current {
+        "Current"
+    } else {
+        "<strong>Drifted</strong>"
+    }
+}
+
+fn report_title(status: &ComplianceStatus, branding: &Branding) -> String {
+    format!("{} compliance report: {}/{}", branding.program, status.repo.owner, status.repo.repo)
+}
+
+fn report_body(status: &ComplianceStatus, annotations: &[Annotation], branding: &Branding) -> String {
+    let passed = status.checks.iter().filter(|c| c.passed).count();
+    let badge = badge::render(
+        status.tier,
+        status.score,
+        &BadgeOptions {
+            value: BadgeValue::Tier,
+            palette: Palette::HighContrast,
+            ..BadgeOptions::branded(branding)
+        },
+    );
+
+    let mut body = format!(
+        "<h1>{}</h1>\n{}\n<dl>\n<dt>Tier</dt><dd><span aria-hidden=\"true\">{} </span>{} {}</dd>\n<dt>Score</dt><dd>{}</dd>\n<dt>Checks passed</dt><dd>{} of {}</dd>\n<dt>Standard</dt><dd>{}</dd>\n{}<dt>Checked</dt><dd>{}</dd>\n</dl>\n",
+        escape(&report_title(status, branding)),
+        badge,
+        status.tier.symbol(),
+        escape(branding.tier_name(status.tier)),
+        escape(branding.tier_code(status.tier)),
+        badge::format_percent(status.score, "en"),
+        passed,
+        status.checks.len(),
+        standard_text(&status.standard),
+        canonical_text(status),
+        time(status.timestamp)
+    );
+
+    let general: Vec<_> = annotations.iter().filter(|a| a.check_id.is_none()).collect();
+    if !general.is_empty() {
+        body.push_str("<section aria-labelledby=\"annotations\">\n<h2 id=\"annotations\">Annotations</h2>\n<ul>\n");
+        for annotation in general {
+            body.push_str(&format!("<li>{}</li>\n", annotation_text(annotation)));
+        }
+        body.push_str("</ul>\n</section>\n");
+    }
+
+    for tier in TIERS {
+        let checks: Vec<_> = status.checks.iter().filter(|c| c.tier == tier).collect();
+        if checks.is_empty() {
+            continue;
+        }
+
+        // Ids stay the standard names so links into reports survive renaming
+        let id = crate::branding::standard_name(tier).to_lowercase();
+        body.push_str(&format!(
+            "<section aria-labelledby=\"{id}\">\n<h2 id=\"{id}\">{} checks</h2>\n<table>\n<caption>{} ({}) checks and results</caption>\n<thead><tr><th scope=\"col\">Check</th><th scope=\"col\">Result</th><th scope=\"col\">Details</th></tr></thead>\n<tbody>\n",
+            escape(branding.tier_name(tier)),
+            escape(branding.tier_name(tier)),
+            escape(branding.tier_code(tier)),
+            id = id
+        ));
+        for check in checks {
+            let result = if check.passed {
+                "<span class=\"pass\"><span aria-hidden=\"true\">✓ </span>Passed</span>"
+            } else {
+                "<span class=\"fail\"><span aria-hidden=\"true\">✗ </span>Failed</span>"
+            };
+            let details = match check.details {
+                Some(ref details) => format!(
+                    "{}<details><summary>More<span class=\"visually-hidden\"> about {}</span></summary><pre>{}</pre></details>",
+                    escape(&check.message),
+                    escape(&check.name),
+                    escape(details)
+                ),
+                None => escape(&check.message),
+            };
+            let notes: String = annotations
+                .iter()
+                .filter(|a| a.check_id.as_deref() == Some(check.id.as_str()))
+                .map(|a| format!("<br>{}", annotation_text(a)))
+                .collect();
+            body.push_str(&format!(
+                "<tr><th scope=\"row\">{}<br><code>{}</code></th><td>{}</td><td>{}{}</td></tr>\n",
+                escape(&check.name),
+                escape(&check.id),
+                result,
+                details,
+                notes
+            ));
+        }
+        body.push_str("</tbody>\n</table>\n</section>\n");
+    }
+
+    body
+}
+
+/// One annotation: kind, message, reference and who added it when
+fn annotation_text(annotation: &Annotation) -> String {
+    let reference = annotation
+        .reference
+        .as_ref()
+        .map(|reference| format!(" ({})", escape(reference)))
+        .unwrap_or_default();
+    format!(
+        "<strong>{}:</strong> {}{} <small>{}, {}</small>",
+        annotation.kind.label(),
+        escape(&annotation.message),
+        reference,
+        escape(&annotation.author),
+        time(annotation.created_at)
+    )
+}
+
+fn time(timestamp: chrono::DateTime<chrono::Utc>) -> String {
+    format!(
+        "<time datetime=\"{}\">{}</time>",
+        timestamp.to_rfc3339(),
+        timestamp.format("%Y-%m-%d %H:%M UTC")
+    )
+}
+
+fn page(title: &str, body: &str, back: Option<&str>, branding: &Branding) -> String {
+    let nav = back
+        .map(|href| format!("<nav aria-label=\"Breadcrumb\"><a href=\"{}\">All repositories</a></nav>\n", escape(href)))
+        .unwrap_or_default();
+    let links: String = branding
+        .links
+        .iter()
+        .map(|link| format!(" · <a href=\"{}\">{}</a>", escape(&link.url), escape(&link.label)))
+        .collect();
+
+    format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>{style}</style>
+</head>
+<body>
+<a class="skip" href="#main">Skip to content</a>
+{nav}<main id="main">
+{body}</main>
+<footer>Generated by rsr {version}{links}</footer>
+</body>
+</html>
+"##,
+        title = escape(title),
+        style = STYLE,
+        nav = nav,
+        body = body,
+        version = env!("CARGO_PKG_VERSION"),
+        links = links
+    )
+}
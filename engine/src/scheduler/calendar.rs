@@ -0,0 +1,164 @@
+//! Calendar exclusions for scheduled scans
+//!
+//! Organizations can block out change freezes, weekends, and recurring
+//! maintenance windows. Exclusions only defer non-urgent scans - webhook
+//! triggered scans are never held back by the calendar.
+
+use super::quota::ScanQuota;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Upper bound on how many back-to-back exclusions are walked when looking
+/// for the next open slot (guards against overlapping rules looping forever)
+const MAX_EXCLUSION_HOPS: usize = 64;
+
+/// A period during which scheduled scans must not run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CalendarExclusion {
+    /// Saturdays and Sundays in the tenant's local time
+    Weekends,
+    /// One-off change freeze between two instants
+    Freeze {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Recurring weekly maintenance window in the tenant's local time
+    Weekly {
+        day: Weekday,
+        start: NaiveTime,
+        duration_minutes: u32,
+    },
+}
+
+impl CalendarExclusion {
+    /// If `at` falls inside this exclusion, return the instant it ends
+    pub fn active_until(&self, at: DateTime<Utc>, offset: FixedOffset) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Weekends => {
+                let local = at.with_timezone(&offset);
+                let days_left = match local.weekday() {
+                    Weekday::Sat => 2,
+                    Weekday::Sun => 1,
+                    _ => return None,
+                };
+                let monday = local.date_naive() + Duration::days(days_left);
+                offset
+                    .from_local_datetime(&monday.and_time(NaiveTime::MIN))
+                    .single()
+                    .map(|dt| dt.with_timezone(&Utc))
+            }
+            Self::Freeze { start, end, .. } => (at >= *start && at < *end).then_some(*end),
+            Self::Weekly {
+                day,
+                start,
+                duration_minutes,
+            } => {
+                let local = at.with_timezone(&offset);
+                let duration = Duration::minutes(i64::from(*duration_minutes));
+
+                // A window may have opened earlier this week and still be running,
+                // so check the most recent occurrence of `day` (today included).
+                let days_back = (7 + local.weekday().num_days_from_monday()
+                    - day.num_days_from_monday())
+                    % 7;
+                let opened = local.date_naive() - Duration::days(i64::from(days_back));
+                let window_start = offset
+                    .from_local_datetime(&opened.and_time(*start))
+                    .single()?
+                    .with_timezone(&Utc);
+
+                // The window may wrap past the end of the week
+                [window_start, window_start - Duration::weeks(1)]
+                    .into_iter()
+                    .map(|s| (s, s + duration))
+                    .find(|(s, e)| at >= *s && at < *e)
+                    .map(|(_, e)| e)
+            }
+        }
+    }
+
+    /// Human-readable description for logs and API responses
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Weekends => "weekend".to_string(),
+            Self::Freeze { reason, end, .. } => match reason {
+                Some(reason) => format!("change freeze until {} ({})", end.to_rfc3339(), reason),
+                None => format!("change freeze until {}", end.to_rfc3339()),
+            },
+            Self::Weekly {
+                day,
+                start,
+                duration_minutes,
+            } => format!(
+                "maintenance window {} {} for {}m",
+                day, start, duration_minutes
+            ),
+        }
+    }
+}
+
+/// Per-tenant scheduling policy
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchedulingPolicy {
+    /// Offset from UTC used for weekend and weekly window boundaries
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+    /// Periods during which scheduled scans are deferred
+    #[serde(default)]
+    pub exclusions: Vec<CalendarExclusion>,
+    /// Scans per hour beyond which scans are parked and coalesced
+    #[serde(default)]
+    pub quota: ScanQuota,
+}
+
+impl SchedulingPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_exclusion(mut self, exclusion: CalendarExclusion) -> Self {
+        self.exclusions.push(exclusion);
+        self
+    }
+
+    pub fn with_utc_offset_minutes(mut self, minutes: i32) -> Self {
+        self.utc_offset_minutes = minutes;
+        self
+    }
+
+    pub fn with_quota(mut self, quota: ScanQuota) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    fn offset(&self) -> FixedOffset {
+        FixedOffset::east_opt(self.utc_offset_minutes * 60)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).expect("zero offset is valid"))
+    }
+
+    /// The first exclusion covering `at`, with the instant it ends
+    pub fn blocking_exclusion(&self, at: DateTime<Utc>) -> Option<(&CalendarExclusion, DateTime<Utc>)> {
+        let offset = self.offset();
+        self.exclusions
+            .iter()
+            .find_map(|e| e.active_until(at, offset).map(|until| (e, until)))
+    }
+
+    /// Earliest instant at or after `at` that is not covered by any exclusion.
+    ///
+    /// Returns `None` if exclusions chain together for longer than we are
+    /// willing to walk, which almost always means a misconfigured policy.
+    pub fn next_open(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut candidate = at;
+        for _ in 0..MAX_EXCLUSION_HOPS {
+            match self.blocking_exclusion(candidate) {
+                Some((_, until)) => candidate = until,
+                None => return Some(candidate),
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,114 @@
+//! Scan scheduling
+//!
+//! Decides whether a requested scan may run now. Webhook-triggered and manual
+//! scans always run; scheduled rescans and backfills honour each tenant's
+//! calendar exclusions. Every scan counts against the tenant's scan quota,
+//! see [`quota`].
+
+pub mod calendar;
+pub mod quota;
+
+pub use calendar::*;
+pub use quota::ScanQuota;
+
+use crate::RepoRef;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What caused a scan to be requested
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanTrigger {
+    /// Platform webhook (push, pull request, ...)
+    Webhook,
+    /// Operator or API request
+    Manual,
+    /// Periodic rescan
+    Scheduled,
+    /// Historical backfill
+    Backfill,
+}
+
+impl ScanTrigger {
+    /// Urgent scans bypass calendar exclusions
+    pub fn is_urgent(&self) -> bool {
+        matches!(self, Self::Webhook | Self::Manual)
+    }
+}
+
+/// Outcome of asking the scheduler whether a scan may run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum ScheduleDecision {
+    Run,
+    Defer {
+        /// When the scan may next run, if the calendar has an opening
+        until: Option<DateTime<Utc>>,
+        reason: String,
+    },
+}
+
+/// Scheduler configuration - a default policy plus per-tenant overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    #[serde(default)]
+    pub default: SchedulingPolicy,
+    /// Keyed by tenant (`platform:owner`, see [`tenant_key`])
+    #[serde(default)]
+    pub tenants: HashMap<String, SchedulingPolicy>,
+}
+
+impl SchedulerConfig {
+    /// Policy of a repository's tenant, or the default
+    pub fn policy_for(&self, repo: &RepoRef) -> &SchedulingPolicy {
+        self.tenants.get(&tenant_key(repo)).unwrap_or(&self.default)
+    }
+}
+
+/// Scan scheduler
+#[derive(Debug, Clone, Default)]
+pub struct Scheduler {
+    config: SchedulerConfig,
+}
+
+impl Scheduler {
+    pub fn new(config: SchedulerConfig) -> Self {
+        Self { config }
+    }
+
+    /// Set or replace the policy for a tenant
+    pub fn set_tenant_policy(&mut self, tenant: impl Into<String>, policy: SchedulingPolicy) {
+        self.config.tenants.insert(tenant.into(), policy);
+    }
+
+    /// Policy that applies to a repository
+    pub fn policy_for(&self, repo: &RepoRef) -> &SchedulingPolicy {
+        self.config.policy_for(repo)
+    }
+
+    /// Decide whether a scan for `repo` may run at `now`
+    pub fn decide(&self, repo: &RepoRef, trigger: ScanTrigger, now: DateTime<Utc>) -> ScheduleDecision {
+        if trigger.is_urgent() {
+            return ScheduleDecision::Run;
+        }
+
+        let policy = self.policy_for(repo);
+        let Some((exclusion, _)) = policy.blocking_exclusion(now) else {
+            return ScheduleDecision::Run;
+        };
+
+        let reason = exclusion.describe();
+        tracing::debug!("Deferring {:?} scan of {}: {}", trigger, repo, reason);
+
+        ScheduleDecision::Defer {
+            until: policy.next_open(now),
+            reason,
+        }
+    }
+}
+
+/// Tenant identifier for a repository (`platform:owner`)
+pub fn tenant_key(repo: &RepoRef) -> String {
+    format!("{}:{}", repo.platform, repo.owner)
+}
@@ -0,0 +1,84 @@
+//! Scan quotas
+//!
+//! Caps how many scans a repository, and a tenant as a whole, may run per
+//! hour - on top of the platforms' own API rate limits. Scans over quota
+//! aren't dropped: they are parked, one per branch or pull request, and a
+//! newer scan of the same branch replaces the parked one. Since a scan always
+//! reads the branch as it is when it runs, the parked scan covers every
+//! commit it replaced. A bot force-pushing every minute therefore costs one
+//! scan per opening in the quota rather than a worker per push.
+
+use crate::db::cache::{CacheBackend, RateLimitDecision};
+use crate::{RepoRef, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Window quotas are counted over
+pub const QUOTA_WINDOW_SECS: u64 = 60 * 60;
+
+/// Parked scans replaced by a newer one, since the process started
+static COALESCED: AtomicU64 = AtomicU64::new(0);
+
+/// Count a parked scan replaced by a newer scan of the same branch
+pub fn record_coalesced() {
+    COALESCED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Parked scans replaced by a newer one, since the process started
+pub fn coalesced_total() -> u64 {
+    COALESCED.load(Ordering::Relaxed)
+}
+
+/// Scans allowed per hour; unset means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanQuota {
+    /// Scans of any one repository
+    #[serde(default)]
+    pub per_repo_per_hour: Option<u64>,
+    /// Scans of all the tenant's repositories together
+    #[serde(default)]
+    pub per_tenant_per_hour: Option<u64>,
+}
+
+impl ScanQuota {
+    pub fn is_unlimited(&self) -> bool {
+        self.per_repo_per_hour.is_none() && self.per_tenant_per_hour.is_none()
+    }
+
+    /// Budgets that apply to `repo`, as (rate limit key, scans per hour)
+    fn budgets(&self, repo: &RepoRef) -> Vec<(String, u64)> {
+        let repo_key = format!("scan:repo:{}:{}/{}", repo.platform, repo.owner, repo.repo);
+        let tenant_key = format!("scan:tenant:{}", super::tenant_key(repo));
+        [(repo_key, self.per_repo_per_hour), (tenant_key, self.per_tenant_per_hour)]
+            .into_iter()
+            .filter_map(|(key, limit)| limit.map(|limit| (key, limit)))
+            .collect()
+    }
+
+    /// Take a scan of `repo` from the quota. Nothing is taken unless every
+    /// budget has room, so a scan refused by the tenant's quota doesn't use
+    /// up the repository's. Returns the denying decision when over quota.
+    pub async fn take(&self, cache: &dyn CacheBackend, repo: &RepoRef) -> Result<Option<RateLimitDecision>> {
+        if let Some(denied) = self.check(cache, repo).await? {
+            return Ok(Some(denied));
+        }
+        for (key, limit) in self.budgets(repo) {
+            let decision = cache.rate_limit(&key, limit, QUOTA_WINDOW_SECS).await?;
+            if !decision.allowed {
+                return Ok(Some(decision));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether a scan of `repo` would be over quota, without taking one
+    pub async fn check(&self, cache: &dyn CacheBackend, repo: &RepoRef) -> Result<Option<RateLimitDecision>> {
+        for (key, limit) in self.budgets(repo) {
+            let decision = cache.rate_limit_check(&key, limit, QUOTA_WINDOW_SECS).await?;
+            if !decision.allowed {
+                return Ok(Some(decision));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -1,15 +1,271 @@
 //! HTTP server for receiving webhooks and serving the API
 
+pub mod mode;
 pub mod routes;
+pub mod slo;
 
-use crate::Result;
-use axum::{routing::get, Router};
+use self::mode::{ModeSwitch, OperatingMode};
+use self::slo::ScanTrigger;
+use crate::adapters::{AdapterFactory, PlatformAdapter};
+use crate::advisories;
+use crate::compliance::rulepack::StandardEngines;
+use crate::compliance::selfcheck::SelfCertification;
+use crate::compliance::authorship::Authorship;
+use crate::compliance::catalog::EngineCatalog;
+use crate::compliance::ci::BranchBuilds;
+use crate::compliance::flakiness::CiHistory;
+use crate::compliance::protection::DefaultBranchProtection;
+use crate::compliance::signing::SignedHistory;
+use crate::compliance::releases::ReleaseHistory;
+use crate::compliance::vetting::AuditSets;
+use crate::compliance::{gate, identity, RepoContents};
+use crate::config::{ConfigAuditEntry, ConfigStore, EngineConfig, ReloadOutcome, ReloadSource};
+use crate::db::annotations::Annotation;
+use crate::db::audit::{self, AuditAction, ENGINE_ACTOR};
+use crate::db::bus::{BusEnvelope, BusMessage, EventBus};
+use crate::db::ci_runs::CiRun;
+use crate::db::documents::{VerificationOutcome, WebhookEvent};
+use crate::db::outbox;
+use crate::db::graphs::UpstreamKind;
+use crate::db::quarantine::{QuarantineReplay, ENGINE_VERSION, MAX_REPLAY_BATCH};
+use crate::db::queue::{JobPayload, JobPriority, NewJob, QueuedJob, TraceContext};
+use crate::deps;
+use crate::discovery::{DiscoveryJobHandler, DISCOVERY_QUEUE};
+use crate::events::{Commit, PullRequestAction, PullRequestEvent, PushEvent, WorkflowEvent};
+use crate::publish::Publisher;
+use crate::scheduler::quota;
+use crate::worker::{JobHandler, WorkerPool};
+use crate::{ComplianceStatus, RepoEvent, RepoRef, Result, RsrError};
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::broadcast;
 use tower_http::trace::TraceLayer;
 
+/// Shared state for route handlers
+#[derive(Clone)]
+pub struct AppState {
+    /// Database pool, if the backing stores were reachable at startup
+    pub db: Option<Arc<crate::db::DatabasePool>>,
+    /// Reloadable engine configuration, if a config file was given
+    pub config: Option<Arc<ConfigStore>>,
+    /// Workers consuming the webhook event queue (requires the databases)
+    pub workers: Option<Arc<WorkerPool>>,
+    /// Normal, maintenance or read-only
+    pub mode: Arc<ModeSwitch>,
+    /// Checks, rulepacks and plugins scans run with, as loaded at startup
+    pub catalog: Arc<EngineCatalog>,
+    /// Bus messages from every replica, relayed to event stream subscribers
+    /// (requires the databases)
+    pub events: Option<broadcast::Sender<BusEnvelope>>,
+}
+
+/// Queue webhook events are placed on for background processing
+pub const EVENTS_QUEUE: &str = "events";
+
+/// Pages of advisories read per sync, 100 advisories each
+const MAX_ADVISORY_PAGES: usize = 50;
+
+/// How long a scanned status stays in the cache
+const COMPLIANCE_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Bus messages buffered for each event stream subscriber; slower ones are
+/// told how many they missed
+const EVENT_STREAM_CAPACITY: usize = 256;
+
+/// Longest wait between attempts to resubscribe to the bus
+const MAX_BUS_BACKOFF_SECS: u64 = 30;
+
+impl AppState {
+    /// Adapter config for a platform from the running configuration
+    pub fn adapter_config(&self, platform: &str) -> crate::adapters::AdapterConfig {
+        let config = self
+            .config
+            .as_ref()
+            .map(|store| store.current().adapter_config(platform))
+            .unwrap_or_default();
+
+        match self.db {
+            Some(ref db) => config.with_etag_cache(db.clone()),
+            None => config,
+        }
+    }
+
+    /// Program and tier names from the running configuration
+    pub fn branding(&self) -> crate::branding::Branding {
+        self.config
+            .as_ref()
+            .map(|store| store.current().branding.clone())
+            .unwrap_or_default()
+    }
+
+    /// Re-process an archived webhook, e.g. after a fix for the bug that made
+    /// it fail. The stored payload is parsed with the current adapter and
+    /// queued again. Deliveries that failed verification can't be replayed.
+    ///
+    /// Returns `None` if there is no archived webhook with that ID.
+    pub async fn replay_event(&self, event_id: &str) -> Result<Option<RepoEvent>> {
+        let Some(ref db) = self.db else {
+            return Err(RsrError::Config("Replaying webhooks requires the databases".to_string()));
+        };
+
+        let Some(archived) = db.docs.get_webhook_event(event_id).await? else {
+            return Ok(None);
+        };
+        let archived = archived.event;
+
+        if archived.verification != VerificationOutcome::Verified {
+            return Err(RsrError::Platform(format!(
+                "Webhook {} failed verification and can't be replayed",
+                event_id
+            )));
+        }
+
+        tracing::info!("Replaying {} webhook {}", archived.platform, event_id);
+
+        let adapter = AdapterFactory::create(&archived.platform, self.adapter_config(&archived.platform))?;
+        let event = match adapter.parse_webhook(archived.payload.as_bytes(), &archived.headers) {
+            Ok(event) => event,
+            Err(e) => {
+                db.docs.quarantine_event(event_id, &e.to_string()).await?;
+                return Err(e);
+            }
+        };
+        if archived.quarantined_by.is_some() {
+            tracing::info!("Releasing {} webhook {} from quarantine", archived.platform, event_id);
+            db.docs.release_quarantine(event_id).await?;
+        }
+
+        if let RepoEvent::Repository(ref repo_event) = event {
+            routes::apply_repository_event(self, &archived.platform, repo_event).await?;
+        }
+
+        let job = EventJob {
+            platform: archived.platform,
+            event: event.clone(),
+            archive_id: Some(event_id.to_string()),
+            received_at: None,
+        };
+        job.enqueue(db, None).await?;
+
+        Ok(Some(event))
+    }
+
+    /// Replay quarantined webhooks, e.g. after an upgrade that fixes the
+    /// parser that rejected them. Unless `all` is set, payloads quarantined
+    /// by this engine version are skipped, since they would fail again.
+    pub async fn replay_quarantine(&self, all: bool) -> Result<QuarantineReplay> {
+        let Some(ref db) = self.db else {
+            return Err(RsrError::Config("Replaying webhooks requires the databases".to_string()));
+        };
+
+        let mut outcome = QuarantineReplay::default();
+        for archived in db.docs.get_quarantined_events(MAX_REPLAY_BATCH).await? {
+            outcome.scanned += 1;
+            if !all && archived.event.quarantined_by.as_deref() == Some(ENGINE_VERSION) {
+                outcome.skipped += 1;
+                continue;
+            }
+
+            match self.replay_event(&archived.id).await {
+                Ok(Some(_)) => outcome.replayed += 1,
+                Ok(None) => outcome.skipped += 1,
+                Err(e) => {
+                    tracing::warn!("Quarantined webhook {} still fails: {}", archived.id, e);
+                    outcome.still_failing += 1;
+                }
+            }
+        }
+
+        tracing::info!(
+            "Replayed {} quarantined webhooks, {} still failing, {} skipped",
+            outcome.replayed,
+            outcome.still_failing,
+            outcome.skipped
+        );
+        Ok(outcome)
+    }
+}
+
 /// Run the RSR webhook server
-pub async fn run(host: &str, port: u16, platforms: &[&str]) -> Result<()> {
-    let app = create_router(platforms);
+pub async fn run(host: &str, port: u16, platforms: &[&str], config_path: Option<&Path>) -> Result<()> {
+    // A broken config file at startup is fatal; later reloads are not
+    let config = config_path.map(ConfigStore::load).transpose()?.map(Arc::new);
+
+    // An unknown RSR_MODE is fatal rather than silently running writable
+    let mode = Arc::new(ModeSwitch::new(OperatingMode::from_env()?));
+
+    let db = match crate::db::init().await {
+        Ok(pool) => Some(Arc::new(pool)),
+        Err(e) => {
+            tracing::warn!("Databases unavailable, serving without persistence: {}", e);
+            None
+        }
+    };
+
+    if let Some(ref store) = config {
+        spawn_reload_on_sighup(store.clone(), db.clone());
+    }
+
+    let events = db.as_ref().map(|db| spawn_bus_relay(EventBus::new(&db.cache)));
+
+    if let Some(ref db) = db {
+        spawn_health_probes(db.clone());
+        spawn_outbox_relay(db.clone(), mode.clone());
+        spawn_cache_gc(db.clone(), mode.clone());
+        spawn_report_pruning(db.clone(), config.clone(), mode.clone());
+        spawn_quota_release(db.clone(), config.clone(), mode.clone());
+        spawn_advisory_sync(db.clone(), config.clone(), mode.clone());
+    }
+
+    // Rulepacks are verified before anything runs; one that fails is fatal
+    let engines = match config {
+        Some(ref store) => StandardEngines::from_config(&store.current().rulepacks)?,
+        None => StandardEngines::default(),
+    };
+
+    // Dogfooding: Gold and above are only issued if the engine passes Bronze itself
+    let self_certification = SelfCertification::run(engines.current()).await.unwrap_or_else(|e| {
+        tracing::error!("Engine self-scan failed: {}", e);
+        SelfCertification::default()
+    });
+    self_certification.log();
+    let self_certification = Arc::new(self_certification);
+    let catalog = Arc::new(EngineCatalog::new(&engines));
+
+    let workers = db.as_ref().map(|db| {
+        let handler = EventJobHandler {
+            db: db.clone(),
+            config: config.clone(),
+            engines,
+            bus: EventBus::new(&db.cache),
+            self_certification,
+        };
+        let mut pool = WorkerPool::new(EVENTS_QUEUE, db.clone(), Arc::new(handler));
+        if let Some(ref store) = config {
+            pool = pool.with_config(store.clone());
+        }
+        let pool = Arc::new(pool);
+        mode.attach(pool.clone());
+        pool.start();
+        pool
+    });
+
+    // Discovery gets its own pool so slow registry lookups never hold up scans
+    if let Some(ref db) = db {
+        let handler = DiscoveryJobHandler::new(db.clone());
+        let pool = Arc::new(WorkerPool::new(DISCOVERY_QUEUE, db.clone(), Arc::new(handler)));
+        mode.attach(pool.clone());
+        pool.start();
+    }
+
+    let app = create_router(platforms, AppState { db, config, workers, mode, catalog, events });
 
     let addr: SocketAddr = format!("{}:{}", host, port).parse().map_err(|e| {
         crate::RsrError::Config(format!("Invalid address: {}", e))
@@ -25,13 +281,48 @@ pub async fn run(host: &str, port: u16, platforms: &[&str]) -> Result<()> {
     Ok(())
 }
 
-fn create_router(platforms: &[&str]) -> Router {
+fn create_router(platforms: &[&str], state: AppState) -> Router {
     let mut router = Router::new()
         .route("/health", get(routes::health))
+        .route("/livez", get(routes::health))
+        .route("/readyz", get(routes::readiness))
         .route("/metrics", get(routes::metrics))
         .route("/api/v1/repo/{owner}/{repo}/status", get(routes::get_repo_status))
         .route("/api/v1/repo/{owner}/{repo}/badge", get(routes::get_badge))
-        .route("/api/v1/repo/{owner}/{repo}/report", get(routes::get_report));
+        .route("/api/v1/repo/{owner}/{repo}/report", get(routes::get_report))
+        .route("/api/v1/repo/{owner}/{repo}/history", get(routes::get_history))
+        .route("/api/v1/repo/{owner}/{repo}/history/verify", get(routes::verify_history))
+        .route("/api/v1/repo/{owner}/{repo}/trend", get(routes::get_trend))
+        .route("/api/v1/repo/{owner}/{repo}/impact/{vulnerability}", get(routes::explain_impact))
+        .route("/api/v1/repo/{owner}/{repo}/forks", get(routes::get_fork_network))
+        .route("/api/v1/repo/{owner}/{repo}/policy", get(routes::get_effective_policy))
+        .route("/api/v1/compare", get(routes::compare_repos))
+        .route("/api/v1/engine/catalog", get(routes::engine_catalog))
+        .route("/api/v1/events", get(routes::event_stream))
+        .route("/api/v1/packages/{registry}/{*package}", get(routes::get_package_status))
+        .route("/api/v1/orgs/{*unit}", get(routes::get_organization_rollup))
+        .route("/api/v1/owners/{*owner}", get(routes::get_owner_summary))
+        .route("/api/v1/admin/config/reload", post(routes::reload_config))
+        .route("/api/v1/admin/config/audit", get(routes::config_audit))
+        .route("/api/v1/admin/config/export", get(routes::export_config))
+        .route("/api/v1/admin/config/import", post(routes::import_config))
+        .route("/api/v1/admin/audit", get(routes::audit_log))
+        .route("/api/v1/admin/mode", get(routes::get_mode).put(routes::set_mode))
+        .route("/api/v1/admin/logging", get(routes::get_logging).put(routes::set_logging))
+        .route("/api/v1/admin/workers", get(routes::worker_status).post(routes::scale_workers))
+        .route("/api/v1/admin/cache/gc", post(routes::run_cache_gc))
+        .route("/api/v1/admin/reports/prune", post(routes::prune_reports))
+        .route("/api/v1/admin/discovery", post(routes::discover_package))
+        .route("/api/v1/admin/annotations", post(routes::annotate_report))
+        .route("/api/v1/admin/annotations/{id}", delete(routes::remove_annotation))
+        .route("/api/v1/admin/webhooks/failed", get(routes::failed_webhooks))
+        .route("/api/v1/admin/webhooks/quarantine", get(routes::quarantined_webhooks))
+        .route("/api/v1/admin/webhooks/quarantine/replay", post(routes::replay_quarantine))
+        .route("/api/v1/admin/webhooks/{id}/replay", post(routes::replay_webhook))
+        .route("/api/v1/admin/queue/dead-letters", get(routes::dead_letters))
+        .route("/api/v1/admin/queue/dead-letters/requeue", post(routes::requeue_all_dead_letters))
+        .route("/api/v1/admin/queue/dead-letters/{id}/requeue", post(routes::requeue_dead_letter))
+        .route("/api/v1/queue/pressure", get(routes::queue_pressure));
 
     // Add webhook routes for enabled platforms
     for platform in platforms {
@@ -39,5 +330,833 @@ fn create_router(platforms: &[&str]) -> Router {
         router = router.route(&path, axum::routing::post(routes::handle_webhook));
     }
 
-    router.layer(TraceLayer::new_for_http())
+    router.layer(TraceLayer::new_for_http()).with_state(state)
+}
+
+/// Probe the databases every `RSR_DB_HEALTH_INTERVAL_SECS` (see
+/// [`crate::db::health`]), logging backends that stop or start answering
+fn spawn_health_probes(db: Arc<crate::db::DatabasePool>) {
+    let Some(interval) = crate::db::health::probe_interval() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let before = db.health();
+            let after = db.health_check().await;
+            for ((backend, was), (_, is)) in before.backends().into_iter().zip(after.backends()) {
+                if is.status == was.status && was.checked_at.is_some() {
+                    continue;
+                }
+                if is.is_up() {
+                    tracing::info!("Database backend {} is answering ({}ms)", backend, is.latency_ms.unwrap_or(0));
+                } else {
+                    tracing::warn!(
+                        "Database backend {} is down: {}",
+                        backend,
+                        is.last_error.as_deref().unwrap_or("no answer")
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Queue events left in the outbox every `RSR_OUTBOX_RELAY_INTERVAL_SECS`
+/// (see [`crate::db::outbox`])
+fn spawn_outbox_relay(db: Arc<crate::db::DatabasePool>, mode: Arc<ModeSwitch>) {
+    let Some(interval) = outbox::relay_interval() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            if !mode.current().accepts_writes() {
+                continue;
+            }
+            match outbox::relay(&db).await {
+                Ok(report) if report.relayed + report.duplicates > 0 => tracing::info!(
+                    "Relayed {} outbox jobs ({} already queued)",
+                    report.relayed + report.duplicates,
+                    report.duplicates
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Outbox relay failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Run cache GC every `RSR_CACHE_GC_INTERVAL_SECS` (default hourly, 0 disables)
+fn spawn_cache_gc(db: Arc<crate::db::DatabasePool>, mode: Arc<ModeSwitch>) {
+    let interval = std::env::var("RSR_CACHE_GC_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3600u64);
+    if interval == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval));
+        // The first tick completes immediately; don't GC during startup
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if !mode.current().accepts_writes() {
+                continue;
+            }
+            if let Err(e) = db.cache.collect_garbage(false).await {
+                tracing::warn!("Cache GC failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Tombstone reports outside the configured retention every
+/// `RSR_REPORT_PRUNE_INTERVAL_SECS` (default daily, 0 disables)
+fn spawn_report_pruning(db: Arc<crate::db::DatabasePool>, config: Option<Arc<ConfigStore>>, mode: Arc<ModeSwitch>) {
+    let interval = std::env::var("RSR_REPORT_PRUNE_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(24 * 3600u64);
+    if interval == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if !mode.current().accepts_writes() {
+                continue;
+            }
+            let policy = config.as_ref().map(|store| store.current().retention).unwrap_or_default();
+            match db.docs.prune_reports(&policy, false).await {
+                Ok(report) if report.tombstoned > 0 => {
+                    let details = serde_json::json!(report);
+                    db.audit(ENGINE_ACTOR, AuditAction::ReportsPruned, "reports", details).await;
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Report pruning failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Queue parked scans whose quota has room again every
+/// `RSR_SCAN_QUOTA_RELEASE_INTERVAL_SECS` (default every minute, 0 disables)
+fn spawn_quota_release(db: Arc<crate::db::DatabasePool>, config: Option<Arc<ConfigStore>>, mode: Arc<ModeSwitch>) {
+    let interval = std::env::var("RSR_SCAN_QUOTA_RELEASE_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60u64);
+    if interval == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if !mode.current().accepts_writes() {
+                continue;
+            }
+            if let Err(e) = release_parked_scans(&db, config.as_deref()).await {
+                tracing::warn!("Releasing parked scans failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Ingest GitHub security advisories every `RSR_GHSA_SYNC_INTERVAL_SECS`
+/// (default every 6 hours, 0 disables), using the `github` adapter's token.
+/// Each run reads at most `MAX_ADVISORY_PAGES` pages; the next one resumes
+/// where it stopped.
+fn spawn_advisory_sync(db: Arc<crate::db::DatabasePool>, config: Option<Arc<ConfigStore>>, mode: Arc<ModeSwitch>) {
+    let interval = std::env::var("RSR_GHSA_SYNC_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(6 * 3600u64);
+    if interval == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if !mode.current().accepts_writes() {
+                continue;
+            }
+            let adapter_config = config
+                .as_ref()
+                .map(|store| store.current().adapter_config("github"))
+                .unwrap_or_default();
+            if adapter_config.api_token.is_none() {
+                tracing::debug!("Skipping advisory sync: no GitHub token configured");
+                continue;
+            }
+            let adapter = crate::adapters::github::GitHubAdapter::new(adapter_config);
+            if let Err(e) = advisories::ingest_ghsa(&adapter, db.graphs.as_ref(), MAX_ADVISORY_PAGES).await {
+                tracing::warn!("Advisory sync failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Queue each parked scan whose repository and tenant have quota left. The
+/// scan takes its share of the quota when it runs, and is parked again if
+/// others got there first.
+async fn release_parked_scans(db: &crate::db::DatabasePool, config: Option<&ConfigStore>) -> Result<usize> {
+    let config = config.map(|store| store.current());
+    let mut released = 0;
+    for (slot, parked) in db.cache.parked_jobs(EVENTS_QUEUE).await? {
+        let job: EventJob = match serde_json::from_str(&parked.payload) {
+            Ok(job) => job,
+            Err(e) => {
+                tracing::warn!("Skipping unreadable parked scan {}: {}", slot, e);
+                continue;
+            }
+        };
+        let repo = job.repo();
+        let quota = config
+            .as_ref()
+            .map(|config| config.scheduler.policy_for(&repo).quota)
+            .unwrap_or_default();
+        if quota.check(db.cache.as_ref(), &repo).await?.is_some() {
+            continue;
+        }
+        if db.cache.unpark_job(EVENTS_QUEUE, &slot, &parked).await? {
+            tracing::debug!("Released parked scan of {} ({})", repo, slot);
+            released += 1;
+        }
+    }
+    Ok(released)
+}
+
+/// Reload the configuration whenever the process receives SIGHUP
+#[cfg(unix)]
+fn spawn_reload_on_sighup(store: Arc<ConfigStore>, db: Option<Arc<crate::db::DatabasePool>>) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            tracing::warn!("Failed to install SIGHUP handler, reload via API only: {}", e);
+            return;
+        }
+    };
+
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            tracing::info!("SIGHUP received, reloading {}", store.path().display());
+            // Rejections are logged by the store and audited like any reload
+            let entry = store.reload(ReloadSource::Signal);
+            if let Some(ref db) = db {
+                db.audit(ENGINE_ACTOR, AuditAction::ConfigReloaded, "config", serde_json::json!(entry)).await;
+                broadcast_config_change(db, &entry).await;
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn spawn_reload_on_sighup(_store: Arc<ConfigStore>, _db: Option<Arc<crate::db::DatabasePool>>) {}
+
+/// Relay the bus into a channel event streams subscribe to, so a subscriber
+/// connected to any replica sees scans and reloads from all of them. This
+/// replica's own messages come back through the bus like everyone else's.
+/// The subscription is re-established, with backoff, whenever it drops;
+/// messages published meanwhile are missed.
+fn spawn_bus_relay(bus: EventBus) -> broadcast::Sender<BusEnvelope> {
+    let (sender, _) = broadcast::channel(EVENT_STREAM_CAPACITY);
+    let relay = sender.clone();
+
+    tokio::spawn(async move {
+        let mut backoff = 1;
+        loop {
+            match bus.subscribe().await {
+                Ok(envelopes) => {
+                    backoff = 1;
+                    futures::pin_mut!(envelopes);
+                    while let Some(envelope) = envelopes.next().await {
+                        // Fails only while nobody is subscribed
+                        let _ = relay.send(envelope);
+                    }
+                    tracing::warn!("Bus subscription ended, resubscribing");
+                }
+                Err(e) => tracing::warn!("Failed to subscribe to the bus: {}", e),
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(backoff)).await;
+            backoff = (backoff * 2).min(MAX_BUS_BACKOFF_SECS);
+        }
+    });
+
+    sender
+}
+
+/// Tell every replica about a reload that applied a new configuration
+pub(crate) async fn broadcast_config_change(db: &crate::db::DatabasePool, entry: &ConfigAuditEntry) {
+    if entry.outcome != ReloadOutcome::Applied {
+        return;
+    }
+    let message = BusMessage::ConfigChanged {
+        digest: entry.digest.clone(),
+        changed: entry.changed.clone(),
+    };
+    if let Err(e) = EventBus::new(&db.cache).publish(message).await {
+        tracing::warn!("Failed to broadcast configuration change: {}", e);
+    }
+}
+
+/// Webhook event as placed on the events queue
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventJob {
+    pub platform: String,
+    pub event: RepoEvent,
+    /// Archived webhook the event was parsed from, updated once processed
+    #[serde(default)]
+    pub archive_id: Option<String>,
+    /// When the webhook arrived, for the latency SLO. Unset for replays.
+    #[serde(default)]
+    pub received_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl JobPayload for EventJob {
+    const KIND: &'static str = "event";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+impl EventJob {
+    /// Queue the event, with the trace context of the webhook it came in
+    /// on. Events of one repository take turns with other repositories' so
+    /// a noisy repository can't starve the rest.
+    pub async fn enqueue(&self, db: &crate::db::DatabasePool, trace: Option<TraceContext>) -> Result<String> {
+        db.cache.enqueue_job(EVENTS_QUEUE, self.job(trace)?).await
+    }
+
+    /// Archive `webhook` and write the event to the outbox in one
+    /// transaction, then relay it onto the events queue. Returns the
+    /// archived webhook's ID. An event the relay can't queue yet stays in the
+    /// outbox for the background relay, so only a failed write is an error.
+    pub async fn archive_and_enqueue(
+        &self,
+        db: &crate::db::DatabasePool,
+        webhook: &WebhookEvent,
+        trace: Option<TraceContext>,
+    ) -> Result<String> {
+        // The outbox keeps the builder past this call, so it owns its copies
+        let (platform, event, received_at) = (self.platform.clone(), self.event.clone(), self.received_at);
+        let job = move |archive_id: &str| {
+            EventJob {
+                platform: platform.clone(),
+                event: event.clone(),
+                archive_id: Some(archive_id.to_string()),
+                received_at,
+            }
+            .job(trace.clone())
+        };
+        let (archive_id, entry) = db.docs.store_webhook_event_with_job(webhook, EVENTS_QUEUE, &job).await?;
+
+        if let Err(e) = outbox::relay_entry(db, &entry).await {
+            tracing::warn!("Leaving event job {} in the outbox: {}", entry.job.id, e);
+        }
+        Ok(archive_id)
+    }
+
+    fn job(&self, trace: Option<TraceContext>) -> Result<NewJob> {
+        Ok(NewJob::new(self, JobPriority::High, self.fairness_key())?.with_trace(trace))
+    }
+
+    /// Hold the event back in `slot` until its quota has room. Parked events
+    /// aren't measured against the latency SLO: the wait is deliberate.
+    async fn park(&self, db: &crate::db::DatabasePool, slot: &str) -> Result<Option<QueuedJob>> {
+        let parked = EventJob {
+            platform: self.platform.clone(),
+            event: self.event.clone(),
+            archive_id: self.archive_id.clone(),
+            received_at: None,
+        };
+        let job = NewJob::new(&parked, JobPriority::High, self.fairness_key())?;
+        db.cache.park_job(EVENTS_QUEUE, slot, job).await
+    }
+
+    fn repo(&self) -> RepoRef {
+        RepoRef::new(&self.platform, self.event.repo_owner(), self.event.repo_name())
+    }
+
+    fn fairness_key(&self) -> String {
+        format!("{}:{}/{}", self.platform, self.event.repo_owner(), self.event.repo_name())
+    }
+}
+
+/// Processes webhook events taken off the events queue
+struct EventJobHandler {
+    db: Arc<crate::db::DatabasePool>,
+    config: Option<Arc<ConfigStore>>,
+    engines: StandardEngines,
+    bus: EventBus,
+    /// Caps the tiers issued unless the engine's own repository passes
+    self_certification: Arc<SelfCertification>,
+}
+
+impl EventJobHandler {
+    /// The running configuration, or the defaults when the server was
+    /// started without a config file
+    fn current_config(&self) -> Arc<EngineConfig> {
+        self.config.as_ref().map(|store| store.current()).unwrap_or_default()
+    }
+
+    /// Scan `repo`, capping its tier at what this installation may issue.
+    /// `pushed` are the commits a push brought, if the scan is for one.
+    async fn scan(
+        &self,
+        config: &EngineConfig,
+        adapter: &dyn PlatformAdapter,
+        repo: RepoRef,
+        pushed: &[Commit],
+    ) -> Result<ComplianceStatus> {
+        let mut status = self.check(config, adapter, repo, pushed).await?;
+        self.self_certification.cap(&mut status);
+        Ok(status)
+    }
+
+    /// Check `repo` against its standard, counting evidence from any linked
+    /// identities on other platforms. A linked identity that can't be
+    /// fetched is skipped rather than failing the scan.
+    async fn check(
+        &self,
+        config: &EngineConfig,
+        adapter: &dyn PlatformAdapter,
+        repo: RepoRef,
+        pushed: &[Commit],
+    ) -> Result<ComplianceStatus> {
+        let engine = self.engines.engine_for(&repo);
+        let mut contents = RepoContents::fetch(adapter, &repo, engine).await?;
+        let policy = config.policy_for(&repo);
+        // Each piece of evidence is its own API call (or store read), and none depends on another
+        let branch = contents.metadata.default_branch.clone();
+        let branch_evidence = async {
+            if branch.is_empty() {
+                return (None, None);
+            }
+            tokio::join!(
+                BranchBuilds::collect(self.db.docs.as_ref(), &repo, &branch),
+                DefaultBranchProtection::collect(adapter, &repo, &branch),
+            )
+        };
+        let (authorship, releases, ci, signing, vetting, (builds, protection)) = tokio::join!(
+            Authorship::collect(adapter, &repo, &policy.authors, pushed),
+            ReleaseHistory::collect(adapter, &repo, &policy.releases),
+            CiHistory::collect(adapter, &repo, &policy.ci),
+            SignedHistory::collect(adapter, &repo, &policy.signing),
+            AuditSets::collect(adapter, &repo, &policy.vetting),
+            branch_evidence,
+        );
+        contents.authorship = Some(authorship);
+        contents.releases = releases;
+        contents.ci = ci;
+        contents.signing = signing;
+        contents.builds = builds;
+        contents.protection = protection;
+        contents.vetting = Some(vetting);
+        let Some(link) = identity::link_for(&config.links, &repo) else {
+            return engine.check_remote(repo, &contents).await;
+        };
+
+        let mut linked = Vec::new();
+        for other in link.others(&repo)? {
+            let other_config = config.adapter_config(&other.platform).with_etag_cache(self.db.clone());
+            let fetched = match AdapterFactory::create(&other.platform, other_config) {
+                Ok(other_adapter) if other_adapter.capabilities().file_listing => {
+                    RepoContents::fetch(other_adapter.as_ref(), &other, engine).await
+                }
+                Ok(_) => continue,
+                Err(e) => Err(e),
+            };
+            match fetched {
+                Ok(other_contents) => linked.push((other, other_contents)),
+                Err(e) => tracing::warn!("Skipping linked {} for {}: {}", other, repo, e),
+            }
+        }
+
+        engine.check_linked(repo, &contents, link, &linked).await
+    }
+
+    /// Scan a pull request's head and post the result as its status, then
+    /// request changes if it drops compliance below the tenant's target tier
+    /// (if the tenant has the review gate enabled)
+    async fn gate_pull_request(&self, job: &EventJob, pr: &PullRequestEvent) -> Result<()> {
+        let (platform, received_at) = (job.platform.as_str(), job.received_at);
+        let config = self.current_config();
+
+        let repo = RepoRef::new(platform, &pr.repo_owner, &pr.repo_name);
+        let policy = config.policy_for(&repo);
+        let adapter = AdapterFactory::create(platform, config.adapter_config(platform).with_etag_cache(self.db.clone()))?;
+        let capabilities = adapter.capabilities();
+        if !capabilities.file_listing {
+            tracing::warn!("Skipping pull request scan for {}: {} cannot list files", repo, platform);
+            return Ok(());
+        }
+        if self.park_over_quota(&config, job, &format!("{}:pull:{}", job.fairness_key(), pr.number)).await {
+            return Ok(());
+        }
+
+        let head = repo.clone().with_branch(&pr.source_branch);
+        let head = self.scan(&config, adapter.as_ref(), head, &[]).await?;
+        self.report_status(adapter.as_ref(), &repo, Some(pr.number), &pr.head_sha, &head).await;
+        if !policy.review_gate {
+            if let Some(received_at) = received_at {
+                slo::record(ScanTrigger::PullRequest, received_at);
+            }
+            return Ok(());
+        }
+        let base = repo.clone().with_branch(&pr.target_branch);
+        let base = self.scan(&config, adapter.as_ref(), base, &[]).await?;
+
+        let Some(regression) = gate::evaluate(&base, &head, policy.target_tier) else {
+            if let Some(received_at) = received_at {
+                slo::record(ScanTrigger::PullRequest, received_at);
+            }
+            return Ok(());
+        };
+        let regression = regression.with_annotations(self.latest_annotations(&repo).await);
+
+        let posted = if capabilities.reviews {
+            tracing::info!("{} #{} drops compliance to {}, requesting changes", repo, pr.number, regression.head_tier.code());
+            adapter.request_changes(&repo, pr.number, &regression.review_body(&config.branding)).await?;
+            "review"
+        } else if capabilities.comments {
+            tracing::info!("{} #{} drops compliance to {}, commenting", repo, pr.number, regression.head_tier.code());
+            adapter.post_comment(&repo, pr.number, &regression.review_body(&config.branding)).await?;
+            "comment"
+        } else {
+            tracing::warn!(
+                "{} #{} drops compliance to {}, but {} can neither review nor comment",
+                repo,
+                pr.number,
+                regression.head_tier.code(),
+                platform
+            );
+            return Ok(());
+        };
+        if let Some(received_at) = received_at {
+            slo::record(ScanTrigger::PullRequest, received_at);
+        }
+
+        let details = serde_json::json!({
+            "via": posted,
+            "pull_request": pr.number,
+            "target": regression.target,
+            "base_tier": regression.base_tier,
+            "head_tier": regression.head_tier,
+            "newly_failing": regression.newly_failing.iter().map(|check| &check.id).collect::<Vec<_>>(),
+        });
+        self.db.audit(ENGINE_ACTOR, AuditAction::StatusPosted, &audit::repo_target(&repo), details).await;
+        Ok(())
+    }
+
+    /// Take a scan from the repository's quota, or park the job in `slot`
+    /// if it is over quota, replacing the scan parked there before. Returns
+    /// true if the job was parked. The quota fails open: if the cache can't
+    /// count scans, the scan runs.
+    async fn park_over_quota(&self, config: &EngineConfig, job: &EventJob, slot: &str) -> bool {
+        let repo = job.repo();
+        let quota = config.scheduler.policy_for(&repo).quota;
+        if quota.is_unlimited() {
+            return false;
+        }
+
+        let denied = match quota.take(self.db.cache.as_ref(), &repo).await {
+            Ok(Some(denied)) => denied,
+            Ok(None) => return false,
+            Err(e) => {
+                tracing::warn!("Scan quota unavailable for {}: {}", repo, e);
+                return false;
+            }
+        };
+
+        match job.park(&self.db, slot).await {
+            Ok(replaced) => {
+                tracing::info!(
+                    "{} is over its scan quota ({} per hour), parked until it has room in {}s",
+                    repo,
+                    denied.limit,
+                    denied.reset_after_secs
+                );
+                if let Some(replaced) = replaced {
+                    quota::record_coalesced();
+                    tracing::info!("Coalesced parked scan {} of {} into a newer one", replaced.id, repo);
+                }
+                true
+            }
+            Err(e) => {
+                tracing::warn!("Failed to park over-quota scan of {}, running it: {}", repo, e);
+                false
+            }
+        }
+    }
+
+    /// Annotations on the repository's last stored report. Best effort: a
+    /// review without them is still worth posting.
+    async fn latest_annotations(&self, repo: &RepoRef) -> Vec<Annotation> {
+        let annotations = async {
+            match self.db.docs.get_latest_compliance(&repo.platform, &repo.owner, &repo.repo).await? {
+                Some(status) => self.db.docs.get_annotations(repo, status.timestamp).await,
+                None => Ok(Vec::new()),
+            }
+        };
+        annotations.await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to read annotations for {}: {}", repo, e);
+            Vec::new()
+        })
+    }
+
+    /// Record a completed workflow run as its workflow's latest build on its
+    /// branch, for `silver.ci_passing`
+    async fn record_ci_run(&self, job: &EventJob, event: &WorkflowEvent) -> Result<()> {
+        let completed_at = job.received_at.unwrap_or_else(chrono::Utc::now);
+        let Some(run) = CiRun::from_event(event, completed_at) else {
+            return Ok(());
+        };
+        self.db.docs.record_ci_run(&job.repo(), &run).await
+    }
+
+    /// Rescan the default branch after a push to it and publish the badges
+    /// and report (if publishing is configured)
+    async fn publish_default_branch(&self, job: &EventJob, push: &PushEvent) -> Result<()> {
+        let (platform, received_at) = (job.platform.as_str(), job.received_at);
+        let config = self.current_config();
+
+        let repo = RepoRef::new(platform, &push.repo_owner, &push.repo_name);
+        let adapter = AdapterFactory::create(platform, config.adapter_config(platform).with_etag_cache(self.db.clone()))?;
+        if !adapter.capabilities().file_listing {
+            tracing::warn!("Skipping scan of {}: {} cannot list files", repo, platform);
+            return Ok(());
+        }
+        let metadata = adapter.get_metadata(&repo).await?;
+        if metadata.default_branch != push.branch {
+            return Ok(());
+        }
+        if self.park_over_quota(&config, job, &format!("{}:push:{}", job.fairness_key(), push.branch)).await {
+            return Ok(());
+        }
+
+        let branch = repo.clone().with_branch(&push.branch);
+        let status = self.scan(&config, adapter.as_ref(), branch.clone(), &push.commits).await?;
+        self.report_status(adapter.as_ref(), &repo, None, &push.after, &status).await;
+        self.broadcast_scan(&status).await;
+        self.register_hierarchy(&config, &repo).await;
+        self.register_dependencies(adapter.as_ref(), &branch).await;
+        self.register_upstream(&config, &repo, metadata.upstream.as_deref()).await;
+        if let Some(received_at) = received_at {
+            slo::record(ScanTrigger::Push, received_at);
+        }
+
+        let Some(ref publish) = config.publish else {
+            return Ok(());
+        };
+        let published = Publisher::from_config(publish)?
+            .with_branding(config.branding.clone())
+            .publish(&status)
+            .await?;
+        if let Err(e) = self.db.docs.mark_badge_issued(&repo, status.timestamp).await {
+            tracing::warn!("Failed to keep the report behind {}'s badge from pruning: {}", repo, e);
+        }
+        let details = serde_json::json!({
+            "tier": status.tier,
+            "score": status.score,
+            "branch": push.branch,
+            "objects": published,
+        });
+        self.db.audit(ENGINE_ACTOR, AuditAction::BadgeIssued, &audit::repo_target(&repo), details).await;
+        Ok(())
+    }
+
+    /// Post `status` back to the platform under [`STATUS_CONTEXT`](crate::adapters::STATUS_CONTEXT): on pull
+    /// request `number` (as its head's status, or however the platform gates
+    /// pull requests), or on commit `sha` alone. Best effort, like
+    /// [`Self::broadcast_scan`]: the scan is stored either way.
+    async fn report_status(
+        &self,
+        adapter: &dyn PlatformAdapter,
+        repo: &RepoRef,
+        number: Option<u64>,
+        sha: &str,
+        status: &ComplianceStatus,
+    ) {
+        // Jobs queued before head commits were recorded have none; a push
+        // deleting a branch has only zeroes
+        if !adapter.capabilities().commit_status || sha.is_empty() || sha.bytes().all(|b| b == b'0') {
+            return;
+        }
+        let posted = match number {
+            Some(number) => adapter.post_pull_request_status(repo, number, sha, status).await,
+            None => adapter.post_status(repo, sha, status).await,
+        };
+        if let Err(e) = posted {
+            tracing::warn!("Failed to post the compliance status of {} on {}: {}", repo, sha, e);
+            return;
+        }
+        let details = serde_json::json!({
+            "via": "status",
+            "pull_request": number,
+            "commit": sha,
+            "tier": status.tier,
+        });
+        self.db.audit(ENGINE_ACTOR, AuditAction::StatusPosted, &audit::repo_target(repo), details).await;
+    }
+
+    /// Place a scanned repository in its organization hierarchy so rollups
+    /// include it. Best effort, like [`Self::broadcast_scan`].
+    async fn register_hierarchy(&self, config: &EngineConfig, repo: &RepoRef) {
+        let registered = async {
+            let repo_key = self.db.graphs.register_repository(&repo.platform, &repo.owner, &repo.repo).await?;
+            self.db.graphs.register_hierarchy(&repo_key, &config.hierarchy.ancestry(repo)).await
+        };
+        if let Err(e) = registered.await {
+            tracing::warn!("Failed to register {} in its organization hierarchy: {}", repo, e);
+        }
+    }
+
+    /// Replace the repository's dependencies in the graph with what its
+    /// lockfiles list. Best effort, like [`Self::broadcast_scan`].
+    async fn register_dependencies(&self, adapter: &dyn PlatformAdapter, repo: &RepoRef) {
+        match deps::ingest(adapter, repo, self.db.graphs.as_ref()).await {
+            Ok(snapshot) => tracing::debug!(
+                "Dependency snapshot {} of {}: {} added, {} updated, {} removed",
+                snapshot.version,
+                repo,
+                snapshot.added,
+                snapshot.updated,
+                snapshot.removed
+            ),
+            Err(e) => tracing::warn!("Failed to record the dependencies of {}: {}", repo, e),
+        }
+    }
+
+    /// Link a fork to the repository it was forked from, or a mirror in an
+    /// identity link to its canonical repository. Best effort, like
+    /// [`Self::broadcast_scan`].
+    async fn register_upstream(&self, config: &EngineConfig, repo: &RepoRef, forked_from: Option<&str>) {
+        let upstream = match forked_from {
+            Some(path) => format!("{}:{}", repo.platform, path)
+                .parse()
+                .ok()
+                .map(|upstream| (upstream, UpstreamKind::Fork)),
+            None => config
+                .links
+                .iter()
+                .find(|link| link.contains(repo))
+                .and_then(|link| link.canonical().ok())
+                .filter(|canonical| canonical != repo)
+                .map(|canonical| (canonical, UpstreamKind::Mirror)),
+        };
+        let Some((upstream, kind)) = upstream else {
+            return;
+        };
+
+        let linked = async {
+            let graphs = &self.db.graphs;
+            let repo_key = graphs.register_repository(&repo.platform, &repo.owner, &repo.repo).await?;
+            let upstream_key = graphs
+                .register_repository(&upstream.platform, &upstream.owner, &upstream.repo)
+                .await?;
+            graphs.link_fork(&repo_key, &upstream_key, kind).await
+        };
+        if let Err(e) = linked.await {
+            tracing::warn!("Failed to link {} to its upstream {}: {}", repo, upstream, e);
+        }
+    }
+
+    /// Store a fresh status in the report history, cache it and tell other
+    /// instances about it. Best effort: the scan itself already succeeded.
+    async fn broadcast_scan(&self, status: &ComplianceStatus) {
+        if let Err(e) = self.db.docs.store_compliance(status).await {
+            tracing::warn!("Failed to store report for {}: {}", status.repo, e);
+        }
+        let previous = match self.db.cache.get_compliance(&status.repo).await {
+            Ok(previous) => previous.map(|previous| previous.tier),
+            Err(e) => {
+                tracing::warn!("Failed to read cached status for {}: {}", status.repo, e);
+                None
+            }
+        };
+        if let Err(e) = self.db.cache.cache_compliance(status, COMPLIANCE_TTL_SECS).await {
+            tracing::warn!("Failed to cache status for {}: {}", status.repo, e);
+        }
+
+        let mut messages = vec![BusMessage::ScanCompleted {
+            repo: status.repo.clone(),
+            tier: status.tier,
+            score: status.score,
+        }];
+        if previous != Some(status.tier) {
+            messages.push(BusMessage::ComplianceChanged {
+                repo: status.repo.clone(),
+                previous,
+                tier: status.tier,
+                score: status.score,
+            });
+        }
+        for message in messages {
+            if let Err(e) = self.bus.publish(message).await {
+                tracing::warn!("Failed to broadcast scan of {}: {}", status.repo, e);
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl JobHandler for EventJobHandler {
+    fn kind(&self) -> &'static str {
+        EventJob::KIND
+    }
+
+    fn schema_version(&self) -> u32 {
+        EventJob::SCHEMA_VERSION
+    }
+
+    async fn handle(&self, job: String) -> Result<()> {
+        let job: EventJob = serde_json::from_str(&job)?;
+        let event = &job.event;
+
+        tracing::info!("Processing {} event for {}/{}", job.platform, event.repo_owner(), event.repo_name());
+
+        let result = match event {
+            RepoEvent::PullRequest(pr)
+                if matches!(
+                    pr.action,
+                    PullRequestAction::Opened | PullRequestAction::Reopened | PullRequestAction::Synchronize
+                ) =>
+            {
+                self.gate_pull_request(&job, pr).await
+            }
+            RepoEvent::Push(push) => self.publish_default_branch(&job, push).await,
+            RepoEvent::WorkflowRun(run) => self.record_ci_run(&job, run).await,
+            _ => Ok(()),
+        };
+
+        if let Some(ref id) = job.archive_id {
+            let marked = match result {
+                Ok(()) => self.db.docs.mark_event_processed(id).await,
+                Err(ref e) => self.db.docs.mark_event_failed(id, &e.to_string()).await,
+            };
+            if let Err(e) = marked {
+                tracing::warn!("Failed to update archived webhook {}: {}", id, e);
+            }
+        }
+
+        result
+    }
 }
@@ -0,0 +1,151 @@
+//! Maintenance and read-only modes
+//!
+//! In maintenance mode webhooks are still verified, archived and queued, but
+//! the worker pools stop taking jobs, so scans, status posts and
+//! notifications wait until the mode is lifted. Read-only mode is for
+//! disaster-recovery replicas: reports are served, but webhooks and admin
+//! actions that write are refused.
+//!
+//! The mode starts from `RSR_MODE` and operators switch it through the admin
+//! API. It applies to this instance only.
+
+use crate::worker::WorkerPool;
+use crate::{Result, RsrError};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+/// Environment variable holding the mode to start in
+pub const MODE_ENV: &str = "RSR_MODE";
+
+/// What the instance is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperatingMode {
+    #[default]
+    Normal,
+    /// Webhooks are spooled; nothing is processed
+    Maintenance,
+    /// Only report reads are served
+    ReadOnly,
+}
+
+impl OperatingMode {
+    /// Mode named by `RSR_MODE`, or normal if unset
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(MODE_ENV) {
+            Ok(mode) => mode.parse(),
+            Err(_) => Ok(Self::Normal),
+        }
+    }
+
+    /// Whether queued jobs are processed
+    pub fn processes_jobs(&self) -> bool {
+        *self == Self::Normal
+    }
+
+    /// Whether webhooks and admin actions that write are accepted
+    pub fn accepts_writes(&self) -> bool {
+        *self != Self::ReadOnly
+    }
+}
+
+impl std::str::FromStr for OperatingMode {
+    type Err = RsrError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "normal" => Ok(Self::Normal),
+            "maintenance" => Ok(Self::Maintenance),
+            "read_only" | "read-only" => Ok(Self::ReadOnly),
+            _ => Err(RsrError::Config(format!(
+                "Invalid {} {}: expected normal, maintenance or read_only",
+                MODE_ENV, s
+            ))),
+        }
+    }
+}
+
+/// The current mode and who set it
+#[derive(Debug, Clone, Serialize)]
+pub struct ModeState {
+    pub mode: OperatingMode,
+    pub since: chrono::DateTime<chrono::Utc>,
+    /// Operator who set the mode; `None` if it was set at startup
+    pub actor: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Holds the instance's mode and pauses its worker pools to match
+pub struct ModeSwitch {
+    state: RwLock<ModeState>,
+    pools: RwLock<Vec<Arc<WorkerPool>>>,
+}
+
+impl ModeSwitch {
+    pub fn new(mode: OperatingMode) -> Self {
+        if mode != OperatingMode::Normal {
+            tracing::warn!("Starting in {:?} mode", mode);
+        }
+        Self {
+            state: RwLock::new(ModeState {
+                mode,
+                since: chrono::Utc::now(),
+                actor: None,
+                reason: None,
+            }),
+            pools: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Pause and resume `pool` with the mode, starting now
+    pub fn attach(&self, pool: Arc<WorkerPool>) {
+        if !self.current().processes_jobs() {
+            pool.pause();
+        }
+        self.pools.write().expect("mode lock poisoned").push(pool);
+    }
+
+    pub fn current(&self) -> OperatingMode {
+        self.state.read().expect("mode lock poisoned").mode
+    }
+
+    pub fn state(&self) -> ModeState {
+        self.state.read().expect("mode lock poisoned").clone()
+    }
+
+    /// Switch modes, pausing or resuming the worker pools
+    pub fn set(&self, mode: OperatingMode, actor: Option<String>, reason: Option<String>) -> ModeState {
+        let state = ModeState {
+            mode,
+            since: chrono::Utc::now(),
+            actor,
+            reason,
+        };
+        let previous = std::mem::replace(&mut *self.state.write().expect("mode lock poisoned"), state.clone());
+        if previous.mode != mode {
+            tracing::warn!(
+                "Switching from {:?} to {:?} mode (by {}: {})",
+                previous.mode,
+                mode,
+                state.actor.as_deref().unwrap_or("unknown"),
+                state.reason.as_deref().unwrap_or("no reason given")
+            );
+        }
+
+        for pool in self.pools.read().expect("mode lock poisoned").iter() {
+            if mode.processes_jobs() {
+                pool.resume();
+            } else {
+                pool.pause();
+            }
+        }
+
+        state
+    }
+}
+
+impl Default for ModeSwitch {
+    fn default() -> Self {
+        Self::new(OperatingMode::Normal)
+    }
+}
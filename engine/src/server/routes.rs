@@ -1,25 +1,73 @@
 //! HTTP route handlers
 
+use super::mode::OperatingMode;
+use super::slo;
+use super::AppState;
+use crate::adapters::signature::constant_time_eq;
+use crate::adapters::strict;
+use crate::badge::{self, BadgeOptions, BadgeStyle, BadgeValue, Palette};
+use crate::compliance::compare;
+use crate::config::bundle::{BundleSection, ConfigBundle, SignedBundle};
+use crate::config::{ReloadOutcome, UpstreamCompliance};
+use crate::db::annotations::{Annotation, AnnotationKind};
+use crate::db::audit::{self, AuditAction, AuditQuery};
+use crate::db::documents::{VerificationOutcome, WebhookEvent};
+use crate::db::graphs::{repository_key, UpstreamKind};
+use crate::db::health::DatabaseHealth;
+use crate::db::quarantine::ENGINE_VERSION;
+use crate::db::queue::TraceContext;
+use crate::db::trends::{TrendInterval, TrendWindow};
+use crate::discovery::{DiscoveryJob, PackageRegistry};
+use crate::events::{RepoEvent, RepositoryAction};
+use crate::hierarchy;
+use crate::logging;
+use crate::report;
+use crate::{CertificationTier, RepoRef};
 use axum::{
-    extract::{Path, Query},
-    http::{HeaderMap, StatusCode},
-    response::{IntoResponse, Response},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Redirect, Response},
     Json,
 };
 use serde::Deserialize;
+use std::collections::BTreeMap;
+use tokio::sync::broadcast::error::RecvError;
 
-/// Health check endpoint
+
+/// Most repositories compared at once
+const MAX_COMPARED_REPOS: usize = 10;
+
+/// Liveness: the process answers, with how scan latency is doing against
+/// its SLO. Database trouble shows on `/readyz` instead, so an engine with a
+/// backend down isn't restarted.
 pub async fn health() -> impl IntoResponse {
     Json(serde_json::json!({
         "status": "healthy",
         "version": env!("CARGO_PKG_VERSION"),
+        "slo": slo::snapshot(),
     }))
 }
 
+/// Readiness: 200 when ready or degraded (serving cached results while the
+/// document or graph store is unreachable), 503 when the engine can't serve
+pub async fn readiness(State(state): State<AppState>) -> impl IntoResponse {
+    let readiness = match state.db {
+        Some(ref db) => db.readiness().await,
+        None => DatabaseHealth::disabled().readiness(),
+    };
+    let status = if readiness.serves_traffic() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
+
 /// Prometheus metrics endpoint
-pub async fn metrics() -> impl IntoResponse {
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
     // TODO: Implement actual metrics collection
-    let metrics = r#"
+    let mut metrics = r#"
 # HELP rsr_checks_total Total number of compliance checks performed
 # TYPE rsr_checks_total counter
 rsr_checks_total 0
@@ -36,7 +84,173 @@ pub async fn metrics() -> impl IntoResponse {
 rsr_certification_tier{tier="gold"} 0
 rsr_certification_tier{tier="silver"} 0
 rsr_certification_tier{tier="bronze"} 0
-"#;
+"#
+    .to_string();
+
+    let quotas = crate::adapters::http::quota_snapshots();
+    if !quotas.is_empty() {
+        metrics.push_str(
+            "\n# HELP rsr_platform_rate_limit_remaining API requests left in the current window\n\
+             # TYPE rsr_platform_rate_limit_remaining gauge\n",
+        );
+        for (platform, quota) in &quotas {
+            if let Some(remaining) = quota.remaining {
+                metrics.push_str(&format!(
+                    "rsr_platform_rate_limit_remaining{{platform=\"{}\"}} {}\n",
+                    platform, remaining
+                ));
+            }
+        }
+
+        metrics.push_str(
+            "\n# HELP rsr_platform_rate_limit_limit API request budget per window\n\
+             # TYPE rsr_platform_rate_limit_limit gauge\n",
+        );
+        for (platform, quota) in &quotas {
+            if let Some(limit) = quota.limit {
+                metrics.push_str(&format!("rsr_platform_rate_limit_limit{{platform=\"{}\"}} {}\n", platform, limit));
+            }
+        }
+    }
+
+    let parse_warnings = strict::warning_totals();
+    if !parse_warnings.is_empty() {
+        metrics.push_str(
+            "\n# HELP rsr_webhook_parse_warnings_total Missing or unrecognized webhook payload fields found by strict parsing\n\
+             # TYPE rsr_webhook_parse_warnings_total counter\n",
+        );
+        for ((platform, event), count) in &parse_warnings {
+            metrics.push_str(&format!(
+                "rsr_webhook_parse_warnings_total{{platform=\"{}\",event=\"{}\"}} {}\n",
+                platform, event, count
+            ));
+        }
+    }
+
+    let gc = crate::db::gc::gc_totals();
+    if gc.runs > 0 {
+        metrics.push_str(&format!(
+            "\n# HELP rsr_cache_gc_runs_total Completed cache GC passes\n\
+             # TYPE rsr_cache_gc_runs_total counter\n\
+             rsr_cache_gc_runs_total {}\n\
+             \n# HELP rsr_cache_gc_last_run_timestamp_seconds Start of the last cache GC pass\n\
+             # TYPE rsr_cache_gc_last_run_timestamp_seconds gauge\n\
+             rsr_cache_gc_last_run_timestamp_seconds {}\n\
+             \n# HELP rsr_cache_gc_keys_deleted_total Cache keys removed by GC\n\
+             # TYPE rsr_cache_gc_keys_deleted_total counter\n",
+            gc.runs,
+            gc.last_run.map(|t| t.timestamp()).unwrap_or(0)
+        ));
+        for (rule, count) in &gc.by_rule {
+            metrics.push_str(&format!("rsr_cache_gc_keys_deleted_total{{rule=\"{}\"}} {}\n", rule.as_str(), count.keys));
+        }
+        metrics.push_str(
+            "\n# HELP rsr_cache_gc_reclaimed_bytes_total Estimated bytes freed by GC\n\
+             # TYPE rsr_cache_gc_reclaimed_bytes_total counter\n",
+        );
+        for (rule, count) in &gc.by_rule {
+            metrics.push_str(&format!("rsr_cache_gc_reclaimed_bytes_total{{rule=\"{}\"}} {}\n", rule.as_str(), count.bytes));
+        }
+    }
+
+    if let Some(ref workers) = state.workers {
+        let status = workers.status();
+        metrics.push_str(&format!(
+            r#"
+# HELP rsr_queue_depth Jobs waiting on the queue
+# TYPE rsr_queue_depth gauge
+rsr_queue_depth{{queue="{queue}"}} {depth}
+
+# HELP rsr_queue_oldest_job_age_seconds Time the oldest waiting job has been queued
+# TYPE rsr_queue_oldest_job_age_seconds gauge
+rsr_queue_oldest_job_age_seconds{{queue="{queue}"}} {age}
+
+# HELP rsr_queue_in_flight Jobs claimed by a worker and not yet acknowledged
+# TYPE rsr_queue_in_flight gauge
+rsr_queue_in_flight{{queue="{queue}"}} {in_flight}
+
+# HELP rsr_queue_dead_letters Jobs that ran out of delivery attempts
+# TYPE rsr_queue_dead_letters gauge
+rsr_queue_dead_letters{{queue="{queue}"}} {dead}
+
+# HELP rsr_queue_parked Jobs held back by a scan quota
+# TYPE rsr_queue_parked gauge
+rsr_queue_parked{{queue="{queue}"}} {parked}
+
+# HELP rsr_workers Running queue workers
+# TYPE rsr_workers gauge
+rsr_workers{{queue="{queue}"}} {workers}
+
+# HELP rsr_workers_desired Worker count the autoscaler is moving towards
+# TYPE rsr_workers_desired gauge
+rsr_workers_desired{{queue="{queue}"}} {desired}
+"#,
+            queue = status.queue,
+            depth = status.pressure.depth,
+            age = status.pressure.oldest_age_secs.unwrap_or(0),
+            in_flight = status.pressure.in_flight,
+            dead = status.pressure.dead_letters,
+            parked = status.pressure.parked,
+            workers = status.workers,
+            desired = status.desired,
+        ));
+
+        metrics.push_str(
+            "\n# HELP rsr_queue_priority_depth Jobs waiting at each priority\n\
+             # TYPE rsr_queue_priority_depth gauge\n",
+        );
+        for (priority, depth) in &status.pressure.depth_by_priority {
+            metrics.push_str(&format!(
+                "rsr_queue_priority_depth{{queue=\"{}\",priority=\"{}\"}} {}\n",
+                status.queue,
+                priority.as_str(),
+                depth
+            ));
+        }
+    }
+
+    if let Some(ref db) = state.db {
+        let health = db.health();
+        let backends = health.backends();
+        metrics.push_str(
+            "\n# HELP rsr_db_up Whether the backend answered its last health probe\n\
+             # TYPE rsr_db_up gauge\n",
+        );
+        for (backend, health) in &backends {
+            metrics.push_str(&format!("rsr_db_up{{backend=\"{}\"}} {}\n", backend, u8::from(health.is_up())));
+        }
+        metrics.push_str(
+            "\n# HELP rsr_db_probe_latency_ms Time the backend took to answer its last answered probe\n\
+             # TYPE rsr_db_probe_latency_ms gauge\n",
+        );
+        for (backend, health) in &backends {
+            if let Some(latency) = health.latency_ms {
+                metrics.push_str(&format!("rsr_db_probe_latency_ms{{backend=\"{}\"}} {}\n", backend, latency));
+            }
+        }
+        metrics.push_str(
+            "\n# HELP rsr_db_probe_failures_total Health probes the backend failed\n\
+             # TYPE rsr_db_probe_failures_total counter\n",
+        );
+        for (backend, health) in &backends {
+            metrics.push_str(&format!("rsr_db_probe_failures_total{{backend=\"{}\"}} {}\n", backend, health.failures));
+        }
+        metrics.push_str(
+            "\n# HELP rsr_db_reconnects_total Reconnects after which the backend answered again\n\
+             # TYPE rsr_db_reconnects_total counter\n",
+        );
+        for (backend, health) in &backends {
+            metrics.push_str(&format!("rsr_db_reconnects_total{{backend=\"{}\"}} {}\n", backend, health.reconnects));
+        }
+    }
+
+    metrics.push_str(&format!(
+        "\n# HELP rsr_scans_coalesced_total Parked over-quota scans replaced by a newer scan of the same branch\n\
+         # TYPE rsr_scans_coalesced_total counter\n\
+         rsr_scans_coalesced_total {}\n",
+        crate::scheduler::quota::coalesced_total()
+    ));
+    metrics.push_str(&slo::render_metrics(&slo::snapshot()));
 
     (
         StatusCode::OK,
@@ -54,48 +268,133 @@ pub struct RepoPath {
 #[derive(Deserialize)]
 pub struct StatusQuery {
     platform: Option<String>,
-    branch: Option<String>,
 }
 
-/// Get compliance status for a repository
+/// Permanent redirect to the new location of a transferred or renamed repository
+async fn transfer_redirect(
+    state: &AppState,
+    platform: &str,
+    owner: &str,
+    repo: &str,
+    endpoint: &str,
+) -> Option<Response> {
+    let db = state.db.as_ref()?;
+    let target = match db.docs.resolve_redirect(platform, owner, repo).await {
+        Ok(target) => target?,
+        Err(e) => {
+            tracing::warn!("Redirect lookup failed for {}/{}: {}", owner, repo, e);
+            return None;
+        }
+    };
+
+    let location = format!(
+        "/api/v1/repo/{}/{}/{}?platform={}",
+        urlencoding::encode(&target.owner),
+        urlencoding::encode(&target.repo),
+        endpoint,
+        urlencoding::encode(platform)
+    );
+    Some(Redirect::permanent(&location).into_response())
+}
+
+/// Get compliance status for a repository: a summary of its last scan,
+/// from the cache or the stored reports
 pub async fn get_repo_status(
+    State(state): State<AppState>,
     Path(RepoPath { owner, repo }): Path<RepoPath>,
     Query(query): Query<StatusQuery>,
-) -> impl IntoResponse {
-    // TODO: Implement actual status lookup from database/cache
-    let _platform = query.platform.unwrap_or_else(|| "github".to_string());
-    let _branch = query.branch;
+) -> Response {
+    let platform = query.platform.unwrap_or_else(|| "github".to_string());
+
+    if let Some(redirect) = transfer_redirect(&state, &platform, &owner, &repo, "status").await {
+        return redirect;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let repo_ref = RepoRef::new(&platform, &owner, &repo);
+    let Some(status) = status_at(db, &repo_ref, None).await else {
+        let error = format!("{} has not been scanned", repo_ref);
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": error }))).into_response();
+    };
 
+    let passed = status.checks.iter().filter(|check| check.passed).count();
     Json(serde_json::json!({
         "owner": owner,
         "repo": repo,
-        "tier": "silver",
-        "tier_code": "RSR-Ag",
-        "score": 0.75,
-        "last_checked": "2024-01-01T00:00:00Z",
+        "tier": status.tier,
+        "tier_code": status.tier.code(),
+        "score": status.score,
+        "last_checked": status.timestamp,
         "checks": {
-            "passed": 9,
-            "failed": 3,
-            "total": 12
+            "passed": passed,
+            "failed": status.checks.len() - passed,
+            "total": status.checks.len()
         }
     }))
+    .into_response()
 }
 
 #[derive(Deserialize)]
 pub struct BadgeQuery {
-    style: Option<String>,
+    platform: Option<String>,
+    #[serde(default)]
+    style: BadgeStyle,
+    #[serde(default)]
+    value: BadgeValue,
+    #[serde(default)]
+    palette: Palette,
+    locale: Option<String>,
+    label: Option<String>,
 }
 
 /// Generate compliance badge SVG
+///
+/// Query parameters: `style` (flat, flat-square, plastic, for-the-badge),
+/// `value` (tier, percent, grade), `palette` (metal, okabe-ito, high-contrast),
+/// `locale` for number formatting and `label` for the left-hand text.
 pub async fn get_badge(
-    Path(RepoPath { owner: _, repo: _ }): Path<RepoPath>,
+    State(state): State<AppState>,
+    Path(RepoPath { owner, repo }): Path<RepoPath>,
     Query(query): Query<BadgeQuery>,
 ) -> Response {
-    let _style = query.style.unwrap_or_else(|| "flat".to_string());
+    let platform = query.platform.unwrap_or_else(|| "github".to_string());
+
+    if let Some(redirect) = transfer_redirect(&state, &platform, &owner, &repo, "badge").await {
+        return redirect;
+    }
+
+    let mut options = BadgeOptions {
+        value: query.value,
+        style: query.style,
+        palette: query.palette,
+        ..BadgeOptions::branded(&state.branding())
+    };
+    if let Some(locale) = query.locale {
+        options.locale = locale;
+    }
+    if let Some(label) = query.label.filter(|label| !label.trim().is_empty()) {
+        options.label = label.chars().take(badge::MAX_LABEL_CHARS).collect();
+    }
 
-    // TODO: Look up actual tier from database
-    let tier = crate::CertificationTier::Silver;
-    let svg = generate_badge_svg(&tier);
+    // Unscanned repositories get a badge without a tier. Serving a badge
+    // writes nothing; publishing marks the report it shows.
+    let repo_ref = RepoRef::new(&platform, &owner, &repo);
+    let status = match state.db {
+        Some(ref db) => status_at(db, &repo_ref, None).await,
+        None => None,
+    };
+    let (tier, score) = match status {
+        Some(ref status) => (status.tier, status.score),
+        None => (crate::CertificationTier::None, 0.0),
+    };
+    let svg = badge::render(tier, score, &options);
 
     (
         StatusCode::OK,
@@ -108,93 +407,431 @@ pub async fn get_badge(
         .into_response()
 }
 
-fn generate_badge_svg(tier: &crate::CertificationTier) -> String {
-    let color = tier.color();
-    let label = tier.code();
-
-    format!(
-        r##"<svg xmlns="https://www.w3.org/2000/svg" width="120" height="20">
-<linearGradient id="b" x2="0" y2="100%">
-<stop offset="0" stop-color="#bbb" stop-opacity=".1"/>
-<stop offset="1" stop-opacity=".1"/>
-</linearGradient>
-<clipPath id="a">
-<rect width="120" height="20" rx="3" fill="#fff"/>
-</clipPath>
-<g clip-path="url(#a)">
-<path fill="#555" d="M0 0h45v20H0z"/>
-<path fill="{}" d="M45 0h75v20H45z"/>
-<path fill="url(#b)" d="M0 0h120v20H0z"/>
-</g>
-<g fill="#fff" text-anchor="middle" font-family="Verdana,sans-serif" font-size="11">
-<text x="22.5" y="15" fill="#010101" fill-opacity=".3">RSR</text>
-<text x="22.5" y="14">RSR</text>
-<text x="82.5" y="15" fill="#010101" fill-opacity=".3">{}</text>
-<text x="82.5" y="14">{}</text>
-</g>
-</svg>"##,
-        color, label, label
-    )
+#[derive(Deserialize)]
+pub struct ReportQuery {
+    platform: Option<String>,
+    /// Reconstruct the report as it stood at this time from stored history
+    as_of: Option<chrono::DateTime<chrono::Utc>>,
+    /// `json` (default) or `html`
+    format: Option<String>,
 }
 
-/// Get detailed compliance report
+/// Status of a repository: the report current at `as_of` if given, otherwise
+/// the last scan from the cache, falling back to the report history
+async fn status_at(
+    db: &crate::db::DatabasePool,
+    repo: &RepoRef,
+    as_of: Option<chrono::DateTime<chrono::Utc>>,
+) -> Option<crate::ComplianceStatus> {
+    let stored = match as_of {
+        Some(as_of) => db.docs.get_compliance_as_of(&repo.platform, &repo.owner, &repo.repo, as_of).await,
+        None => match db.cache.get_compliance(repo).await {
+            Ok(Some(status)) => return Some(status),
+            _ => db.docs.get_latest_compliance(&repo.platform, &repo.owner, &repo.repo).await,
+        },
+    };
+
+    stored.unwrap_or_else(|e| {
+        tracing::warn!("Failed to read stored compliance for {}: {}", repo, e);
+        None
+    })
+}
+
+/// Get detailed compliance report, with its annotations
+///
+/// With `as_of` (RFC 3339), returns the report that was current at that
+/// time, e.g. to show an auditor what was certified on an incident date.
+/// `format=html` renders it as a standalone page.
 pub async fn get_report(
+    State(state): State<AppState>,
     Path(RepoPath { owner, repo }): Path<RepoPath>,
-) -> impl IntoResponse {
-    // TODO: Implement actual report generation
+    Query(query): Query<ReportQuery>,
+) -> Response {
+    let platform = query.platform.unwrap_or_else(|| "github".to_string());
+
+    if let Some(redirect) = transfer_redirect(&state, &platform, &owner, &repo, "report").await {
+        return redirect;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let repo_ref = RepoRef::new(&platform, &owner, &repo);
+    let policy = state
+        .config
+        .as_ref()
+        .map(|store| store.current().policy_for(&repo_ref).upstream)
+        .unwrap_or_default();
+    // Forks refer to their upstream's current certification, so historical
+    // reports stand on their own
+    let upstream = match policy {
+        UpstreamCompliance::Ignore => None,
+        _ if query.as_of.is_some() => None,
+        _ => db.inherit_compliance_from_upstream(&repo_ref).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to read upstream compliance for {}: {}", repo_ref, e);
+            None
+        }),
+    };
+
+    let (status, inherited) = match (status_at(db, &repo_ref, query.as_of).await, &upstream) {
+        (Some(status), _) => (status, false),
+        (None, Some(upstream)) if policy == UpstreamCompliance::Inherit => (upstream.clone(), true),
+        (None, _) => {
+            let error = match query.as_of {
+                Some(as_of) => format!("{} had no report as of {}", repo_ref, as_of.to_rfc3339()),
+                None => format!("{} has not been scanned", repo_ref),
+            };
+            return (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": error }))).into_response();
+        }
+    };
+
+    // A scan that was never stored has no annotations
+    let annotated = if inherited { &status.repo } else { &repo_ref };
+    let annotations = db.docs.get_annotations(annotated, status.timestamp).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to read annotations for {}: {}", annotated, e);
+        Vec::new()
+    });
+
+    if query.format.as_deref() == Some("html") {
+        return (
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            report::render_annotated_report(&status, &annotations, &state.branding()),
+        )
+            .into_response();
+    }
+
     Json(serde_json::json!({
+        "platform": platform,
         "owner": owner,
         "repo": repo,
-        "generated_at": "2024-01-01T00:00:00Z",
-        "tier": "silver",
-        "tier_code": "RSR-Ag",
-        "score": 0.75,
-        "checks": [
-            {
-                "id": "bronze.license",
-                "name": "License File",
-                "tier": "bronze",
-                "passed": true,
-                "message": "Found valid license file: LICENSE"
-            },
-            {
-                "id": "bronze.readme",
-                "name": "README File",
-                "tier": "bronze",
-                "passed": true,
-                "message": "Found README: README.md"
-            },
-            {
-                "id": "silver.contributing",
-                "name": "Contributing Guide",
-                "tier": "silver",
-                "passed": true,
-                "message": "Found contributing guide: CONTRIBUTING.md"
-            },
-            {
-                "id": "gold.documentation",
-                "name": "Comprehensive Documentation",
-                "tier": "gold",
-                "passed": false,
-                "message": "No comprehensive documentation found"
-            }
-        ],
-        "recommendations": [
-            "Add a docs/ directory with API documentation",
-            "Configure test coverage reporting",
-            "Add Dependabot or Renovate for dependency updates"
-        ]
+        "as_of": query.as_of,
+        "generated_at": status.timestamp,
+        "tier": status.tier,
+        "tier_code": status.tier.code(),
+        "score": status.score,
+        "standard": status.standard,
+        "checks": status.checks,
+        "inherited_from": inherited.then(|| status.repo.to_string()),
+        "upstream": upstream.map(|upstream| serde_json::json!({
+            "repo": upstream.repo.to_string(),
+            "tier": upstream.tier,
+            "tier_code": upstream.tier.code(),
+            "score": upstream.score,
+            "generated_at": upstream.timestamp,
+        })),
+        "annotations": annotations,
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct CatalogQuery {
+    /// Comma-separated standard versions, as recorded on a status
+    standard: Option<String>,
+}
+
+/// Checks, rulepacks (with their digests and signatures) and plugins the
+/// engine certifies with. `?standard=` narrows it to the standard a given
+/// report was checked against.
+pub async fn engine_catalog(State(state): State<AppState>, Query(query): Query<CatalogQuery>) -> Response {
+    let Some(ref standard) = query.standard else {
+        return Json(state.catalog.as_ref()).into_response();
+    };
+
+    let standard: Vec<String> = standard.split(',').map(|s| s.trim().to_string()).collect();
+    match state.catalog.find_standard(&standard) {
+        Some(found) => Json(serde_json::json!({
+            "engine_version": state.catalog.engine_version,
+            "standards": [found],
+            "plugins": state.catalog.plugins,
+            "generated_at": state.catalog.generated_at,
+        }))
+        .into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": format!("This engine doesn't run standard {}", standard.join(",")) })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CompareQuery {
+    /// Comma-separated `platform:owner/repo` identifiers
+    repos: String,
+    /// Compare the reports that were current at this time
+    as_of: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Compare repositories' reports side by side: a matrix of check results
+/// plus each repository's tier, score and per-tier pass rates
+pub async fn compare_repos(State(state): State<AppState>, Query(query): Query<CompareQuery>) -> Response {
+    let repos: Vec<RepoRef> = match query.repos.split(',').map(|repo| repo.trim().parse()).collect() {
+        Ok(repos) => repos,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response();
+        }
+    };
+    if !(2..=MAX_COMPARED_REPOS).contains(&repos.len()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("Compare between 2 and {} repositories", MAX_COMPARED_REPOS),
+            })),
+        )
+            .into_response();
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let mut reports = Vec::new();
+    let mut missing = Vec::new();
+    for repo in &repos {
+        match status_at(db, repo, query.as_of).await {
+            Some(status) => reports.push(status),
+            None => missing.push(repo.to_string()),
+        }
+    }
+    if !missing.is_empty() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "No report for some repositories", "missing": missing })),
+        )
+            .into_response();
+    }
+
+    Json(compare::compare(&reports)).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    platform: Option<String>,
+    /// Reports per page (at most [`crate::db::documents::MAX_HISTORY_PAGE`])
+    limit: Option<u32>,
+    /// Only reports stored before this time, i.e. the previous page's `next`
+    before: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Stored compliance reports for a repository, newest first, a page at a time
+pub async fn get_history(
+    State(state): State<AppState>,
+    Path(RepoPath { owner, repo }): Path<RepoPath>,
+    Query(query): Query<HistoryQuery>,
+) -> Response {
+    let platform = query.platform.unwrap_or_else(|| "github".to_string());
+
+    if let Some(redirect) = transfer_redirect(&state, &platform, &owner, &repo, "history").await {
+        return redirect;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let limit = query.limit.unwrap_or(20);
+    match db.docs.get_compliance_history(&platform, &owner, &repo, limit, query.before).await {
+        Ok(page) => Json(page).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TrendQuery {
+    platform: Option<String>,
+    #[serde(default)]
+    interval: TrendInterval,
+    /// Number of intervals back from now (at most [`crate::db::trends::MAX_TREND_BUCKETS`])
+    buckets: Option<u32>,
+}
+
+/// Daily or weekly score aggregates and tier transitions, for trend charts
+pub async fn get_trend(
+    State(state): State<AppState>,
+    Path(RepoPath { owner, repo }): Path<RepoPath>,
+    Query(query): Query<TrendQuery>,
+) -> Response {
+    let platform = query.platform.unwrap_or_else(|| "github".to_string());
+
+    if let Some(redirect) = transfer_redirect(&state, &platform, &owner, &repo, "trend").await {
+        return redirect;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let default_buckets = match query.interval {
+        TrendInterval::Day => 30,
+        TrendInterval::Week => 26,
+    };
+    let window = TrendWindow::last(query.interval, query.buckets.unwrap_or(default_buckets));
+    match db.docs.get_compliance_trend(&platform, &owner, &repo, window).await {
+        Ok(trend) => Json(trend).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ImpactRoute {
+    owner: String,
+    repo: String,
+    /// Vulnerability id or alias, e.g. a GHSA or CVE id
+    vulnerability: String,
+}
+
+#[derive(Deserialize)]
+pub struct PlatformQuery {
+    platform: Option<String>,
+}
+
+/// The dependency paths through which a vulnerability affects a repository
+pub async fn explain_impact(
+    State(state): State<AppState>,
+    Path(ImpactRoute { owner, repo, vulnerability }): Path<ImpactRoute>,
+    Query(query): Query<PlatformQuery>,
+) -> Response {
+    let platform = query.platform.unwrap_or_else(|| "github".to_string());
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let repo_key = repository_key(&platform, &owner, &repo);
+    match db.graphs.explain_impact(&vulnerability, &repo_key).await {
+        Ok(paths) => Json(serde_json::json!({
+            "repository": RepoRef::new(platform, owner, repo).to_string(),
+            "vulnerability": vulnerability,
+            "affected": !paths.is_empty(),
+            "paths": paths,
+        }))
+        .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Policy a repository is held to, with the units it was inherited through
+pub async fn get_effective_policy(
+    State(state): State<AppState>,
+    Path(RepoPath { owner, repo }): Path<RepoPath>,
+    Query(query): Query<PlatformQuery>,
+) -> Response {
+    let platform = query.platform.unwrap_or_else(|| "github".to_string());
+    let repo = RepoRef::new(platform, owner, repo);
+
+    let config = state.config.as_ref().map(|store| store.current()).unwrap_or_default();
+    Json(serde_json::json!({
+        "repository": repo.to_string(),
+        "ancestry": config.hierarchy.ancestry(&repo),
+        "effective": config.effective_policy(&repo),
     }))
+    .into_response()
+}
+
+/// The repository a repository was ultimately forked or mirrored from, and
+/// every fork and mirror of it
+pub async fn get_fork_network(
+    State(state): State<AppState>,
+    Path(RepoPath { owner, repo }): Path<RepoPath>,
+    Query(query): Query<PlatformQuery>,
+) -> Response {
+    let platform = query.platform.unwrap_or_else(|| "github".to_string());
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    match db.graphs.get_fork_network(&repository_key(&platform, &owner, &repo)).await {
+        Ok(members) => Json(serde_json::json!({ "members": members })).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Check a repository's stored reports against their hash chain, for audits
+pub async fn verify_history(
+    State(state): State<AppState>,
+    Path(RepoPath { owner, repo }): Path<RepoPath>,
+    Query(query): Query<HistoryQuery>,
+) -> Response {
+    let platform = query.platform.unwrap_or_else(|| "github".to_string());
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    match db.docs.verify_report_chain(&RepoRef::new(platform, owner, repo)).await {
+        Ok(verification) => Json(serde_json::json!({
+            "intact": verification.is_intact(),
+            "verification": verification,
+        }))
+        .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
 }
 
 /// Handle incoming webhooks from git platforms
 pub async fn handle_webhook(
+    State(state): State<AppState>,
     Path(platform): Path<String>,
     headers: HeaderMap,
     body: axum::body::Bytes,
-) -> impl IntoResponse {
+) -> Response {
     tracing::info!("Received webhook from platform: {}", platform);
 
+    if let Some(rejection) = reject_if_read_only(&state) {
+        return rejection;
+    }
+
     // Convert headers to our format
     let headers_map: std::collections::HashMap<String, String> = headers
         .iter()
@@ -206,7 +843,7 @@ pub async fn handle_webhook(
         .collect();
 
     // Get the appropriate adapter
-    let config = crate::adapters::AdapterConfig::new();
+    let config = state.adapter_config(&platform);
     let adapter = match crate::adapters::AdapterFactory::create(&platform, config) {
         Ok(a) => a,
         Err(e) => {
@@ -214,24 +851,92 @@ pub async fn handle_webhook(
             return (
                 StatusCode::BAD_REQUEST,
                 Json(serde_json::json!({ "error": format!("Unknown platform: {}", platform) })),
-            );
+            )
+                .into_response();
         }
     };
 
+    if !adapter.capabilities().webhooks {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": format!("{} does not accept webhooks", platform) })),
+        )
+            .into_response();
+    }
+
+    let received_at = chrono::Utc::now();
+    let archive = |verification, event_type: &str, error: Option<String>| WebhookEvent {
+        platform: platform.clone(),
+        event_type: event_type.to_string(),
+        delivery_id: adapter.delivery_id(&headers_map),
+        headers: headers_map.clone(),
+        payload: String::from_utf8_lossy(&body).into_owned(),
+        verification,
+        processed: false,
+        error,
+        quarantined_by: None,
+        parse_warnings: Vec::new(),
+        received_at,
+    };
+
     // Verify webhook signature
     match adapter.verify_webhook(&body, &headers_map) {
         Ok(true) => {}
+        Err(crate::RsrError::InsecureConfiguration(reason)) => {
+            tracing::error!("Refusing {} webhook: {}", platform, reason);
+            archive_webhook(&state, archive(VerificationOutcome::Unconfigured, "unknown", Some(reason))).await;
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "error": "Webhook verification is not configured" })),
+            )
+                .into_response();
+        }
         Ok(false) | Err(_) => {
             tracing::warn!("Webhook signature verification failed for {}", platform);
+            archive_webhook(&state, archive(VerificationOutcome::Rejected, "unknown", None)).await;
             return (
                 StatusCode::UNAUTHORIZED,
                 Json(serde_json::json!({ "error": "Invalid signature" })),
-            );
+            )
+                .into_response();
         }
     }
 
-    // Parse the webhook
-    match adapter.parse_webhook(&body, &headers_map) {
+    // Reject replayed deliveries. Without a cache we can't tell, so accept.
+    let delivery = match (state.db.as_ref(), adapter.delivery_id(&headers_map)) {
+        (Some(db), Some(id)) => match db.cache.record_delivery(&platform, &id, replay_window_secs()).await {
+            Ok(true) => Some(id),
+            Ok(false) => {
+                tracing::warn!("Rejecting duplicate {} delivery {}", platform, id);
+                return (
+                    StatusCode::CONFLICT,
+                    Json(serde_json::json!({ "error": "Duplicate delivery" })),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                tracing::warn!("Replay check unavailable for {} delivery {}: {}", platform, id, e);
+                None
+            }
+        },
+        _ => None,
+    };
+
+    // Parse the webhook, collecting payload drift if parsing strictly
+    let (parsed, parse_warnings) = if strict::enabled() {
+        strict::parse(adapter.as_ref(), &body, &headers_map)
+    } else {
+        (adapter.parse_webhook(&body, &headers_map), Vec::new())
+    };
+    for warning in &parse_warnings {
+        tracing::warn!("Unexpected {} webhook payload: {}", platform, warning);
+    }
+    let archive = |verification, event_type: &str, error: Option<String>| WebhookEvent {
+        parse_warnings: parse_warnings.clone(),
+        ..archive(verification, event_type, error)
+    };
+
+    match parsed {
         Ok(event) => {
             tracing::info!(
                 "Parsed {} event for {}/{}",
@@ -240,8 +945,55 @@ pub async fn handle_webhook(
                 event.repo_name()
             );
 
-            // TODO: Queue the event for async processing
-            // For now, just acknowledge receipt
+            if let Some(limited) = check_webhook_rate(&state, &platform, &event).await {
+                forget_delivery(&state, &platform, delivery.as_deref()).await;
+                return limited;
+            }
+
+            if let RepoEvent::Repository(ref repo_event) = event {
+                if let Err(e) = apply_repository_event(&state, &platform, repo_event).await {
+                    tracing::error!("Failed to migrate repository data: {}", e);
+                    forget_delivery(&state, &platform, delivery.as_deref()).await;
+                    archive_webhook(&state, archive(VerificationOutcome::Verified, event.kind(), Some(e.to_string()))).await;
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(serde_json::json!({ "error": format!("Failed to migrate: {}", e) })),
+                    )
+                        .into_response();
+                }
+            }
+
+            if let Some(ref db) = state.db {
+                let trace = headers
+                    .get("traceparent")
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|traceparent| {
+                        let tracestate = headers.get("tracestate").and_then(|value| value.to_str().ok());
+                        TraceContext::parse(traceparent, tracestate)
+                    });
+                let webhook = archive(VerificationOutcome::Verified, event.kind(), None);
+                let job = super::EventJob {
+                    platform: platform.clone(),
+                    event: event.clone(),
+                    archive_id: None,
+                    received_at: Some(received_at),
+                };
+
+                // Without the document store there's no outbox; queue the
+                // event directly, unarchived
+                if let Err(e) = job.archive_and_enqueue(db, &webhook, trace.clone()).await {
+                    tracing::warn!("Failed to archive {} webhook with its job, queueing directly: {}", platform, e);
+                    if let Err(e) = job.enqueue(db, trace).await {
+                        tracing::error!("Failed to queue event: {}", e);
+                        forget_delivery(&state, &platform, delivery.as_deref()).await;
+                        return (
+                            StatusCode::SERVICE_UNAVAILABLE,
+                            Json(serde_json::json!({ "error": "Failed to queue event" })),
+                        )
+                            .into_response();
+                    }
+                }
+            }
 
             (
                 StatusCode::OK,
@@ -250,13 +1002,1347 @@ pub async fn handle_webhook(
                     "repo": format!("{}/{}", event.repo_owner(), event.repo_name())
                 })),
             )
+                .into_response()
         }
         Err(e) => {
-            tracing::error!("Failed to parse webhook: {}", e);
+            tracing::error!("Quarantining unparseable {} webhook: {}", platform, e);
+            let quarantined = WebhookEvent {
+                quarantined_by: Some(ENGINE_VERSION.to_string()),
+                ..archive(VerificationOutcome::Verified, "unknown", Some(e.to_string()))
+            };
+            archive_webhook(&state, quarantined).await;
             (
                 StatusCode::BAD_REQUEST,
                 Json(serde_json::json!({ "error": format!("Failed to parse: {}", e) })),
             )
+                .into_response()
+        }
+    }
+}
+
+/// Archive a webhook delivery for later replay. Archival is best effort and
+/// never fails the request.
+async fn archive_webhook(state: &AppState, event: WebhookEvent) -> Option<String> {
+    let db = state.db.as_ref()?;
+    match db.docs.store_webhook_event(&event).await {
+        Ok(id) => Some(id),
+        Err(e) => {
+            tracing::warn!("Failed to archive {} webhook: {}", event.platform, e);
+            None
+        }
+    }
+}
+
+/// Spend one of the repository's webhook budget, returning a 429 response
+/// if it is used up. Without a cache, or if the check fails, accept.
+async fn check_webhook_rate(state: &AppState, platform: &str, event: &RepoEvent) -> Option<Response> {
+    let (db, (max_requests, window_secs)) = (state.db.as_ref()?, webhook_rate_limit()?);
+    let key = format!("webhook:{}:{}/{}", platform, event.repo_owner(), event.repo_name());
+
+    let decision = match db.cache.rate_limit(&key, max_requests, window_secs).await {
+        Ok(decision) => decision,
+        Err(e) => {
+            tracing::warn!("Rate limit unavailable for {}: {}", key, e);
+            return None;
+        }
+    };
+
+    let retry_after = decision.retry_after_secs()?;
+    tracing::warn!("Rate limiting {} webhooks for {}/{}", platform, event.repo_owner(), event.repo_name());
+
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(serde_json::json!({ "error": "Rate limit exceeded", "retry_after_secs": retry_after })),
+    )
+        .into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::RETRY_AFTER, retry_after.into());
+    headers.insert("ratelimit-limit", decision.limit.into());
+    headers.insert("ratelimit-remaining", decision.remaining.into());
+    headers.insert("ratelimit-reset", decision.reset_after_secs.into());
+    Some(response)
+}
+
+/// Webhooks accepted per repository per window, from
+/// `RSR_WEBHOOK_RATE_LIMIT` and `RSR_WEBHOOK_RATE_WINDOW_SECS` (0 disables)
+fn webhook_rate_limit() -> Option<(u64, u64)> {
+    let env = |name: &str, default: u64| {
+        std::env::var(name)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default)
+    };
+    let max_requests = env("RSR_WEBHOOK_RATE_LIMIT", 120);
+    let window_secs = env("RSR_WEBHOOK_RATE_WINDOW_SECS", 60);
+
+    (max_requests > 0 && window_secs > 0).then_some((max_requests, window_secs))
+}
+
+/// How long delivery IDs are remembered for replay protection
+fn replay_window_secs() -> u64 {
+    std::env::var("RSR_WEBHOOK_REPLAY_WINDOW_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(72 * 60 * 60)
+}
+
+/// Let the platform redeliver an event we failed to handle
+async fn forget_delivery(state: &AppState, platform: &str, delivery: Option<&str>) {
+    if let (Some(db), Some(id)) = (state.db.as_ref(), delivery) {
+        if let Err(e) = db.cache.forget_delivery(platform, id).await {
+            tracing::warn!("Failed to forget {} delivery {}: {}", platform, id, e);
         }
     }
 }
+
+/// Reload the engine configuration from disk
+pub async fn reload_config(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    let Some(ref store) = state.config else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Server was started without a config file" })),
+        )
+            .into_response();
+    };
+
+    let entry = store.reload(crate::config::ReloadSource::Api { actor: request_actor(&headers) });
+    if let Some(ref db) = state.db {
+        db.audit(&audit_actor(&headers), AuditAction::ConfigReloaded, "config", serde_json::json!(entry)).await;
+        super::broadcast_config_change(db, &entry).await;
+    }
+
+    match entry.outcome {
+        ReloadOutcome::Rejected { ref reason } => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({ "error": reason })),
+        )
+            .into_response(),
+        _ => (StatusCode::OK, Json(serde_json::json!(entry))).into_response(),
+    }
+}
+
+/// Configuration load/reload audit log
+pub async fn config_audit(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    let entries = state
+        .config
+        .as_ref()
+        .map(|store| store.audit_log())
+        .unwrap_or_default();
+
+    Json(serde_json::json!({ "entries": entries })).into_response()
+}
+
+/// Sign the running policies, hierarchy and notification rules as a bundle
+/// another instance can import
+pub async fn export_config(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    let Some(ref store) = state.config else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Server was started without a config file" })),
+        )
+            .into_response();
+    };
+
+    let bundle = ConfigBundle::export(&store.current(), &store.digest());
+    match SignedBundle::sign_with_env(&bundle) {
+        Ok(signed) => Json(signed).into_response(),
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BundleImportQuery {
+    /// Comma-separated sections to apply; all of them by default
+    sections: Option<String>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Apply a signed bundle exported by another instance
+pub async fn import_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<BundleImportQuery>,
+    Json(signed): Json<SignedBundle>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+    if !query.dry_run {
+        if let Some(rejection) = reject_if_read_only(&state) {
+            return rejection;
+        }
+    }
+
+    let Some(ref store) = state.config else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Server was started without a config file" })),
+        )
+            .into_response();
+    };
+
+    let sections = match query.sections {
+        Some(ref names) => match names.split(',').map(BundleSection::parse).collect::<crate::Result<Vec<_>>>() {
+            Ok(sections) => sections,
+            Err(e) => {
+                return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response()
+            }
+        },
+        None => BundleSection::ALL.to_vec(),
+    };
+
+    match store.import(&signed, &sections, query.dry_run, request_actor(&headers)) {
+        Ok(import) => {
+            if let (Some(ref db), Some(ref entry)) = (&state.db, &import.reload) {
+                db.audit(&audit_actor(&headers), AuditAction::ConfigImported, "config", serde_json::json!(import)).await;
+                super::broadcast_config_change(db, entry).await;
+            }
+            Json(import).into_response()
+        }
+        Err(e) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EventStreamQuery {
+    /// Only events about this repository, as `platform:owner/repo`
+    repo: Option<String>,
+}
+
+/// Server-sent events for scans and compliance changes on any replica.
+/// Configuration changes are only streamed to admins. A subscriber that
+/// falls behind gets a `lagged` event with the number of events it missed.
+pub async fn event_stream(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<EventStreamQuery>,
+) -> Response {
+    let Some(ref events) = state.events else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Event streams require the databases" })),
+        )
+            .into_response();
+    };
+
+    let repo = match query.repo.as_deref().map(str::parse::<RepoRef>).transpose() {
+        Ok(repo) => repo,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response();
+        }
+    };
+    let admin = reject_unless_admin(&headers).is_none();
+
+    let stream = futures::stream::unfold(events.subscribe(), move |mut receiver| {
+        let repo = repo.clone();
+        async move {
+            loop {
+                let envelope = match receiver.recv().await {
+                    Ok(envelope) => envelope,
+                    Err(RecvError::Lagged(missed)) => {
+                        let event = Event::default().event("lagged").data(missed.to_string());
+                        return Some((Ok(event), receiver));
+                    }
+                    Err(RecvError::Closed) => return None,
+                };
+
+                let wanted = match (envelope.message.repo(), &repo) {
+                    (Some(about), Some(repo)) => {
+                        about.platform == repo.platform && about.owner == repo.owner && about.repo == repo.repo
+                    }
+                    (Some(_), None) => true,
+                    (None, Some(_)) => false,
+                    (None, None) => admin,
+                };
+                if wanted {
+                    let event = Event::default().event(envelope.message.kind()).json_data(&envelope);
+                    return Some((event, receiver));
+                }
+            }
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct AuditLogQuery {
+    /// `platform:owner/repo`
+    repo: Option<String>,
+    actor: Option<String>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Events per page (at most [`crate::db::audit::MAX_AUDIT_PAGE`])
+    limit: Option<u32>,
+    /// Only events before this time, i.e. the previous page's `next`
+    before: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Audit log, newest first, a page at a time
+pub async fn audit_log(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AuditLogQuery>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let repo = match query.repo.as_deref().map(str::parse::<RepoRef>).transpose() {
+        Ok(repo) => repo,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response();
+        }
+    };
+    let filters = AuditQuery {
+        repo,
+        actor: query.actor,
+        since: query.since,
+        until: query.until,
+    };
+
+    match db.docs.get_audit_events(&filters, query.limit.unwrap_or(50), query.before).await {
+        Ok(page) => Json(page).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Current operating mode
+pub async fn get_mode(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    Json(state.mode.state()).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ModeRequest {
+    mode: OperatingMode,
+    reason: Option<String>,
+}
+
+/// Enter or leave maintenance or read-only mode
+pub async fn set_mode(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ModeRequest>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    let previous = state.mode.current();
+    let actor = audit_actor(&headers);
+    let current = state.mode.set(request.mode, Some(actor.clone()), request.reason);
+
+    if let Some(ref db) = state.db {
+        let details = serde_json::json!({ "from": previous, "to": current.mode, "reason": current.reason });
+        db.audit(&actor, AuditAction::ModeChanged, "mode", details).await;
+    }
+    Json(current).into_response()
+}
+
+/// Logging profile, base filter and per-module overrides
+pub async fn get_logging(headers: HeaderMap) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    match logging::control() {
+        Some(control) => Json(control.settings()).into_response(),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Logging is not configurable in this process" })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LoggingRequest {
+    /// Level by module path, e.g. `{"rsr_engine::adapters::github": "trace"}`;
+    /// replaces the current overrides, so `{}` clears them
+    overrides: BTreeMap<String, String>,
+}
+
+/// Replace the per-module log level overrides
+pub async fn set_logging(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<LoggingRequest>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    let Some(control) = logging::control() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Logging is not configurable in this process" })),
+        )
+            .into_response();
+    };
+
+    let previous = control.settings().overrides;
+    match control.set_overrides(request.overrides) {
+        Ok(settings) => {
+            if let Some(ref db) = state.db {
+                let details = serde_json::json!({ "from": previous, "to": settings.overrides });
+                db.audit(&audit_actor(&headers), AuditAction::LogLevelsChanged, "logging", details).await;
+            }
+            Json(settings).into_response()
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Queue pressure for external autoscalers (KEDA metrics-api, HPA external metrics)
+pub async fn queue_pressure(State(state): State<AppState>) -> Response {
+    let Some(ref workers) = state.workers else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "No job queue configured" })),
+        )
+            .into_response();
+    };
+
+    let status = workers.status();
+    Json(serde_json::json!({
+        "queue": status.queue,
+        "depth": status.pressure.depth,
+        "depth_by_priority": status.pressure.depth_by_priority,
+        "oldest_age_secs": status.pressure.oldest_age_secs.unwrap_or(0),
+        "in_flight": status.pressure.in_flight,
+        "dead_letters": status.pressure.dead_letters,
+        "workers": status.workers,
+        "desired": status.desired,
+    }))
+    .into_response()
+}
+
+/// Worker pool status
+pub async fn worker_status(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    match state.workers {
+        Some(ref workers) => Json(serde_json::json!(workers.status())).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "No job queue configured" })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ScaleRequest {
+    /// Pinned worker count; omit or null to return to autoscaling
+    workers: Option<usize>,
+}
+
+/// Pin the worker count or hand control back to the autoscaler
+pub async fn scale_workers(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ScaleRequest>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    let Some(ref workers) = state.workers else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "No job queue configured" })),
+        )
+            .into_response();
+    };
+
+    workers.set_override(request.workers);
+    if let Some(ref db) = state.db {
+        let details = serde_json::json!({ "queue": super::EVENTS_QUEUE, "workers": request.workers });
+        db.audit(&audit_actor(&headers), AuditAction::WorkersScaled, "workers", details).await;
+    }
+    Json(serde_json::json!(workers.status())).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct GcQuery {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Run a cache GC pass now
+pub async fn run_cache_gc(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<GcQuery>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+    if !query.dry_run {
+        if let Some(rejection) = reject_if_read_only(&state) {
+            return rejection;
+        }
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    match db.cache.collect_garbage(query.dry_run).await {
+        Ok(report) => Json(serde_json::json!(report)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PruneQuery {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Tombstone stored reports outside the configured retention now
+pub async fn prune_reports(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<PruneQuery>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+    if !query.dry_run {
+        if let Some(rejection) = reject_if_read_only(&state) {
+            return rejection;
+        }
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let policy = state.config.as_ref().map(|store| store.current().retention).unwrap_or_default();
+    match db.docs.prune_reports(&policy, query.dry_run).await {
+        Ok(report) => {
+            if !report.dry_run && report.tombstoned > 0 {
+                let details = serde_json::json!(report);
+                db.audit(&audit_actor(&headers), AuditAction::ReportsPruned, "reports", details).await;
+            }
+            Json(report).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AnnotationRequest {
+    /// `platform:owner/repo`
+    repo: String,
+    /// Report to annotate, by its `generated_at`; defaults to the latest
+    report_at: Option<chrono::DateTime<chrono::Utc>>,
+    kind: AnnotationKind,
+    check_id: Option<String>,
+    message: String,
+    reference: Option<String>,
+}
+
+/// Annotate a stored report. The author is taken from `X-RSR-Actor`.
+pub async fn annotate_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<AnnotationRequest>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+    if let Some(rejection) = reject_if_read_only(&state) {
+        return rejection;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let repo: RepoRef = match request.repo.parse() {
+        Ok(repo) => repo,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response();
+        }
+    };
+
+    let stored = match request.report_at {
+        Some(report_at) => db
+            .docs
+            .get_compliance_as_of(&repo.platform, &repo.owner, &repo.repo, report_at)
+            .await
+            .map(|status| status.filter(|status| status.timestamp == report_at)),
+        None => db.docs.get_latest_compliance(&repo.platform, &repo.owner, &repo.repo).await,
+    };
+    let report = match stored {
+        Ok(Some(report)) => report,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": format!("No such report for {}", repo) })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(ref check_id) = request.check_id {
+        if !report.checks.iter().any(|check| &check.id == check_id) {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(serde_json::json!({ "error": format!("The report has no check {}", check_id) })),
+            )
+                .into_response();
+        }
+    }
+
+    let author = audit_actor(&headers);
+    let annotation = Annotation {
+        id: None,
+        kind: request.kind,
+        check_id: request.check_id,
+        message: request.message,
+        reference: request.reference,
+        author,
+        created_at: chrono::Utc::now(),
+    };
+
+    match db.docs.annotate_report(&repo, report.timestamp, &annotation).await {
+        Ok(id) => {
+            let details = serde_json::json!({
+                "id": id,
+                "report_at": report.timestamp,
+                "kind": annotation.kind,
+                "check_id": annotation.check_id,
+            });
+            db.audit(&annotation.author, AuditAction::AnnotationAdded, &audit::repo_target(&repo), details).await;
+            (
+                StatusCode::CREATED,
+                Json(serde_json::json!({
+                    "id": id,
+                    "repo": repo,
+                    "report_at": report.timestamp,
+                    "annotation": annotation,
+                })),
+            )
+                .into_response()
+        }
+        Err(crate::RsrError::Config(e)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({ "error": e }))).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Remove an annotation
+pub async fn remove_annotation(State(state): State<AppState>, headers: HeaderMap, Path(id): Path<String>) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+    if let Some(rejection) = reject_if_read_only(&state) {
+        return rejection;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    match db.docs.remove_annotation(&id).await {
+        Ok(true) => {
+            db.audit(&audit_actor(&headers), AuditAction::AnnotationRemoved, &id, serde_json::json!({})).await;
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": format!("No annotation {}", id) })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DiscoveryRequest {
+    registry: String,
+    package: String,
+}
+
+/// Queue discovery of a package's repository from its registry
+pub async fn discover_package(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<DiscoveryRequest>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+    if let Some(rejection) = reject_if_read_only(&state) {
+        return rejection;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let registry = match request.registry.parse::<PackageRegistry>() {
+        Ok(registry) => registry,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response();
+        }
+    };
+
+    match DiscoveryJob::new(registry, &request.package).enqueue(db).await {
+        Ok(job_id) => (
+            StatusCode::ACCEPTED,
+            Json(serde_json::json!({
+                "status": "queued",
+                "job_id": job_id,
+                "registry": registry,
+                "package": request.package,
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Certification of the repository a package is developed in, by package
+/// name. The package must have been discovered first.
+pub async fn get_package_status(
+    State(state): State<AppState>,
+    Path((registry, package)): Path<(String, String)>,
+) -> Response {
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let registry = match registry.parse::<PackageRegistry>() {
+        Ok(registry) => registry,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response();
+        }
+    };
+
+    let repo = match db.graphs.get_package_repository(registry.as_str(), &package).await {
+        Ok(Some(repo)) => repo,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({
+                    "error": format!("{} on {} has not been discovered", package, registry),
+                })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response();
+        }
+    };
+
+    let status = status_at(db, &repo, None).await;
+
+    Json(serde_json::json!({
+        "registry": registry,
+        "package": package,
+        "repo": repo,
+        "certified": status.as_ref().is_some_and(|status| status.tier > CertificationTier::None),
+        "tier": status.as_ref().map(|status| status.tier),
+        "tier_code": status.as_ref().map(|status| status.tier.code()),
+        "score": status.as_ref().map(|status| status.score),
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct RollupQuery {
+    /// Roll up the reports that were current at this time
+    as_of: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Compliance rolled up to an organizational unit (`platform:path` or
+/// `enterprise:name`) and every unit below it.
+///
+/// With `as_of`, rolls up each repository's report as of that time.
+/// Membership is current, not historical: repositories are rolled up under
+/// the units they belong to now, and ones with no report by then count as
+/// unscanned.
+pub async fn get_organization_rollup(
+    State(state): State<AppState>,
+    Path(unit): Path<String>,
+    Query(query): Query<RollupQuery>,
+) -> Response {
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    let repos = match db.graphs.get_organization_repositories(&unit).await {
+        Ok(repos) if repos.is_empty() => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": format!("No repositories are registered under {}", unit) })),
+            )
+                .into_response();
+        }
+        Ok(repos) => repos,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response();
+        }
+    };
+
+    let reports = match query.as_of {
+        Some(as_of) => {
+            let mut reports = Vec::new();
+            for repo in &repos {
+                reports.extend(status_at(db, repo, Some(as_of)).await);
+            }
+            reports
+        }
+        // The cache holds the last scans; fall back to the report history for misses
+        None => {
+            let cached = db.cache.get_compliance_many(&repos).await.unwrap_or_else(|e| {
+                tracing::warn!("Failed to read cached statuses under {}: {}", unit, e);
+                vec![None; repos.len()]
+            });
+            let mut reports = Vec::new();
+            for (repo, status) in repos.iter().zip(cached) {
+                match status {
+                    Some(status) => reports.push(status),
+                    None => reports.extend(status_at(db, repo, None).await),
+                }
+            }
+            reports
+        }
+    };
+
+    let config = state.config.as_ref().map(|store| store.current()).unwrap_or_default();
+    let rollups: Vec<_> = hierarchy::rollup(&reports, &config.hierarchy, &config.policies)
+        .into_iter()
+        .filter(|rollup| hierarchy::is_within(&rollup.unit, &unit))
+        .collect();
+
+    Json(serde_json::json!({
+        "unit": unit,
+        "as_of": query.as_of,
+        "unscanned": repos.len() - reports.len(),
+        "rollups": rollups,
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+pub struct OwnerSummaryQuery {
+    platform: Option<String>,
+}
+
+/// Tier counts, mean score, worst offenders and lapsed certifications across
+/// every repository under an owner, from each one's latest report
+pub async fn get_owner_summary(
+    State(state): State<AppState>,
+    Path(owner): Path<String>,
+    Query(query): Query<OwnerSummaryQuery>,
+) -> Response {
+    let platform = query.platform.unwrap_or_else(|| "github".to_string());
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    match db.docs.get_org_summary(&platform, &owner).await {
+        Ok(summary) => Json(summary).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct FailedWebhooksQuery {
+    #[serde(default = "default_failed_limit")]
+    limit: u32,
+}
+
+fn default_failed_limit() -> u32 {
+    50
+}
+
+/// Archived webhooks that failed to parse, queue or process
+pub async fn failed_webhooks(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<FailedWebhooksQuery>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    match db.docs.get_failed_events(query.limit).await {
+        Ok(events) => Json(serde_json::json!(events)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Webhooks quarantined because their payload failed to parse, with the
+/// error and the engine version that rejected them
+pub async fn quarantined_webhooks(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<FailedWebhooksQuery>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    match db.docs.get_quarantined_events(query.limit).await {
+        Ok(events) => Json(serde_json::json!({
+            "engine_version": ENGINE_VERSION,
+            "events": events,
+        }))
+        .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct QuarantineReplayQuery {
+    /// Also retry payloads this engine version already failed to parse
+    #[serde(default)]
+    all: bool,
+}
+
+/// Replay quarantined webhooks after an upgrade
+pub async fn replay_quarantine(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<QuarantineReplayQuery>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+    if let Some(rejection) = reject_if_read_only(&state) {
+        return rejection;
+    }
+
+    match state.replay_quarantine(query.all).await {
+        Ok(outcome) => {
+            if let Some(ref db) = state.db {
+                db.audit(
+                    &audit_actor(&headers),
+                    AuditAction::QuarantineReplayed,
+                    "webhooks",
+                    serde_json::json!(outcome),
+                )
+                .await;
+            }
+            Json(serde_json::json!(outcome)).into_response()
+        }
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Re-process an archived webhook
+pub async fn replay_webhook(
+    State(state): State<AppState>,
+    Path(event_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+    if let Some(rejection) = reject_if_read_only(&state) {
+        return rejection;
+    }
+
+    match state.replay_event(&event_id).await {
+        Ok(Some(event)) => {
+            let repo = format!("{}/{}", event.repo_owner(), event.repo_name());
+            if let Some(ref db) = state.db {
+                let details = serde_json::json!({ "type": event.kind(), "repo": repo });
+                db.audit(&audit_actor(&headers), AuditAction::WebhookReplayed, &event_id, details).await;
+            }
+            Json(serde_json::json!({
+                "status": "queued",
+                "event_id": event_id,
+                "type": event.kind(),
+                "repo": repo,
+            }))
+            .into_response()
+        }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": format!("No archived webhook {}", event_id) })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DeadLettersQuery {
+    #[serde(default = "default_dead_letter_limit")]
+    limit: usize,
+}
+
+fn default_dead_letter_limit() -> usize {
+    100
+}
+
+/// Event jobs that ran out of delivery attempts
+pub async fn dead_letters(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<DeadLettersQuery>,
+) -> Response {
+    if let Some(rejection) = reject_unless_admin(&headers) {
+        return rejection;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    match db.cache.dead_letters(super::EVENTS_QUEUE, query.limit).await {
+        Ok(jobs) => Json(serde_json::json!(jobs)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Put one dead-lettered event job back on the queue
+pub async fn requeue_dead_letter(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    requeue_dead_letters(&state, &headers, Some(&job_id)).await
+}
+
+/// Put every dead-lettered event job back on the queue
+pub async fn requeue_all_dead_letters(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    requeue_dead_letters(&state, &headers, None).await
+}
+
+async fn requeue_dead_letters(state: &AppState, headers: &HeaderMap, job_id: Option<&str>) -> Response {
+    if let Some(rejection) = reject_unless_admin(headers) {
+        return rejection;
+    }
+    if let Some(rejection) = reject_if_read_only(state) {
+        return rejection;
+    }
+
+    let Some(ref db) = state.db else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Databases unavailable" })),
+        )
+            .into_response();
+    };
+
+    match db.cache.requeue_dead_letters(super::EVENTS_QUEUE, job_id).await {
+        Ok(0) if job_id.is_some() => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": format!("No dead-lettered job {}", job_id.unwrap_or_default()) })),
+        )
+            .into_response(),
+        Ok(requeued) => {
+            let details = serde_json::json!({ "job_id": job_id, "requeued": requeued });
+            db.audit(&audit_actor(headers), AuditAction::DeadLettersRequeued, super::EVENTS_QUEUE, details).await;
+            Json(serde_json::json!({ "requeued": requeued })).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+/// Refuse requests that write while the instance is a read-only replica
+fn reject_if_read_only(state: &AppState) -> Option<Response> {
+    if state.mode.current().accepts_writes() {
+        return None;
+    }
+    Some(
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "This instance is read-only" })),
+        )
+            .into_response(),
+    )
+}
+
+/// Who an admin request is made on behalf of, from `X-RSR-Actor`
+fn request_actor(headers: &HeaderMap) -> Option<String> {
+    headers.get("x-rsr-actor").and_then(|v| v.to_str().ok()).map(str::to_string)
+}
+
+/// Audit actor for an admin request
+fn audit_actor(headers: &HeaderMap) -> String {
+    request_actor(headers).unwrap_or_else(|| "admin".to_string())
+}
+
+/// Admin endpoints require `Authorization: Bearer $RSR_ADMIN_TOKEN`.
+/// They are disabled entirely when no token, or an empty one, is configured.
+fn reject_unless_admin(headers: &HeaderMap) -> Option<Response> {
+    let Some(expected) = std::env::var("RSR_ADMIN_TOKEN").ok().filter(|token| !token.trim().is_empty()) else {
+        return Some(
+            (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({ "error": "Admin API disabled (RSR_ADMIN_TOKEN not set)" })),
+            )
+                .into_response(),
+        );
+    };
+
+    (!bearer_matches(headers, &expected)).then(|| {
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({ "error": "Invalid admin token" })),
+        )
+            .into_response()
+    })
+}
+
+/// Whether the request's bearer token is `expected`, which must not be empty
+fn bearer_matches(headers: &HeaderMap, expected: &str) -> bool {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|token| !token.is_empty() && constant_time_eq(token.as_bytes(), expected.as_bytes()))
+}
+
+/// Move or retire stored data when a repository is transferred, renamed or deleted
+pub(super) async fn apply_repository_event(
+    state: &AppState,
+    platform: &str,
+    event: &crate::events::RepositoryEvent,
+) -> crate::Result<()> {
+    let Some(ref db) = state.db else {
+        tracing::warn!("No database configured, ignoring repository {:?} event", event.action);
+        return Ok(());
+    };
+
+    let current = RepoRef::new(platform, &event.repo_owner, &event.repo_name);
+
+    match event.action {
+        RepositoryAction::Created => {
+            tracing::info!("Repository created: {}", current);
+            db.docs.register_repository(&current, None).await.map(|_| ())
+        }
+        RepositoryAction::Transferred | RepositoryAction::Renamed => {
+            let Some((owner, name)) = event.previous_identity() else {
+                return Err(crate::RsrError::Platform(
+                    "Repository event is missing its previous owner/name".to_string(),
+                ));
+            };
+            let previous = RepoRef::new(platform, owner, name);
+
+            // A redelivered or replayed event finds the move already made:
+            // the old identity redirects to the new one, or to wherever it
+            // has moved since
+            if let Some(moved_to) = db.docs.resolve_redirect(platform, owner, name).await? {
+                let current_moved_to = db.docs.resolve_redirect(platform, &current.owner, &current.repo).await?;
+                if moved_to == current || Some(&moved_to) == current_moved_to.as_ref() {
+                    tracing::info!("Repository move {} -> {} already applied", previous, current);
+                    return Ok(());
+                }
+            }
+
+            tracing::info!("Repository moved: {} -> {}", previous, current);
+            db.transfer_repository(&previous, &current).await
+        }
+        RepositoryAction::Archived | RepositoryAction::Unarchived => {
+            // Archival doesn't move data; the scan picks up the new state
+            tracing::info!("Repository {:?}: {}", event.action, current);
+            Ok(())
+        }
+        RepositoryAction::Deleted => {
+            tracing::info!("Repository deleted: {}", current);
+            db.mark_repository_deleted(&current).await
+        }
+        RepositoryAction::Forked => {
+            let Some(upstream) = event.upstream.as_deref() else {
+                return Err(crate::RsrError::Platform("Fork event is missing its upstream".to_string()));
+            };
+            let upstream: RepoRef = format!("{}:{}", platform, upstream).parse()?;
+
+            tracing::info!("Repository forked: {} -> {}", upstream, current);
+            db.docs.register_repository(&current, None).await?;
+            let fork_key = db.graphs.register_repository(platform, &current.owner, &current.repo).await?;
+            let upstream_key = db.graphs.register_repository(platform, &upstream.owner, &upstream.repo).await?;
+            db.graphs.link_fork(&fork_key, &upstream_key, UpstreamKind::Fork).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bearer(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn admin_token_must_match_and_not_be_empty() {
+        assert!(bearer_matches(&bearer("Bearer s3cret"), "s3cret"));
+        assert!(!bearer_matches(&bearer("Bearer s3cre"), "s3cret"));
+        assert!(!bearer_matches(&bearer("s3cret"), "s3cret"));
+        assert!(!bearer_matches(&bearer("Bearer "), "s3cret"));
+        assert!(!bearer_matches(&HeaderMap::new(), "s3cret"));
+    }
+}
@@ -0,0 +1,253 @@
+//! End-to-end latency SLO
+//!
+//! Measures how long a webhook takes to turn into feedback: from the moment
+//! it is received to the moment its scan's result is delivered (a review or
+//! comment on a pull request, a pull request that passed the gate, or badges
+//! published for a push). A scan is on time if that takes at most
+//! `RSR_SLO_LATENCY_SECS` (default 120); the objective is for
+//! `RSR_SLO_TARGET` (default 0.95) of scans to be on time.
+//!
+//! The burn rate of a window is how fast late scans spend the error budget:
+//! at 1 the budget lasts exactly as long as the objective allows, at 10 it is
+//! gone in a tenth of that. Latencies are kept per process, in memory, for
+//! the longest window. Replayed webhooks aren't measured, since their
+//! latency is mostly the time they spent archived.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+/// Upper bounds of the latency histogram buckets, in seconds
+pub const LATENCY_BUCKETS: [f64; 9] = [5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0];
+
+/// Windows burn rates are computed over, shortest first
+pub const BURN_WINDOWS: [(&str, i64); 3] = [("5m", 5 * 60), ("1h", 60 * 60), ("6h", 6 * 60 * 60)];
+
+/// Most latencies kept for burn rates; older ones are dropped first
+const MAX_SAMPLES: usize = 100_000;
+
+static OBJECTIVE: Lazy<SloObjective> = Lazy::new(SloObjective::from_env);
+
+static TRACKER: Lazy<Mutex<LatencyTracker>> = Lazy::new(Default::default);
+
+/// What the scan was for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanTrigger {
+    PullRequest,
+    Push,
+}
+
+impl ScanTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PullRequest => "pull_request",
+            Self::Push => "push",
+        }
+    }
+}
+
+/// Latency objective scans are held to
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SloObjective {
+    /// Longest an on-time scan may take
+    pub latency_secs: f64,
+    /// Share of scans that must be on time
+    pub target: f64,
+}
+
+impl Default for SloObjective {
+    fn default() -> Self {
+        Self {
+            latency_secs: 120.0,
+            target: 0.95,
+        }
+    }
+}
+
+impl SloObjective {
+    /// Objective from `RSR_SLO_LATENCY_SECS` and `RSR_SLO_TARGET`. Values
+    /// that don't parse, or a target outside (0, 1), keep the default.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let latency_secs = std::env::var("RSR_SLO_LATENCY_SECS")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|secs| *secs > 0.0)
+            .unwrap_or(default.latency_secs);
+        let target = std::env::var("RSR_SLO_TARGET")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|target| *target > 0.0 && *target < 1.0)
+            .unwrap_or(default.target);
+
+        Self { latency_secs, target }
+    }
+
+    /// Share of scans allowed to be late
+    pub fn error_budget(&self) -> f64 {
+        1.0 - self.target
+    }
+}
+
+/// Cumulative latency histogram for one trigger
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencyTotals {
+    /// Scans at or under each of [`LATENCY_BUCKETS`]
+    pub buckets: [u64; LATENCY_BUCKETS.len()],
+    pub count: u64,
+    pub sum_secs: f64,
+    /// Scans over the objective
+    pub late: u64,
+}
+
+/// Scans and burn rate over one window
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowBurn {
+    pub window: &'static str,
+    pub scans: u64,
+    pub late: u64,
+    /// Late share of scans over the error budget; 0 without scans
+    pub burn_rate: f64,
+}
+
+/// Objective and how the engine is doing against it
+#[derive(Debug, Clone, Serialize)]
+pub struct SloSnapshot {
+    pub objective: SloObjective,
+    /// Whether the longest window is within the objective
+    pub met: bool,
+    pub windows: Vec<WindowBurn>,
+    pub totals: BTreeMap<ScanTrigger, LatencyTotals>,
+}
+
+#[derive(Default)]
+struct LatencyTracker {
+    /// When each scan finished and whether it was late, oldest first
+    recent: VecDeque<(chrono::DateTime<chrono::Utc>, bool)>,
+    totals: BTreeMap<ScanTrigger, LatencyTotals>,
+}
+
+/// Record a scan whose result was just delivered for a webhook received at
+/// `received_at`
+pub fn record(trigger: ScanTrigger, received_at: chrono::DateTime<chrono::Utc>) {
+    let now = chrono::Utc::now();
+    let latency_secs = ((now - received_at).num_milliseconds() as f64 / 1000.0).max(0.0);
+    let late = latency_secs > OBJECTIVE.latency_secs;
+    if late {
+        tracing::warn!(
+            "{} scan took {:.1}s from webhook to result, over the {}s objective",
+            trigger.as_str(),
+            latency_secs,
+            OBJECTIVE.latency_secs
+        );
+    }
+
+    let mut tracker = TRACKER.lock().expect("SLO tracker lock poisoned");
+    let totals = tracker.totals.entry(trigger).or_default();
+    for (bucket, bound) in totals.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+        if latency_secs <= bound {
+            *bucket += 1;
+        }
+    }
+    totals.count += 1;
+    totals.sum_secs += latency_secs;
+    totals.late += u64::from(late);
+
+    let longest = BURN_WINDOWS[BURN_WINDOWS.len() - 1].1;
+    let horizon = now - chrono::Duration::seconds(longest);
+    while tracker.recent.len() >= MAX_SAMPLES || tracker.recent.front().is_some_and(|(at, _)| *at < horizon) {
+        tracker.recent.pop_front();
+    }
+    tracker.recent.push_back((now, late));
+}
+
+/// Current objective, burn rates and latency totals
+pub fn snapshot() -> SloSnapshot {
+    let objective = *OBJECTIVE;
+    let now = chrono::Utc::now();
+    let tracker = TRACKER.lock().expect("SLO tracker lock poisoned");
+
+    let windows: Vec<WindowBurn> = BURN_WINDOWS
+        .iter()
+        .map(|(window, secs)| {
+            let since = now - chrono::Duration::seconds(*secs);
+            let (scans, late) = tracker
+                .recent
+                .iter()
+                .rev()
+                .take_while(|(at, _)| *at >= since)
+                .fold((0u64, 0u64), |(scans, late), (_, was_late)| (scans + 1, late + u64::from(*was_late)));
+            let burn_rate = if scans == 0 {
+                0.0
+            } else {
+                (late as f64 / scans as f64) / objective.error_budget()
+            };
+            WindowBurn {
+                window,
+                scans,
+                late,
+                burn_rate,
+            }
+        })
+        .collect();
+    let met = !windows.last().is_some_and(|longest| longest.burn_rate > 1.0);
+
+    SloSnapshot {
+        objective,
+        met,
+        windows,
+        totals: tracker.totals.clone(),
+    }
+}
+
+/// The snapshot in Prometheus text format
+pub fn render_metrics(snapshot: &SloSnapshot) -> String {
+    let mut metrics = format!(
+        "\n# HELP rsr_slo_latency_objective_seconds Longest an on-time scan may take, webhook to result\n\
+         # TYPE rsr_slo_latency_objective_seconds gauge\n\
+         rsr_slo_latency_objective_seconds {}\n\
+         \n# HELP rsr_slo_target Share of scans that must be on time\n\
+         # TYPE rsr_slo_target gauge\n\
+         rsr_slo_target {}\n\
+         \n# HELP rsr_slo_burn_rate Rate the latency error budget is being spent (1 spends it exactly)\n\
+         # TYPE rsr_slo_burn_rate gauge\n",
+        snapshot.objective.latency_secs, snapshot.objective.target
+    );
+    for window in &snapshot.windows {
+        metrics.push_str(&format!("rsr_slo_burn_rate{{window=\"{}\"}} {}\n", window.window, window.burn_rate));
+    }
+
+    if snapshot.totals.is_empty() {
+        return metrics;
+    }
+    metrics.push_str(
+        "\n# HELP rsr_scan_latency_seconds Time from receiving a webhook to delivering its scan's result\n\
+         # TYPE rsr_scan_latency_seconds histogram\n",
+    );
+    for (trigger, totals) in &snapshot.totals {
+        let trigger = trigger.as_str();
+        for (count, bound) in totals.buckets.iter().zip(LATENCY_BUCKETS) {
+            metrics.push_str(&format!(
+                "rsr_scan_latency_seconds_bucket{{trigger=\"{}\",le=\"{}\"}} {}\n",
+                trigger, bound, count
+            ));
+        }
+        metrics.push_str(&format!(
+            "rsr_scan_latency_seconds_bucket{{trigger=\"{}\",le=\"+Inf\"}} {}\n\
+             rsr_scan_latency_seconds_sum{{trigger=\"{}\"}} {}\n\
+             rsr_scan_latency_seconds_count{{trigger=\"{}\"}} {}\n",
+            trigger, totals.count, trigger, totals.sum_secs, trigger, totals.count
+        ));
+    }
+
+    metrics.push_str(
+        "\n# HELP rsr_slo_late_scans_total Scans that took longer than the latency objective\n\
+         # TYPE rsr_slo_late_scans_total counter\n",
+    );
+    for (trigger, totals) in &snapshot.totals {
+        metrics.push_str(&format!("rsr_slo_late_scans_total{{trigger=\"{}\"}} {}\n", trigger.as_str(), totals.late));
+    }
+    metrics
+}
@@ -0,0 +1,53 @@
+//! Worker-count scaling policy
+//!
+//! Scaling is driven by backlog age rather than depth: a deep queue of quick
+//! jobs is fine, while a shallow queue whose head has waited minutes is not.
+
+use crate::db::queue::QueuePressure;
+use serde::{Deserialize, Serialize};
+
+/// Bounds and thresholds for dynamic worker adjustment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScalingPolicy {
+    pub min_workers: usize,
+    pub max_workers: usize,
+    /// Add workers when the oldest job has waited longer than this
+    pub scale_up_age_secs: u64,
+    /// Remove a worker when the oldest job has waited less than this
+    pub scale_down_age_secs: u64,
+    /// How often the backlog is sampled
+    pub evaluate_interval_secs: u64,
+}
+
+impl Default for ScalingPolicy {
+    fn default() -> Self {
+        Self {
+            min_workers: 1,
+            max_workers: 8,
+            scale_up_age_secs: 30,
+            scale_down_age_secs: 5,
+            evaluate_interval_secs: 10,
+        }
+    }
+}
+
+impl ScalingPolicy {
+    /// Clamp a worker count into the configured range
+    pub fn clamp(&self, workers: usize) -> usize {
+        workers.clamp(self.min_workers, self.max_workers.max(self.min_workers))
+    }
+
+    /// Worker count to move towards given the current backlog.
+    ///
+    /// Scales up by half again (at least one) so a sustained backlog converges
+    /// quickly, and down one at a time to avoid flapping.
+    pub fn desired_workers(&self, current: usize, pressure: &QueuePressure) -> usize {
+        let desired = match pressure.oldest_age_secs {
+            _ if pressure.depth == 0 => self.min_workers,
+            Some(age) if age > self.scale_up_age_secs => current + (current / 2).max(1),
+            Some(age) if age < self.scale_down_age_secs => current.saturating_sub(1),
+            _ => current,
+        };
+        self.clamp(desired)
+    }
+}
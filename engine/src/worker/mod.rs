@@ -0,0 +1,390 @@
+//! Background job workers
+//!
+//! A `WorkerPool` consumes one cache queue with a variable number of workers.
+//! An autoscaler samples queue pressure and adjusts the worker count within
+//! the configured bounds; operators can pin the count via the admin API.
+//! A paused pool runs no workers, leaving jobs on the queue until resumed.
+//!
+//! Jobs are acknowledged once handled. Failed jobs, and jobs held by a worker
+//! that died, are retried until the delivery policy dead-letters them; a job
+//! failing with an error that can't succeed on retry (see
+//! [`RsrError::is_retryable`](crate::RsrError::is_retryable)) is
+//! dead-lettered straight away.
+//!
+//! Each handler declares the job type and the newest payload schema it
+//! understands. A job in a newer schema, queued by an upgraded instance
+//! during a rolling upgrade, is put back for an upgraded worker rather than
+//! failed; one of another type is failed outright.
+
+pub mod autoscale;
+
+pub use autoscale::ScalingPolicy;
+
+use crate::config::ConfigStore;
+use crate::db::queue::{Compatibility, DeliveryPolicy, JobOutcome, QueuePressure, MAX_DEFERRALS};
+use crate::db::DatabasePool;
+use crate::Result;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+/// How long a worker blocks on an empty queue before rechecking its stop flag
+const DEQUEUE_TIMEOUT_SECS: u64 = 5;
+
+/// Jobs taken per wake-up unless configured otherwise
+const DEFAULT_BATCH_SIZE: usize = 10;
+
+/// Processes jobs taken off the queue
+#[async_trait::async_trait]
+pub trait JobHandler: Send + Sync {
+    /// Job type handled, as recorded on the queue envelope
+    fn kind(&self) -> &'static str;
+    /// Newest payload schema version handled
+    fn schema_version(&self) -> u32;
+    async fn handle(&self, job: String) -> Result<()>;
+}
+
+struct Worker {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+/// Snapshot of the pool for metrics and the control endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerPoolStatus {
+    pub queue: String,
+    pub workers: usize,
+    pub desired: usize,
+    pub min_workers: usize,
+    pub max_workers: usize,
+    /// Set when an operator has pinned the worker count
+    pub manual_override: Option<usize>,
+    /// Workers are stopped and jobs left queued
+    pub paused: bool,
+    pub pressure: QueuePressure,
+}
+
+/// Autoscaling pool of queue workers
+pub struct WorkerPool {
+    queue: String,
+    db: Arc<DatabasePool>,
+    handler: Arc<dyn JobHandler>,
+    batch_size: usize,
+    policy: RwLock<ScalingPolicy>,
+    delivery: DeliveryPolicy,
+    config: Option<Arc<ConfigStore>>,
+    workers: Mutex<Vec<Worker>>,
+    desired: Mutex<usize>,
+    manual_override: Mutex<Option<usize>>,
+    paused: AtomicBool,
+    pressure: RwLock<QueuePressure>,
+}
+
+impl WorkerPool {
+    pub fn new(queue: impl Into<String>, db: Arc<DatabasePool>, handler: Arc<dyn JobHandler>) -> Self {
+        let queue = queue.into();
+        Self {
+            pressure: RwLock::new(QueuePressure {
+                queue: queue.clone(),
+                ..Default::default()
+            }),
+            queue,
+            db,
+            handler,
+            batch_size: DEFAULT_BATCH_SIZE,
+            policy: RwLock::new(ScalingPolicy::default()),
+            delivery: DeliveryPolicy::default(),
+            config: None,
+            workers: Mutex::new(Vec::new()),
+            desired: Mutex::new(0),
+            manual_override: Mutex::new(None),
+            paused: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_policy(self, policy: ScalingPolicy) -> Self {
+        *self.policy.write().expect("policy lock poisoned") = policy;
+        self
+    }
+
+    /// Maximum jobs a worker takes from the queue per wake-up
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Visibility timeout and attempt limit, unless a configuration is followed
+    pub fn with_delivery_policy(mut self, delivery: DeliveryPolicy) -> Self {
+        self.delivery = delivery;
+        self
+    }
+
+    /// Follow the `workers` and `queue` sections of a reloadable configuration
+    pub fn with_config(mut self, config: Arc<ConfigStore>) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    fn policy(&self) -> ScalingPolicy {
+        match self.config {
+            Some(ref config) => config.current().workers.clone(),
+            None => self.policy.read().expect("policy lock poisoned").clone(),
+        }
+    }
+
+    fn delivery(&self) -> DeliveryPolicy {
+        match self.config {
+            Some(ref config) => config.current().queue,
+            None => self.delivery,
+        }
+    }
+
+    /// Start the minimum number of workers (none if paused) and the
+    /// autoscaler loop
+    pub fn start(self: &Arc<Self>) -> JoinHandle<()> {
+        if !self.is_paused() {
+            self.resize(self.policy().min_workers);
+        }
+
+        let pool = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                let policy = pool.policy();
+                tokio::time::sleep(Duration::from_secs(policy.evaluate_interval_secs.max(1))).await;
+
+                match pool.db.cache.requeue_expired(&pool.queue, &pool.delivery()).await {
+                    Ok(0) => {}
+                    Ok(released) => tracing::warn!("Released {} timed-out jobs on {}", released, pool.queue),
+                    Err(e) => tracing::warn!("Failed to release timed-out jobs on {}: {}", pool.queue, e),
+                }
+
+                let pressure = match pool.db.cache.queue_pressure(&pool.queue).await {
+                    Ok(pressure) => pressure,
+                    Err(e) => {
+                        tracing::warn!("Failed to sample queue {}: {}", pool.queue, e);
+                        continue;
+                    }
+                };
+
+                let current = pool.worker_count();
+                let desired = match *pool.manual_override.lock().expect("override lock poisoned") {
+                    _ if pool.is_paused() => 0,
+                    Some(pinned) => policy.clamp(pinned),
+                    None => policy.desired_workers(current, &pressure),
+                };
+                *pool.pressure.write().expect("pressure lock poisoned") = pressure;
+
+                if desired != current {
+                    tracing::info!("Scaling {} workers {} -> {}", pool.queue, current, desired);
+                }
+                pool.resize(desired);
+            }
+        })
+    }
+
+    /// Pin the worker count, or return to automatic scaling with `None`.
+    /// Pinned counts are still clamped to the configured bounds.
+    pub fn set_override(&self, workers: Option<usize>) -> usize {
+        *self.manual_override.lock().expect("override lock poisoned") = workers;
+
+        let desired = match workers {
+            // Applied on resume
+            _ if self.is_paused() => 0,
+            Some(pinned) => self.policy().clamp(pinned),
+            None => self.worker_count(),
+        };
+        self.resize(desired);
+        desired
+    }
+
+    /// Stop every worker and take no jobs until resumed. Workers finish the
+    /// batch in hand; queued jobs wait.
+    pub fn pause(&self) {
+        if !self.paused.swap(true, Ordering::Relaxed) {
+            tracing::info!("Pausing {} workers", self.queue);
+        }
+        self.resize(0);
+    }
+
+    /// Start taking jobs again, at the pinned count or the minimum until
+    /// the autoscaler next runs
+    pub fn resume(&self) {
+        if self.paused.swap(false, Ordering::Relaxed) {
+            tracing::info!("Resuming {} workers", self.queue);
+        }
+        let policy = self.policy();
+        let desired = match *self.manual_override.lock().expect("override lock poisoned") {
+            Some(pinned) => policy.clamp(pinned),
+            None => policy.min_workers.max(self.worker_count()),
+        };
+        self.resize(desired);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Number of live workers
+    pub fn worker_count(&self) -> usize {
+        let mut workers = self.workers.lock().expect("workers lock poisoned");
+        workers.retain(|w| !w.handle.is_finished());
+        workers.len()
+    }
+
+    pub fn status(&self) -> WorkerPoolStatus {
+        let policy = self.policy();
+        WorkerPoolStatus {
+            queue: self.queue.clone(),
+            workers: self.worker_count(),
+            desired: *self.desired.lock().expect("desired lock poisoned"),
+            min_workers: policy.min_workers,
+            max_workers: policy.max_workers,
+            manual_override: *self.manual_override.lock().expect("override lock poisoned"),
+            paused: self.is_paused(),
+            pressure: self.pressure.read().expect("pressure lock poisoned").clone(),
+        }
+    }
+
+    /// Spawn or stop workers until `target` are running.
+    /// Stopped workers finish their current job before exiting.
+    fn resize(&self, target: usize) {
+        *self.desired.lock().expect("desired lock poisoned") = target;
+
+        let mut workers = self.workers.lock().expect("workers lock poisoned");
+        workers.retain(|w| !w.handle.is_finished());
+
+        while workers.len() > target {
+            if let Some(worker) = workers.pop() {
+                worker.stop.store(true, Ordering::Relaxed);
+            }
+        }
+
+        while workers.len() < target {
+            let stop = Arc::new(AtomicBool::new(false));
+            let handle = tokio::spawn(run_worker(
+                self.queue.clone(),
+                Arc::clone(&self.db),
+                Arc::clone(&self.handler),
+                self.config.clone(),
+                self.delivery,
+                self.batch_size,
+                Arc::clone(&stop),
+            ));
+            workers.push(Worker { stop, handle });
+        }
+    }
+}
+
+async fn run_worker(
+    queue: String,
+    db: Arc<DatabasePool>,
+    handler: Arc<dyn JobHandler>,
+    config: Option<Arc<ConfigStore>>,
+    delivery: DeliveryPolicy,
+    batch_size: usize,
+    stop: Arc<AtomicBool>,
+) {
+    let mut consumer = None;
+
+    while !stop.load(Ordering::Relaxed) {
+        let active = match consumer {
+            Some(ref mut active) => active,
+            None => match db.cache.consumer(&queue).await {
+                Ok(opened) => consumer.insert(opened),
+                Err(e) => {
+                    tracing::warn!("Failed to open consumer for {}: {}", queue, e);
+                    tokio::time::sleep(Duration::from_secs(DEQUEUE_TIMEOUT_SECS)).await;
+                    continue;
+                }
+            },
+        };
+
+        // Read per batch so a reload applies to running workers
+        let delivery = config.as_ref().map_or(delivery, |config| config.current().queue);
+
+        let jobs = match active.next_batch(batch_size, DEQUEUE_TIMEOUT_SECS, &delivery).await {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                // Reconnect on the next iteration
+                tracing::warn!("Dequeue from {} failed: {}", queue, e);
+                consumer = None;
+                tokio::time::sleep(Duration::from_secs(DEQUEUE_TIMEOUT_SECS)).await;
+                continue;
+            }
+        };
+
+        let mut deferred = false;
+        for claimed in jobs {
+            let job = &claimed.job;
+            let settled = match job.compatibility(handler.kind(), handler.schema_version()) {
+                Compatibility::Newer if job.deferrals < MAX_DEFERRALS => {
+                    tracing::debug!(
+                        "Deferring job {} on {}: schema {} is newer than {}",
+                        job.id,
+                        queue,
+                        job.schema_version,
+                        handler.schema_version()
+                    );
+                    deferred = true;
+                    db.cache.defer_job(&queue, &claimed).await.map(Some)
+                }
+                Compatibility::Newer => {
+                    let error = format!(
+                        "{} schema {} is newer than this worker handles ({}) and no upgraded worker took it",
+                        job.kind,
+                        job.schema_version,
+                        handler.schema_version()
+                    );
+                    tracing::error!("Job {} on {} failed: {}", job.id, queue, error);
+                    db.cache.fail_job(&queue, &claimed, &error, &delivery).await.map(Some)
+                }
+                Compatibility::OtherKind => {
+                    let error = format!("{} job on a queue handling {} jobs", job.kind, handler.kind());
+                    tracing::error!("Job {} on {} failed: {}", job.id, queue, error);
+                    db.cache.fail_job(&queue, &claimed, &error, &delivery).await.map(Some)
+                }
+                Compatibility::Compatible => {
+                    let span = tracing::info_span!(
+                        "job",
+                        id = %job.id,
+                        kind = %job.kind,
+                        trace_id = job.trace.as_ref().map(|trace| trace.trace_id()),
+                    );
+                    match handler.handle(job.payload.clone()).instrument(span).await {
+                        Ok(()) => db.cache.ack_job(&queue, &claimed).await.map(|_| None),
+                        Err(e) => {
+                            tracing::error!("Job {} on {} failed: {}", job.id, queue, e);
+                            // A fatal error would fail every redelivery the same way
+                            let delivery = if e.is_retryable() {
+                                delivery
+                            } else {
+                                DeliveryPolicy {
+                                    max_attempts: job.attempts + 1,
+                                    ..delivery
+                                }
+                            };
+                            db.cache.fail_job(&queue, &claimed, &e.to_string(), &delivery).await.map(Some)
+                        }
+                    }
+                }
+            };
+
+            match settled {
+                Ok(Some(JobOutcome::Reclaimed)) => {
+                    tracing::warn!("Job {} on {} was redelivered before it finished", claimed.job.id, queue);
+                }
+                Ok(_) => {}
+                // The lease will expire and the job be redelivered
+                Err(e) => tracing::warn!("Failed to settle job {} on {}: {}", claimed.job.id, queue, e),
+            }
+        }
+
+        // Don't spin on jobs only an upgraded worker can take
+        if deferred {
+            tokio::time::sleep(Duration::from_secs(DEQUEUE_TIMEOUT_SECS)).await;
+        }
+    }
+}
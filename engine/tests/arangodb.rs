@@ -0,0 +1,353 @@
+//! Integration tests for the ArangoDB graph layer
+//!
+//! These need a running ArangoDB and are skipped unless
+//! `RSR_ARANGODB_TEST_URL` is set. `just test-arangodb` starts a throwaway
+//! container and runs them. Each test works in a database of its own.
+
+#![cfg(feature = "graphs-arangodb")]
+
+use rsr_engine::config::{PolicyConfig, TierPolicy};
+use rsr_engine::CertificationTier;
+use rsr_engine::db::graphs::{
+    registry_package_key, repository_key, ArangoPool, Dependency, GraphStore, ImpactStep, UpstreamKind, Vulnerability,
+};
+
+/// Connect to a fresh, migrated database, or `None` to skip the test
+async fn graphs(test: &str) -> Option<ArangoPool> {
+    let Ok(url) = std::env::var("RSR_ARANGODB_TEST_URL") else {
+        eprintln!("RSR_ARANGODB_TEST_URL is not set, skipping {}", test);
+        return None;
+    };
+    let username = std::env::var("RSR_ARANGODB_TEST_USER").unwrap_or_else(|_| "root".to_string());
+    let password = std::env::var("RSR_ARANGODB_TEST_PASS").unwrap_or_default();
+    let database = format!("rsr_test_{}_{}", test, chrono::Utc::now().timestamp_micros());
+
+    let pool = ArangoPool::connect(&url, &database, &username, &password)
+        .await
+        .expect("ArangoDB should accept the test credentials");
+    pool.migrate().await.expect("migrations should apply");
+    Some(pool)
+}
+
+/// `app` uses the `lib` crate, which is developed in the `lib` repository
+/// and uses `serde`
+async fn two_level_graph(pool: &ArangoPool) -> (String, String) {
+    let app = pool.register_repository("github", "acme", "app").await.unwrap();
+    let lib = pool.register_repository("github", "acme", "lib").await.unwrap();
+    pool.link_package_repository("crates", "lib", &lib).await.unwrap();
+    pool.add_dependency(&app, "crates", "lib", "0.3.1").await.unwrap();
+    pool.add_dependency(&lib, "crates", "serde", "1.0.200").await.unwrap();
+    (app, lib)
+}
+
+#[tokio::test]
+async fn migrations_can_run_again() {
+    let Some(pool) = graphs("migrations").await else {
+        return;
+    };
+
+    pool.migrate().await.expect("a second migration run should be a no-op");
+    pool.ping().await.unwrap();
+}
+
+#[tokio::test]
+async fn dependencies_are_followed_through_their_repositories() {
+    let Some(pool) = graphs("dependencies").await else {
+        return;
+    };
+    let (app, _) = two_level_graph(&pool).await;
+
+    let dependencies = pool.get_dependencies(&app).await.unwrap();
+    let found: Vec<_> = dependencies
+        .iter()
+        .map(|dep| (dep.registry.as_str(), dep.name.as_str(), dep.version.as_str(), dep.depth, dep.direct))
+        .collect();
+    assert_eq!(
+        found,
+        vec![("crates", "lib", "0.3.1", 1, true), ("crates", "serde", "1.0.200", 2, false)]
+    );
+    assert_eq!(pool.get_dependency_depth(&app).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn adding_a_dependency_again_updates_its_version() {
+    let Some(pool) = graphs("versions").await else {
+        return;
+    };
+    let (app, _) = two_level_graph(&pool).await;
+
+    pool.add_dependency(&app, "crates", "lib", "0.4.0").await.unwrap();
+
+    let dependencies = pool.get_dependencies(&app).await.unwrap();
+    let direct: Vec<_> = dependencies.iter().filter(|dep| dep.direct).collect();
+    assert_eq!(direct.len(), 1);
+    assert_eq!(direct[0].version, "0.4.0");
+}
+
+fn direct(registry: &str, name: &str, version: &str) -> Dependency {
+    Dependency {
+        registry: registry.to_string(),
+        name: name.to_string(),
+        version: version.to_string(),
+        depth: 1,
+        direct: true,
+    }
+}
+
+#[tokio::test]
+async fn snapshots_replace_the_direct_dependencies() {
+    let Some(pool) = graphs("snapshots").await else {
+        return;
+    };
+    let app = pool.register_repository("github", "acme", "app").await.unwrap();
+
+    let first = pool
+        .upsert_dependency_snapshot(&app, vec![direct("crates", "serde", "1.0.200"), direct("crates", "tokio", "1.37.0")])
+        .await
+        .unwrap();
+    assert_eq!((first.version, first.added, first.updated, first.removed), (1, 2, 0, 0));
+
+    let mut transitive = direct("crates", "mio", "0.8.11");
+    transitive.direct = false;
+    let second = pool
+        .upsert_dependency_snapshot(
+            &app,
+            vec![direct("crates", "serde", "1.0.201"), direct("npm", "@acme/ui", "3.0.0"), transitive],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        (second.version, second.added, second.updated, second.removed, second.unchanged),
+        (2, 1, 1, 1, 0)
+    );
+
+    let found: Vec<_> = pool
+        .get_dependencies(&app)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|dep| (dep.registry, dep.name, dep.version))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("crates".to_string(), "serde".to_string(), "1.0.201".to_string()),
+            ("npm".to_string(), "@acme/ui".to_string(), "3.0.0".to_string()),
+        ]
+    );
+
+    let unregistered = repository_key("github", "acme", "missing");
+    assert!(pool.upsert_dependency_snapshot(&unregistered, vec![direct("crates", "serde", "1.0.0")]).await.is_err());
+    assert!(pool.get_dependencies(&unregistered).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn dependents_include_transitive_users() {
+    let Some(pool) = graphs("dependents").await else {
+        return;
+    };
+    let (app, lib) = two_level_graph(&pool).await;
+    let tool = pool.register_repository("gitlab", "acme", "tool").await.unwrap();
+    pool.link_package_repository("crates", "app", &app).await.unwrap();
+    pool.add_dependency(&tool, "crates", "app", "2.0.0").await.unwrap();
+
+    assert_eq!(pool.get_dependents(&lib).await.unwrap(), vec![app.clone(), tool.clone()]);
+    assert_eq!(pool.get_dependents(&app).await.unwrap(), vec![tool.clone()]);
+    assert!(pool.get_dependents(&tool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn vulnerabilities_reach_every_affected_repository() {
+    let Some(pool) = graphs("vulnerabilities").await else {
+        return;
+    };
+    let (app, lib) = two_level_graph(&pool).await;
+    let vulnerability = Vulnerability {
+        id: "RUSTSEC-2099-0001".to_string(),
+        aliases: Vec::new(),
+        severity: "high".to_string(),
+        affected_versions: vec!["1.0.200".to_string()],
+        patched_versions: vec!["1.0.201".to_string()],
+    };
+    pool.add_vulnerability(&vulnerability, &registry_package_key("crates", "serde"))
+        .await
+        .unwrap();
+
+    let mut expected = vec![app, lib];
+    expected.sort();
+    assert_eq!(pool.get_affected_repos(&vulnerability.id).await.unwrap(), expected);
+    assert!(pool.get_affected_repos("RUSTSEC-2099-9999").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn impact_is_explained_by_the_dependency_chain() {
+    let Some(pool) = graphs("impact").await else {
+        return;
+    };
+    let (app, lib) = two_level_graph(&pool).await;
+    let vulnerability = Vulnerability {
+        id: "RUSTSEC-2099-0003".to_string(),
+        aliases: vec!["CVE-2099-0003".to_string()],
+        severity: "critical".to_string(),
+        affected_versions: vec!["1.0.200".to_string()],
+        patched_versions: vec!["1.0.201".to_string()],
+    };
+    pool.add_vulnerability(&vulnerability, &registry_package_key("crates", "serde"))
+        .await
+        .unwrap();
+
+    let paths = pool.explain_impact("CVE-2099-0003", &app).await.unwrap();
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].vulnerability, vulnerability.id);
+    assert_eq!(paths[0].depth, 2);
+    assert_eq!(
+        paths[0].packages,
+        vec![
+            ImpactStep {
+                registry: "crates".to_string(),
+                name: "lib".to_string(),
+                version: "0.3.1".to_string(),
+                depth: 1,
+                dependent: app.clone(),
+            },
+            ImpactStep {
+                registry: "crates".to_string(),
+                name: "serde".to_string(),
+                version: "1.0.200".to_string(),
+                depth: 2,
+                dependent: lib.clone(),
+            },
+        ]
+    );
+
+    let direct = pool.explain_impact(&vulnerability.id, &lib).await.unwrap();
+    assert_eq!(direct.len(), 1);
+    assert_eq!(direct[0].depth, 1);
+    assert!(pool.explain_impact("RUSTSEC-2099-9999", &app).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn advisories_merge_into_vulnerabilities_sharing_an_id() {
+    let Some(pool) = graphs("advisories").await else {
+        return;
+    };
+    let (app, lib) = two_level_graph(&pool).await;
+    let updated_at = "2099-01-02T03:04:05Z".parse().unwrap();
+    let serde = vec![("crates".to_string(), "serde".to_string())];
+
+    let rustsec = Vulnerability {
+        id: "RUSTSEC-2099-0002".to_string(),
+        aliases: vec!["CVE-2099-0002".to_string()],
+        severity: "high".to_string(),
+        affected_versions: vec!["1.0.200".to_string()],
+        patched_versions: vec!["1.0.201".to_string()],
+    };
+    let key = pool.upsert_advisory(&rustsec, "osv", updated_at, &serde).await.unwrap();
+    assert_eq!(key, rustsec.id);
+
+    let ghsa = Vulnerability {
+        id: "GHSA-aaaa-bbbb-cccc".to_string(),
+        aliases: vec!["CVE-2099-0002".to_string()],
+        severity: "medium".to_string(),
+        affected_versions: vec!["< 1.0.201".to_string()],
+        patched_versions: vec!["1.0.201".to_string()],
+    };
+    let key = pool.upsert_advisory(&ghsa, "ghsa", updated_at, &serde).await.unwrap();
+    assert_eq!(key, rustsec.id, "the shared CVE should merge the advisories");
+
+    let mut expected = vec![app, lib];
+    expected.sort();
+    assert_eq!(pool.get_affected_repos(&rustsec.id).await.unwrap(), expected);
+    assert!(pool.get_affected_repos(&ghsa.id).await.unwrap().is_empty());
+    assert_eq!(pool.latest_advisory_update("ghsa").await.unwrap(), Some(updated_at));
+    assert_eq!(pool.latest_advisory_update("nvd").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn fork_networks_are_found_from_any_member() {
+    let Some(pool) = graphs("forks").await else {
+        return;
+    };
+    let upstream = pool.register_repository("github", "acme", "app").await.unwrap();
+    let fork = pool.register_repository("github", "alice", "app").await.unwrap();
+    let fork_of_fork = pool.register_repository("github", "bob", "app").await.unwrap();
+    let mirror = pool.register_repository("gitlab", "acme", "app").await.unwrap();
+    pool.link_fork(&fork, &upstream, UpstreamKind::Fork).await.unwrap();
+    pool.link_fork(&fork_of_fork, &fork, UpstreamKind::Fork).await.unwrap();
+    pool.link_fork(&mirror, &fork, UpstreamKind::Mirror).await.unwrap();
+    // Linking again moves the mirror under the upstream
+    pool.link_fork(&mirror, &upstream, UpstreamKind::Mirror).await.unwrap();
+
+    let upstreams = pool.get_upstreams(&fork_of_fork).await.unwrap();
+    let upstreams: Vec<_> = upstreams.iter().map(|repo| repo.owner.as_str()).collect();
+    assert_eq!(upstreams, vec!["alice", "acme"]);
+
+    let network = pool.get_fork_network(&fork_of_fork).await.unwrap();
+    let mut members: Vec<_> = network
+        .iter()
+        .map(|member| (member.key.clone(), member.upstream.clone(), member.kind, member.depth))
+        .collect();
+    members.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut expected = vec![
+        (upstream.clone(), None, None, 0),
+        (fork.clone(), Some(upstream.clone()), Some(UpstreamKind::Fork), 1),
+        (fork_of_fork, Some(fork), Some(UpstreamKind::Fork), 2),
+        (mirror, Some(upstream.clone()), Some(UpstreamKind::Mirror), 1),
+    ];
+    expected.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(members, expected);
+    assert_eq!(network[0].key, upstream, "the root comes first");
+    assert!(pool.get_fork_network("github__nobody_nothing").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn organization_constraints_carry_down_through_teams() {
+    let Some(pool) = graphs("inheritance").await else {
+        return;
+    };
+    let app = pool.register_repository("github", "acme", "app").await.unwrap();
+    let ancestry = vec![
+        "github:acme#payments".to_string(),
+        "github:acme".to_string(),
+        "enterprise:acme".to_string(),
+    ];
+    pool.register_hierarchy(&app, &ancestry).await.unwrap();
+    assert_eq!(pool.get_ancestry(&app).await.unwrap(), ancestry);
+
+    let mut policies = PolicyConfig::default();
+    policies.tenants.insert(
+        "github:acme".to_string(),
+        TierPolicy {
+            minimum_tier: Some(CertificationTier::Silver),
+            required_checks: vec!["bronze.license".to_string()],
+            ..TierPolicy::default()
+        },
+    );
+    policies.tenants.insert(
+        "github:acme#payments".to_string(),
+        TierPolicy {
+            disabled_checks: vec!["bronze.license".to_string(), "gold.sbom".to_string()],
+            ..TierPolicy::default()
+        },
+    );
+
+    let effective = pool.effective_policy(&app, &policies).await.unwrap();
+    assert_eq!(effective.source, "github:acme#payments");
+    assert_eq!(effective.chain, vec!["github:acme#payments", "github:acme", "default"]);
+    assert_eq!(effective.policy.target_tier, CertificationTier::Silver);
+    assert_eq!(effective.policy.disabled_checks, vec!["gold.sbom".to_string()]);
+}
+
+#[tokio::test]
+async fn dependency_cycles_terminate() {
+    let Some(pool) = graphs("cycles").await else {
+        return;
+    };
+    let (app, lib) = two_level_graph(&pool).await;
+    pool.link_package_repository("crates", "app", &app).await.unwrap();
+    pool.add_dependency(&lib, "crates", "app", "2.0.0").await.unwrap();
+
+    assert_eq!(pool.get_dependency_depth(&app).await.unwrap(), 2);
+    assert_eq!(pool.get_dependents(&lib).await.unwrap(), vec![app]);
+    assert_eq!(repository_key("github", "acme", "lib"), lib);
+}
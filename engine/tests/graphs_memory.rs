@@ -0,0 +1,145 @@
+//! Tests for the in-process graph store
+//!
+//! Run with `cargo test -p rsr-engine --features graphs-memory --test graphs_memory`.
+
+#![cfg(feature = "graphs-memory")]
+
+use rsr_engine::db::graphs::{registry_package_key, repository_key, GraphStore, ImpactStep, UpstreamKind, Vulnerability};
+use rsr_engine::db::graphs_memory::MemoryGraph;
+use rsr_engine::db::DbError;
+use rsr_engine::{RepoRef, RsrError};
+
+/// `app` uses the `lib` crate, which is developed in the `lib` repository
+/// and uses `serde`
+async fn two_level_graph(graph: &MemoryGraph) -> (String, String) {
+    let app = graph.register_repository("github", "acme", "app").await.unwrap();
+    let lib = graph.register_repository("github", "acme", "lib").await.unwrap();
+    graph.link_package_repository("crates", "lib", &lib).await.unwrap();
+    graph.add_dependency(&app, "crates", "lib", "0.3.1").await.unwrap();
+    graph.add_dependency(&lib, "crates", "serde", "1.0.200").await.unwrap();
+    (app, lib)
+}
+
+fn serde_vulnerability() -> Vulnerability {
+    Vulnerability {
+        id: "RUSTSEC-2099-0003".to_string(),
+        aliases: vec!["CVE-2099-0003".to_string()],
+        severity: "critical".to_string(),
+        affected_versions: vec!["1.0.200".to_string()],
+        patched_versions: vec!["1.0.201".to_string()],
+    }
+}
+
+#[tokio::test]
+async fn dependencies_and_impact_follow_the_repositories_packages_are_developed_in() {
+    let graph = MemoryGraph::new();
+    let (app, lib) = two_level_graph(&graph).await;
+
+    let found: Vec<_> = graph
+        .get_dependencies(&app)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|dep| (dep.name, dep.version, dep.depth, dep.direct))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("lib".to_string(), "0.3.1".to_string(), 1, true),
+            ("serde".to_string(), "1.0.200".to_string(), 2, false),
+        ]
+    );
+    assert_eq!(graph.get_dependency_depth(&app).await.unwrap(), 2);
+    assert_eq!(graph.get_dependents(&lib).await.unwrap(), vec![app.clone()]);
+
+    let vulnerability = serde_vulnerability();
+    graph
+        .add_vulnerability(&vulnerability, &registry_package_key("crates", "serde"))
+        .await
+        .unwrap();
+    let mut affected = vec![app.clone(), lib.clone()];
+    affected.sort();
+    assert_eq!(graph.get_affected_repos(&vulnerability.id).await.unwrap(), affected);
+
+    let paths = graph.explain_impact("CVE-2099-0003", &app).await.unwrap();
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].vulnerability, vulnerability.id);
+    assert_eq!(
+        paths[0].packages.last(),
+        Some(&ImpactStep {
+            registry: "crates".to_string(),
+            name: "serde".to_string(),
+            version: "1.0.200".to_string(),
+            depth: 2,
+            dependent: lib,
+        })
+    );
+}
+
+#[tokio::test]
+async fn dependency_cycles_terminate() {
+    let graph = MemoryGraph::new();
+    let (app, lib) = two_level_graph(&graph).await;
+    graph.link_package_repository("crates", "app", &app).await.unwrap();
+    graph.add_dependency(&lib, "crates", "app", "1.0.0").await.unwrap();
+
+    assert_eq!(graph.get_dependency_depth(&app).await.unwrap(), 2);
+    assert_eq!(graph.get_dependents(&app).await.unwrap(), vec![lib]);
+}
+
+#[tokio::test]
+async fn transfers_keep_edges_but_not_membership() {
+    let graph = MemoryGraph::new();
+    let (app, _) = two_level_graph(&graph).await;
+    let fork = graph.register_repository("github", "someone", "app").await.unwrap();
+    graph.link_fork(&fork, &app, UpstreamKind::Fork).await.unwrap();
+    graph.register_hierarchy(&app, &["github:acme".to_string()]).await.unwrap();
+
+    let moved = graph
+        .transfer_repository(&RepoRef::new("github", "acme", "app"), &RepoRef::new("github", "acme-labs", "app"))
+        .await
+        .unwrap();
+    assert_eq!(moved, repository_key("github", "acme-labs", "app"));
+
+    assert_eq!(graph.get_dependencies(&app).await.unwrap().len(), 0);
+    assert_eq!(graph.get_dependencies(&moved).await.unwrap().len(), 2);
+    assert_eq!(graph.get_upstreams(&fork).await.unwrap(), vec![RepoRef::new("github", "acme-labs", "app")]);
+    assert!(graph.get_ancestry(&moved).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn snapshots_survive_a_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("graph.json");
+
+    let graph = MemoryGraph::open(Some(path.clone())).unwrap();
+    let (app, _) = two_level_graph(&graph).await;
+    graph
+        .register_hierarchy(&app, &["github:acme#platform".to_string(), "github:acme".to_string()])
+        .await
+        .unwrap();
+    let updated_at = "2099-01-02T03:04:05Z".parse().unwrap();
+    let serde = vec![("crates".to_string(), "serde".to_string())];
+    graph.upsert_advisory(&serde_vulnerability(), "ghsa", updated_at, &serde).await.unwrap();
+    drop(graph);
+
+    let reopened = MemoryGraph::open(Some(path)).unwrap();
+    assert_eq!(reopened.get_dependency_depth(&app).await.unwrap(), 2);
+    assert_eq!(
+        reopened.get_ancestry(&app).await.unwrap(),
+        vec!["github:acme#platform".to_string(), "github:acme".to_string()]
+    );
+    assert_eq!(reopened.latest_advisory_update("ghsa").await.unwrap(), Some(updated_at));
+    assert_eq!(reopened.explain_impact("CVE-2099-0003", &app).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn linking_an_unknown_repository_is_a_fatal_not_found() {
+    let graph = MemoryGraph::new();
+    let err = graph
+        .link_package_repository("crates", "serde", &repository_key("github", "nobody", "nothing"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RsrError::Db(DbError::NotFound(_))));
+    assert!(!err.is_retryable());
+}
@@ -0,0 +1,69 @@
+//! Integration tests for the Postgres document store
+//!
+//! These need a running Postgres and are skipped unless
+//! `RSR_POSTGRES_TEST_URL` is set. Tests share the database, so each one
+//! works on repositories of its own.
+
+#![cfg(feature = "documents-postgres")]
+
+use chrono::{DateTime, Duration, Utc};
+use rsr_engine::db::documents::DocumentStore;
+use rsr_engine::db::postgres::PostgresStore;
+use rsr_engine::{CertificationTier, CheckResult, ComplianceStatus, RepoRef};
+
+/// Connect to the migrated test database, or `None` to skip the test
+async fn documents(test: &str) -> Option<PostgresStore> {
+    let Ok(url) = std::env::var("RSR_POSTGRES_TEST_URL") else {
+        eprintln!("RSR_POSTGRES_TEST_URL is not set, skipping {}", test);
+        return None;
+    };
+
+    let store = PostgresStore::connect(&url).await.expect("the database should accept connections");
+    store.migrate().await.expect("migrations should apply");
+    Some(store)
+}
+
+/// A repository no other test run uses
+fn repo(test: &str) -> RepoRef {
+    RepoRef::new("github", "acme", format!("{}-{}", test, Utc::now().timestamp_micros()))
+}
+
+/// Scan of `repo` taken at `timestamp`, failing the Bronze license check
+fn report(repo: RepoRef, timestamp: DateTime<Utc>) -> ComplianceStatus {
+    let license = CheckResult {
+        id: "bronze.license".to_string(),
+        name: "License".to_string(),
+        tier: CertificationTier::Bronze,
+        passed: false,
+        message: "No license".to_string(),
+        details: None,
+        findings: Vec::new(),
+    };
+    ComplianceStatus::builder().repo(repo).check(license).timestamp(timestamp).build().unwrap()
+}
+
+#[tokio::test]
+async fn reports_round_trip_and_chain() {
+    let Some(store) = documents("round_trip").await else {
+        return;
+    };
+    let app = repo("round-trip");
+    // Whole seconds, within the microseconds Postgres keeps
+    let now = DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap();
+
+    for timestamp in [now - Duration::days(1), now] {
+        store.store_compliance(&report(app.clone(), timestamp)).await.unwrap();
+    }
+    // Applying migrations again finds nothing left to do
+    assert!(store.migrate().await.unwrap().is_empty());
+
+    let latest = store.get_latest_compliance(&app.platform, &app.owner, &app.repo).await.unwrap().unwrap();
+    assert_eq!(latest.repo, app);
+    assert_eq!(latest.timestamp, now);
+    assert_eq!(latest.checks.len(), 1);
+    assert!(!latest.checks[0].passed);
+
+    let chain = store.verify_report_chain(&app).await.unwrap();
+    assert_eq!(chain.reports, 2);
+    assert!(chain.problems.is_empty(), "{:?}", chain.problems);
+}
@@ -0,0 +1,138 @@
+//! Integration tests for the SQLite document store
+//!
+//! Run with `cargo test -p rsr-engine --features documents-sqlite --test sqlite`.
+//! Each test works in a database file of its own, or in memory.
+
+#![cfg(feature = "documents-sqlite")]
+
+use chrono::{DateTime, Duration, Utc};
+use rsr_engine::db::documents::DocumentStore;
+use rsr_engine::db::retention::RetentionPolicy;
+use rsr_engine::db::sqlite::SqliteStore;
+use rsr_engine::{CertificationTier, CheckResult, ComplianceStatus, RepoRef};
+
+/// Open a fresh, migrated database in `dir`
+async fn documents(dir: &tempfile::TempDir) -> SqliteStore {
+    let path = dir.path().join("rsr.db");
+    let store = SqliteStore::open(path.to_str().unwrap()).await.expect("the database should open");
+    store.migrate().await.expect("migrations should apply");
+    store
+}
+
+fn app() -> RepoRef {
+    RepoRef::new("github", "acme", "app")
+}
+
+/// Scan of `repo` taken at `timestamp`, failing the Bronze license check
+fn report(repo: RepoRef, timestamp: DateTime<Utc>) -> ComplianceStatus {
+    let license = CheckResult {
+        id: "bronze.license".to_string(),
+        name: "License".to_string(),
+        tier: CertificationTier::Bronze,
+        passed: false,
+        message: "No license".to_string(),
+        details: None,
+        findings: Vec::new(),
+    };
+    ComplianceStatus::builder().repo(repo).check(license).timestamp(timestamp).build().unwrap()
+}
+
+/// Store scans of `repo` taken the given numbers of days ago
+async fn scans(store: &SqliteStore, repo: RepoRef, days_ago: &[i64]) -> Vec<DateTime<Utc>> {
+    // Whole seconds, as reports are read back
+    let now = DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap();
+    let mut stamps = Vec::new();
+    for days in days_ago {
+        let timestamp = now - Duration::days(*days);
+        store.store_compliance(&report(repo.clone(), timestamp)).await.unwrap();
+        stamps.push(timestamp);
+    }
+    stamps
+}
+
+/// Timestamps of a repository's live reports, newest first
+async fn live(store: &SqliteStore, repo: &RepoRef) -> Vec<DateTime<Utc>> {
+    let page = store.get_compliance_history(&repo.platform, &repo.owner, &repo.repo, 100, None).await.unwrap();
+    page.reports.iter().map(|status| status.timestamp).collect()
+}
+
+#[tokio::test]
+async fn in_memory_store_round_trips_reports() {
+    let store = SqliteStore::open("sqlite::memory:").await.expect("the database should open");
+    assert!(!store.migrate().await.unwrap().is_empty());
+    // Migrations are visible to every later query, so none are left to apply
+    assert!(store.pending_migrations().await.unwrap().is_empty());
+
+    let stamps = scans(&store, app(), &[1, 0]).await;
+
+    let latest = store.get_latest_compliance("github", "acme", "app").await.unwrap().expect("a report was stored");
+    assert_eq!(latest.repo, app());
+    assert_eq!(latest.timestamp, stamps[1]);
+    assert_eq!(latest.tier, report(app(), stamps[1]).tier);
+    assert_eq!(latest.checks.len(), 1);
+    assert_eq!(latest.checks[0].id, "bronze.license");
+    assert!(!latest.checks[0].passed);
+
+    let chain = store.verify_report_chain(&app()).await.unwrap();
+    assert_eq!(chain.reports, 2);
+    assert!(chain.problems.is_empty(), "{:?}", chain.problems);
+}
+
+#[tokio::test]
+async fn pruning_by_count_keeps_the_newest_and_badged_reports() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = documents(&dir).await;
+    let stamps = scans(&store, app(), &[40, 30, 20, 10, 0]).await;
+    store.mark_badge_issued(&app(), stamps[0]).await.unwrap();
+
+    let policy = RetentionPolicy {
+        max_reports: Some(2),
+        max_age_days: None,
+    };
+    let dry_run = store.prune_reports(&policy, true).await.unwrap();
+    assert_eq!(dry_run.tombstoned, 2);
+    assert_eq!(live(&store, &app()).await.len(), 5);
+
+    let pruned = store.prune_reports(&policy, false).await.unwrap();
+    assert_eq!(pruned.scanned, 1);
+    assert_eq!(pruned.repositories, 1);
+    assert_eq!(pruned.tombstoned, 2);
+    assert_eq!(live(&store, &app()).await, [stamps[4], stamps[3], stamps[0]]);
+}
+
+#[tokio::test]
+async fn pruning_by_age_keeps_recent_badged_and_latest_reports() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = documents(&dir).await;
+    let stamps = scans(&store, app(), &[40, 30, 20, 3, 0]).await;
+    store.mark_badge_issued(&app(), stamps[1]).await.unwrap();
+    // Only old reports, so the latest stays however old it is
+    let stale = RepoRef::new("github", "acme", "stale");
+    let stale_stamps = scans(&store, stale.clone(), &[60, 50]).await;
+
+    let policy = RetentionPolicy {
+        max_reports: None,
+        max_age_days: Some(7),
+    };
+    let pruned = store.prune_reports(&policy, false).await.unwrap();
+    assert_eq!(pruned.scanned, 2);
+    assert_eq!(pruned.repositories, 2);
+    assert_eq!(pruned.tombstoned, 3);
+    assert_eq!(live(&store, &app()).await, [stamps[4], stamps[3], stamps[1]]);
+    assert_eq!(live(&store, &stale).await, [stale_stamps[1]]);
+}
+
+#[tokio::test]
+async fn pruning_needs_a_report_outside_every_limit() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = documents(&dir).await;
+    let stamps = scans(&store, app(), &[40, 30, 3, 2, 1, 0]).await;
+
+    let policy = RetentionPolicy {
+        max_reports: Some(2),
+        max_age_days: Some(7),
+    };
+    let pruned = store.prune_reports(&policy, false).await.unwrap();
+    assert_eq!(pruned.tombstoned, 2);
+    assert_eq!(live(&store, &app()).await, [stamps[5], stamps[4], stamps[3], stamps[2]]);
+}
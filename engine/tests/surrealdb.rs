@@ -0,0 +1,218 @@
+//! Integration tests for the SurrealDB document store
+//!
+//! Built with the `surrealdb-mem` feature, these run against an embedded
+//! in-memory database; otherwise they need a running SurrealDB and are
+//! skipped unless `RSR_SURREALDB_TEST_URL` is set. `just test-surrealdb` runs
+//! them in memory. Each test works in a database of its own.
+
+#![cfg(feature = "documents-surrealdb")]
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rsr_engine::db::documents::{DocumentStore, SurrealPool};
+use rsr_engine::db::retention::RetentionPolicy;
+use rsr_engine::{CertificationTier, CheckResult, ComplianceStatus, RepoRef};
+
+/// Connect to a fresh, migrated database, or `None` to skip the test
+async fn documents(test: &str) -> Option<SurrealPool> {
+    let url = match std::env::var("RSR_SURREALDB_TEST_URL") {
+        Ok(url) => url,
+        Err(_) if cfg!(feature = "surrealdb-mem") => "mem://".to_string(),
+        Err(_) => {
+            eprintln!("RSR_SURREALDB_TEST_URL is not set, skipping {}", test);
+            return None;
+        }
+    };
+    let username = std::env::var("RSR_SURREALDB_TEST_USER").unwrap_or_else(|_| "root".to_string());
+    let password = std::env::var("RSR_SURREALDB_TEST_PASS").unwrap_or_default();
+    let database = format!("rsr_test_{}_{}", test, Utc::now().timestamp_micros());
+
+    let pool = SurrealPool::connect(&url, "rsr_test", &database, &username, &password)
+        .await
+        .expect("SurrealDB should accept the test credentials");
+    pool.migrate().await.expect("migrations should apply");
+    Some(pool)
+}
+
+fn app() -> RepoRef {
+    RepoRef::new("github", "acme", "app")
+}
+
+/// Scan `day` days into 2026 that passes the Bronze license check if `licensed`
+fn report(repo: RepoRef, day: i64, licensed: bool) -> ComplianceStatus {
+    let license = CheckResult {
+        id: "bronze.license".to_string(),
+        name: "License".to_string(),
+        tier: CertificationTier::Bronze,
+        passed: licensed,
+        message: if licensed { "MIT" } else { "No license" }.to_string(),
+        details: None,
+        findings: Vec::new(),
+    };
+    ComplianceStatus::builder()
+        .repo(repo)
+        .check(license)
+        .timestamp(day_of_2026(day))
+        .build()
+        .unwrap()
+}
+
+fn day_of_2026(day: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap() + Duration::days(day)
+}
+
+/// Store three daily scans of `app`, the middle one failing
+async fn three_scans(pool: &SurrealPool) {
+    for (day, licensed) in [(0, true), (1, false), (2, true)] {
+        pool.store_compliance(&report(app(), day, licensed)).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn migrations_can_run_again() {
+    let Some(pool) = documents("migrations").await else {
+        return;
+    };
+
+    assert!(pool.pending_migrations().await.unwrap().is_empty());
+    pool.migrate().await.expect("a second migration run should be a no-op");
+    pool.ping().await.unwrap();
+}
+
+#[tokio::test]
+async fn stored_reports_read_back_as_stored() {
+    let Some(pool) = documents("round_trip").await else {
+        return;
+    };
+    let stored = report(app(), 0, false);
+    pool.store_compliance(&stored).await.unwrap();
+
+    let latest = pool.get_latest_compliance("github", "acme", "app").await.unwrap().expect("a stored report");
+    assert_eq!(latest.repo, stored.repo);
+    assert_eq!(latest.tier, stored.tier);
+    assert_eq!(latest.timestamp, stored.timestamp);
+    assert_eq!(latest.standard, stored.standard);
+    assert_eq!(latest.checks.len(), 1);
+    assert_eq!(latest.checks[0].id, "bronze.license");
+    assert!(!latest.checks[0].passed);
+}
+
+#[tokio::test]
+async fn history_pages_run_newest_first() {
+    let Some(pool) = documents("history").await else {
+        return;
+    };
+    three_scans(&pool).await;
+
+    let first = pool.get_compliance_history("github", "acme", "app", 2, None).await.unwrap();
+    let days: Vec<_> = first.reports.iter().map(|status| status.timestamp).collect();
+    assert_eq!(days, [day_of_2026(2), day_of_2026(1)]);
+    assert_eq!(first.next, Some(day_of_2026(1)));
+
+    let second = pool.get_compliance_history("github", "acme", "app", 2, first.next).await.unwrap();
+    let days: Vec<_> = second.reports.iter().map(|status| status.timestamp).collect();
+    assert_eq!(days, [day_of_2026(0)]);
+    assert_eq!(second.next, None);
+}
+
+#[tokio::test]
+async fn as_of_finds_the_report_current_at_the_time() {
+    let Some(pool) = documents("as_of").await else {
+        return;
+    };
+    three_scans(&pool).await;
+
+    let while_unlicensed = pool.get_compliance_as_of("github", "acme", "app", day_of_2026(1) + Duration::hours(6)).await;
+    assert_eq!(while_unlicensed.unwrap().unwrap().tier, CertificationTier::None);
+    let at_scan = pool.get_compliance_as_of("github", "acme", "app", day_of_2026(2)).await;
+    assert_eq!(at_scan.unwrap().unwrap().timestamp, day_of_2026(2));
+    let before_any = pool.get_compliance_as_of("github", "acme", "app", day_of_2026(-1)).await;
+    assert!(before_any.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn reports_chain_per_repository() {
+    let Some(pool) = documents("chain").await else {
+        return;
+    };
+    three_scans(&pool).await;
+    // A repository whose name extends `app`'s keeps a chain of its own
+    pool.store_compliance(&report(RepoRef::new("github", "acme", "app-web"), 1, true)).await.unwrap();
+
+    let chain = pool.verify_report_chain(&app()).await.unwrap();
+    assert_eq!(chain.reports, 3);
+    assert_eq!(chain.unchained, 0);
+    assert!(chain.problems.is_empty(), "{:?}", chain.problems);
+    assert!(chain.head.is_some());
+
+    let sibling = pool.verify_report_chain(&RepoRef::new("github", "acme", "app-web")).await.unwrap();
+    assert_eq!(sibling.reports, 1);
+    assert!(sibling.problems.is_empty(), "{:?}", sibling.problems);
+}
+
+#[tokio::test]
+async fn transferred_reports_keep_their_chain() {
+    let Some(pool) = documents("transfer").await else {
+        return;
+    };
+    three_scans(&pool).await;
+    let before = pool.verify_report_chain(&app()).await.unwrap();
+    let moved = RepoRef::new("github", "acme-labs", "app");
+
+    pool.transfer_repository(&app(), &moved).await.unwrap();
+
+    let chain = pool.verify_report_chain(&moved).await.unwrap();
+    assert_eq!(chain.reports, 3);
+    assert!(chain.problems.is_empty(), "{:?}", chain.problems);
+    assert_eq!(chain.head, before.head);
+    assert_eq!(pool.verify_report_chain(&app()).await.unwrap().reports, 0);
+    let redirect = pool.resolve_redirect("github", "acme", "app").await.unwrap();
+    assert_eq!(redirect, Some(moved));
+}
+
+#[tokio::test]
+async fn pruning_keeps_the_newest_recent_and_badged_reports() {
+    let Some(pool) = documents("pruning").await else {
+        return;
+    };
+    let now = DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap();
+    let stamps: Vec<_> = [40, 30, 20, 3, 0].iter().map(|days| now - Duration::days(*days)).collect();
+    for (i, stamp) in stamps.iter().enumerate() {
+        let mut status = report(app(), 0, i % 2 == 0);
+        status.timestamp = *stamp;
+        pool.store_compliance(&status).await.unwrap();
+    }
+    pool.mark_badge_issued(&app(), stamps[1]).await.unwrap();
+    let live = |page: rsr_engine::db::documents::HistoryPage| -> Vec<_> {
+        page.reports.iter().map(|status| status.timestamp).collect()
+    };
+
+    // By count, the badged report outlives the two newest
+    let by_count = RetentionPolicy {
+        max_reports: Some(2),
+        max_age_days: None,
+    };
+    let dry_run = pool.prune_reports(&by_count, true).await.unwrap();
+    assert_eq!(dry_run.tombstoned, 2);
+    let pruned = pool.prune_reports(&by_count, false).await.unwrap();
+    assert_eq!(pruned.tombstoned, 2);
+    let page = pool.get_compliance_history("github", "acme", "app", 10, None).await.unwrap();
+    assert_eq!(live(page), [stamps[4], stamps[3], stamps[1]]);
+
+    // By age, the latest report is kept however old it is
+    let stale = RepoRef::new("github", "acme", "stale");
+    for days in [60, 50] {
+        let mut status = report(stale.clone(), 0, true);
+        status.timestamp = now - Duration::days(days);
+        pool.store_compliance(&status).await.unwrap();
+    }
+    let by_age = RetentionPolicy {
+        max_reports: None,
+        max_age_days: Some(7),
+    };
+    let pruned = pool.prune_reports(&by_age, false).await.unwrap();
+    assert_eq!(pruned.tombstoned, 1);
+    let page = pool.get_compliance_history("github", "acme", "stale", 10, None).await.unwrap();
+    assert_eq!(live(page), [now - Duration::days(50)]);
+    let page = pool.get_compliance_history("github", "acme", "app", 10, None).await.unwrap();
+    assert_eq!(live(page), [stamps[4], stamps[3], stamps[1]]);
+}
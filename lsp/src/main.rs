@@ -75,14 +75,14 @@ async fn shutdown(&self) -> Result<()> {
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         // Check if this is an RSR-relevant file
         let uri = params.text_document.uri;
-        if is_rsr_relevant(&uri.path()) {
+        if is_rsr_relevant(uri.path()) {
             self.run_compliance_check(&uri).await;
         }
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
         let uri = params.text_document.uri;
-        if is_rsr_relevant(&uri.path()) {
+        if is_rsr_relevant(uri.path()) {
             self.run_compliance_check(&uri).await;
         }
     }
@@ -0,0 +1,500 @@
+//! Conformance suite for `PlatformAdapter` implementations
+//!
+//! Runs the behaviour the engine relies on against any adapter, built-in or
+//! third-party:
+//! - webhook verification accepts signed deliveries and rejects tampered,
+//!   unsigned and unconfigured ones
+//! - webhook parsing produces an event and rejects malformed payloads
+//! - API failures map onto `RsrError` the way the worker expects
+//!
+//! API checks point the adapter at a local fixture server that answers every
+//! request with a scripted response, so they don't depend on URL layout.
+//!
+//! ```ignore
+//! #[tokio::test]
+//! async fn conforms() {
+//!     let payload = include_bytes!("fixtures/push.json");
+//!     let signature = sign_hmac(HmacAlgorithm::Sha256, b"fixture-secret", payload);
+//!     let delivery = Delivery::new(payload.to_vec())
+//!         .header("x-myhost-event", "push")
+//!         .header("x-myhost-signature", signature);
+//!
+//!     ConformanceSuite::new(|config| Box::new(MyHostAdapter::new(config)))
+//!         .with_secret("fixture-secret")
+//!         .with_delivery(delivery)
+//!         .with_expected_repo("octo", "widgets")
+//!         .run()
+//!         .await
+//!         .assert_conformant();
+//! }
+//! ```
+
+use base64::Engine;
+use rsr_engine::adapters::http::RetryPolicy;
+use rsr_engine::adapters::{AdapterConfig, Headers, PlatformAdapter};
+use rsr_engine::{RepoRef, RsrError};
+use std::net::SocketAddr;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// API token handed to adapters under test; shaped `user:password` so
+/// basic-auth adapters can use it too
+pub const FIXTURE_TOKEN: &str = "rsr-conformance:token";
+
+/// A webhook delivery: raw body plus headers (lowercase names)
+#[derive(Debug, Clone, Default)]
+pub struct Delivery {
+    pub payload: Vec<u8>,
+    pub headers: Headers,
+}
+
+impl Delivery {
+    pub fn new(payload: impl Into<Vec<u8>>) -> Self {
+        Self {
+            payload: payload.into(),
+            headers: Headers::new(),
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into().to_lowercase(), value.into());
+        self
+    }
+}
+
+/// Response the fixture server gives to every request
+#[derive(Debug, Clone)]
+pub struct FixtureResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl FixtureResponse {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: body.into(),
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// A request received by the fixture server
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub uri: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct FixtureState {
+    response: FixtureResponse,
+    requests: Vec<RecordedRequest>,
+}
+
+/// Local HTTP server answering every request with one scripted response
+#[derive(Debug, Clone)]
+pub struct FixtureServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<FixtureState>>,
+}
+
+impl FixtureServer {
+    /// Start on an ephemeral port; the server lives until the runtime shuts down
+    pub async fn start() -> Self {
+        let state = Arc::new(Mutex::new(FixtureState {
+            response: FixtureResponse::new(200, "{}"),
+            requests: Vec::new(),
+        }));
+
+        let handler_state = state.clone();
+        let router = axum::Router::new().fallback(move |request: axum::extract::Request| {
+            let state = handler_state.clone();
+            async move { respond(state, request).await }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("fixture server can bind a local port");
+        let addr = listener.local_addr().expect("bound listener has an address");
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, router).await;
+        });
+
+        Self { addr, state }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Answer all further requests with `response`
+    pub fn respond_with(&self, response: FixtureResponse) {
+        self.lock().response = response;
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    pub fn clear_requests(&self) {
+        self.lock().requests.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FixtureState> {
+        self.state.lock().expect("fixture state lock poisoned")
+    }
+}
+
+async fn respond(state: Arc<Mutex<FixtureState>>, request: axum::extract::Request) -> axum::response::Response {
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX).await.unwrap_or_default();
+
+    let response = {
+        let mut state = state.lock().expect("fixture state lock poisoned");
+        state.requests.push(RecordedRequest {
+            method: parts.method.to_string(),
+            uri: parts.uri.to_string(),
+            headers: parts
+                .headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+                .collect(),
+            body: body.to_vec(),
+        });
+        state.response.clone()
+    };
+
+    let mut builder = axum::response::Response::builder().status(response.status);
+    for (name, value) in &response.headers {
+        builder = builder.header(name, value);
+    }
+    builder
+        .body(axum::body::Body::from(response.body))
+        .expect("fixture response is valid")
+}
+
+/// Outcome of one conformance check
+#[derive(Debug, Clone)]
+pub struct ConformanceResult {
+    pub id: &'static str,
+    pub passed: bool,
+    pub message: String,
+}
+
+/// Outcome of a suite run
+#[derive(Debug, Clone)]
+pub struct ConformanceReport {
+    pub platform: String,
+    pub results: Vec<ConformanceResult>,
+}
+
+impl ConformanceReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &ConformanceResult> {
+        self.results.iter().filter(|r| !r.passed)
+    }
+
+    /// Panic listing every failed check
+    pub fn assert_conformant(&self) {
+        if !self.passed() {
+            panic!("{}", self);
+        }
+    }
+}
+
+impl std::fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let failed = self.failures().count();
+        writeln!(
+            f,
+            "{} adapter: {} of {} conformance checks passed",
+            self.platform,
+            self.results.len() - failed,
+            self.results.len()
+        )?;
+        for result in &self.results {
+            writeln!(
+                f,
+                "  {} {} - {}",
+                if result.passed { "PASS" } else { "FAIL" },
+                result.id,
+                result.message
+            )?;
+        }
+        Ok(())
+    }
+}
+
+type AdapterBuilder = Box<dyn Fn(AdapterConfig) -> Box<dyn PlatformAdapter>>;
+
+/// Behavioural checks for one adapter
+pub struct ConformanceSuite {
+    build: AdapterBuilder,
+    secret: Option<String>,
+    delivery: Option<Delivery>,
+    expected_repo: Option<(String, String)>,
+    webhooks: bool,
+    not_found: FixtureResponse,
+    repo: RepoRef,
+}
+
+impl ConformanceSuite {
+    pub fn new<F>(build: F) -> Self
+    where
+        F: Fn(AdapterConfig) -> Box<dyn PlatformAdapter> + 'static,
+    {
+        Self {
+            build: Box::new(build),
+            secret: None,
+            delivery: None,
+            expected_repo: None,
+            webhooks: true,
+            not_found: FixtureResponse::new(404, r#"{"message":"Not Found"}"#),
+            repo: RepoRef::new("conformance", "octo", "widgets"),
+        }
+    }
+
+    /// Webhook secret the fixture delivery is signed with
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// A genuine delivery, signed with the configured secret
+    pub fn with_delivery(mut self, delivery: Delivery) -> Self {
+        self.delivery = Some(delivery);
+        self
+    }
+
+    /// Repository the delivery's event must name
+    pub fn with_expected_repo(mut self, owner: impl Into<String>, repo: impl Into<String>) -> Self {
+        self.expected_repo = Some((owner.into(), repo.into()));
+        self
+    }
+
+    /// How the platform answers a request for something that doesn't exist,
+    /// for platforms that don't use a plain 404
+    pub fn with_not_found_response(mut self, response: FixtureResponse) -> Self {
+        self.not_found = response;
+        self
+    }
+
+    /// Repository used for API checks
+    pub fn with_repo(mut self, repo: RepoRef) -> Self {
+        self.repo = repo;
+        self
+    }
+
+    /// Skip webhook checks. Adapters without the webhook capability are
+    /// skipped automatically.
+    pub fn without_webhooks(mut self) -> Self {
+        self.webhooks = false;
+        self
+    }
+
+    pub async fn run(&self) -> ConformanceReport {
+        let server = FixtureServer::start().await;
+        let adapter = (self.build)(self.config(&server));
+
+        let mut results = Vec::new();
+        if self.webhooks && adapter.capabilities().webhooks {
+            self.check_webhooks(&mut results);
+        }
+        self.check_api(adapter.as_ref(), &server, &mut results).await;
+
+        ConformanceReport {
+            platform: adapter.platform_id().to_string(),
+            results,
+        }
+    }
+
+    fn config(&self, server: &FixtureServer) -> AdapterConfig {
+        // No retries, so failures surface immediately
+        let retry = RetryPolicy {
+            max_retries: 0,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            max_rate_limit_wait: Duration::ZERO,
+        };
+
+        let mut config = AdapterConfig::new()
+            .with_api_url(server.url())
+            .with_api_token(FIXTURE_TOKEN)
+            .with_retry_policy(retry)
+            .with_require_signature(true);
+        if let Some(ref secret) = self.secret {
+            config = config.with_webhook_secret(secret);
+        }
+        config
+    }
+
+    fn check_webhooks(&self, results: &mut Vec<ConformanceResult>) {
+        let (Some(secret), Some(delivery)) = (self.secret.as_deref(), self.delivery.as_ref()) else {
+            results.push(fail(
+                "webhook.fixture",
+                "no signed delivery configured; use with_secret and with_delivery, or without_webhooks",
+            ));
+            return;
+        };
+
+        let adapter = (self.build)(AdapterConfig::new().with_webhook_secret(secret));
+
+        results.push(match guard(|| adapter.verify_webhook(&delivery.payload, &delivery.headers)) {
+            Ok(Ok(true)) => pass("webhook.verifies_signed_delivery", "signed delivery accepted"),
+            Ok(other) => fail("webhook.verifies_signed_delivery", format!("expected Ok(true), got {:?}", other)),
+            Err(panic) => fail("webhook.verifies_signed_delivery", panic),
+        });
+
+        let mut tampered = delivery.payload.clone();
+        tampered.extend_from_slice(b" ");
+        results.push(match guard(|| adapter.verify_webhook(&tampered, &delivery.headers)) {
+            Ok(Ok(false)) | Ok(Err(RsrError::WebhookVerification)) => {
+                pass("webhook.rejects_tampered_delivery", "modified body rejected")
+            }
+            Ok(other) => fail("webhook.rejects_tampered_delivery", format!("expected rejection, got {:?}", other)),
+            Err(panic) => fail("webhook.rejects_tampered_delivery", panic),
+        });
+
+        results.push(match guard(|| adapter.verify_webhook(&delivery.payload, &Headers::new())) {
+            Ok(Ok(false)) | Ok(Err(RsrError::WebhookVerification)) => {
+                pass("webhook.rejects_unsigned_delivery", "delivery without headers rejected")
+            }
+            Ok(other) => fail("webhook.rejects_unsigned_delivery", format!("expected rejection, got {:?}", other)),
+            Err(panic) => fail("webhook.rejects_unsigned_delivery", panic),
+        });
+
+        let unconfigured = (self.build)(AdapterConfig::new().with_require_signature(true));
+        results.push(match guard(|| unconfigured.verify_webhook(&delivery.payload, &delivery.headers)) {
+            Ok(Err(RsrError::InsecureConfiguration(_))) => {
+                pass("webhook.fails_closed_without_secret", "refused with InsecureConfiguration")
+            }
+            Ok(other) => fail(
+                "webhook.fails_closed_without_secret",
+                format!("expected InsecureConfiguration, got {:?}", other),
+            ),
+            Err(panic) => fail("webhook.fails_closed_without_secret", panic),
+        });
+
+        results.push(match guard(|| adapter.parse_webhook(&delivery.payload, &delivery.headers)) {
+            Ok(Ok(event)) => match self.expected_repo {
+                Some((ref owner, ref repo)) if event.repo_owner() != owner || event.repo_name() != repo => fail(
+                    "webhook.parses_delivery",
+                    format!(
+                        "expected {}/{}, got {}/{}",
+                        owner,
+                        repo,
+                        event.repo_owner(),
+                        event.repo_name()
+                    ),
+                ),
+                _ => pass(
+                    "webhook.parses_delivery",
+                    format!("parsed event for {}/{}", event.repo_owner(), event.repo_name()),
+                ),
+            },
+            Ok(Err(e)) => fail("webhook.parses_delivery", format!("parse failed: {}", e)),
+            Err(panic) => fail("webhook.parses_delivery", panic),
+        });
+
+        results.push(match guard(|| adapter.parse_webhook(b"{not json", &delivery.headers)) {
+            Ok(Err(_)) => pass("webhook.rejects_malformed_payload", "malformed payload returned an error"),
+            Ok(Ok(event)) => fail(
+                "webhook.rejects_malformed_payload",
+                format!("malformed payload parsed as {:?}", event),
+            ),
+            Err(panic) => fail("webhook.rejects_malformed_payload", panic),
+        });
+    }
+
+    async fn check_api(&self, adapter: &dyn PlatformAdapter, server: &FixtureServer, results: &mut Vec<ConformanceResult>) {
+        server.respond_with(self.not_found.clone());
+        server.clear_requests();
+        results.push(match adapter.fetch_file(&self.repo, "README.md").await {
+            Err(RsrError::RepoNotFound { .. }) => pass("api.maps_not_found", "missing file is RepoNotFound"),
+            other => fail("api.maps_not_found", format!("expected RepoNotFound, got {:?}", other.map(|b| b.len()))),
+        });
+
+        let requests = server.requests();
+        results.push(if requests.is_empty() {
+            fail("api.authenticates_requests", "no request reached the fixture server")
+        } else if requests.iter().all(carries_token) {
+            pass("api.authenticates_requests", "the configured token was sent")
+        } else {
+            fail("api.authenticates_requests", "a request was sent without the configured token")
+        });
+
+        server.respond_with(FixtureResponse::new(500, r#"{"message":"Internal Server Error"}"#));
+        results.push(match adapter.fetch_file(&self.repo, "README.md").await {
+            Err(RsrError::RepoNotFound { .. }) => {
+                fail("api.surfaces_server_errors", "server error reported as RepoNotFound")
+            }
+            Err(_) => pass("api.surfaces_server_errors", "server error returned an error"),
+            Ok(body) => fail("api.surfaces_server_errors", format!("server error returned {} bytes as content", body.len())),
+        });
+
+        // Last: a long rate limit may be remembered for the rest of the process
+        server.respond_with(
+            FixtureResponse::new(429, r#"{"message":"Too Many Requests"}"#).header("retry-after", "3600"),
+        );
+        results.push(match adapter.fetch_file(&self.repo, "README.md").await {
+            Err(RsrError::RateLimited) => pass("api.maps_rate_limit", "429 is RateLimited"),
+            other => fail("api.maps_rate_limit", format!("expected RateLimited, got {:?}", other.map(|b| b.len()))),
+        });
+    }
+}
+
+/// Whether a request carries the fixture token: in a header, as basic auth,
+/// or in a (possibly form-encoded) body
+fn carries_token(request: &RecordedRequest) -> bool {
+    let basic = base64::engine::general_purpose::STANDARD.encode(FIXTURE_TOKEN);
+    let form_encoded = FIXTURE_TOKEN.replace(':', "%3A");
+    let body = String::from_utf8_lossy(&request.body);
+
+    request
+        .headers
+        .iter()
+        .any(|(_, value)| value.contains(FIXTURE_TOKEN) || value.contains(&basic))
+        || body.contains(FIXTURE_TOKEN)
+        || body.contains(&form_encoded)
+}
+
+/// Run a check, turning a panic into a failure message
+fn guard<T>(check: impl FnOnce() -> T) -> std::result::Result<T, String> {
+    std::panic::catch_unwind(AssertUnwindSafe(check)).map_err(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        format!("panicked: {}", message)
+    })
+}
+
+fn pass(id: &'static str, message: impl Into<String>) -> ConformanceResult {
+    ConformanceResult {
+        id,
+        passed: true,
+        message: message.into(),
+    }
+}
+
+fn fail(id: &'static str, message: impl Into<String>) -> ConformanceResult {
+    ConformanceResult {
+        id,
+        passed: false,
+        message: message.into(),
+    }
+}
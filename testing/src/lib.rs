@@ -0,0 +1,203 @@
+//! Testing helpers for RSR checks, compliance reports and platform adapters
+//!
+//! Adapter authors can prove their implementation against the engine's
+//! expectations with the [`conformance`] suite. The rest of this crate is
+//! snapshot testing for check results.
+//!
+//! Reports are normalized before comparison so snapshots only change when
+//! the findings do:
+//! - checks are sorted by ID
+//! - timestamps are replaced with `[timestamp]`
+//! - scores are rounded to three decimal places
+//!
+//! Snapshots live in `tests/snapshots/<name>.json` under the calling crate.
+//! A missing snapshot is written on first run (and fails under `CI`);
+//! set `RSR_UPDATE_SNAPSHOTS=1` to rewrite snapshots that no longer match.
+//!
+//! ```ignore
+//! let status = ComplianceEngine::new().check_local(fixture).await?;
+//! rsr_test::assert_status_snapshot!("minimal_repo", &status);
+//! ```
+
+pub mod conformance;
+
+use rsr_engine::{CheckResult, ComplianceStatus};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Placeholder written in place of timestamps
+pub const REDACTED_TIMESTAMP: &str = "[timestamp]";
+
+/// Environment variable that rewrites mismatching snapshots
+pub const UPDATE_ENV: &str = "RSR_UPDATE_SNAPSHOTS";
+
+/// Stable JSON form of a value, suitable for snapshotting
+pub trait Snapshot {
+    fn to_snapshot(&self) -> Value;
+}
+
+impl Snapshot for CheckResult {
+    fn to_snapshot(&self) -> Value {
+        json!({
+            "id": self.id,
+            "name": self.name,
+            "tier": self.tier,
+            "passed": self.passed,
+            "message": self.message,
+            "details": self.details,
+        })
+    }
+}
+
+impl Snapshot for [CheckResult] {
+    fn to_snapshot(&self) -> Value {
+        let mut checks: Vec<&CheckResult> = self.iter().collect();
+        checks.sort_by(|a, b| a.id.cmp(&b.id));
+        Value::Array(checks.into_iter().map(Snapshot::to_snapshot).collect())
+    }
+}
+
+impl Snapshot for Vec<CheckResult> {
+    fn to_snapshot(&self) -> Value {
+        self.as_slice().to_snapshot()
+    }
+}
+
+impl Snapshot for ComplianceStatus {
+    fn to_snapshot(&self) -> Value {
+        json!({
+            "repo": self.repo,
+            "tier": self.tier,
+            "score": round_score(self.score),
+            "timestamp": REDACTED_TIMESTAMP,
+            "checks": self.checks.to_snapshot(),
+        })
+    }
+}
+
+impl<T: Snapshot + ?Sized> Snapshot for &T {
+    fn to_snapshot(&self) -> Value {
+        (**self).to_snapshot()
+    }
+}
+
+/// Pretty-printed snapshot text, with a trailing newline
+pub fn render<T: Snapshot + ?Sized>(value: &T) -> String {
+    let mut text = serde_json::to_string_pretty(&value.to_snapshot()).expect("snapshot values always serialize");
+    text.push('\n');
+    text
+}
+
+/// Snapshot file for `name` under a crate's manifest directory
+pub fn snapshot_path(manifest_dir: &str, name: &str) -> PathBuf {
+    Path::new(manifest_dir)
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{}.json", name))
+}
+
+/// Compare `value` against the stored snapshot, panicking with a diff on mismatch.
+///
+/// Prefer the `assert_status_snapshot!` / `assert_checks_snapshot!` macros,
+/// which fill in the calling crate's manifest directory.
+pub fn assert_snapshot<T: Snapshot + ?Sized>(manifest_dir: &str, name: &str, value: &T) {
+    let path = snapshot_path(manifest_dir, name);
+    let actual = render(value);
+    let update = std::env::var(UPDATE_ENV).is_ok_and(|v| v == "1");
+
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => expected,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            if std::env::var_os("CI").is_some() && !update {
+                panic!("snapshot {} is missing ({}); run tests locally to create it", name, path.display());
+            }
+            write_snapshot(&path, &actual);
+            return;
+        }
+        Err(e) => panic!("failed to read snapshot {}: {}", path.display(), e),
+    };
+
+    if expected == actual {
+        return;
+    }
+
+    if update {
+        write_snapshot(&path, &actual);
+        return;
+    }
+
+    panic!(
+        "snapshot {} does not match ({})\n{}\nSet {}=1 to accept the new output.",
+        name,
+        path.display(),
+        diff(&expected, &actual),
+        UPDATE_ENV
+    );
+}
+
+/// Assert a `ComplianceStatus` matches `tests/snapshots/<name>.json`
+#[macro_export]
+macro_rules! assert_status_snapshot {
+    ($name:expr, $status:expr) => {
+        $crate::assert_snapshot::<$crate::rsr_engine::ComplianceStatus>(env!("CARGO_MANIFEST_DIR"), $name, $status)
+    };
+}
+
+/// Assert a `CheckResult` or a list of them matches `tests/snapshots/<name>.json`
+#[macro_export]
+macro_rules! assert_checks_snapshot {
+    ($name:expr, $checks:expr) => {
+        $crate::assert_snapshot(env!("CARGO_MANIFEST_DIR"), $name, $checks)
+    };
+}
+
+#[doc(hidden)]
+pub use rsr_engine;
+
+fn write_snapshot(path: &Path, contents: &str) {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .unwrap_or_else(|e| panic!("failed to create {}: {}", parent.display(), e));
+    }
+    std::fs::write(path, contents).unwrap_or_else(|e| panic!("failed to write snapshot {}: {}", path.display(), e));
+}
+
+/// Scores are f32; rounding keeps float noise out of snapshots
+fn round_score(score: f32) -> f64 {
+    (score as f64 * 1000.0).round() / 1000.0
+}
+
+/// Line diff of two snapshots, marking removed (`-`) and added (`+`) lines
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence table; snapshots are small
+    let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            out.push_str(&format!("  {}\n", expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push_str(&format!("- {}\n", expected[i]));
+            i += 1;
+        } else {
+            out.push_str(&format!("+ {}\n", actual[j]));
+            j += 1;
+        }
+    }
+    out
+}